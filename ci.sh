@@ -103,7 +103,8 @@ job_examples() {
     step "Run examples (parallel)"
     run_parallel \
         "scenario_run (500 riders, 100 drivers)" "cargo run -p sim_core --example scenario_run --release" \
-        "scenario_run_large (10K riders, 7K drivers)" "cargo run -p sim_core --example scenario_run_large --release"
+        "scenario_run_large (10K riders, 7K drivers)" "cargo run -p sim_core --example scenario_run_large --release" \
+        "smoke_matrix (algorithm x routing x batch)" "cargo run -p sim_experiments --example smoke_matrix --release"
 }
 
 job_bench() {
@@ -0,0 +1,240 @@
+//! Simulation server mode: exposes a running scenario as a ride-hailing backend sandbox.
+//!
+//! External clients create riders and drive them through the quote flow over HTTP, and follow
+//! a ride over WebSocket, while the rest of the marketplace is simulated (see
+//! `sim_core::sandbox`). Simulation time follows the wall clock scaled by `SIM_API_SPEED`;
+//! with a speed of 0 it only moves on `POST /clock/advance`.
+//!
+//! Routes: `POST /quotes`, `GET /riders/{id}`, `POST /riders/{id}/accept`,
+//! `POST /riders/{id}/decline`, `GET /riders/{id}/ws`, `GET /clock`, `POST /clock/advance`.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use sim_core::clock::ONE_HOUR_MS;
+use sim_core::sandbox::{Location, RiderStatus, RiderView, Sandbox, SandboxError};
+use sim_core::scenario::ScenarioParams;
+
+/// How often the pacing task moves simulation time forward.
+const TICK: Duration = Duration::from_millis(100);
+/// How often a WebSocket subscription checks its rider for changes.
+const WS_POLL: Duration = Duration::from_millis(250);
+
+struct ServerState {
+    sandbox: Mutex<Sandbox>,
+    /// Simulation ms per wall-clock ms; 0 disables pacing.
+    speed: f64,
+}
+
+impl ServerState {
+    fn sandbox(&self) -> MutexGuard<'_, Sandbox> {
+        self.sandbox.lock().expect("poisoned mutex")
+    }
+}
+
+#[derive(Deserialize)]
+struct QuoteRequest {
+    pickup: Location,
+    dropoff: Location,
+}
+
+#[derive(Deserialize)]
+struct AdvanceRequest {
+    ms: u64,
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> Result<T, String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| format!("{name} has an invalid value '{value}'")),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Scenario from `SIM_API_PRESET` (default `small-test`), `SIM_API_SEED` and the optional
+/// `SIM_API_DURATION_HOURS`, which replaces the preset's end time.
+fn scenario_from_env() -> Result<ScenarioParams, String> {
+    let preset = env_or("SIM_API_PRESET", "small-test".to_string())?;
+    let mut params = ScenarioParams::preset(&preset).ok_or_else(|| {
+        format!(
+            "unknown SIM_API_PRESET '{preset}' (expected one of {})",
+            ScenarioParams::preset_names().join(", ")
+        )
+    })?;
+    params = params.with_seed(env_or("SIM_API_SEED", 42)?);
+    if let Ok(hours) = std::env::var("SIM_API_DURATION_HOURS") {
+        let hours: u64 = hours
+            .parse()
+            .map_err(|_| format!("SIM_API_DURATION_HOURS has an invalid value '{hours}'"))?;
+        params = params.with_simulation_end_time_ms(hours * ONE_HOUR_MS);
+    }
+    Ok(params)
+}
+
+fn error_response(error: SandboxError) -> Response {
+    let (status, message) = match error {
+        SandboxError::InvalidLocation(location) => (
+            StatusCode::BAD_REQUEST,
+            format!("invalid location ({}, {})", location.lat, location.lng),
+        ),
+        SandboxError::UnknownRider(id) => (StatusCode::NOT_FOUND, format!("unknown rider {id}")),
+        SandboxError::NoQuote(id) => (
+            StatusCode::CONFLICT,
+            format!("rider {id} has no open quote"),
+        ),
+        SandboxError::QuoteExpired(id) => (
+            StatusCode::CONFLICT,
+            format!("quote for rider {id} expired; decline it to get a new one"),
+        ),
+    };
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn view_response(result: Result<RiderView, SandboxError>, status: StatusCode) -> Response {
+    match result {
+        Ok(view) => (status, Json(view)).into_response(),
+        Err(error) => error_response(error),
+    }
+}
+
+async fn request_quote(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<QuoteRequest>,
+) -> Response {
+    let result = tokio::task::block_in_place(|| {
+        state
+            .sandbox()
+            .request_quote(request.pickup, request.dropoff)
+    });
+    view_response(result, StatusCode::CREATED)
+}
+
+async fn rider(State(state): State<Arc<ServerState>>, Path(id): Path<u64>) -> Response {
+    view_response(state.sandbox().rider(id), StatusCode::OK)
+}
+
+async fn accept(State(state): State<Arc<ServerState>>, Path(id): Path<u64>) -> Response {
+    let result = tokio::task::block_in_place(|| state.sandbox().accept_quote(id));
+    view_response(result, StatusCode::OK)
+}
+
+async fn decline(State(state): State<Arc<ServerState>>, Path(id): Path<u64>) -> Response {
+    let result = tokio::task::block_in_place(|| state.sandbox().decline_quote(id));
+    view_response(result, StatusCode::OK)
+}
+
+async fn clock(State(state): State<Arc<ServerState>>) -> Response {
+    Json(json!({ "now_ms": state.sandbox().now_ms() })).into_response()
+}
+
+async fn advance_clock(
+    State(state): State<Arc<ServerState>>,
+    Json(request): Json<AdvanceRequest>,
+) -> Response {
+    let now_ms = tokio::task::block_in_place(|| {
+        let mut sandbox = state.sandbox();
+        let target = sandbox.now_ms().saturating_add(request.ms);
+        sandbox.advance_to(target);
+        sandbox.now_ms()
+    });
+    Json(json!({ "now_ms": now_ms })).into_response()
+}
+
+async fn rider_updates(
+    State(state): State<Arc<ServerState>>,
+    Path(id): Path<u64>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| stream_rider(state, id, socket))
+}
+
+/// Push the rider's view whenever its status, quote or trip changes; close once the rider
+/// has left the marketplace.
+async fn stream_rider(state: Arc<ServerState>, id: u64, mut socket: WebSocket) {
+    let mut last_sent: Option<RiderView> = None;
+    loop {
+        let view = state.sandbox().rider(id);
+        let (text, done) = match view {
+            Ok(view) => {
+                let changed = last_sent.is_none_or(|last| {
+                    (last.status, last.quote, last.trip) != (view.status, view.quote, view.trip)
+                });
+                if !changed {
+                    tokio::time::sleep(WS_POLL).await;
+                    continue;
+                }
+                last_sent = Some(view);
+                let done = matches!(
+                    view.status,
+                    RiderStatus::Completed | RiderStatus::Abandoned | RiderStatus::Cancelled
+                );
+                (json!(view).to_string(), done)
+            }
+            Err(_) => (
+                json!({ "error": format!("unknown rider {id}") }).to_string(),
+                true,
+            ),
+        };
+        if socket.send(Message::Text(text.into())).await.is_err() || done {
+            break;
+        }
+        tokio::time::sleep(WS_POLL).await;
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// Move simulation time along with the wall clock.
+async fn pace(state: Arc<ServerState>) {
+    let started_at = Instant::now();
+    let mut interval = tokio::time::interval(TICK);
+    loop {
+        interval.tick().await;
+        let target = (started_at.elapsed().as_secs_f64() * 1000.0 * state.speed) as u64;
+        tokio::task::block_in_place(|| {
+            state.sandbox().advance_to(target);
+        });
+    }
+}
+
+/// Port from `PORT`, default 8080.
+fn listen_port() -> u16 {
+    std::env::var("PORT")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(8080)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let speed: f64 = env_or("SIM_API_SPEED", 1.0)?;
+    let state = Arc::new(ServerState {
+        sandbox: Mutex::new(Sandbox::new(scenario_from_env()?)),
+        speed: speed.max(0.0),
+    });
+    if state.speed > 0.0 {
+        tokio::spawn(pace(state.clone()));
+    }
+
+    let app = Router::new()
+        .route("/quotes", post(request_quote))
+        .route("/riders/{id}", get(rider))
+        .route("/riders/{id}/accept", post(accept))
+        .route("/riders/{id}/decline", post(decline))
+        .route("/riders/{id}/ws", get(rider_updates))
+        .route("/clock", get(clock))
+        .route("/clock/advance", post(advance_clock))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", listen_port())).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -1,9 +1,13 @@
 //! Performance benchmarks for sim_core using Criterion.rs.
 
 use bevy_ecs::prelude::World;
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
 use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
 use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::SimSnapshotConfig;
+use std::time::Duration;
 
 fn bench_simulation_run(c: &mut Criterion) {
     let scenarios = vec![
@@ -41,6 +45,66 @@ fn bench_simulation_run(c: &mut Criterion) {
     group.finish();
 }
 
+/// Built and initialized world for the 100k rider / 30k driver scale target.
+///
+/// Runs the first `minutes` of simulated time; snapshots keep counts only.
+fn scale_target_world(minutes: u64) -> World {
+    let mut world = World::new();
+    let params = ScenarioParams {
+        num_drivers: 30_000,
+        num_riders: 100_000,
+        ..Default::default()
+    }
+    .with_seed(42)
+    .with_request_window_hours(4)
+    .with_driver_spread_hours(4)
+    .with_match_radius(5)
+    .with_snapshot_config(SimSnapshotConfig {
+        interval_ms: 60_000,
+        include_riders: false,
+        include_drivers: false,
+        include_trips: false,
+        ..Default::default()
+    })
+    .with_simulation_end_time_ms(minutes * 60 * 1000);
+
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    world
+}
+
+/// Events/sec of the scale target; `xtask bench-compare` tracks this group as a regression metric.
+fn bench_simulation_throughput(c: &mut Criterion) {
+    const MINUTES: u64 = 10;
+    const MAX_STEPS: usize = 100_000_000;
+
+    // Seeded runs are deterministic, so one untimed run gives the event count of every iteration
+    let events = run_until_empty(
+        &mut scale_target_world(MINUTES),
+        &mut simulation_schedule(),
+        MAX_STEPS,
+    );
+
+    let mut group = c.benchmark_group("simulation_throughput");
+    group
+        .sample_size(10)
+        .measurement_time(Duration::from_secs(120))
+        .throughput(Throughput::Elements(events as u64));
+    group.bench_function("100k_riders_30k_drivers", |b| {
+        b.iter_batched(
+            || scale_target_world(MINUTES),
+            |mut world| {
+                let mut schedule = simulation_schedule();
+                black_box(run_until_empty(&mut world, &mut schedule, MAX_STEPS));
+                // Dropped by Criterion outside the timed section
+                world
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    group.finish();
+}
+
 fn bench_matching_algorithms(c: &mut Criterion) {
     use bevy_ecs::prelude::Entity;
     use sim_core::matching::algorithm::MatchingAlgorithm;
@@ -90,8 +154,159 @@ fn bench_matching_algorithms(c: &mut Criterion) {
         });
     });
 
+    // Thousands of concurrent riders: one large component (greedy above the exact-size limit)
+    // and the same batch with every component solved exactly
+    let spread = rider_pos.grid_disk::<Vec<_>>(30);
+    let batch_riders: Vec<_> = spread
+        .iter()
+        .step_by(2)
+        .take(1000)
+        .enumerate()
+        .map(|(i, cell)| (Entity::from_raw(i as u32), *cell, None))
+        .collect();
+    let batch_drivers: Vec<_> = spread
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .take(1000)
+        .enumerate()
+        .map(|(i, cell)| (Entity::from_raw(i as u32 + 1000), *cell))
+        .collect();
+    group.sample_size(10);
+    group.bench_function("hungarian_1000x1000_radius_2", |b| {
+        b.iter(|| {
+            black_box(hungarian.find_batch_matches(&batch_riders, &batch_drivers, 2, 0));
+        });
+    });
+    // Independent spatial clusters, solved as separate components in parallel
+    let mut cluster_riders = Vec::new();
+    let mut cluster_drivers = Vec::new();
+    for center in rider_pos.grid_ring_fast(25).flatten().step_by(6).take(20) {
+        let cells = center.grid_disk::<Vec<_>>(4);
+        for cell in cells.iter().step_by(2).take(30) {
+            cluster_riders.push((Entity::from_raw(cluster_riders.len() as u32), *cell, None));
+        }
+        for cell in cells.iter().skip(1).step_by(2).take(30) {
+            cluster_drivers.push((Entity::from_raw(cluster_drivers.len() as u32 + 1000), *cell));
+        }
+    }
+    group.bench_function("hungarian_20_clusters_30x30", |b| {
+        b.iter(|| {
+            black_box(hungarian.find_batch_matches(&cluster_riders, &cluster_drivers, 2, 0));
+        });
+    });
+    let exact = HungarianMatching::default().with_max_exact_size(usize::MAX);
+    group.bench_function("hungarian_1000x1000_radius_2_exact", |b| {
+        b.iter(|| {
+            black_box(exact.find_batch_matches(&batch_riders, &batch_drivers, 2, 0));
+        });
+    });
+
+    group.finish();
+}
+
+fn bench_distance_kernels(c: &mut Criterion) {
+    use sim_core::spatial::{distance_km_between_cells, distances_km_from_cell};
+    use sim_core::test_helpers::test_cell;
+
+    let origin = test_cell();
+    let targets: Vec<_> = origin
+        .grid_disk::<Vec<_>>(10)
+        .into_iter()
+        .take(200)
+        .collect();
+
+    let mut group = c.benchmark_group("distance_kernels");
+    group.throughput(Throughput::Elements(targets.len() as u64));
+
+    // Per-pair path through the shared LRU cache (warm after the first iteration)
+    group.bench_function("scalar_200_targets", |b| {
+        b.iter(|| {
+            let distances: Vec<f64> = targets
+                .iter()
+                .map(|target| distance_km_between_cells(origin, *target))
+                .collect();
+            black_box(distances)
+        });
+    });
+
+    group.bench_function("batched_200_targets", |b| {
+        b.iter(|| black_box(distances_km_from_cell(origin, &targets)));
+    });
+
+    group.finish();
+}
+
+fn bench_spatial_index_updates(c: &mut Criterion) {
+    use bevy_ecs::prelude::{Entity, Schedule};
+    use sim_core::ecs::{Driver, Position};
+    use sim_core::spatial::SpatialIndex;
+    use sim_core::systems::spatial_index::update_spatial_index_system;
+    use sim_core::test_helpers::test_cell;
+
+    const DRIVERS: usize = 20_000;
+    let cells = test_cell().grid_disk::<Vec<_>>(30);
+    let mut world = World::new();
+    world.insert_resource(SpatialIndex::new());
+    let drivers: Vec<Entity> = (0..DRIVERS)
+        .map(|i| {
+            let driver = Driver {
+                matched_rider: None,
+                assigned_trip: None,
+            };
+            world.spawn((driver, Position(cells[i % cells.len()]))).id()
+        })
+        .collect();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(update_spatial_index_system);
+    schedule.run(&mut world);
+
+    let mut group = c.benchmark_group("spatial_index");
+    // One index update after `moved` drivers changed cell, as movement events do
+    for moved in [1usize, 100, 1000] {
+        let mut step = 0usize;
+        group.bench_with_input(
+            BenchmarkId::new("incremental_update_20k_drivers", moved),
+            &moved,
+            |b, &moved| {
+                b.iter(|| {
+                    step += 1;
+                    for (i, &driver) in drivers.iter().take(moved).enumerate() {
+                        let cell = cells[(i + step) % cells.len()];
+                        world.get_mut::<Position>(driver).expect("driver").0 = cell;
+                        world.resource::<SpatialIndex>().mark_moved(driver);
+                    }
+                    schedule.run(&mut world);
+                });
+            },
+        );
+    }
+
+    // Rebuilding from every position, the cost an update pays without move tracking
+    group.bench_function("full_rebuild_20k_drivers", |b| {
+        b.iter(|| {
+            let positions: Vec<(Entity, Position)> = world
+                .query::<(Entity, &Position)>()
+                .iter(&world)
+                .map(|(entity, position)| (entity, *position))
+                .collect();
+            let mut index = world.resource_mut::<SpatialIndex>();
+            index.clear();
+            for (entity, position) in positions {
+                index.insert_driver(entity, position.0);
+            }
+        });
+    });
+
     group.finish();
 }
 
-criterion_group!(benches, bench_simulation_run, bench_matching_algorithms);
+criterion_group!(
+    benches,
+    bench_simulation_run,
+    bench_simulation_throughput,
+    bench_matching_algorithms,
+    bench_distance_kernels,
+    bench_spatial_index_updates
+);
 criterion_main!(benches);
@@ -3,11 +3,18 @@
 //! Run with: cargo run -p sim_core --example scenario_run
 
 use bevy_ecs::prelude::World;
+use sim_core::provenance::ExportProvenance;
+use sim_core::run_bundle::RunBundle;
 use sim_core::runner::{run_until_empty, simulation_schedule};
-use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::scenario::{build_scenario, MatchingAlgorithmType, ScenarioParams};
 use sim_core::telemetry_export::{
-    write_agent_positions_parquet, write_completed_trips_parquet, write_snapshot_counts_parquet,
-    write_trips_parquet,
+    set_max_batch_bytes, write_agent_positions_ipc, write_agent_positions_parquet,
+    write_cell_hours_ipc, write_cell_hours_parquet, write_completed_trips_ipc,
+    write_completed_trips_ndjson, write_completed_trips_parquet, write_driver_utilization_ipc,
+    write_driver_utilization_parquet, write_funnel_ipc, write_funnel_parquet,
+    write_match_diagnostics_parquet, write_quote_features_parquet, write_shadow_matches_parquet,
+    write_snapshot_counts_ipc, write_snapshot_counts_parquet, write_snapshots_ndjson,
+    write_trips_ipc, write_trips_ndjson, write_trips_parquet, JsonCompression,
 };
 use std::env;
 use std::fs;
@@ -23,26 +30,93 @@ fn main() {
     const BUFFER_HOURS: u64 = 2;
     const END_TIME_MS: u64 = (SIMULATION_HOURS + BUFFER_HOURS) * 3_600_000;
 
+    let mut params = ScenarioParams {
+        num_riders: NUM_RIDERS,
+        num_drivers: NUM_DRIVERS,
+        ..Default::default()
+    }
+    .with_seed(123)
+    .with_request_window_hours(SIMULATION_HOURS)
+    .with_match_radius(5)
+    .with_trip_duration_cells(5, 60)
+    .with_simulation_end_time_ms(END_TIME_MS);
+    // Optional: SIM_MATCH_DIAGNOSTICS=1 records scored candidates for every matching decision
+    if env::var("SIM_MATCH_DIAGNOSTICS").is_ok() {
+        params = params.with_match_diagnostics();
+    }
+    // Optional: SIM_QUOTE_FEATURES=1 records market features of every quote
+    if env::var("SIM_QUOTE_FEATURES").is_ok() {
+        params = params.with_quote_features();
+    }
+    // Optional: SIM_SHADOW_MATCHING=simple|cost_based|hungarian logs what that algorithm would
+    // have chosen at every dispatch decision
+    if let Ok(kind) = env::var("SIM_SHADOW_MATCHING") {
+        match kind.as_str() {
+            "simple" => params = params.with_shadow_matching(MatchingAlgorithmType::Simple),
+            "cost_based" => params = params.with_shadow_matching(MatchingAlgorithmType::CostBased),
+            "hungarian" => params = params.with_shadow_matching(MatchingAlgorithmType::Hungarian),
+            other => eprintln!("Unknown SIM_SHADOW_MATCHING algorithm: {}", other),
+        }
+    }
+
     let mut world = World::new();
-    build_scenario(
-        &mut world,
-        ScenarioParams {
-            num_riders: NUM_RIDERS,
-            num_drivers: NUM_DRIVERS,
-            ..Default::default()
-        }
-        .with_seed(123)
-        .with_request_window_hours(SIMULATION_HOURS)
-        .with_match_radius(5)
-        .with_trip_duration_cells(5, 60)
-        .with_simulation_end_time_ms(END_TIME_MS),
-    );
+    build_scenario(&mut world, params);
     sim_core::runner::initialize_simulation(&mut world);
 
+    // Optional live streaming: SIM_STREAM_KAFKA=host:9092[,host2:9092], SIM_STREAM_NATS=nats://host:4222
+    // or SIM_STREAM_WEBSOCKET=0.0.0.0:9001 (waits up to 30s for a dashboard to connect)
+    #[cfg(feature = "kafka")]
+    if let Ok(brokers) = env::var("SIM_STREAM_KAFKA") {
+        let hosts = brokers.split(',').map(str::to_string).collect();
+        match sim_core::telemetry_stream::KafkaPublisher::connect(hosts) {
+            Ok(publisher) => world.insert_resource(
+                sim_core::telemetry_stream::TelemetryStream::new(Box::new(publisher)),
+            ),
+            Err(err) => eprintln!("Failed to connect to Kafka: {}", err),
+        }
+    }
+    #[cfg(feature = "nats")]
+    if let Ok(url) = env::var("SIM_STREAM_NATS") {
+        match sim_core::telemetry_stream::NatsPublisher::connect(&url) {
+            Ok(publisher) => world.insert_resource(
+                sim_core::telemetry_stream::TelemetryStream::new(Box::new(publisher)),
+            ),
+            Err(err) => eprintln!("Failed to connect to NATS: {}", err),
+        }
+    }
+    #[cfg(feature = "websocket")]
+    if let Ok(addr) = env::var("SIM_STREAM_WEBSOCKET") {
+        match sim_core::telemetry_stream::WebSocketPublisher::bind(addr.as_str()) {
+            Ok(publisher) => {
+                println!(
+                    "Streaming telemetry on ws://{}/ (waiting for a client)",
+                    publisher.local_addr()
+                );
+                publisher.wait_for_client(std::time::Duration::from_secs(30));
+                world.insert_resource(
+                    sim_core::telemetry_stream::TelemetryStream::new(Box::new(publisher))
+                        .with_agent_positions(),
+                );
+            }
+            Err(err) => eprintln!("Failed to bind WebSocket server: {}", err),
+        }
+    }
+
     let mut schedule = simulation_schedule();
     // 4h of sim time + 500 riders × many events each; allow enough steps to drain the queue
     let max_steps = 2_000_000;
     let steps = run_until_empty(&mut world, &mut schedule, max_steps);
+    if let Some(mut stream) =
+        world.get_resource_mut::<sim_core::telemetry_stream::TelemetryStream>()
+    {
+        if let Err(err) = stream.flush() {
+            eprintln!("Failed to flush telemetry stream: {}", err);
+        }
+        println!(
+            "Streamed {} messages ({} failed)",
+            stream.published, stream.failed
+        );
+    }
 
     let telemetry = world.resource::<sim_core::telemetry::SimTelemetry>();
     let completed = telemetry.completed_trips.len();
@@ -60,6 +134,28 @@ fn main() {
         sim_time_secs as f64 / 60.0
     );
     println!("Completed trips: {}", completed);
+    let funnel = telemetry.funnel_summary();
+    println!(
+        "Funnel: {} requested -> {} quoted -> {} accepted -> {} matched -> {} picked up -> {} completed",
+        funnel.requested,
+        funnel.quoted,
+        funnel.quote_accepted,
+        funnel.matched,
+        funnel.picked_up,
+        funnel.completed,
+    );
+    if let Some(log) = world.get_resource::<sim_core::telemetry::ShadowMatchLog>() {
+        let shadow = log.summary();
+        println!(
+            "Shadow matching: {} decisions, {:.1}% agreement; mean pickup {:.2} km live vs {:.2} km shadow ({} vs {} matches)",
+            shadow.decisions,
+            shadow.agreement_rate() * 100.0,
+            shadow.live_mean_pickup_km(),
+            shadow.shadow_mean_pickup_km(),
+            shadow.live_matches,
+            shadow.shadow_matches,
+        );
+    }
 
     if completed > 0 {
         println!("\nSample completed trips (first 100):");
@@ -90,25 +186,195 @@ fn main() {
             eprintln!("Failed to create export dir {:?}: {}", export_path, err);
             return;
         }
+        if let Some(mb) = env::var("SIM_EXPORT_MAX_BATCH_MB")
+            .ok()
+            .and_then(|mb| mb.parse::<usize>().ok())
+        {
+            set_max_batch_bytes(mb * 1024 * 1024);
+        }
         let trips_path = export_path.join("completed_trips.parquet");
         let counts_path = export_path.join("snapshot_counts.parquet");
         let positions_path = export_path.join("agent_positions.parquet");
         let all_trips_path = export_path.join("trips.parquet");
+        let utilization_path = export_path.join("driver_utilization.parquet");
+        let funnel_path = export_path.join("funnel.parquet");
+        let cell_hours_path = export_path.join("cell_hours.parquet");
 
         let snapshots = world.resource::<sim_core::telemetry::SimSnapshots>();
-        if let Err(err) = write_completed_trips_parquet(&trips_path, telemetry) {
+        let end_ms = world.resource::<sim_core::clock::SimulationClock>().now();
+        let provenance = ExportProvenance::for_world(&world);
+        if let Err(err) = write_completed_trips_parquet(&trips_path, telemetry, &provenance) {
             eprintln!("Failed to export completed trips: {}", err);
         }
-        if let Err(err) = write_snapshot_counts_parquet(&counts_path, snapshots) {
+        if let Err(err) = write_snapshot_counts_parquet(&counts_path, snapshots, &provenance) {
             eprintln!("Failed to export snapshot counts: {}", err);
         }
-        if let Err(err) = write_agent_positions_parquet(&positions_path, snapshots) {
+        if let Err(err) = write_agent_positions_parquet(&positions_path, snapshots, &provenance) {
             eprintln!("Failed to export agent positions: {}", err);
         }
-        if let Err(err) = write_trips_parquet(&all_trips_path, snapshots) {
+        if let Err(err) = write_trips_parquet(&all_trips_path, snapshots, &provenance) {
             eprintln!("Failed to export trips: {}", err);
         }
+        if let Err(err) =
+            write_driver_utilization_parquet(&utilization_path, telemetry, end_ms, &provenance)
+        {
+            eprintln!("Failed to export driver utilization: {}", err);
+        }
+        if let Err(err) = write_funnel_parquet(&funnel_path, telemetry, &provenance) {
+            eprintln!("Failed to export funnel: {}", err);
+        }
+        if let Err(err) = write_cell_hours_parquet(&cell_hours_path, telemetry, end_ms, &provenance)
+        {
+            eprintln!("Failed to export cell-hour aggregates: {}", err);
+        }
+        if let Some(diagnostics) = world.get_resource::<sim_core::telemetry::MatchDiagnostics>() {
+            let diagnostics_path = export_path.join("match_diagnostics.parquet");
+            if let Err(err) =
+                write_match_diagnostics_parquet(&diagnostics_path, diagnostics, &provenance)
+            {
+                eprintln!("Failed to export match diagnostics: {}", err);
+            }
+        }
+        if let Some(log) = world.get_resource::<sim_core::telemetry::QuoteFeatureLog>() {
+            let features_path = export_path.join("quote_features.parquet");
+            if let Err(err) =
+                write_quote_features_parquet(&features_path, log, telemetry, &provenance)
+            {
+                eprintln!("Failed to export quote features: {}", err);
+            }
+        }
+        if let Some(log) = world.get_resource::<sim_core::telemetry::ShadowMatchLog>() {
+            let shadow_path = export_path.join("shadow_matches.parquet");
+            if let Err(err) = write_shadow_matches_parquet(&shadow_path, log, &provenance) {
+                eprintln!("Failed to export shadow matches: {}", err);
+            }
+        }
 
         println!("Exported Parquet files to {:?}", export_path);
+
+        if env::var("SIM_EXPORT_IPC").is_ok() {
+            let exports: [(&str, Result<(), Box<dyn std::error::Error>>); 7] = [
+                (
+                    "completed trips",
+                    write_completed_trips_ipc(
+                        export_path.join("completed_trips.arrow"),
+                        telemetry,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "snapshot counts",
+                    write_snapshot_counts_ipc(
+                        export_path.join("snapshot_counts.arrow"),
+                        snapshots,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "agent positions",
+                    write_agent_positions_ipc(
+                        export_path.join("agent_positions.arrow"),
+                        snapshots,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "trips",
+                    write_trips_ipc(export_path.join("trips.arrow"), snapshots, &provenance),
+                ),
+                (
+                    "driver utilization",
+                    write_driver_utilization_ipc(
+                        export_path.join("driver_utilization.arrow"),
+                        telemetry,
+                        end_ms,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "funnel",
+                    write_funnel_ipc(export_path.join("funnel.arrow"), telemetry, &provenance),
+                ),
+                (
+                    "cell-hour aggregates",
+                    write_cell_hours_ipc(
+                        export_path.join("cell_hours.arrow"),
+                        telemetry,
+                        end_ms,
+                        &provenance,
+                    ),
+                ),
+            ];
+            for (name, result) in exports {
+                if let Err(err) = result {
+                    eprintln!("Failed to export {} (Arrow IPC): {}", name, err);
+                }
+            }
+            println!("Exported Arrow IPC files to {:?}", export_path);
+        }
+
+        // SIM_EXPORT_NDJSON=1 writes NDJSON files; SIM_EXPORT_NDJSON=zstd compresses them
+        if let Ok(ndjson) = env::var("SIM_EXPORT_NDJSON") {
+            let (extension, compression) = if ndjson == "zstd" {
+                ("ndjson.zst", JsonCompression::Zstd)
+            } else {
+                ("ndjson", JsonCompression::None)
+            };
+            let exports: [(&str, Result<(), Box<dyn std::error::Error>>); 3] = [
+                (
+                    "completed trips",
+                    write_completed_trips_ndjson(
+                        export_path.join(format!("completed_trips.{extension}")),
+                        telemetry,
+                        compression,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "trips",
+                    write_trips_ndjson(
+                        export_path.join(format!("trips.{extension}")),
+                        snapshots,
+                        compression,
+                        &provenance,
+                    ),
+                ),
+                (
+                    "snapshots",
+                    write_snapshots_ndjson(
+                        export_path.join(format!("snapshots.{extension}")),
+                        snapshots,
+                        compression,
+                        &provenance,
+                    ),
+                ),
+            ];
+            for (name, result) in exports {
+                if let Err(err) = result {
+                    eprintln!("Failed to export {} (NDJSON): {}", name, err);
+                }
+            }
+            println!("Exported NDJSON files to {:?}", export_path);
+        }
+
+        #[cfg(feature = "sqlite")]
+        {
+            let db_path = export_path.join("run.db");
+            match sim_core::telemetry_export::write_run_sqlite(&db_path, telemetry, snapshots) {
+                Ok(()) => println!("Exported SQLite database to {:?}", db_path),
+                Err(err) => eprintln!("Failed to export SQLite database: {}", err),
+            }
+        }
+    }
+
+    // Optional: SIM_RUN_BUNDLE=run.simrun records the run for replay in sim_ui or re-analysis
+    if let Ok(bundle_path) = env::var("SIM_RUN_BUNDLE") {
+        match RunBundle::capture(&mut world)
+            .with_label("scenario_run")
+            .write(&bundle_path)
+        {
+            Ok(()) => println!("Wrote run bundle to {}", bundle_path),
+            Err(err) => eprintln!("Failed to write run bundle: {}", err),
+        }
     }
 }
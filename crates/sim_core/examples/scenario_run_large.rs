@@ -8,11 +8,12 @@
 use bevy_ecs::prelude::World;
 use sim_core::pricing::PricingConfig;
 use sim_core::profiling::EventMetrics;
+use sim_core::provenance::ExportProvenance;
 use sim_core::runner::{run_until_empty, simulation_schedule};
 use sim_core::scenario::{build_scenario, ScenarioParams};
 use sim_core::telemetry_export::{
-    write_agent_positions_parquet, write_completed_trips_parquet, write_snapshot_counts_parquet,
-    write_trips_parquet,
+    set_max_batch_bytes, write_agent_positions_parquet, write_completed_trips_parquet,
+    write_snapshot_counts_parquet, write_trips_parquet,
 };
 use std::env;
 use std::fs;
@@ -52,6 +53,7 @@ fn main() {
             surge_enabled: true,
             surge_radius_k: 2,
             surge_max_multiplier: 1.3,
+            ..Default::default()
         }),
     );
     sim_core::runner::initialize_simulation(&mut world);
@@ -179,22 +181,29 @@ fn main() {
             eprintln!("Failed to create export dir {:?}: {}", export_path, err);
             return;
         }
+        if let Some(mb) = env::var("SIM_EXPORT_MAX_BATCH_MB")
+            .ok()
+            .and_then(|mb| mb.parse::<usize>().ok())
+        {
+            set_max_batch_bytes(mb * 1024 * 1024);
+        }
         let trips_path = export_path.join("completed_trips.parquet");
         let counts_path = export_path.join("snapshot_counts.parquet");
         let positions_path = export_path.join("agent_positions.parquet");
         let all_trips_path = export_path.join("trips.parquet");
 
         let snapshots = world.resource::<sim_core::telemetry::SimSnapshots>();
-        if let Err(err) = write_completed_trips_parquet(&trips_path, telemetry) {
+        let provenance = ExportProvenance::for_world(&world);
+        if let Err(err) = write_completed_trips_parquet(&trips_path, telemetry, &provenance) {
             eprintln!("Failed to export completed trips: {}", err);
         }
-        if let Err(err) = write_snapshot_counts_parquet(&counts_path, snapshots) {
+        if let Err(err) = write_snapshot_counts_parquet(&counts_path, snapshots, &provenance) {
             eprintln!("Failed to export snapshot counts: {}", err);
         }
-        if let Err(err) = write_agent_positions_parquet(&positions_path, snapshots) {
+        if let Err(err) = write_agent_positions_parquet(&positions_path, snapshots, &provenance) {
             eprintln!("Failed to export agent positions: {}", err);
         }
-        if let Err(err) = write_trips_parquet(&all_trips_path, snapshots) {
+        if let Err(err) = write_trips_parquet(&all_trips_path, snapshots, &provenance) {
             eprintln!("Failed to export trips: {}", err);
         }
 
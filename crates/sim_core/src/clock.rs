@@ -2,12 +2,24 @@
 //!
 //! All timestamps and `clock.now()` are in **simulation milliseconds**. Time 0 is
 //! mapped to a real-world datetime via `epoch_ms`. The timeline advances by
-//! popping the next scheduled event (same-ms events are ordered by `EventKind`).
+//! popping the next scheduled event. Events sharing a timestamp fire in [`EventPriorities`]
+//! order (completions before matches before spawns by default), then in scheduling order.
+//! Every scheduled event gets an [`EventHandle`] that can cancel or move it while it is
+//! still pending. Periodic work registers a [`Recurrence`] once with
+//! [`SimulationClock::schedule_recurring`] instead of rescheduling itself.
+//!
+//! Time-of-day lookups (demand curves, traffic, scheduled surge) read the city's local time
+//! via [`LocalTime`]: UTC by default, or a [`civil::CityTimeZone`] with DST when the
+//! `time-zones` feature is enabled.
 
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 
 use bevy_ecs::prelude::{Entity, Resource};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "time-zones")]
+pub mod civil;
 
 /// One second in simulation milliseconds.
 pub const ONE_SEC_MS: u64 = 1000;
@@ -15,8 +27,23 @@ pub const ONE_SEC_MS: u64 = 1000;
 pub const ONE_MIN_MS: u64 = 60 * ONE_SEC_MS;
 /// One hour in simulation milliseconds.
 pub const ONE_HOUR_MS: u64 = 60 * ONE_MIN_MS;
+/// One day in simulation milliseconds.
+pub const ONE_DAY_MS: u64 = 24 * ONE_HOUR_MS;
+/// One week in simulation milliseconds.
+pub const ONE_WEEK_MS: u64 = 7 * ONE_DAY_MS;
+
+/// Hour of day (0–23) for a real-world timestamp in ms.
+pub fn hour_of_day(real_ms: i64) -> usize {
+    real_ms.div_euclid(ONE_HOUR_MS as i64).rem_euclid(24) as usize
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Day of week (0 = Monday) for a real-world timestamp in ms. Day 0 of the Unix epoch was a
+/// Thursday.
+pub fn day_of_week(real_ms: i64) -> usize {
+    (real_ms.div_euclid(ONE_DAY_MS as i64) + 3).rem_euclid(7) as usize
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum EventKind {
     SimulationStarted,
     SpawnRider,
@@ -36,6 +63,132 @@ pub enum EventKind {
     TripCompleted,
     RiderCancel,
     CheckDriverOffDuty,
+    /// One rider request from a special-event venue (see `spawner::DemandEvents`).
+    SpawnEventRider,
+    /// Periodic driver location report tick (see `location::LocationReportingConfig`).
+    LocationReport,
+}
+
+impl EventKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [EventKind; 20] = [
+        EventKind::SimulationStarted,
+        EventKind::SpawnRider,
+        EventKind::SpawnDriver,
+        EventKind::ShowQuote,
+        EventKind::QuoteDecision,
+        EventKind::QuoteAccepted,
+        EventKind::QuoteRejected,
+        EventKind::TryMatch,
+        EventKind::BatchMatchRun,
+        EventKind::MatchAccepted,
+        EventKind::DriverDecision,
+        EventKind::MatchRejected,
+        EventKind::MoveStep,
+        EventKind::PickupEtaUpdated,
+        EventKind::TripStarted,
+        EventKind::TripCompleted,
+        EventKind::RiderCancel,
+        EventKind::CheckDriverOffDuty,
+        EventKind::SpawnEventRider,
+        EventKind::LocationReport,
+    ];
+
+    /// Rank among events with the same timestamp; lower fires first. Fresh driver positions
+    /// and supply changes land first, then trip progress (so finished trips free their
+    /// drivers), then matching, then quoting, then new arrivals.
+    pub const fn default_priority(self) -> u8 {
+        match self {
+            EventKind::SimulationStarted => 0,
+            EventKind::LocationReport => 1,
+            EventKind::SpawnEventRider => 2,
+            EventKind::CheckDriverOffDuty => 3,
+            EventKind::RiderCancel => 4,
+            EventKind::TripCompleted => 5,
+            EventKind::TripStarted => 6,
+            EventKind::PickupEtaUpdated => 7,
+            EventKind::MoveStep => 8,
+            EventKind::MatchRejected => 9,
+            EventKind::DriverDecision => 10,
+            EventKind::MatchAccepted => 11,
+            EventKind::BatchMatchRun => 12,
+            EventKind::TryMatch => 13,
+            EventKind::QuoteRejected => 14,
+            EventKind::QuoteAccepted => 15,
+            EventKind::QuoteDecision => 16,
+            EventKind::ShowQuote => 17,
+            EventKind::SpawnDriver => 18,
+            EventKind::SpawnRider => 19,
+        }
+    }
+}
+
+/// Tie-break for events with the same timestamp: each kind has a priority (lower fires
+/// first), [`EventKind::default_priority`] unless overridden. Events with equal timestamp
+/// and priority fire in the order they were scheduled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventPriorities {
+    overrides: BTreeMap<EventKind, u8>,
+}
+
+impl EventPriorities {
+    /// Override the priority of `kind`.
+    pub fn with(mut self, kind: EventKind, priority: u8) -> Self {
+        self.set(kind, priority);
+        self
+    }
+
+    pub fn set(&mut self, kind: EventKind, priority: u8) {
+        self.overrides.insert(kind, priority);
+    }
+
+    pub fn get(&self, kind: EventKind) -> u8 {
+        self.overrides
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.default_priority())
+    }
+}
+
+/// Wall clock of the modeled city, used to turn real-world (UTC) ms into local ms for
+/// time-of-day lookups. Local ms are UTC ms shifted by the zone's offset at that instant, so
+/// `hour = (local_ms / ONE_HOUR_MS) % 24` is the local hour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalTime {
+    #[cfg(feature = "time-zones")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    zone: Option<civil::CityTimeZone>,
+}
+
+impl LocalTime {
+    pub const UTC: Self = Self {
+        #[cfg(feature = "time-zones")]
+        zone: None,
+    };
+
+    /// Offset of local time from UTC at `utc_ms`, in ms (0 without a time zone).
+    pub fn offset_ms(&self, utc_ms: i64) -> i64 {
+        #[cfg(feature = "time-zones")]
+        if let Some(zone) = self.zone {
+            return zone.offset_ms(utc_ms);
+        }
+        let _ = utc_ms;
+        0
+    }
+
+    /// IANA name of the zone ("UTC" without a time zone).
+    pub fn zone_name(&self) -> &'static str {
+        #[cfg(feature = "time-zones")]
+        if let Some(zone) = self.zone {
+            return zone.name();
+        }
+        "UTC"
+    }
+
+    /// Local wall-clock ms for a real-world (UTC) ms.
+    pub fn to_local_ms(&self, utc_ms: i64) -> i64 {
+        utc_ms.saturating_add(self.offset_ms(utc_ms))
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,7 +198,9 @@ pub enum EventSubject {
     Trip(Entity),
 }
 
-/// Simulation event. `timestamp` is in **milliseconds** (simulation time).
+/// Simulation event. `timestamp` is in **milliseconds** (simulation time). Events have no
+/// ordering of their own: the queue order (time, [`EventPriorities`], scheduling order) is
+/// kept by [`SimulationClock`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Event {
     pub timestamp: u64,
@@ -53,25 +208,115 @@ pub struct Event {
     pub subject: Option<EventSubject>,
 }
 
-impl Ord for Event {
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct CurrentEvent(pub Event);
+
+/// Handle to a scheduled event, returned by the `schedule*` methods. Stays valid across
+/// [`SimulationClock::reschedule_at`] and stops being pending once the event is popped or
+/// cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventHandle(u64);
+
+/// When a recurring event fires: a pattern, bounded to `[start_ms, end_ms]` (simulation ms).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub pattern: RecurrencePattern,
+    /// First possible occurrence (clamped to the time it is registered).
+    pub start_ms: u64,
+    /// Last possible occurrence, inclusive. None repeats until the run ends.
+    pub end_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecurrencePattern {
+    /// Every `interval_ms`, counted from `start_ms`.
+    Every { interval_ms: u64 },
+    /// Every day at these local times (ms since local midnight, read in the clock's
+    /// [`LocalTime`]).
+    TimesOfDay { times_ms: Vec<u64> },
+}
+
+impl Recurrence {
+    /// Every `interval_ms` (at least 1 ms), starting at time 0.
+    pub fn every(interval_ms: u64) -> Self {
+        Self {
+            pattern: RecurrencePattern::Every {
+                interval_ms: interval_ms.max(1),
+            },
+            start_ms: 0,
+            end_ms: None,
+        }
+    }
+
+    /// Every `interval_mins` minutes, starting at time 0.
+    pub fn every_mins(interval_mins: u64) -> Self {
+        Self::every(interval_mins.saturating_mul(ONE_MIN_MS))
+    }
+
+    /// Every day at the given local `(hour, minute)` times, like a daily cron entry.
+    pub fn daily_at(times: &[(u32, u32)]) -> Self {
+        let mut times_ms: Vec<u64> = times
+            .iter()
+            .map(|&(hour, minute)| {
+                (u64::from(hour) * ONE_HOUR_MS + u64::from(minute) * ONE_MIN_MS) % ONE_DAY_MS
+            })
+            .collect();
+        times_ms.sort_unstable();
+        times_ms.dedup();
+        Self {
+            pattern: RecurrencePattern::TimesOfDay { times_ms },
+            start_ms: 0,
+            end_ms: None,
+        }
+    }
+
+    pub fn starting_at(mut self, start_ms: u64) -> Self {
+        self.start_ms = start_ms;
+        self
+    }
+
+    pub fn until(mut self, end_ms: u64) -> Self {
+        self.end_ms = Some(end_ms);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RecurringEvent {
+    kind: EventKind,
+    subject: Option<EventSubject>,
+    recurrence: Recurrence,
+}
+
+/// Queue entry: the event plus the handle and entry sequence that must still be current for
+/// the entry to fire (cancelled and rescheduled entries are dropped lazily when popped).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QueuedEvent {
+    event: Event,
+    handle: EventHandle,
+    seq: u64,
+    priority: u8,
+}
+
+impl Ord for QueuedEvent {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Min-heap by timestamp; same timestamp ordered by kind for determinism.
+        // Max-heap pops the greatest entry: earliest timestamp, then lowest priority, then
+        // earliest scheduled.
         other
+            .event
             .timestamp
-            .cmp(&self.timestamp)
-            .then_with(|| self.kind.cmp(&other.kind))
+            .cmp(&self.event.timestamp)
+            .then_with(|| other.priority.cmp(&self.priority))
+            .then_with(|| other.seq.cmp(&self.seq))
     }
 }
 
-impl PartialOrd for Event {
+impl PartialOrd for QueuedEvent {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-#[derive(Debug, Clone, Copy, Resource)]
-pub struct CurrentEvent(pub Event);
-
 /// Simulation clock: time in **milliseconds**, advances to the next scheduled event.
 /// Time 0 maps to a real-world datetime via `epoch_ms` (e.g. Unix epoch offset).
 #[derive(Debug, Clone, Default, Resource)]
@@ -80,7 +325,18 @@ pub struct SimulationClock {
     now: u64,
     /// Real-world ms corresponding to simulation time 0 (e.g. Unix epoch or a fixed datetime).
     epoch_ms: i64,
-    events: BinaryHeap<Event>,
+    /// City wall clock for time-of-day lookups (UTC by default).
+    local_time: LocalTime,
+    events: BinaryHeap<QueuedEvent>,
+    /// Pending handles: the sequence of their live queue entry, its timestamp and kind.
+    pending: HashMap<EventHandle, (u64, u64, EventKind)>,
+    /// Live (pending) events per kind, so [`Self::has_pending`] does not scan the queue.
+    pending_by_kind: HashMap<EventKind, usize>,
+    /// Recurring series by handle; the next occurrence is queued when the current one pops.
+    recurring: HashMap<EventHandle, RecurringEvent>,
+    next_seq: u64,
+    /// Same-timestamp ordering by event kind.
+    priorities: EventPriorities,
 }
 
 impl SimulationClock {
@@ -89,7 +345,8 @@ impl SimulationClock {
         Self {
             now: 0,
             epoch_ms,
-            events: BinaryHeap::new(),
+            local_time: LocalTime::UTC,
+            ..Self::default()
         }
     }
 
@@ -123,6 +380,45 @@ impl SimulationClock {
         self.epoch_ms.saturating_add(sim_ms as i64)
     }
 
+    /// City wall clock used for time-of-day lookups.
+    pub fn local_time(&self) -> LocalTime {
+        self.local_time
+    }
+
+    pub fn set_local_time(&mut self, local_time: LocalTime) {
+        self.local_time = local_time;
+    }
+
+    /// Convert simulation ms to local wall-clock ms (real-world ms shifted by the zone offset).
+    pub fn sim_to_local_ms(&self, sim_ms: u64) -> i64 {
+        self.local_time.to_local_ms(self.sim_to_real_ms(sim_ms))
+    }
+
+    /// Epoch for time-of-day lookups at the current time: `epoch_ms` plus the local offset at
+    /// `now`, so `local_epoch_ms() + sim_ms` is local wall-clock ms near `now`.
+    pub fn local_epoch_ms(&self) -> i64 {
+        self.sim_to_local_ms(self.now)
+            .saturating_sub(self.now as i64)
+    }
+
+    /// Same-timestamp ordering by event kind.
+    pub fn event_priorities(&self) -> &EventPriorities {
+        &self.priorities
+    }
+
+    /// Replace the same-timestamp ordering. Events already queued are re-ranked.
+    pub fn set_event_priorities(&mut self, priorities: EventPriorities) {
+        self.priorities = priorities;
+        let queued = std::mem::take(&mut self.events);
+        self.events = queued
+            .into_iter()
+            .map(|entry| QueuedEvent {
+                priority: self.priorities.get(entry.event.kind),
+                ..entry
+            })
+            .collect();
+    }
+
     /// Convert real-world ms to simulation ms. Returns `None` if real_ms is before the epoch.
     pub fn real_to_sim_ms(&self, real_ms: i64) -> Option<u64> {
         let delta = real_ms.saturating_sub(self.epoch_ms);
@@ -133,12 +429,17 @@ impl SimulationClock {
     }
 
     /// Schedule an event at a specific simulation timestamp (ms).
-    pub fn schedule_at(&mut self, at_ms: u64, kind: EventKind, subject: Option<EventSubject>) {
+    pub fn schedule_at(
+        &mut self,
+        at_ms: u64,
+        kind: EventKind,
+        subject: Option<EventSubject>,
+    ) -> EventHandle {
         self.schedule(Event {
             timestamp: at_ms,
             kind,
             subject,
-        });
+        })
     }
 
     /// Schedule an event at a simulation time in **seconds** (at_secs × 1000 ms).
@@ -147,8 +448,8 @@ impl SimulationClock {
         at_secs: u64,
         kind: EventKind,
         subject: Option<EventSubject>,
-    ) {
-        self.schedule_at(at_secs.saturating_mul(ONE_SEC_MS), kind, subject);
+    ) -> EventHandle {
+        self.schedule_at(at_secs.saturating_mul(ONE_SEC_MS), kind, subject)
     }
 
     /// Schedule an event at a simulation time in **minutes** (at_mins × 60_000 ms).
@@ -157,13 +458,18 @@ impl SimulationClock {
         at_mins: u64,
         kind: EventKind,
         subject: Option<EventSubject>,
-    ) {
-        self.schedule_at(at_mins.saturating_mul(ONE_MIN_MS), kind, subject);
+    ) -> EventHandle {
+        self.schedule_at(at_mins.saturating_mul(ONE_MIN_MS), kind, subject)
     }
 
     /// Schedule an event at `now + delta_ms` (relative, in ms).
-    pub fn schedule_in(&mut self, delta_ms: u64, kind: EventKind, subject: Option<EventSubject>) {
-        self.schedule_at(self.now.saturating_add(delta_ms), kind, subject);
+    pub fn schedule_in(
+        &mut self,
+        delta_ms: u64,
+        kind: EventKind,
+        subject: Option<EventSubject>,
+    ) -> EventHandle {
+        self.schedule_at(self.now.saturating_add(delta_ms), kind, subject)
     }
 
     /// Schedule an event in **delta_secs** seconds from now.
@@ -172,8 +478,8 @@ impl SimulationClock {
         delta_secs: u64,
         kind: EventKind,
         subject: Option<EventSubject>,
-    ) {
-        self.schedule_in(delta_secs.saturating_mul(ONE_SEC_MS), kind, subject);
+    ) -> EventHandle {
+        self.schedule_in(delta_secs.saturating_mul(ONE_SEC_MS), kind, subject)
     }
 
     /// Schedule an event in **delta_mins** minutes from now.
@@ -182,37 +488,232 @@ impl SimulationClock {
         delta_mins: u64,
         kind: EventKind,
         subject: Option<EventSubject>,
-    ) {
-        self.schedule_in(delta_mins.saturating_mul(ONE_MIN_MS), kind, subject);
+    ) -> EventHandle {
+        self.schedule_in(delta_mins.saturating_mul(ONE_MIN_MS), kind, subject)
     }
 
     /// Schedule a full event (for flexibility; timestamp must be in ms, >= now).
-    pub fn schedule(&mut self, event: Event) {
-        debug_assert!(
-            event.timestamp >= self.now,
-            "event timestamp must be >= current time"
+    pub fn schedule(&mut self, event: Event) -> EventHandle {
+        let handle = EventHandle(self.next_seq);
+        self.enqueue(handle, event);
+        handle
+    }
+
+    /// Register a recurring event. Each occurrence is queued when the previous one pops, under
+    /// the same handle, so `cancel(handle)` ends the series. Returns None if the recurrence has
+    /// no occurrence at or after now.
+    pub fn schedule_recurring(
+        &mut self,
+        kind: EventKind,
+        subject: Option<EventSubject>,
+        recurrence: Recurrence,
+    ) -> Option<EventHandle> {
+        let first = self.next_occurrence(&recurrence, self.now, true)?;
+        let handle = self.schedule_at(first, kind, subject);
+        self.recurring.insert(
+            handle,
+            RecurringEvent {
+                kind,
+                subject,
+                recurrence,
+            },
+        );
+        Some(handle)
+    }
+
+    /// Cancel a pending event, or the rest of a recurring series. Returns false if it already
+    /// fired or was cancelled.
+    pub fn cancel(&mut self, handle: EventHandle) -> bool {
+        self.recurring.remove(&handle);
+        let Some((_, _, kind)) = self.pending.remove(&handle) else {
+            return false;
+        };
+        self.release_pending(kind);
+        self.drop_stale_head();
+        true
+    }
+
+    /// Move a pending event to `at_ms` (>= now), keeping its kind, subject and handle.
+    /// Returns false if it already fired or was cancelled.
+    pub fn reschedule_at(&mut self, handle: EventHandle, at_ms: u64) -> bool {
+        let Some(&(seq, _, _)) = self.pending.get(&handle) else {
+            return false;
+        };
+        let Some(event) = self
+            .events
+            .iter()
+            .find(|queued| queued.handle == handle && queued.seq == seq)
+            .map(|queued| queued.event)
+        else {
+            return false;
+        };
+        self.enqueue(
+            handle,
+            Event {
+                timestamp: at_ms,
+                ..event
+            },
         );
-        self.events.push(event);
+        self.drop_stale_head();
+        true
+    }
+
+    /// Move a pending event to `now + delta_ms`. See [`Self::reschedule_at`].
+    pub fn reschedule_in(&mut self, handle: EventHandle, delta_ms: u64) -> bool {
+        self.reschedule_at(handle, self.now.saturating_add(delta_ms))
+    }
+
+    /// Whether the event behind `handle` is still queued.
+    pub fn is_pending(&self, handle: EventHandle) -> bool {
+        self.pending.contains_key(&handle)
+    }
+
+    /// Timestamp the event behind `handle` will fire at, if it is still queued.
+    pub fn scheduled_time(&self, handle: EventHandle) -> Option<u64> {
+        self.pending
+            .get(&handle)
+            .map(|&(_, timestamp, _)| timestamp)
     }
 
-    /// Pop the next event (earliest timestamp; same-ms order by kind). Advances `now` to that timestamp.
+    /// Pop the next event (earliest timestamp; same-ms order by [`EventPriorities`], then
+    /// scheduling order). Advances `now` to that timestamp.
     pub fn pop_next(&mut self) -> Option<Event> {
-        let event = self.events.pop()?;
-        self.now = event.timestamp;
-        Some(event)
+        self.drop_stale_head();
+        let queued = self.events.pop()?;
+        if self.pending.remove(&queued.handle).is_some() {
+            self.release_pending(queued.event.kind);
+        }
+        self.now = queued.event.timestamp;
+        if let Some(series) = self.recurring.get(&queued.handle) {
+            match self.next_occurrence(&series.recurrence, self.now, false) {
+                Some(next) => {
+                    let event = Event {
+                        timestamp: next,
+                        kind: series.kind,
+                        subject: series.subject,
+                    };
+                    self.enqueue(queued.handle, event);
+                }
+                None => {
+                    self.recurring.remove(&queued.handle);
+                }
+            }
+        }
+        self.drop_stale_head();
+        Some(queued.event)
     }
 
     /// Timestamp of the next scheduled event without popping it.
     pub fn next_event_time(&self) -> Option<u64> {
-        self.events.peek().map(|event| event.timestamp)
+        self.events.peek().map(|queued| queued.event.timestamp)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.events.is_empty()
+        self.pending.is_empty()
+    }
+
+    /// Whether an event of `kind` is still queued.
+    pub fn has_pending(&self, kind: EventKind) -> bool {
+        self.pending_by_kind.contains_key(&kind)
     }
 
     /// Number of events still in the queue (for tests and scenario validation).
     pub fn pending_event_count(&self) -> usize {
-        self.events.len()
+        self.pending.len()
+    }
+
+    /// First occurrence of `recurrence` after `from_ms` (or at it, when `inclusive`).
+    fn next_occurrence(
+        &self,
+        recurrence: &Recurrence,
+        from_ms: u64,
+        inclusive: bool,
+    ) -> Option<u64> {
+        let (from_ms, inclusive) = if from_ms < recurrence.start_ms {
+            (recurrence.start_ms, true)
+        } else {
+            (from_ms, inclusive)
+        };
+        let next = match &recurrence.pattern {
+            RecurrencePattern::Every { interval_ms } => {
+                let interval_ms = (*interval_ms).max(1);
+                let elapsed = from_ms - recurrence.start_ms;
+                let mut steps = elapsed / interval_ms;
+                if !elapsed.is_multiple_of(interval_ms) || !inclusive {
+                    steps += 1;
+                }
+                recurrence
+                    .start_ms
+                    .saturating_add(steps.saturating_mul(interval_ms))
+            }
+            RecurrencePattern::TimesOfDay { times_ms } => {
+                // Work in local ms with the offset at `from_ms`; a DST change moves at most the
+                // first occurrence after it by the size of the change.
+                let local_from = self.sim_to_local_ms(from_ms);
+                let offset = local_from - from_ms as i64;
+                let day_start = local_from.div_euclid(ONE_DAY_MS as i64) * ONE_DAY_MS as i64;
+                (0..=1i64)
+                    .flat_map(|day| {
+                        times_ms.iter().map(move |&time_ms| {
+                            day_start + day * ONE_DAY_MS as i64 + time_ms as i64 - offset
+                        })
+                    })
+                    .filter(|&at| at > from_ms as i64 || (inclusive && at == from_ms as i64))
+                    .min()
+                    .map(|at| at as u64)?
+            }
+        };
+        match recurrence.end_ms {
+            Some(end_ms) if next > end_ms => None,
+            _ => Some(next),
+        }
+    }
+
+    fn enqueue(&mut self, handle: EventHandle, event: Event) {
+        debug_assert!(
+            event.timestamp >= self.now,
+            "event timestamp must be >= current time"
+        );
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let replaced = self
+            .pending
+            .insert(handle, (seq, event.timestamp, event.kind));
+        if replaced.is_none() {
+            *self.pending_by_kind.entry(event.kind).or_default() += 1;
+        }
+        let priority = self.priorities.get(event.kind);
+        self.events.push(QueuedEvent {
+            event,
+            handle,
+            seq,
+            priority,
+        });
+    }
+
+    fn is_live(&self, queued: &QueuedEvent) -> bool {
+        self.pending
+            .get(&queued.handle)
+            .is_some_and(|&(seq, _, _)| seq == queued.seq)
+    }
+
+    /// One pending event of `kind` fired or was cancelled.
+    fn release_pending(&mut self, kind: EventKind) {
+        if let Some(count) = self.pending_by_kind.get_mut(&kind) {
+            *count -= 1;
+            if *count == 0 {
+                self.pending_by_kind.remove(&kind);
+            }
+        }
+    }
+
+    /// Pop cancelled or superseded entries off the top so `next_event_time` sees a live event.
+    fn drop_stale_head(&mut self) {
+        while let Some(queued) = self.events.peek() {
+            if self.is_live(queued) {
+                break;
+            }
+            self.events.pop();
+        }
     }
 }
@@ -0,0 +1,230 @@
+//! City time zones from the IANA tz database, backed by `chrono-tz` (`time-zones` feature).
+//!
+//! A [`CityTimeZone`] wraps an IANA zone such as `Europe/Berlin`, so offsets and DST
+//! transitions follow the real rules for every year chrono-tz covers. Wrapped in
+//! [`LocalTime`] and set on the clock (`ScenarioParams::with_time_zone`), it moves every
+//! time-of-day lookup (spawn rates, weekly patterns, traffic, scheduled surge) onto the
+//! city's wall clock, so a 08:00 peak stays at 08:00 local time across DST changes.
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, Tz};
+use serde::{Deserialize, Serialize};
+
+use super::LocalTime;
+
+/// A city's civil time: an IANA time zone. Serializes as its name (e.g. `"Europe/Berlin"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CityTimeZone(Tz);
+
+impl CityTimeZone {
+    pub const UTC: Self = Self(Tz::UTC);
+
+    pub const fn new(tz: Tz) -> Self {
+        Self(tz)
+    }
+
+    /// Look up an IANA zone by name (e.g. `"America/Sao_Paulo"`).
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        name.trim()
+            .parse::<Tz>()
+            .map(Self)
+            .map_err(|_| format!("Unknown IANA time zone '{name}'"))
+    }
+
+    /// Every zone in the bundled tz database, in name order.
+    pub fn all() -> impl Iterator<Item = Self> {
+        chrono_tz::TZ_VARIANTS.iter().copied().map(Self)
+    }
+
+    /// IANA name of the zone.
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    /// Europe/Berlin: CET/CEST.
+    pub const fn berlin() -> Self {
+        Self(Tz::Europe__Berlin)
+    }
+
+    /// Europe/London: GMT/BST.
+    pub const fn london() -> Self {
+        Self(Tz::Europe__London)
+    }
+
+    /// America/New_York: EST/EDT.
+    pub const fn new_york() -> Self {
+        Self(Tz::America__New_York)
+    }
+
+    /// Whether DST is in effect at `utc_ms`.
+    pub fn is_dst(&self, utc_ms: i64) -> bool {
+        DateTime::from_timestamp_millis(utc_ms)
+            .map(|utc| {
+                !self
+                    .0
+                    .offset_from_utc_datetime(&utc.naive_utc())
+                    .dst_offset()
+                    .is_zero()
+            })
+            .unwrap_or(false)
+    }
+
+    /// Offset from UTC at `utc_ms`, in milliseconds.
+    pub fn offset_ms(&self, utc_ms: i64) -> i64 {
+        i64::from(self.offset_at(utc_ms).local_minus_utc()) * 1000
+    }
+
+    /// Offset from UTC at `utc_ms` (zero if the timestamp is out of chrono's range).
+    pub fn offset_at(&self, utc_ms: i64) -> FixedOffset {
+        DateTime::from_timestamp_millis(utc_ms)
+            .map(|utc| self.0.offset_from_utc_datetime(&utc.naive_utc()).fix())
+            .unwrap_or(Utc.fix())
+    }
+
+    /// Local date and time at `utc_ms`. None if the timestamp is out of chrono's range.
+    pub fn local_datetime(&self, utc_ms: i64) -> Option<DateTime<FixedOffset>> {
+        let utc: DateTime<Utc> = DateTime::from_timestamp_millis(utc_ms)?;
+        Some(utc.with_timezone(&self.0).fixed_offset())
+    }
+
+    /// UTC ms for a local wall-clock time. Times repeated when clocks go back resolve to the
+    /// earlier instant; times skipped when clocks go forward return None.
+    pub fn utc_ms_from_local(&self, local: NaiveDateTime) -> Option<i64> {
+        self.0
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|dt| dt.timestamp_millis())
+    }
+}
+
+impl Default for CityTimeZone {
+    fn default() -> Self {
+        Self::UTC
+    }
+}
+
+impl LocalTime {
+    /// Follow `zone`'s wall clock.
+    pub const fn zone(zone: CityTimeZone) -> Self {
+        Self { zone: Some(zone) }
+    }
+
+    pub fn time_zone(&self) -> Option<CityTimeZone> {
+        self.zone
+    }
+
+    /// Local date and time at `utc_ms` (UTC when no zone is set).
+    pub fn datetime(&self, utc_ms: i64) -> Option<DateTime<FixedOffset>> {
+        self.zone
+            .unwrap_or(CityTimeZone::UTC)
+            .local_datetime(utc_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, NaiveDate, Timelike};
+
+    const MS_PER_MIN: i64 = 60_000;
+
+    fn utc_ms(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .expect("valid datetime")
+            .and_utc()
+            .timestamp_millis()
+    }
+
+    #[test]
+    fn berlin_switches_to_summer_time_on_last_sunday_of_march() {
+        let berlin = CityTimeZone::berlin();
+        // 2024-03-31 00:59 UTC is 01:59 CET; one hour later clocks read 03:00 CEST.
+        let before = utc_ms(2024, 3, 31, 0, 59);
+        let after = utc_ms(2024, 3, 31, 1, 0);
+        assert_eq!(berlin.offset_ms(before), 60 * MS_PER_MIN);
+        assert_eq!(berlin.offset_ms(after), 120 * MS_PER_MIN);
+        let local = berlin.local_datetime(after).unwrap();
+        assert_eq!((local.hour(), local.minute()), (3, 0));
+        assert!(!berlin.is_dst(utc_ms(2024, 10, 27, 1, 0)));
+        assert!(berlin.is_dst(utc_ms(2024, 10, 27, 0, 59)));
+    }
+
+    #[test]
+    fn new_york_follows_north_american_rule() {
+        let new_york = CityTimeZone::new_york();
+        // 2024-03-10 02:00 EST = 07:00 UTC; 2024-11-03 02:00 EDT = 06:00 UTC.
+        assert!(!new_york.is_dst(utc_ms(2024, 3, 10, 6, 59)));
+        assert!(new_york.is_dst(utc_ms(2024, 3, 10, 7, 0)));
+        assert!(new_york.is_dst(utc_ms(2024, 11, 3, 5, 59)));
+        assert!(!new_york.is_dst(utc_ms(2024, 11, 3, 6, 0)));
+        let summer = new_york.local_datetime(utc_ms(2024, 7, 4, 16, 0)).unwrap();
+        assert_eq!((summer.day(), summer.hour()), (4, 12));
+    }
+
+    #[test]
+    fn named_zones_follow_the_tz_database() {
+        let sydney = CityTimeZone::from_name("Australia/Sydney").unwrap();
+        assert_eq!(sydney.name(), "Australia/Sydney");
+        // Southern hemisphere: AEDT in January, AEST in July.
+        assert_eq!(
+            sydney.offset_ms(utc_ms(2024, 1, 15, 0, 0)),
+            660 * MS_PER_MIN
+        );
+        assert_eq!(
+            sydney.offset_ms(utc_ms(2024, 7, 15, 0, 0)),
+            600 * MS_PER_MIN
+        );
+        // Before 2007 US DST started on the first Sunday of April.
+        assert!(!CityTimeZone::new_york().is_dst(utc_ms(2006, 3, 20, 12, 0)));
+        assert!(CityTimeZone::from_name("Mars/Olympus_Mons").is_err());
+        let json = serde_json::to_string(&CityTimeZone::berlin()).unwrap();
+        assert_eq!(json, "\"Europe/Berlin\"");
+        assert_eq!(
+            serde_json::from_str::<CityTimeZone>(&json).unwrap(),
+            CityTimeZone::berlin()
+        );
+    }
+
+    #[test]
+    fn local_wall_clock_round_trips_except_in_the_spring_gap() {
+        let berlin = CityTimeZone::berlin();
+        let local = |day, hour, minute| {
+            NaiveDate::from_ymd_opt(2024, 3, day)
+                .and_then(|date| date.and_hms_opt(hour, minute, 0))
+                .unwrap()
+        };
+        assert_eq!(
+            berlin.utc_ms_from_local(local(15, 8, 30)),
+            Some(utc_ms(2024, 3, 15, 7, 30))
+        );
+        assert_eq!(
+            berlin.utc_ms_from_local(local(31, 3, 30)),
+            Some(utc_ms(2024, 3, 31, 1, 30))
+        );
+        assert_eq!(berlin.utc_ms_from_local(local(31, 2, 30)), None);
+        // 02:30 on the autumn change happens twice; the earlier (CEST) instant wins.
+        let repeated = NaiveDate::from_ymd_opt(2024, 10, 27)
+            .and_then(|date| date.and_hms_opt(2, 30, 0))
+            .unwrap();
+        assert_eq!(
+            berlin.utc_ms_from_local(repeated),
+            Some(utc_ms(2024, 10, 27, 0, 30))
+        );
+    }
+
+    #[test]
+    fn local_time_shifts_hours_onto_the_city_clock() {
+        let summer_noon_utc = utc_ms(2024, 7, 1, 12, 0);
+        let utc = LocalTime::UTC;
+        let berlin = LocalTime::zone(CityTimeZone::berlin());
+        assert_eq!(utc.to_local_ms(summer_noon_utc), summer_noon_utc);
+        assert_eq!(
+            berlin.to_local_ms(summer_noon_utc) - summer_noon_utc,
+            120 * MS_PER_MIN
+        );
+        assert_eq!(berlin.datetime(summer_noon_utc).unwrap().hour(), 14);
+        assert_eq!(utc.datetime(summer_noon_utc).unwrap().month(), 7);
+    }
+}
@@ -0,0 +1,200 @@
+//! Trained rider conversion and driver acceptance models.
+//!
+//! A [`LogisticModel`] is a logistic regression over named features, loaded from a
+//! linear-model JSON file. A [`ConversionModel`] (from `RiderQuoteConfig::conversion_model_path`)
+//! replaces the hand-tuned logit in `quote_decision_system`; the willingness-to-pay and ETA
+//! limits still apply first. A [`DriverAcceptanceModel`] (from
+//! `DriverDecisionConfig::acceptance_model_path`) replaces the fixed logit weights in
+//! `driver_decision_system`. Rider feature names follow the `quote_features` export columns,
+//! so a model fitted on that table loads directly:
+//!
+//! ```json
+//! { "intercept": 1.2, "weights": { "quoted_fare": -0.04, "quoted_eta_ms": -0.000002 } }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use bevy_ecs::prelude::Resource;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Feature values of one decision, looked up by feature name.
+pub trait FeatureValues {
+    type Feature;
+
+    fn value(&self, feature: Self::Feature) -> f64;
+}
+
+/// Logistic regression: `p = 1 / (1 + exp(-(intercept + sum(weight * feature))))`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource)]
+pub struct LogisticModel<F: Ord> {
+    pub intercept: f64,
+    #[serde(default = "BTreeMap::new")]
+    pub weights: BTreeMap<F, f64>,
+}
+
+/// Rider quote acceptance model used by `quote_decision_system`.
+pub type ConversionModel = LogisticModel<ConversionFeature>;
+
+/// Driver match acceptance model used by `driver_decision_system`.
+pub type DriverAcceptanceModel = LogisticModel<DriverAcceptanceFeature>;
+
+impl<F: Ord> Default for LogisticModel<F> {
+    fn default() -> Self {
+        Self {
+            intercept: 0.0,
+            weights: BTreeMap::new(),
+        }
+    }
+}
+
+impl<F: Ord + Copy + DeserializeOwned> LogisticModel<F> {
+    /// Read a linear-model JSON file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read model {path}: {e}"))?;
+        Self::from_json(&data).map_err(|e| format!("Invalid model {path}: {e}"))
+    }
+
+    pub fn from_json(data: &str) -> Result<Self, String> {
+        let model: Self = serde_json::from_str(data).map_err(|e| e.to_string())?;
+        if !model.intercept.is_finite() || model.weights.values().any(|w| !w.is_finite()) {
+            return Err("weights must be finite".to_string());
+        }
+        Ok(model)
+    }
+
+    /// Probability that the decision is an accept.
+    pub fn accept_probability(&self, features: &impl FeatureValues<Feature = F>) -> f64 {
+        let score = self.intercept
+            + self
+                .weights
+                .iter()
+                .map(|(&feature, weight)| weight * features.value(feature))
+                .sum::<f64>();
+        1.0 / (1.0 + (-score).exp())
+    }
+}
+
+/// Model inputs available when a rider decides on a quote.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionFeature {
+    QuotedFare,
+    QuotedEtaMs,
+    DistanceKm,
+    /// Local hour of day (0-23).
+    LocalHour,
+    /// Local day of week, 0 = Monday.
+    LocalWeekday,
+    /// Quotes the rider rejected before this one.
+    QuoteIndex,
+    /// `fare / reference - 1` against the rider's reference price.
+    RelativePrice,
+}
+
+/// Feature values of one quote decision.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConversionFeatures {
+    pub fare: f64,
+    pub eta_ms: u64,
+    pub distance_km: f64,
+    pub local_hour: u8,
+    pub local_weekday: u8,
+    pub quote_index: u32,
+    pub relative_price: f64,
+}
+
+impl FeatureValues for ConversionFeatures {
+    type Feature = ConversionFeature;
+
+    fn value(&self, feature: ConversionFeature) -> f64 {
+        match feature {
+            ConversionFeature::QuotedFare => self.fare,
+            ConversionFeature::QuotedEtaMs => self.eta_ms as f64,
+            ConversionFeature::DistanceKm => self.distance_km,
+            ConversionFeature::LocalHour => f64::from(self.local_hour),
+            ConversionFeature::LocalWeekday => f64::from(self.local_weekday),
+            ConversionFeature::QuoteIndex => f64::from(self.quote_index),
+            ConversionFeature::RelativePrice => self.relative_price,
+        }
+    }
+}
+
+/// Model inputs available when a driver decides on a match; the terms of the fixed logit in
+/// `DriverDecisionConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriverAcceptanceFeature {
+    Fare,
+    PickupDistanceKm,
+    TripDistanceKm,
+    /// Daily earnings over the daily earnings target.
+    EarningsProgress,
+    /// Session time over the fatigue threshold.
+    FatigueRatio,
+    /// Local hour of day (0-23).
+    LocalHour,
+}
+
+/// Feature values of one driver decision.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DriverAcceptanceFeatures {
+    pub fare: f64,
+    pub pickup_distance_km: f64,
+    pub trip_distance_km: f64,
+    pub earnings_progress: f64,
+    pub fatigue_ratio: f64,
+    pub local_hour: u8,
+}
+
+impl FeatureValues for DriverAcceptanceFeatures {
+    type Feature = DriverAcceptanceFeature;
+
+    fn value(&self, feature: DriverAcceptanceFeature) -> f64 {
+        match feature {
+            DriverAcceptanceFeature::Fare => self.fare,
+            DriverAcceptanceFeature::PickupDistanceKm => self.pickup_distance_km,
+            DriverAcceptanceFeature::TripDistanceKm => self.trip_distance_km,
+            DriverAcceptanceFeature::EarningsProgress => self.earnings_progress,
+            DriverAcceptanceFeature::FatigueRatio => self.fatigue_ratio,
+            DriverAcceptanceFeature::LocalHour => f64::from(self.local_hour),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_scores_features_and_rejects_unknown_names() {
+        let model = ConversionModel::from_json(
+            r#"{ "intercept": 0.0, "weights": { "quoted_fare": -0.1, "quote_index": 0.5 } }"#,
+        )
+        .expect("valid model");
+        let features = ConversionFeatures {
+            fare: 10.0,
+            quote_index: 2,
+            ..Default::default()
+        };
+        assert_eq!(model.accept_probability(&features), 0.5);
+        let pricier = ConversionFeatures {
+            fare: 20.0,
+            ..features
+        };
+        assert!(model.accept_probability(&pricier) < 0.5);
+
+        assert!(ConversionModel::from_json(r#"{ "intercept": 0.0 }"#).is_ok());
+        assert!(ConversionModel::from_json(
+            r#"{ "intercept": 0.0, "weights": { "rider_entity": 1.0 } }"#
+        )
+        .is_err());
+        // Feature names are per model
+        assert!(DriverAcceptanceModel::from_json(
+            r#"{ "intercept": 0.0, "weights": { "quoted_fare": 1.0 } }"#
+        )
+        .is_err());
+    }
+}
@@ -7,6 +7,8 @@ use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 
+use crate::clock::{day_of_week, hour_of_day, LocalTime};
+
 /// Trait for sampling inter-arrival times (in milliseconds).
 pub trait InterArrivalDistribution: Send + Sync + std::fmt::Debug {
     /// Sample the next inter-arrival time in milliseconds.
@@ -92,6 +94,8 @@ pub struct TimeOfDayDistribution {
     /// Epoch in milliseconds (real-world time corresponding to simulation time 0).
     /// Used to convert simulation time to real datetime for hour/day calculation.
     pub epoch_ms: i64,
+    /// City wall clock the hour/day are read in (UTC by default).
+    pub local_time: LocalTime,
     /// Seed for RNG (for reproducibility).
     pub seed: u64,
 }
@@ -103,10 +107,17 @@ impl TimeOfDayDistribution {
             base_rate_per_sec: base_rate_per_sec.max(0.0),
             multipliers: [[1.0; 24]; 7],
             epoch_ms,
+            local_time: LocalTime::UTC,
             seed,
         }
     }
 
+    /// Read hours and days in the given local time instead of UTC.
+    pub fn with_local_time(mut self, local_time: LocalTime) -> Self {
+        self.local_time = local_time;
+        self
+    }
+
     /// Set the multiplier for a specific day of week and hour.
     /// `day_of_week`: 0=Monday, 1=Tuesday, ..., 6=Sunday
     /// `hour`: 0-23
@@ -131,22 +142,12 @@ impl TimeOfDayDistribution {
 
     /// Get the current rate multiplier based on simulation time.
     fn get_rate_multiplier(&self, sim_time_ms: u64) -> f64 {
-        // Convert simulation time to real-world time
-        let real_ms = self.epoch_ms.saturating_add(sim_time_ms as i64);
-
-        // Convert to seconds since Unix epoch
-        let total_secs = real_ms / 1000;
-
-        // Calculate day of week (0=Monday, 6=Sunday)
-        // Unix epoch (1970-01-01) was a Thursday (day 3)
-        let days_since_epoch = total_secs / 86400;
-        let day_of_week = ((days_since_epoch + 3) % 7) as usize; // +3 because epoch was Thursday
-
-        // Calculate hour of day (0-23) in UTC
-        let secs_in_day = total_secs % 86400;
-        let hour = ((secs_in_day / 3600) % 24) as usize;
+        // Convert simulation time to local wall-clock time
+        let real_ms = self
+            .local_time
+            .to_local_ms(self.epoch_ms.saturating_add(sim_time_ms as i64));
 
-        self.multipliers[day_of_week][hour]
+        self.multipliers[day_of_week(real_ms)][hour_of_day(real_ms)]
     }
 }
 
@@ -2,7 +2,8 @@
 //!
 //! This module defines the core data structures used in the simulation:
 //!
-//! - **Components**: `Rider`, `Driver`, `Trip`, `Position`, `DriverEarnings`, `DriverFatigue`
+//! - **Components**: `Rider`, `Driver`, `Trip`, `Position`, `DriverEarnings`, `DriverFatigue`,
+//!   `DriverVehicle`
 //! - **State Markers**: rider/driver/trip marker components (e.g. `Browsing`, `Idle`, `TripEnRoute`)
 //!
 //! Components are attached to entities in the ECS world, and systems query/modify them
@@ -12,9 +13,12 @@ use bevy_ecs::prelude::{Component, Entity};
 use bevy_ecs::system::EntityCommands;
 use h3o::{CellIndex, LatLng};
 
-use crate::routing::RouteResult;
+use crate::clock::EventHandle;
+use crate::emissions::VehicleType;
+use crate::routing::{RoadClass, RouteResult};
 use crate::spatial::distance_km_between_lat_lng;
-use crate::telemetry::RiderAbandonmentReason;
+use crate::state_machine::StateTransitionCommands;
+use crate::telemetry::{DriverState, RiderAbandonmentReason};
 
 // Rider state markers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
@@ -41,12 +45,23 @@ pub struct Rider {
     pub requested_at: Option<u64>,
     /// Number of times this rider has rejected a quote (used for give-up after max_quote_rejections).
     pub quote_rejections: u32,
+    /// Number of quotes that expired before this rider decided. Tracked apart from
+    /// `quote_rejections` so expiry does not eat into the rejection budget; the rider gives up
+    /// once this also exceeds max_quote_rejections.
+    pub quote_expiries: u32,
     /// Fare the rider accepted when they transitioned to Waiting; used for driver earnings and trip completion.
     pub accepted_fare: Option<f64>,
     /// Last reason this rider rejected a quote (used to track abandonment reason when they give up).
     pub last_rejection_reason: Option<RiderAbandonmentReason>,
 }
 
+impl Rider {
+    /// Quotes this rider has already declined or let expire; seeds per-quote draws.
+    pub fn quote_attempts(&self) -> u32 {
+        self.quote_rejections + self.quote_expiries
+    }
+}
+
 /// Current quote shown to a rider (fare + ETA). Attached while rider is viewing a quote; used for UI/telemetry.
 #[derive(Debug, Clone, Copy, PartialEq, Component)]
 pub struct RiderQuote {
@@ -56,6 +71,41 @@ pub struct RiderQuote {
     pub eta_ms: u64,
 }
 
+/// Rider driven by an external client (see [`crate::sandbox`]): quotes wait for the client to
+/// accept or decline instead of the rider choice model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct ExternalRider;
+
+/// Simulation time (ms) after which the rider's current quote is no longer valid.
+/// Only present when `RiderQuoteConfig::quote_ttl_secs` is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct QuoteExpiresAt(pub u64);
+
+/// The rider's current quote is for a deferred-dispatch (wait-and-save) class: matching is held
+/// back for `delay_ms` after the quote is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct DeferredDispatch {
+    pub delay_ms: u64,
+}
+
+/// Vehicle class picked from the menu for the rider's current quote, and the class offered
+/// first (the menu default). Set on the rider by show_quote_system and copied onto the trip
+/// at match; `chosen != offered` is a substitution.
+#[derive(Debug, Clone, PartialEq, Eq, Component)]
+pub struct VehicleClassChoice {
+    pub chosen: String,
+    pub offered: String,
+}
+
+/// Simulation time (ms) before which a wait-and-save rider is not offered to the matcher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct DispatchNotBefore(pub u64);
+
+/// The waiting rider's pending `RiderCancel` event, scheduled when the quote is accepted.
+/// Cancelled at pickup and brought forward when the pickup ETA exceeds the rider's patience.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct PickupTimeout(pub EventHandle);
+
 // Driver state markers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
 pub struct Idle;
@@ -68,8 +118,9 @@ pub struct OnTrip;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
 pub struct OffDuty;
 
-/// Extension trait to transition a driver entity to a single state by clearing all driver state
-/// markers and inserting the target. Use this instead of manually removing Idle/Evaluating/EnRoute/OnTrip/OffDuty.
+/// Extension trait to transition a driver entity to a single state. Each call is a guarded
+/// [`StateTransitionCommands::transition_to`]: illegal transitions (e.g. OnTrip -> EnRoute) are
+/// rejected and recorded instead of applied.
 pub trait DriverStateCommands {
     fn set_driver_state_idle(&mut self) -> &mut Self;
     fn set_driver_state_evaluating(&mut self) -> &mut Self;
@@ -80,44 +131,19 @@ pub trait DriverStateCommands {
 
 impl<'a> DriverStateCommands for EntityCommands<'a> {
     fn set_driver_state_idle(&mut self) -> &mut Self {
-        self.remove::<Idle>()
-            .remove::<Evaluating>()
-            .remove::<EnRoute>()
-            .remove::<OnTrip>()
-            .remove::<OffDuty>()
-            .insert(Idle)
+        self.transition_to(DriverState::Idle)
     }
     fn set_driver_state_evaluating(&mut self) -> &mut Self {
-        self.remove::<Idle>()
-            .remove::<Evaluating>()
-            .remove::<EnRoute>()
-            .remove::<OnTrip>()
-            .remove::<OffDuty>()
-            .insert(Evaluating)
+        self.transition_to(DriverState::Evaluating)
     }
     fn set_driver_state_en_route(&mut self) -> &mut Self {
-        self.remove::<Idle>()
-            .remove::<Evaluating>()
-            .remove::<EnRoute>()
-            .remove::<OnTrip>()
-            .remove::<OffDuty>()
-            .insert(EnRoute)
+        self.transition_to(DriverState::EnRoute)
     }
     fn set_driver_state_on_trip(&mut self) -> &mut Self {
-        self.remove::<Idle>()
-            .remove::<Evaluating>()
-            .remove::<EnRoute>()
-            .remove::<OnTrip>()
-            .remove::<OffDuty>()
-            .insert(OnTrip)
+        self.transition_to(DriverState::OnTrip)
     }
     fn set_driver_state_off_duty(&mut self) -> &mut Self {
-        self.remove::<Idle>()
-            .remove::<Evaluating>()
-            .remove::<EnRoute>()
-            .remove::<OnTrip>()
-            .remove::<OffDuty>()
-            .insert(OffDuty)
+        self.transition_to(DriverState::OffDuty)
     }
 }
 
@@ -141,6 +167,25 @@ pub struct DriverEarnings {
     pub session_start_time_ms: u64,
     /// Simulation time when driver went OffDuty (`None` while still active).
     pub session_end_time_ms: Option<u64>,
+    /// Trips completed in the week identified by `week_index` (drives tiered commission).
+    pub weekly_trips: u32,
+    /// Simulation week (`now / ONE_WEEK_MS`) that `weekly_trips` counts towards.
+    pub week_index: u64,
+}
+
+impl DriverEarnings {
+    /// Record a completed trip at `now_ms`, resetting the weekly counter on week rollover.
+    /// Returns the number of trips completed earlier in the same week.
+    pub fn record_completed_trip(&mut self, now_ms: u64) -> u32 {
+        let week = now_ms / crate::clock::ONE_WEEK_MS;
+        if week != self.week_index {
+            self.week_index = week;
+            self.weekly_trips = 0;
+        }
+        let prior = self.weekly_trips;
+        self.weekly_trips = self.weekly_trips.saturating_add(1);
+        prior
+    }
 }
 
 /// Tracks driver fatigue thresholds.
@@ -150,6 +195,11 @@ pub struct DriverFatigue {
     pub fatigue_threshold_ms: u64,
 }
 
+/// Drivetrain of the driver's vehicle (sets the per-km CO2 factor). Assigned at spawn from the
+/// scenario's `VehicleMix`; drivers without it count as `VehicleType::Petrol`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct DriverVehicle(pub VehicleType);
+
 // Trip state markers
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
 pub struct TripEnRoute;
@@ -191,8 +241,16 @@ pub struct TripFinancials {
     pub agreed_fare: Option<f64>,
     /// Distance (km) from driver to pickup at match acceptance time.
     pub pickup_distance_km_at_accept: f64,
+    /// Distance (km) driven empty to the pickup, accumulated in movement_system.
+    pub deadhead_km: f64,
+    /// Distance (km) driven with the rider on board, accumulated in movement_system.
+    pub trip_km: f64,
 }
 
+/// The trip's next pending `MoveStep`; cancelled when the trip is cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct NextMoveStep(pub EventHandle);
+
 /// Trip live data: actively updated during en-route phase.
 #[derive(Debug, Clone, Copy, PartialEq, Component)]
 pub struct TripLiveData {
@@ -228,6 +286,17 @@ pub struct TripRoute {
     pub distance_traveled_km: f64,
     /// Total route distance in km (from provider or segment sum).
     pub total_distance_km: f64,
+    /// Road class per segment; empty when the route is not class-annotated.
+    pub segment_road_classes: Vec<RoadClass>,
+    /// Simulation time (ms) the route was resolved at.
+    pub routed_at_ms: u64,
+    /// Traffic factor at the driver's cell when the route was resolved (for rerouting).
+    pub traffic_factor_at_routing: f64,
+    /// `points` are road waypoints from the route provider rather than H3 cell centres (and
+    /// so were those of any route this one replaced).
+    pub follows_roads: bool,
+    /// Points driven on earlier routes of this leg before it was rerouted.
+    pub driven_before_reroute: Vec<LatLng>,
 }
 
 impl TripRoute {
@@ -253,6 +322,11 @@ impl TripRoute {
             next_segment_index: 0,
             distance_traveled_km: 0.0,
             total_distance_km: total,
+            segment_road_classes: Vec::new(),
+            routed_at_ms: 0,
+            traffic_factor_at_routing: 1.0,
+            follows_roads: false,
+            driven_before_reroute: Vec::new(),
         })
     }
 
@@ -262,17 +336,29 @@ impl TripRoute {
     }
 
     pub fn from_route_result(result: RouteResult) -> Option<Self> {
+        let waypoint_count = result.waypoints.len();
         let waypoints: Vec<LatLng> = result
             .waypoints
             .into_iter()
             .filter_map(|(lat, lng)| LatLng::new(lat, lng).ok())
             .collect();
-        let points = if waypoints.len() >= 2 {
+        // Classes are per waypoint segment, so they only apply when the waypoints are used
+        // as-is (all of them valid).
+        let classified = waypoints.len() >= 2
+            && waypoints.len() == waypoint_count
+            && result.road_classes.len() + 1 == waypoints.len();
+        let follows_roads = waypoints.len() >= 2;
+        let points = if follows_roads {
             waypoints
         } else {
             result.cells.into_iter().map(|cell| cell.into()).collect()
         };
-        Self::from_points(points, Some(result.distance_km))
+        let mut route = Self::from_points(points, Some(result.distance_km))?;
+        route.follows_roads = follows_roads;
+        if classified {
+            route.segment_road_classes = result.road_classes;
+        }
+        Some(route)
     }
 
     pub fn advance(&mut self) -> Option<(LatLng, f64)> {
@@ -286,7 +372,37 @@ impl TripRoute {
         Some((point, distance))
     }
 
+    /// Points driven so far on this leg, across reroutes: from the leg's start up to the end
+    /// of the segment most recently returned by [`Self::advance`].
+    pub fn driven_points(&self) -> Vec<LatLng> {
+        let driven = &self.points[..=self.next_segment_index.min(self.points.len() - 1)];
+        let mut points = self.driven_before_reroute.clone();
+        let rejoined = usize::from(points.last() == driven.first());
+        points.extend_from_slice(&driven[rejoined..]);
+        points
+    }
+
+    /// Carry the path driven on `previous` (which this route replaces) over to this route.
+    pub fn continue_from(mut self, previous: &TripRoute) -> Self {
+        self.driven_before_reroute = previous.driven_points();
+        self.follows_roads &= previous.follows_roads;
+        self
+    }
+
     pub fn remaining_distance_km(&self) -> f64 {
         (self.total_distance_km - self.distance_traveled_km).max(0.0)
     }
+
+    /// Record when, and under which traffic factor, the route was resolved.
+    pub fn stamp(mut self, routed_at_ms: u64, traffic_factor: f64) -> Self {
+        self.routed_at_ms = routed_at_ms;
+        self.traffic_factor_at_routing = traffic_factor;
+        self
+    }
+
+    /// Road class of the segment most recently returned by [`Self::advance`].
+    pub fn current_road_class(&self) -> Option<RoadClass> {
+        let index = self.next_segment_index.checked_sub(1)?;
+        self.segment_road_classes.get(index).copied()
+    }
 }
@@ -0,0 +1,127 @@
+//! Emissions accounting: per-km CO2 factors by vehicle type and the fleet mix drivers are
+//! drawn from.
+//!
+//! Every km a driver moves is attributed to a [`DrivingLeg`] (with a rider on board, empty
+//! to a pickup, or empty without a trip) and charged at the [`EmissionFactors`] rate of the
+//! driver's [`VehicleType`]. Totals accumulate in `SimTelemetry::emissions`.
+
+use bevy_ecs::prelude::Resource;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Drivetrain of a driver's vehicle. Drivers without a `DriverVehicle` component count as petrol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VehicleType {
+    #[default]
+    Petrol,
+    Diesel,
+    Hybrid,
+    Electric,
+}
+
+/// Why a driven km happened, for splitting emissions by purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrivingLeg {
+    /// Rider on board.
+    Occupied,
+    /// Empty, en route to a pickup.
+    Deadhead,
+}
+
+/// Grams of CO2 emitted per km driven, by vehicle type.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Serialize, Deserialize)]
+pub struct EmissionFactors {
+    pub petrol_g_per_km: f64,
+    pub diesel_g_per_km: f64,
+    pub hybrid_g_per_km: f64,
+    /// Electricity generation at grid-average intensity.
+    pub electric_g_per_km: f64,
+}
+
+impl Default for EmissionFactors {
+    /// Typical mid-size car figures (tank-to-wheel; grid-average for electric).
+    fn default() -> Self {
+        Self {
+            petrol_g_per_km: 170.0,
+            diesel_g_per_km: 150.0,
+            hybrid_g_per_km: 105.0,
+            electric_g_per_km: 45.0,
+        }
+    }
+}
+
+impl EmissionFactors {
+    pub fn grams_per_km(&self, vehicle: VehicleType) -> f64 {
+        match vehicle {
+            VehicleType::Petrol => self.petrol_g_per_km,
+            VehicleType::Diesel => self.diesel_g_per_km,
+            VehicleType::Hybrid => self.hybrid_g_per_km,
+            VehicleType::Electric => self.electric_g_per_km,
+        }
+    }
+}
+
+/// Share of each vehicle type in the driver fleet. Empty by default (no `DriverVehicle`
+/// component is assigned, so every driver counts as petrol).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VehicleMix {
+    /// `(vehicle type, relative weight)`; weights need not sum to 1.
+    pub shares: Vec<(VehicleType, f64)>,
+}
+
+impl VehicleMix {
+    pub fn with_share(mut self, vehicle: VehicleType, weight: f64) -> Self {
+        self.shares.push((vehicle, weight));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shares.iter().all(|(_, weight)| *weight <= 0.0)
+    }
+
+    /// Sample a vehicle type in proportion to the weights. Returns None for an empty mix.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<VehicleType> {
+        let total: f64 = self.shares.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let draw = rng.gen::<f64>() * total;
+        let mut cumulative = 0.0;
+        for (vehicle, weight) in &self.shares {
+            cumulative += weight.max(0.0);
+            if draw < cumulative {
+                return Some(*vehicle);
+            }
+        }
+        self.shares.last().map(|(vehicle, _)| *vehicle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn electric_emits_less_than_petrol_by_default() {
+        let factors = EmissionFactors::default();
+        assert!(
+            factors.grams_per_km(VehicleType::Electric) < factors.grams_per_km(VehicleType::Petrol)
+        );
+    }
+
+    #[test]
+    fn sample_follows_weights() {
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(VehicleMix::default().sample(&mut rng), None);
+
+        let mix = VehicleMix::default()
+            .with_share(VehicleType::Electric, 3.0)
+            .with_share(VehicleType::Diesel, 1.0);
+        let electric = (0..1000)
+            .filter(|_| mix.sample(&mut rng) == Some(VehicleType::Electric))
+            .count();
+        assert!((650..850).contains(&electric), "electric draws: {electric}");
+    }
+}
@@ -0,0 +1,221 @@
+//! Opt-in per-entity event tracing.
+//!
+//! Insert an [`EventTrace`] resource to record every event targeted at a chosen set of
+//! riders, drivers or trips (or a deterministic sample of all entities). Each traced entity
+//! gets an ordered timeline of the events that touched it, which answers questions like
+//! "why was this rider never matched?" without logging the whole run.
+//!
+//! An event is attributed to its subject entity; events on a trip are also attributed to the
+//! trip's rider and driver. Events without a subject (spawns, batch matching runs) are not
+//! traced. The runner records events as they are popped, before systems react to them.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy_ecs::prelude::{Entity, Resource, World};
+
+use crate::clock::{Event, EventKind, EventSubject};
+use crate::ecs::Trip;
+
+/// Default cap on events kept per entity; older events are dropped first.
+pub const DEFAULT_MAX_EVENTS_PER_ENTITY: usize = 256;
+
+/// One event in an entity's timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TracedEvent {
+    /// Simulation time in ms.
+    pub timestamp: u64,
+    pub kind: EventKind,
+    /// The event's subject: the entity itself, or the trip it belongs to.
+    pub subject: EventSubject,
+}
+
+/// Event timelines for watched or sampled entities.
+#[derive(Debug, Clone, Resource)]
+pub struct EventTrace {
+    watched: HashSet<Entity>,
+    /// Also trace every entity whose index is a multiple of this (1 = all entities).
+    sample_one_in: Option<u32>,
+    max_events_per_entity: usize,
+    timelines: HashMap<Entity, VecDeque<TracedEvent>>,
+}
+
+impl Default for EventTrace {
+    fn default() -> Self {
+        Self {
+            watched: HashSet::new(),
+            sample_one_in: None,
+            max_events_per_entity: DEFAULT_MAX_EVENTS_PER_ENTITY,
+            timelines: HashMap::new(),
+        }
+    }
+}
+
+impl EventTrace {
+    /// Trace only the given entities.
+    pub fn watching(entities: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            watched: entities.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Trace a deterministic sample: entities whose index is a multiple of `one_in`
+    /// (1 traces every entity).
+    pub fn sampled(one_in: u32) -> Self {
+        Self {
+            sample_one_in: Some(one_in.max(1)),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_max_events_per_entity(mut self, max_events: usize) -> Self {
+        self.max_events_per_entity = max_events.max(1);
+        self
+    }
+
+    /// Start tracing `entity`. Events before this call are not recorded.
+    pub fn watch(&mut self, entity: Entity) {
+        self.watched.insert(entity);
+    }
+
+    /// Stop watching `entity` and drop its timeline (sampled entities are still traced).
+    pub fn unwatch(&mut self, entity: Entity) {
+        self.watched.remove(&entity);
+        self.timelines.remove(&entity);
+    }
+
+    pub fn is_traced(&self, entity: Entity) -> bool {
+        self.watched.contains(&entity)
+            || self
+                .sample_one_in
+                .is_some_and(|one_in| entity.index().is_multiple_of(one_in))
+    }
+
+    /// Events recorded for `entity`, oldest first.
+    pub fn timeline(&self, entity: Entity) -> impl Iterator<Item = &TracedEvent> {
+        self.timelines.get(&entity).into_iter().flatten()
+    }
+
+    /// Entities with at least one recorded event.
+    pub fn traced_entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.timelines.keys().copied()
+    }
+
+    /// Drop all recorded timelines, keeping the selection.
+    pub fn clear(&mut self) {
+        self.timelines.clear();
+    }
+
+    /// Record `event` for each traced entity in `entities`.
+    pub fn record(&mut self, event: &Event, entities: &[Entity]) {
+        let Some(subject) = event.subject else {
+            return;
+        };
+        for &entity in entities {
+            if !self.is_traced(entity) {
+                continue;
+            }
+            let timeline = self.timelines.entry(entity).or_default();
+            if timeline.len() >= self.max_events_per_entity {
+                timeline.pop_front();
+            }
+            timeline.push_back(TracedEvent {
+                timestamp: event.timestamp,
+                kind: event.kind,
+                subject,
+            });
+        }
+    }
+}
+
+/// Entities an event is attributed to: its subject, plus the rider and driver of a trip.
+pub fn event_entities(world: &World, event: &Event) -> Vec<Entity> {
+    match event.subject {
+        Some(EventSubject::Rider(entity)) | Some(EventSubject::Driver(entity)) => vec![entity],
+        Some(EventSubject::Trip(trip_entity)) => {
+            let mut entities = vec![trip_entity];
+            if let Some(trip) = world.get::<Trip>(trip_entity) {
+                entities.extend([trip.rider, trip.driver]);
+            }
+            entities
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Record `event` in the world's [`EventTrace`], if one is present.
+pub(crate) fn record_event(world: &mut World, event: &Event) {
+    if !world.contains_resource::<EventTrace>() || event.subject.is_none() {
+        return;
+    }
+    let entities = event_entities(world, event);
+    if let Some(mut trace) = world.get_resource_mut::<EventTrace>() {
+        trace.record(event, &entities);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: u64, kind: EventKind, subject: EventSubject) -> Event {
+        Event {
+            timestamp,
+            kind,
+            subject: Some(subject),
+        }
+    }
+
+    #[test]
+    fn records_only_traced_entities_in_order() {
+        let watched = Entity::from_raw(3);
+        let other = Entity::from_raw(5);
+        let mut trace = EventTrace::watching([watched]);
+        trace.record(
+            &event(10, EventKind::ShowQuote, EventSubject::Rider(watched)),
+            &[watched],
+        );
+        trace.record(
+            &event(20, EventKind::ShowQuote, EventSubject::Rider(other)),
+            &[other],
+        );
+        trace.record(
+            &event(30, EventKind::TryMatch, EventSubject::Rider(watched)),
+            &[watched],
+        );
+
+        let kinds: Vec<(u64, EventKind)> = trace
+            .timeline(watched)
+            .map(|traced| (traced.timestamp, traced.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![(10, EventKind::ShowQuote), (30, EventKind::TryMatch)]
+        );
+        assert_eq!(trace.timeline(other).count(), 0);
+        assert_eq!(trace.traced_entities().collect::<Vec<_>>(), vec![watched]);
+    }
+
+    #[test]
+    fn sampling_and_cap_bound_the_trace() {
+        let mut trace = EventTrace::sampled(2).with_max_events_per_entity(2);
+        let even = Entity::from_raw(4);
+        assert!(trace.is_traced(even));
+        assert!(!trace.is_traced(Entity::from_raw(7)));
+
+        for timestamp in 0..3 {
+            trace.record(
+                &event(timestamp, EventKind::MoveStep, EventSubject::Driver(even)),
+                &[even],
+            );
+        }
+        let timestamps: Vec<u64> = trace
+            .timeline(even)
+            .map(|traced| traced.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![1, 2]);
+
+        trace.unwatch(even);
+        assert_eq!(trace.timeline(even).count(), 0);
+    }
+}
@@ -10,7 +10,7 @@
 //! - **ECS Framework**: Entity Component System for multi-agent state management
 //! - **Spatial Indexing**: H3-based geographic operations
 //! - **Matching Algorithms**: Pluggable driver-rider matching strategies
-//! - **Telemetry**: Snapshot capture and data export
+//! - **Telemetry**: Snapshot capture, data export, and optional live streaming
 //!
 //! ## Key Concepts
 //!
@@ -35,22 +35,34 @@
 //! ```
 
 pub mod clock;
+pub mod decision_models;
 pub mod distributions;
 pub mod ecs;
+pub mod emissions;
+pub mod event_trace;
+pub mod location;
 pub mod matching;
 pub mod patterns;
 pub mod pricing;
 pub mod profiling;
+pub mod provenance;
 pub mod routing;
+pub mod run_bundle;
 pub mod runner;
+pub mod sandbox;
 pub mod scenario;
+pub mod seeds;
+pub mod sim_log;
 pub mod spatial;
 pub mod spawner;
 pub mod speed;
+pub mod state_machine;
 pub mod systems;
 pub mod telemetry;
 pub mod telemetry_export;
+pub mod telemetry_stream;
 pub mod traffic;
+pub mod warm_start;
 
 #[cfg(any(test, feature = "test-helpers"))]
 pub mod test_helpers;
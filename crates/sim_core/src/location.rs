@@ -0,0 +1,160 @@
+//! Driver location reporting: discrete update cadence and GPS noise.
+//!
+//! Real driver apps report their position every few seconds, and each fix carries GPS error,
+//! so the dispatcher matches against stale, noisy locations. When [`LocationReportingConfig`]
+//! is present, `location_report_system` refreshes every driver's [`ReportedPosition`] once
+//! per update interval, and the matching systems use the reported cell instead of the true
+//! [`Position`](crate::ecs::Position). Without the resource matching sees true positions.
+
+use bevy_ecs::prelude::{Component, Entity, Resource};
+use h3o::{CellIndex, LatLng};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::spatial::distance_km_between_cells;
+use crate::telemetry::LocationErrorTotals;
+
+/// Seed stream name for GPS noise draws (see `SeedHierarchy::named_seed`).
+pub const LOCATION_NOISE_STREAM: &str = "location_noise";
+
+const METERS_PER_DEGREE_LAT: f64 = 111_320.0;
+
+/// How often drivers report their location and how noisy each fix is.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Resource)]
+pub struct LocationReportingConfig {
+    /// Seconds between location reports (every driver reports on the same ticks).
+    pub update_interval_secs: u64,
+    /// Standard deviation of the GPS error per axis, in meters (0 = exact fixes).
+    pub gps_noise_m: f64,
+}
+
+impl Default for LocationReportingConfig {
+    fn default() -> Self {
+        Self {
+            update_interval_secs: 4,
+            gps_noise_m: 0.0,
+        }
+    }
+}
+
+impl LocationReportingConfig {
+    /// Report interval in milliseconds (at least 1 s).
+    pub fn update_interval_ms(&self) -> u64 {
+        self.update_interval_secs.max(1) * 1000
+    }
+
+    /// Sample the cell a driver at `cell` reports on report tick `tick`.
+    ///
+    /// Draws are keyed by seed, driver and tick, so they do not depend on query order.
+    pub fn reported_cell(
+        &self,
+        cell: CellIndex,
+        seed: u64,
+        driver: Entity,
+        tick: u64,
+    ) -> CellIndex {
+        if self.gps_noise_m <= 0.0 {
+            return cell;
+        }
+        let rng_seed = seed
+            .wrapping_add(u64::from(driver.index()))
+            .wrapping_add(tick.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let mut rng = StdRng::seed_from_u64(rng_seed);
+        let (north_m, east_m) = gaussian_pair(&mut rng);
+        offset_cell(cell, north_m * self.gps_noise_m, east_m * self.gps_noise_m)
+    }
+}
+
+/// Last location a driver reported to the platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct ReportedPosition {
+    pub cell: CellIndex,
+    pub reported_at_ms: u64,
+}
+
+impl ReportedPosition {
+    /// Record this report's error against the driver's true cell when matched at `now_ms`.
+    pub fn record_match(
+        &self,
+        true_cell: CellIndex,
+        now_ms: u64,
+        totals: &mut LocationErrorTotals,
+    ) {
+        totals.record(
+            distance_km_between_cells(self.cell, true_cell),
+            now_ms.saturating_sub(self.reported_at_ms),
+        );
+    }
+}
+
+/// Two independent standard normal draws (Box-Muller).
+fn gaussian_pair(rng: &mut StdRng) -> (f64, f64) {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    let radius = (-2.0 * u1.ln()).sqrt();
+    let angle = std::f64::consts::TAU * u2;
+    (radius * angle.cos(), radius * angle.sin())
+}
+
+/// Move `cell`'s center by the given offsets in meters and return the cell containing the
+/// result, at the same resolution. Falls back to `cell` if the offset leaves valid coordinates.
+fn offset_cell(cell: CellIndex, north_m: f64, east_m: f64) -> CellIndex {
+    let center: LatLng = cell.into();
+    let lat = center.lat() + north_m / METERS_PER_DEGREE_LAT;
+    let lng =
+        center.lng() + east_m / (METERS_PER_DEGREE_LAT * center.lat().to_radians().cos().max(0.01));
+    LatLng::new(lat, lng)
+        .map(|coord| coord.to_cell(cell.resolution()))
+        .unwrap_or(cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use h3o::Resolution;
+
+    fn berlin_cell() -> CellIndex {
+        LatLng::new(52.52, 13.405)
+            .expect("valid coordinate")
+            .to_cell(Resolution::Nine)
+    }
+
+    #[test]
+    fn noiseless_reports_are_exact() {
+        let config = LocationReportingConfig::default();
+        let cell = berlin_cell();
+        assert_eq!(config.reported_cell(cell, 7, Entity::from_raw(3), 12), cell);
+    }
+
+    #[test]
+    fn noisy_reports_are_deterministic_and_scale_with_noise() {
+        let cell = berlin_cell();
+        let mean_error_km = |gps_noise_m: f64| {
+            let config = LocationReportingConfig {
+                update_interval_secs: 4,
+                gps_noise_m,
+            };
+            (0..200u64)
+                .map(|tick| {
+                    distance_km_between_cells(
+                        cell,
+                        config.reported_cell(cell, 7, Entity::from_raw(1), tick),
+                    )
+                })
+                .sum::<f64>()
+                / 200.0
+        };
+
+        let config = LocationReportingConfig {
+            update_interval_secs: 4,
+            gps_noise_m: 500.0,
+        };
+        assert_eq!(
+            config.reported_cell(cell, 7, Entity::from_raw(1), 5),
+            config.reported_cell(cell, 7, Entity::from_raw(1), 5)
+        );
+        assert!(mean_error_km(500.0) > mean_error_km(50.0));
+        assert!(mean_error_km(500.0) < 2.0);
+    }
+}
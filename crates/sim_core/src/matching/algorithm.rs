@@ -1,7 +1,8 @@
 use bevy_ecs::prelude::Entity;
 use h3o::CellIndex;
 
-use super::types::MatchResult;
+use super::cost_based::CostBasedMatching;
+use super::types::{MatchCandidate, MatchResult};
 
 /// Trait for matching algorithms that can find driver-rider pairings.
 ///
@@ -114,4 +115,30 @@ pub trait MatchingAlgorithm: Send + Sync {
             })
             .collect()
     }
+
+    /// Score every driver within `match_radius` of a rider, for matching diagnostics.
+    ///
+    /// Returns one `MatchCandidate` per driver in radius, in `available_drivers` order. Only
+    /// called when diagnostics are enabled, so it is kept separate from `find_match`.
+    ///
+    /// # Default Implementation
+    ///
+    /// Scores with `CostBasedMatching` at `DEFAULT_ETA_WEIGHT`, which gives algorithms without a
+    /// cost model (e.g. `SimpleMatching`) a comparable quality score for their choices.
+    /// Algorithms with their own scoring override this so the recorded scores are the ones
+    /// they rank by.
+    fn score_candidates(
+        &self,
+        rider_entity: Entity,
+        rider_pos: CellIndex,
+        available_drivers: &[(Entity, CellIndex)],
+        match_radius: u32,
+    ) -> Vec<MatchCandidate> {
+        CostBasedMatching::default().score_candidates(
+            rider_entity,
+            rider_pos,
+            available_drivers,
+            match_radius,
+        )
+    }
 }
@@ -1,9 +1,10 @@
 use bevy_ecs::prelude::Entity;
 use h3o::CellIndex;
 
-use crate::spatial::distance_km_between_cells;
+use crate::spatial::distances_km_from_cell;
 
 use super::algorithm::MatchingAlgorithm;
+use super::types::MatchCandidate;
 
 /// Average speed for ETA estimation (km/h).
 const AVG_SPEED_KMH: f64 = 40.0;
@@ -99,6 +100,22 @@ impl CostBasedMatching {
     }
 }
 
+/// Drivers within `match_radius` grid steps of `rider_pos`, in input order.
+fn drivers_in_radius(
+    rider_pos: CellIndex,
+    available_drivers: &[(Entity, CellIndex)],
+    match_radius: u32,
+) -> Vec<(Entity, CellIndex)> {
+    available_drivers
+        .iter()
+        .filter(|(_, driver_pos)| {
+            let grid_dist = rider_pos.grid_distance(*driver_pos).unwrap_or(i32::MAX);
+            grid_dist >= 0 && grid_dist <= match_radius as i32
+        })
+        .copied()
+        .collect()
+}
+
 impl Default for CostBasedMatching {
     fn default() -> Self {
         Self::new(DEFAULT_ETA_WEIGHT)
@@ -117,15 +134,12 @@ impl MatchingAlgorithm for CostBasedMatching {
     ) -> Option<Entity> {
         let mut best_match: Option<(Entity, f64)> = None;
 
-        for (driver_entity, driver_pos) in available_drivers {
-            let grid_dist = rider_pos.grid_distance(*driver_pos).unwrap_or(i32::MAX);
-
-            // Filter by match radius
-            if grid_dist < 0 || grid_dist > match_radius as i32 {
-                continue;
-            }
+        // Filter by match radius, then compute all pickup distances in one batch
+        let in_radius = drivers_in_radius(rider_pos, available_drivers, match_radius);
+        let driver_cells: Vec<CellIndex> = in_radius.iter().map(|(_, cell)| *cell).collect();
+        let distances = distances_km_from_cell(rider_pos, &driver_cells);
 
-            let pickup_distance_km = distance_km_between_cells(rider_pos, *driver_pos);
+        for ((driver_entity, _), pickup_distance_km) in in_radius.iter().zip(distances) {
             let pickup_eta_ms = self.estimate_pickup_eta_ms(pickup_distance_km);
             let score = self.score_pairing(pickup_distance_km, pickup_eta_ms);
 
@@ -140,11 +154,38 @@ impl MatchingAlgorithm for CostBasedMatching {
 
         best_match.map(|(driver_entity, _)| driver_entity)
     }
+
+    fn score_candidates(
+        &self,
+        rider_entity: Entity,
+        rider_pos: CellIndex,
+        available_drivers: &[(Entity, CellIndex)],
+        match_radius: u32,
+    ) -> Vec<MatchCandidate> {
+        let in_radius = drivers_in_radius(rider_pos, available_drivers, match_radius);
+        let driver_cells: Vec<CellIndex> = in_radius.iter().map(|(_, cell)| *cell).collect();
+        let distances = distances_km_from_cell(rider_pos, &driver_cells);
+        in_radius
+            .iter()
+            .zip(distances)
+            .map(|((driver_entity, _), pickup_distance_km)| {
+                let pickup_eta_ms = self.estimate_pickup_eta_ms(pickup_distance_km);
+                MatchCandidate {
+                    rider_entity,
+                    driver_entity: *driver_entity,
+                    pickup_distance_km,
+                    pickup_eta_ms,
+                    score: self.score_pairing(pickup_distance_km, pickup_eta_ms),
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::spatial::distance_km_between_cells;
 
     #[test]
     fn selects_closer_driver() {
@@ -200,4 +241,34 @@ mod tests {
             far_dist_km
         );
     }
+
+    #[test]
+    fn scores_only_candidates_within_radius() {
+        let matcher = CostBasedMatching::new(DEFAULT_ETA_WEIGHT);
+        let cell = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+        let far = cell
+            .grid_disk::<Vec<_>>(3)
+            .into_iter()
+            .find(|c| cell.grid_distance(*c).unwrap_or(i32::MAX) == 3)
+            .expect("distant cell");
+
+        let rider_entity = bevy_ecs::prelude::Entity::from_raw(1);
+        let drivers = vec![
+            (bevy_ecs::prelude::Entity::from_raw(2), far),
+            (bevy_ecs::prelude::Entity::from_raw(3), cell),
+        ];
+        let candidates = matcher.score_candidates(rider_entity, cell, &drivers, 1);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            candidates[0].driver_entity,
+            bevy_ecs::prelude::Entity::from_raw(3)
+        );
+        assert_eq!(candidates[0].rider_entity, rider_entity);
+        assert_eq!(candidates[0].pickup_eta_ms, 1000);
+
+        let all = matcher.score_candidates(rider_entity, cell, &drivers, 5);
+        assert_eq!(all.len(), 2);
+        assert!(all[1].score > all[0].score);
+    }
 }
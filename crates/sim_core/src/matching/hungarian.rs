@@ -2,15 +2,27 @@
 //!
 //! Uses the same scoring as CostBasedMatching (distance + ETA) but optimizes
 //! globally across all rider-driver pairs in a batch to minimize total cost.
+//!
+//! Only pairs within match radius enter the (sparse) cost matrix (found through a driver-by-cell
+//! index when the radius disk is smaller than the driver list), and the batch is split into
+//! connected components of that rider-driver graph (independent spatial clusters), each solved
+//! on its own dense sub-matrix. Riders are scored and components solved in parallel on the
+//! rayon thread pool; results do not depend on the thread count.
+//! Components whose smaller side exceeds `max_exact_size` are assigned greedily instead, so
+//! batches with thousands of concurrent riders stay tractable.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 
 use bevy_ecs::prelude::Entity;
 use h3o::CellIndex;
 use pathfinding::kuhn_munkres::{kuhn_munkres, Weights};
+use rayon::prelude::*;
 
-use crate::spatial::distance_km_between_cells;
+use crate::spatial::distances_km_from_cell;
 
 use super::algorithm::MatchingAlgorithm;
-use super::types::MatchResult;
+use super::types::{MatchCandidate, MatchResult};
 use super::CostBasedMatching;
 
 /// Average speed for ETA estimation (km/h), same as CostBasedMatching.
@@ -19,6 +31,9 @@ const AVG_SPEED_KMH: f64 = 40.0;
 /// Scale factor to convert f64 score to i64 for the assignment algorithm.
 const SCALE: f64 = 1_000_000.0;
 
+/// Default largest component (riders or drivers, whichever side is smaller) solved exactly.
+pub const DEFAULT_MAX_EXACT_SIZE: usize = 500;
+
 /// Weight for pairs outside match radius (never selected).
 /// Must be worse than any feasible score but not so extreme that negating and summing
 /// (e.g. in pathfinding's internal use of neg()) overflows i64.
@@ -59,6 +74,7 @@ impl Weights<i64> for I64Weights {
 #[derive(Debug)]
 pub struct HungarianMatching {
     eta_weight: f64,
+    max_exact_size: usize,
     fallback: CostBasedMatching,
 }
 
@@ -67,10 +83,18 @@ impl HungarianMatching {
     pub fn new(eta_weight: f64) -> Self {
         Self {
             eta_weight,
+            max_exact_size: DEFAULT_MAX_EXACT_SIZE,
             fallback: CostBasedMatching::new(eta_weight),
         }
     }
 
+    /// Solve components whose smaller side has more than `max_exact_size` agents greedily
+    /// instead of with the O(n³) assignment.
+    pub fn with_max_exact_size(mut self, max_exact_size: usize) -> Self {
+        self.max_exact_size = max_exact_size;
+        self
+    }
+
     fn estimate_pickup_eta_ms(&self, distance_km: f64) -> u64 {
         if distance_km <= 0.0 {
             return 1000;
@@ -109,19 +133,20 @@ impl HungarianMatching {
         for (rider_entity, rider_pos, _) in riders {
             let mut best_driver: Option<(Entity, f64)> = None;
 
-            for (driver_entity, driver_pos) in available_drivers {
-                if used_drivers.contains(driver_entity) {
-                    continue;
-                }
-
-                // Check grid distance first (cheap)
-                let grid_dist = rider_pos.grid_distance(*driver_pos).unwrap_or(i32::MAX);
-                if grid_dist < 0 || grid_dist > match_radius as i32 {
-                    continue;
-                }
-
+            // Check grid distance first (cheap), then batch the remaining distances
+            let candidates: Vec<(Entity, CellIndex)> = available_drivers
+                .iter()
+                .filter(|(driver_entity, driver_pos)| {
+                    !used_drivers.contains(driver_entity)
+                        && within_radius(*rider_pos, *driver_pos, match_radius)
+                })
+                .copied()
+                .collect();
+            let driver_cells: Vec<CellIndex> = candidates.iter().map(|(_, cell)| *cell).collect();
+            let distances = distances_km_from_cell(*rider_pos, &driver_cells);
+
+            for ((driver_entity, _), distance_km) in candidates.iter().zip(distances) {
                 // Calculate score
-                let distance_km = distance_km_between_cells(*rider_pos, *driver_pos);
                 let eta_ms = self.estimate_pickup_eta_ms(distance_km);
                 let score = self.score_pairing(distance_km, eta_ms);
 
@@ -143,6 +168,12 @@ impl HungarianMatching {
     }
 }
 
+/// Whether `driver_pos` is within `match_radius` grid steps of `rider_pos`.
+fn within_radius(rider_pos: CellIndex, driver_pos: CellIndex, match_radius: u32) -> bool {
+    let grid_dist = rider_pos.grid_distance(driver_pos).unwrap_or(i32::MAX);
+    grid_dist >= 0 && grid_dist <= match_radius as i32
+}
+
 impl Default for HungarianMatching {
     fn default() -> Self {
         Self::new(super::DEFAULT_ETA_WEIGHT)
@@ -169,6 +200,17 @@ impl MatchingAlgorithm for HungarianMatching {
         )
     }
 
+    fn score_candidates(
+        &self,
+        rider_entity: Entity,
+        rider_pos: CellIndex,
+        available_drivers: &[(Entity, CellIndex)],
+        match_radius: u32,
+    ) -> Vec<MatchCandidate> {
+        self.fallback
+            .score_candidates(rider_entity, rider_pos, available_drivers, match_radius)
+    }
+
     fn find_batch_matches(
         &self,
         riders: &[(Entity, CellIndex, Option<CellIndex>)],
@@ -186,87 +228,317 @@ impl MatchingAlgorithm for HungarianMatching {
             return self.greedy_batch_matches(riders, available_drivers, match_radius);
         }
 
-        // Kuhn-Munkres requires rows <= columns. So we use the smaller set as rows.
-        let (rider_idx_to_entity, _driver_idx_to_entity) =
-            if riders.len() <= available_drivers.len() {
-                (true, false)
-            } else {
-                (false, true)
-            };
+        // Sparse cost matrix: only pairs within match radius become edges. When the radius
+        // disk is smaller than the driver list, candidates come from a driver-by-cell index
+        // instead of a scan over every driver.
+        let disk_cells = 3 * match_radius as usize * (match_radius as usize + 1) + 1;
+        let drivers_by_cell = (disk_cells < available_drivers.len()).then(|| {
+            let mut by_cell: HashMap<CellIndex, Vec<usize>> = HashMap::new();
+            for (driver_idx, (_, driver_pos)) in available_drivers.iter().enumerate() {
+                by_cell.entry(*driver_pos).or_default().push(driver_idx);
+            }
+            by_cell
+        });
+        // Riders are scored in parallel; collecting keeps rider order
+        let rider_edges: Vec<Vec<Edge>> = riders
+            .par_iter()
+            .enumerate()
+            .map(|(rider_idx, (_, rider_pos, _))| {
+                let feasible: Vec<(usize, CellIndex)> = match &drivers_by_cell {
+                    Some(by_cell) => rider_pos
+                        .grid_disk::<Vec<_>>(match_radius)
+                        .iter()
+                        .filter_map(|cell| by_cell.get(cell).map(|drivers| (cell, drivers)))
+                        .flat_map(|(cell, drivers)| drivers.iter().map(|&idx| (idx, *cell)))
+                        .collect(),
+                    None => available_drivers
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (_, driver_pos))| {
+                            within_radius(*rider_pos, *driver_pos, match_radius)
+                        })
+                        .map(|(driver_idx, (_, driver_pos))| (driver_idx, *driver_pos))
+                        .collect(),
+                };
+                let driver_cells: Vec<CellIndex> = feasible.iter().map(|(_, cell)| *cell).collect();
+                let distances = distances_km_from_cell(*rider_pos, &driver_cells);
+
+                feasible
+                    .iter()
+                    .zip(distances)
+                    .map(|((driver_idx, _), distance_km)| {
+                        let eta_ms = self.estimate_pickup_eta_ms(distance_km);
+                        let score = self.score_pairing(distance_km, eta_ms);
+                        Edge {
+                            rider: rider_idx,
+                            driver: *driver_idx,
+                            weight: Self::score_to_weight(score),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let edges: Vec<Edge> = rider_edges.into_iter().flatten().collect();
+
+        // Riders and drivers with no edge between them never compete, so each connected
+        // component is an independent assignment problem, solved in parallel. The final sort
+        // makes the result independent of which component finishes first.
+        let components = connected_components(&edges, riders.len(), available_drivers.len());
+        let mut pairs: Vec<(usize, usize)> = components
+            .into_par_iter()
+            .flat_map_iter(|component| {
+                let (rider_count, driver_count) = component_sizes(&component);
+                if rider_count.min(driver_count) <= self.max_exact_size {
+                    assign_exact(&component)
+                } else {
+                    assign_greedy(component)
+                }
+            })
+            .collect();
 
-        let (rows, cols) = if rider_idx_to_entity {
-            (riders.len(), available_drivers.len())
+        // Same order as a single assignment over the full matrix: by index of the smaller side
+        if riders.len() <= available_drivers.len() {
+            pairs.sort_unstable_by_key(|&(rider, _)| rider);
         } else {
-            (available_drivers.len(), riders.len())
-        };
+            pairs.sort_unstable_by_key(|&(_, driver)| driver);
+        }
+        pairs
+            .into_iter()
+            .map(|(rider_idx, driver_idx)| MatchResult {
+                rider_entity: riders[rider_idx].0,
+                driver_entity: available_drivers[driver_idx].0,
+            })
+            .collect()
+    }
+}
 
-        // Pre-filter feasible pairs by grid_distance before expensive distance calculations
-        // This avoids computing distance_km_between_cells for pairs outside match radius
-        let mut feasible_pairs = Vec::new();
-
-        if rider_idx_to_entity {
-            for (i, (_, rider_pos, _)) in riders.iter().enumerate() {
-                for (j, (_, driver_pos)) in available_drivers.iter().enumerate() {
-                    let grid_dist = rider_pos.grid_distance(*driver_pos).unwrap_or(i32::MAX);
-                    if grid_dist >= 0 && grid_dist <= match_radius as i32 {
-                        feasible_pairs.push((i, j, *rider_pos, *driver_pos));
-                    }
-                }
-            }
+/// One feasible rider-driver pair of a batch, by index, with its assignment weight.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    rider: usize,
+    driver: usize,
+    weight: i64,
+}
+
+/// Split `edges` into connected components of the rider-driver graph, in order of each
+/// component's first edge.
+fn connected_components(edges: &[Edge], riders: usize, drivers: usize) -> Vec<Vec<Edge>> {
+    // Union-find over riders `0..riders` followed by drivers
+    let mut parent: Vec<usize> = (0..riders + drivers).collect();
+    fn root(parent: &mut [usize], mut node: usize) -> usize {
+        while parent[node] != node {
+            parent[node] = parent[parent[node]];
+            node = parent[node];
+        }
+        node
+    }
+    for edge in edges {
+        let (a, b) = (
+            root(&mut parent, edge.rider),
+            root(&mut parent, riders + edge.driver),
+        );
+        parent[a.max(b)] = a.min(b);
+    }
+
+    let mut component_of_root = HashMap::new();
+    let mut components: Vec<Vec<Edge>> = Vec::new();
+    for edge in edges {
+        let component = *component_of_root
+            .entry(root(&mut parent, edge.rider))
+            .or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+        components[component].push(*edge);
+    }
+    components
+}
+
+/// Distinct riders and drivers in a component.
+fn component_sizes(edges: &[Edge]) -> (usize, usize) {
+    let riders: HashSet<usize> = edges.iter().map(|edge| edge.rider).collect();
+    let drivers: HashSet<usize> = edges.iter().map(|edge| edge.driver).collect();
+    (riders.len(), drivers.len())
+}
+
+/// Optimal assignment of one component via Kuhn-Munkres on its dense sub-matrix.
+fn assign_exact(edges: &[Edge]) -> Vec<(usize, usize)> {
+    let mut riders: Vec<usize> = edges.iter().map(|edge| edge.rider).collect();
+    let mut drivers: Vec<usize> = edges.iter().map(|edge| edge.driver).collect();
+    riders.sort_unstable();
+    riders.dedup();
+    drivers.sort_unstable();
+    drivers.dedup();
+
+    // Kuhn-Munkres requires rows <= columns. So we use the smaller set as rows.
+    let rider_rows = riders.len() <= drivers.len();
+    let (rows, cols) = if rider_rows {
+        (riders.len(), drivers.len())
+    } else {
+        (drivers.len(), riders.len())
+    };
+
+    // Pairs outside match radius remain INFEASIBLE
+    let mut matrix = vec![vec![INFEASIBLE; cols]; rows];
+    for edge in edges {
+        let rider = riders
+            .binary_search(&edge.rider)
+            .expect("rider in component");
+        let driver = drivers
+            .binary_search(&edge.driver)
+            .expect("driver in component");
+        let (i, j) = if rider_rows {
+            (rider, driver)
         } else {
-            for (j, (_, rider_pos, _)) in riders.iter().enumerate() {
-                for (i, (_, driver_pos)) in available_drivers.iter().enumerate() {
-                    let grid_dist = rider_pos.grid_distance(*driver_pos).unwrap_or(i32::MAX);
-                    if grid_dist >= 0 && grid_dist <= match_radius as i32 {
-                        feasible_pairs.push((i, j, *rider_pos, *driver_pos));
-                    }
-                }
+            (driver, rider)
+        };
+        matrix[i][j] = edge.weight;
+    }
+
+    let weights = I64Weights(matrix);
+    let (_total, assignments) = kuhn_munkres(&weights);
+    assignments
+        .iter()
+        .enumerate()
+        .filter(|&(row, &col)| weights.at(row, col) > INFEASIBLE)
+        .map(|(row, &col)| {
+            if rider_rows {
+                (riders[row], drivers[col])
+            } else {
+                (riders[col], drivers[row])
             }
+        })
+        .collect()
+}
+
+/// Greedy assignment for components too large to solve exactly: repeatedly take the
+/// highest-weight pair whose rider and driver are both still free.
+fn assign_greedy(mut edges: Vec<Edge>) -> Vec<(usize, usize)> {
+    edges.sort_unstable_by_key(|edge| (Reverse(edge.weight), edge.rider, edge.driver));
+    let mut used_riders = HashSet::new();
+    let mut used_drivers = HashSet::new();
+    let mut pairs = Vec::new();
+    for edge in edges {
+        if used_riders.contains(&edge.rider) || used_drivers.contains(&edge.driver) {
+            continue;
         }
+        used_riders.insert(edge.rider);
+        used_drivers.insert(edge.driver);
+        pairs.push((edge.rider, edge.driver));
+    }
+    pairs
+}
 
-        // Build matrix only for feasible pairs (others remain INFEASIBLE)
-        let mut matrix = vec![vec![INFEASIBLE; cols]; rows];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::test_cell;
 
-        for (i, j, rider_pos, driver_pos) in feasible_pairs {
-            let distance_km = distance_km_between_cells(rider_pos, driver_pos);
-            let eta_ms = self.estimate_pickup_eta_ms(distance_km);
-            let score = self.score_pairing(distance_km, eta_ms);
-            matrix[i][j] = Self::score_to_weight(score);
+    fn edge(rider: usize, driver: usize, weight: i64) -> Edge {
+        Edge {
+            rider,
+            driver,
+            weight,
         }
+    }
 
-        let weights = I64Weights(matrix);
+    #[test]
+    fn exact_assignment_beats_greedy_on_crossed_pairs() {
+        // Greedy takes the single best pair (0, 0) and is left with the very poor (1, 1)
+        let edges = vec![
+            edge(0, 0, -1),
+            edge(0, 1, -2),
+            edge(1, 0, -2),
+            edge(1, 1, -100),
+        ];
+        let mut exact = assign_exact(&edges);
+        exact.sort_unstable();
+        assert_eq!(exact, vec![(0, 1), (1, 0)]);
+
+        let mut greedy = assign_greedy(edges);
+        greedy.sort_unstable();
+        assert_eq!(greedy, vec![(0, 0), (1, 1)]);
+    }
 
-        // Only run if at least one feasible pair exists (avoid panic on all INFEASIBLE)
-        let has_feasible = (0..weights.rows())
-            .any(|r| (0..weights.columns()).any(|c| weights.at(r, c) > INFEASIBLE));
-        if !has_feasible {
-            return Vec::new();
-        }
+    #[test]
+    fn batch_splits_into_independent_components() {
+        let edges = vec![
+            edge(0, 0, -1),
+            edge(2, 1, -1),
+            edge(1, 0, -1),
+            edge(3, 2, -1),
+        ];
+        let components = connected_components(&edges, 4, 3);
+        let riders: Vec<Vec<usize>> = components
+            .iter()
+            .map(|component| component.iter().map(|edge| edge.rider).collect())
+            .collect();
+        assert_eq!(riders, vec![vec![0, 1], vec![2], vec![3]]);
+    }
 
-        let (_total, assignments) = kuhn_munkres(&weights);
+    #[test]
+    fn distant_clusters_match_within_themselves_exactly_or_greedily() {
+        let cluster_a = test_cell();
+        let cluster_b = cluster_a
+            .grid_ring_fast(30)
+            .flatten()
+            .next()
+            .expect("cell 30 steps away");
+        let cells: Vec<CellIndex> = [cluster_a, cluster_b]
+            .iter()
+            .flat_map(|center| center.grid_disk::<Vec<_>>(1).into_iter().take(6))
+            .collect();
+        // One driver on every rider's cell; more than the small-batch greedy shortcut handles
+        let riders: Vec<_> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (Entity::from_raw(i as u32), *cell, None))
+            .collect();
+        let drivers: Vec<_> = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (Entity::from_raw(100 + i as u32), *cell))
+            .collect();
+
+        for matcher in [
+            HungarianMatching::default(),
+            HungarianMatching::default().with_max_exact_size(0),
+        ] {
+            let matches = matcher.find_batch_matches(&riders, &drivers, 3, 0);
+            assert_eq!(matches.len(), riders.len());
+            for (result, rider) in matches.iter().zip(&riders) {
+                assert_eq!(result.rider_entity, rider.0);
+                assert_eq!(result.driver_entity.index(), 100 + rider.0.index());
+            }
+        }
+    }
 
-        let mut results = Vec::new();
-        if rider_idx_to_entity {
-            for (rider_idx, &driver_idx) in assignments.iter().enumerate() {
-                if driver_idx < available_drivers.len()
-                    && weights.at(rider_idx, driver_idx) > INFEASIBLE
-                {
-                    results.push(MatchResult {
-                        rider_entity: riders[rider_idx].0,
-                        driver_entity: available_drivers[driver_idx].0,
-                    });
-                }
+    #[test]
+    fn clustered_batch_matches_identically_on_any_thread_count() {
+        // Eight clusters 20 steps apart, each with more riders than drivers
+        let mut riders = Vec::new();
+        let mut drivers = Vec::new();
+        let centers = test_cell().grid_ring_fast(20).flatten().step_by(15).take(8);
+        for center in centers {
+            let cells = center.grid_disk::<Vec<_>>(2);
+            for cell in cells.iter().take(12) {
+                riders.push((Entity::from_raw(riders.len() as u32), *cell, None));
             }
-        } else {
-            for (driver_idx, &rider_idx) in assignments.iter().enumerate() {
-                if rider_idx < riders.len() && weights.at(driver_idx, rider_idx) > INFEASIBLE {
-                    results.push(MatchResult {
-                        rider_entity: riders[rider_idx].0,
-                        driver_entity: available_drivers[driver_idx].0,
-                    });
-                }
+            for cell in cells.iter().rev().take(8) {
+                drivers.push((Entity::from_raw(1000 + drivers.len() as u32), *cell));
             }
         }
-        results
+
+        let matcher = HungarianMatching::default();
+        let run = |threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("thread pool")
+                .install(|| matcher.find_batch_matches(&riders, &drivers, 2, 0))
+        };
+        let single = run(1);
+        assert_eq!(single.len(), drivers.len());
+        assert_eq!(run(4), single);
     }
 }
@@ -16,7 +16,9 @@
 //! ## Usage
 //!
 //! Algorithms are stored as a `MatchingAlgorithmResource` in the ECS world and can
-//! be swapped dynamically during simulation execution.
+//! be swapped dynamically during simulation execution. An optional
+//! `ShadowMatchingAlgorithm` is evaluated on the same inputs at every dispatch decision
+//! without being applied, so two policies can be compared on one demand realization.
 
 pub mod algorithm;
 pub mod cost_based;
@@ -49,3 +51,8 @@ impl std::ops::Deref for MatchingAlgorithmResource {
         self.0.as_ref()
     }
 }
+
+/// Alternative algorithm run alongside the live one at every dispatch decision. Its choices are
+/// logged in `ShadowMatchLog` and never applied.
+#[derive(Resource)]
+pub struct ShadowMatchingAlgorithm(pub Box<dyn MatchingAlgorithm>);
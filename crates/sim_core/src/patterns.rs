@@ -2,6 +2,16 @@
 //!
 //! This module defines realistic demand and supply patterns that vary by hour of day
 //! and day of week. Patterns are used to configure TimeOfDayDistribution instances.
+//! [`WeeklyPattern`] holds a full week of hourly multipliers so scenarios can supply their own
+//! weekday/weekend (or per-day) profiles for multi-day runs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{day_of_week, hour_of_day, LocalTime, ONE_HOUR_MS};
+use crate::distributions::TimeOfDayDistribution;
+
+/// Days treated as weekend by the default patterns (0=Monday: Friday and Saturday).
+pub const DEFAULT_WEEKEND_DAYS: [usize; 2] = [4, 5];
 
 /// Default weekday pattern for rider demand (Monday-Thursday, Sunday).
 /// Multipliers for each hour (0-23) representing demand relative to base rate.
@@ -120,36 +130,135 @@ pub const DRIVER_WEEKEND_PATTERN: [f64; 24] = [
     1.2, // 11 PM - 12 AM
 ];
 
-/// Apply rider demand patterns to a TimeOfDayDistribution.
-/// Uses weekday pattern for Monday-Thursday and Sunday, weekend pattern for Friday-Saturday.
-pub fn apply_rider_patterns(
-    mut dist: crate::distributions::TimeOfDayDistribution,
-) -> crate::distributions::TimeOfDayDistribution {
-    // Apply weekday pattern to Monday (0), Tuesday (1), Wednesday (2), Thursday (3), Sunday (6)
-    for &day in &[0, 1, 2, 3, 6] {
-        dist = dist.set_day_multipliers(day, RIDER_WEEKDAY_PATTERN);
+/// Hourly multipliers for a full week, indexed `[day_of_week][hour]` (0=Monday, hour in UTC).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeeklyPattern {
+    pub days: [[f64; 24]; 7],
+}
+
+impl WeeklyPattern {
+    /// Same 24-hour profile every day.
+    pub fn daily(hours: [f64; 24]) -> Self {
+        Self { days: [hours; 7] }
+    }
+
+    /// `weekday` profile on every day except `weekend_days`, which use `weekend`.
+    pub fn weekday_weekend(weekday: [f64; 24], weekend: [f64; 24], weekend_days: &[usize]) -> Self {
+        let mut pattern = Self::daily(weekday);
+        for &day in weekend_days {
+            pattern = pattern.with_day(day, weekend);
+        }
+        pattern
+    }
+
+    /// Default rider demand: weekday profile, Friday/Saturday nightlife profile.
+    pub fn rider_default() -> Self {
+        Self::weekday_weekend(
+            RIDER_WEEKDAY_PATTERN,
+            RIDER_WEEKEND_PATTERN,
+            &DEFAULT_WEEKEND_DAYS,
+        )
+    }
+
+    /// Default driver supply: weekday profile, Friday/Saturday evening profile.
+    pub fn driver_default() -> Self {
+        Self::weekday_weekend(
+            DRIVER_WEEKDAY_PATTERN,
+            DRIVER_WEEKEND_PATTERN,
+            &DEFAULT_WEEKEND_DAYS,
+        )
+    }
+
+    /// Replace one day's hourly profile (`day`: 0=Monday ... 6=Sunday).
+    pub fn with_day(mut self, day: usize, hours: [f64; 24]) -> Self {
+        if day < 7 {
+            self.days[day] = hours.map(|m| m.max(0.0));
+        }
+        self
     }
 
-    // Apply weekend pattern to Friday (4) and Saturday (5)
-    dist = dist.set_day_multipliers(4, RIDER_WEEKEND_PATTERN);
-    dist = dist.set_day_multipliers(5, RIDER_WEEKEND_PATTERN);
+    /// Scale one day's volume (e.g. 1.3 for a busier Saturday).
+    pub fn with_day_scale(mut self, day: usize, factor: f64) -> Self {
+        if day < 7 {
+            self.days[day] = self.days[day].map(|m| (m * factor).max(0.0));
+        }
+        self
+    }
 
-    dist
+    /// Multiplier at a real-world timestamp (UTC).
+    pub fn multiplier_at(&self, real_ms: i64) -> f64 {
+        self.days[day_of_week(real_ms)][hour_of_day(real_ms)]
+    }
+
+    /// Mean multiplier over the simulation window `[0, window_ms)` starting at `epoch_ms`
+    /// (hourly resolution, hours read in `local_time`). Used to normalize the base rate so the
+    /// expected spawn count over the window matches the configured total, whatever days the
+    /// window covers.
+    pub fn average_multiplier_over(
+        &self,
+        epoch_ms: i64,
+        window_ms: u64,
+        local_time: LocalTime,
+    ) -> f64 {
+        let hours = window_ms.div_ceil(ONE_HOUR_MS).max(1);
+        let total: f64 = (0..hours)
+            .map(|h| {
+                let real_ms = epoch_ms.saturating_add((h * ONE_HOUR_MS) as i64);
+                self.multiplier_at(local_time.to_local_ms(real_ms))
+            })
+            .sum();
+        total / hours as f64
+    }
+
+    /// Apply every day's profile to a distribution.
+    pub fn apply(&self, mut dist: TimeOfDayDistribution) -> TimeOfDayDistribution {
+        for (day, hours) in self.days.iter().enumerate() {
+            dist = dist.set_day_multipliers(day, *hours);
+        }
+        dist
+    }
+}
+
+/// Apply rider demand patterns to a TimeOfDayDistribution.
+/// Uses weekday pattern for Monday-Thursday and Sunday, weekend pattern for Friday-Saturday.
+pub fn apply_rider_patterns(dist: TimeOfDayDistribution) -> TimeOfDayDistribution {
+    WeeklyPattern::rider_default().apply(dist)
 }
 
 /// Apply driver supply patterns to a TimeOfDayDistribution.
 /// Uses weekday pattern for Monday-Thursday and Sunday, weekend pattern for Friday-Saturday.
-pub fn apply_driver_patterns(
-    mut dist: crate::distributions::TimeOfDayDistribution,
-) -> crate::distributions::TimeOfDayDistribution {
-    // Apply weekday pattern to Monday (0), Tuesday (1), Wednesday (2), Thursday (3), Sunday (6)
-    for &day in &[0, 1, 2, 3, 6] {
-        dist = dist.set_day_multipliers(day, DRIVER_WEEKDAY_PATTERN);
-    }
+pub fn apply_driver_patterns(dist: TimeOfDayDistribution) -> TimeOfDayDistribution {
+    WeeklyPattern::driver_default().apply(dist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Apply weekend pattern to Friday (4) and Saturday (5)
-    dist = dist.set_day_multipliers(4, DRIVER_WEEKEND_PATTERN);
-    dist = dist.set_day_multipliers(5, DRIVER_WEEKEND_PATTERN);
+    /// 2024-01-06 00:00 UTC, a Saturday.
+    const SATURDAY_MS: i64 = 1_704_499_200_000;
 
-    dist
+    #[test]
+    fn default_patterns_use_weekend_profile_on_friday_and_saturday() {
+        let rider = WeeklyPattern::rider_default();
+        assert_eq!(rider.days[0], RIDER_WEEKDAY_PATTERN);
+        assert_eq!(rider.days[5], RIDER_WEEKEND_PATTERN);
+        assert_eq!(rider.days[6], RIDER_WEEKDAY_PATTERN);
+        assert_eq!(
+            rider.multiplier_at(SATURDAY_MS + 2 * ONE_HOUR_MS as i64),
+            RIDER_WEEKEND_PATTERN[2]
+        );
+    }
+
+    #[test]
+    fn average_over_window_reflects_covered_days() {
+        let pattern = WeeklyPattern::daily([1.0; 24]).with_day_scale(5, 3.0);
+        let day_ms = 24 * ONE_HOUR_MS;
+        let utc = LocalTime::UTC;
+        assert!((pattern.average_multiplier_over(SATURDAY_MS, day_ms, utc) - 3.0).abs() < 1e-9);
+        let two_days = pattern.average_multiplier_over(SATURDAY_MS, 2 * day_ms, utc);
+        assert!((two_days - 2.0).abs() < 1e-9);
+        let week = pattern.average_multiplier_over(SATURDAY_MS, 7 * day_ms, utc);
+        assert!((week - 9.0 / 7.0).abs() < 1e-9);
+    }
 }
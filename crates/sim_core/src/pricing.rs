@@ -1,8 +1,19 @@
 //! Pricing system for calculating trip fares with optional surge pricing.
 
+pub mod earnings_floor;
+pub mod guardrails;
+pub mod probe;
+pub mod surge;
+pub mod tolls;
+pub mod vehicle_classes;
+
+use std::collections::HashMap;
+
 use bevy_ecs::prelude::Resource;
 use h3o::CellIndex;
+use serde::{Deserialize, Serialize};
 
+use crate::pricing::guardrails::SurgeRateLimit;
 use crate::spatial::distance_km_between_cells;
 
 /// Base fare in currency units (e.g., dollars).
@@ -11,8 +22,38 @@ pub const BASE_FARE: f64 = 2.50;
 /// Per-kilometer rate in currency units.
 pub const PER_KM_RATE: f64 = 1.50;
 
+/// How the platform's cut of a fare is computed. Serializable so it can be swept in experiments.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum CommissionModel {
+    /// `fare * commission_rate` for every trip.
+    #[default]
+    Flat,
+    /// `commission_rate` until the driver has completed `weekly_trip_threshold` trips in the
+    /// current week, then `reduced_rate`.
+    Tiered {
+        weekly_trip_threshold: u32,
+        reduced_rate: f64,
+    },
+    /// Fixed platform fee per trip (capped at the fare) instead of a percentage.
+    FixedFee { fee_per_trip: f64 },
+    /// Rate looked up from [`CommissionZones`] by pickup cell; `commission_rate` outside zones.
+    PerZone,
+}
+
+impl CommissionModel {
+    /// Short name for labels and exports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Flat => "flat",
+            Self::Tiered { .. } => "tiered",
+            Self::FixedFee { .. } => "fixed_fee",
+            Self::PerZone => "per_zone",
+        }
+    }
+}
+
 /// Pricing configuration for the marketplace.
-#[derive(Debug, Clone, Copy, Resource)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Resource)]
 pub struct PricingConfig {
     /// Base fare in currency units (e.g., dollars).
     pub base_fare: f64,
@@ -20,12 +61,23 @@ pub struct PricingConfig {
     pub per_km_rate: f64,
     /// Commission rate as a fraction (0.0-1.0). 0.15 means 15% commission.
     pub commission_rate: f64,
+    /// Commission structure. `commission_rate` is the flat / default rate for non-flat models.
+    pub commission_model: CommissionModel,
     /// When true, apply surge multiplier when demand exceeds supply in pickup H3 cluster.
     pub surge_enabled: bool,
     /// H3 grid disk radius (k) for surge cluster around pickup. 1 = immediate neighbors.
     pub surge_radius_k: u32,
     /// Maximum surge multiplier cap (e.g. 2.0 = 2x base fare).
     pub surge_max_multiplier: f64,
+    /// Optional cap on how fast the surge multiplier may change per pickup cell.
+    pub surge_rate_limit: Option<SurgeRateLimit>,
+    /// Percentage tax on the fare (excluding tolls) as a fraction. 0.07 means 7%.
+    pub tax_rate: f64,
+    /// Flat regulatory fee per completed trip, in currency units.
+    pub regulatory_fee_per_trip: f64,
+    /// Guaranteed driver earnings per active hour, topped up by the platform when a session
+    /// ends. 0.0 disables the floor.
+    pub min_hourly_earnings: f64,
 }
 
 impl Default for PricingConfig {
@@ -34,13 +86,119 @@ impl Default for PricingConfig {
             base_fare: BASE_FARE,
             per_km_rate: PER_KM_RATE,
             commission_rate: 0.0,
+            commission_model: CommissionModel::Flat,
             surge_enabled: false,
             surge_radius_k: 1,
             surge_max_multiplier: 2.0,
+            surge_rate_limit: None,
+            tax_rate: 0.0,
+            regulatory_fee_per_trip: 0.0,
+            min_hourly_earnings: 0.0,
         }
     }
 }
 
+/// Per-zone commission rates keyed by pickup cell, used by [`CommissionModel::PerZone`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Resource)]
+pub struct CommissionZones {
+    /// Commission rate overrides. Cells not in the map use `PricingConfig::commission_rate`.
+    #[serde(with = "crate::run_bundle::cell_map")]
+    pub cell_rates: HashMap<CellIndex, f64>,
+}
+
+impl CommissionZones {
+    /// Assign `rate` to every cell within `radius_k` of `center`.
+    pub fn with_zone(mut self, center: CellIndex, radius_k: u32, rate: f64) -> Self {
+        for cell in center.grid_disk::<Vec<_>>(radius_k) {
+            self.cell_rates.insert(cell, rate);
+        }
+        self
+    }
+
+    /// Commission rate override for a cell, if it is inside a zone.
+    pub fn rate_for_cell(&self, cell: CellIndex) -> Option<f64> {
+        self.cell_rates.get(&cell).copied()
+    }
+}
+
+/// Tax rate and regulatory fee that apply to trips picked up in a zone.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TaxRule {
+    /// Percentage tax on the fare (excluding tolls) as a fraction.
+    pub tax_rate: f64,
+    /// Flat regulatory fee per trip.
+    pub regulatory_fee: f64,
+}
+
+/// Per-zone tax / regulatory fee overrides keyed by pickup cell.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Resource)]
+pub struct TaxZones {
+    /// Overrides. Cells not in the map use `PricingConfig::tax_rate` / `regulatory_fee_per_trip`.
+    #[serde(with = "crate::run_bundle::cell_map")]
+    pub cell_rules: HashMap<CellIndex, TaxRule>,
+}
+
+impl TaxZones {
+    /// Apply `rule` to every cell within `radius_k` of `center`.
+    pub fn with_zone(mut self, center: CellIndex, radius_k: u32, rule: TaxRule) -> Self {
+        for cell in center.grid_disk::<Vec<_>>(radius_k) {
+            self.cell_rules.insert(cell, rule);
+        }
+        self
+    }
+
+    /// Tax rule override for a cell, if it is inside a zone.
+    pub fn rule_for_cell(&self, cell: CellIndex) -> Option<TaxRule> {
+        self.cell_rules.get(&cell).copied()
+    }
+}
+
+/// Calculate taxes and regulatory fees withheld from one trip's fare (excluding tolls).
+///
+/// `zone_rule` (from [`TaxZones`] for the pickup cell) replaces the config's tax rate and
+/// regulatory fee. The result is clamped to `[0, fare]`; it is deducted before commission,
+/// so both driver pay and platform revenue are net of taxes.
+pub fn calculate_trip_taxes(fare: f64, config: &PricingConfig, zone_rule: Option<TaxRule>) -> f64 {
+    let rule = zone_rule.unwrap_or(TaxRule {
+        tax_rate: config.tax_rate,
+        regulatory_fee: config.regulatory_fee_per_trip,
+    });
+    (fare * rule.tax_rate + rule.regulatory_fee).clamp(0.0, fare.max(0.0))
+}
+
+/// Calculate the platform commission for one trip under `config.commission_model`.
+///
+/// `prior_weekly_trips` is the number of trips the driver already completed this week
+/// (used by [`CommissionModel::Tiered`]); `zone_rate` is the [`CommissionZones`] rate for
+/// the pickup cell (used by [`CommissionModel::PerZone`]). The result is clamped to `[0, fare]`
+/// so driver earnings never go negative.
+pub fn calculate_trip_commission(
+    fare: f64,
+    config: &PricingConfig,
+    prior_weekly_trips: u32,
+    zone_rate: Option<f64>,
+) -> f64 {
+    let commission = match config.commission_model {
+        CommissionModel::Flat => calculate_commission(fare, config.commission_rate),
+        CommissionModel::Tiered {
+            weekly_trip_threshold,
+            reduced_rate,
+        } => {
+            let rate = if prior_weekly_trips >= weekly_trip_threshold {
+                reduced_rate
+            } else {
+                config.commission_rate
+            };
+            calculate_commission(fare, rate)
+        }
+        CommissionModel::FixedFee { fee_per_trip } => fee_per_trip,
+        CommissionModel::PerZone => {
+            calculate_commission(fare, zone_rate.unwrap_or(config.commission_rate))
+        }
+    };
+    commission.clamp(0.0, fare.max(0.0))
+}
+
 /// Calculate commission amount from fare and commission rate.
 pub fn calculate_commission(fare: f64, commission_rate: f64) -> f64 {
     fare * commission_rate
@@ -121,10 +279,7 @@ mod tests {
         let config = PricingConfig {
             base_fare: 3.0,
             per_km_rate: 2.0,
-            commission_rate: 0.0,
-            surge_enabled: false,
-            surge_radius_k: 1,
-            surge_max_multiplier: 2.0,
+            ..Default::default()
         };
         let fare = calculate_trip_fare_with_config(cell, nearby, config);
         let distance = distance_km_between_cells(cell, nearby);
@@ -164,4 +319,66 @@ mod tests {
         let driver_earnings = calculate_driver_earnings(fare, commission_rate);
         assert_eq!(driver_earnings, fare);
     }
+
+    #[test]
+    fn tiered_commission_drops_after_threshold() {
+        let config = PricingConfig {
+            commission_rate: 0.25,
+            commission_model: CommissionModel::Tiered {
+                weekly_trip_threshold: 10,
+                reduced_rate: 0.15,
+            },
+            ..Default::default()
+        };
+        assert_eq!(calculate_trip_commission(100.0, &config, 9, None), 25.0);
+        assert_eq!(calculate_trip_commission(100.0, &config, 10, None), 15.0);
+    }
+
+    #[test]
+    fn fixed_fee_commission_is_capped_at_fare() {
+        let config = PricingConfig {
+            commission_model: CommissionModel::FixedFee { fee_per_trip: 3.0 },
+            ..Default::default()
+        };
+        assert_eq!(calculate_trip_commission(20.0, &config, 0, None), 3.0);
+        assert_eq!(calculate_trip_commission(2.0, &config, 0, None), 2.0);
+    }
+
+    #[test]
+    fn per_zone_commission_falls_back_to_default_rate() {
+        let cell = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+        let zones = CommissionZones::default().with_zone(cell, 0, 0.3);
+        let config = PricingConfig {
+            commission_rate: 0.1,
+            commission_model: CommissionModel::PerZone,
+            ..Default::default()
+        };
+        let in_zone = calculate_trip_commission(100.0, &config, 0, zones.rate_for_cell(cell));
+        assert!((in_zone - 30.0).abs() < 1e-9);
+        let outside = calculate_trip_commission(100.0, &config, 0, None);
+        assert!((outside - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn taxes_use_zone_rule_and_are_capped_at_fare() {
+        let cell = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+        let zones = TaxZones::default().with_zone(
+            cell,
+            0,
+            TaxRule {
+                tax_rate: 0.2,
+                regulatory_fee: 1.0,
+            },
+        );
+        let config = PricingConfig {
+            tax_rate: 0.1,
+            regulatory_fee_per_trip: 0.5,
+            ..Default::default()
+        };
+        let default_taxes = calculate_trip_taxes(10.0, &config, None);
+        assert!((default_taxes - 1.5).abs() < 1e-9);
+        let zone_taxes = calculate_trip_taxes(10.0, &config, zones.rule_for_cell(cell));
+        assert!((zone_taxes - 3.0).abs() < 1e-9);
+        assert_eq!(calculate_trip_taxes(0.2, &config, None), 0.2);
+    }
 }
@@ -0,0 +1,127 @@
+//! Minimum earnings floor: guarantee drivers a minimum per-active-hour earning.
+//!
+//! With `PricingConfig::min_hourly_earnings > 0`, each driver session is settled when it ends
+//! (driver goes OffDuty, or the run finishes): if the driver earned less than
+//! `min_hourly_earnings × active_hours`, the platform tops up the difference. Top-ups are added to
+//! `DriverEarnings::daily_earnings` and accounted separately in [`SimTelemetry`] so the cost of
+//! the policy can be compared across sweeps.
+
+use bevy_ecs::prelude::World;
+
+use crate::clock::{SimulationClock, ONE_HOUR_MS};
+use crate::ecs::DriverEarnings;
+use crate::pricing::PricingConfig;
+use crate::telemetry::SimTelemetry;
+
+/// Platform top-up owed for a session of `active_ms` in which the driver earned `earned`.
+/// Zero when the floor is disabled (`min_hourly_earnings <= 0`) or already met.
+pub fn earnings_floor_top_up(earned: f64, active_ms: u64, min_hourly_earnings: f64) -> f64 {
+    if min_hourly_earnings <= 0.0 {
+        return 0.0;
+    }
+    let guaranteed = min_hourly_earnings * active_ms as f64 / ONE_HOUR_MS as f64;
+    (guaranteed - earned).max(0.0)
+}
+
+/// End a driver's session at `now_ms` and pay any top-up owed under `config`.
+/// Returns the top-up amount. Sessions that already ended are left untouched.
+pub fn settle_driver_session(
+    earnings: &mut DriverEarnings,
+    now_ms: u64,
+    config: &PricingConfig,
+    telemetry: Option<&mut SimTelemetry>,
+) -> f64 {
+    if earnings.session_end_time_ms.is_some() {
+        return 0.0;
+    }
+    earnings.session_end_time_ms = Some(now_ms);
+    let active_ms = now_ms.saturating_sub(earnings.session_start_time_ms);
+    let top_up = earnings_floor_top_up(
+        earnings.daily_earnings,
+        active_ms,
+        config.min_hourly_earnings,
+    );
+    if top_up > 0.0 {
+        earnings.daily_earnings += top_up;
+        if let Some(telemetry) = telemetry {
+            telemetry.earnings_floor_top_ups_total += top_up;
+            telemetry.drivers_topped_up_total = telemetry.drivers_topped_up_total.saturating_add(1);
+        }
+    }
+    top_up
+}
+
+/// Settle the earnings floor for every driver still on duty. Call once when a run finishes,
+/// before reading driver payouts; drivers that already went OffDuty were settled then.
+pub fn settle_earnings_floor(world: &mut World) {
+    let Some(config) = world.get_resource::<PricingConfig>().copied() else {
+        return;
+    };
+    if config.min_hourly_earnings <= 0.0 {
+        return;
+    }
+    let Some(now) = world
+        .get_resource::<SimulationClock>()
+        .map(|clock| clock.now())
+    else {
+        return;
+    };
+    let mut top_ups = 0.0;
+    let mut topped_up = 0u64;
+    let mut query = world.query::<&mut DriverEarnings>();
+    for mut earnings in query.iter_mut(world) {
+        let top_up = settle_driver_session(&mut earnings, now, &config, None);
+        if top_up > 0.0 {
+            top_ups += top_up;
+            topped_up += 1;
+        }
+    }
+    if let Some(mut telemetry) = world.get_resource_mut::<SimTelemetry>() {
+        telemetry.earnings_floor_top_ups_total += top_ups;
+        telemetry.drivers_topped_up_total =
+            telemetry.drivers_topped_up_total.saturating_add(topped_up);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_up_covers_shortfall_per_active_hour() {
+        let top_up = earnings_floor_top_up(40.0, 2 * ONE_HOUR_MS, 30.0);
+        assert!((top_up - 20.0).abs() < 1e-9);
+        assert_eq!(earnings_floor_top_up(80.0, 2 * ONE_HOUR_MS, 30.0), 0.0);
+        assert_eq!(earnings_floor_top_up(0.0, 2 * ONE_HOUR_MS, 0.0), 0.0);
+    }
+
+    #[test]
+    fn session_is_settled_once() {
+        let config = PricingConfig {
+            min_hourly_earnings: 25.0,
+            ..Default::default()
+        };
+        let mut earnings = DriverEarnings {
+            daily_earnings: 10.0,
+            daily_earnings_target: 100.0,
+            session_start_time_ms: 0,
+            session_end_time_ms: None,
+            weekly_trips: 0,
+            week_index: 0,
+        };
+        let mut telemetry = SimTelemetry::default();
+        let top_up =
+            settle_driver_session(&mut earnings, ONE_HOUR_MS, &config, Some(&mut telemetry));
+        assert!((top_up - 15.0).abs() < 1e-9);
+        assert!((earnings.daily_earnings - 25.0).abs() < 1e-9);
+        assert_eq!(telemetry.drivers_topped_up_total, 1);
+        let again = settle_driver_session(
+            &mut earnings,
+            2 * ONE_HOUR_MS,
+            &config,
+            Some(&mut telemetry),
+        );
+        assert_eq!(again, 0.0);
+        assert_eq!(earnings.session_end_time_ms, Some(ONE_HOUR_MS));
+    }
+}
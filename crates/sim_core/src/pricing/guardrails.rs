@@ -0,0 +1,129 @@
+//! Price-change guardrails: cap how fast the surge multiplier may move per cell.
+//!
+//! With a [`SurgeRateLimit`] configured, the multiplier quoted in a pickup cell can change by
+//! at most `max_increase` / `max_decrease` per `window_ms`, scaled linearly by the time since
+//! the last quote in that cell. Cells without history start from 1.0 with one full window of
+//! headroom. State lives in the [`SurgeRateLimiter`] resource.
+
+use std::collections::HashMap;
+
+use bevy_ecs::prelude::Resource;
+use h3o::CellIndex;
+use serde::{Deserialize, Serialize};
+
+/// Maximum surge multiplier movement per time window (e.g. +0.2x per 5 minutes).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurgeRateLimit {
+    /// Largest allowed increase of the multiplier per window.
+    pub max_increase: f64,
+    /// Largest allowed decrease of the multiplier per window.
+    pub max_decrease: f64,
+    /// Window length in simulation milliseconds.
+    pub window_ms: u64,
+}
+
+impl Default for SurgeRateLimit {
+    fn default() -> Self {
+        Self {
+            max_increase: 0.2,
+            max_decrease: 0.2,
+            window_ms: 5 * crate::clock::ONE_MIN_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CellPrice {
+    multiplier: f64,
+    at_ms: u64,
+}
+
+/// Last quoted multiplier per pickup cell, used to enforce [`SurgeRateLimit`].
+#[derive(Debug, Default, Resource)]
+pub struct SurgeRateLimiter {
+    cells: HashMap<CellIndex, CellPrice>,
+    /// Quotes whose multiplier was clipped by the limit.
+    pub limited_quotes: u64,
+}
+
+impl SurgeRateLimiter {
+    /// Clamp `raw` to the band allowed by `limit` for `cell` at `now_ms`, record it, and return it.
+    pub fn apply(&mut self, cell: CellIndex, raw: f64, now_ms: u64, limit: &SurgeRateLimit) -> f64 {
+        let limited = self.preview(cell, raw, now_ms, limit);
+        if (limited - raw).abs() > f64::EPSILON {
+            self.limited_quotes = self.limited_quotes.saturating_add(1);
+        }
+        self.cells.insert(
+            cell,
+            CellPrice {
+                multiplier: limited,
+                at_ms: now_ms,
+            },
+        );
+        limited
+    }
+
+    /// Multiplier [`Self::apply`] would return, without recording it.
+    pub fn preview(&self, cell: CellIndex, raw: f64, now_ms: u64, limit: &SurgeRateLimit) -> f64 {
+        let window = limit.window_ms.max(1) as f64;
+        let (previous, elapsed_windows) = match self.cells.get(&cell) {
+            Some(last) => (
+                last.multiplier,
+                now_ms.saturating_sub(last.at_ms) as f64 / window,
+            ),
+            None => (1.0, 1.0),
+        };
+        let upper = previous + limit.max_increase.max(0.0) * elapsed_windows;
+        let lower = previous - limit.max_decrease.max(0.0) * elapsed_windows;
+        raw.clamp(lower.min(upper), upper).max(1.0)
+    }
+
+    /// Last multiplier quoted in `cell`, if any.
+    pub fn last_multiplier(&self, cell: CellIndex) -> Option<f64> {
+        self.cells.get(&cell).map(|price| price.multiplier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell() -> CellIndex {
+        CellIndex::try_from(0x8a1fb46622dffff).expect("cell")
+    }
+
+    #[test]
+    fn first_quote_gets_one_window_of_headroom() {
+        let mut limiter = SurgeRateLimiter::default();
+        let preview = limiter.preview(cell(), 2.0, 0, &SurgeRateLimit::default());
+        assert_eq!(limiter.last_multiplier(cell()), None);
+        let limited = limiter.apply(cell(), 2.0, 0, &SurgeRateLimit::default());
+        assert!((limited - 1.2).abs() < 1e-9);
+        assert_eq!(preview, limited);
+        assert_eq!(limiter.limited_quotes, 1);
+    }
+
+    #[test]
+    fn increase_scales_with_elapsed_time() {
+        let limit = SurgeRateLimit::default();
+        let mut limiter = SurgeRateLimiter::default();
+        limiter.apply(cell(), 1.2, 0, &limit);
+        let half_window = limit.window_ms / 2;
+        let limited = limiter.apply(cell(), 2.0, half_window, &limit);
+        assert!((limited - 1.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn decrease_is_limited_and_unclipped_quotes_pass_through() {
+        let limit = SurgeRateLimit::default();
+        let mut limiter = SurgeRateLimiter::default();
+        limiter.apply(cell(), 1.2, 0, &limit);
+        limiter.apply(cell(), 1.4, limit.window_ms, &limit);
+        let limited = limiter.apply(cell(), 1.0, limit.window_ms * 2, &limit);
+        assert!((limited - 1.2).abs() < 1e-9);
+        let limited_before = limiter.limited_quotes;
+        let unchanged = limiter.apply(cell(), 1.25, limit.window_ms * 3, &limit);
+        assert!((unchanged - 1.25).abs() < 1e-9);
+        assert_eq!(limiter.limited_quotes, limited_before);
+    }
+}
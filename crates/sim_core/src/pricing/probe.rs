@@ -0,0 +1,186 @@
+//! Read-only pricing probe: what a quote from a given pickup cell would look like right now.
+//!
+//! [`probe_pricing`] counts demand and supply in the surge cluster around a cell the way
+//! `show_quote_system` does, asks the active surge strategy and rate limiter for the
+//! multiplier they would quote (via their `preview` methods, so no state changes), and looks
+//! up zone commission and tax rules. [`PricingProbe::fare_for_distance`] then prices a
+//! hypothetical trip of a given length. Tolls are route-dependent and left out.
+
+use bevy_ecs::prelude::{Entity, World};
+use h3o::CellIndex;
+
+use crate::clock::{SimulationClock, ONE_SEC_MS};
+use crate::ecs::{Browsing, Driver, Idle, Position, Rider, Waiting};
+use crate::pricing::guardrails::SurgeRateLimiter;
+use crate::pricing::surge::{
+    SupplyDemandRatioSurge, SurgeContext, SurgeStrategy, SurgeStrategyResource,
+};
+use crate::pricing::{
+    calculate_trip_commission, calculate_trip_taxes, CommissionZones, PricingConfig, TaxRule,
+    TaxZones,
+};
+use crate::spatial::{distance_km_between_cells, grid_disk_cached, SpatialIndex};
+use crate::systems::show_quote::{DEFAULT_ETA_MS, ETA_SPEED_KMH};
+
+/// Market state and pricing rules for one pickup cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PricingProbe {
+    pub cell: CellIndex,
+    /// Browsing + waiting riders in the surge cluster.
+    pub demand: usize,
+    /// Idle drivers in the surge cluster.
+    pub supply: usize,
+    /// Pickup ETA from the nearest idle driver (ms).
+    pub eta_ms: u64,
+    /// Strategy multiplier before the rate limit.
+    pub raw_multiplier: f64,
+    /// Multiplier a quote from this cell would get now (1.0 with surge disabled).
+    pub surge_multiplier: f64,
+    /// Multiplier last quoted in this cell, when a rate limiter tracks it.
+    pub last_quoted_multiplier: Option<f64>,
+    pub commission_zone_rate: Option<f64>,
+    pub tax_rule: Option<TaxRule>,
+    pub config: PricingConfig,
+}
+
+/// Fare split for a hypothetical trip.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FareBreakdown {
+    /// Base fare plus distance charge, before surge.
+    pub base_fare: f64,
+    /// Fare the rider pays (excluding tolls).
+    pub fare: f64,
+    pub taxes: f64,
+    pub commission: f64,
+    pub driver_earnings: f64,
+}
+
+impl PricingProbe {
+    /// Demand divided by supply (see [`SurgeContext::demand_supply_ratio`]).
+    pub fn demand_supply_ratio(&self) -> f64 {
+        self.context(0).demand_supply_ratio()
+    }
+
+    /// Price a trip of `distance_km` from this cell. Tiered commission assumes a driver with no
+    /// trips yet this week.
+    pub fn fare_for_distance(&self, distance_km: f64) -> FareBreakdown {
+        let base_fare = self.config.base_fare + distance_km.max(0.0) * self.config.per_km_rate;
+        let fare = base_fare * self.surge_multiplier;
+        let taxes = calculate_trip_taxes(fare, &self.config, self.tax_rule);
+        let net = fare - taxes;
+        let commission = calculate_trip_commission(net, &self.config, 0, self.commission_zone_rate);
+        FareBreakdown {
+            base_fare,
+            fare,
+            taxes,
+            commission,
+            driver_earnings: net - commission,
+        }
+    }
+
+    fn context(&self, now_ms: u64) -> SurgeContext {
+        SurgeContext {
+            demand: self.demand,
+            supply: self.supply,
+            eta_ms: self.eta_ms,
+            now_ms,
+            epoch_ms: 0,
+            max_multiplier: self.config.surge_max_multiplier,
+        }
+    }
+}
+
+/// Probe pricing for a pickup in `cell`. `None` until the world has a [`PricingConfig`] and a
+/// [`SimulationClock`].
+pub fn probe_pricing(world: &World, cell: CellIndex) -> Option<PricingProbe> {
+    let config = *world.get_resource::<PricingConfig>()?;
+    let clock = world.get_resource::<SimulationClock>()?;
+
+    let cluster_cells = grid_disk_cached(cell, config.surge_radius_k);
+    let (demand, supply) = count_demand_and_supply(world, &cluster_cells);
+    let eta_ms = world
+        .iter_entities()
+        .filter(|entity| entity.contains::<Driver>() && entity.contains::<Idle>())
+        .filter_map(|entity| entity.get::<Position>())
+        .map(|position| {
+            let hours = distance_km_between_cells(position.0, cell) / ETA_SPEED_KMH;
+            (hours * 3_600_000.0) as u64
+        })
+        .min()
+        .unwrap_or(DEFAULT_ETA_MS)
+        .max(ONE_SEC_MS);
+
+    let mut probe = PricingProbe {
+        cell,
+        demand,
+        supply,
+        eta_ms,
+        raw_multiplier: 1.0,
+        surge_multiplier: 1.0,
+        last_quoted_multiplier: None,
+        commission_zone_rate: world
+            .get_resource::<CommissionZones>()
+            .and_then(|zones| zones.rate_for_cell(cell)),
+        tax_rule: world
+            .get_resource::<TaxZones>()
+            .and_then(|zones| zones.rule_for_cell(cell)),
+        config,
+    };
+    if config.surge_enabled && config.surge_radius_k > 0 {
+        let ctx = SurgeContext {
+            epoch_ms: clock.local_epoch_ms(),
+            ..probe.context(clock.now())
+        };
+        probe.raw_multiplier = match world.get_resource::<SurgeStrategyResource>() {
+            Some(strategy) => strategy.0.preview(&ctx),
+            None => SupplyDemandRatioSurge.preview(&ctx),
+        };
+    }
+    probe.surge_multiplier = probe.raw_multiplier;
+    if let Some(limiter) = world.get_resource::<SurgeRateLimiter>() {
+        probe.last_quoted_multiplier = limiter.last_multiplier(cell);
+        if let Some(limit) = config.surge_rate_limit {
+            probe.surge_multiplier =
+                limiter.preview(cell, probe.raw_multiplier, clock.now(), &limit);
+        }
+    }
+    Some(probe)
+}
+
+fn count_demand_and_supply(world: &World, cluster_cells: &[CellIndex]) -> (usize, usize) {
+    let is_demand = |entity: Entity| {
+        world.get::<Browsing>(entity).is_some() || world.get::<Waiting>(entity).is_some()
+    };
+    let is_supply = |entity: Entity| world.get::<Idle>(entity).is_some();
+
+    if let Some(index) = world.get_resource::<SpatialIndex>() {
+        let demand = index
+            .get_riders_in_cells(cluster_cells)
+            .into_iter()
+            .filter(|&entity| world.get::<Rider>(entity).is_some() && is_demand(entity))
+            .count();
+        let supply = index
+            .get_drivers_in_cells(cluster_cells)
+            .into_iter()
+            .filter(|&entity| world.get::<Driver>(entity).is_some() && is_supply(entity))
+            .count();
+        return (demand, supply);
+    }
+
+    let mut demand = 0;
+    let mut supply = 0;
+    for entity in world.iter_entities() {
+        let Some(position) = entity.get::<Position>() else {
+            continue;
+        };
+        if !cluster_cells.contains(&position.0) {
+            continue;
+        }
+        if entity.contains::<Rider>() && is_demand(entity.id()) {
+            demand += 1;
+        } else if entity.contains::<Driver>() && is_supply(entity.id()) {
+            supply += 1;
+        }
+    }
+    (demand, supply)
+}
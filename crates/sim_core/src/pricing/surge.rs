@@ -0,0 +1,442 @@
+//! Surge strategies: pluggable policies that turn local market state into a fare multiplier.
+//!
+//! Four implementations, selectable via [`SurgeStrategyKind`]:
+//!
+//! - **`SupplyDemandRatioSurge`**: `1 + (demand - supply) / supply`, capped (original behaviour).
+//! - **`StepwiseSurge`**: Discrete multiplier tiers keyed on the demand/supply ratio.
+//! - **`PidTargetWaitSurge`**: PID controller steering the quoted pickup ETA towards a target wait.
+//! - **`ScheduledSurge`**: Fixed multipliers for configured hour-of-day windows.
+//!
+//! The strategy is stored as a `Box<dyn SurgeStrategy>` ECS resource, constructed from
+//! `SurgeStrategyKind` during scenario building. All strategies clamp their output to
+//! `[1.0, max_multiplier]`.
+
+use bevy_ecs::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+/// Market state around a pickup, passed to a [`SurgeStrategy`] when quoting.
+#[derive(Debug, Clone, Copy)]
+pub struct SurgeContext {
+    /// Browsing + waiting riders in the surge cluster.
+    pub demand: usize,
+    /// Idle drivers in the surge cluster.
+    pub supply: usize,
+    /// Estimated time to pickup for this quote (ms).
+    pub eta_ms: u64,
+    /// Current simulation time (ms).
+    pub now_ms: u64,
+    /// Local wall-clock ms for simulation time 0 (for time-of-day schedules; see
+    /// `SimulationClock::local_epoch_ms`).
+    pub epoch_ms: i64,
+    /// Upper bound for the multiplier (from `PricingConfig::surge_max_multiplier`).
+    pub max_multiplier: f64,
+}
+
+impl SurgeContext {
+    /// Demand divided by supply. Infinite when there is demand but no supply, 0 when both are 0.
+    pub fn demand_supply_ratio(&self) -> f64 {
+        match (self.demand, self.supply) {
+            (0, _) => 0.0,
+            (_, 0) => f64::INFINITY,
+            (demand, supply) => demand as f64 / supply as f64,
+        }
+    }
+
+    fn clamp(&self, multiplier: f64) -> f64 {
+        multiplier.clamp(1.0, self.max_multiplier.max(1.0))
+    }
+}
+
+/// Trait for surge policies. Takes `&mut self` so controllers can keep state between quotes.
+pub trait SurgeStrategy: Send + Sync {
+    /// Multiplier applied to the base fare for a quote with the given context.
+    fn multiplier(&mut self, ctx: &SurgeContext) -> f64;
+
+    /// Multiplier a quote with this context would get, without updating controller state.
+    fn preview(&self, ctx: &SurgeContext) -> f64;
+}
+
+/// One tier of a [`SurgeStrategyKind::Stepwise`] schedule.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SurgeTier {
+    /// Tier applies when demand/supply ratio is at least this value.
+    pub min_ratio: f64,
+    /// Multiplier applied while in this tier.
+    pub multiplier: f64,
+}
+
+/// Hour-of-day window for [`SurgeStrategyKind::Scheduled`]. `end_hour` is exclusive;
+/// windows with `start_hour > end_hour` wrap around midnight.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ScheduledSurgeWindow {
+    pub start_hour: u8,
+    pub end_hour: u8,
+    pub multiplier: f64,
+}
+
+impl ScheduledSurgeWindow {
+    fn contains(&self, hour: u8) -> bool {
+        if self.start_hour <= self.end_hour {
+            hour >= self.start_hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
+/// Which surge strategy to use. Stored in `ScenarioParams` so it can be swept in experiments.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SurgeStrategyKind {
+    /// Continuous supply/demand ratio (original behaviour).
+    #[default]
+    SupplyDemandRatio,
+    /// Discrete tiers keyed on the demand/supply ratio.
+    Stepwise { tiers: Vec<SurgeTier> },
+    /// PID controller targeting a pickup wait time.
+    PidTargetWait {
+        target_wait_ms: u64,
+        kp: f64,
+        ki: f64,
+        kd: f64,
+    },
+    /// Fixed multipliers during configured hour-of-day windows.
+    Scheduled { windows: Vec<ScheduledSurgeWindow> },
+}
+
+impl SurgeStrategyKind {
+    /// Stepwise tiers: 1.2x from 1.5 riders/driver, 1.5x from 2.0, 2.0x from 3.0.
+    pub fn default_stepwise() -> Self {
+        Self::Stepwise {
+            tiers: vec![
+                SurgeTier {
+                    min_ratio: 1.5,
+                    multiplier: 1.2,
+                },
+                SurgeTier {
+                    min_ratio: 2.0,
+                    multiplier: 1.5,
+                },
+                SurgeTier {
+                    min_ratio: 3.0,
+                    multiplier: 2.0,
+                },
+            ],
+        }
+    }
+
+    /// PID controller targeting a 5 minute pickup wait.
+    pub fn default_pid() -> Self {
+        Self::PidTargetWait {
+            target_wait_ms: 300_000,
+            kp: 0.5,
+            ki: 0.05,
+            kd: 0.1,
+        }
+    }
+
+    /// 1.5x during morning (07–09) and evening (17–19) rush hours.
+    pub fn default_scheduled() -> Self {
+        Self::Scheduled {
+            windows: vec![
+                ScheduledSurgeWindow {
+                    start_hour: 7,
+                    end_hour: 9,
+                    multiplier: 1.5,
+                },
+                ScheduledSurgeWindow {
+                    start_hour: 17,
+                    end_hour: 19,
+                    multiplier: 1.5,
+                },
+            ],
+        }
+    }
+
+    /// Short stable name used in exports and sweep dimensions.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SupplyDemandRatio => "supply_demand_ratio",
+            Self::Stepwise { .. } => "stepwise",
+            Self::PidTargetWait { .. } => "pid_target_wait",
+            Self::Scheduled { .. } => "scheduled",
+        }
+    }
+
+    /// Parse a strategy name (as produced by [`Self::name`]) into its default configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "supply_demand_ratio" | "ratio" => Some(Self::SupplyDemandRatio),
+            "stepwise" => Some(Self::default_stepwise()),
+            "pid_target_wait" | "pid" => Some(Self::default_pid()),
+            "scheduled" => Some(Self::default_scheduled()),
+            _ => None,
+        }
+    }
+}
+
+/// Original continuous formula: `1 + (demand - supply) / supply`, max when supply is 0.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SupplyDemandRatioSurge;
+
+impl SurgeStrategy for SupplyDemandRatioSurge {
+    fn multiplier(&mut self, ctx: &SurgeContext) -> f64 {
+        self.preview(ctx)
+    }
+
+    fn preview(&self, ctx: &SurgeContext) -> f64 {
+        if ctx.demand <= ctx.supply {
+            return 1.0;
+        }
+        if ctx.supply == 0 {
+            return ctx.max_multiplier;
+        }
+        let raw = 1.0 + (ctx.demand - ctx.supply) as f64 / ctx.supply as f64;
+        raw.min(ctx.max_multiplier)
+    }
+}
+
+/// Picks the highest tier whose `min_ratio` is reached.
+#[derive(Debug, Clone)]
+pub struct StepwiseSurge {
+    tiers: Vec<SurgeTier>,
+}
+
+impl StepwiseSurge {
+    pub fn new(mut tiers: Vec<SurgeTier>) -> Self {
+        tiers.sort_by(|a, b| a.min_ratio.total_cmp(&b.min_ratio));
+        Self { tiers }
+    }
+}
+
+impl SurgeStrategy for StepwiseSurge {
+    fn multiplier(&mut self, ctx: &SurgeContext) -> f64 {
+        self.preview(ctx)
+    }
+
+    fn preview(&self, ctx: &SurgeContext) -> f64 {
+        let ratio = ctx.demand_supply_ratio();
+        let multiplier = self
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| ratio >= tier.min_ratio)
+            .map(|tier| tier.multiplier)
+            .unwrap_or(1.0);
+        ctx.clamp(multiplier)
+    }
+}
+
+/// PID controller on the normalized wait error `(eta - target) / target`.
+///
+/// Positive error (riders waiting longer than target) raises the multiplier to shed demand
+/// and attract supply. The integral term is clamped to avoid windup.
+#[derive(Debug, Clone)]
+pub struct PidTargetWaitSurge {
+    target_wait_ms: u64,
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    integral: f64,
+    last_error: Option<f64>,
+}
+
+/// Bound on the accumulated integral term (in normalized error units).
+const PID_INTEGRAL_LIMIT: f64 = 20.0;
+
+impl PidTargetWaitSurge {
+    pub fn new(target_wait_ms: u64, kp: f64, ki: f64, kd: f64) -> Self {
+        Self {
+            target_wait_ms: target_wait_ms.max(1),
+            kp,
+            ki,
+            kd,
+            integral: 0.0,
+            last_error: None,
+        }
+    }
+}
+
+impl PidTargetWaitSurge {
+    /// Normalized wait error and the integral it would produce.
+    fn step(&self, ctx: &SurgeContext) -> (f64, f64) {
+        let target = self.target_wait_ms as f64;
+        let error = (ctx.eta_ms as f64 - target) / target;
+        let integral = (self.integral + error).clamp(-PID_INTEGRAL_LIMIT, PID_INTEGRAL_LIMIT);
+        (error, integral)
+    }
+
+    fn output(&self, ctx: &SurgeContext, error: f64, integral: f64) -> f64 {
+        let derivative = self.last_error.map(|last| error - last).unwrap_or(0.0);
+        let output = self.kp * error + self.ki * integral + self.kd * derivative;
+        ctx.clamp(1.0 + output)
+    }
+}
+
+impl SurgeStrategy for PidTargetWaitSurge {
+    fn multiplier(&mut self, ctx: &SurgeContext) -> f64 {
+        let (error, integral) = self.step(ctx);
+        let multiplier = self.output(ctx, error, integral);
+        self.integral = integral;
+        self.last_error = Some(error);
+        multiplier
+    }
+
+    fn preview(&self, ctx: &SurgeContext) -> f64 {
+        let (error, integral) = self.step(ctx);
+        self.output(ctx, error, integral)
+    }
+}
+
+/// Applies the first window covering the current hour of day.
+#[derive(Debug, Clone)]
+pub struct ScheduledSurge {
+    windows: Vec<ScheduledSurgeWindow>,
+}
+
+impl ScheduledSurge {
+    pub fn new(windows: Vec<ScheduledSurgeWindow>) -> Self {
+        Self { windows }
+    }
+}
+
+impl SurgeStrategy for ScheduledSurge {
+    fn multiplier(&mut self, ctx: &SurgeContext) -> f64 {
+        self.preview(ctx)
+    }
+
+    fn preview(&self, ctx: &SurgeContext) -> f64 {
+        let local_ms = ctx.epoch_ms + ctx.now_ms as i64;
+        let hour = local_ms.div_euclid(3_600_000).rem_euclid(24) as u8;
+        let multiplier = self
+            .windows
+            .iter()
+            .find(|window| window.contains(hour))
+            .map(|window| window.multiplier)
+            .unwrap_or(1.0);
+        ctx.clamp(multiplier)
+    }
+}
+
+/// Build a boxed strategy from a [`SurgeStrategyKind`] descriptor.
+pub fn build_surge_strategy(kind: &SurgeStrategyKind) -> Box<dyn SurgeStrategy> {
+    match kind {
+        SurgeStrategyKind::SupplyDemandRatio => Box::new(SupplyDemandRatioSurge),
+        SurgeStrategyKind::Stepwise { tiers } => Box::new(StepwiseSurge::new(tiers.clone())),
+        SurgeStrategyKind::PidTargetWait {
+            target_wait_ms,
+            kp,
+            ki,
+            kd,
+        } => Box::new(PidTargetWaitSurge::new(*target_wait_ms, *kp, *ki, *kd)),
+        SurgeStrategyKind::Scheduled { windows } => Box::new(ScheduledSurge::new(windows.clone())),
+    }
+}
+
+/// ECS resource wrapping the active surge strategy.
+#[derive(Resource)]
+pub struct SurgeStrategyResource(pub Box<dyn SurgeStrategy>);
+
+impl SurgeStrategyResource {
+    pub fn from_kind(kind: &SurgeStrategyKind) -> Self {
+        Self(build_surge_strategy(kind))
+    }
+}
+
+impl Default for SurgeStrategyResource {
+    fn default() -> Self {
+        Self(Box::new(SupplyDemandRatioSurge))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(demand: usize, supply: usize) -> SurgeContext {
+        SurgeContext {
+            demand,
+            supply,
+            eta_ms: 60_000,
+            now_ms: 0,
+            epoch_ms: 0,
+            max_multiplier: 2.0,
+        }
+    }
+
+    #[test]
+    fn ratio_matches_original_formula() {
+        let mut strategy = SupplyDemandRatioSurge;
+        assert_eq!(strategy.multiplier(&ctx(2, 4)), 1.0);
+        assert!((strategy.multiplier(&ctx(6, 4)) - 1.5).abs() < 1e-9);
+        assert_eq!(strategy.multiplier(&ctx(10, 2)), 2.0);
+        assert_eq!(strategy.multiplier(&ctx(3, 0)), 2.0);
+    }
+
+    #[test]
+    fn stepwise_selects_highest_reached_tier() {
+        let SurgeStrategyKind::Stepwise { tiers } = SurgeStrategyKind::default_stepwise() else {
+            unreachable!();
+        };
+        let mut strategy = StepwiseSurge::new(tiers);
+        assert_eq!(strategy.multiplier(&ctx(1, 1)), 1.0);
+        assert_eq!(strategy.multiplier(&ctx(3, 2)), 1.2);
+        assert_eq!(strategy.multiplier(&ctx(5, 2)), 1.5);
+        assert_eq!(strategy.multiplier(&ctx(9, 3)), 2.0);
+        assert_eq!(strategy.multiplier(&ctx(4, 0)), 2.0);
+    }
+
+    #[test]
+    fn pid_raises_multiplier_when_waits_exceed_target() {
+        let mut strategy = PidTargetWaitSurge::new(60_000, 0.5, 0.1, 0.0);
+        let mut long_wait = ctx(1, 1);
+        long_wait.eta_ms = 120_000;
+        let first = strategy.multiplier(&long_wait);
+        let second = strategy.multiplier(&long_wait);
+        assert!(first > 1.0);
+        assert!(second >= first, "integral term should accumulate");
+
+        let mut short_wait = ctx(1, 1);
+        short_wait.eta_ms = 10_000;
+        let mut fresh = PidTargetWaitSurge::new(60_000, 0.5, 0.1, 0.0);
+        assert_eq!(fresh.multiplier(&short_wait), 1.0);
+    }
+
+    #[test]
+    fn pid_preview_matches_next_quote_without_updating_state() {
+        let mut strategy = PidTargetWaitSurge::new(60_000, 0.5, 0.1, 0.2);
+        let mut long_wait = ctx(1, 1);
+        long_wait.eta_ms = 120_000;
+        strategy.multiplier(&long_wait);
+
+        let preview = strategy.preview(&long_wait);
+        assert_eq!(strategy.preview(&long_wait), preview);
+        assert_eq!(strategy.multiplier(&long_wait), preview);
+    }
+
+    #[test]
+    fn scheduled_applies_window_for_hour_of_day() {
+        let mut strategy = ScheduledSurge::new(vec![ScheduledSurgeWindow {
+            start_hour: 22,
+            end_hour: 2,
+            multiplier: 1.8,
+        }]);
+        let mut late = ctx(0, 0);
+        late.now_ms = 23 * 3_600_000;
+        assert_eq!(strategy.multiplier(&late), 1.8);
+        let mut noon = ctx(0, 0);
+        noon.now_ms = 12 * 3_600_000;
+        assert_eq!(strategy.multiplier(&noon), 1.0);
+    }
+
+    #[test]
+    fn kind_names_round_trip() {
+        for kind in [
+            SurgeStrategyKind::SupplyDemandRatio,
+            SurgeStrategyKind::default_stepwise(),
+            SurgeStrategyKind::default_pid(),
+            SurgeStrategyKind::default_scheduled(),
+        ] {
+            assert_eq!(SurgeStrategyKind::from_name(kind.name()), Some(kind));
+        }
+    }
+}
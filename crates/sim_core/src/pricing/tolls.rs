@@ -0,0 +1,186 @@
+//! Toll roads and zone-entry fees: pass-through charges added on top of the fare.
+//!
+//! A [`TollZone`] is a set of H3 cells (a cordon area or the cells along a toll road) with a
+//! fee charged each time a trip route enters it. A route starting inside a zone counts as one
+//! entry. Tolls are route-aware: the quote prices the pickup → dropoff route from the active
+//! route provider (falling back to the H3 grid path), and trip completion charges the route
+//! actually driven, reroutes included.
+//!
+//! Tolls are collected from the rider but are not commissioned and not paid to the driver;
+//! they are accounted separately (`CompletedTripRecord::tolls`, `SimTelemetry::tolls_collected_total`).
+
+use std::collections::BTreeSet;
+
+use bevy_ecs::prelude::Resource;
+use h3o::{CellIndex, LatLng, Resolution};
+use serde::{Deserialize, Serialize};
+
+use crate::routing::RouteProvider;
+use crate::spatial::grid_path_cells_cached;
+
+/// One toll zone or toll road segment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TollZone {
+    /// Human-readable name (for exports and debugging).
+    pub name: String,
+    /// Cells covered by the zone / road. Ordered, so a zone always serializes the same way.
+    #[serde(with = "crate::run_bundle::cell_set")]
+    pub cells: BTreeSet<CellIndex>,
+    /// Fee charged per entry, in currency units.
+    pub entry_fee: f64,
+}
+
+impl TollZone {
+    /// Zone covering every cell within `radius_k` of `center`.
+    pub fn disk(name: impl Into<String>, center: CellIndex, radius_k: u32, entry_fee: f64) -> Self {
+        Self {
+            name: name.into(),
+            cells: center.grid_disk::<Vec<_>>(radius_k).into_iter().collect(),
+            entry_fee,
+        }
+    }
+
+    /// Toll road segment covering the grid path between `from` and `to`.
+    pub fn segment(
+        name: impl Into<String>,
+        from: CellIndex,
+        to: CellIndex,
+        entry_fee: f64,
+    ) -> Self {
+        let cells = grid_path_cells_cached(from, to)
+            .unwrap_or_else(|| vec![from, to])
+            .into_iter()
+            .collect();
+        Self {
+            name: name.into(),
+            cells,
+            entry_fee,
+        }
+    }
+
+    /// Number of times the ordered route `cells` enters this zone.
+    pub fn entries(&self, cells: &[CellIndex]) -> usize {
+        let mut inside = false;
+        let mut entries = 0;
+        for cell in cells {
+            let now_inside = self.cells.contains(cell);
+            if now_inside && !inside {
+                entries += 1;
+            }
+            inside = now_inside;
+        }
+        entries
+    }
+}
+
+/// Configured toll zones. Empty by default (no tolls).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Resource)]
+pub struct TollZones {
+    pub zones: Vec<TollZone>,
+}
+
+impl TollZones {
+    pub fn with_zone(mut self, zone: TollZone) -> Self {
+        self.zones.push(zone);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// Total toll for an ordered list of route cells.
+    pub fn toll_for_cells(&self, cells: &[CellIndex]) -> f64 {
+        self.zones
+            .iter()
+            .map(|zone| zone.entries(cells) as f64 * zone.entry_fee)
+            .sum()
+    }
+
+    /// Total toll for a driven path, snapped to cells at `resolution`.
+    pub fn toll_for_points(&self, points: &[LatLng], resolution: Resolution) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let mut cells: Vec<CellIndex> = points
+            .iter()
+            .map(|point| point.to_cell(resolution))
+            .collect();
+        cells.dedup();
+        self.toll_for_cells(&cells)
+    }
+
+    /// Total toll for the pickup → dropoff route. Uses `provider` when given, otherwise (or if
+    /// it finds no route) the H3 grid path.
+    pub fn toll_for_trip(
+        &self,
+        pickup: CellIndex,
+        dropoff: CellIndex,
+        provider: Option<&dyn RouteProvider>,
+    ) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let cells = provider
+            .and_then(|provider| provider.route(pickup, dropoff))
+            .map(|route| route.cells)
+            .filter(|cells| !cells.is_empty())
+            .or_else(|| grid_path_cells_cached(pickup, dropoff))
+            .unwrap_or_else(|| vec![pickup, dropoff]);
+        self.toll_for_cells(&cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin() -> CellIndex {
+        CellIndex::try_from(0x8a1fb46622dffff).expect("cell")
+    }
+
+    fn far_cell(from: CellIndex, k: u32) -> CellIndex {
+        from.grid_disk::<Vec<_>>(k)
+            .into_iter()
+            .find(|cell| from.grid_distance(*cell) == Ok(k as i32))
+            .expect("cell at distance k")
+    }
+
+    #[test]
+    fn counts_each_entry_into_zone() {
+        let a = origin();
+        let zone = TollZone::disk("center", a, 0, 2.5);
+        let b = far_cell(a, 1);
+        assert_eq!(zone.entries(&[b, a, b, a]), 2);
+        assert_eq!(zone.entries(&[a, b]), 1);
+        assert_eq!(zone.entries(&[b]), 0);
+    }
+
+    #[test]
+    fn trip_toll_uses_grid_path_when_no_provider() {
+        let pickup = origin();
+        let dropoff = far_cell(pickup, 4);
+        let path = grid_path_cells_cached(pickup, dropoff).expect("path");
+        let middle = path[path.len() / 2];
+        let zones = TollZones::default().with_zone(TollZone::disk("bridge", middle, 0, 3.0));
+        assert_eq!(zones.toll_for_trip(pickup, dropoff, None), 3.0);
+        assert_eq!(
+            TollZones::default().toll_for_trip(pickup, dropoff, None),
+            0.0
+        );
+    }
+
+    #[test]
+    fn driven_toll_counts_the_cells_the_points_pass() {
+        let pickup = origin();
+        let detour = far_cell(pickup, 3);
+        let dropoff = far_cell(pickup, 1);
+        let zones = TollZones::default().with_zone(TollZone::disk("bridge", detour, 0, 3.0));
+        let points: Vec<LatLng> = [pickup, detour, dropoff]
+            .into_iter()
+            .map(LatLng::from)
+            .collect();
+        assert_eq!(zones.toll_for_points(&points, Resolution::Ten), 3.0);
+        assert_eq!(zones.toll_for_points(&points[..1], Resolution::Ten), 0.0);
+    }
+}
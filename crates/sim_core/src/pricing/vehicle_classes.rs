@@ -0,0 +1,195 @@
+//! Vehicle classes and comparison shopping across them.
+//!
+//! With a non-empty [`VehicleClassMenu`], `show_quote_system` prices every class for the trip
+//! (surged base fare × `fare_multiplier`, ETA × `eta_multiplier`) and the rider picks one with a
+//! multinomial logit on price, ETA, and a per-class constant. The chosen class's fare and ETA
+//! become the rider's `RiderQuote`; the usual accept/reject decision follows. The first class is
+//! the rider's default, so picking any other class counts as a substitution.
+//!
+//! A class with `dispatch_delay_secs > 0` is a wait-and-save product: the rider accepts a longer
+//! matching window (held out of matching for the delay) in exchange for a discount.
+
+use bevy_ecs::prelude::Resource;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// One product offered to riders (e.g. "standard", "pool", "premium").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VehicleClass {
+    pub name: String,
+    /// Multiplier on the surged base fare (tolls are added unscaled).
+    pub fare_multiplier: f64,
+    /// Multiplier on the pickup ETA (pooled rides wait longer).
+    pub eta_multiplier: f64,
+    /// Class-specific utility constant (comfort, detours, brand).
+    pub utility_constant: f64,
+    /// Deferred dispatch window; matching waits this long after the quote is accepted.
+    #[serde(default)]
+    pub dispatch_delay_secs: u64,
+}
+
+impl VehicleClass {
+    pub fn new(name: impl Into<String>, fare_multiplier: f64, eta_multiplier: f64) -> Self {
+        Self {
+            name: name.into(),
+            fare_multiplier,
+            eta_multiplier,
+            utility_constant: 0.0,
+            dispatch_delay_secs: 0,
+        }
+    }
+
+    /// Wait-and-save class: `discount` off the standard fare for a `dispatch_delay_secs` window.
+    pub fn wait_and_save(discount: f64, dispatch_delay_secs: u64) -> Self {
+        Self {
+            name: "wait_and_save".to_string(),
+            fare_multiplier: (1.0 - discount).max(0.0),
+            eta_multiplier: 1.0,
+            utility_constant: 0.0,
+            dispatch_delay_secs,
+        }
+    }
+
+    pub fn with_utility_constant(mut self, utility_constant: f64) -> Self {
+        self.utility_constant = utility_constant;
+        self
+    }
+}
+
+/// Quote for one class, as compared by the rider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClassQuote {
+    pub fare: f64,
+    pub eta_ms: u64,
+}
+
+/// Classes offered to riders and the utility coefficients used to choose between them.
+/// Empty by default (single quote, no comparison shopping).
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+pub struct VehicleClassMenu {
+    pub classes: Vec<VehicleClass>,
+    /// Utility per currency unit of fare (negative: cheaper classes are preferred).
+    pub price_coefficient: f64,
+    /// Utility per minute of pickup ETA (negative: faster classes are preferred).
+    pub eta_coefficient_per_min: f64,
+    /// Seed of the riders' class choices; set by `build_scenario` from the
+    /// `vehicle_class_choice` stream.
+    #[serde(skip)]
+    pub seed: u64,
+}
+
+impl VehicleClassMenu {
+    /// Standard / pool / premium menu with moderate price and ETA sensitivity.
+    pub fn standard_pool_premium() -> Self {
+        Self {
+            classes: vec![
+                VehicleClass::new("standard", 1.0, 1.0),
+                VehicleClass::new("pool", 0.7, 1.5).with_utility_constant(-0.5),
+                VehicleClass::new("premium", 1.6, 0.8).with_utility_constant(0.3),
+            ],
+            price_coefficient: -0.15,
+            eta_coefficient_per_min: -0.1,
+            seed: 0,
+        }
+    }
+
+    pub fn with_class(mut self, class: VehicleClass) -> Self {
+        self.classes.push(class);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Quote every class given the surged base fare (without tolls), tolls, and base ETA.
+    /// Deferred-dispatch windows are included in the class ETA.
+    pub fn quotes(&self, surged_fare: f64, tolls: f64, eta_ms: u64) -> Vec<ClassQuote> {
+        self.classes
+            .iter()
+            .map(|class| ClassQuote {
+                fare: surged_fare * class.fare_multiplier + tolls,
+                eta_ms: (eta_ms as f64 * class.eta_multiplier).round() as u64
+                    + class.dispatch_delay_secs * 1000,
+            })
+            .collect()
+    }
+
+    /// Logit choice probabilities for `quotes` (same order as `classes`).
+    pub fn choice_probabilities(&self, quotes: &[ClassQuote]) -> Vec<f64> {
+        let utilities: Vec<f64> = self
+            .classes
+            .iter()
+            .zip(quotes)
+            .map(|(class, quote)| {
+                class.utility_constant
+                    + self.price_coefficient * quote.fare
+                    + self.eta_coefficient_per_min * quote.eta_ms as f64 / 60_000.0
+            })
+            .collect();
+        let max = utilities.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = utilities.iter().map(|u| (u - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+        weights.iter().map(|w| w / total).collect()
+    }
+
+    /// Sample the chosen class index. Returns None for an empty menu.
+    pub fn choose<R: Rng>(&self, quotes: &[ClassQuote], rng: &mut R) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let probabilities = self.choice_probabilities(quotes);
+        let draw = rng.gen::<f64>();
+        let mut cumulative = 0.0;
+        for (index, probability) in probabilities.iter().enumerate() {
+            cumulative += probability;
+            if draw < cumulative {
+                return Some(index);
+            }
+        }
+        Some(probabilities.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn quotes_scale_fare_but_not_tolls() {
+        let menu = VehicleClassMenu::standard_pool_premium();
+        let quotes = menu.quotes(10.0, 2.0, 60_000);
+        assert_eq!(quotes[0].fare, 12.0);
+        assert!((quotes[1].fare - 9.0).abs() < 1e-9);
+        assert_eq!(quotes[1].eta_ms, 90_000);
+    }
+
+    #[test]
+    fn wait_and_save_quote_is_discounted_and_includes_window() {
+        let menu = VehicleClassMenu::default()
+            .with_class(VehicleClass::new("standard", 1.0, 1.0))
+            .with_class(VehicleClass::wait_and_save(0.25, 300));
+        let quotes = menu.quotes(20.0, 0.0, 60_000);
+        assert_eq!(quotes[1].fare, 15.0);
+        assert_eq!(quotes[1].eta_ms, 360_000);
+    }
+
+    #[test]
+    fn cheaper_class_gains_share_with_price_sensitivity() {
+        let mut menu = VehicleClassMenu::default()
+            .with_class(VehicleClass::new("standard", 1.0, 1.0))
+            .with_class(VehicleClass::new("pool", 0.5, 1.0));
+        menu.price_coefficient = -0.5;
+        let probabilities = menu.choice_probabilities(&menu.quotes(20.0, 0.0, 60_000));
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(probabilities[1] > probabilities[0]);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!(menu
+            .choose(&menu.quotes(20.0, 0.0, 60_000), &mut rng)
+            .is_some());
+        assert_eq!(VehicleClassMenu::default().choose(&[], &mut rng), None);
+    }
+}
@@ -1,11 +1,20 @@
-//! Performance profiling infrastructure: system timing, event rate tracking, and metrics collection.
+//! Performance profiling infrastructure: system timing, event rate tracking, world growth
+//! sampling, and metrics collection.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::mem::size_of;
 use std::time::{Duration, Instant};
 
+use bevy_ecs::archetype::Archetypes;
+use bevy_ecs::component::Components;
+use bevy_ecs::entity::Entities;
 use bevy_ecs::prelude::Resource;
 
-use crate::clock::EventKind;
+use crate::clock::{Event, EventKind};
+use crate::telemetry::{
+    CellAggregate, CompletedTripRecord, DriverSnapshot, RiderSnapshot, SimSnapshots, SimTelemetry,
+    TripSnapshot,
+};
 
 /// Per-system timing metrics.
 #[derive(Debug, Clone, Default)]
@@ -76,7 +85,7 @@ impl SystemTimings {
     pub fn print_summary(&self) {
         println!("\n=== System Timing Summary ===");
         let mut entries: Vec<_> = self.timings.iter().collect();
-        entries.sort_by(|a, b| b.1.total_duration.cmp(&a.1.total_duration));
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.1.total_duration));
 
         for (name, timing) in entries {
             println!(
@@ -146,6 +155,148 @@ impl EventMetrics {
     }
 }
 
+/// World size at one snapshot: entity counts by archetype, event queue length and an
+/// approximate heap footprint.
+///
+/// Heap figures are estimates from type sizes (component layouts, queued events, retained
+/// telemetry records and snapshots), not allocator measurements: they ignore allocator
+/// overhead, spare capacity and heap data owned by components, but grow with the same
+/// things that make a long run run out of memory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorldGrowthSample {
+    pub timestamp_ms: u64,
+    /// Live entities in the world.
+    pub entities: u64,
+    /// Entity count per non-empty archetype, keyed by its component names joined with `+`.
+    pub archetypes: BTreeMap<String, u64>,
+    /// Events pending in the simulation clock.
+    pub event_queue_len: u64,
+    /// Estimated bytes of component data.
+    pub component_bytes: u64,
+    /// Estimated bytes of queued events.
+    pub event_queue_bytes: u64,
+    /// Estimated bytes of retained telemetry records and snapshots.
+    pub telemetry_bytes: u64,
+}
+
+impl WorldGrowthSample {
+    pub fn collect(
+        timestamp_ms: u64,
+        archetypes: &Archetypes,
+        components: &Components,
+        entities: &Entities,
+        event_queue_len: usize,
+    ) -> Self {
+        let mut sample = Self {
+            timestamp_ms,
+            entities: u64::from(entities.len()),
+            event_queue_len: event_queue_len as u64,
+            event_queue_bytes: (event_queue_len * size_of::<Event>()) as u64,
+            ..Default::default()
+        };
+        for archetype in archetypes.iter().filter(|archetype| !archetype.is_empty()) {
+            let mut names = Vec::new();
+            let mut row_bytes = 0;
+            for info in archetype
+                .components()
+                .filter_map(|id| components.get_info(id))
+            {
+                names.push(short_type_name(info.name()));
+                row_bytes += info.layout().size();
+            }
+            names.sort_unstable();
+            let key = if names.is_empty() {
+                "(empty)".to_string()
+            } else {
+                names.join("+")
+            };
+            let len = archetype.len() as u64;
+            *sample.archetypes.entry(key).or_insert(0) += len;
+            sample.component_bytes += len * row_bytes as u64;
+        }
+        sample
+    }
+
+    /// Add the retained telemetry records and snapshot buffer to `telemetry_bytes`.
+    pub fn add_retained(&mut self, telemetry: &SimTelemetry, snapshots: &SimSnapshots) {
+        let mut bytes = telemetry.completed_trips.len() * size_of::<CompletedTripRecord>();
+        for snapshot in &snapshots.snapshots {
+            bytes += snapshot.riders.len() * size_of::<RiderSnapshot>()
+                + snapshot.drivers.len() * size_of::<DriverSnapshot>()
+                + snapshot.trips.len() * size_of::<TripSnapshot>()
+                + snapshot.cells.len() * size_of::<CellAggregate>();
+        }
+        self.telemetry_bytes += bytes as u64;
+    }
+
+    /// Total estimated heap bytes.
+    pub fn approx_heap_bytes(&self) -> u64 {
+        self.component_bytes + self.event_queue_bytes + self.telemetry_bytes
+    }
+}
+
+/// `sim_core::ecs::Position` -> `Position`; generic arguments keep their own short names.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment_start = 0;
+    for (index, ch) in name.char_indices() {
+        match ch {
+            ':' => segment_start = index + 1,
+            '<' | '>' | ',' | ' ' => {
+                short.push_str(&name[segment_start..index]);
+                short.push(ch);
+                segment_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    short.push_str(&name[segment_start..]);
+    short
+}
+
+/// World growth over the run: the latest sample and the peaks seen so far.
+///
+/// Present only when `SimSnapshotConfig::include_world_growth` is set; updated each time a
+/// snapshot is captured, so the snapshots themselves hold the time series.
+#[derive(Debug, Default, Resource)]
+pub struct WorldGrowthMetrics {
+    pub latest: Option<WorldGrowthSample>,
+    pub samples: u64,
+    pub peak_entities: u64,
+    pub peak_event_queue_len: u64,
+    pub peak_heap_bytes: u64,
+}
+
+impl WorldGrowthMetrics {
+    pub fn record(&mut self, sample: WorldGrowthSample) {
+        self.samples += 1;
+        self.peak_entities = self.peak_entities.max(sample.entities);
+        self.peak_event_queue_len = self.peak_event_queue_len.max(sample.event_queue_len);
+        self.peak_heap_bytes = self.peak_heap_bytes.max(sample.approx_heap_bytes());
+        self.latest = Some(sample);
+    }
+
+    /// Print summary statistics.
+    pub fn print_summary(&self) {
+        println!("\n=== World Growth Summary ===");
+        println!("Samples: {}", self.samples);
+        println!("Peak entities: {}", self.peak_entities);
+        println!("Peak event queue length: {}", self.peak_event_queue_len);
+        println!(
+            "Peak approx heap: {:.2} MiB",
+            self.peak_heap_bytes as f64 / (1024.0 * 1024.0)
+        );
+        if let Some(latest) = &self.latest {
+            println!("\nLatest archetypes by entity count:");
+            let mut entries: Vec<_> = latest.archetypes.iter().collect();
+            entries.sort_by(|a, b| b.1.cmp(a.1));
+            for (archetype, count) in entries.into_iter().take(10) {
+                println!("  {:8} : {}", count, archetype);
+            }
+        }
+    }
+}
+
 /// Helper macro to time a system execution.
 #[macro_export]
 macro_rules! time_system {
@@ -0,0 +1,174 @@
+//! Provenance stamped on exports: which engine produced a file, from which scenario.
+//!
+//! Every Parquet / Arrow IPC table written by `telemetry_export` carries the crate versions, the
+//! git commit the engine was built from and the scenario hash twice: as file metadata
+//! (key-value metadata in Parquet, schema metadata in both formats) and as constant columns, so
+//! the stamp survives concatenating tables from many runs.
+//!
+//! The git commit is read by the build script (`git rev-parse HEAD`, overridable with the
+//! `SIM_GIT_SHA` environment variable at build time) and is `unknown` outside a checkout.
+
+use bevy_ecs::prelude::World;
+
+use crate::run_bundle::ScenarioRecord;
+
+/// Git commit `sim_core` was built from, or `unknown`.
+pub const GIT_SHA: &str = env!("SIM_GIT_SHA");
+
+/// Metadata key / column name of the git commit.
+pub const GIT_SHA_KEY: &str = "git_sha";
+
+/// Metadata key / column name of the scenario hash (see [`ScenarioRecord::scenario_hash`]).
+pub const SCENARIO_HASH_KEY: &str = "scenario_hash";
+
+/// Crate versions, git commit and scenario hash of one export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportProvenance {
+    /// `(crate name, version)`, `sim_core` first.
+    pub crate_versions: Vec<(String, String)>,
+    pub git_sha: String,
+    /// `None` when the data did not come from a single `build_scenario` run.
+    pub scenario_hash: Option<String>,
+}
+
+impl ExportProvenance {
+    /// This build of `sim_core`, without a scenario.
+    pub fn current() -> Self {
+        Self {
+            crate_versions: vec![(
+                "sim_core".to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            )],
+            git_sha: GIT_SHA.to_string(),
+            scenario_hash: None,
+        }
+    }
+
+    /// This build of `sim_core` and the scenario `world` was built from (the `ScenarioRecord`
+    /// inserted by `build_scenario`, when present).
+    pub fn for_world(world: &World) -> Self {
+        Self {
+            scenario_hash: world
+                .get_resource::<ScenarioRecord>()
+                .and_then(ScenarioRecord::scenario_hash),
+            ..Self::current()
+        }
+    }
+
+    /// Also record the version of a downstream crate (e.g. `sim_experiments`).
+    pub fn with_crate_version(mut self, name: &str, version: &str) -> Self {
+        self.crate_versions
+            .push((name.to_string(), version.to_string()));
+        self
+    }
+
+    pub fn with_scenario_hash(mut self, scenario_hash: impl Into<String>) -> Self {
+        self.scenario_hash = Some(scenario_hash.into());
+        self
+    }
+
+    /// `(key, value)` pairs in column order: `<crate>_version` per crate, then `git_sha` and
+    /// `scenario_hash` (`None` when unknown).
+    pub fn entries(&self) -> Vec<(String, Option<String>)> {
+        let mut entries: Vec<(String, Option<String>)> = self
+            .crate_versions
+            .iter()
+            .map(|(name, version)| (format!("{name}_version"), Some(version.clone())))
+            .collect();
+        entries.push((GIT_SHA_KEY.to_string(), Some(self.git_sha.clone())));
+        entries.push((SCENARIO_HASH_KEY.to_string(), self.scenario_hash.clone()));
+        entries
+    }
+
+    /// File metadata: the known [`entries`](Self::entries).
+    pub fn metadata(&self) -> Vec<(String, String)> {
+        self.entries()
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use h3o::CellIndex;
+
+    use super::*;
+    use crate::pricing::tolls::{TollZone, TollZones};
+    use crate::scenario::{build_scenario, ScenarioParams};
+
+    fn hash_of(params: ScenarioParams) -> String {
+        ScenarioRecord::from_params(&params)
+            .scenario_hash()
+            .expect("scenario hash")
+    }
+
+    /// Toll zones with the given names and fees over disjoint cells, each zone's cells
+    /// inserted forwards or backwards.
+    fn tolls(zones: &[(&str, f64)], reversed: bool) -> ScenarioParams {
+        let center = CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+        let cells: Vec<CellIndex> = center.grid_disk(2);
+        let zones = zones.iter().zip(cells.chunks(4)).fold(
+            TollZones::default(),
+            |tolls, ((name, fee), chunk)| {
+                let mut zone_cells = chunk.to_vec();
+                if reversed {
+                    zone_cells.reverse();
+                }
+                tolls.with_zone(TollZone {
+                    name: name.to_string(),
+                    cells: zone_cells.into_iter().collect(),
+                    entry_fee: *fee,
+                })
+            },
+        );
+        ScenarioParams::default().with_toll_zones(zones)
+    }
+
+    #[test]
+    fn for_world_hashes_the_built_scenario() {
+        let mut world = World::new();
+        build_scenario(&mut world, ScenarioParams::default().with_seed(3));
+        let provenance = ExportProvenance::for_world(&world);
+        let hash = provenance.scenario_hash.clone().expect("scenario hash");
+        assert_eq!(hash.len(), 16);
+
+        let mut other = World::new();
+        build_scenario(&mut other, ScenarioParams::default().with_seed(4));
+        assert_ne!(
+            ExportProvenance::for_world(&other).scenario_hash,
+            Some(hash)
+        );
+        assert_eq!(
+            ExportProvenance::for_world(&World::new()).scenario_hash,
+            None
+        );
+
+        let keys: Vec<String> = provenance
+            .with_crate_version("sim_experiments", "9.9.9")
+            .metadata()
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "sim_core_version",
+                "sim_experiments_version",
+                GIT_SHA_KEY,
+                SCENARIO_HASH_KEY
+            ]
+        );
+    }
+
+    #[test]
+    fn scenario_hash_ignores_insertion_order_and_tells_apart_moved_values() {
+        let zones = [("a", 1.0), ("b", 2.0)];
+        assert_eq!(hash_of(tolls(&zones, false)), hash_of(tolls(&zones, true)));
+        // Same values in a different place; the sorted `Debug` lines of both are equal.
+        assert_ne!(
+            hash_of(tolls(&zones, false)),
+            hash_of(tolls(&[("a", 2.0), ("b", 1.0)], false))
+        );
+    }
+}
@@ -4,7 +4,10 @@
 //!
 //! - **`H3GridRouteProvider`**: Existing H3 grid-path + Haversine behavior. Zero dependencies.
 //! - **`OsrmRouteProvider`** (feature `osrm`): Calls a local/remote OSRM HTTP endpoint.
-//! - **`PrecomputedRouteProvider`** (feature `precomputed`): Loads a serialized route table from disk.
+//!   Bulk lookups ([`RouteProvider::route_costs`]) go through the batched async
+//!   `osrm_table::OsrmTableClient` instead.
+//! - **`PrecomputedRouteProvider`** (feature `precomputed`): Loads a serialized route table from disk
+//!   (see [`precomputed::RouteTable`] for the binary format and its embedded metadata).
 //!
 //! The provider is stored as a `Box<dyn RouteProvider>` ECS resource, constructed from
 //! `RouteProviderKind` during scenario building.
@@ -37,6 +40,30 @@ mod cell_vec_serde {
     }
 }
 
+/// Functional road class of a route segment, used for class-aware speeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RoadClass {
+    Residential,
+    Arterial,
+    Highway,
+}
+
+impl RoadClass {
+    /// Map an OSM `highway` tag or Valhalla `road_class` (e.g. `"motorway"`, `"primary"`,
+    /// `"residential"`) to a road class.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "motorway" | "motorway_link" | "trunk" | "trunk_link" => Some(Self::Highway),
+            "primary" | "primary_link" | "secondary" | "secondary_link" | "tertiary"
+            | "tertiary_link" => Some(Self::Arterial),
+            "residential" | "unclassified" | "living_street" | "service" | "service_other" => {
+                Some(Self::Residential)
+            }
+            _ => None,
+        }
+    }
+}
+
 /// Result of a route query between two H3 cells.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RouteResult {
@@ -49,6 +76,26 @@ pub struct RouteResult {
     /// H3 cells along the route used for step-by-step movement.
     #[serde(with = "cell_vec_serde")]
     pub cells: Vec<CellIndex>,
+    /// Road class of each waypoint segment (`waypoints.len() - 1` entries); empty when the
+    /// provider does not annotate classes.
+    #[serde(default)]
+    pub road_classes: Vec<RoadClass>,
+}
+
+/// Road-network distance and free-flow duration between two cells, without the geometry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RouteCost {
+    pub distance_km: f64,
+    pub duration_secs: f64,
+}
+
+impl From<&RouteResult> for RouteCost {
+    fn from(route: &RouteResult) -> Self {
+        Self {
+            distance_km: route.distance_km,
+            duration_secs: route.duration_secs,
+        }
+    }
 }
 
 /// Which routing backend to use. Stored in [`ScenarioParams`] so it serializes
@@ -58,9 +105,14 @@ pub enum RouteProviderKind {
     /// Existing H3 grid-path behaviour, zero external dependencies.
     #[default]
     H3Grid,
-    /// OSRM HTTP endpoint (e.g. `"http://localhost:5000"`).
+    /// OSRM HTTP endpoint (e.g. `"http://localhost:5000"`), optionally recording responses
+    /// to or replaying them from an offline cache file.
     #[cfg(feature = "osrm")]
-    Osrm { endpoint: String },
+    Osrm {
+        endpoint: String,
+        #[serde(default)]
+        cache: osrm_cache::OsrmCacheMode,
+    },
     /// Pre-computed route table loaded from a binary file at startup.
     #[cfg(feature = "precomputed")]
     Precomputed { path: String },
@@ -71,6 +123,15 @@ pub enum RouteProviderKind {
 pub trait RouteProvider: Send + Sync {
     /// Compute a route between two H3 cells. Returns `None` if no route exists.
     fn route(&self, from: CellIndex, to: CellIndex) -> Option<RouteResult>;
+
+    /// Distance and duration for many cell pairs at once, in `pairs` order (`None` where no
+    /// route exists). Routes each pair by default; backends with a bulk service override it.
+    fn route_costs(&self, pairs: &[(CellIndex, CellIndex)]) -> Vec<Option<RouteCost>> {
+        pairs
+            .iter()
+            .map(|&(from, to)| self.route(from, to).as_ref().map(RouteCost::from))
+            .collect()
+    }
 }
 
 /// ECS resource wrapping a boxed route provider.
@@ -100,6 +161,7 @@ impl RouteProvider for H3GridRouteProvider {
             distance_km,
             duration_secs,
             cells,
+            road_classes: Vec::new(),
         })
     }
 }
@@ -110,14 +172,25 @@ impl RouteProvider for H3GridRouteProvider {
 
 #[cfg(feature = "osrm")]
 pub mod osrm {
+    use super::osrm_cache::{self, OsrmResponseCache};
+    use super::osrm_table::{OsrmTableClient, OsrmTableConfig};
     use super::*;
+    use crate::telemetry::OsrmTableTelemetry;
     use reqwest::blocking::Client;
+    use reqwest::Url;
+    use std::sync::{Arc, OnceLock};
     use std::time::Duration;
+    use tokio::runtime::Runtime;
 
-    /// Routes via an OSRM HTTP endpoint.
+    /// Routes via an OSRM HTTP endpoint: single routes with blocking `/route` calls, bulk
+    /// lookups with the batched `/table` client.
     pub struct OsrmRouteProvider {
         client: Client,
         endpoint: String,
+        cache: Option<Arc<OsrmResponseCache>>,
+        table: OsrmTableClient,
+        /// Drives `table` for bulk lookups; built on first use.
+        runtime: OnceLock<Runtime>,
     }
 
     impl OsrmRouteProvider {
@@ -129,8 +202,28 @@ pub mod osrm {
             Self {
                 client,
                 endpoint: endpoint.trim_end_matches('/').to_string(),
+                cache: None,
+                table: OsrmTableClient::new(endpoint, OsrmTableConfig::default()),
+                runtime: OnceLock::new(),
             }
         }
+
+        /// Record responses to, or replay them from, an offline cache.
+        pub fn with_cache(mut self, cache: Option<Arc<OsrmResponseCache>>) -> Self {
+            self.cache = cache;
+            self
+        }
+
+        /// Tune the `/table` client used for bulk lookups.
+        pub fn with_table_config(mut self, config: OsrmTableConfig) -> Self {
+            self.table = OsrmTableClient::new(&self.endpoint, config);
+            self
+        }
+
+        /// Request, retry and latency counters of the bulk `/table` lookups.
+        pub fn table_telemetry(&self) -> Arc<OsrmTableTelemetry> {
+            self.table.telemetry()
+        }
     }
 
     /// Minimal OSRM JSON response structures.
@@ -166,13 +259,9 @@ pub mod osrm {
                 to_ll.lat(),
             );
 
-            let resp: OsrmResponse = match self.client.get(&url).send() {
-                Ok(r) => match r.json() {
-                    Ok(j) => j,
-                    Err(_) => return None,
-                },
-                Err(_) => return None,
-            };
+            let url = Url::parse(&url).ok()?;
+            let body = osrm_cache::fetch(&self.client, self.cache.as_deref(), url).ok()?;
+            let resp: OsrmResponse = serde_json::from_str(&body).ok()?;
 
             if resp.code != "Ok" {
                 return None;
@@ -210,14 +299,41 @@ pub mod osrm {
                 distance_km: route.distance / 1000.0,
                 duration_secs: route.duration,
                 cells: deduped_cells,
+                road_classes: Vec::new(),
             })
         }
+
+        /// Batched `/table` requests. With a response cache the pairs are routed one by one
+        /// instead, so recorded runs replay without a server.
+        fn route_costs(&self, pairs: &[(CellIndex, CellIndex)]) -> Vec<Option<RouteCost>> {
+            if self.cache.is_some() {
+                return pairs
+                    .iter()
+                    .map(|&(from, to)| self.route(from, to).as_ref().map(RouteCost::from))
+                    .collect();
+            }
+            let runtime = self.runtime.get_or_init(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to build OSRM table runtime")
+            });
+            runtime.block_on(self.table.route_costs(pairs))
+        }
     }
 }
 
+pub mod polyline;
+
+#[cfg(feature = "osrm")]
+pub mod osrm_cache;
+
 #[cfg(feature = "osrm")]
 pub mod osrm_spawn;
 
+#[cfg(feature = "osrm")]
+pub mod osrm_table;
+
 // ---------------------------------------------------------------------------
 // Pre-computed provider (behind `precomputed` feature)
 // ---------------------------------------------------------------------------
@@ -239,30 +355,215 @@ pub mod precomputed {
         }
     }
 
+    /// Magic bytes at the start of a route table file.
+    pub const ROUTE_TABLE_MAGIC: &[u8; 4] = b"RHRT";
+    /// Current route table format version, stored right after the magic bytes.
+    /// Version 2 added per-segment road classes to routes.
+    pub const ROUTE_TABLE_FORMAT_VERSION: u32 = 2;
+
+    /// Lat/lng bounding box the table's cells were sampled from.
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct RegionBounds {
+        pub lat_min: f64,
+        pub lat_max: f64,
+        pub lng_min: f64,
+        pub lng_max: f64,
+    }
+
+    /// Provenance embedded in a route table file.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct RouteTableMetadata {
+        /// Provider the routes were computed with (e.g. `"H3GridRouteProvider"`).
+        pub provider: String,
+        /// OSRM version of the routing backend; `None` when OSRM was not used.
+        pub osrm_version: Option<String>,
+        pub bounds: RegionBounds,
+        /// Seed of the origin/destination cell sample.
+        pub sample_seed: u64,
+        pub sample_cells: usize,
+    }
+
+    /// A route table with its metadata, as stored on disk.
+    ///
+    /// Layout: [`ROUTE_TABLE_MAGIC`], [`ROUTE_TABLE_FORMAT_VERSION`] as little-endian `u32`,
+    /// then the bincode-encoded metadata and routes. Version 1 files and files without the
+    /// magic prefix (the original format, a bare bincode map of cell pairs to routes) are
+    /// read with routes lacking road classes.
+    pub struct RouteTable {
+        pub metadata: Option<RouteTableMetadata>,
+        pub routes: HashMap<CellPair, RouteResult>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct RouteTableBody<R> {
+        metadata: RouteTableMetadata,
+        routes: Vec<(CellPair, R)>,
+    }
+
+    /// [`RouteResult`] as stored before road classes were added (bincode is positional, so
+    /// older files need the old shape).
+    #[derive(Serialize, Deserialize)]
+    struct UnclassifiedRoute {
+        waypoints: Vec<(f64, f64)>,
+        distance_km: f64,
+        duration_secs: f64,
+        #[serde(with = "cell_vec_serde")]
+        cells: Vec<CellIndex>,
+    }
+
+    impl From<UnclassifiedRoute> for RouteResult {
+        fn from(route: UnclassifiedRoute) -> Self {
+            Self {
+                waypoints: route.waypoints,
+                distance_km: route.distance_km,
+                duration_secs: route.duration_secs,
+                cells: route.cells,
+                road_classes: Vec::new(),
+            }
+        }
+    }
+
+    impl From<&RouteResult> for UnclassifiedRoute {
+        fn from(route: &RouteResult) -> Self {
+            Self {
+                waypoints: route.waypoints.clone(),
+                distance_km: route.distance_km,
+                duration_secs: route.duration_secs,
+                cells: route.cells.clone(),
+            }
+        }
+    }
+
+    impl RouteTable {
+        pub fn new(metadata: RouteTableMetadata, routes: HashMap<CellPair, RouteResult>) -> Self {
+            Self {
+                metadata: Some(metadata),
+                routes,
+            }
+        }
+
+        /// Decode a route table in either the current or the original format.
+        pub fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+            let Some(rest) = data.strip_prefix(ROUTE_TABLE_MAGIC.as_slice()) else {
+                let routes: HashMap<CellPair, UnclassifiedRoute> = bincode::deserialize(data)?;
+                return Ok(Self {
+                    metadata: None,
+                    routes: routes
+                        .into_iter()
+                        .map(|(pair, route)| (pair, route.into()))
+                        .collect(),
+                });
+            };
+            let (version, body) = rest
+                .split_first_chunk::<4>()
+                .ok_or("route table header is truncated")?;
+            match u32::from_le_bytes(*version) {
+                1 => {
+                    let body: RouteTableBody<UnclassifiedRoute> = bincode::deserialize(body)?;
+                    Ok(Self {
+                        metadata: Some(body.metadata),
+                        routes: body
+                            .routes
+                            .into_iter()
+                            .map(|(pair, route)| (pair, route.into()))
+                            .collect(),
+                    })
+                }
+                ROUTE_TABLE_FORMAT_VERSION => {
+                    let body: RouteTableBody<RouteResult> = bincode::deserialize(body)?;
+                    Ok(Self {
+                        metadata: Some(body.metadata),
+                        routes: body.routes.into_iter().collect(),
+                    })
+                }
+                version => Err(format!(
+                    "unsupported route table format version {version} (expected at most {ROUTE_TABLE_FORMAT_VERSION})"
+                )
+                .into()),
+            }
+        }
+
+        /// Encode in the current format. Tables without metadata cannot be encoded.
+        pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            let metadata = self
+                .metadata
+                .clone()
+                .ok_or("route table metadata is required to write a route table")?;
+            let body = RouteTableBody {
+                metadata,
+                routes: self
+                    .routes
+                    .iter()
+                    .map(|(pair, route)| (*pair, route.clone()))
+                    .collect(),
+            };
+            let mut data = Vec::from(ROUTE_TABLE_MAGIC.as_slice());
+            data.extend_from_slice(&ROUTE_TABLE_FORMAT_VERSION.to_le_bytes());
+            data.extend(bincode::serialize(&body)?);
+            Ok(data)
+        }
+
+        pub fn read(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+            Self::from_bytes(&fs::read(path)?)
+        }
+
+        pub fn write(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+            fs::write(path, self.to_bytes()?)?;
+            Ok(())
+        }
+    }
+
     /// Pre-computed route table: a HashMap of cell-pair → RouteResult loaded from disk.
     pub struct PrecomputedRouteProvider {
         table: HashMap<CellPair, RouteResult>,
+        metadata: Option<RouteTableMetadata>,
     }
 
     impl PrecomputedRouteProvider {
-        /// Load from a bincode-serialized file.
+        /// Load a route table file (current or original format).
         pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-            let data = fs::read(path)?;
-            let table: HashMap<CellPair, RouteResult> = bincode::deserialize(&data)?;
-            Ok(Self { table })
+            Ok(Self::from_route_table(RouteTable::read(path)?))
+        }
+
+        pub fn from_route_table(table: RouteTable) -> Self {
+            Self {
+                table: table.routes,
+                metadata: table.metadata,
+            }
         }
 
         /// Create from an in-memory table (useful for tests).
         pub fn from_table(table: HashMap<CellPair, RouteResult>) -> Self {
-            Self { table }
+            Self {
+                table,
+                metadata: None,
+            }
+        }
+
+        /// Metadata embedded in the loaded file; `None` for tables in the original format.
+        pub fn metadata(&self) -> Option<&RouteTableMetadata> {
+            self.metadata.as_ref()
         }
 
-        /// Serialize the table to a file.
+        pub fn len(&self) -> usize {
+            self.table.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.table.is_empty()
+        }
+
+        /// Serialize the table to a file in the original format, without metadata or road
+        /// classes.
         pub fn save_to_file(
             table: &HashMap<CellPair, RouteResult>,
             path: &str,
         ) -> Result<(), Box<dyn std::error::Error>> {
-            let data = bincode::serialize(table)?;
+            let table: HashMap<CellPair, UnclassifiedRoute> = table
+                .iter()
+                .map(|(pair, route)| (*pair, route.into()))
+                .collect();
+            let data = bincode::serialize(&table)?;
             fs::write(path, data)?;
             Ok(())
         }
@@ -341,6 +642,43 @@ impl RouteProvider for CachedRouteProvider {
 
         result
     }
+
+    /// Cached pairs are served from the cache; the rest go to the inner provider in one bulk
+    /// lookup, with the H3 fallback for pairs it cannot route.
+    fn route_costs(&self, pairs: &[(CellIndex, CellIndex)]) -> Vec<Option<RouteCost>> {
+        let mut costs: Vec<Option<RouteCost>> = match self.cache.lock() {
+            Ok(mut cache) => pairs
+                .iter()
+                .map(|&(from, to)| {
+                    cache
+                        .get(&(u64::from(from), u64::from(to)))
+                        .map(RouteCost::from)
+                })
+                .collect(),
+            Err(_) => vec![None; pairs.len()],
+        };
+        let missing: Vec<usize> = (0..pairs.len()).filter(|&i| costs[i].is_none()).collect();
+        if missing.is_empty() {
+            return costs;
+        }
+
+        let missing_pairs: Vec<(CellIndex, CellIndex)> =
+            missing.iter().map(|&i| pairs[i]).collect();
+        for (index, cost) in missing
+            .into_iter()
+            .zip(self.inner.route_costs(&missing_pairs))
+        {
+            costs[index] = cost.or_else(|| {
+                let (from, to) = pairs[index];
+                self.fallback_to_h3
+                    .then(|| H3GridRouteProvider.route(from, to))
+                    .flatten()
+                    .as_ref()
+                    .map(RouteCost::from)
+            });
+        }
+        costs
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -356,39 +694,46 @@ const DEFAULT_ROUTE_CACHE_CAPACITY: usize = 20_000;
 /// - `H3Grid` is returned without caching (it's already fast and internally cached).
 /// - `Osrm` and `Precomputed` providers are wrapped in a [`CachedRouteProvider`]
 ///   with H3 fallback on failure.
+///
+/// When the provider cannot be built (unreadable OSRM cache, missing route table) a
+/// warning is printed and [`H3GridRouteProvider`] is returned; use
+/// [`try_build_route_provider`] to handle the error instead.
 pub fn build_route_provider(kind: &RouteProviderKind) -> Box<dyn RouteProvider> {
+    try_build_route_provider(kind).unwrap_or_else(|e| {
+        eprintln!("WARNING: {e}. Falling back to H3Grid.");
+        Box::new(H3GridRouteProvider)
+    })
+}
+
+/// Like [`build_route_provider`], but returns the reason instead of falling back when the
+/// provider cannot be built.
+pub fn try_build_route_provider(
+    kind: &RouteProviderKind,
+) -> Result<Box<dyn RouteProvider>, String> {
     match kind {
-        RouteProviderKind::H3Grid => Box::new(H3GridRouteProvider),
+        RouteProviderKind::H3Grid => Ok(Box::new(H3GridRouteProvider)),
 
         #[cfg(feature = "osrm")]
-        RouteProviderKind::Osrm { endpoint } => {
-            let inner = Box::new(osrm::OsrmRouteProvider::new(endpoint));
-            Box::new(CachedRouteProvider::new(
+        RouteProviderKind::Osrm { endpoint, cache } => {
+            let cache = osrm_cache::OsrmResponseCache::open(cache)?;
+            let inner = Box::new(osrm::OsrmRouteProvider::new(endpoint).with_cache(cache));
+            Ok(Box::new(CachedRouteProvider::new(
                 inner,
                 DEFAULT_ROUTE_CACHE_CAPACITY,
                 true, // fallback to H3 on OSRM failure
-            ))
+            )))
         }
 
         #[cfg(feature = "precomputed")]
         RouteProviderKind::Precomputed { path } => {
-            match precomputed::PrecomputedRouteProvider::from_file(path) {
-                Ok(provider) => {
-                    let inner = Box::new(provider);
-                    Box::new(CachedRouteProvider::new(
-                        inner,
-                        DEFAULT_ROUTE_CACHE_CAPACITY,
-                        true, // fallback to H3 on cache/table miss
-                    ))
-                }
-                Err(e) => {
-                    eprintln!(
-                        "WARNING: Failed to load pre-computed route table from '{}': {}. Falling back to H3Grid.",
-                        path, e
-                    );
-                    Box::new(H3GridRouteProvider)
-                }
-            }
+            let provider = precomputed::PrecomputedRouteProvider::from_file(path).map_err(|e| {
+                format!("Failed to load pre-computed route table from '{path}': {e}")
+            })?;
+            Ok(Box::new(CachedRouteProvider::new(
+                Box::new(provider),
+                DEFAULT_ROUTE_CACHE_CAPACITY,
+                true, // fallback to H3 on cache/table miss
+            )))
         }
     }
 }
@@ -0,0 +1,267 @@
+//! Offline OSRM response cache for deterministic, server-free runs.
+//!
+//! In [`OsrmCacheMode::Record`] every OSRM response body (routes and spawn snapping) is
+//! stored under its request path and query, and written to a JSON cache file when the last
+//! user of the cache is dropped. In [`OsrmCacheMode::Replay`] responses are served only from
+//! that file: no HTTP request is made, and requests missing from the cache fail like an
+//! unreachable server, so the usual H3 fallbacks apply and the run stays deterministic.
+//!
+//! Caches are shared per file path within a process, so the route provider and the spawn
+//! client of a scenario (and parallel runs recording to the same file) write one cache.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// Whether OSRM responses are fetched live, recorded to a cache file, or replayed from one.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum OsrmCacheMode {
+    #[default]
+    Off,
+    /// Query OSRM and record every response into the cache file at `path`; an existing
+    /// file is loaded first and extended.
+    Record { path: String },
+    /// Serve responses from the cache file at `path` only.
+    Replay { path: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    responses: BTreeMap<String, String>,
+}
+
+const CACHE_FILE_VERSION: u32 = 1;
+
+/// Errors from a cache-aware OSRM GET.
+#[derive(Debug)]
+pub enum OsrmFetchError {
+    Http(reqwest::Error),
+    /// Replay mode and the request is not in the cache.
+    CacheMiss(String),
+}
+
+#[derive(Debug)]
+pub struct OsrmResponseCache {
+    path: String,
+    replay: bool,
+    responses: Mutex<BTreeMap<String, String>>,
+    dirty: AtomicBool,
+    misses: AtomicU64,
+}
+
+type CacheRegistry = Mutex<HashMap<String, Weak<OsrmResponseCache>>>;
+
+fn registry() -> &'static CacheRegistry {
+    static REGISTRY: OnceLock<CacheRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl OsrmResponseCache {
+    /// The shared cache for `mode`, loading its file on first use. `Ok(None)` when caching
+    /// is off; an error when a replay file cannot be read.
+    pub fn open(mode: &OsrmCacheMode) -> Result<Option<Arc<Self>>, String> {
+        let (path, replay) = match mode {
+            OsrmCacheMode::Off => return Ok(None),
+            OsrmCacheMode::Record { path } => (path, false),
+            OsrmCacheMode::Replay { path } => (path, true),
+        };
+
+        let mut registry = registry()
+            .lock()
+            .map_err(|_| "OSRM cache registry is poisoned".to_string())?;
+        if let Some(cache) = registry.get(path).and_then(Weak::upgrade) {
+            if cache.replay != replay {
+                return Err(format!(
+                    "OSRM cache '{path}' is already open in {} mode",
+                    if cache.replay { "replay" } else { "record" }
+                ));
+            }
+            return Ok(Some(cache));
+        }
+
+        let responses = match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).map_err(|e| format!("{path}: {e}"))?,
+            Err(e) if replay => return Err(format!("failed to read OSRM cache '{path}': {e}")),
+            Err(_) => BTreeMap::new(),
+        };
+        let cache = Arc::new(Self {
+            path: path.clone(),
+            replay,
+            responses: Mutex::new(responses),
+            dirty: AtomicBool::new(false),
+            misses: AtomicU64::new(0),
+        });
+        registry.insert(path.clone(), Arc::downgrade(&cache));
+        Ok(Some(cache))
+    }
+
+    fn parse(text: &str) -> Result<BTreeMap<String, String>, String> {
+        let file: CacheFile =
+            serde_json::from_str(text).map_err(|e| format!("invalid OSRM cache file: {e}"))?;
+        if file.version != CACHE_FILE_VERSION {
+            return Err(format!(
+                "unsupported OSRM cache version {} (expected {CACHE_FILE_VERSION})",
+                file.version
+            ));
+        }
+        Ok(file.responses)
+    }
+
+    /// Cache key of a request: path and query, without scheme and host, so recordings work
+    /// against any OSRM endpoint serving the same data.
+    pub fn key(url: &Url) -> String {
+        match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replay lookups that were not in the cache.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.responses.lock().ok()?.get(key).cloned()
+    }
+
+    pub fn insert(&self, key: String, body: String) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.insert(key, body);
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// GET `url` through the cache: replay serves from the file, record fetches and stores
+    /// successful responses.
+    pub fn fetch(&self, client: &Client, url: Url) -> Result<String, OsrmFetchError> {
+        let key = Self::key(&url);
+        if self.replay {
+            return self.get(&key).ok_or_else(|| {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                OsrmFetchError::CacheMiss(key)
+            });
+        }
+        let body = fetch_text(client, url)?;
+        self.insert(key, body.clone());
+        Ok(body)
+    }
+
+    /// Write recorded responses to the cache file. Also done automatically on drop.
+    pub fn save(&self) -> Result<(), String> {
+        let responses = self
+            .responses
+            .lock()
+            .map_err(|_| "OSRM cache is poisoned".to_string())?
+            .clone();
+        let file = CacheFile {
+            version: CACHE_FILE_VERSION,
+            responses,
+        };
+        let text = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        fs::write(&self.path, text)
+            .map_err(|e| format!("failed to write OSRM cache '{}': {e}", self.path))?;
+        self.dirty.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl Drop for OsrmResponseCache {
+    fn drop(&mut self) {
+        if !self.replay && self.dirty.load(Ordering::Relaxed) {
+            if let Err(e) = self.save() {
+                eprintln!("WARNING: {e}");
+            }
+        }
+    }
+}
+
+fn fetch_text(client: &Client, url: Url) -> Result<String, OsrmFetchError> {
+    client
+        .get(url)
+        .send()
+        .and_then(|response| response.text())
+        .map_err(OsrmFetchError::Http)
+}
+
+/// GET `url`, through `cache` when one is configured.
+pub(crate) fn fetch(
+    client: &Client,
+    cache: Option<&OsrmResponseCache>,
+    url: Url,
+) -> Result<String, OsrmFetchError> {
+    match cache {
+        Some(cache) => cache.fetch(client, url),
+        None => fetch_text(client, url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("osrm_cache_{name}_{}.json", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn recorded_responses_are_saved_on_drop_and_replayed() {
+        let path = temp_path("round_trip");
+        let url = Url::parse("http://osrm:5000/route/v1/driving/13.4,52.5;13.5,52.5?overview=full")
+            .unwrap();
+        {
+            let recorder = OsrmResponseCache::open(&OsrmCacheMode::Record { path: path.clone() })
+                .unwrap()
+                .unwrap();
+            let shared = OsrmResponseCache::open(&OsrmCacheMode::Record { path: path.clone() })
+                .unwrap()
+                .unwrap();
+            assert!(Arc::ptr_eq(&recorder, &shared));
+            recorder.insert(OsrmResponseCache::key(&url), r#"{"code":"Ok"}"#.to_string());
+        }
+
+        let replay = OsrmResponseCache::open(&OsrmCacheMode::Replay { path: path.clone() })
+            .unwrap()
+            .unwrap();
+        let client = Client::new();
+        let other_host =
+            Url::parse("http://localhost:1/route/v1/driving/13.4,52.5;13.5,52.5?overview=full")
+                .unwrap();
+        assert_eq!(
+            replay.fetch(&client, other_host).unwrap(),
+            r#"{"code":"Ok"}"#
+        );
+        let missing = Url::parse("http://localhost:1/nearest/v1/driving/13.4,52.5").unwrap();
+        assert!(matches!(
+            replay.fetch(&client, missing),
+            Err(OsrmFetchError::CacheMiss(key)) if key == "/nearest/v1/driving/13.4,52.5"
+        ));
+        assert_eq!(replay.misses(), 1);
+        assert!(OsrmResponseCache::open(&OsrmCacheMode::Record { path: path.clone() }).is_err());
+        drop(replay);
+
+        fs::remove_file(&path).unwrap();
+        assert!(OsrmResponseCache::open(&OsrmCacheMode::Replay { path }).is_err());
+    }
+}
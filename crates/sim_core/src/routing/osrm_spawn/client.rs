@@ -1,11 +1,13 @@
 use h3o::LatLng;
 use reqwest::{blocking::Client, Url};
+use std::sync::Arc;
 use std::time::Duration;
 
 use super::error::OsrmSpawnError;
 use super::parser::{parse_match_response, parse_nearest_response};
 use super::radius::{default_radiuses, encode_radiuses};
 use super::response::{OsrmMatchResponse, OsrmNearestMatch, OsrmNearestResponse, OsrmSpawnMatch};
+use crate::routing::osrm_cache::{self, OsrmResponseCache};
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
 
@@ -14,6 +16,7 @@ const REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
 pub struct OsrmSpawnClient {
     client: Client,
     endpoint: String,
+    cache: Option<Arc<OsrmResponseCache>>,
 }
 
 impl OsrmSpawnClient {
@@ -26,9 +29,21 @@ impl OsrmSpawnClient {
         Self {
             client,
             endpoint: endpoint.trim_end_matches('/').to_string(),
+            cache: None,
         }
     }
 
+    /// Record responses to, or replay them from, an offline cache.
+    pub fn with_cache(mut self, cache: Option<Arc<OsrmResponseCache>>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, url: Url) -> Result<T, OsrmSpawnError> {
+        let body = osrm_cache::fetch(&self.client, self.cache.as_deref(), url)?;
+        serde_json::from_str(&body).map_err(OsrmSpawnError::Json)
+    }
+
     /// Snap the supplied points to roads and return the best match record.
     pub fn snap_trace(
         &self,
@@ -56,9 +71,7 @@ impl OsrmSpawnClient {
             .append_pair("geometries", "geojson")
             .append_pair("radiuses", &radiuses);
 
-        let response = self.client.get(url).send().map_err(OsrmSpawnError::Http)?;
-
-        let parsed: OsrmMatchResponse = response.json().map_err(OsrmSpawnError::Json)?;
+        let parsed: OsrmMatchResponse = self.get(url)?;
         parse_match_response(parsed)
     }
 
@@ -68,8 +81,7 @@ impl OsrmSpawnClient {
         let url = Url::parse(&format!("{}/nearest/v1/driving/{}", self.endpoint, coord))
             .map_err(|err| OsrmSpawnError::Api(format!("failed to build OSRM URL: {}", err)))?;
 
-        let response = self.client.get(url).send().map_err(OsrmSpawnError::Http)?;
-        let parsed: OsrmNearestResponse = response.json().map_err(OsrmSpawnError::Json)?;
+        let parsed: OsrmNearestResponse = self.get(url)?;
         parse_nearest_response(parsed)
     }
 
@@ -1,10 +1,14 @@
+use crate::routing::osrm_cache::OsrmFetchError;
+
 /// Errors encountered while snapping a candidate trace.
 #[derive(Debug)]
 pub enum OsrmSpawnError {
     Http(reqwest::Error),
-    Json(reqwest::Error),
+    Json(serde_json::Error),
     Api(String),
     NoMatch,
+    /// Replaying from an offline cache that has no response for this request.
+    CacheMiss(String),
 }
 
 impl From<reqwest::Error> for OsrmSpawnError {
@@ -12,3 +16,12 @@ impl From<reqwest::Error> for OsrmSpawnError {
         OsrmSpawnError::Http(err)
     }
 }
+
+impl From<OsrmFetchError> for OsrmSpawnError {
+    fn from(err: OsrmFetchError) -> Self {
+        match err {
+            OsrmFetchError::Http(err) => OsrmSpawnError::Http(err),
+            OsrmFetchError::CacheMiss(key) => OsrmSpawnError::CacheMiss(key),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Batched client for OSRM's `/table` service.
+//!
+//! Where a single [`super::osrm::OsrmRouteProvider`] route is one blocking `/route` request,
+//! [`OsrmTableClient`] resolves many origin/destination pairs at once: pairs are grouped by
+//! origin into distance/duration matrix requests, sent over a pooled async connection with
+//! bounded concurrency, retried with jittered exponential backoff, and short-circuited by a
+//! circuit breaker while OSRM is failing. Request counts, retries and latency are recorded in
+//! [`crate::telemetry::OsrmTableTelemetry`].
+//!
+//! The client is async and must be driven on a Tokio runtime with the time driver enabled.
+//! `OsrmRouteProvider::route_costs` drives it on its own runtime for bulk lookups.
+
+mod batching;
+mod breaker;
+mod client;
+mod error;
+mod response;
+mod retry;
+
+pub use client::{OsrmTableClient, OsrmTableConfig};
+pub use error::OsrmTableError;
+pub use response::OsrmTable;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+use h3o::CellIndex;
+
+/// One `/table` request: `sources` and `destinations` are sent as a single coordinate list
+/// (sources first), and `entries` maps each requested pair back to its matrix position.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct TableBatch {
+    pub(super) sources: Vec<CellIndex>,
+    pub(super) destinations: Vec<CellIndex>,
+    /// `(pair index, source index, destination index)`.
+    pub(super) entries: Vec<(usize, usize, usize)>,
+}
+
+impl TableBatch {
+    fn coordinate_count(&self) -> usize {
+        self.sources.len() + self.destinations.len()
+    }
+}
+
+/// Group pairs by origin into table requests of at most `max_coordinates` coordinates.
+/// Origins are packed greedily in first-seen order; an origin with more destinations than
+/// fit in one request is split across several.
+pub(super) fn plan_batches(
+    pairs: &[(CellIndex, CellIndex)],
+    max_coordinates: usize,
+) -> Vec<TableBatch> {
+    let max_coordinates = max_coordinates.max(2);
+
+    let mut origin_order: Vec<CellIndex> = Vec::new();
+    let mut by_origin: HashMap<CellIndex, Vec<(usize, CellIndex)>> = HashMap::new();
+    for (pair_index, &(from, to)) in pairs.iter().enumerate() {
+        by_origin
+            .entry(from)
+            .or_insert_with(|| {
+                origin_order.push(from);
+                Vec::new()
+            })
+            .push((pair_index, to));
+    }
+
+    let mut batches = Vec::new();
+    let mut current = TableBatch {
+        sources: Vec::new(),
+        destinations: Vec::new(),
+        entries: Vec::new(),
+    };
+    for origin in origin_order {
+        for chunk in by_origin[&origin].chunks(max_coordinates - 1) {
+            let new_destinations = chunk
+                .iter()
+                .filter(|(_, to)| !current.destinations.contains(to))
+                .count();
+            if !current.sources.is_empty()
+                && current.coordinate_count() + 1 + new_destinations > max_coordinates
+            {
+                batches.push(std::mem::replace(
+                    &mut current,
+                    TableBatch {
+                        sources: Vec::new(),
+                        destinations: Vec::new(),
+                        entries: Vec::new(),
+                    },
+                ));
+            }
+
+            let source_index = current.sources.len();
+            current.sources.push(origin);
+            for &(pair_index, to) in chunk {
+                let destination_index = match current.destinations.iter().position(|d| *d == to) {
+                    Some(index) => index,
+                    None => {
+                        current.destinations.push(to);
+                        current.destinations.len() - 1
+                    }
+                };
+                current
+                    .entries
+                    .push((pair_index, source_index, destination_index));
+            }
+        }
+    }
+    if !current.sources.is_empty() {
+        batches.push(current);
+    }
+    batches
+}
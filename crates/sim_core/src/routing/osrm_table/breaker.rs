@@ -0,0 +1,70 @@
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BreakerState {
+    Closed {
+        consecutive_failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// Cooldown elapsed; one probe request is in flight.
+    HalfOpen,
+}
+
+/// Consecutive-failure circuit breaker. After `failure_threshold` failures in a row it
+/// rejects requests for `cooldown`, then lets a single probe through: success closes it,
+/// failure opens it again.
+#[derive(Debug)]
+pub(super) struct CircuitBreaker {
+    state: BreakerState,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    pub(super) fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            state: BreakerState::Closed {
+                consecutive_failures: 0,
+            },
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+
+    /// Whether a request may be sent now.
+    pub(super) fn allow(&mut self, now: Instant) -> bool {
+        match self.state {
+            BreakerState::Closed { .. } => true,
+            BreakerState::Open { until } if now >= until => {
+                self.state = BreakerState::HalfOpen;
+                true
+            }
+            BreakerState::Open { .. } | BreakerState::HalfOpen => false,
+        }
+    }
+
+    pub(super) fn record_success(&mut self) {
+        self.state = BreakerState::Closed {
+            consecutive_failures: 0,
+        };
+    }
+
+    pub(super) fn record_failure(&mut self, now: Instant) {
+        self.state = match self.state {
+            BreakerState::Closed {
+                consecutive_failures,
+            } if consecutive_failures + 1 < self.failure_threshold => BreakerState::Closed {
+                consecutive_failures: consecutive_failures + 1,
+            },
+            _ => BreakerState::Open {
+                until: now + self.cooldown,
+            },
+        };
+    }
+
+    pub(super) fn is_open(&self) -> bool {
+        !matches!(self.state, BreakerState::Closed { .. })
+    }
+}
@@ -0,0 +1,261 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use h3o::{CellIndex, LatLng};
+use reqwest::{Client, Url};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::batching::{plan_batches, TableBatch};
+use super::breaker::CircuitBreaker;
+use super::error::OsrmTableError;
+use super::response::{parse_table_response, OsrmTable, OsrmTableResponse};
+use super::retry::backoff_delay;
+use crate::routing::RouteCost;
+use crate::telemetry::OsrmTableTelemetry;
+
+/// Tuning for [`OsrmTableClient`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsrmTableConfig {
+    /// Table requests in flight at once, across all callers of one client.
+    pub max_concurrency: usize,
+    /// Coordinates (sources plus destinations) per request; OSRM's `--max-table-size`
+    /// defaults to 100.
+    pub max_coordinates_per_request: usize,
+    pub request_timeout: Duration,
+    /// Retries after the first attempt for retryable failures.
+    pub max_retries: u32,
+    pub retry_base_delay: Duration,
+    pub retry_max_delay: Duration,
+    /// Consecutive failed attempts that open the circuit breaker.
+    pub breaker_failure_threshold: u32,
+    pub breaker_cooldown: Duration,
+    /// Idle keep-alive connections kept per host for reuse.
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for OsrmTableConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            max_coordinates_per_request: 100,
+            request_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(100),
+            retry_max_delay: Duration::from_secs(2),
+            breaker_failure_threshold: 5,
+            breaker_cooldown: Duration::from_secs(10),
+            pool_max_idle_per_host: 16,
+        }
+    }
+}
+
+struct Inner {
+    client: Client,
+    endpoint: String,
+    config: OsrmTableConfig,
+    permits: Semaphore,
+    breaker: Mutex<CircuitBreaker>,
+    telemetry: Arc<OsrmTableTelemetry>,
+}
+
+/// Async OSRM `/table` client. Cheap to clone; clones share the connection pool,
+/// concurrency limit, circuit breaker and telemetry.
+#[derive(Clone)]
+pub struct OsrmTableClient {
+    inner: Arc<Inner>,
+}
+
+impl OsrmTableClient {
+    /// Create a client for the given OSRM endpoint (e.g. `http://localhost:5000`).
+    pub fn new(endpoint: &str, config: OsrmTableConfig) -> Self {
+        let client = Client::builder()
+            .timeout(config.request_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .build()
+            .expect("failed to build OSRM table client");
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                endpoint: endpoint.trim_end_matches('/').to_string(),
+                permits: Semaphore::new(config.max_concurrency.max(1)),
+                breaker: Mutex::new(CircuitBreaker::new(
+                    config.breaker_failure_threshold,
+                    config.breaker_cooldown,
+                )),
+                config,
+                telemetry: Arc::new(OsrmTableTelemetry::default()),
+            }),
+        }
+    }
+
+    /// Shared telemetry counters, e.g. to insert as an ECS resource or export.
+    pub fn telemetry(&self) -> Arc<OsrmTableTelemetry> {
+        Arc::clone(&self.inner.telemetry)
+    }
+
+    pub fn is_circuit_open(&self) -> bool {
+        self.inner
+            .breaker
+            .lock()
+            .map(|breaker| breaker.is_open())
+            .unwrap_or(true)
+    }
+
+    /// Fetch the distance/duration matrix from every source to every destination.
+    pub async fn table(
+        &self,
+        sources: &[CellIndex],
+        destinations: &[CellIndex],
+    ) -> Result<OsrmTable, OsrmTableError> {
+        let url = self.table_url(sources, destinations)?;
+        let _permit = self
+            .inner
+            .permits
+            .acquire()
+            .await
+            .expect("OSRM table semaphore is never closed");
+
+        let config = &self.inner.config;
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .send_once(url.clone(), sources.len(), destinations.len())
+                .await;
+            match result {
+                Err(error) if error.is_retryable() && attempt < config.max_retries => {
+                    let delay = backoff_delay(
+                        attempt,
+                        config.retry_base_delay,
+                        config.retry_max_delay,
+                        &mut rand::thread_rng(),
+                    );
+                    self.inner.telemetry.record_retry();
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Resolve many cell pairs with as few table requests as possible, run concurrently up
+    /// to `max_concurrency`. Results are in `pairs` order; pairs OSRM cannot route, or whose
+    /// request failed after retries, are `None`.
+    pub async fn route_costs(&self, pairs: &[(CellIndex, CellIndex)]) -> Vec<Option<RouteCost>> {
+        let mut costs = vec![None; pairs.len()];
+        let mut tasks = JoinSet::new();
+        for batch in plan_batches(pairs, self.inner.config.max_coordinates_per_request) {
+            let client = self.clone();
+            tasks.spawn(async move {
+                let table = client.table(&batch.sources, &batch.destinations).await;
+                (batch, table)
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let Ok((batch, Ok(table))) = joined else {
+                continue;
+            };
+            let TableBatch { entries, .. } = batch;
+            for (pair_index, source, destination) in entries {
+                costs[pair_index] = table.cost(source, destination);
+            }
+        }
+        costs
+    }
+
+    fn table_url(
+        &self,
+        sources: &[CellIndex],
+        destinations: &[CellIndex],
+    ) -> Result<Url, OsrmTableError> {
+        if sources.is_empty() || destinations.is_empty() {
+            return Err(OsrmTableError::Api(
+                "table requests need at least one source and one destination".to_string(),
+            ));
+        }
+
+        let coord_segment = sources
+            .iter()
+            .chain(destinations)
+            .map(|cell| {
+                let point = LatLng::from(*cell);
+                format!("{:.6},{:.6}", point.lng(), point.lat())
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+        let indices = |range: std::ops::Range<usize>| {
+            range.map(|i| i.to_string()).collect::<Vec<_>>().join(";")
+        };
+
+        let base = format!("{}/table/v1/driving/{}", self.inner.endpoint, coord_segment);
+        let mut url = Url::parse(&base)
+            .map_err(|err| OsrmTableError::Api(format!("failed to build OSRM URL: {}", err)))?;
+        url.query_pairs_mut()
+            .append_pair("sources", &indices(0..sources.len()))
+            .append_pair(
+                "destinations",
+                &indices(sources.len()..sources.len() + destinations.len()),
+            )
+            .append_pair("annotations", "duration,distance");
+        Ok(url)
+    }
+
+    /// One attempt, gated by the circuit breaker and recorded in telemetry.
+    async fn send_once(
+        &self,
+        url: Url,
+        sources: usize,
+        destinations: usize,
+    ) -> Result<OsrmTable, OsrmTableError> {
+        let allowed = self
+            .inner
+            .breaker
+            .lock()
+            .map(|mut breaker| breaker.allow(Instant::now()))
+            .unwrap_or(false);
+        if !allowed {
+            self.inner.telemetry.record_circuit_rejection();
+            return Err(OsrmTableError::CircuitOpen);
+        }
+
+        let started = Instant::now();
+        let result = self.fetch(url, sources, destinations).await;
+        self.inner
+            .telemetry
+            .record_request(started.elapsed(), result.is_err());
+
+        if let Ok(mut breaker) = self.inner.breaker.lock() {
+            match &result {
+                Err(error) if error.is_retryable() => breaker.record_failure(Instant::now()),
+                _ => breaker.record_success(),
+            }
+        }
+        result
+    }
+
+    async fn fetch(
+        &self,
+        url: Url,
+        sources: usize,
+        destinations: usize,
+    ) -> Result<OsrmTable, OsrmTableError> {
+        let response = self
+            .inner
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(OsrmTableError::Http)?;
+        let status = response.status();
+        // OSRM reports rejected queries as 400 with a JSON code; keep those as API errors.
+        if status.is_server_error() || status.as_u16() == 429 {
+            return Err(OsrmTableError::Status(status.as_u16()));
+        }
+        let parsed: OsrmTableResponse = response.json().await.map_err(OsrmTableError::Json)?;
+        parse_table_response(parsed, sources, destinations)
+    }
+}
@@ -0,0 +1,24 @@
+/// Errors returned by the OSRM table client.
+#[derive(Debug)]
+pub enum OsrmTableError {
+    /// The circuit breaker is open; no request was sent.
+    CircuitOpen,
+    Http(reqwest::Error),
+    /// Non-success HTTP status from OSRM.
+    Status(u16),
+    Json(reqwest::Error),
+    /// OSRM answered with a code other than `Ok`, or a malformed matrix.
+    Api(String),
+}
+
+impl OsrmTableError {
+    /// Transport failures, timeouts, throttling and server errors are worth retrying;
+    /// rejected queries are not.
+    pub(super) fn is_retryable(&self) -> bool {
+        match self {
+            OsrmTableError::Http(_) => true,
+            OsrmTableError::Status(status) => *status == 429 || *status >= 500,
+            OsrmTableError::CircuitOpen | OsrmTableError::Json(_) | OsrmTableError::Api(_) => false,
+        }
+    }
+}
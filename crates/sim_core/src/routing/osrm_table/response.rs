@@ -0,0 +1,59 @@
+use super::error::OsrmTableError;
+use crate::routing::RouteCost;
+
+/// Distance/duration matrix for one `/table` request, indexed `[source][destination]`.
+/// `None` marks pairs OSRM could not route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OsrmTable {
+    pub durations_secs: Vec<Vec<Option<f64>>>,
+    pub distances_m: Vec<Vec<Option<f64>>>,
+}
+
+impl OsrmTable {
+    pub fn cost(&self, source: usize, destination: usize) -> Option<RouteCost> {
+        let duration_secs = (*self.durations_secs.get(source)?.get(destination)?)?;
+        let distance_m = (*self.distances_m.get(source)?.get(destination)?)?;
+        Some(RouteCost {
+            distance_km: distance_m / 1000.0,
+            duration_secs,
+        })
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub(super) struct OsrmTableResponse {
+    pub(super) code: String,
+    pub(super) message: Option<String>,
+    pub(super) durations: Option<Vec<Vec<Option<f64>>>>,
+    pub(super) distances: Option<Vec<Vec<Option<f64>>>>,
+}
+
+pub(super) fn parse_table_response(
+    resp: OsrmTableResponse,
+    sources: usize,
+    destinations: usize,
+) -> Result<OsrmTable, OsrmTableError> {
+    if resp.code != "Ok" {
+        return Err(OsrmTableError::Api(match resp.message {
+            Some(message) => format!("{}: {}", resp.code, message),
+            None => resp.code,
+        }));
+    }
+
+    let shape_ok = |matrix: &Vec<Vec<Option<f64>>>| {
+        matrix.len() == sources && matrix.iter().all(|row| row.len() == destinations)
+    };
+    match (resp.durations, resp.distances) {
+        (Some(durations_secs), Some(distances_m))
+            if shape_ok(&durations_secs) && shape_ok(&distances_m) =>
+        {
+            Ok(OsrmTable {
+                durations_secs,
+                distances_m,
+            })
+        }
+        _ => Err(OsrmTableError::Api(format!(
+            "expected {sources}x{destinations} duration and distance matrices"
+        ))),
+    }
+}
@@ -0,0 +1,20 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Delay before retry number `attempt` (0-based): "full jitter" backoff, uniform in
+/// `[0, min(max, base * 2^attempt)]`, so clients retrying together spread out.
+pub(super) fn backoff_delay<R: Rng>(
+    attempt: u32,
+    base: Duration,
+    max: Duration,
+    rng: &mut R,
+) -> Duration {
+    let ceiling = base
+        .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+        .min(max);
+    if ceiling.is_zero() {
+        return ceiling;
+    }
+    Duration::from_secs_f64(rng.gen_range(0.0..=ceiling.as_secs_f64()))
+}
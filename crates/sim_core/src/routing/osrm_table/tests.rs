@@ -0,0 +1,196 @@
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+use h3o::CellIndex;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use super::batching::plan_batches;
+use super::breaker::CircuitBreaker;
+use super::retry::backoff_delay;
+use super::{OsrmTableClient, OsrmTableConfig};
+use crate::routing::osrm::OsrmRouteProvider;
+use crate::routing::{RouteCost, RouteProvider};
+
+fn cells(count: usize) -> Vec<CellIndex> {
+    let origin = CellIndex::try_from(0x8a1fb46622dffff_u64).expect("valid cell");
+    origin
+        .grid_disk::<Vec<_>>(2)
+        .into_iter()
+        .take(count)
+        .collect()
+}
+
+#[test]
+fn plan_batches_groups_by_origin_within_coordinate_limit() {
+    let c = cells(5);
+    let pairs = vec![
+        (c[0], c[1]),
+        (c[0], c[2]),
+        (c[1], c[2]),
+        (c[3], c[4]),
+        (c[0], c[3]),
+    ];
+
+    let batches = plan_batches(&pairs, 4);
+    assert!(batches
+        .iter()
+        .all(|batch| batch.sources.len() + batch.destinations.len() <= 4));
+    // c[0] fills the first request on its own; c[1] and c[3] share the second.
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].sources, vec![c[0]]);
+    assert_eq!(batches[0].destinations, vec![c[1], c[2], c[3]]);
+    assert_eq!(batches[1].sources, vec![c[1], c[3]]);
+    assert_eq!(batches[1].destinations, vec![c[2], c[4]]);
+
+    let mut covered: Vec<_> = batches
+        .iter()
+        .flat_map(|batch| {
+            batch.entries.iter().map(|&(pair, source, destination)| {
+                assert_eq!(
+                    (batch.sources[source], batch.destinations[destination]),
+                    pairs[pair]
+                );
+                pair
+            })
+        })
+        .collect();
+    covered.sort_unstable();
+    assert_eq!(covered, vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn backoff_is_jittered_and_capped() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let base = Duration::from_millis(100);
+    let max = Duration::from_millis(250);
+    for attempt in 0..6 {
+        let delay = backoff_delay(attempt, base, max, &mut rng);
+        assert!(delay <= (base * 2u32.pow(attempt)).min(max));
+    }
+    assert_eq!(
+        backoff_delay(3, Duration::ZERO, max, &mut rng),
+        Duration::ZERO
+    );
+}
+
+#[test]
+fn circuit_breaker_opens_after_threshold_and_probes_after_cooldown() {
+    let mut breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+    let now = Instant::now();
+
+    breaker.record_failure(now);
+    assert!(breaker.allow(now));
+    breaker.record_failure(now);
+    assert!(breaker.is_open());
+    assert!(!breaker.allow(now + Duration::from_secs(5)));
+
+    // One probe after the cooldown; a failed probe re-opens immediately.
+    assert!(breaker.allow(now + Duration::from_secs(10)));
+    assert!(!breaker.allow(now + Duration::from_secs(10)));
+    breaker.record_failure(now + Duration::from_secs(10));
+    assert!(!breaker.allow(now + Duration::from_secs(11)));
+
+    assert!(breaker.allow(now + Duration::from_secs(20)));
+    breaker.record_success();
+    assert!(!breaker.is_open());
+    assert!(breaker.allow(now + Duration::from_secs(20)));
+}
+
+/// Serve one canned HTTP response per connection, in order.
+fn serve(responses: Vec<(u16, &'static str)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+    let address = listener.local_addr().expect("server address");
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).expect("read request");
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let response = format!(
+                "HTTP/1.1 {status} Test\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream
+                .write_all(response.as_bytes())
+                .expect("write response");
+        }
+    });
+    format!("http://{address}")
+}
+
+#[test]
+fn route_costs_retries_server_errors_and_records_telemetry() {
+    let endpoint = serve(vec![
+        (503, "{}"),
+        (
+            200,
+            r#"{"code":"Ok","durations":[[60.0,null]],"distances":[[1500.0,null]]}"#,
+        ),
+    ]);
+    let client = OsrmTableClient::new(
+        &endpoint,
+        OsrmTableConfig {
+            retry_base_delay: Duration::from_millis(1),
+            ..OsrmTableConfig::default()
+        },
+    );
+    let c = cells(3);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let costs = runtime.block_on(client.route_costs(&[(c[0], c[1]), (c[0], c[2])]));
+
+    assert_eq!(
+        costs,
+        vec![
+            Some(RouteCost {
+                distance_km: 1.5,
+                duration_secs: 60.0
+            }),
+            None
+        ]
+    );
+    let telemetry = client.telemetry().snapshot();
+    assert_eq!(telemetry.requests, 2);
+    assert_eq!(telemetry.request_errors, 1);
+    assert_eq!(telemetry.retries, 1);
+    assert_eq!(telemetry.error_rate, 0.5);
+    assert!(!client.is_circuit_open());
+}
+
+#[test]
+fn osrm_provider_bulk_lookups_use_one_table_request() {
+    let endpoint = serve(vec![(
+        200,
+        r#"{"code":"Ok","durations":[[60.0,90.0]],"distances":[[1500.0,2500.0]]}"#,
+    )]);
+    let provider = OsrmRouteProvider::new(&endpoint);
+    let c = cells(3);
+
+    let costs = provider.route_costs(&[(c[0], c[1]), (c[0], c[2])]);
+
+    assert_eq!(
+        costs,
+        vec![
+            Some(RouteCost {
+                distance_km: 1.5,
+                duration_secs: 60.0
+            }),
+            Some(RouteCost {
+                distance_km: 2.5,
+                duration_secs: 90.0
+            })
+        ]
+    );
+    assert_eq!(provider.table_telemetry().snapshot().requests, 1);
+}
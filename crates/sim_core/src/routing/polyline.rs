@@ -0,0 +1,78 @@
+//! Encoded polyline format (precision 5), as read by kepler.gl, deck.gl and most map tools.
+//!
+//! Each coordinate is rounded to 1e-5 degrees and stored as a zig-zag varint delta from the
+//! previous point, in latitude-then-longitude order.
+
+/// Decimal digits kept per coordinate.
+pub const POLYLINE_PRECISION: u32 = 5;
+
+const SCALE: f64 = 1e5;
+
+fn encode_value(value: i64, out: &mut String) {
+    let mut bits = if value < 0 { !(value << 1) } else { value << 1 } as u64;
+    while bits >= 0x20 {
+        out.push(char::from((0x20 | (bits & 0x1f)) as u8 + 63));
+        bits >>= 5;
+    }
+    out.push(char::from(bits as u8 + 63));
+}
+
+/// Encode `(lat, lng)` points (degrees).
+pub fn encode_polyline(points: impl IntoIterator<Item = (f64, f64)>) -> String {
+    let mut encoded = String::new();
+    let (mut prev_lat, mut prev_lng) = (0_i64, 0_i64);
+    for (lat, lng) in points {
+        let lat = (lat * SCALE).round() as i64;
+        let lng = (lng * SCALE).round() as i64;
+        encode_value(lat - prev_lat, &mut encoded);
+        encode_value(lng - prev_lng, &mut encoded);
+        (prev_lat, prev_lng) = (lat, lng);
+    }
+    encoded
+}
+
+fn decode_value(bytes: &mut impl Iterator<Item = u8>) -> Option<i64> {
+    let mut bits = 0_u64;
+    let mut shift = 0;
+    loop {
+        let chunk = u64::from(bytes.next()?.checked_sub(63)?);
+        if shift > 60 {
+            return None;
+        }
+        bits |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+    let value = (bits >> 1) as i64;
+    Some(if bits & 1 == 1 { !value } else { value })
+}
+
+/// Decode an encoded polyline into `(lat, lng)` points; `None` if it is malformed.
+pub fn decode_polyline(encoded: &str) -> Option<Vec<(f64, f64)>> {
+    let mut bytes = encoded.bytes().peekable();
+    let mut points = Vec::new();
+    let (mut lat, mut lng) = (0_i64, 0_i64);
+    while bytes.peek().is_some() {
+        lat += decode_value(&mut bytes)?;
+        lng += decode_value(&mut bytes)?;
+        points.push((lat as f64 / SCALE, lng as f64 / SCALE));
+    }
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference_encoding_and_round_trips() {
+        let points = [(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+        let encoded = encode_polyline(points);
+        assert_eq!(encoded, "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+        assert_eq!(decode_polyline(&encoded), Some(points.to_vec()));
+        assert_eq!(encode_polyline([]), "");
+        assert_eq!(decode_polyline("_p~iF"), None);
+    }
+}
@@ -0,0 +1,518 @@
+//! Run bundles: one portable `.simrun` artifact per run.
+//!
+//! A [`RunBundle`] records what produced a run and what it produced: format and crate version,
+//! a [`ScenarioRecord`] of the scenario parameters, the seed hierarchy, the recorded snapshots,
+//! the completed trips, the rider funnel and the outcome counters. [`RunBundle::capture`] reads it from a finished
+//! world and [`RunBundle::restore_world`] turns it back into a world holding the recorded
+//! telemetry, so the UI can replay it and code that reads `SimTelemetry` / `SimSnapshots`
+//! (exports, experiment metrics) runs on it unchanged.
+//!
+//! Bundles are JSON. Replay plays the recording back rather than re-simulating, so a bundle
+//! stays readable after the simulation logic changes. Entities are stored as
+//! `Entity::to_bits()` and cells as H3 indices.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use bevy_ecs::prelude::{Entity, Resource, World};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SimulationClock;
+use crate::ecs::DriverEarnings;
+use crate::location::LOCATION_NOISE_STREAM;
+use crate::scenario::ScenarioParams;
+use crate::seeds::{fnv1a, SeedHierarchy, SeedStream};
+use crate::telemetry::{
+    retain_snapshots, CompletedTripRecord, EmissionsTotals, RiderFunnel, SimSnapshot, SimSnapshots,
+    SimTelemetry,
+};
+
+/// Current bundle format. Readers accept this version and older ones. Version 2 may hold delta
+/// snapshots (`SimSnapshot::keyframe`, `removed`); version 3 stores the full `ScenarioParams`
+/// (`ScenarioRecord::params`).
+pub const RUN_BUNDLE_FORMAT_VERSION: u32 = 3;
+
+/// File extension for run bundles.
+pub const RUN_BUNDLE_EXTENSION: &str = "simrun";
+
+/// The scenario a run was built from. Inserted by `build_scenario`.
+///
+/// The scalar parameters are kept as fields for a quick look; `params` is the full parameter
+/// set (pricing, traffic, demand patterns, ...), so a bundle can rebuild the run it recorded.
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
+pub struct ScenarioRecord {
+    pub seed: Option<u64>,
+    pub num_riders: usize,
+    pub num_drivers: usize,
+    pub initial_rider_count: usize,
+    pub initial_driver_count: usize,
+    pub lat_min: f64,
+    pub lat_max: f64,
+    pub lng_min: f64,
+    pub lng_max: f64,
+    pub request_window_ms: u64,
+    pub driver_spread_ms: u64,
+    pub match_radius: u32,
+    pub min_trip_cells: u32,
+    pub max_trip_cells: u32,
+    pub epoch_ms: Option<i64>,
+    pub simulation_end_time_ms: Option<u64>,
+    /// The full `ScenarioParams`. None in bundles recorded before format 3.
+    #[serde(default)]
+    pub params: Option<ScenarioParams>,
+}
+
+impl ScenarioRecord {
+    pub fn from_params(params: &ScenarioParams) -> Self {
+        Self {
+            seed: params.seed,
+            num_riders: params.num_riders,
+            num_drivers: params.num_drivers,
+            initial_rider_count: params.initial_rider_count,
+            initial_driver_count: params.initial_driver_count,
+            lat_min: params.lat_min,
+            lat_max: params.lat_max,
+            lng_min: params.lng_min,
+            lng_max: params.lng_max,
+            request_window_ms: params.request_window_ms,
+            driver_spread_ms: params.driver_spread_ms,
+            match_radius: params.match_radius,
+            min_trip_cells: params.min_trip_cells,
+            max_trip_cells: params.max_trip_cells,
+            epoch_ms: params.epoch_ms,
+            simulation_end_time_ms: params.simulation_end_time_ms,
+            params: Some(params.clone()),
+        }
+    }
+
+    /// Stable hash of the full parameter set as 16 hex digits, stamped on exports. It hashes
+    /// the canonical JSON of `params` (object keys sorted, cell sets and maps in index order),
+    /// so equal scenarios hash the same in every process. None without `params`.
+    pub fn scenario_hash(&self) -> Option<String> {
+        let params = self.params.as_ref()?;
+        let canonical = serde_json::to_value(params)
+            .expect("ScenarioParams serializes to JSON")
+            .to_string();
+        Some(format!("{:016x}", fnv1a(canonical.as_bytes())))
+    }
+}
+
+/// Master seed and the seed of every named stream derived from it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedRecord {
+    pub master: u64,
+    /// Stream name → derived seed.
+    pub streams: Vec<(String, u64)>,
+}
+
+impl From<&SeedHierarchy> for SeedRecord {
+    fn from(seeds: &SeedHierarchy) -> Self {
+        let mut streams: Vec<(String, u64)> = SeedStream::ALL
+            .iter()
+            .map(|&stream| (stream.name().to_string(), seeds.seed(stream)))
+            .collect();
+        streams.push((
+            LOCATION_NOISE_STREAM.to_string(),
+            seeds.named_seed(LOCATION_NOISE_STREAM),
+        ));
+        Self {
+            master: seeds.master(),
+            streams,
+        }
+    }
+}
+
+/// Run-level counters from `SimTelemetry`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutcomeRecord {
+    pub riders_completed_total: u64,
+    pub riders_cancelled_total: u64,
+    pub riders_cancelled_pickup_timeout: u64,
+    pub riders_abandoned_quote_total: u64,
+    pub riders_abandoned_price: u64,
+    pub riders_abandoned_eta: u64,
+    pub riders_abandoned_stochastic: u64,
+    pub riders_abandoned_expired: u64,
+    pub quotes_expired_total: u64,
+    pub requotes_total: u64,
+    pub platform_revenue_total: f64,
+    pub total_fares_collected: f64,
+    pub tolls_collected_total: f64,
+    pub taxes_and_fees_total: f64,
+    pub earnings_floor_top_ups_total: f64,
+    pub drivers_topped_up_total: u64,
+    pub emissions: EmissionsTotals,
+}
+
+impl From<&SimTelemetry> for OutcomeRecord {
+    fn from(telemetry: &SimTelemetry) -> Self {
+        Self {
+            riders_completed_total: telemetry.riders_completed_total,
+            riders_cancelled_total: telemetry.riders_cancelled_total,
+            riders_cancelled_pickup_timeout: telemetry.riders_cancelled_pickup_timeout,
+            riders_abandoned_quote_total: telemetry.riders_abandoned_quote_total,
+            riders_abandoned_price: telemetry.riders_abandoned_price,
+            riders_abandoned_eta: telemetry.riders_abandoned_eta,
+            riders_abandoned_stochastic: telemetry.riders_abandoned_stochastic,
+            riders_abandoned_expired: telemetry.riders_abandoned_expired,
+            quotes_expired_total: telemetry.quotes_expired_total,
+            requotes_total: telemetry.requotes_total,
+            platform_revenue_total: telemetry.platform_revenue_total,
+            total_fares_collected: telemetry.total_fares_collected,
+            tolls_collected_total: telemetry.tolls_collected_total,
+            taxes_and_fees_total: telemetry.taxes_and_fees_total,
+            earnings_floor_top_ups_total: telemetry.earnings_floor_top_ups_total,
+            drivers_topped_up_total: telemetry.drivers_topped_up_total,
+            emissions: telemetry.emissions,
+        }
+    }
+}
+
+impl OutcomeRecord {
+    fn restore(&self, telemetry: &mut SimTelemetry) {
+        telemetry.riders_completed_total = self.riders_completed_total;
+        telemetry.riders_cancelled_total = self.riders_cancelled_total;
+        telemetry.riders_cancelled_pickup_timeout = self.riders_cancelled_pickup_timeout;
+        telemetry.riders_abandoned_quote_total = self.riders_abandoned_quote_total;
+        telemetry.riders_abandoned_price = self.riders_abandoned_price;
+        telemetry.riders_abandoned_eta = self.riders_abandoned_eta;
+        telemetry.riders_abandoned_stochastic = self.riders_abandoned_stochastic;
+        telemetry.riders_abandoned_expired = self.riders_abandoned_expired;
+        telemetry.quotes_expired_total = self.quotes_expired_total;
+        telemetry.requotes_total = self.requotes_total;
+        telemetry.platform_revenue_total = self.platform_revenue_total;
+        telemetry.total_fares_collected = self.total_fares_collected;
+        telemetry.tolls_collected_total = self.tolls_collected_total;
+        telemetry.taxes_and_fees_total = self.taxes_and_fees_total;
+        telemetry.earnings_floor_top_ups_total = self.earnings_floor_top_ups_total;
+        telemetry.drivers_topped_up_total = self.drivers_topped_up_total;
+        telemetry.emissions = self.emissions;
+    }
+}
+
+/// One driver's earnings at capture time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DriverEarningsRecord {
+    #[serde(with = "entity_bits")]
+    pub driver: Entity,
+    pub daily_earnings: f64,
+    pub daily_earnings_target: f64,
+    pub session_start_time_ms: u64,
+    pub session_end_time_ms: Option<u64>,
+}
+
+/// A recorded run. See the module docs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunBundle {
+    pub format_version: u32,
+    /// `sim_core` version that recorded the run.
+    pub sim_core_version: String,
+    /// Free-form name for the run (preset, experiment and run id, ...).
+    pub label: Option<String>,
+    /// `None` when the world was not built by `build_scenario`.
+    pub scenario: Option<ScenarioRecord>,
+    pub seeds: Option<SeedRecord>,
+    /// Simulation time at capture.
+    pub end_time_ms: u64,
+    pub outcome: OutcomeRecord,
+    pub completed_trips: Vec<CompletedTripRecord>,
+    /// Per-rider request funnel, ordered by rider entity.
+    pub funnel: Vec<RiderFunnel>,
+    pub driver_earnings: Vec<DriverEarningsRecord>,
+    /// Recorded snapshots, oldest first (world growth samples are not kept).
+    pub snapshots: Vec<SimSnapshot>,
+}
+
+impl RunBundle {
+    /// Record `world`, normally once the run has finished.
+    pub fn capture(world: &mut World) -> Self {
+        let driver_earnings = world
+            .query::<(Entity, &DriverEarnings)>()
+            .iter(world)
+            .map(|(driver, earnings)| DriverEarningsRecord {
+                driver,
+                daily_earnings: earnings.daily_earnings,
+                daily_earnings_target: earnings.daily_earnings_target,
+                session_start_time_ms: earnings.session_start_time_ms,
+                session_end_time_ms: earnings.session_end_time_ms,
+            })
+            .collect();
+        let telemetry = world.get_resource::<SimTelemetry>();
+        Self {
+            format_version: RUN_BUNDLE_FORMAT_VERSION,
+            sim_core_version: env!("CARGO_PKG_VERSION").to_string(),
+            label: None,
+            scenario: world.get_resource::<ScenarioRecord>().cloned(),
+            seeds: world.get_resource::<SeedHierarchy>().map(SeedRecord::from),
+            end_time_ms: world
+                .get_resource::<SimulationClock>()
+                .map_or(0, |clock| clock.now()),
+            outcome: telemetry.map(OutcomeRecord::from).unwrap_or_default(),
+            completed_trips: telemetry
+                .map(|telemetry| telemetry.completed_trips.clone())
+                .unwrap_or_default(),
+            funnel: telemetry
+                .map(|telemetry| telemetry.funnel.values().copied().collect())
+                .unwrap_or_default(),
+            driver_earnings,
+            snapshots: world
+                .get_resource::<SimSnapshots>()
+                .map(|snapshots| snapshots.snapshots.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Keep only snapshots at least `interval_ms` apart (and always the last one), to shrink
+    /// bundles of runs recorded at a fine snapshot interval.
+    pub fn with_snapshot_interval_ms(mut self, interval_ms: u64) -> Self {
+        let last = self.snapshots.len().saturating_sub(1);
+        let mut index = 0;
+        let mut kept_at: Option<u64> = None;
+        self.snapshots = retain_snapshots(std::mem::take(&mut self.snapshots), |snapshot| {
+            let keep =
+                index == last || kept_at.is_none_or(|at| snapshot.timestamp_ms >= at + interval_ms);
+            if keep && index != last {
+                kept_at = Some(snapshot.timestamp_ms);
+            }
+            index += 1;
+            keep
+        });
+        self
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read a bundle, rejecting formats newer than [`RUN_BUNDLE_FORMAT_VERSION`].
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let bundle: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if bundle.format_version > RUN_BUNDLE_FORMAT_VERSION {
+            return Err(format!(
+                "run bundle format {} is newer than supported format {} (recorded by sim_core {})",
+                bundle.format_version, RUN_BUNDLE_FORMAT_VERSION, bundle.sim_core_version
+            )
+            .into());
+        }
+        Ok(bundle)
+    }
+
+    /// A world holding the recorded telemetry: `SimSnapshots`, `SimTelemetry` (completed trips,
+    /// funnel and outcome counters), one entity per recorded `DriverEarnings`, the seed hierarchy and
+    /// a clock at the recorded epoch. It has no scheduled events and nothing to simulate.
+    pub fn restore_world(&self) -> World {
+        let mut world = World::new();
+        let epoch_ms = self
+            .scenario
+            .as_ref()
+            .and_then(|scenario| scenario.epoch_ms)
+            .unwrap_or(0);
+        world.insert_resource(SimulationClock::with_epoch(epoch_ms));
+
+        let mut telemetry = SimTelemetry::default();
+        self.outcome.restore(&mut telemetry);
+        telemetry.completed_trips = self.completed_trips.clone();
+        telemetry.funnel = self
+            .funnel
+            .iter()
+            .map(|funnel| (funnel.rider, *funnel))
+            .collect();
+        world.insert_resource(telemetry);
+
+        world.insert_resource(SimSnapshots {
+            snapshots: self.snapshots.iter().cloned().collect(),
+            last_snapshot_at: self.snapshots.last().map(|snapshot| snapshot.timestamp_ms),
+        });
+
+        if let Some(scenario) = &self.scenario {
+            world.insert_resource(scenario.clone());
+        }
+        if let Some(seeds) = &self.seeds {
+            world.insert_resource(SeedHierarchy::new(seeds.master));
+        }
+        for record in &self.driver_earnings {
+            world.spawn(DriverEarnings {
+                daily_earnings: record.daily_earnings,
+                daily_earnings_target: record.daily_earnings_target,
+                session_start_time_ms: record.session_start_time_ms,
+                session_end_time_ms: record.session_end_time_ms,
+                weekly_trips: 0,
+                week_index: 0,
+            });
+        }
+        world
+    }
+}
+
+/// Serde helper: an `Entity` as `Entity::to_bits()`.
+pub(crate) mod entity_bits {
+    use bevy_ecs::prelude::Entity;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(entity.to_bits())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        let bits = u64::deserialize(deserializer)?;
+        Entity::try_from_bits(bits)
+            .map_err(|_| D::Error::custom(format!("invalid entity id {bits}")))
+    }
+}
+
+/// Serde helper: an `Option<Entity>` as optional `Entity::to_bits()`.
+pub(crate) mod option_entity_bits {
+    use bevy_ecs::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        entity: &Option<Entity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        entity.map(Entity::to_bits).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Entity>, D::Error> {
+        #[derive(Deserialize)]
+        struct Bits(#[serde(with = "super::entity_bits")] Entity);
+        Ok(Option::<Bits>::deserialize(deserializer)?.map(|Bits(entity)| entity))
+    }
+}
+
+/// Serde helper: a `Vec<Entity>` as `Entity::to_bits()` values.
+pub(crate) mod entity_bits_vec {
+    use bevy_ecs::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(entities: &[Entity], serializer: S) -> Result<S::Ok, S::Error> {
+        entities
+            .iter()
+            .map(|entity| entity.to_bits())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Entity>, D::Error> {
+        #[derive(Deserialize)]
+        struct Bits(#[serde(with = "super::entity_bits")] Entity);
+        Ok(Vec::<Bits>::deserialize(deserializer)?
+            .into_iter()
+            .map(|Bits(entity)| entity)
+            .collect())
+    }
+}
+
+/// Serde helper: a `CellIndex` as its H3 index.
+pub(crate) mod cell_index {
+    use h3o::CellIndex;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(cell: &CellIndex, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::from(*cell))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CellIndex, D::Error> {
+        let index = u64::deserialize(deserializer)?;
+        CellIndex::try_from(index).map_err(|err| D::Error::custom(format!("cell {index}: {err}")))
+    }
+}
+
+/// Serde helper: a `BTreeSet<CellIndex>` as sorted H3 indices.
+pub(crate) mod cell_set {
+    use std::collections::BTreeSet;
+
+    use h3o::CellIndex;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        cells: &BTreeSet<CellIndex>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        cells
+            .iter()
+            .map(|cell| u64::from(*cell))
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeSet<CellIndex>, D::Error> {
+        Vec::<u64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|index| {
+                CellIndex::try_from(index)
+                    .map_err(|err| D::Error::custom(format!("cell {index}: {err}")))
+            })
+            .collect()
+    }
+}
+
+/// Serde helper: a `HashMap<CellIndex, V>` as `(H3 index, value)` pairs sorted by index, so
+/// the same map always serializes the same way.
+pub(crate) mod cell_map {
+    use std::collections::HashMap;
+
+    use h3o::CellIndex;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, V: Serialize>(
+        map: &HashMap<CellIndex, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<(u64, &V)> = map
+            .iter()
+            .map(|(cell, value)| (u64::from(*cell), value))
+            .collect();
+        entries.sort_unstable_by_key(|(index, _)| *index);
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, V: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<CellIndex, V>, D::Error> {
+        Vec::<(u64, V)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(index, value)| {
+                CellIndex::try_from(index)
+                    .map(|cell| (cell, value))
+                    .map_err(|err| D::Error::custom(format!("cell {index}: {err}")))
+            })
+            .collect()
+    }
+}
+
+/// Serde helper: a `LatLng` as `[lat, lng]` in degrees.
+pub(crate) mod lat_lng {
+    use h3o::LatLng;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(geo: &LatLng, serializer: S) -> Result<S::Ok, S::Error> {
+        [geo.lat(), geo.lng()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LatLng, D::Error> {
+        let [lat, lng] = <[f64; 2]>::deserialize(deserializer)?;
+        LatLng::new(lat, lng)
+            .map_err(|err| D::Error::custom(format!("lat/lng {lat}, {lng}: {err}")))
+    }
+}
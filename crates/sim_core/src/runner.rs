@@ -4,17 +4,23 @@
 //! pops the next event from [SimulationClock], inserts it as [CurrentEvent],
 //! then runs the schedule.
 
-use bevy_ecs::prelude::Res;
+use bevy_ecs::prelude::{Mut, QueryState, Res, Resource, With, Without};
 use bevy_ecs::prelude::{Schedule, World};
-use bevy_ecs::schedule::{apply_deferred, IntoSystemConfigs};
+use bevy_ecs::schedule::{apply_deferred, IntoSystemConfigs, SystemSet};
 
 use crate::clock::{CurrentEvent, Event, EventKind, SimulationClock};
+use crate::ecs::{Rider, RiderCancelled, RiderCompleted};
+use crate::event_trace::record_event as record_event_trace;
 use crate::profiling::EventMetrics;
-use crate::scenario::SimulationEndTimeMs;
+use crate::scenario::{EntityGcConfig, SimulationEndTimeMs, StopCondition, StopConditions};
 use crate::systems::{
     batch_matching::batch_matching_system,
     driver_decision::driver_decision_system,
     driver_offduty::driver_offduty_check_system,
+    driver_utilization::driver_utilization_system,
+    entity_gc::entity_gc_system,
+    funnel::rider_funnel_system,
+    location_report::location_report_system,
     match_accepted::match_accepted_system,
     match_rejected::match_rejected_system,
     matching::matching_system,
@@ -25,12 +31,21 @@ use crate::systems::{
     quote_rejected::quote_rejected_system,
     rider_cancel::rider_cancel_system,
     show_quote::show_quote_system,
-    spatial_index::{update_spatial_index_drivers_system, update_spatial_index_riders_system},
-    spawner::{driver_spawner_system, rider_spawner_system, simulation_started_system},
+    spatial_index::update_spatial_index_system,
+    spawner::{
+        demand_event_spawner_system, driver_spawner_system, rider_spawner_system,
+        simulation_started_system,
+    },
     telemetry_snapshot::capture_snapshot_system,
     trip_completed::trip_completed_system,
     trip_started::trip_started_system,
 };
+use crate::telemetry::SimTelemetry;
+use crate::telemetry_stream::{stream_telemetry_system, TelemetryStream};
+
+/// The event-reacting systems; systems ordered after this set see their applied commands.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct EventSystems;
 
 // Condition functions for each event kind
 fn is_simulation_started(event: Option<Res<CurrentEvent>>) -> bool {
@@ -45,6 +60,12 @@ fn is_spawn_rider(event: Option<Res<CurrentEvent>>) -> bool {
         .unwrap_or(false)
 }
 
+fn is_spawn_event_rider(event: Option<Res<CurrentEvent>>) -> bool {
+    event
+        .map(|e| e.0.kind == EventKind::SpawnEventRider)
+        .unwrap_or(false)
+}
+
 fn is_spawn_driver(event: Option<Res<CurrentEvent>>) -> bool {
     event
         .map(|e| e.0.kind == EventKind::SpawnDriver)
@@ -143,6 +164,17 @@ fn is_check_driver_offduty(event: Option<Res<CurrentEvent>>) -> bool {
         .unwrap_or(false)
 }
 
+fn is_location_report(event: Option<Res<CurrentEvent>>) -> bool {
+    event
+        .map(|e| e.0.kind == EventKind::LocationReport || e.0.kind == EventKind::SimulationStarted)
+        .unwrap_or(false)
+}
+
+/// Condition: a streaming telemetry sink is installed.
+fn has_telemetry_stream(stream: Option<Res<TelemetryStream>>) -> bool {
+    stream.is_some()
+}
+
 /// Condition: telemetry snapshot interval has elapsed.
 fn should_capture_snapshot(
     clock: Option<Res<SimulationClock>>,
@@ -166,18 +198,137 @@ fn should_capture_snapshot(
     }
 }
 
-/// Runs one simulation step: pops the next event, inserts it as [CurrentEvent], then runs the schedule.
-/// Returns `true` if an event was processed, `false` if the clock was empty or if the next event
-/// is at or past [SimulationEndTimeMs] (when that resource is present).
-pub fn run_next_event(world: &mut World, schedule: &mut Schedule) -> bool {
+/// Condition: entity GC is configured and its interval has elapsed.
+fn should_collect_garbage(
+    clock: Option<Res<SimulationClock>>,
+    config: Option<Res<EntityGcConfig>>,
+    telemetry: Option<Res<crate::telemetry::SimTelemetry>>,
+) -> bool {
+    let (Some(clock), Some(config), Some(telemetry)) = (clock, config, telemetry) else {
+        return false;
+    };
+    match telemetry.entity_gc.last_run_at_ms {
+        None => true,
+        Some(last) => clock.now().saturating_sub(last) >= config.interval_secs * 1000,
+    }
+}
+
+/// Why a run ended early: the first [StopCondition] that held, and when. Inserted by the runner;
+/// absent if the run ended at its end time or ran out of events.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct SimulationStopped {
+    pub at_ms: u64,
+    pub condition: StopCondition,
+}
+
+/// Whether the run is over before popping the next event: a stop condition already held, or
+/// the next event is at or past [SimulationEndTimeMs].
+fn should_stop(world: &World) -> bool {
+    if world.contains_resource::<SimulationStopped>() {
+        return true;
+    }
     let stop_at = world.get_resource::<SimulationEndTimeMs>().map(|e| e.0);
     let next_ts = world
         .get_resource::<SimulationClock>()
         .and_then(|c| c.next_event_time());
-    if let (Some(end_ms), Some(ts)) = (stop_at, next_ts) {
-        if ts >= end_ms {
-            return false;
+    matches!((stop_at, next_ts), (Some(end_ms), Some(ts)) if ts >= end_ms)
+}
+
+/// Riders still in flight: spawned and neither completed nor cancelled.
+type UnresolvedRiders = (
+    With<Rider>,
+    Without<RiderCompleted>,
+    Without<RiderCancelled>,
+);
+
+/// State carried between [StopCondition] checks so they stay cheap on every step.
+#[derive(Resource)]
+struct StopConditionCache {
+    unresolved_riders: QueryState<(), UnresolvedRiders>,
+    /// `riders_resolved_total` when [StopCondition::AllRidersResolved] was last evaluated.
+    resolved_seen: Option<u64>,
+}
+
+/// After a step, insert [SimulationStopped] if any configured [StopCondition] holds.
+fn check_stop_conditions(world: &mut World, event: &Event) {
+    if !world.contains_resource::<StopConditions>() {
+        return;
+    }
+    if !world.contains_resource::<StopConditionCache>() {
+        let unresolved_riders = world.query_filtered::<(), UnresolvedRiders>();
+        world.insert_resource(StopConditionCache {
+            unresolved_riders,
+            resolved_seen: None,
+        });
+    }
+    let condition = world.resource_scope(|world, mut cache: Mut<StopConditionCache>| {
+        world.resource_scope(|world, conditions: Mut<StopConditions>| {
+            conditions
+                .0
+                .iter()
+                .copied()
+                .find(|condition| stop_condition_holds(world, &mut cache, event, condition))
+        })
+    });
+    let Some(condition) = condition else {
+        return;
+    };
+    let at_ms = world.resource::<SimulationClock>().now();
+    world.insert_resource(SimulationStopped { at_ms, condition });
+}
+
+fn stop_condition_holds(
+    world: &World,
+    cache: &mut StopConditionCache,
+    event: &Event,
+    condition: &StopCondition,
+) -> bool {
+    match *condition {
+        StopCondition::AllRidersResolved => {
+            let clock = world.resource::<SimulationClock>();
+            if clock.has_pending(EventKind::SpawnRider)
+                || clock.has_pending(EventKind::SpawnEventRider)
+            {
+                return false;
+            }
+            // Riders only resolve on steps that bump the resolution counters, and the last
+            // spawn event can leave nothing to wait for; other steps cannot flip the answer.
+            let resolved = world
+                .get_resource::<SimTelemetry>()
+                .map(SimTelemetry::riders_resolved_total);
+            let spawn_step = matches!(
+                event.kind,
+                EventKind::SpawnRider | EventKind::SpawnEventRider
+            );
+            if resolved.is_some() && resolved == cache.resolved_seen && !spawn_step {
+                return false;
+            }
+            cache.resolved_seen = resolved;
+            cache.unresolved_riders.update_archetypes(world);
+            cache.unresolved_riders.iter_manual(world).next().is_none()
         }
+        StopCondition::CompletedTrips(trips) => world
+            .get_resource::<SimTelemetry>()
+            .is_some_and(|telemetry| telemetry.completed_trips.len() as u64 >= trips),
+        StopCondition::CancellationRateAbove {
+            threshold,
+            min_resolved,
+        } => world
+            .get_resource::<SimTelemetry>()
+            .is_some_and(|telemetry| {
+                telemetry.riders_resolved_total() >= min_resolved.max(1)
+                    && telemetry.rider_cancellation_rate() > threshold
+            }),
+    }
+}
+
+/// Runs one simulation step: pops the next event, inserts it as [CurrentEvent], then runs the schedule.
+/// Returns `true` if an event was processed, `false` if the clock was empty, the next event
+/// is at or past [SimulationEndTimeMs] (when that resource is present), or a [StopCondition]
+/// held after an earlier step (see [SimulationStopped]).
+pub fn run_next_event(world: &mut World, schedule: &mut Schedule) -> bool {
+    if should_stop(world) {
+        return false;
     }
 
     let event = match world.resource_mut::<SimulationClock>().pop_next() {
@@ -190,8 +341,10 @@ pub fn run_next_event(world: &mut World, schedule: &mut Schedule) -> bool {
     if let Some(mut metrics) = world.get_resource_mut::<EventMetrics>() {
         metrics.record_event(event.kind);
     }
+    record_event_trace(world, &event);
 
     schedule.run(world);
+    check_stop_conditions(world, &event);
     true
 }
 
@@ -200,14 +353,8 @@ pub fn run_next_event_with_hook<F>(world: &mut World, schedule: &mut Schedule, m
 where
     F: FnMut(&World, &Event),
 {
-    let stop_at = world.get_resource::<SimulationEndTimeMs>().map(|e| e.0);
-    let next_ts = world
-        .get_resource::<SimulationClock>()
-        .and_then(|c| c.next_event_time());
-    if let (Some(end_ms), Some(ts)) = (stop_at, next_ts) {
-        if ts >= end_ms {
-            return false;
-        }
+    if should_stop(world) {
+        return false;
     }
 
     let event = match world.resource_mut::<SimulationClock>().pop_next() {
@@ -220,8 +367,10 @@ where
     if let Some(mut metrics) = world.get_resource_mut::<EventMetrics>() {
         metrics.record_event(event.kind);
     }
+    record_event_trace(world, &event);
 
     schedule.run(world);
+    check_stop_conditions(world, &event);
     hook(world, &event);
     true
 }
@@ -261,57 +410,84 @@ pub fn simulation_schedule() -> Schedule {
     let mut schedule = Schedule::default();
 
     // Group systems by event type using conditions to avoid running all systems on every event
+    schedule.add_systems(
+        (
+            // SimulationStarted
+            simulation_started_system.run_if(is_simulation_started),
+            // SpawnRider / SpawnEventRider
+            (
+                rider_spawner_system.run_if(is_spawn_rider),
+                demand_event_spawner_system.run_if(is_spawn_event_rider),
+            ),
+            // SpawnDriver
+            driver_spawner_system.run_if(is_spawn_driver),
+            // ShowQuote
+            show_quote_system.run_if(is_show_quote),
+            // QuoteDecision
+            quote_decision_system.run_if(is_quote_decision),
+            // QuoteAccepted
+            quote_accepted_system.run_if(is_quote_accepted),
+            // QuoteRejected
+            quote_rejected_system.run_if(is_quote_rejected),
+            // TryMatch
+            matching_system.run_if(is_try_match),
+            // BatchMatchRun
+            batch_matching_system.run_if(is_batch_match_run),
+            // MatchAccepted
+            match_accepted_system.run_if(is_match_accepted),
+            // DriverDecision
+            driver_decision_system.run_if(is_driver_decision),
+            // MatchRejected
+            match_rejected_system.run_if(is_match_rejected),
+            // RiderCancel
+            rider_cancel_system.run_if(is_rider_cancel),
+            // MoveStep
+            movement_system.run_if(is_move_step),
+            // PickupEtaUpdated
+            pickup_eta_updated_system.run_if(is_pickup_eta_updated),
+            // TripStarted
+            trip_started_system.run_if(is_trip_started),
+            // TripCompleted
+            trip_completed_system.run_if(is_trip_completed),
+            // CheckDriverOffDuty / LocationReport
+            (
+                driver_offduty_check_system.run_if(is_check_driver_offduty),
+                location_report_system.run_if(is_location_report),
+            ),
+            // Always run apply_deferred to ensure spawned entities are available
+            apply_deferred,
+        )
+            .in_set(EventSystems),
+    );
+
+    // Spatial index update runs after apply_deferred so spawned entities are available
+    // It runs on every event to keep the index in sync
+    schedule.add_systems(update_spatial_index_system.after(EventSystems));
+
+    // Driver state durations and funnel stages are stamped with the event that caused them
     schedule.add_systems((
-        // SimulationStarted
-        simulation_started_system.run_if(is_simulation_started),
-        // SpawnRider
-        rider_spawner_system.run_if(is_spawn_rider),
-        // SpawnDriver
-        driver_spawner_system.run_if(is_spawn_driver),
-        // ShowQuote
-        show_quote_system.run_if(is_show_quote),
-        // QuoteDecision
-        quote_decision_system.run_if(is_quote_decision),
-        // QuoteAccepted
-        quote_accepted_system.run_if(is_quote_accepted),
-        // QuoteRejected
-        quote_rejected_system.run_if(is_quote_rejected),
-        // TryMatch
-        matching_system.run_if(is_try_match),
-        // BatchMatchRun
-        batch_matching_system.run_if(is_batch_match_run),
-        // MatchAccepted
-        match_accepted_system.run_if(is_match_accepted),
-        // DriverDecision
-        driver_decision_system.run_if(is_driver_decision),
-        // MatchRejected
-        match_rejected_system.run_if(is_match_rejected),
-        // RiderCancel
-        rider_cancel_system.run_if(is_rider_cancel),
-        // MoveStep
-        movement_system.run_if(is_move_step),
-        // PickupEtaUpdated
-        pickup_eta_updated_system.run_if(is_pickup_eta_updated),
-        // TripStarted
-        trip_started_system.run_if(is_trip_started),
-        // TripCompleted
-        trip_completed_system.run_if(is_trip_completed),
-        // CheckDriverOffDuty
-        driver_offduty_check_system.run_if(is_check_driver_offduty),
-        // Always run apply_deferred to ensure spawned entities are available
-        apply_deferred,
-    ));
-
-    // Spatial index updates run after apply_deferred so spawned entities are available
-    // These run on every event to keep the index in sync
-    schedule.add_systems((
-        update_spatial_index_riders_system,
-        update_spatial_index_drivers_system,
+        driver_utilization_system.after(EventSystems),
+        rider_funnel_system.after(EventSystems),
     ));
 
     // Telemetry snapshot runs conditionally based on interval to avoid overhead
     schedule.add_systems(capture_snapshot_system.run_if(should_capture_snapshot));
 
+    // Streaming sink publishes after the snapshot so new snapshots go out on the same step
+    schedule.add_systems(
+        stream_telemetry_system
+            .run_if(has_telemetry_stream)
+            .after(capture_snapshot_system),
+    );
+
+    // Finished entities are collected last, once this step's snapshot and stream have seen them
+    schedule.add_systems(
+        entity_gc_system
+            .run_if(should_collect_garbage)
+            .after(EventSystems)
+            .after(stream_telemetry_system),
+    );
+
     schedule
 }
 
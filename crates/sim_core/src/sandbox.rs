@@ -0,0 +1,363 @@
+//! Sandbox mode: drive riders against a running simulation from outside.
+//!
+//! A [`Sandbox`] owns a built scenario and plays the rider-facing side of a ride-hailing
+//! backend: request a quote, accept or decline it, and poll the ride. Riders created here carry
+//! [`ExternalRider`], so the rider choice model leaves their quotes to the caller; matching,
+//! driver decisions, pickup timeouts and movement are simulated as for any other rider. The
+//! simulated riders and drivers of the scenario keep running alongside.
+//!
+//! Time only moves when the caller advances it ([`Sandbox::advance_to`]); the `sim_api_server`
+//! crate paces it against the wall clock and serves the operations over HTTP and WebSocket.
+
+use std::collections::HashSet;
+
+use bevy_ecs::prelude::{Entity, World};
+use bevy_ecs::schedule::Schedule;
+use h3o::{LatLng, Resolution};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{EventKind, EventSubject, SimulationClock};
+use crate::ecs::{
+    Browsing, ExternalRider, GeoPosition, InTransit, Position, QuoteExpiresAt, Rider, RiderQuote,
+    Trip, TripCancelled, TripCompleted, TripFinancials, TripOnTrip, TripTiming, Waiting,
+};
+use crate::runner::{initialize_simulation, run_next_event, simulation_schedule};
+use crate::scenario::{build_scenario, ScenarioParams};
+use crate::telemetry::{RiderAbandonmentReason, SimTelemetry};
+
+/// A point given or returned by the sandbox API.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl From<LatLng> for Location {
+    fn from(ll: LatLng) -> Self {
+        Self {
+            lat: ll.lat(),
+            lng: ll.lng(),
+        }
+    }
+}
+
+/// Where an external rider is in the request lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiderStatus {
+    /// Waiting for the marketplace to (re-)quote.
+    Quoting,
+    /// A quote is ready to accept or decline.
+    Quoted,
+    /// Quote accepted; waiting for a match or for the driver to arrive.
+    Waiting,
+    InTransit,
+    Completed,
+    /// Gave up after declining too many quotes.
+    Abandoned,
+    /// Cancelled while waiting for pickup.
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct QuoteView {
+    pub fare: f64,
+    pub eta_ms: u64,
+    /// Simulation time after which the quote can no longer be accepted.
+    pub expires_at_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TripStatus {
+    EnRoute,
+    OnTrip,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TripView {
+    pub id: u64,
+    pub driver: u64,
+    pub status: TripStatus,
+    pub fare: Option<f64>,
+    /// Current driver position, while the driver is in the world.
+    pub driver_location: Option<Location>,
+    pub matched_at_ms: Option<u64>,
+    pub pickup_at_ms: Option<u64>,
+    pub dropoff_at_ms: Option<u64>,
+}
+
+/// State of an external rider as returned to API clients.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RiderView {
+    pub id: u64,
+    pub status: RiderStatus,
+    pub quote: Option<QuoteView>,
+    pub trip: Option<TripView>,
+    /// Simulation time of this view.
+    pub now_ms: u64,
+}
+
+/// Errors returned by sandbox operations.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SandboxError {
+    /// Latitude/longitude out of range.
+    InvalidLocation(Location),
+    /// No external rider with this id (never created, or created by the simulation).
+    UnknownRider(u64),
+    /// The rider has no quote to accept or decline right now.
+    NoQuote(u64),
+    /// The quote's TTL passed; decline it or wait for a new one.
+    QuoteExpired(u64),
+}
+
+/// A simulation driven by external riders.
+pub struct Sandbox {
+    world: World,
+    schedule: Schedule,
+    /// Simulation time the caller has advanced to; the clock only moves when events pop.
+    now_ms: u64,
+    /// Riders created through [`Self::request_quote`], kept after they are despawned.
+    external_riders: HashSet<Entity>,
+}
+
+impl Sandbox {
+    /// Build `params` and start the simulation at time 0.
+    pub fn new(params: ScenarioParams) -> Self {
+        let mut world = World::new();
+        build_scenario(&mut world, params);
+        initialize_simulation(&mut world);
+        let mut sandbox = Self {
+            world,
+            schedule: simulation_schedule(),
+            now_ms: 0,
+            external_riders: HashSet::new(),
+        };
+        sandbox.advance_to(0);
+        sandbox
+    }
+
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// World access for configuration the scenario does not cover (e.g. `RiderCancelConfig`).
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Run every event due at or before `target_ms` and move the sandbox clock there. Returns
+    /// the number of events processed; the run may stop earlier at the scenario's end time.
+    pub fn advance_to(&mut self, target_ms: u64) -> usize {
+        self.now_ms = self.now_ms.max(target_ms);
+        let mut steps = 0;
+        while self
+            .world
+            .resource::<SimulationClock>()
+            .next_event_time()
+            .is_some_and(|next| next <= self.now_ms)
+        {
+            if !run_next_event(&mut self.world, &mut self.schedule) {
+                break;
+            }
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Create an external rider at `pickup` going to `dropoff` and quote the trip now.
+    pub fn request_quote(
+        &mut self,
+        pickup: Location,
+        dropoff: Location,
+    ) -> Result<RiderView, SandboxError> {
+        let pickup_ll = to_lat_lng(pickup)?;
+        let dropoff_ll = to_lat_lng(dropoff)?;
+        let now = self.now_ms;
+        let rider_entity = self
+            .world
+            .spawn((
+                Rider {
+                    matched_driver: None,
+                    assigned_trip: None,
+                    destination: Some(dropoff_ll.to_cell(Resolution::Nine)),
+                    requested_at: Some(now),
+                    quote_rejections: 0,
+                    quote_expiries: 0,
+                    accepted_fare: None,
+                    last_rejection_reason: None,
+                },
+                Browsing,
+                Position(pickup_ll.to_cell(Resolution::Nine)),
+                GeoPosition(pickup_ll),
+                ExternalRider,
+            ))
+            .id();
+        self.external_riders.insert(rider_entity);
+        self.schedule_now(EventKind::ShowQuote, rider_entity);
+        self.rider(rider_entity.to_bits())
+    }
+
+    /// Accept the rider's current quote; the rider starts waiting for a match.
+    pub fn accept_quote(&mut self, rider_id: u64) -> Result<RiderView, SandboxError> {
+        let rider_entity = self.external_rider(rider_id)?;
+        let quoted = self.world.get::<Browsing>(rider_entity).is_some()
+            && self.world.get::<RiderQuote>(rider_entity).is_some();
+        if !quoted {
+            return Err(SandboxError::NoQuote(rider_id));
+        }
+        if self
+            .world
+            .get::<QuoteExpiresAt>(rider_entity)
+            .is_some_and(|expires_at| self.now_ms > expires_at.0)
+        {
+            return Err(SandboxError::QuoteExpired(rider_id));
+        }
+        self.schedule_now(EventKind::QuoteAccepted, rider_entity);
+        self.rider(rider_id)
+    }
+
+    /// Decline the rider's current quote. Like a simulated rider, they are re-quoted after
+    /// `re_quote_delay_secs` until `max_quote_rejections` is exceeded, then abandon.
+    pub fn decline_quote(&mut self, rider_id: u64) -> Result<RiderView, SandboxError> {
+        let rider_entity = self.external_rider(rider_id)?;
+        if self.world.get::<Browsing>(rider_entity).is_none()
+            || self.world.get::<RiderQuote>(rider_entity).is_none()
+        {
+            return Err(SandboxError::NoQuote(rider_id));
+        }
+        if let Some(mut rider) = self.world.get_mut::<Rider>(rider_entity) {
+            rider.last_rejection_reason = Some(RiderAbandonmentReason::QuoteStochasticRejection);
+        }
+        // The declined quote must not be accepted while the re-quote is pending
+        self.world.entity_mut(rider_entity).remove::<RiderQuote>();
+        self.schedule_now(EventKind::QuoteRejected, rider_entity);
+        self.rider(rider_id)
+    }
+
+    /// Current state of an external rider, including riders that already finished or left.
+    pub fn rider(&mut self, rider_id: u64) -> Result<RiderView, SandboxError> {
+        let rider_entity = self.external_rider(rider_id)?;
+        let status = match self.world.get_entity(rider_entity) {
+            Some(entity) => {
+                if entity.contains::<InTransit>() {
+                    RiderStatus::InTransit
+                } else if entity.contains::<Waiting>() {
+                    RiderStatus::Waiting
+                } else if entity.contains::<RiderQuote>() {
+                    RiderStatus::Quoted
+                } else {
+                    RiderStatus::Quoting
+                }
+            }
+            // Riders are despawned when they leave; the funnel remembers how
+            None => {
+                let funnel = self
+                    .world
+                    .resource::<SimTelemetry>()
+                    .funnel
+                    .get(&rider_entity)
+                    .filter(|funnel| funnel.exited_at.is_some())
+                    .ok_or(SandboxError::UnknownRider(rider_id))?;
+                match funnel.abandon_reason {
+                    None => RiderStatus::Completed,
+                    Some(RiderAbandonmentReason::PickupTimeout) => RiderStatus::Cancelled,
+                    Some(_) => RiderStatus::Abandoned,
+                }
+            }
+        };
+
+        let quote = match (status, self.world.get::<RiderQuote>(rider_entity)) {
+            (RiderStatus::Quoted, Some(quote)) => Some(QuoteView {
+                fare: quote.fare,
+                eta_ms: quote.eta_ms,
+                expires_at_ms: self
+                    .world
+                    .get::<QuoteExpiresAt>(rider_entity)
+                    .map(|expires_at| expires_at.0),
+            }),
+            _ => None,
+        };
+
+        Ok(RiderView {
+            id: rider_id,
+            status,
+            quote,
+            trip: self.trip_view(rider_entity),
+            now_ms: self.now_ms,
+        })
+    }
+
+    fn external_rider(&self, rider_id: u64) -> Result<Entity, SandboxError> {
+        Entity::try_from_bits(rider_id)
+            .ok()
+            .filter(|entity| self.external_riders.contains(entity))
+            .ok_or(SandboxError::UnknownRider(rider_id))
+    }
+
+    /// Schedule a rider event at the sandbox time and process it.
+    fn schedule_now(&mut self, kind: EventKind, rider_entity: Entity) {
+        let now = self.now_ms;
+        self.world.resource_mut::<SimulationClock>().schedule_at(
+            now,
+            kind,
+            Some(EventSubject::Rider(rider_entity)),
+        );
+        self.advance_to(now);
+    }
+
+    /// The rider's latest trip: the active one, or the last one naming the rider.
+    fn trip_view(&mut self, rider_entity: Entity) -> Option<TripView> {
+        let trip_entity = match self
+            .world
+            .get::<Rider>(rider_entity)
+            .and_then(|rider| rider.assigned_trip)
+        {
+            Some(trip_entity) => trip_entity,
+            None => self
+                .world
+                .query::<(Entity, &Trip)>()
+                .iter(&self.world)
+                .filter(|(_, trip)| trip.rider == rider_entity)
+                .map(|(entity, _)| entity)
+                .max()?,
+        };
+        let entity = self.world.get_entity(trip_entity)?;
+        let trip = entity.get::<Trip>()?;
+        let status = if entity.contains::<TripCompleted>() {
+            TripStatus::Completed
+        } else if entity.contains::<TripCancelled>() {
+            TripStatus::Cancelled
+        } else if entity.contains::<TripOnTrip>() {
+            TripStatus::OnTrip
+        } else {
+            TripStatus::EnRoute
+        };
+        let timing = entity.get::<TripTiming>();
+        Some(TripView {
+            id: trip_entity.to_bits(),
+            driver: trip.driver.to_bits(),
+            status,
+            fare: entity
+                .get::<TripFinancials>()
+                .and_then(|financials| financials.agreed_fare),
+            driver_location: self
+                .world
+                .get::<GeoPosition>(trip.driver)
+                .map(|geo| geo.0.into()),
+            matched_at_ms: timing.map(|timing| timing.matched_at),
+            pickup_at_ms: timing.and_then(|timing| timing.pickup_at),
+            dropoff_at_ms: timing.and_then(|timing| timing.dropoff_at),
+        })
+    }
+}
+
+fn to_lat_lng(location: Location) -> Result<LatLng, SandboxError> {
+    LatLng::new(location.lat, location.lng).map_err(|_| SandboxError::InvalidLocation(location))
+}
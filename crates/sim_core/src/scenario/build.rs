@@ -1,28 +1,42 @@
 use bevy_ecs::prelude::World;
+use serde::de::DeserializeOwned;
 
-use crate::clock::SimulationClock;
+use crate::clock::{LocalTime, SimulationClock};
+use crate::decision_models::{ConversionFeature, DriverAcceptanceFeature, LogisticModel};
 use crate::distributions::TimeOfDayDistribution;
 use crate::matching::{
-    CostBasedMatching, HungarianMatching, MatchingAlgorithmResource, SimpleMatching,
+    CostBasedMatching, HungarianMatching, MatchingAlgorithmResource, ShadowMatchingAlgorithm,
+    SimpleMatching,
 };
-use crate::patterns::{apply_driver_patterns, apply_rider_patterns};
+use crate::patterns::{apply_driver_patterns, apply_rider_patterns, WeeklyPattern};
+use crate::pricing::guardrails::SurgeRateLimiter;
+use crate::pricing::surge::SurgeStrategyResource;
+use crate::pricing::vehicle_classes::VehicleClassMenu;
+use crate::profiling::WorldGrowthMetrics;
+#[cfg(feature = "osrm")]
+use crate::routing::osrm_cache::OsrmResponseCache;
 #[cfg(feature = "osrm")]
 use crate::routing::osrm_spawn::OsrmSpawnClient;
 #[cfg(feature = "osrm")]
 use crate::routing::RouteProviderKind;
-use crate::routing::{build_route_provider, RouteProviderResource};
+use crate::routing::{try_build_route_provider, H3GridRouteProvider, RouteProviderResource};
+use crate::run_bundle::ScenarioRecord;
 use crate::scenario::params::{
     BatchMatchingConfig, DriverDecisionConfig, MatchRadius, MatchingAlgorithmType,
-    RiderCancelConfig, RiderQuoteConfig, ScenarioParams, SimulationEndTimeMs,
+    RiderCancelConfig, RiderQuoteConfig, ScenarioParams, SimulationEndTimeMs, StopConditions,
 };
-use crate::spatial::{cell_in_bounds, GeoIndex, SpatialIndex};
+use crate::seeds::{SeedHierarchy, SeedStream};
+use crate::sim_log::{SimLog, SimLogEvent};
+use crate::spatial::{cells_in_bounds, GeoIndex, SpatialIndex};
 use crate::spawner::{
     DriverSpawner, DriverSpawnerConfig, RiderSpawner, RiderSpawnerConfig, SpawnWeighting,
 };
 use crate::speed::SpeedModel;
 #[cfg(feature = "osrm")]
 use crate::telemetry::OsrmSpawnTelemetry;
-use crate::telemetry::{SimSnapshotConfig, SimSnapshots, SimTelemetry};
+use crate::telemetry::{
+    MatchDiagnostics, QuoteFeatureLog, ShadowMatchLog, SimSnapshots, SimTelemetry,
+};
 use crate::traffic::{CongestionZones, DynamicCongestionConfig, TrafficProfile};
 
 /// Average multiplier for rider demand patterns.
@@ -59,11 +73,15 @@ fn is_valid_destination(
 
 /// Strategy for small distances: generate grid disk and filter candidates.
 /// More efficient for small radii where the disk size is manageable.
+///
+/// Bounds are checked against the shared [`cells_in_bounds`] tiles, and the disk's ring
+/// distances replace per-cell `grid_distance` calls.
 #[allow(clippy::too_many_arguments)]
 fn grid_disk_strategy<R: rand::Rng>(
     rng: &mut R,
     pickup: h3o::CellIndex,
     geo: &GeoIndex,
+    radius: u32,
     min_cells: u32,
     max_cells: u32,
     lat_min: f64,
@@ -71,13 +89,14 @@ fn grid_disk_strategy<R: rand::Rng>(
     lng_min: f64,
     lng_max: f64,
 ) -> Option<h3o::CellIndex> {
-    let disk = geo.grid_disk(pickup, max_cells);
-    let candidates: Vec<h3o::CellIndex> = disk
+    let in_bounds = cells_in_bounds(lat_min, lat_max, lng_min, lng_max, geo.resolution());
+    let candidates: Vec<h3o::CellIndex> = pickup
+        .grid_disk_distances::<Vec<_>>(radius)
         .into_iter()
-        .filter(|c| {
-            is_valid_destination(*c, pickup, min_cells, max_cells)
-                && cell_in_bounds(*c, lat_min, lat_max, lng_min, lng_max)
+        .filter(|&(cell, distance)| {
+            (min_cells..=max_cells).contains(&distance) && in_bounds.contains(cell)
         })
+        .map(|(cell, _)| cell)
         .collect();
 
     if candidates.is_empty() {
@@ -122,36 +141,6 @@ fn rejection_sampling_strategy<R: rand::Rng>(
     None
 }
 
-#[allow(clippy::too_many_arguments)]
-fn fallback_grid_disk_strategy<R: rand::Rng>(
-    rng: &mut R,
-    pickup: h3o::CellIndex,
-    geo: &GeoIndex,
-    min_cells: u32,
-    max_cells: u32,
-    lat_min: f64,
-    lat_max: f64,
-    lng_min: f64,
-    lng_max: f64,
-) -> Option<h3o::CellIndex> {
-    let fallback_radius = (min_cells + max_cells) / 2;
-    let disk = geo.grid_disk(pickup, fallback_radius);
-    let candidates: Vec<h3o::CellIndex> = disk
-        .into_iter()
-        .filter(|c| {
-            is_valid_destination(*c, pickup, min_cells, max_cells)
-                && cell_in_bounds(*c, lat_min, lat_max, lng_min, lng_max)
-        })
-        .collect();
-
-    if candidates.is_empty() {
-        None
-    } else {
-        let i = rng.gen_range(0..candidates.len());
-        Some(candidates[i])
-    }
-}
-
 #[allow(clippy::too_many_arguments)]
 pub fn random_destination<R: rand::Rng>(
     rng: &mut R,
@@ -168,7 +157,7 @@ pub fn random_destination<R: rand::Rng>(
 
     if max_cells <= GRID_DISK_THRESHOLD {
         if let Some(destination) = grid_disk_strategy(
-            rng, pickup, geo, min_cells, max_cells, lat_min, lat_max, lng_min, lng_max,
+            rng, pickup, geo, max_cells, min_cells, max_cells, lat_min, lat_max, lng_min, lng_max,
         ) {
             return destination;
         }
@@ -180,8 +169,19 @@ pub fn random_destination<R: rand::Rng>(
         return destination;
     }
 
-    if let Some(destination) = fallback_grid_disk_strategy(
-        rng, pickup, geo, min_cells, max_cells, lat_min, lat_max, lng_min, lng_max,
+    // Fallback: a smaller disk than the full range
+    let fallback_radius = (min_cells + max_cells) / 2;
+    if let Some(destination) = grid_disk_strategy(
+        rng,
+        pickup,
+        geo,
+        fallback_radius,
+        min_cells,
+        max_cells,
+        lat_min,
+        lat_max,
+        lng_min,
+        lng_max,
     ) {
         return destination;
     }
@@ -201,38 +201,137 @@ pub fn create_hungarian_matching(eta_weight: f64) -> MatchingAlgorithmResource {
     MatchingAlgorithmResource::new(Box::new(HungarianMatching::new(eta_weight)))
 }
 
+fn create_matching(
+    kind: MatchingAlgorithmType,
+    eta_weight: f64,
+    hungarian_max_exact_size: usize,
+) -> MatchingAlgorithmResource {
+    match kind {
+        MatchingAlgorithmType::Simple => create_simple_matching(),
+        MatchingAlgorithmType::CostBased => create_cost_based_matching(eta_weight),
+        MatchingAlgorithmType::Hungarian => MatchingAlgorithmResource::new(Box::new(
+            HungarianMatching::new(eta_weight).with_max_exact_size(hungarian_max_exact_size),
+        )),
+    }
+}
+
 fn create_rider_time_of_day_pattern(
     base_rate_per_sec: f64,
     epoch_ms: i64,
+    local_time: LocalTime,
     seed: u64,
+    weekly_pattern: Option<&WeeklyPattern>,
 ) -> TimeOfDayDistribution {
-    let dist = TimeOfDayDistribution::new(base_rate_per_sec, epoch_ms, seed);
-    apply_rider_patterns(dist)
+    let dist =
+        TimeOfDayDistribution::new(base_rate_per_sec, epoch_ms, seed).with_local_time(local_time);
+    match weekly_pattern {
+        Some(pattern) => pattern.apply(dist),
+        None => apply_rider_patterns(dist),
+    }
 }
 
 fn create_driver_time_of_day_pattern(
     base_rate_per_sec: f64,
     epoch_ms: i64,
+    local_time: LocalTime,
     seed: u64,
+    weekly_pattern: Option<&WeeklyPattern>,
 ) -> TimeOfDayDistribution {
-    let dist = TimeOfDayDistribution::new(base_rate_per_sec, epoch_ms, seed);
-    apply_driver_patterns(dist)
+    let dist =
+        TimeOfDayDistribution::new(base_rate_per_sec, epoch_ms, seed).with_local_time(local_time);
+    match weekly_pattern {
+        Some(pattern) => pattern.apply(dist),
+        None => apply_driver_patterns(dist),
+    }
+}
+
+/// Base spawn rate for an average rate over a window: divides out the pattern's mean multiplier
+/// (over the actual window for custom weekly patterns, the calibrated default otherwise).
+fn pattern_base_rate(
+    avg_rate_per_sec: f64,
+    weekly_pattern: Option<&WeeklyPattern>,
+    default_average_multiplier: f64,
+    epoch_ms: i64,
+    local_time: LocalTime,
+    window_ms: u64,
+) -> f64 {
+    let average_multiplier = weekly_pattern
+        .map(|pattern| pattern.average_multiplier_over(epoch_ms, window_ms, local_time))
+        .unwrap_or(default_average_multiplier);
+    if average_multiplier > 0.0 {
+        avg_rate_per_sec / average_multiplier
+    } else {
+        0.0
+    }
+}
+
+/// Load a trained decision model, keeping the hand-tuned logit if it cannot be loaded.
+fn insert_decision_model<F>(world: &mut World, path: Option<String>, sim_log: &mut SimLog)
+where
+    F: Ord + Copy + DeserializeOwned + Send + Sync + 'static,
+{
+    let Some(path) = path else {
+        return;
+    };
+    match LogisticModel::<F>::load(&path) {
+        Ok(model) => world.insert_resource(model),
+        Err(reason) => {
+            eprintln!("WARNING: {reason}. Falling back to the hand-tuned logit.");
+            sim_log.push(0, SimLogEvent::DecisionModelFallback { reason });
+        }
+    }
 }
 
 pub fn build_scenario(world: &mut World, params: ScenarioParams) {
+    world.insert_resource(ScenarioRecord::from_params(&params));
     let epoch_ms = params.epoch_ms.unwrap_or(0);
     let mut clock = SimulationClock::default();
     clock.set_epoch_ms(epoch_ms);
+    clock.set_local_time(params.local_time);
+    clock.set_event_priorities(params.event_priorities.clone());
     world.insert_resource(clock);
 
     world.insert_resource(SimTelemetry::default());
-    world.insert_resource(SimSnapshotConfig::default());
+    let snapshot_config = params.snapshot_config.unwrap_or_default();
+    if snapshot_config.include_world_growth {
+        world.insert_resource(WorldGrowthMetrics::default());
+    }
+    world.insert_resource(snapshot_config);
     world.insert_resource(SimSnapshots::default());
+    if params.match_diagnostics {
+        world.insert_resource(MatchDiagnostics::default());
+    }
+    if params.quote_features {
+        world.insert_resource(QuoteFeatureLog::default());
+    }
+    if let Some(config) = params.location_reporting.clone() {
+        world.insert_resource(config);
+    }
+    if let Some(config) = params.entity_gc {
+        world.insert_resource(config);
+    }
+    if !params.stop_conditions.is_empty() {
+        world.insert_resource(StopConditions(params.stop_conditions.clone()));
+    }
+    if let Some(state) = params.warm_start.clone() {
+        world.insert_resource(state);
+    }
 
     let total_entities = params.num_riders + params.num_drivers;
     if total_entities > 200 {
         world.insert_resource(SpatialIndex::new());
     }
+    // Materialize (in parallel) the in-bounds cells trip destinations are drawn from; later
+    // resets with the same bounds reuse them. Very large maps skip this and fill in lazily
+    // around spawned agents.
+    cells_in_bounds(
+        params.lat_min,
+        params.lat_max,
+        params.lng_min,
+        params.lng_max,
+        GeoIndex::default().resolution(),
+    )
+    .materialize_all();
 
     world.insert_resource(MatchRadius(params.match_radius));
     world.insert_resource(BatchMatchingConfig {
@@ -242,11 +341,12 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
     if let Some(end_ms) = params.simulation_end_time_ms {
         world.insert_resource(SimulationEndTimeMs(end_ms));
     }
-    let seed = params.seed.unwrap_or(0);
+    let seeds = SeedHierarchy::new(params.seed.unwrap_or(0));
+    world.insert_resource(seeds);
     world.insert_resource(RiderCancelConfig {
         min_wait_secs: 120,
         max_wait_secs: 2400,
-        seed: seed.wrapping_add(0xcafe_babe),
+        seed: seeds.seed(SeedStream::Cancellation),
     });
     world.insert_resource(
         params
@@ -255,51 +355,80 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
                 max_quote_rejections: 3,
                 re_quote_delay_secs: 10,
                 accept_probability: 0.8,
-                seed: seed.wrapping_add(0x0071_1073_beef),
+                seed: seeds.seed(SeedStream::Quotes),
                 max_willingness_to_pay: 100.0,
                 max_acceptable_eta_ms: 600_000,
+                ..Default::default()
             }),
     );
     world.insert_resource(
         params
             .driver_decision_config
             .unwrap_or_else(|| DriverDecisionConfig {
-                seed: seed.wrapping_add(0xdead_beef),
+                seed: seeds.seed(SeedStream::DriverDecisions),
                 ..Default::default()
             }),
     );
-    if let Some(base) = params.base_speed_kmh {
+    // Unseeded scenarios keep entropy-seeded speeds
+    let speed_seed = params.seed.map(|_| seeds.seed(SeedStream::Speed));
+    let speed_model = if let Some(base) = params.base_speed_kmh {
         let min = (base - 10.0).max(5.0);
         let max = base + 10.0;
-        world.insert_resource(SpeedModel::with_range(
-            params.seed.map(|seed| seed ^ 0x5eed_cafe),
-            min,
-            max,
-        ));
+        SpeedModel::with_range(speed_seed, min, max)
     } else {
-        world.insert_resource(SpeedModel::new(params.seed.map(|seed| seed ^ 0x5eed_cafe)));
-    }
+        SpeedModel::new(speed_seed)
+    };
+    world.insert_resource(
+        speed_model.with_road_class_speeds(params.road_class_speeds.unwrap_or_default()),
+    );
 
     let eta_weight = params
         .eta_weight
         .unwrap_or(crate::matching::DEFAULT_ETA_WEIGHT);
-    let algorithm = match params
-        .matching_algorithm_type
-        .unwrap_or(MatchingAlgorithmType::Hungarian)
-    {
-        MatchingAlgorithmType::Simple => create_simple_matching(),
-        MatchingAlgorithmType::CostBased => create_cost_based_matching(eta_weight),
-        MatchingAlgorithmType::Hungarian => create_hungarian_matching(eta_weight),
-    };
+    let hungarian_max_exact_size = params
+        .hungarian_max_exact_size
+        .unwrap_or(crate::matching::hungarian::DEFAULT_MAX_EXACT_SIZE);
+    let algorithm = create_matching(
+        params
+            .matching_algorithm_type
+            .unwrap_or(MatchingAlgorithmType::Hungarian),
+        eta_weight,
+        hungarian_max_exact_size,
+    );
     world.insert_resource(algorithm);
+    if let Some(kind) = params.shadow_matching_algorithm_type {
+        let shadow = create_matching(kind, eta_weight, hungarian_max_exact_size);
+        world.insert_resource(ShadowMatchingAlgorithm(shadow.0));
+        world.insert_resource(ShadowMatchLog::default());
+    }
     world.insert_resource(params.pricing_config.unwrap_or_default());
-
-    let route_provider = build_route_provider(&params.route_provider_kind);
+    world.insert_resource(params.commission_zones.clone().unwrap_or_default());
+    world.insert_resource(SurgeStrategyResource::from_kind(&params.surge_strategy));
+    world.insert_resource(SurgeRateLimiter::default());
+    world.insert_resource(params.toll_zones.clone().unwrap_or_default());
+    world.insert_resource(params.tax_zones.clone().unwrap_or_default());
+    world.insert_resource(VehicleClassMenu {
+        seed: seeds.seed(SeedStream::VehicleClassChoice),
+        ..params.vehicle_classes.clone().unwrap_or_default()
+    });
+    world.insert_resource(params.emission_factors.unwrap_or_default());
+
+    // Build-time fallbacks still go to stderr for headless runs, and to the log for front ends.
+    let mut sim_log = SimLog::default();
+    let route_provider =
+        try_build_route_provider(&params.route_provider_kind).unwrap_or_else(|reason| {
+            eprintln!("WARNING: {reason}. Falling back to H3Grid.");
+            sim_log.push(0, SimLogEvent::RouteProviderFallback { reason });
+            Box::new(H3GridRouteProvider)
+        });
     world.insert_resource(RouteProviderResource(route_provider));
 
+    // Shares the route provider's response cache, so recordings cover spawn snapping too.
     #[cfg(feature = "osrm")]
     let osrm_spawn_client = match &params.route_provider_kind {
-        RouteProviderKind::Osrm { endpoint } => Some(OsrmSpawnClient::new(endpoint)),
+        RouteProviderKind::Osrm { endpoint, cache } => OsrmResponseCache::open(cache)
+            .ok()
+            .map(|cache| OsrmSpawnClient::new(endpoint).with_cache(cache)),
         _ => None,
     };
 
@@ -317,8 +446,31 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
     world.insert_resource(DynamicCongestionConfig {
         enabled: params.dynamic_congestion_enabled,
     });
-
-    world.insert_resource(SpawnWeighting::from_kind(&params.spawn_weighting));
+    world.insert_resource(params.reroute_config.clone().unwrap_or_default());
+
+    let spawn_weighting =
+        SpawnWeighting::try_from_kind(&params.spawn_weighting).unwrap_or_else(|reason| {
+            eprintln!("WARNING: {reason}. Falling back to Uniform.");
+            sim_log.push(0, SimLogEvent::SpawnWeightingFallback { reason });
+            SpawnWeighting::uniform()
+        });
+    world.insert_resource(spawn_weighting);
+    let conversion_model_path = world
+        .resource::<RiderQuoteConfig>()
+        .conversion_model_path
+        .clone();
+    insert_decision_model::<ConversionFeature>(world, conversion_model_path, &mut sim_log);
+    let acceptance_model_path = world
+        .resource::<DriverDecisionConfig>()
+        .acceptance_model_path
+        .clone();
+    insert_decision_model::<DriverAcceptanceFeature>(world, acceptance_model_path, &mut sim_log);
+    world.insert_resource(sim_log);
+    world.insert_resource(params.demand_events.clone().unwrap_or_default().with_seeds(
+        seeds.seed(SeedStream::DemandEvents),
+        seeds.seed(SeedStream::EventRiders),
+    ));
+    world.insert_resource(params.supply_elasticity.clone().unwrap_or_default());
 
     let request_window_ms = params.request_window_ms;
     let driver_spread_ms = params.driver_spread_ms;
@@ -335,13 +487,23 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
     } else {
         0.0
     };
-    let base_rate_per_sec = avg_rate_per_sec / RIDER_DEMAND_AVERAGE_MULTIPLIER;
+    let base_rate_per_sec = pattern_base_rate(
+        avg_rate_per_sec,
+        params.rider_weekly_pattern.as_ref(),
+        RIDER_DEMAND_AVERAGE_MULTIPLIER,
+        epoch_ms,
+        params.local_time,
+        request_window_ms,
+    );
 
+    let rider_seed = seeds.seed(SeedStream::RiderSpawning);
     let rider_spawner_config = RiderSpawnerConfig {
         inter_arrival_dist: Box::new(create_rider_time_of_day_pattern(
             base_rate_per_sec,
             epoch_ms,
-            seed,
+            params.local_time,
+            rider_seed,
+            params.rider_weekly_pattern.as_ref(),
         )),
         lat_min,
         lat_max,
@@ -349,11 +511,12 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
         lng_max,
         min_trip_cells: min_trip,
         max_trip_cells: max_trip,
+        od_matrix: params.od_matrix.clone().filter(|matrix| !matrix.is_empty()),
         start_time_ms: Some(0),
         end_time_ms: Some(request_window_ms),
         max_count: Some(scheduled_rider_count),
         initial_count: params.initial_rider_count,
-        seed,
+        seed: rider_seed,
     };
     let rider_spawner = {
         let base = RiderSpawner::new(rider_spawner_config);
@@ -368,14 +531,19 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
     };
     world.insert_resource(rider_spawner);
 
-    let driver_seed = seed.wrapping_add(0xdead_beef);
+    let driver_seed = seeds.seed(SeedStream::DriverSpawning);
     let scheduled_driver_count = params
         .num_drivers
         .saturating_sub(params.initial_driver_count);
     let driver_base_rate_per_sec = if driver_spread_ms > 0 && scheduled_driver_count > 0 {
-        (scheduled_driver_count as f64)
-            / (driver_spread_ms as f64 / 1000.0)
-            / DRIVER_SUPPLY_AVERAGE_MULTIPLIER
+        pattern_base_rate(
+            (scheduled_driver_count as f64) / (driver_spread_ms as f64 / 1000.0),
+            params.driver_weekly_pattern.as_ref(),
+            DRIVER_SUPPLY_AVERAGE_MULTIPLIER,
+            epoch_ms,
+            params.local_time,
+            driver_spread_ms,
+        )
     } else {
         0.0
     };
@@ -384,7 +552,9 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
         inter_arrival_dist: Box::new(create_driver_time_of_day_pattern(
             driver_base_rate_per_sec,
             epoch_ms,
+            params.local_time,
             driver_seed,
+            params.driver_weekly_pattern.as_ref(),
         )),
         lat_min,
         lat_max,
@@ -397,7 +567,8 @@ pub fn build_scenario(world: &mut World, params: ScenarioParams) {
         seed: driver_seed,
     };
     let driver_spawner = {
-        let base = DriverSpawner::new(driver_spawner_config);
+        let base = DriverSpawner::new(driver_spawner_config)
+            .with_vehicle_mix(params.vehicle_mix.clone().unwrap_or_default());
         #[cfg(feature = "osrm")]
         {
             base.with_osrm_spawn_client(osrm_spawn_client.clone())
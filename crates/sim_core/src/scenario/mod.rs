@@ -5,12 +5,15 @@
 
 mod build;
 mod params;
+mod presets;
 
 pub use build::{
     build_scenario, create_cost_based_matching, create_hungarian_matching, create_simple_matching,
     random_destination,
 };
 pub use params::{
-    BatchMatchingConfig, DriverDecisionConfig, MatchRadius, MatchingAlgorithmType,
-    RiderCancelConfig, RiderQuoteConfig, ScenarioParams, SimulationEndTimeMs,
+    BatchMatchingConfig, DriverDecisionConfig, EntityGcConfig, MatchRadius, MatchingAlgorithmType,
+    RiderCancelConfig, RiderQuoteConfig, ScenarioParams, SimulationEndTimeMs, StopCondition,
+    StopConditions,
 };
+pub use presets::PRESET_NAMES;
@@ -1,9 +1,22 @@
 use bevy_ecs::prelude::Resource;
+use serde::{Deserialize, Serialize};
 
-use crate::pricing::PricingConfig;
+#[cfg(feature = "time-zones")]
+use crate::clock::civil::CityTimeZone;
+use crate::clock::{EventKind, EventPriorities, LocalTime, ONE_DAY_MS};
+use crate::emissions::{EmissionFactors, VehicleMix};
+use crate::location::LocationReportingConfig;
+use crate::patterns::WeeklyPattern;
+use crate::pricing::surge::SurgeStrategyKind;
+use crate::pricing::tolls::TollZones;
+use crate::pricing::vehicle_classes::VehicleClassMenu;
+use crate::pricing::{CommissionZones, PricingConfig, TaxZones};
 use crate::routing::RouteProviderKind;
-use crate::spawner::SpawnWeightingKind;
-use crate::traffic::TrafficProfileKind;
+use crate::spawner::{DemandEvent, DemandEvents, OdMatrix, SpawnWeightingKind, SupplyElasticity};
+use crate::speed::RoadClassSpeeds;
+use crate::telemetry::SimSnapshotConfig;
+use crate::traffic::{RerouteConfig, TrafficProfileKind};
+use crate::warm_start::WarmStartState;
 
 /// Default bounding box: Berlin, Germany (approx).
 const DEFAULT_LAT_MIN: f64 = 52.34;
@@ -15,7 +28,7 @@ const DEFAULT_LNG_MAX: f64 = 13.76;
 const DEFAULT_REQUEST_WINDOW_MS: u64 = 60 * 60 * 1000;
 
 /// Type of matching algorithm to use.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MatchingAlgorithmType {
     Simple,
     CostBased,
@@ -31,6 +44,23 @@ pub struct MatchRadius(pub u32);
 #[derive(Debug, Clone, Copy, Resource)]
 pub struct SimulationEndTimeMs(pub u64);
 
+/// A condition that ends the run early, checked by the runner after every step.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum StopCondition {
+    /// Every spawned rider completed, cancelled or abandoned, and no more riders are scheduled.
+    AllRidersResolved,
+    /// At least this many trips have completed.
+    CompletedTrips(u64),
+    /// Share of resolved riders who cancelled or abandoned exceeds `threshold` (0.0–1.0),
+    /// once at least `min_resolved` riders are resolved (so a few early cancels do not stop the run).
+    CancellationRateAbove { threshold: f64, min_resolved: u64 },
+}
+
+/// Stop conditions in configuration order; the run ends when any of them holds.
+/// Absent = the run ends at `SimulationEndTimeMs` or when the event queue empties.
+#[derive(Debug, Clone, Default, PartialEq, Resource)]
+pub struct StopConditions(pub Vec<StopCondition>);
+
 /// Batch matching: run a global matching pass every N seconds instead of per-rider TryMatch.
 #[derive(Debug, Clone, Copy, Resource)]
 pub struct BatchMatchingConfig {
@@ -49,6 +79,27 @@ impl Default for BatchMatchingConfig {
     }
 }
 
+/// Entity garbage collection: periodically despawn terminal trips and riders (completed or
+/// cancelled) once `retention_secs` have passed since they finished. Their records are already
+/// in `SimTelemetry` by then; despawned counts are kept in `SimTelemetry::entity_gc` so snapshot
+/// counts stay cumulative. Absent = entities are kept for the whole run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Resource)]
+pub struct EntityGcConfig {
+    /// Interval in seconds between collection passes.
+    pub interval_secs: u64,
+    /// How long a finished trip stays in the world (and in snapshots) before it is despawned.
+    pub retention_secs: u64,
+}
+
+impl Default for EntityGcConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            retention_secs: 600,
+        }
+    }
+}
+
 /// Rider cancel window while waiting for pickup (seconds).
 /// Uses a uniform distribution between min_wait_secs and max_wait_secs.
 #[derive(Debug, Clone, Copy, Resource)]
@@ -70,13 +121,14 @@ impl Default for RiderCancelConfig {
 }
 
 /// Rider quote behavior: reject/retry and give-up after max rejections.
-#[derive(Debug, Clone, Copy, Resource)]
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct RiderQuoteConfig {
     /// Maximum number of quote rejections before rider gives up.
     pub max_quote_rejections: u32,
     /// Delay in seconds before requesting another quote after rejection.
     pub re_quote_delay_secs: u64,
-    /// Probability (0.0–1.0) that rider accepts the quote when within price/ETA limits.
+    /// Probability (0.0–1.0) that rider accepts the quote when within price/ETA limits
+    /// (at the reference price and zero ETA when sensitivities are set).
     pub accept_probability: f64,
     /// Seed for RNG (for reproducibility).
     pub seed: u64,
@@ -84,6 +136,25 @@ pub struct RiderQuoteConfig {
     pub max_willingness_to_pay: f64,
     /// Maximum acceptable ETA to pickup (ms); reject quote if eta_ms exceeds this.
     pub max_acceptable_eta_ms: u64,
+    /// Logit weight on price relative to the rider's reference price, `fare / reference - 1`
+    /// (negative: pricier quotes are accepted less). 0.0 ignores price within the limits.
+    pub price_sensitivity: f64,
+    /// Logit weight per minute of pickup ETA (negative: longer waits are accepted less).
+    pub eta_sensitivity_per_min: f64,
+    /// Spread of rider-specific reference prices around the base (non-surge) fare, as a fraction.
+    /// 0.2 draws each rider's reference uniformly from 0.8x–1.2x the base fare.
+    pub reference_price_spread: f64,
+    /// Quote time-to-live in seconds. A quote decided after it expired is rejected and the rider
+    /// re-requests at current surge. None = quotes never expire.
+    pub quote_ttl_secs: Option<u64>,
+    /// Seconds the rider takes to decide on a quote.
+    pub decision_delay_secs: u64,
+    /// Extra decision time drawn uniformly from `0..=decision_delay_jitter_secs` per quote.
+    pub decision_delay_jitter_secs: u64,
+    /// Trained conversion model (linear-model JSON, see `ConversionModel`) that replaces
+    /// [`accept_probability_for`](Self::accept_probability_for) within the price/ETA limits.
+    /// None = hand-tuned logit. Falls back to the logit if the file cannot be loaded.
+    pub conversion_model_path: Option<String>,
 }
 
 impl Default for RiderQuoteConfig {
@@ -95,12 +166,42 @@ impl Default for RiderQuoteConfig {
             seed: 0,
             max_willingness_to_pay: 100.0,
             max_acceptable_eta_ms: 600_000, // 10 min
+            price_sensitivity: 0.0,
+            eta_sensitivity_per_min: 0.0,
+            reference_price_spread: 0.0,
+            quote_ttl_secs: None,
+            decision_delay_secs: 1,
+            decision_delay_jitter_secs: 0,
+            conversion_model_path: None,
+        }
+    }
+}
+
+impl RiderQuoteConfig {
+    /// Probability that a rider accepts a quote within the price/ETA limits.
+    ///
+    /// Logit model with intercept `logit(accept_probability)`, so with zero sensitivities the
+    /// result is exactly `accept_probability` (the flat model).
+    pub fn accept_probability_for(&self, fare: f64, reference_fare: f64, eta_ms: u64) -> f64 {
+        if self.price_sensitivity == 0.0 && self.eta_sensitivity_per_min == 0.0 {
+            return self.accept_probability;
         }
+        let base = self.accept_probability.clamp(1e-6, 1.0 - 1e-6);
+        let relative_price = if reference_fare > 0.0 {
+            fare / reference_fare - 1.0
+        } else {
+            0.0
+        };
+        let eta_min = eta_ms as f64 / 60_000.0;
+        let score = (base / (1.0 - base)).ln()
+            + self.price_sensitivity * relative_price
+            + self.eta_sensitivity_per_min * eta_min;
+        1.0 / (1.0 + (-score).exp())
     }
 }
 
 /// Driver decision behavior: stochastic logit model for accept/reject decisions.
-#[derive(Debug, Clone, Copy, Resource)]
+#[derive(Debug, Clone, Serialize, Deserialize, Resource)]
 pub struct DriverDecisionConfig {
     /// Seed for RNG (for reproducibility).
     pub seed: u64,
@@ -116,6 +217,9 @@ pub struct DriverDecisionConfig {
     pub fatigue_penalty: f64,
     /// Base acceptance score before factors are applied.
     pub base_acceptance_score: f64,
+    /// Trained acceptance model (linear-model JSON, see `DriverAcceptanceModel`) that replaces
+    /// the weights above. None = fixed weights. Falls back to them if the file cannot be loaded.
+    pub acceptance_model_path: Option<String>,
 }
 
 impl Default for DriverDecisionConfig {
@@ -128,12 +232,13 @@ impl Default for DriverDecisionConfig {
             earnings_progress_weight: -0.5,
             fatigue_penalty: -1.0,
             base_acceptance_score: 1.0,
+            acceptance_model_path: None,
         }
     }
 }
 
 /// Parameters for building a simulation scenario.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScenarioParams {
     pub num_riders: usize,
     pub num_drivers: usize,
@@ -151,8 +256,20 @@ pub struct ScenarioParams {
     pub max_trip_cells: u32,
     /// Optional epoch for time-of-day patterns. If None, defaults to 0.
     pub epoch_ms: Option<i64>,
+    /// City wall clock for time-of-day patterns, traffic and scheduled surge. Defaults to UTC.
+    pub local_time: LocalTime,
+    /// Order of events sharing a timestamp, by kind. Defaults to [`EventKind::default_priority`].
+    pub event_priorities: EventPriorities,
     /// Optional pricing configuration. If None, defaults are used.
     pub pricing_config: Option<PricingConfig>,
+    /// Optional per-zone commission rates (used by `CommissionModel::PerZone`).
+    pub commission_zones: Option<CommissionZones>,
+    /// Optional toll zones / toll road segments (pass-through charges added to fares).
+    pub toll_zones: Option<TollZones>,
+    /// Optional per-zone tax rates and regulatory fees (override `PricingConfig` defaults).
+    pub tax_zones: Option<TaxZones>,
+    /// Optional vehicle class menu; riders compare quotes across classes when set.
+    pub vehicle_classes: Option<VehicleClassMenu>,
     /// Optional rider quote behavior config. If None, defaults are used.
     pub rider_quote_config: Option<RiderQuoteConfig>,
     /// Optional driver decision behavior config. If None, defaults are used.
@@ -167,6 +284,9 @@ pub struct ScenarioParams {
     pub batch_interval_secs: Option<u64>,
     /// ETA weight for cost-based and Hungarian matching algorithms. If None, uses DEFAULT_ETA_WEIGHT.
     pub eta_weight: Option<f64>,
+    /// Largest connected batch (smaller side, riders or drivers) Hungarian matching solves
+    /// exactly; larger ones are assigned greedily. If None, uses `DEFAULT_MAX_EXACT_SIZE` (500).
+    pub hungarian_max_exact_size: Option<usize>,
     /// Which routing backend to use. Defaults to H3Grid (existing behaviour).
     pub route_provider_kind: RouteProviderKind,
     /// Traffic profile (time-of-day speed factors). Defaults to None (no traffic effects).
@@ -175,11 +295,58 @@ pub struct ScenarioParams {
     pub congestion_zones_enabled: bool,
     /// Whether dynamic congestion from vehicle density is enabled.
     pub dynamic_congestion_enabled: bool,
+    /// Mid-route rerouting on traffic changes. Defaults to None (disabled; a trip leg keeps
+    /// the route resolved on its first move step).
+    pub reroute_config: Option<RerouteConfig>,
     /// Free-flow base speed in km/h (used as the reference speed before traffic factors).
     /// When set, overrides the default SpeedModel range. Defaults to None (use 20-60 km/h).
     pub base_speed_kmh: Option<f64>,
-    /// Spawn location weighting. Defaults to Uniform (existing behaviour).
+    /// Base speeds per road class, applied on routes with road-class annotations. Defaults
+    /// to None (30/50/90 km/h for residential/arterial/highway).
+    pub road_class_speeds: Option<RoadClassSpeeds>,
+    /// Spawn location weighting (Uniform, BerlinHotspots, or a hotspot file). Defaults to Uniform.
     pub spawn_weighting: SpawnWeightingKind,
+    /// Origin-destination demand matrix. When set, rider pickup and dropoff are sampled jointly
+    /// from zone flows (overrides spawn weighting and trip length for riders). Defaults to None.
+    pub od_matrix: Option<OdMatrix>,
+    /// Special-event demand spikes (venue request bursts on top of regular demand). Defaults to None.
+    pub demand_events: Option<DemandEvents>,
+    /// Weekly rider demand profile (hourly multipliers per day of week). Defaults to None
+    /// (built-in weekday + Friday/Saturday profiles).
+    pub rider_weekly_pattern: Option<WeeklyPattern>,
+    /// Weekly driver supply profile. Defaults to None (built-in weekday + Friday/Saturday profiles).
+    pub driver_weekly_pattern: Option<WeeklyPattern>,
+    /// Driver supply elasticity to lagged surge and incentives. Defaults to None (fixed schedule).
+    pub supply_elasticity: Option<SupplyElasticity>,
+    /// Snapshot interval, retention and content (entity lists, cell aggregates, sampling).
+    /// Defaults to None (`SimSnapshotConfig::default()`).
+    pub snapshot_config: Option<SimSnapshotConfig>,
+    /// Driver location update cadence and GPS noise seen by matching. Defaults to None
+    /// (matching uses true driver positions).
+    pub location_reporting: Option<LocationReportingConfig>,
+    /// Despawn finished trips and riders periodically (see `EntityGcConfig`). Defaults to None
+    /// (terminal entities are kept for the whole run).
+    pub entity_gc: Option<EntityGcConfig>,
+    /// Conditions that end the run before `simulation_end_time_ms` (see `StopCondition`).
+    /// Defaults to empty (fixed horizon).
+    pub stop_conditions: Vec<StopCondition>,
+    /// Drivers carried over from a previous run's end state (see `WarmStartState`). Defaults to
+    /// None (the city starts empty).
+    pub warm_start: Option<WarmStartState>,
+    /// Record scored candidate drivers for every matching decision (`MatchDiagnostics`).
+    /// Defaults to false; scoring every candidate adds overhead to each matching pass.
+    pub match_diagnostics: bool,
+    /// Record market features of every quote (`QuoteFeatureLog`). Defaults to false.
+    pub quote_features: bool,
+    /// Alternative algorithm evaluated (never applied) at every dispatch decision; choices are
+    /// recorded in `ShadowMatchLog`. Defaults to None.
+    pub shadow_matching_algorithm_type: Option<MatchingAlgorithmType>,
+    /// Per-km CO2 factors by vehicle type. Defaults to None (`EmissionFactors::default()`).
+    pub emission_factors: Option<EmissionFactors>,
+    /// Fleet vehicle-type mix drivers are drawn from. Defaults to None (all petrol).
+    pub vehicle_mix: Option<VehicleMix>,
+    /// Surge multiplier policy (used when surge is enabled in pricing). Defaults to SupplyDemandRatio.
+    pub surge_strategy: SurgeStrategyKind,
 }
 
 impl Default for ScenarioParams {
@@ -200,7 +367,13 @@ impl Default for ScenarioParams {
             min_trip_cells: 5,
             max_trip_cells: 60,
             epoch_ms: None,
+            local_time: LocalTime::UTC,
+            event_priorities: EventPriorities::default(),
             pricing_config: None,
+            commission_zones: None,
+            toll_zones: None,
+            tax_zones: None,
+            vehicle_classes: None,
             rider_quote_config: None,
             driver_decision_config: None,
             simulation_end_time_ms: None,
@@ -208,12 +381,31 @@ impl Default for ScenarioParams {
             batch_matching_enabled: None,
             batch_interval_secs: None,
             eta_weight: None,
+            hungarian_max_exact_size: None,
             route_provider_kind: RouteProviderKind::default(),
             traffic_profile: TrafficProfileKind::default(),
             congestion_zones_enabled: false,
             dynamic_congestion_enabled: false,
+            reroute_config: None,
             base_speed_kmh: None,
+            road_class_speeds: None,
             spawn_weighting: SpawnWeightingKind::default(),
+            od_matrix: None,
+            demand_events: None,
+            rider_weekly_pattern: None,
+            driver_weekly_pattern: None,
+            supply_elasticity: None,
+            snapshot_config: None,
+            location_reporting: None,
+            entity_gc: None,
+            stop_conditions: Vec::new(),
+            warm_start: None,
+            match_diagnostics: false,
+            quote_features: false,
+            shadow_matching_algorithm_type: None,
+            emission_factors: None,
+            vehicle_mix: None,
+            surge_strategy: SurgeStrategyKind::default(),
         }
     }
 }
@@ -236,6 +428,22 @@ impl ScenarioParams {
         self
     }
 
+    /// Run for `days` days: riders request and drivers spawn over the whole period, and the
+    /// simulation ends after it.
+    pub fn with_simulation_days(mut self, days: u64) -> Self {
+        self.request_window_ms = days * ONE_DAY_MS;
+        self.driver_spread_ms = days * ONE_DAY_MS;
+        self.simulation_end_time_ms = Some(days * ONE_DAY_MS);
+        self
+    }
+
+    /// Set weekly rider demand and driver supply profiles.
+    pub fn with_weekly_patterns(mut self, rider: WeeklyPattern, driver: WeeklyPattern) -> Self {
+        self.rider_weekly_pattern = Some(rider);
+        self.driver_weekly_pattern = Some(driver);
+        self
+    }
+
     /// Match riders to drivers within this H3 grid distance (0 = same cell only).
     pub fn with_match_radius(mut self, radius: u32) -> Self {
         self.match_radius = radius;
@@ -255,12 +463,156 @@ impl ScenarioParams {
         self
     }
 
+    /// Read time-of-day patterns in the city's local time zone, following its DST rule.
+    #[cfg(feature = "time-zones")]
+    pub fn with_time_zone(mut self, zone: CityTimeZone) -> Self {
+        self.local_time = LocalTime::zone(zone);
+        self
+    }
+
+    /// Override where `kind` fires among events with the same timestamp (lower fires first).
+    pub fn with_event_priority(mut self, kind: EventKind, priority: u8) -> Self {
+        self.event_priorities.set(kind, priority);
+        self
+    }
+
     /// Set pricing configuration.
     pub fn with_pricing_config(mut self, pricing_config: PricingConfig) -> Self {
         self.pricing_config = Some(pricing_config);
         self
     }
 
+    /// Set the surge strategy used when surge pricing is enabled.
+    pub fn with_surge_strategy(mut self, surge_strategy: SurgeStrategyKind) -> Self {
+        self.surge_strategy = surge_strategy;
+        self
+    }
+
+    /// Set per-zone commission rates.
+    pub fn with_commission_zones(mut self, commission_zones: CommissionZones) -> Self {
+        self.commission_zones = Some(commission_zones);
+        self
+    }
+
+    /// Set per-zone tax rates and regulatory fees.
+    pub fn with_tax_zones(mut self, tax_zones: TaxZones) -> Self {
+        self.tax_zones = Some(tax_zones);
+        self
+    }
+
+    /// Set the vehicle class menu riders compare quotes across.
+    pub fn with_vehicle_classes(mut self, vehicle_classes: VehicleClassMenu) -> Self {
+        self.vehicle_classes = Some(vehicle_classes);
+        self
+    }
+
+    /// Set toll zones / toll road segments.
+    pub fn with_toll_zones(mut self, toll_zones: TollZones) -> Self {
+        self.toll_zones = Some(toll_zones);
+        self
+    }
+
+    /// Spawn riders/drivers from weighted hotspots in a JSON or CSV file.
+    pub fn with_hotspot_file(mut self, path: impl Into<String>) -> Self {
+        self.spawn_weighting = SpawnWeightingKind::HotspotFile(path.into());
+        self
+    }
+
+    /// Sample rider pickup/dropoff pairs from an origin-destination matrix.
+    pub fn with_od_matrix(mut self, od_matrix: OdMatrix) -> Self {
+        self.od_matrix = Some(od_matrix);
+        self
+    }
+
+    /// Add a special-event demand spike.
+    pub fn with_demand_event(mut self, event: DemandEvent) -> Self {
+        self.demand_events = Some(
+            self.demand_events
+                .take()
+                .unwrap_or_default()
+                .with_event(event),
+        );
+        self
+    }
+
+    /// Make driver arrivals respond to lagged surge and incentives.
+    pub fn with_supply_elasticity(mut self, supply: SupplyElasticity) -> Self {
+        self.supply_elasticity = Some(supply);
+        self
+    }
+
+    /// Configure what telemetry snapshots capture and how often.
+    pub fn with_snapshot_config(mut self, config: SimSnapshotConfig) -> Self {
+        self.snapshot_config = Some(config);
+        self
+    }
+
+    /// Match on driver locations reported at a fixed cadence with GPS noise.
+    pub fn with_location_reporting(mut self, config: LocationReportingConfig) -> Self {
+        self.location_reporting = Some(config);
+        self
+    }
+
+    /// Periodically despawn finished trips and riders to bound long multi-day runs.
+    pub fn with_entity_gc(mut self, config: EntityGcConfig) -> Self {
+        self.entity_gc = Some(config);
+        self
+    }
+
+    /// End the run as soon as `condition` holds (in addition to any end time).
+    pub fn with_stop_condition(mut self, condition: StopCondition) -> Self {
+        self.stop_conditions.push(condition);
+        self
+    }
+
+    /// Start from the fleet a previous run ended with (`WarmStartState::capture`).
+    pub fn with_warm_start(mut self, state: WarmStartState) -> Self {
+        self.warm_start = Some(state);
+        self
+    }
+
+    /// Record candidate sets and scores for every matching decision.
+    pub fn with_match_diagnostics(mut self) -> Self {
+        self.match_diagnostics = true;
+        self
+    }
+
+    /// Record market features of every quote shown to a rider.
+    pub fn with_quote_features(mut self) -> Self {
+        self.quote_features = true;
+        self
+    }
+
+    /// Log what `kind` would have chosen at every dispatch decision next to the live choice.
+    pub fn with_shadow_matching(mut self, kind: MatchingAlgorithmType) -> Self {
+        self.shadow_matching_algorithm_type = Some(kind);
+        self
+    }
+
+    /// Enable mid-route rerouting with the given interval and traffic-change threshold.
+    pub fn with_reroute_config(mut self, config: RerouteConfig) -> Self {
+        self.reroute_config = Some(config);
+        self
+    }
+
+    /// Set base speeds per road class for class-annotated routes.
+    pub fn with_road_class_speeds(mut self, speeds: RoadClassSpeeds) -> Self {
+        self.road_class_speeds = Some(speeds);
+        self
+    }
+
+    /// Set per-km CO2 factors by vehicle type.
+    pub fn with_emission_factors(mut self, factors: EmissionFactors) -> Self {
+        self.emission_factors = Some(factors);
+        self
+    }
+
+    /// Set the fleet vehicle-type mix drivers are drawn from at spawn.
+    pub fn with_vehicle_mix(mut self, vehicle_mix: VehicleMix) -> Self {
+        self.vehicle_mix = Some(vehicle_mix);
+        self
+    }
+
     /// Set simulation end time in ms. Runner stops when the next event is at or after this time.
     pub fn with_simulation_end_time_ms(mut self, end_ms: u64) -> Self {
         self.simulation_end_time_ms = Some(end_ms);
@@ -0,0 +1,146 @@
+//! Built-in scenario presets: curated [`ScenarioParams`] bundles addressed by name.
+//!
+//! The UI, experiments and the serverless sweep all resolve presets through
+//! [`ScenarioParams::preset`], so a preset name means the same scenario everywhere. Presets set
+//! no seed; callers add one with `with_seed` (or per run, as experiments do).
+
+use crate::clock::ONE_HOUR_MS;
+use crate::pricing::PricingConfig;
+use crate::spawner::SpawnWeightingKind;
+use crate::traffic::TrafficProfileKind;
+
+use super::params::{DriverDecisionConfig, RiderQuoteConfig, ScenarioParams};
+
+/// Names accepted by [`ScenarioParams::preset`], in display order.
+pub const PRESET_NAMES: [&str; 4] = [
+    "small-test",
+    "berlin-weekday-rush",
+    "suburban-sprawl",
+    "high-churn",
+];
+
+/// Tuesday 2024-03-05 06:00 UTC (07:00 Berlin), the start of the weekday morning rush.
+const WEEKDAY_MORNING_EPOCH_MS: i64 = 1_709_618_400_000;
+
+impl ScenarioParams {
+    /// Built-in preset by name (see [`PRESET_NAMES`]); `None` for unknown names.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "small-test" => Some(small_test()),
+            "berlin-weekday-rush" => Some(berlin_weekday_rush()),
+            "suburban-sprawl" => Some(suburban_sprawl()),
+            "high-churn" => Some(high_churn()),
+            _ => None,
+        }
+    }
+
+    pub fn preset_names() -> &'static [&'static str] {
+        &PRESET_NAMES
+    }
+}
+
+/// Small, fast scenario for smoke tests and demos: 50 riders, 20 drivers, two simulated hours.
+fn small_test() -> ScenarioParams {
+    ScenarioParams {
+        num_riders: 50,
+        num_drivers: 20,
+        initial_rider_count: 10,
+        initial_driver_count: 20,
+        ..Default::default()
+    }
+    .with_request_window_hours(1)
+    .with_driver_spread_hours(1)
+    .with_match_radius(5)
+    .with_trip_duration_cells(5, 20)
+    .with_simulation_end_time_ms(2 * ONE_HOUR_MS)
+}
+
+/// Berlin weekday morning rush: hotspot demand, rush-hour traffic with congestion and surge.
+fn berlin_weekday_rush() -> ScenarioParams {
+    let mut params = ScenarioParams {
+        num_riders: 2_000,
+        num_drivers: 400,
+        initial_driver_count: 150,
+        ..Default::default()
+    }
+    .with_request_window_hours(3)
+    .with_driver_spread_hours(3)
+    .with_match_radius(8)
+    .with_trip_duration_cells(5, 60)
+    .with_epoch_ms(WEEKDAY_MORNING_EPOCH_MS)
+    .with_simulation_end_time_ms(4 * ONE_HOUR_MS)
+    .with_pricing_config(PricingConfig {
+        surge_enabled: true,
+        ..Default::default()
+    });
+    params.traffic_profile = TrafficProfileKind::Berlin;
+    params.congestion_zones_enabled = true;
+    params.dynamic_congestion_enabled = true;
+    params.spawn_weighting = SpawnWeightingKind::BerlinHotspots;
+    params
+}
+
+/// Low-density suburban area: wide bounds, sparse supply, long trips and a wide match radius.
+fn suburban_sprawl() -> ScenarioParams {
+    let mut params = ScenarioParams {
+        num_riders: 400,
+        num_drivers: 60,
+        initial_driver_count: 30,
+        lat_min: 52.20,
+        lat_max: 52.80,
+        lng_min: 12.80,
+        lng_max: 14.00,
+        ..Default::default()
+    }
+    .with_request_window_hours(4)
+    .with_driver_spread_hours(4)
+    .with_match_radius(15)
+    .with_trip_duration_cells(20, 120)
+    .with_simulation_end_time_ms(6 * ONE_HOUR_MS);
+    params.base_speed_kmh = Some(50.0);
+    params
+}
+
+/// Impatient, price-sensitive riders and picky drivers: high abandonment and rejection rates.
+fn high_churn() -> ScenarioParams {
+    ScenarioParams {
+        num_riders: 800,
+        num_drivers: 120,
+        ..Default::default()
+    }
+    .with_request_window_hours(2)
+    .with_driver_spread_hours(2)
+    .with_match_radius(5)
+    .with_trip_duration_cells(5, 60)
+    .with_simulation_end_time_ms(3 * ONE_HOUR_MS)
+    .with_rider_quote_config(RiderQuoteConfig {
+        max_quote_rejections: 1,
+        accept_probability: 0.6,
+        max_willingness_to_pay: 30.0,
+        max_acceptable_eta_ms: 300_000,
+        price_sensitivity: -3.0,
+        eta_sensitivity_per_min: -0.2,
+        ..Default::default()
+    })
+    .with_driver_decision_config(DriverDecisionConfig {
+        base_acceptance_score: 0.0,
+        pickup_distance_penalty: -3.0,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_listed_preset_resolves() {
+        for name in ScenarioParams::preset_names() {
+            let params = ScenarioParams::preset(name).expect("listed preset should resolve");
+            assert!(params.num_riders > 0 && params.num_drivers > 0, "{name}");
+            assert!(params.min_trip_cells <= params.max_trip_cells, "{name}");
+            assert!(params.simulation_end_time_ms.is_some(), "{name}");
+        }
+        assert!(ScenarioParams::preset("no-such-preset").is_none());
+    }
+}
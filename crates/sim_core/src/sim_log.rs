@@ -0,0 +1,294 @@
+//! Structured simulation log.
+//!
+//! The [`SimLog`] resource collects warnings that would otherwise go unnoticed: riders that
+//! found no driver in range, spawners that ran out of agents, routes that fell back to the H3
+//! grid and scenario inputs (route tables, hotspot files, decision models) that failed to
+//! load. Each entry carries a severity, a category and the entity it concerns, so a front end
+//! can filter the log and link entries to that entity.
+//!
+//! Systems record entries through [`SimLogCommands`], which defers the write to the end of the
+//! schedule run and is a no-op when no [`SimLog`] is present. The log is bounded; the oldest
+//! entries are dropped first.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use bevy_ecs::prelude::{Commands, Entity, Resource, World};
+
+/// Default cap on entries kept in the log.
+pub const DEFAULT_SIM_LOG_CAPACITY: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for LogSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogCategory {
+    Matching,
+    Spawner,
+    Routing,
+    Scenario,
+}
+
+impl LogCategory {
+    pub const ALL: [Self; 4] = [Self::Matching, Self::Spawner, Self::Routing, Self::Scenario];
+}
+
+impl fmt::Display for LogCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Matching => "matching",
+            Self::Spawner => "spawner",
+            Self::Routing => "routing",
+            Self::Scenario => "scenario",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpawnerKind {
+    Rider,
+    Driver,
+}
+
+/// What happened. Severity, category and message are derived from the variant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimLogEvent {
+    /// A rider's match attempt found no idle driver in range; matching is retried later.
+    NoDriverInRadius { rider: Entity, idle_drivers: usize },
+    /// A spawner reached its maximum count and stops spawning.
+    SpawnerExhausted {
+        spawner: SpawnerKind,
+        spawned: usize,
+    },
+    /// The route provider returned no route for a trip leg; the H3 grid path is driven instead.
+    RouteFallback { trip: Entity },
+    /// Neither the route provider nor the H3 grid produced a route; the leg ends in place.
+    RouteUnavailable { trip: Entity },
+    /// The configured route provider could not be built; the scenario runs on the H3 grid.
+    RouteProviderFallback { reason: String },
+    /// The configured spawn hotspots could not be loaded; spawning is uniform.
+    SpawnWeightingFallback { reason: String },
+    /// A configured rider conversion or driver acceptance model could not be loaded; decisions
+    /// use the hand-tuned logit.
+    DecisionModelFallback { reason: String },
+}
+
+impl SimLogEvent {
+    pub fn severity(&self) -> LogSeverity {
+        match self {
+            Self::SpawnerExhausted { .. } => LogSeverity::Info,
+            Self::NoDriverInRadius { .. } | Self::RouteFallback { .. } => LogSeverity::Warning,
+            Self::RouteUnavailable { .. }
+            | Self::RouteProviderFallback { .. }
+            | Self::SpawnWeightingFallback { .. }
+            | Self::DecisionModelFallback { .. } => LogSeverity::Error,
+        }
+    }
+
+    pub fn category(&self) -> LogCategory {
+        match self {
+            Self::NoDriverInRadius { .. } => LogCategory::Matching,
+            Self::SpawnerExhausted { .. } => LogCategory::Spawner,
+            Self::RouteFallback { .. } | Self::RouteUnavailable { .. } => LogCategory::Routing,
+            Self::RouteProviderFallback { .. }
+            | Self::SpawnWeightingFallback { .. }
+            | Self::DecisionModelFallback { .. } => LogCategory::Scenario,
+        }
+    }
+
+    /// The rider or trip the entry concerns, if any.
+    pub fn entity(&self) -> Option<Entity> {
+        match self {
+            Self::NoDriverInRadius { rider, .. } => Some(*rider),
+            Self::RouteFallback { trip } | Self::RouteUnavailable { trip } => Some(*trip),
+            Self::SpawnerExhausted { .. }
+            | Self::RouteProviderFallback { .. }
+            | Self::SpawnWeightingFallback { .. }
+            | Self::DecisionModelFallback { .. } => None,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::NoDriverInRadius { idle_drivers, .. } => format!(
+                "No idle driver within the match radius ({idle_drivers} idle in total); retrying"
+            ),
+            Self::SpawnerExhausted { spawner, spawned } => {
+                let agents = match spawner {
+                    SpawnerKind::Rider => "riders",
+                    SpawnerKind::Driver => "drivers",
+                };
+                format!("Spawner exhausted after {spawned} {agents}")
+            }
+            Self::RouteFallback { .. } => {
+                "Route provider returned no route; driving the H3 grid path".to_string()
+            }
+            Self::RouteUnavailable { .. } => {
+                "No route to the target; leg ended at the current position".to_string()
+            }
+            Self::RouteProviderFallback { reason } => {
+                format!("{reason}. Falling back to H3Grid routing")
+            }
+            Self::SpawnWeightingFallback { reason } => {
+                format!("{reason}. Falling back to uniform spawning")
+            }
+            Self::DecisionModelFallback { reason } => {
+                format!("{reason}. Falling back to the hand-tuned logit")
+            }
+        }
+    }
+}
+
+/// One log entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimLogEntry {
+    /// Simulation time in ms.
+    pub timestamp_ms: u64,
+    pub event: SimLogEvent,
+}
+
+impl SimLogEntry {
+    pub fn severity(&self) -> LogSeverity {
+        self.event.severity()
+    }
+
+    pub fn category(&self) -> LogCategory {
+        self.event.category()
+    }
+}
+
+/// Bounded, time-ordered log of simulation warnings.
+#[derive(Debug, Clone, Resource)]
+pub struct SimLog {
+    entries: VecDeque<SimLogEntry>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl Default for SimLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_SIM_LOG_CAPACITY)
+    }
+}
+
+impl SimLog {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, timestamp_ms: u64, event: SimLogEvent) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(SimLogEntry {
+            timestamp_ms,
+            event,
+        });
+    }
+
+    /// Entries in the order they were recorded, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &SimLogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries dropped because the log was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Number of kept entries with the given severity.
+    pub fn count(&self, severity: LogSeverity) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.severity() == severity)
+            .count()
+    }
+}
+
+/// Record [`SimLog`] entries from systems.
+pub trait SimLogCommands {
+    fn log_sim_event(&mut self, timestamp_ms: u64, event: SimLogEvent);
+}
+
+impl SimLogCommands for Commands<'_, '_> {
+    fn log_sim_event(&mut self, timestamp_ms: u64, event: SimLogEvent) {
+        self.add(move |world: &mut World| {
+            if let Some(mut log) = world.get_resource_mut::<SimLog>() {
+                log.push(timestamp_ms, event);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_log_drops_oldest_entries() {
+        let mut log = SimLog::with_capacity(2);
+        for spawned in 1..=3 {
+            log.push(
+                spawned as u64,
+                SimLogEvent::SpawnerExhausted {
+                    spawner: SpawnerKind::Rider,
+                    spawned,
+                },
+            );
+        }
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.dropped(), 1);
+        assert_eq!(
+            log.entries().next().map(|entry| entry.timestamp_ms),
+            Some(2)
+        );
+        assert_eq!(log.count(LogSeverity::Info), 2);
+    }
+
+    #[test]
+    fn events_derive_severity_category_and_entity() {
+        let rider = Entity::from_raw(7);
+        let event = SimLogEvent::NoDriverInRadius {
+            rider,
+            idle_drivers: 3,
+        };
+        assert_eq!(event.severity(), LogSeverity::Warning);
+        assert_eq!(event.category(), LogCategory::Matching);
+        assert_eq!(event.entity(), Some(rider));
+        assert!(event.message().contains("3 idle"));
+
+        let event = SimLogEvent::RouteProviderFallback {
+            reason: "table missing".to_string(),
+        };
+        assert_eq!(event.severity(), LogSeverity::Error);
+        assert_eq!(event.entity(), None);
+    }
+}
@@ -4,7 +4,10 @@
 //!
 //! - **GeoIndex**: Wrapper for H3 resolution configuration
 //! - **Grid disk queries**: Find cells within K grid distance
-//! - **Distance calculations**: Haversine distance between H3 cells
+//! - **Cells in bounds**: Cells inside a lat/lng box, materialized per tile (eagerly in parallel
+//!   for city-sized maps, on demand for very large ones) and cached
+//! - **Distance calculations**: Haversine distance between H3 cells, cached per pair or batched
+//!   from one origin to many cells
 //! - **SpatialIndex**: H3 cell → entity mappings for efficient spatial queries
 //!
 //! Default resolution is 9 (~240m cell size), suitable for city-scale simulations.
@@ -12,9 +15,10 @@
 use bevy_ecs::prelude::{Entity, Resource};
 use h3o::{CellIndex, LatLng, Resolution};
 use lru::LruCache;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroUsize;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
 
 #[derive(Debug, Clone, Copy)]
 pub struct GeoIndex {
@@ -183,17 +187,16 @@ fn get_path_cache() -> &'static PathCache {
 /// Spatial index for efficient entity lookups by H3 cell.
 ///
 /// Maintains mappings from H3 cells to entities (riders and drivers) for O(1) spatial queries
-/// instead of scanning all entities. Updated incrementally as entities move or change state.
+/// instead of scanning all entities. Updated incrementally by `update_spatial_index_system`:
+/// insert, remove and move are O(1) per entity, and only entities marked with
+/// [`mark_moved`](Self::mark_moved) are re-read after a move. Code that changes a rider's or
+/// driver's `Position` must mark it; new and despawned entities are picked up without marks.
 #[derive(Debug, Resource, Default)]
 pub struct SpatialIndex {
-    /// Map from H3 cell to rider entities in that cell
-    riders_by_cell: HashMap<CellIndex, Vec<Entity>>,
-    /// Map from H3 cell to driver entities in that cell
-    drivers_by_cell: HashMap<CellIndex, Vec<Entity>>,
-    /// Reverse mapping: rider entity → current cell (for efficient updates)
-    rider_entity_to_cell: HashMap<Entity, CellIndex>,
-    /// Reverse mapping: driver entity → current cell (for efficient updates)
-    driver_entity_to_cell: HashMap<Entity, CellIndex>,
+    riders: CellBuckets,
+    drivers: CellBuckets,
+    /// Entities whose `Position` changed since the last index update.
+    moved: Mutex<Vec<Entity>>,
 }
 
 impl SpatialIndex {
@@ -202,40 +205,24 @@ impl SpatialIndex {
         Self::default()
     }
 
-    /// Insert a rider entity at the given cell.
+    /// Insert a rider entity at the given cell (moves it if already indexed).
     pub fn insert_rider(&mut self, entity: Entity, cell: CellIndex) {
-        self.riders_by_cell.entry(cell).or_default().push(entity);
-        self.rider_entity_to_cell.insert(entity, cell);
+        self.riders.insert(entity, cell);
     }
 
-    /// Insert a driver entity at the given cell.
+    /// Insert a driver entity at the given cell (moves it if already indexed).
     pub fn insert_driver(&mut self, entity: Entity, cell: CellIndex) {
-        self.drivers_by_cell.entry(cell).or_default().push(entity);
-        self.driver_entity_to_cell.insert(entity, cell);
+        self.drivers.insert(entity, cell);
     }
 
     /// Remove a rider entity from the index.
     pub fn remove_rider(&mut self, entity: Entity) {
-        if let Some(cell) = self.rider_entity_to_cell.remove(&entity) {
-            if let Some(entities) = self.riders_by_cell.get_mut(&cell) {
-                entities.retain(|&e| e != entity);
-                if entities.is_empty() {
-                    self.riders_by_cell.remove(&cell);
-                }
-            }
-        }
+        self.riders.remove(entity);
     }
 
     /// Remove a driver entity from the index.
     pub fn remove_driver(&mut self, entity: Entity) {
-        if let Some(cell) = self.driver_entity_to_cell.remove(&entity) {
-            if let Some(entities) = self.drivers_by_cell.get_mut(&cell) {
-                entities.retain(|&e| e != entity);
-                if entities.is_empty() {
-                    self.drivers_by_cell.remove(&cell);
-                }
-            }
-        }
+        self.drivers.remove(entity);
     }
 
     /// Update a rider's position (remove from old cell, add to new cell).
@@ -245,22 +232,9 @@ impl SpatialIndex {
         old_cell: CellIndex,
         new_cell: CellIndex,
     ) {
-        if old_cell == new_cell {
-            return;
+        if old_cell != new_cell {
+            self.riders.insert(entity, new_cell);
         }
-        // Remove from old cell
-        if let Some(entities) = self.riders_by_cell.get_mut(&old_cell) {
-            entities.retain(|&e| e != entity);
-            if entities.is_empty() {
-                self.riders_by_cell.remove(&old_cell);
-            }
-        }
-        // Add to new cell
-        self.riders_by_cell
-            .entry(new_cell)
-            .or_default()
-            .push(entity);
-        self.rider_entity_to_cell.insert(entity, new_cell);
     }
 
     /// Update a driver's position (remove from old cell, add to new cell).
@@ -270,62 +244,121 @@ impl SpatialIndex {
         old_cell: CellIndex,
         new_cell: CellIndex,
     ) {
-        if old_cell == new_cell {
-            return;
-        }
-        // Remove from old cell
-        if let Some(entities) = self.drivers_by_cell.get_mut(&old_cell) {
-            entities.retain(|&e| e != entity);
-            if entities.is_empty() {
-                self.drivers_by_cell.remove(&old_cell);
-            }
+        if old_cell != new_cell {
+            self.drivers.insert(entity, new_cell);
         }
-        // Add to new cell
-        self.drivers_by_cell
-            .entry(new_cell)
-            .or_default()
-            .push(entity);
-        self.driver_entity_to_cell.insert(entity, new_cell);
     }
 
     /// Get all rider entities in the given cells.
     pub fn get_riders_in_cells(&self, cells: &[CellIndex]) -> Vec<Entity> {
-        let mut result = Vec::new();
-        for cell in cells {
-            if let Some(entities) = self.riders_by_cell.get(cell) {
-                result.extend(entities.iter().copied());
-            }
-        }
-        result
+        self.riders.in_cells(cells)
     }
 
     /// Get all driver entities in the given cells.
     pub fn get_drivers_in_cells(&self, cells: &[CellIndex]) -> Vec<Entity> {
-        let mut result = Vec::new();
-        for cell in cells {
-            if let Some(entities) = self.drivers_by_cell.get(cell) {
-                result.extend(entities.iter().copied());
-            }
-        }
-        result
+        self.drivers.in_cells(cells)
     }
 
     /// Get the current cell for a rider entity.
     pub fn get_rider_cell(&self, entity: Entity) -> Option<CellIndex> {
-        self.rider_entity_to_cell.get(&entity).copied()
+        self.riders.cell(entity)
     }
 
     /// Get the current cell for a driver entity.
     pub fn get_driver_cell(&self, entity: Entity) -> Option<CellIndex> {
-        self.driver_entity_to_cell.get(&entity).copied()
+        self.drivers.cell(entity)
+    }
+
+    /// Number of indexed riders.
+    pub fn rider_count(&self) -> usize {
+        self.riders.len()
+    }
+
+    /// Number of indexed drivers.
+    pub fn driver_count(&self) -> usize {
+        self.drivers.len()
+    }
+
+    /// Record that `entity`'s `Position` changed; applied at the next index update. Takes
+    /// `&self` so systems holding `Res<SpatialIndex>` can mark moves.
+    pub fn mark_moved(&self, entity: Entity) {
+        match self.moved.lock() {
+            Ok(mut moved) => moved.push(entity),
+            Err(poisoned) => poisoned.into_inner().push(entity),
+        }
+    }
+
+    /// Entities marked since the last call, sorted and deduplicated so updates are applied in
+    /// a deterministic order.
+    pub fn take_moved(&mut self) -> Vec<Entity> {
+        let moved = match self.moved.get_mut() {
+            Ok(moved) => moved,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut moved = std::mem::take(moved);
+        moved.sort_unstable();
+        moved.dedup();
+        moved
     }
 
     /// Clear all entries (for reset scenarios).
     pub fn clear(&mut self) {
-        self.riders_by_cell.clear();
-        self.drivers_by_cell.clear();
-        self.rider_entity_to_cell.clear();
-        self.driver_entity_to_cell.clear();
+        self.riders = CellBuckets::default();
+        self.drivers = CellBuckets::default();
+        self.take_moved();
+    }
+}
+
+/// Entities grouped by cell, with each entity's slot in its cell's list so that insert,
+/// remove and move are O(1) (removal swaps the cell's last entity into the freed slot).
+#[derive(Debug, Default)]
+struct CellBuckets {
+    by_cell: HashMap<CellIndex, Vec<Entity>>,
+    slots: HashMap<Entity, (CellIndex, usize)>,
+}
+
+impl CellBuckets {
+    fn insert(&mut self, entity: Entity, cell: CellIndex) {
+        if self.cell(entity) == Some(cell) {
+            return;
+        }
+        self.remove(entity);
+        let entities = self.by_cell.entry(cell).or_default();
+        self.slots.insert(entity, (cell, entities.len()));
+        entities.push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some((cell, slot)) = self.slots.remove(&entity) else {
+            return;
+        };
+        let Some(entities) = self.by_cell.get_mut(&cell) else {
+            return;
+        };
+        entities.swap_remove(slot);
+        if let Some(&swapped) = entities.get(slot) {
+            self.slots.insert(swapped, (cell, slot));
+        }
+        if entities.is_empty() {
+            self.by_cell.remove(&cell);
+        }
+    }
+
+    fn cell(&self, entity: Entity) -> Option<CellIndex> {
+        self.slots.get(&entity).map(|&(cell, _)| cell)
+    }
+
+    fn in_cells(&self, cells: &[CellIndex]) -> Vec<Entity> {
+        cells
+            .iter()
+            .filter_map(|cell| self.by_cell.get(cell))
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
     }
 }
 
@@ -346,6 +379,72 @@ pub fn distance_km_between_cells(a: CellIndex, b: CellIndex) -> f64 {
     *cache.get_or_insert(key, || distance_km_between_cells_uncached(key.0, key.1))
 }
 
+/// Haversine distances (km) from `origin` to each of `targets`, in order.
+///
+/// Batched alternative to calling [`distance_km_between_cells`] per pair when scoring many
+/// candidates against one rider: the shared cache is locked once for the whole batch, and misses
+/// are computed from an origin converted only once. Results are bit-identical to the per-pair
+/// path and populate the same cache.
+pub fn distances_km_from_cell(origin: CellIndex, targets: &[CellIndex]) -> Vec<f64> {
+    let mut from_origin: Option<HaversineOrigin> = None;
+    let mut cache = match get_distance_cache().lock() {
+        Ok(guard) => guard,
+        Err(_) => {
+            // Fallback: compute without cache if mutex poisoned
+            let from_origin = HaversineOrigin::new(origin);
+            return targets
+                .iter()
+                .map(|&target| from_origin.distance_km(target))
+                .collect();
+        }
+    };
+    targets
+        .iter()
+        .map(|&target| {
+            let key = if origin < target {
+                (origin, target)
+            } else {
+                (target, origin)
+            };
+            *cache.get_or_insert(key, || {
+                from_origin
+                    .get_or_insert_with(|| HaversineOrigin::new(origin))
+                    .distance_km(target)
+            })
+        })
+        .collect()
+}
+
+/// Origin of a batch of haversine distances, in radians with its latitude cosine precomputed.
+struct HaversineOrigin {
+    lat: f64,
+    lng: f64,
+    cos_lat: f64,
+}
+
+impl HaversineOrigin {
+    fn new(cell: CellIndex) -> Self {
+        let coord: LatLng = cell.into();
+        let lat = coord.lat().to_radians();
+        Self {
+            lat,
+            lng: coord.lng().to_radians(),
+            cos_lat: lat.cos(),
+        }
+    }
+
+    /// Same formula as `distance_km_between_cells_uncached`, term for term.
+    fn distance_km(&self, target: CellIndex) -> f64 {
+        let target: LatLng = target.into();
+        let (lat2, lng2) = (target.lat().to_radians(), target.lng().to_radians());
+        let sin_dlat = ((lat2 - self.lat) * 0.5).sin();
+        let sin_dlng = ((lng2 - self.lng) * 0.5).sin();
+        let h = sin_dlat * sin_dlat + self.cos_lat * lat2.cos() * sin_dlng * sin_dlng;
+        let c = 2.0 * h.sqrt().atan2((1.0 - h).sqrt());
+        6371.0 * c
+    }
+}
+
 impl Default for GeoIndex {
     fn default() -> Self {
         Self {
@@ -360,6 +459,231 @@ pub fn grid_disk_cached(origin: CellIndex, k: u32) -> Vec<CellIndex> {
     get_grid_disk_cache().get_or_compute(origin, k, &geo)
 }
 
+/// Bounds are split into tiles this many resolutions above the cell resolution (343 cells per
+/// tile); each tile's in-bounds cells are materialized on first use.
+const TILE_LEVELS: u8 = 3;
+
+/// Bounds estimated to hold more cells than this are not materialized up front; their tiles fill
+/// in where agents actually spawn and travel.
+const EAGER_MAX_CELLS: f64 = 200_000.0;
+
+/// Tiles materialized on the calling thread below this count.
+const PARALLEL_MATERIALIZE_MIN_TILES: usize = 64;
+
+/// Bounds as `f64` bits plus resolution.
+type BoundsKey = ([u64; 4], Resolution);
+
+/// In-bounds cell sets per geometry; only a handful of geometries are live at a time.
+type BoundsCellsCache = Mutex<LruCache<BoundsKey, Arc<BoundsCells>>>;
+
+fn get_bounds_cells_cache() -> &'static BoundsCellsCache {
+    static CACHE: OnceLock<BoundsCellsCache> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(LruCache::new(
+            NonZeroUsize::new(8).expect("cache size must be non-zero"),
+        ))
+    })
+}
+
+/// Cells at one resolution whose centers lie within lat/lng bounds (same test as
+/// [`cell_in_bounds`]), materialized per tile (a coarser parent cell) on demand.
+///
+/// City-sized bounds are filled up front with [`Self::materialize_all`]; for very large, sparse
+/// maps only the tiles agents actually touch are ever computed.
+#[derive(Debug)]
+pub struct BoundsCells {
+    lat_min: f64,
+    lat_max: f64,
+    lng_min: f64,
+    lng_max: f64,
+    resolution: Resolution,
+    tile_resolution: Resolution,
+    tiles: Mutex<HashMap<CellIndex, HashSet<CellIndex>>>,
+}
+
+impl BoundsCells {
+    pub fn new(
+        lat_min: f64,
+        lat_max: f64,
+        lng_min: f64,
+        lng_max: f64,
+        resolution: Resolution,
+    ) -> Self {
+        let tile_resolution =
+            Resolution::try_from(u8::from(resolution).saturating_sub(TILE_LEVELS))
+                .expect("coarser resolution is valid");
+        Self {
+            lat_min,
+            lat_max,
+            lng_min,
+            lng_max,
+            resolution,
+            tile_resolution,
+            tiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether `cell` lies within the bounds; materializes its tile on first use.
+    pub fn contains(&self, cell: CellIndex) -> bool {
+        let in_bounds =
+            || cell_in_bounds(cell, self.lat_min, self.lat_max, self.lng_min, self.lng_max);
+        let Some(tile) = cell.parent(self.tile_resolution) else {
+            return in_bounds();
+        };
+        let mut tiles = match self.tiles.lock() {
+            Ok(guard) => guard,
+            Err(_) => return in_bounds(), // Fallback: check directly if mutex poisoned
+        };
+        tiles
+            .entry(tile)
+            .or_insert_with(|| self.tile_cells(tile))
+            .contains(&cell)
+    }
+
+    /// Tiles materialized so far.
+    pub fn materialized_tiles(&self) -> usize {
+        self.tiles.lock().map_or(0, |tiles| tiles.len())
+    }
+
+    /// Materialize every tile covering the bounds, in parallel. Skipped (returns false) when the
+    /// bounds hold more than `EAGER_MAX_CELLS` cells; those tiles stay lazy.
+    pub fn materialize_all(&self) -> bool {
+        let Some(tiles) = self.covering_tiles() else {
+            return false;
+        };
+        let tiles: Vec<CellIndex> = {
+            let Ok(materialized) = self.tiles.lock() else {
+                return false;
+            };
+            tiles
+                .into_iter()
+                .filter(|tile| !materialized.contains_key(tile))
+                .collect()
+        };
+        let computed: Vec<(CellIndex, HashSet<CellIndex>)> =
+            if tiles.len() < PARALLEL_MATERIALIZE_MIN_TILES {
+                tiles
+                    .iter()
+                    .map(|&tile| (tile, self.tile_cells(tile)))
+                    .collect()
+            } else {
+                let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+                let chunk_size = tiles.len().div_ceil(threads);
+                thread::scope(|scope| {
+                    let chunks: Vec<_> = tiles
+                        .chunks(chunk_size)
+                        .map(|chunk| {
+                            scope.spawn(move || {
+                                chunk
+                                    .iter()
+                                    .map(|&tile| (tile, self.tile_cells(tile)))
+                                    .collect::<Vec<_>>()
+                            })
+                        })
+                        .collect();
+                    chunks
+                        .into_iter()
+                        .flat_map(|chunk| chunk.join().expect("tile materialization panicked"))
+                        .collect()
+                })
+            };
+        let Ok(mut materialized) = self.tiles.lock() else {
+            return false;
+        };
+        materialized.extend(computed);
+        true
+    }
+
+    fn tile_cells(&self, tile: CellIndex) -> HashSet<CellIndex> {
+        tile.children(self.resolution)
+            .filter(|cell| {
+                cell_in_bounds(
+                    *cell,
+                    self.lat_min,
+                    self.lat_max,
+                    self.lng_min,
+                    self.lng_max,
+                )
+            })
+            .collect()
+    }
+
+    /// Tiles around the bounds' center, in a disk wide enough to reach every corner. None when
+    /// the bounds are inverted or too large to materialize up front.
+    fn covering_tiles(&self) -> Option<Vec<CellIndex>> {
+        if !(self.lat_min <= self.lat_max && self.lng_min <= self.lng_max) {
+            return None;
+        }
+        let center_lat = (self.lat_min + self.lat_max) / 2.0;
+        let center_ll = LatLng::new(center_lat, (self.lng_min + self.lng_max) / 2.0).ok()?;
+        let height_km = distance_km_between_lat_lng(
+            LatLng::new(self.lat_min, center_ll.lng()).ok()?,
+            LatLng::new(self.lat_max, center_ll.lng()).ok()?,
+        );
+        let width_km = distance_km_between_lat_lng(
+            LatLng::new(center_lat, self.lng_min).ok()?,
+            LatLng::new(center_lat, self.lng_max).ok()?,
+        );
+        if height_km * width_km / self.resolution.area_km2() > EAGER_MAX_CELLS {
+            return None;
+        }
+
+        let center = center_ll.to_cell(self.tile_resolution);
+        let center_ll: LatLng = center.into();
+        let spacing_km = center
+            .grid_disk::<Vec<_>>(1)
+            .into_iter()
+            .filter(|tile| *tile != center)
+            .map(|tile| distance_km_between_lat_lng(center_ll, tile.into()))
+            .fold(f64::INFINITY, f64::min);
+        let reach_km = [
+            (self.lat_min, self.lng_min),
+            (self.lat_min, self.lng_max),
+            (self.lat_max, self.lng_min),
+            (self.lat_max, self.lng_max),
+        ]
+        .into_iter()
+        .filter_map(|(lat, lng)| LatLng::new(lat, lng).ok())
+        .map(|corner| distance_km_between_lat_lng(center_ll, corner))
+        .fold(0.0, f64::max);
+        // A tile n steps away is at least n * spacing * sqrt(3)/2 away; the extra rings cover
+        // children that stick out of their parent's hexagon. Tiles the disk misses still
+        // materialize on first use.
+        let k = (reach_km / (spacing_km * 0.75)).ceil() + 2.0;
+        k.is_finite().then(|| center.grid_disk(k as u32))
+    }
+}
+
+/// The [`BoundsCells`] for these bounds and resolution, shared per (bounds, resolution) so
+/// scenario resets with unchanged geometry reuse the materialized tiles.
+pub fn cells_in_bounds(
+    lat_min: f64,
+    lat_max: f64,
+    lng_min: f64,
+    lng_max: f64,
+    resolution: Resolution,
+) -> Arc<BoundsCells> {
+    let key = (
+        [
+            lat_min.to_bits(),
+            lat_max.to_bits(),
+            lng_min.to_bits(),
+            lng_max.to_bits(),
+        ],
+        resolution,
+    );
+    let create = || {
+        Arc::new(BoundsCells::new(
+            lat_min, lat_max, lng_min, lng_max, resolution,
+        ))
+    };
+    let mut cache = match get_bounds_cells_cache().lock() {
+        Ok(guard) => guard,
+        Err(_) => return create(), // Fallback: fresh, uncached set if mutex poisoned
+    };
+    cache.get_or_insert(key, create).clone()
+}
+
 /// Get grid path with caching.
 pub fn grid_path_cells_cached(from: CellIndex, to: CellIndex) -> Option<Vec<CellIndex>> {
     get_path_cache().get_or_compute(from, to)
@@ -382,4 +706,87 @@ mod tests {
             assert!(distance <= 1);
         }
     }
+
+    #[test]
+    fn spatial_index_moves_and_removes_keep_cells_consistent() {
+        let cell = CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell");
+        let other = cell.grid_disk::<Vec<_>>(1)[1];
+        let [a, b, c] = [1, 2, 3].map(Entity::from_raw);
+        let mut index = SpatialIndex::new();
+        for entity in [a, b, c] {
+            index.insert_driver(entity, cell);
+        }
+
+        // Removing the first entity swaps the last one into its slot
+        index.remove_driver(a);
+        index.update_driver_position(c, cell, other);
+        index.remove_driver(b);
+        assert_eq!(index.get_drivers_in_cells(&[cell]), Vec::<Entity>::new());
+        assert_eq!(index.get_drivers_in_cells(&[other]), vec![c]);
+        assert_eq!(index.get_driver_cell(c), Some(other));
+        assert_eq!(index.driver_count(), 1);
+
+        index.mark_moved(c);
+        index.mark_moved(a);
+        index.mark_moved(c);
+        assert_eq!(index.take_moved(), vec![a, c]);
+        assert!(index.take_moved().is_empty());
+    }
+
+    #[test]
+    fn batched_distances_match_per_pair_distances_exactly() {
+        let origin = CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell");
+        let targets = origin.grid_disk::<Vec<_>>(8);
+        let batched = distances_km_from_cell(origin, &targets);
+
+        assert_eq!(batched.len(), targets.len());
+        for (target, distance) in targets.iter().zip(&batched) {
+            assert_eq!(
+                distance.to_bits(),
+                distance_km_between_cells_uncached(origin, *target).to_bits()
+            );
+            // Symmetric, and misses computed from the batch origin match cached pairs
+            let from_target = HaversineOrigin::new(*target).distance_km(origin);
+            assert_eq!(distance.to_bits(), from_target.to_bits());
+        }
+        assert!(distances_km_from_cell(origin, &[]).is_empty());
+    }
+
+    #[test]
+    fn cells_in_bounds_matches_per_cell_check_and_is_cached() {
+        let (lat_min, lat_max, lng_min, lng_max) = (52.35, 52.65, 13.10, 13.70);
+        let cells = cells_in_bounds(lat_min, lat_max, lng_min, lng_max, Resolution::Nine);
+        assert!(cells.materialize_all());
+        let tiles = cells.materialized_tiles();
+        assert!(tiles > 0);
+
+        let center = LatLng::new(52.50, 13.40)
+            .expect("valid coordinates")
+            .to_cell(Resolution::Nine);
+        for cell in center.grid_disk::<Vec<_>>(160) {
+            assert_eq!(
+                cells.contains(cell),
+                cell_in_bounds(cell, lat_min, lat_max, lng_min, lng_max)
+            );
+        }
+
+        let again = cells_in_bounds(lat_min, lat_max, lng_min, lng_max, Resolution::Nine);
+        assert!(Arc::ptr_eq(&again, &cells));
+    }
+
+    #[test]
+    fn cells_in_bounds_of_a_large_map_materialize_where_used() {
+        // ~200 km x 200 km
+        let cells = BoundsCells::new(51.6, 53.4, 12.0, 15.0, Resolution::Nine);
+        assert!(!cells.materialize_all());
+        assert_eq!(cells.materialized_tiles(), 0);
+
+        let agent = LatLng::new(52.5, 13.4)
+            .expect("valid coordinates")
+            .to_cell(Resolution::Nine);
+        for cell in agent.grid_disk::<Vec<_>>(2) {
+            assert!(cells.contains(cell));
+        }
+        assert!(cells.materialized_tiles() <= 19);
+    }
 }
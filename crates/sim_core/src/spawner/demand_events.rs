@@ -0,0 +1,160 @@
+//! Special-event demand spikes (concerts, sports): bursts of ride requests from a venue.
+//!
+//! Each [`DemandEvent`] emits `request_count` riders at the venue cell, spread uniformly over
+//! `[start_ms, start_ms + duration_ms)`, with dropoffs distributed across the map. Arrival times are
+//! drawn when the simulation starts and scheduled as `SpawnEventRider` events; the riders then go
+//! through the normal quote → match → trip flow on top of regular demand.
+
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::Resource;
+use h3o::{CellIndex, LatLng, Resolution};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::ONE_MIN_MS;
+
+/// One venue burst: `request_count` requests from the venue over `duration_ms` starting at
+/// `start_ms` (simulation time).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemandEvent {
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    pub request_count: usize,
+}
+
+impl DemandEvent {
+    /// Event at the venue emitting `request_count` requests over `duration_mins` from `start_ms`.
+    pub fn new(
+        name: impl Into<String>,
+        lat: f64,
+        lng: f64,
+        start_ms: u64,
+        duration_mins: u64,
+        request_count: usize,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            lat,
+            lng,
+            start_ms,
+            duration_ms: duration_mins * ONE_MIN_MS,
+            request_count,
+        }
+    }
+
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms + self.duration_ms
+    }
+
+    /// H3 cell (resolution 9) of the venue, or None for invalid coordinates.
+    pub fn venue_cell(&self) -> Option<CellIndex> {
+        LatLng::new(self.lat, self.lng)
+            .ok()
+            .map(|ll| ll.to_cell(Resolution::Nine))
+    }
+}
+
+/// Configured demand events and the pending request queue. Empty by default.
+#[derive(Debug, Clone, Default, PartialEq, Resource, Serialize, Deserialize)]
+pub struct DemandEvents {
+    pub events: Vec<DemandEvent>,
+    /// Scheduled (time, event index) arrivals, sorted by time; filled by [`Self::schedule_arrivals`].
+    #[serde(skip)]
+    pending: VecDeque<(u64, usize)>,
+    /// Seed of the arrival times; set by `build_scenario` from the `demand_events` stream.
+    #[serde(skip)]
+    pub arrival_seed: u64,
+    /// Seed of the event riders' destinations; set by `build_scenario` from the
+    /// `event_riders` stream.
+    #[serde(skip)]
+    pub rider_seed: u64,
+    #[serde(skip)]
+    spawned: usize,
+}
+
+impl DemandEvents {
+    pub fn with_event(mut self, event: DemandEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn with_seeds(mut self, arrival_seed: u64, rider_seed: u64) -> Self {
+        self.arrival_seed = arrival_seed;
+        self.rider_seed = rider_seed;
+        self
+    }
+
+    /// Draw every event's arrival times (from `arrival_seed`) and queue them. Returns the sorted
+    /// arrival times so the caller can schedule one `SpawnEventRider` per arrival.
+    pub fn schedule_arrivals(&mut self) -> Vec<u64> {
+        let mut arrivals = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            let mut rng = StdRng::seed_from_u64(self.arrival_seed.wrapping_add(index as u64));
+            for _ in 0..event.request_count {
+                let offset = if event.duration_ms > 0 {
+                    rng.gen_range(0..event.duration_ms)
+                } else {
+                    0
+                };
+                arrivals.push((event.start_ms + offset, index));
+            }
+        }
+        arrivals.sort_unstable();
+        self.pending = arrivals.into_iter().collect();
+        self.pending.iter().map(|&(at, _)| at).collect()
+    }
+
+    /// Event riders spawned so far (used to vary the per-rider RNG seed).
+    pub fn spawned_count(&self) -> usize {
+        self.spawned
+    }
+
+    /// Pop the next arrival due at or before `now_ms` and return its event index.
+    pub fn pop_due(&mut self, now_ms: u64) -> Option<usize> {
+        match self.pending.front() {
+            Some(&(at, index)) if at <= now_ms => {
+                self.pending.pop_front();
+                self.spawned += 1;
+                Some(index)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrivals_fall_in_event_window_and_pop_in_order() {
+        let mut events = DemandEvents::default()
+            .with_event(DemandEvent::new("concert", 52.5, 13.4, 60_000, 10, 5))
+            .with_event(DemandEvent::new("match", 52.5, 13.2, 0, 1, 2))
+            .with_seeds(42, 7);
+        let arrivals = events.schedule_arrivals();
+        assert_eq!(arrivals.len(), 7);
+        assert!(arrivals.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut concert = 0;
+        for at in arrivals {
+            let index = events.pop_due(at).expect("due arrival");
+            let event = &events.events[index];
+            assert!(at >= event.start_ms && at < event.end_ms());
+            if index == 0 {
+                concert += 1;
+            }
+        }
+        assert_eq!(concert, 5);
+        assert_eq!(events.pop_due(u64::MAX), None);
+    }
+}
@@ -4,12 +4,22 @@
 //! variable supply and demand patterns. They react to SimulationStarted events
 //! and schedule their own spawn events.
 
+mod demand_events;
+mod od_matrix;
+mod supply_elasticity;
 mod weighting;
 
-pub use weighting::{SpawnWeighting, SpawnWeightingKind, WeightedCell};
+pub use demand_events::{DemandEvent, DemandEvents};
+pub use od_matrix::{OdMatrix, OdPeriod, OdZone};
+pub use supply_elasticity::SupplyElasticity;
+pub use weighting::{
+    hotspots_to_csv, parse_hotspots_csv, parse_hotspots_json, Hotspot, HotspotRole, SpawnWeighting,
+    SpawnWeightingKind, WeightedCell,
+};
 
 #[cfg(feature = "osrm")]
 use crate::routing::osrm_spawn::OsrmSpawnClient;
+use crate::spatial::cell_in_bounds;
 use bevy_ecs::prelude::Resource;
 use h3o::{CellIndex, LatLng, Resolution};
 
@@ -19,6 +29,7 @@ pub(crate) type MaybeOsrmSpawnClient<'a> = Option<&'a OsrmSpawnClient>;
 pub(crate) type MaybeOsrmSpawnClient<'a> = ();
 
 use crate::distributions::InterArrivalDistribution;
+use crate::emissions::VehicleMix;
 
 #[derive(Debug)]
 struct SpawnerState {
@@ -104,10 +115,12 @@ fn advance_common(
     state: &mut SpawnerState,
     config: &dyn SpawnerConfig,
     current_time_ms: u64,
+    rate_multiplier: f64,
 ) -> f64 {
     let inter_arrival_ms = config
         .inter_arrival_dist()
-        .sample_ms(state.spawned_count as u64, current_time_ms);
+        .sample_ms(state.spawned_count as u64, current_time_ms)
+        / rate_multiplier.max(f64::EPSILON);
     state.next_spawn_time_ms = current_time_ms + inter_arrival_ms.max(0.0) as u64;
     state.spawned_count += 1;
     inter_arrival_ms
@@ -122,6 +135,9 @@ pub struct RiderSpawnerConfig {
     pub lng_max: f64,
     pub min_trip_cells: u32,
     pub max_trip_cells: u32,
+    /// When set, pickup and dropoff are sampled jointly from the OD matrix instead of
+    /// spawn weighting + `[min_trip_cells, max_trip_cells]` destination.
+    pub od_matrix: Option<OdMatrix>,
     pub start_time_ms: Option<u64>,
     pub end_time_ms: Option<u64>,
     pub max_count: Option<usize>,
@@ -166,7 +182,7 @@ impl RiderSpawner {
     }
 
     pub fn advance(&mut self, current_time_ms: u64) -> f64 {
-        advance_common(&mut self.state, &self.config, current_time_ms)
+        advance_common(&mut self.state, &self.config, current_time_ms, 1.0)
     }
 
     pub fn next_spawn_time_ms(&self) -> u64 {
@@ -217,6 +233,7 @@ impl RiderSpawner {
 pub struct DriverSpawner {
     pub config: DriverSpawnerConfig,
     state: SpawnerState,
+    vehicle_mix: VehicleMix,
     #[cfg(feature = "osrm")]
     osrm_spawn_client: Option<OsrmSpawnClient>,
 }
@@ -226,17 +243,38 @@ impl DriverSpawner {
         Self {
             state: SpawnerState::new(config.start_time_ms),
             config,
+            vehicle_mix: VehicleMix::default(),
             #[cfg(feature = "osrm")]
             osrm_spawn_client: None,
         }
     }
 
+    /// Assign each spawned driver a `DriverVehicle` drawn from `vehicle_mix`.
+    pub fn with_vehicle_mix(mut self, vehicle_mix: VehicleMix) -> Self {
+        self.vehicle_mix = vehicle_mix;
+        self
+    }
+
+    pub fn vehicle_mix(&self) -> &VehicleMix {
+        &self.vehicle_mix
+    }
+
     pub fn should_spawn(&self, current_time_ms: u64) -> bool {
         should_spawn_common(&self.state, &self.config, current_time_ms)
     }
 
     pub fn advance(&mut self, current_time_ms: u64) -> f64 {
-        advance_common(&mut self.state, &self.config, current_time_ms)
+        self.advance_scaled(current_time_ms, 1.0)
+    }
+
+    /// Advance with the arrival rate scaled by `rate_multiplier` (> 1 shortens the gap).
+    pub fn advance_scaled(&mut self, current_time_ms: u64, rate_multiplier: f64) -> f64 {
+        advance_common(
+            &mut self.state,
+            &self.config,
+            current_time_ms,
+            rate_multiplier,
+        )
     }
 
     pub fn next_spawn_time_ms(&self) -> u64 {
@@ -283,6 +321,12 @@ impl DriverSpawner {
     }
 }
 
+/// Draws `random_cell_in_bounds` makes before settling for the cell at the center of the bounds.
+const MAX_CELL_DRAWS: usize = 32;
+
+/// Resolution-9 cell of a uniform point in the bounds. A cell whose center falls outside the
+/// bounds (a point near an edge) is redrawn, so spawned entities stay inside them; bounds too
+/// small to hold a cell center yield the cell at their center.
 pub fn random_cell_in_bounds<R: rand::Rng>(
     rng: &mut R,
     lat_min: f64,
@@ -303,9 +347,18 @@ pub fn random_cell_in_bounds<R: rand::Rng>(
         ));
     }
 
-    let lat = rng.gen_range(lat_min..=lat_max);
-    let lng = rng.gen_range(lng_min..=lng_max);
+    for _ in 0..MAX_CELL_DRAWS {
+        let lat = rng.gen_range(lat_min..=lat_max);
+        let lng = rng.gen_range(lng_min..=lng_max);
+        let cell = cell_at(lat, lng)?;
+        if cell_in_bounds(cell, lat_min, lat_max, lng_min, lng_max) {
+            return Ok(cell);
+        }
+    }
+    cell_at((lat_min + lat_max) / 2.0, (lng_min + lng_max) / 2.0)
+}
 
+fn cell_at(lat: f64, lng: f64) -> Result<CellIndex, String> {
     let coord = LatLng::new(lat, lng)
         .map_err(|e| format!("Invalid coordinates ({}, {}): {}", lat, lng, e))?;
     Ok(coord.to_cell(Resolution::Nine))
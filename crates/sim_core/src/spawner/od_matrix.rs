@@ -0,0 +1,312 @@
+//! Origin-destination (OD) matrix demand: sample a rider's pickup and dropoff jointly.
+//!
+//! Instead of a pickup from [`super::SpawnWeighting`] and an independent destination within
+//! `[min_trip_cells, max_trip_cells]`, an [`OdMatrix`] picks an (origin zone, destination zone)
+//! pair weighted by flow, then a cell inside each zone. Time-of-day [`OdPeriod`]s override the base
+//! flows for their hours, capturing commuter flows such as suburbs → center in the morning and the
+//! reverse in the evening.
+
+use h3o::{CellIndex, LatLng, Resolution};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::spatial::cell_in_bounds;
+
+/// A demand zone: all cells within `radius_cells` of the center cell.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OdZone {
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    /// H3 grid radius (resolution 9) around the center cell.
+    pub radius_cells: u32,
+}
+
+impl OdZone {
+    pub fn new(name: impl Into<String>, lat: f64, lng: f64, radius_cells: u32) -> Self {
+        Self {
+            name: name.into(),
+            lat,
+            lng,
+            radius_cells,
+        }
+    }
+
+    fn center_cell(&self) -> Option<CellIndex> {
+        LatLng::new(self.lat, self.lng)
+            .ok()
+            .map(|ll| ll.to_cell(Resolution::Nine))
+    }
+
+    /// Sample a cell in the zone that lies within the bounds. None if no zone cell is in bounds.
+    fn sample_cell<R: Rng>(
+        &self,
+        rng: &mut R,
+        lat_min: f64,
+        lat_max: f64,
+        lng_min: f64,
+        lng_max: f64,
+    ) -> Option<CellIndex> {
+        let center = self.center_cell()?;
+        let cells: Vec<CellIndex> = center
+            .grid_disk::<Vec<_>>(self.radius_cells)
+            .into_iter()
+            .filter(|cell| cell_in_bounds(*cell, lat_min, lat_max, lng_min, lng_max))
+            .collect();
+        if cells.is_empty() {
+            return None;
+        }
+        Some(cells[rng.gen_range(0..cells.len())])
+    }
+}
+
+/// Flows that apply during `[start_hour, end_hour)` (hours of day, 0–23; wraps past midnight
+/// when `start_hour > end_hour`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OdPeriod {
+    pub start_hour: u8,
+    pub end_hour: u8,
+    /// `flows[origin][destination]` relative trip weights.
+    pub flows: Vec<Vec<f64>>,
+}
+
+impl OdPeriod {
+    fn contains(&self, hour: usize) -> bool {
+        let (start, end) = (self.start_hour as usize, self.end_hour as usize);
+        if start <= end {
+            hour >= start && hour < end
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+/// Zone-to-zone demand. Empty by default (riders use independent pickup/destination sampling).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OdMatrix {
+    pub zones: Vec<OdZone>,
+    /// Base `flows[origin][destination]` weights, used outside every period.
+    pub flows: Vec<Vec<f64>>,
+    /// Time-of-day overrides; the first period containing the hour wins.
+    #[serde(default)]
+    pub periods: Vec<OdPeriod>,
+}
+
+impl OdMatrix {
+    /// Matrix over `zones` with all base flows zero.
+    pub fn new(zones: Vec<OdZone>) -> Self {
+        let n = zones.len();
+        Self {
+            zones,
+            flows: vec![vec![0.0; n]; n],
+            periods: Vec::new(),
+        }
+    }
+
+    /// Set the base flow between two zones (by index). Errors if either index is not a zone.
+    pub fn with_flow(
+        mut self,
+        origin: usize,
+        destination: usize,
+        weight: f64,
+    ) -> Result<Self, String> {
+        let zones = self.zones.len();
+        if origin >= zones || destination >= zones {
+            return Err(format!(
+                "OD flow {origin} -> {destination} is out of range for {zones} zones"
+            ));
+        }
+        self.flows[origin][destination] = weight;
+        Ok(self)
+    }
+
+    /// Add a time-of-day period with its own flows.
+    pub fn with_period(mut self, start_hour: u8, end_hour: u8, flows: Vec<Vec<f64>>) -> Self {
+        self.periods.push(OdPeriod {
+            start_hour,
+            end_hour,
+            flows,
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.zones.is_empty()
+    }
+
+    /// Berlin commuter pattern: outer districts → Mitte in the morning peak (6–10),
+    /// Mitte → outer districts in the evening peak (16–20), mostly intra-zone trips otherwise.
+    pub fn berlin_commuter() -> Self {
+        let zones = vec![
+            OdZone::new("mitte", 52.520, 13.405, 6),
+            OdZone::new("spandau", 52.535, 13.200, 5),
+            OdZone::new("marzahn", 52.545, 13.565, 5),
+            OdZone::new("steglitz", 52.457, 13.320, 5),
+        ];
+        let off_peak = vec![
+            vec![4.0, 0.5, 0.5, 0.5],
+            vec![0.5, 1.0, 0.1, 0.2],
+            vec![0.5, 0.1, 1.0, 0.1],
+            vec![0.5, 0.2, 0.1, 1.0],
+        ];
+        let morning = vec![
+            vec![2.0, 0.3, 0.3, 0.3],
+            vec![3.0, 0.5, 0.1, 0.2],
+            vec![3.0, 0.1, 0.5, 0.1],
+            vec![3.0, 0.2, 0.1, 0.5],
+        ];
+        let evening = vec![
+            vec![2.0, 3.0, 3.0, 3.0],
+            vec![0.3, 0.5, 0.1, 0.2],
+            vec![0.3, 0.1, 0.5, 0.1],
+            vec![0.3, 0.2, 0.1, 0.5],
+        ];
+        Self {
+            zones,
+            flows: off_peak,
+            periods: Vec::new(),
+        }
+        .with_period(6, 10, morning)
+        .with_period(16, 20, evening)
+    }
+
+    /// Flows in effect at `hour` (0–23).
+    pub fn flows_at(&self, hour: usize) -> &[Vec<f64>] {
+        self.periods
+            .iter()
+            .find(|period| period.contains(hour % 24))
+            .map(|period| period.flows.as_slice())
+            .unwrap_or(&self.flows)
+    }
+
+    /// Sample an (origin zone, destination zone) index pair for `hour`, weighted by flow.
+    pub fn sample_zone_pair<R: Rng>(&self, hour: usize, rng: &mut R) -> Option<(usize, usize)> {
+        let n = self.zones.len();
+        let flows = self.flows_at(hour);
+        let weight = |o: usize, d: usize| {
+            flows
+                .get(o)
+                .and_then(|row| row.get(d))
+                .copied()
+                .filter(|w| w.is_finite() && *w > 0.0)
+                .unwrap_or(0.0)
+        };
+        let total: f64 = (0..n)
+            .flat_map(|o| (0..n).map(move |d| (o, d)))
+            .map(|(o, d)| weight(o, d))
+            .sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut r = rng.gen_range(0.0..total);
+        let mut last = None;
+        for o in 0..n {
+            for d in 0..n {
+                let w = weight(o, d);
+                if w <= 0.0 {
+                    continue;
+                }
+                if r < w {
+                    return Some((o, d));
+                }
+                r -= w;
+                last = Some((o, d));
+            }
+        }
+        last
+    }
+
+    /// Sample a (pickup, dropoff) cell pair for `hour` within the bounds. Dropoff differs from
+    /// pickup. None if the matrix is empty or the sampled zones have no cells in bounds.
+    pub fn sample_trip<R: Rng>(
+        &self,
+        hour: usize,
+        rng: &mut R,
+        lat_min: f64,
+        lat_max: f64,
+        lng_min: f64,
+        lng_max: f64,
+    ) -> Option<(CellIndex, CellIndex)> {
+        let (origin, destination) = self.sample_zone_pair(hour, rng)?;
+        let pickup = self.zones[origin].sample_cell(rng, lat_min, lat_max, lng_min, lng_max)?;
+        const MAX_ATTEMPTS: usize = 8;
+        for _ in 0..MAX_ATTEMPTS {
+            let dropoff =
+                self.zones[destination].sample_cell(rng, lat_min, lat_max, lng_min, lng_max)?;
+            if dropoff != pickup {
+                return Some((pickup, dropoff));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const BOUNDS: (f64, f64, f64, f64) = (52.34, 52.68, 13.08, 13.76);
+
+    #[test]
+    fn morning_flows_go_to_center_and_evening_flows_leave_it() {
+        let matrix = OdMatrix::berlin_commuter();
+        let mut rng = StdRng::seed_from_u64(3);
+        let samples = 2_000;
+        let to_center = (0..samples)
+            .filter_map(|_| matrix.sample_zone_pair(8, &mut rng))
+            .filter(|&(o, d)| o != 0 && d == 0)
+            .count();
+        let from_center = (0..samples)
+            .filter_map(|_| matrix.sample_zone_pair(18, &mut rng))
+            .filter(|&(o, d)| o == 0 && d != 0)
+            .count();
+        let to_center_evening = (0..samples)
+            .filter_map(|_| matrix.sample_zone_pair(18, &mut rng))
+            .filter(|&(o, d)| o != 0 && d == 0)
+            .count();
+        assert!(to_center > samples / 2);
+        assert!(from_center > samples / 2);
+        assert!(to_center_evening < to_center / 4);
+    }
+
+    #[test]
+    fn sampled_trip_stays_in_zones() {
+        let matrix = OdMatrix::new(vec![
+            OdZone::new("a", 52.52, 13.40, 2),
+            OdZone::new("b", 52.45, 13.30, 2),
+        ])
+        .with_flow(0, 1, 1.0)
+        .expect("zones exist");
+        let (lat_min, lat_max, lng_min, lng_max) = BOUNDS;
+        let mut rng = StdRng::seed_from_u64(11);
+        let (pickup, dropoff) = matrix
+            .sample_trip(3, &mut rng, lat_min, lat_max, lng_min, lng_max)
+            .expect("trip");
+        let a = matrix.zones[0].center_cell().unwrap();
+        let b = matrix.zones[1].center_cell().unwrap();
+        assert!(a.grid_distance(pickup).unwrap() <= 2);
+        assert!(b.grid_distance(dropoff).unwrap() <= 2);
+        assert_eq!(OdMatrix::default().sample_zone_pair(0, &mut rng), None);
+    }
+
+    #[test]
+    fn flow_between_unknown_zones_is_rejected() {
+        let matrix = OdMatrix::new(vec![OdZone::new("a", 52.52, 13.40, 2)]);
+        assert!(matrix.clone().with_flow(0, 0, 1.0).is_ok());
+        assert!(matrix.clone().with_flow(0, 1, 1.0).is_err());
+        assert!(matrix.with_flow(2, 0, 1.0).is_err());
+    }
+
+    #[test]
+    fn period_wraps_past_midnight() {
+        let period = OdPeriod {
+            start_hour: 22,
+            end_hour: 2,
+            flows: Vec::new(),
+        };
+        assert!(period.contains(23) && period.contains(1));
+        assert!(!period.contains(12));
+    }
+}
@@ -0,0 +1,181 @@
+//! Driver supply elasticity: more drivers log on when earnings look good.
+//!
+//! Quoted surge multipliers are recorded as they happen; when the driver spawner schedules its
+//! next arrival, the inter-arrival time is divided by a rate multiplier built from the average
+//! surge observed `lag_ms` ago (over `window_ms`) and the active hourly incentive (configured
+//! bonus plus the earnings floor). Drivers therefore react to market conditions with a delay
+//! instead of following a fixed schedule. Disabled by default (both elasticities zero).
+
+use std::collections::VecDeque;
+
+use bevy_ecs::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::ONE_MIN_MS;
+
+/// Supply response to surge and incentives, plus the recent surge observations it reacts to.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+pub struct SupplyElasticity {
+    /// Rate change per unit of average surge above 1.0 (e.g. 0.8: surge 1.5x → +40% arrivals).
+    pub surge_elasticity: f64,
+    /// Rate change per unit of `incentive / reference_hourly_earnings`.
+    pub incentive_elasticity: f64,
+    /// Flat hourly bonus offered to drivers, added to `PricingConfig::min_hourly_earnings`.
+    pub incentive_per_hour: f64,
+    /// Typical hourly earnings that incentives are compared against.
+    pub reference_hourly_earnings: f64,
+    /// Delay before drivers respond to observed surge (simulation ms).
+    pub lag_ms: u64,
+    /// Length of the surge averaging window ending `lag_ms` before now.
+    pub window_ms: u64,
+    pub min_multiplier: f64,
+    pub max_multiplier: f64,
+    /// Recorded (time, surge multiplier) quote observations, oldest first.
+    #[serde(skip)]
+    observations: VecDeque<(u64, f64)>,
+    #[serde(skip)]
+    adjusted_spawns: usize,
+    #[serde(skip)]
+    multiplier_sum: f64,
+}
+
+impl Default for SupplyElasticity {
+    fn default() -> Self {
+        Self {
+            surge_elasticity: 0.0,
+            incentive_elasticity: 0.0,
+            incentive_per_hour: 0.0,
+            reference_hourly_earnings: 25.0,
+            lag_ms: 15 * ONE_MIN_MS,
+            window_ms: 15 * ONE_MIN_MS,
+            min_multiplier: 0.5,
+            max_multiplier: 3.0,
+            observations: VecDeque::new(),
+            adjusted_spawns: 0,
+            multiplier_sum: 0.0,
+        }
+    }
+}
+
+impl SupplyElasticity {
+    /// Supply responding to surge with the given elasticity and lag (in minutes).
+    pub fn surge(elasticity: f64, lag_mins: u64) -> Self {
+        Self {
+            surge_elasticity: elasticity,
+            lag_ms: lag_mins * ONE_MIN_MS,
+            ..Default::default()
+        }
+    }
+
+    /// Add an hourly incentive and the elasticity drivers respond to it with.
+    pub fn with_incentive(mut self, incentive_per_hour: f64, elasticity: f64) -> Self {
+        self.incentive_per_hour = incentive_per_hour;
+        self.incentive_elasticity = elasticity;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.surge_elasticity != 0.0 || self.incentive_elasticity != 0.0
+    }
+
+    /// Record a quoted surge multiplier at `now_ms`. No-op when disabled.
+    pub fn observe_surge(&mut self, now_ms: u64, multiplier: f64) {
+        if !self.is_enabled() || !multiplier.is_finite() {
+            return;
+        }
+        self.observations.push_back((now_ms, multiplier));
+        let horizon = self.lag_ms + self.window_ms;
+        while let Some(&(at, _)) = self.observations.front() {
+            if at + horizon < now_ms {
+                self.observations.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Average surge quoted in `[now - lag - window, now - lag]`; 1.0 without observations
+    /// (including before the first `lag_ms` has elapsed).
+    pub fn lagged_average_surge(&self, now_ms: u64) -> f64 {
+        let Some(end) = now_ms.checked_sub(self.lag_ms) else {
+            return 1.0;
+        };
+        let start = end.saturating_sub(self.window_ms);
+        let (sum, count) = self
+            .observations
+            .iter()
+            .filter(|&&(at, _)| at >= start && at <= end)
+            .fold((0.0, 0usize), |(sum, count), &(_, m)| (sum + m, count + 1));
+        if count == 0 {
+            1.0
+        } else {
+            sum / count as f64
+        }
+    }
+
+    /// Driver arrival rate multiplier at `now_ms`, given the earnings floor in effect.
+    pub fn rate_multiplier(&self, now_ms: u64, min_hourly_earnings: f64) -> f64 {
+        if !self.is_enabled() {
+            return 1.0;
+        }
+        let surge_term = self.surge_elasticity * (self.lagged_average_surge(now_ms) - 1.0);
+        let incentive = self.incentive_per_hour + min_hourly_earnings.max(0.0);
+        let incentive_term = if self.reference_hourly_earnings > 0.0 {
+            self.incentive_elasticity * incentive / self.reference_hourly_earnings
+        } else {
+            0.0
+        };
+        (1.0 + surge_term + incentive_term).clamp(self.min_multiplier, self.max_multiplier)
+    }
+
+    /// Record a multiplier applied to a driver arrival (for reporting).
+    pub fn record_applied(&mut self, multiplier: f64) {
+        self.adjusted_spawns += 1;
+        self.multiplier_sum += multiplier;
+    }
+
+    /// Mean rate multiplier over all adjusted driver arrivals; 1.0 if none.
+    pub fn average_applied_multiplier(&self) -> f64 {
+        if self.adjusted_spawns == 0 {
+            1.0
+        } else {
+            self.multiplier_sum / self.adjusted_spawns as f64
+        }
+    }
+
+    pub fn adjusted_spawns(&self) -> usize {
+        self.adjusted_spawns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surge_responds_only_after_lag() {
+        let mut supply = SupplyElasticity::surge(1.0, 10);
+        supply.observe_surge(0, 2.0);
+        supply.observe_surge(ONE_MIN_MS, 2.0);
+        assert_eq!(supply.rate_multiplier(5 * ONE_MIN_MS, 0.0), 1.0);
+        let lagged = supply.rate_multiplier(11 * ONE_MIN_MS, 0.0);
+        assert!((lagged - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn incentives_raise_rate_and_multiplier_is_clamped() {
+        let supply = SupplyElasticity::default().with_incentive(5.0, 1.0);
+        let with_floor = supply.rate_multiplier(0, 20.0);
+        assert!((with_floor - 2.0).abs() < 1e-9);
+        let capped = SupplyElasticity::default().with_incentive(500.0, 1.0);
+        assert_eq!(capped.rate_multiplier(0, 0.0), capped.max_multiplier);
+        assert_eq!(SupplyElasticity::default().rate_multiplier(0, 50.0), 1.0);
+    }
+
+    #[test]
+    fn disabled_supply_records_nothing() {
+        let mut supply = SupplyElasticity::default();
+        supply.observe_surge(0, 3.0);
+        assert!(supply.observations.is_empty());
+    }
+}
@@ -1,3 +1,13 @@
+//! Spawn location weighting: sample rider/driver spawn cells from weighted hotspot sets.
+//!
+//! Hotspots are either the built-in Berlin set or loaded from a JSON/CSV file
+//! ([`SpawnWeightingKind::HotspotFile`]), so any city can be modeled. A hotspot may carry a
+//! 24-entry time-of-day profile that scales its weight by hour (index 0 = midnight).
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
 use bevy_ecs::prelude::Resource;
 use h3o::{CellIndex, LatLng, Resolution};
 use serde::{Deserialize, Serialize};
@@ -6,6 +16,8 @@ use serde::{Deserialize, Serialize};
 pub struct WeightedCell {
     pub cell: CellIndex,
     pub weight: f64,
+    /// Per-hour weight multipliers (index 0 = midnight). None = constant weight.
+    pub hourly_profile: Option<[f64; 24]>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
@@ -13,6 +25,148 @@ pub enum SpawnWeightingKind {
     #[default]
     Uniform,
     BerlinHotspots,
+    /// Weighted hotspots loaded from a JSON or CSV file (see [`Hotspot`]).
+    /// Falls back to Uniform if the file cannot be loaded.
+    HotspotFile(String),
+    /// Weighted hotspots given inline, e.g. painted on the UI map.
+    Hotspots(Vec<Hotspot>),
+}
+
+/// Which spawner a hotspot applies to.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HotspotRole {
+    Rider,
+    Driver,
+    #[default]
+    Both,
+}
+
+/// One weighted spawn hotspot, as read from a hotspot file.
+///
+/// JSON files hold an array of hotspots:
+/// `[{"lat": 52.52, "lng": 13.405, "weight": 3.0, "role": "rider", "hourly_profile": [..24 values..]}]`
+/// (`role` defaults to `both`, `hourly_profile` is optional).
+///
+/// CSV files have the columns `lat,lng,weight[,role[,hourly_profile]]`, with the profile as 24
+/// `;`-separated values. A header row and blank or `#` lines are skipped.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Hotspot {
+    pub lat: f64,
+    pub lng: f64,
+    pub weight: f64,
+    #[serde(default)]
+    pub role: HotspotRole,
+    #[serde(default)]
+    pub hourly_profile: Option<Vec<f64>>,
+}
+
+impl Hotspot {
+    fn applies_to_riders(&self) -> bool {
+        matches!(self.role, HotspotRole::Rider | HotspotRole::Both)
+    }
+
+    fn applies_to_drivers(&self) -> bool {
+        matches!(self.role, HotspotRole::Driver | HotspotRole::Both)
+    }
+}
+
+/// Parse hotspots from JSON (an array of [`Hotspot`]).
+pub fn parse_hotspots_json(data: &str) -> Result<Vec<Hotspot>, Box<dyn Error>> {
+    let hotspots: Vec<Hotspot> = serde_json::from_str(data)?;
+    for hotspot in &hotspots {
+        validate_hotspot(hotspot)?;
+    }
+    Ok(hotspots)
+}
+
+/// Parse hotspots from CSV (`lat,lng,weight[,role[,hourly_profile]]`).
+pub fn parse_hotspots_csv(data: &str) -> Result<Vec<Hotspot>, Box<dyn Error>> {
+    let mut hotspots = Vec::new();
+    for (line_no, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if line_no == 0 && fields[0].parse::<f64>().is_err() {
+            continue; // header
+        }
+        if fields.len() < 3 {
+            return Err(format!("line {}: expected lat,lng,weight", line_no + 1).into());
+        }
+        let role = match fields.get(3).copied().unwrap_or("") {
+            "" | "both" => HotspotRole::Both,
+            "rider" => HotspotRole::Rider,
+            "driver" => HotspotRole::Driver,
+            other => return Err(format!("line {}: unknown role '{}'", line_no + 1, other).into()),
+        };
+        let hourly_profile = match fields.get(4).copied().unwrap_or("") {
+            "" => None,
+            profile => Some(
+                profile
+                    .split(';')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+        };
+        let hotspot = Hotspot {
+            lat: fields[0].parse()?,
+            lng: fields[1].parse()?,
+            weight: fields[2].parse()?,
+            role,
+            hourly_profile,
+        };
+        validate_hotspot(&hotspot).map_err(|e| format!("line {}: {}", line_no + 1, e))?;
+        hotspots.push(hotspot);
+    }
+    Ok(hotspots)
+}
+
+/// Write hotspots as CSV in the format read by [`parse_hotspots_csv`], with a header row.
+pub fn hotspots_to_csv(hotspots: &[Hotspot]) -> String {
+    let mut csv = String::from("lat,lng,weight,role,hourly_profile\n");
+    for hotspot in hotspots {
+        let role = match hotspot.role {
+            HotspotRole::Rider => "rider",
+            HotspotRole::Driver => "driver",
+            HotspotRole::Both => "both",
+        };
+        let profile = hotspot
+            .hourly_profile
+            .as_deref()
+            .map(|profile| {
+                profile
+                    .iter()
+                    .map(f64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(";")
+            })
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            hotspot.lat, hotspot.lng, hotspot.weight, role, profile
+        ));
+    }
+    csv
+}
+
+fn validate_hotspot(hotspot: &Hotspot) -> Result<(), String> {
+    if !hotspot.weight.is_finite() || hotspot.weight < 0.0 {
+        return Err(format!("invalid weight {}", hotspot.weight));
+    }
+    if let Some(profile) = &hotspot.hourly_profile {
+        if profile.len() != 24 {
+            return Err(format!(
+                "hourly_profile needs 24 values, got {}",
+                profile.len()
+            ));
+        }
+        if profile.iter().any(|f| !f.is_finite() || *f < 0.0) {
+            return Err("hourly_profile values must be non-negative".to_string());
+        }
+    }
+    Ok(())
 }
 
 #[derive(Debug, Resource)]
@@ -65,23 +219,53 @@ impl SpawnWeighting {
             (52.534, 13.198, 0.8),
         ];
 
-        fn cells_from_coords(data: &[(f64, f64, f64)]) -> (Vec<WeightedCell>, Vec<f64>) {
+        let hotspot = |role: HotspotRole| {
+            move |&(lat, lng, weight): &(f64, f64, f64)| Hotspot {
+                lat,
+                lng,
+                weight,
+                role,
+                hourly_profile: None,
+            }
+        };
+        let hotspots: Vec<Hotspot> = rider_hotspots
+            .iter()
+            .map(hotspot(HotspotRole::Rider))
+            .chain(driver_hotspots.iter().map(hotspot(HotspotRole::Driver)))
+            .collect();
+        Self::from_hotspots(&hotspots)
+    }
+
+    /// Build from a hotspot list; each hotspot feeds the rider and/or driver set per its role.
+    pub fn from_hotspots(hotspots: &[Hotspot]) -> Self {
+        fn cells_for<'a>(
+            hotspots: impl Iterator<Item = &'a Hotspot>,
+        ) -> (Vec<WeightedCell>, Vec<f64>) {
             let mut cells = Vec::new();
             let mut cumulative = Vec::new();
             let mut total = 0.0;
-            for &(lat, lng, weight) in data {
-                if let Ok(ll) = LatLng::new(lat, lng) {
-                    let cell = ll.to_cell(Resolution::Nine);
-                    cells.push(WeightedCell { cell, weight });
-                    total += weight;
+            for hotspot in hotspots {
+                if let Ok(ll) = LatLng::new(hotspot.lat, hotspot.lng) {
+                    let hourly_profile = hotspot
+                        .hourly_profile
+                        .as_deref()
+                        .and_then(|p| <[f64; 24]>::try_from(p).ok());
+                    cells.push(WeightedCell {
+                        cell: ll.to_cell(Resolution::Nine),
+                        weight: hotspot.weight,
+                        hourly_profile,
+                    });
+                    total += hotspot.weight;
                     cumulative.push(total);
                 }
             }
             (cells, cumulative)
         }
 
-        let (rider_cells, rider_cumulative) = cells_from_coords(&rider_hotspots);
-        let (driver_cells, driver_cumulative) = cells_from_coords(&driver_hotspots);
+        let (rider_cells, rider_cumulative) =
+            cells_for(hotspots.iter().filter(|h| h.applies_to_riders()));
+        let (driver_cells, driver_cumulative) =
+            cells_for(hotspots.iter().filter(|h| h.applies_to_drivers()));
 
         Self {
             rider_cells,
@@ -91,10 +275,41 @@ impl SpawnWeighting {
         }
     }
 
+    /// Load hotspots from a `.csv` file, or JSON for any other extension.
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let data = fs::read_to_string(path)?;
+        let is_csv = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let hotspots = if is_csv {
+            parse_hotspots_csv(&data)?
+        } else {
+            parse_hotspots_json(&data)?
+        };
+        if hotspots.is_empty() {
+            return Err("no hotspots in file".into());
+        }
+        Ok(Self::from_hotspots(&hotspots))
+    }
+
+    /// Build the weighting for `kind`. A hotspot file that fails to load prints a warning and
+    /// falls back to uniform weighting; use [`Self::try_from_kind`] to handle the error instead.
     pub fn from_kind(kind: &SpawnWeightingKind) -> Self {
+        Self::try_from_kind(kind).unwrap_or_else(|e| {
+            eprintln!("WARNING: {e}. Falling back to Uniform.");
+            Self::uniform()
+        })
+    }
+
+    /// Like [`Self::from_kind`], but returns the reason instead of falling back when the
+    /// hotspot file cannot be loaded.
+    pub fn try_from_kind(kind: &SpawnWeightingKind) -> Result<Self, String> {
         match kind {
-            SpawnWeightingKind::Uniform => Self::uniform(),
-            SpawnWeightingKind::BerlinHotspots => Self::berlin_hotspots(),
+            SpawnWeightingKind::Uniform => Ok(Self::uniform()),
+            SpawnWeightingKind::BerlinHotspots => Ok(Self::berlin_hotspots()),
+            SpawnWeightingKind::HotspotFile(path) => Self::from_file(path)
+                .map_err(|e| format!("Failed to load spawn hotspots from '{path}': {e}")),
+            SpawnWeightingKind::Hotspots(hotspots) => Ok(Self::from_hotspots(hotspots)),
         }
     }
 
@@ -106,6 +321,44 @@ impl SpawnWeighting {
         self.sample_from(&self.driver_cells, &self.driver_cumulative, rng)
     }
 
+    /// Sample a rider cell with weights scaled by each hotspot's profile for `hour` (0–23).
+    pub fn sample_rider_cell_at<R: rand::Rng>(
+        &self,
+        hour: usize,
+        rng: &mut R,
+    ) -> Option<CellIndex> {
+        self.sample_at_hour(&self.rider_cells, &self.rider_cumulative, hour, rng)
+    }
+
+    /// Sample a driver cell with weights scaled by each hotspot's profile for `hour` (0–23).
+    pub fn sample_driver_cell_at<R: rand::Rng>(
+        &self,
+        hour: usize,
+        rng: &mut R,
+    ) -> Option<CellIndex> {
+        self.sample_at_hour(&self.driver_cells, &self.driver_cumulative, hour, rng)
+    }
+
+    fn sample_at_hour<R: rand::Rng>(
+        &self,
+        cells: &[WeightedCell],
+        cumulative: &[f64],
+        hour: usize,
+        rng: &mut R,
+    ) -> Option<CellIndex> {
+        if cells.iter().all(|c| c.hourly_profile.is_none()) {
+            return self.sample_from(cells, cumulative, rng);
+        }
+        let mut hourly_cumulative = Vec::with_capacity(cells.len());
+        let mut total = 0.0;
+        for cell in cells {
+            let factor = cell.hourly_profile.map_or(1.0, |p| p[hour % 24]);
+            total += cell.weight * factor;
+            hourly_cumulative.push(total);
+        }
+        self.sample_from(cells, &hourly_cumulative, rng)
+    }
+
     fn sample_from<R: rand::Rng>(
         &self,
         cells: &[WeightedCell],
@@ -124,3 +377,96 @@ impl SpawnWeighting {
         Some(cells[idx].cell)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_csv_with_header_roles_and_profiles() {
+        let night: Vec<String> = (0..24)
+            .map(|h| if h < 6 { "1" } else { "0" }.to_string())
+            .collect();
+        let data = format!(
+            "lat,lng,weight,role,hourly_profile\n# comment\n40.75,-73.99,2.0,rider,{}\n40.70,-74.01,1.0\n",
+            night.join(";")
+        );
+        let hotspots = parse_hotspots_csv(&data).expect("csv parses");
+        assert_eq!(hotspots.len(), 2);
+        assert_eq!(hotspots[0].role, HotspotRole::Rider);
+        assert_eq!(hotspots[0].hourly_profile.as_ref().map(Vec::len), Some(24));
+        assert_eq!(hotspots[1].role, HotspotRole::Both);
+
+        assert!(parse_hotspots_csv("40.75,-73.99,1.0,rider,1;2;3").is_err());
+        assert!(parse_hotspots_csv("40.75,-73.99,1.0,taxi").is_err());
+    }
+
+    #[test]
+    fn csv_export_round_trips() {
+        let hotspots = vec![
+            Hotspot {
+                lat: 52.52,
+                lng: 13.405,
+                weight: 2.5,
+                role: HotspotRole::Rider,
+                hourly_profile: Some((0..24).map(f64::from).collect()),
+            },
+            Hotspot {
+                lat: 52.5,
+                lng: 13.4,
+                weight: 1.0,
+                role: HotspotRole::Both,
+                hourly_profile: None,
+            },
+        ];
+        let parsed = parse_hotspots_csv(&hotspots_to_csv(&hotspots)).expect("csv parses");
+        assert_eq!(parsed, hotspots);
+    }
+
+    #[test]
+    fn parses_json_and_splits_by_role() {
+        let data = r#"[
+            {"lat": 48.858, "lng": 2.294, "weight": 3.0, "role": "rider"},
+            {"lat": 48.853, "lng": 2.349, "weight": 1.0, "role": "driver"},
+            {"lat": 48.873, "lng": 2.295, "weight": 2.0}
+        ]"#;
+        let weighting = SpawnWeighting::from_hotspots(&parse_hotspots_json(data).unwrap());
+        assert_eq!(weighting.rider_cells.len(), 2);
+        assert_eq!(weighting.driver_cells.len(), 2);
+        assert!(parse_hotspots_json(r#"[{"lat": 1.0, "lng": 1.0, "weight": -1.0}]"#).is_err());
+    }
+
+    #[test]
+    fn hourly_profile_shifts_samples_by_time_of_day() {
+        let mut day_only = vec![0.0; 24];
+        day_only[12] = 1.0;
+        let mut night_only = vec![0.0; 24];
+        night_only[2] = 1.0;
+        let day = Hotspot {
+            lat: 52.52,
+            lng: 13.405,
+            weight: 1.0,
+            role: HotspotRole::Both,
+            hourly_profile: Some(day_only),
+        };
+        let night = Hotspot {
+            lat: 52.48,
+            lng: 13.35,
+            hourly_profile: Some(night_only),
+            ..day.clone()
+        };
+        let weighting = SpawnWeighting::from_hotspots(&[day, night]);
+        let day_cell = weighting.rider_cells[0].cell;
+        let night_cell = weighting.rider_cells[1].cell;
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            assert_eq!(weighting.sample_rider_cell_at(12, &mut rng), Some(day_cell));
+            assert_eq!(
+                weighting.sample_driver_cell_at(2, &mut rng),
+                Some(night_cell)
+            );
+        }
+    }
+}
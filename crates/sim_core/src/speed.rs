@@ -3,10 +3,45 @@
 //! Provides configurable speed ranges (default 20-60 km/h for city driving) with
 //! seeded RNG for reproducible results. Used by the movement system to calculate
 //! travel times between H3 cells.
+//!
+//! When a route carries road-class annotations, each sampled speed is rescaled to the
+//! segment's class via [`RoadClassSpeeds`], so highways are driven faster than residential
+//! streets while keeping the sampled spread and traffic factors.
 
 use bevy_ecs::prelude::Resource;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::routing::RoadClass;
+
+/// Typical free-flow speed per road class.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RoadClassSpeeds {
+    pub residential_kmh: f64,
+    pub arterial_kmh: f64,
+    pub highway_kmh: f64,
+}
+
+impl Default for RoadClassSpeeds {
+    fn default() -> Self {
+        Self {
+            residential_kmh: 30.0,
+            arterial_kmh: 50.0,
+            highway_kmh: 90.0,
+        }
+    }
+}
+
+impl RoadClassSpeeds {
+    pub fn base_kmh(&self, road_class: RoadClass) -> f64 {
+        match road_class {
+            RoadClass::Residential => self.residential_kmh,
+            RoadClass::Arterial => self.arterial_kmh,
+            RoadClass::Highway => self.highway_kmh,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 pub struct SpeedFactors {
@@ -24,6 +59,7 @@ pub struct SpeedModel {
     rng: StdRng,
     min_kmh: f64,
     max_kmh: f64,
+    road_class_speeds: RoadClassSpeeds,
 }
 
 impl SpeedModel {
@@ -40,11 +76,60 @@ impl SpeedModel {
             rng,
             min_kmh,
             max_kmh,
+            road_class_speeds: RoadClassSpeeds::default(),
         }
     }
 
+    pub fn with_road_class_speeds(mut self, road_class_speeds: RoadClassSpeeds) -> Self {
+        self.road_class_speeds = road_class_speeds;
+        self
+    }
+
     pub fn sample_kmh(&mut self, factors: SpeedFactors) -> f64 {
         let base = self.rng.gen_range(self.min_kmh..=self.max_kmh);
         (base * factors.multiplier).max(1.0)
     }
+
+    /// Rescale a sampled speed to a segment's road class: the sample's ratio to the middle
+    /// of the model's range is applied to the class base speed. Unclassified segments keep
+    /// the sampled speed.
+    pub fn adjust_for_road_class(&self, speed_kmh: f64, road_class: Option<RoadClass>) -> f64 {
+        let Some(road_class) = road_class else {
+            return speed_kmh;
+        };
+        let reference_kmh = (self.min_kmh + self.max_kmh) / 2.0;
+        if reference_kmh <= 0.0 {
+            return speed_kmh;
+        }
+        (speed_kmh * self.road_class_speeds.base_kmh(road_class) / reference_kmh).max(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn road_class_scales_sampled_speed_around_range_midpoint() {
+        let model = SpeedModel::with_range(Some(1), 40.0, 40.0);
+        assert_eq!(model.adjust_for_road_class(40.0, None), 40.0);
+        assert_eq!(
+            model.adjust_for_road_class(40.0, Some(RoadClass::Highway)),
+            90.0
+        );
+        // Traffic slowdowns already in the sample carry over to the class speed.
+        assert_eq!(
+            model.adjust_for_road_class(20.0, Some(RoadClass::Residential)),
+            15.0
+        );
+
+        let custom = model.with_road_class_speeds(RoadClassSpeeds {
+            arterial_kmh: 60.0,
+            ..RoadClassSpeeds::default()
+        });
+        assert_eq!(
+            custom.adjust_for_road_class(40.0, Some(RoadClass::Arterial)),
+            60.0
+        );
+    }
 }
@@ -0,0 +1,350 @@
+//! Lifecycle state machines for riders, drivers and trips.
+//!
+//! Entity state is carried by marker components (`Waiting`, `EnRoute`, `TripOnTrip`, ...). The
+//! [`Lifecycle`] impls map those markers to the [`RiderState`], [`DriverState`] and
+//! [`TripState`] enums and encode which transitions are legal. Systems change state through
+//! [`StateTransitionCommands::transition_to`], which checks the transition when the command is
+//! applied: a legal transition swaps the markers, an illegal one leaves the entity untouched
+//! and is recorded in `SimTelemetry::illegal_transitions`. When [`TransitionGuard`] says so
+//! (the default in debug builds) an illegal transition also panics, so tests fail at the source.
+
+use std::fmt::Debug;
+
+use bevy_ecs::prelude::{Entity, Resource, World};
+use bevy_ecs::system::EntityCommands;
+use bevy_ecs::world::EntityWorldMut;
+
+use crate::clock::SimulationClock;
+use crate::ecs::{
+    Browsing, EnRoute, Evaluating, Idle, InTransit, OffDuty, OnTrip, RiderCancelled,
+    RiderCompleted, TripCancelled, TripCompleted, TripEnRoute, TripOnTrip, Waiting,
+};
+use crate::telemetry::{DriverState, RiderState, SimTelemetry, TripState};
+
+/// A lifecycle whose states are marker components on an entity.
+pub trait Lifecycle: Copy + Eq + Debug + Send + Sync + 'static {
+    /// Every state, in lifecycle order.
+    const ALL: &'static [Self];
+
+    /// Transition table: whether an entity in `self` may move to `next`.
+    fn can_transition_to(self, next: Self) -> bool;
+
+    /// Current state from the entity's markers, or None if it carries none.
+    fn current(entity: &EntityWorldMut) -> Option<Self>;
+
+    /// Replace the entity's state markers with the one for `self`.
+    fn apply(self, entity: &mut EntityWorldMut);
+
+    fn illegal(from: Self, to: Self) -> IllegalTransition;
+}
+
+impl Lifecycle for RiderState {
+    const ALL: &'static [Self] = &[
+        RiderState::Browsing,
+        RiderState::Waiting,
+        RiderState::InTransit,
+        RiderState::Completed,
+        RiderState::Cancelled,
+    ];
+
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Browsing, Self::Waiting | Self::Cancelled)
+                | (Self::Waiting, Self::InTransit | Self::Cancelled)
+                | (Self::InTransit, Self::Completed)
+        )
+    }
+
+    fn current(entity: &EntityWorldMut) -> Option<Self> {
+        if entity.contains::<Browsing>() {
+            Some(RiderState::Browsing)
+        } else if entity.contains::<Waiting>() {
+            Some(RiderState::Waiting)
+        } else if entity.contains::<InTransit>() {
+            Some(RiderState::InTransit)
+        } else if entity.contains::<RiderCompleted>() {
+            Some(RiderState::Completed)
+        } else if entity.contains::<RiderCancelled>() {
+            Some(RiderState::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    fn apply(self, entity: &mut EntityWorldMut) {
+        entity.remove::<(Browsing, Waiting, InTransit, RiderCompleted, RiderCancelled)>();
+        match self {
+            RiderState::Browsing => entity.insert(Browsing),
+            RiderState::Waiting => entity.insert(Waiting),
+            RiderState::InTransit => entity.insert(InTransit),
+            RiderState::Completed => entity.insert(RiderCompleted),
+            RiderState::Cancelled => entity.insert(RiderCancelled),
+        };
+    }
+
+    fn illegal(from: Self, to: Self) -> IllegalTransition {
+        IllegalTransition::Rider { from, to }
+    }
+}
+
+impl Lifecycle for DriverState {
+    const ALL: &'static [Self] = &[
+        DriverState::Idle,
+        DriverState::Evaluating,
+        DriverState::EnRoute,
+        DriverState::OnTrip,
+        DriverState::OffDuty,
+    ];
+
+    /// Any on-duty state may go OffDuty; an OffDuty driver never comes back in this session.
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Idle, Self::Evaluating)
+                | (Self::Evaluating, Self::Idle | Self::EnRoute)
+                | (Self::EnRoute, Self::Idle | Self::OnTrip)
+                | (Self::OnTrip, Self::Idle)
+                | (
+                    Self::Idle | Self::Evaluating | Self::EnRoute | Self::OnTrip,
+                    Self::OffDuty
+                )
+        )
+    }
+
+    fn current(entity: &EntityWorldMut) -> Option<Self> {
+        if entity.contains::<Idle>() {
+            Some(DriverState::Idle)
+        } else if entity.contains::<Evaluating>() {
+            Some(DriverState::Evaluating)
+        } else if entity.contains::<EnRoute>() {
+            Some(DriverState::EnRoute)
+        } else if entity.contains::<OnTrip>() {
+            Some(DriverState::OnTrip)
+        } else if entity.contains::<OffDuty>() {
+            Some(DriverState::OffDuty)
+        } else {
+            None
+        }
+    }
+
+    fn apply(self, entity: &mut EntityWorldMut) {
+        entity.remove::<(Idle, Evaluating, EnRoute, OnTrip, OffDuty)>();
+        match self {
+            DriverState::Idle => entity.insert(Idle),
+            DriverState::Evaluating => entity.insert(Evaluating),
+            DriverState::EnRoute => entity.insert(EnRoute),
+            DriverState::OnTrip => entity.insert(OnTrip),
+            DriverState::OffDuty => entity.insert(OffDuty),
+        };
+    }
+
+    fn illegal(from: Self, to: Self) -> IllegalTransition {
+        IllegalTransition::Driver { from, to }
+    }
+}
+
+impl Lifecycle for TripState {
+    const ALL: &'static [Self] = &[
+        TripState::EnRoute,
+        TripState::OnTrip,
+        TripState::Completed,
+        TripState::Cancelled,
+    ];
+
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::EnRoute, Self::OnTrip | Self::Cancelled) | (Self::OnTrip, Self::Completed)
+        )
+    }
+
+    fn current(entity: &EntityWorldMut) -> Option<Self> {
+        if entity.contains::<TripEnRoute>() {
+            Some(TripState::EnRoute)
+        } else if entity.contains::<TripOnTrip>() {
+            Some(TripState::OnTrip)
+        } else if entity.contains::<TripCompleted>() {
+            Some(TripState::Completed)
+        } else if entity.contains::<TripCancelled>() {
+            Some(TripState::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    fn apply(self, entity: &mut EntityWorldMut) {
+        entity.remove::<(TripEnRoute, TripOnTrip, TripCompleted, TripCancelled)>();
+        match self {
+            TripState::EnRoute => entity.insert(TripEnRoute),
+            TripState::OnTrip => entity.insert(TripOnTrip),
+            TripState::Completed => entity.insert(TripCompleted),
+            TripState::Cancelled => entity.insert(TripCancelled),
+        };
+    }
+
+    fn illegal(from: Self, to: Self) -> IllegalTransition {
+        IllegalTransition::Trip { from, to }
+    }
+}
+
+/// A rejected state change, by lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IllegalTransition {
+    Rider { from: RiderState, to: RiderState },
+    Driver { from: DriverState, to: DriverState },
+    Trip { from: TripState, to: TripState },
+}
+
+/// Structured error event for an illegal transition (see `SimTelemetry::illegal_transitions`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IllegalTransitionRecord {
+    pub timestamp_ms: u64,
+    pub entity: Entity,
+    pub transition: IllegalTransition,
+}
+
+/// Whether an illegal transition panics after it is recorded. Without the resource this
+/// follows `debug_assertions`, so debug builds and tests fail fast while release runs keep
+/// going with the entity's state unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct TransitionGuard {
+    pub panic_on_illegal: bool,
+}
+
+impl Default for TransitionGuard {
+    fn default() -> Self {
+        Self {
+            panic_on_illegal: cfg!(debug_assertions),
+        }
+    }
+}
+
+/// Guarded state changes on entity commands.
+pub trait StateTransitionCommands {
+    /// Move the entity to `next` when the command is applied, if the transition is legal.
+    /// Entities without a state marker of this lifecycle take `next` as their initial state.
+    fn transition_to<S: Lifecycle>(&mut self, next: S) -> &mut Self;
+}
+
+impl<'a> StateTransitionCommands for EntityCommands<'a> {
+    fn transition_to<S: Lifecycle>(&mut self, next: S) -> &mut Self {
+        self.add(move |entity: Entity, world: &mut World| {
+            apply_transition(world, entity, next);
+        })
+    }
+}
+
+/// Apply `next` to `entity` if legal; otherwise record (and optionally panic on) the attempt.
+/// Despawned entities are ignored. Returns whether the state changed.
+pub fn apply_transition<S: Lifecycle>(world: &mut World, entity: Entity, next: S) -> bool {
+    let Some(mut entity_mut) = world.get_entity_mut(entity) else {
+        return false;
+    };
+    let current = S::current(&entity_mut);
+    match current {
+        Some(from) if !from.can_transition_to(next) => {
+            let record = IllegalTransitionRecord {
+                timestamp_ms: world
+                    .get_resource::<SimulationClock>()
+                    .map(|clock| clock.now())
+                    .unwrap_or(0),
+                entity,
+                transition: S::illegal(from, next),
+            };
+            if let Some(mut telemetry) = world.get_resource_mut::<SimTelemetry>() {
+                telemetry.illegal_transitions.push(record);
+            }
+            let guard = world
+                .get_resource::<TransitionGuard>()
+                .copied()
+                .unwrap_or_default();
+            if guard.panic_on_illegal {
+                panic!("illegal state transition: {record:?}");
+            }
+            false
+        }
+        _ => {
+            next.apply(&mut entity_mut);
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Try every (from, to) pair of `S` and check the outcome against the transition table.
+    fn check_all_transitions<S: Lifecycle>() -> usize {
+        let mut world = World::new();
+        world.insert_resource(SimTelemetry::default());
+        world.insert_resource(TransitionGuard {
+            panic_on_illegal: false,
+        });
+
+        let mut illegal = 0;
+        for &from in S::ALL {
+            for &to in S::ALL {
+                let entity = world.spawn_empty().id();
+                assert!(apply_transition(&mut world, entity, from));
+                let applied = apply_transition(&mut world, entity, to);
+                let state = S::current(&world.entity_mut(entity));
+
+                assert_eq!(applied, from.can_transition_to(to), "{from:?} -> {to:?}");
+                if applied {
+                    assert_eq!(state, Some(to));
+                } else {
+                    assert_eq!(state, Some(from), "state must survive {from:?} -> {to:?}");
+                    illegal += 1;
+                    assert_eq!(
+                        world
+                            .resource::<SimTelemetry>()
+                            .illegal_transitions
+                            .last()
+                            .map(|record| (record.entity, record.transition)),
+                        Some((entity, S::illegal(from, to)))
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            world.resource::<SimTelemetry>().illegal_transitions.len(),
+            illegal
+        );
+        illegal
+    }
+
+    #[test]
+    fn rider_transitions_follow_table() {
+        // 5 x 5 pairs, 5 legal
+        assert_eq!(check_all_transitions::<RiderState>(), 20);
+        assert!(!RiderState::InTransit.can_transition_to(RiderState::Waiting));
+    }
+
+    #[test]
+    fn driver_transitions_follow_table() {
+        // 5 x 5 pairs, 10 legal
+        assert_eq!(check_all_transitions::<DriverState>(), 15);
+        assert!(!DriverState::OnTrip.can_transition_to(DriverState::EnRoute));
+        assert!(!DriverState::OffDuty.can_transition_to(DriverState::Idle));
+    }
+
+    #[test]
+    fn trip_transitions_follow_table() {
+        // 4 x 4 pairs, 3 legal
+        assert_eq!(check_all_transitions::<TripState>(), 13);
+        assert!(!TripState::Completed.can_transition_to(TripState::Cancelled));
+    }
+
+    #[test]
+    #[should_panic(expected = "illegal state transition")]
+    fn guard_panics_on_illegal_transition() {
+        let mut world = World::new();
+        world.insert_resource(TransitionGuard {
+            panic_on_illegal: true,
+        });
+        let entity = world.spawn(OnTrip).id();
+        apply_transition(&mut world, entity, DriverState::EnRoute);
+    }
+}
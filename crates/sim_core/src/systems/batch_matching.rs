@@ -1,15 +1,23 @@
 //! Batch matching system: run a global matching pass when BatchMatchRun fires.
 //!
 //! Collects all riders in Waiting state and all Idle drivers, calls the matching
-//! algorithm's find_batch_matches, applies matches, and schedules the next batch run.
+//! algorithm's find_batch_matches and applies matches. Runs recur every
+//! `BatchMatchingConfig::interval_secs` (registered by `simulation_started_system`).
+//! When `MatchDiagnostics` is present, each waiting rider's scored candidates are recorded.
+//! With a `ShadowMatchingAlgorithm` and `ShadowMatchLog`, the shadow algorithm's batch on the
+//! same riders and drivers is recorded next to the live one.
 
 use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
-use crate::ecs::{Driver, DriverStateCommands, Idle, Position, Rider, Waiting};
-use crate::matching::MatchingAlgorithmResource;
+use crate::ecs::{DispatchNotBefore, Driver, DriverStateCommands, Idle, Position, Rider, Waiting};
+use crate::location::ReportedPosition;
+use crate::matching::{MatchResult, MatchingAlgorithmResource, ShadowMatchingAlgorithm};
 use crate::scenario::{BatchMatchingConfig, MatchRadius};
+use crate::systems::matching::shadow_match_record;
+use crate::telemetry::{MatchDiagnosticRecord, MatchDiagnostics, ShadowMatchLog, SimTelemetry};
 
+#[allow(clippy::too_many_arguments)]
 pub fn batch_matching_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
@@ -17,8 +25,26 @@ pub fn batch_matching_system(
     batch_config: Option<Res<BatchMatchingConfig>>,
     match_radius: Option<Res<MatchRadius>>,
     matching_algorithm: Res<MatchingAlgorithmResource>,
-    mut riders: Query<(Entity, &mut Rider, &Position, Option<&Waiting>)>,
-    mut drivers: Query<(Entity, &mut Driver, &Position, Option<&Idle>)>,
+    diagnostics: Option<ResMut<MatchDiagnostics>>,
+    shadow: (
+        Option<Res<ShadowMatchingAlgorithm>>,
+        Option<ResMut<ShadowMatchLog>>,
+    ),
+    mut telemetry: Option<ResMut<SimTelemetry>>,
+    mut riders: Query<(
+        Entity,
+        &mut Rider,
+        &Position,
+        Option<&Waiting>,
+        Option<&DispatchNotBefore>,
+    )>,
+    mut drivers: Query<(
+        Entity,
+        &mut Driver,
+        &Position,
+        Option<&Idle>,
+        Option<&ReportedPosition>,
+    )>,
 ) {
     if event.0.kind != EventKind::BatchMatchRun {
         return;
@@ -35,17 +61,26 @@ pub fn batch_matching_system(
 
     // Collect only riders who are Waiting and not yet assigned (looking for a match).
     // Riders who are Waiting but already have matched_driver are waiting for that driver
-    // to accept/drive to pickup and must not be re-matched.
+    // to accept/drive to pickup and must not be re-matched. Wait-and-save riders join
+    // once their deferred dispatch window has passed.
+    let now = clock.now();
     let waiting_riders: Vec<(Entity, h3o::CellIndex, Option<h3o::CellIndex>)> = riders
         .iter()
-        .filter(|(_, rider, _, waiting)| waiting.is_some() && rider.matched_driver.is_none())
-        .map(|(entity, rider, position, _)| (entity, position.0, rider.destination))
+        .filter(|(_, rider, _, waiting, not_before)| {
+            waiting.is_some()
+                && rider.matched_driver.is_none()
+                && not_before.is_none_or(|not_before| not_before.0 <= now)
+        })
+        .map(|(entity, rider, position, _, _)| (entity, position.0, rider.destination))
         .collect();
 
-    // Collect all Idle drivers (exclude OffDuty and others)
+    // Collect all Idle drivers (exclude OffDuty and others) at the position they last
+    // reported, when location reporting is modeled
     let available_drivers: Vec<(Entity, h3o::CellIndex)> = drivers
         .iter()
-        .filter_map(|(entity, _driver, position, idle)| idle.map(|_| (entity, position.0)))
+        .filter_map(|(entity, _driver, position, idle, reported)| {
+            idle.map(|_| (entity, reported.map_or(position.0, |r| r.cell)))
+        })
         .collect();
 
     let matches = matching_algorithm.find_batch_matches(
@@ -55,15 +90,63 @@ pub fn batch_matching_system(
         clock.now(),
     );
 
+    if let Some(mut diagnostics) = diagnostics {
+        for (rider_entity, rider_pos, _) in &waiting_riders {
+            let chosen_driver = matches
+                .iter()
+                .find(|m| m.rider_entity == *rider_entity)
+                .map(|m| m.driver_entity);
+            let candidates = matching_algorithm.score_candidates(
+                *rider_entity,
+                *rider_pos,
+                &available_drivers,
+                radius,
+            );
+            diagnostics.records.push(MatchDiagnosticRecord {
+                timestamp_ms: now,
+                rider: *rider_entity,
+                batch: true,
+                chosen_driver,
+                candidates,
+            });
+        }
+    }
+
+    if let (Some(shadow_algorithm), Some(mut shadow_log)) = shadow {
+        let shadow_matches =
+            shadow_algorithm
+                .0
+                .find_batch_matches(&waiting_riders, &available_drivers, radius, now);
+        let driver_for = |matches: &[MatchResult], rider: Entity| {
+            matches
+                .iter()
+                .find(|m| m.rider_entity == rider)
+                .map(|m| m.driver_entity)
+        };
+        for (rider_entity, rider_pos, _) in &waiting_riders {
+            shadow_log.records.push(shadow_match_record(
+                now,
+                (*rider_entity, *rider_pos),
+                true,
+                driver_for(&matches, *rider_entity),
+                driver_for(&shadow_matches, *rider_entity),
+                &available_drivers,
+            ));
+        }
+    }
+
     for m in matches {
-        if let Ok((_, mut rider, _, _)) = riders.get_mut(m.rider_entity) {
+        if let Ok((_, mut rider, _, _, _)) = riders.get_mut(m.rider_entity) {
             rider.matched_driver = Some(m.driver_entity);
         }
-        if let Ok((_, mut driver, _, _)) = drivers.get_mut(m.driver_entity) {
+        if let Ok((_, mut driver, position, _, reported)) = drivers.get_mut(m.driver_entity) {
             commands
                 .entity(m.driver_entity)
                 .set_driver_state_evaluating();
             driver.matched_rider = Some(m.rider_entity);
+            if let (Some(reported), Some(telemetry)) = (reported, telemetry.as_deref_mut()) {
+                reported.record_match(position.0, now, &mut telemetry.location_error);
+            }
         }
         clock.schedule_in_secs(
             1,
@@ -71,7 +154,4 @@ pub fn batch_matching_system(
             Some(EventSubject::Driver(m.driver_entity)),
         );
     }
-
-    // Schedule next batch run
-    clock.schedule_in_secs(config.interval_secs, EventKind::BatchMatchRun, None);
 }
@@ -1,18 +1,19 @@
-use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut, With};
+use bevy_ecs::prelude::{Commands, Entity, Local, Query, Res, ResMut, With};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
-use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
+use crate::clock::{hour_of_day, CurrentEvent, EventKind, EventSubject, SimulationClock};
+use crate::decision_models::{DriverAcceptanceFeatures, DriverAcceptanceModel};
 use crate::ecs::{
-    Driver, DriverEarnings, DriverFatigue, DriverStateCommands, Evaluating, Position, Rider, Trip,
-    TripEnRoute, TripFinancials, TripLiveData, TripTiming, Waiting,
+    Driver, DriverEarnings, DriverFatigue, DriverStateCommands, Evaluating, NextMoveStep, Position,
+    Rider, Trip, TripEnRoute, TripFinancials, TripLiveData, TripTiming, VehicleClassChoice,
+    Waiting,
 };
 use crate::scenario::DriverDecisionConfig;
 use crate::spatial::distance_km_between_cells;
 
-/// Calculate logit probability from score and sample stochastically using seeded RNG.
-fn logit_accepts_stochastic(score: f64, seed: u64, driver_entity: Entity) -> bool {
-    let probability = 1.0 / (1.0 + (-score).exp());
+/// Sample an accept with the given probability using seeded RNG.
+fn accepts_stochastic(probability: f64, seed: u64, driver_entity: Entity) -> bool {
     let rng_seed = seed.wrapping_add(driver_entity.index() as u64);
     let mut rng = StdRng::seed_from_u64(rng_seed);
     rng.gen::<f64>() < probability
@@ -22,6 +23,8 @@ pub fn driver_decision_system(
     mut clock: ResMut<SimulationClock>,
     event: Res<CurrentEvent>,
     driver_config: Option<Res<DriverDecisionConfig>>,
+    default_driver_config: Local<DriverDecisionConfig>,
+    acceptance_model: Option<Res<DriverAcceptanceModel>>,
     mut commands: Commands,
     mut drivers: Query<
         (
@@ -33,7 +36,13 @@ pub fn driver_decision_system(
         ),
         With<Evaluating>,
     >,
-    mut riders: Query<(Entity, &mut Rider, &Position, Option<&Waiting>)>,
+    mut riders: Query<(
+        Entity,
+        &mut Rider,
+        &Position,
+        Option<&Waiting>,
+        Option<&VehicleClassChoice>,
+    )>,
 ) {
     if event.0.kind != EventKind::DriverDecision {
         return;
@@ -55,7 +64,7 @@ pub fn driver_decision_system(
 
     // Get trip characteristics for score calculation
     let (pickup, dropoff, requested_at, rider_waiting, fare) = match riders.get_mut(rider_entity) {
-        Ok((_entity, rider, pos, waiting)) => {
+        Ok((_entity, rider, pos, waiting, _)) => {
             let pickup = pos.0;
             let Some(dropoff) = rider.destination else {
                 // Rider has no destination; reset driver and bail
@@ -76,14 +85,14 @@ pub fn driver_decision_system(
     if !rider_waiting {
         commands.entity(driver_entity).set_driver_state_idle();
         driver.matched_rider = None;
-        if let Ok((_entity, mut rider, _, _)) = riders.get_mut(rider_entity) {
+        if let Ok((_entity, mut rider, _, _, _)) = riders.get_mut(rider_entity) {
             rider.matched_driver = None;
         }
         return;
     }
 
     // Calculate logit score based on trip and driver characteristics
-    let config = driver_config.as_deref().copied().unwrap_or_default();
+    let config = driver_config.as_deref().unwrap_or(&default_driver_config);
 
     let pickup_distance_km = distance_km_between_cells(driver_pos.0, pickup);
     let trip_distance_km = distance_km_between_cells(pickup, dropoff);
@@ -97,22 +106,36 @@ pub fn driver_decision_system(
     let fatigue_ratio =
         session_duration_ms as f64 / driver_fatigue.fatigue_threshold_ms.max(1) as f64;
 
-    // Calculate score: higher score = higher acceptance probability
-    let score = config.base_acceptance_score
-        + (fare * config.fare_weight)
-        + (pickup_distance_km * config.pickup_distance_penalty)
-        + (trip_distance_km * config.trip_distance_bonus)
-        + (earnings_progress * config.earnings_progress_weight)
-        + (fatigue_ratio * config.fatigue_penalty);
+    // A trained acceptance model replaces the fixed logit weights
+    let accept_probability = match acceptance_model.as_deref() {
+        Some(model) => model.accept_probability(&DriverAcceptanceFeatures {
+            fare,
+            pickup_distance_km,
+            trip_distance_km,
+            earnings_progress,
+            fatigue_ratio,
+            local_hour: hour_of_day(clock.sim_to_local_ms(clock.now())) as u8,
+        }),
+        None => {
+            // Calculate score: higher score = higher acceptance probability
+            let score = config.base_acceptance_score
+                + (fare * config.fare_weight)
+                + (pickup_distance_km * config.pickup_distance_penalty)
+                + (trip_distance_km * config.trip_distance_bonus)
+                + (earnings_progress * config.earnings_progress_weight)
+                + (fatigue_ratio * config.fatigue_penalty);
+            1.0 / (1.0 + (-score).exp())
+        }
+    };
 
-    if logit_accepts_stochastic(score, config.seed, driver_entity) {
+    if accepts_stochastic(accept_probability, config.seed, driver_entity) {
         let matched_at = clock.now();
         let pickup_distance_km_at_accept = distance_km_between_cells(driver_pos.0, pickup);
         commands.entity(driver_entity).set_driver_state_en_route();
-        let agreed_fare = riders
+        let (agreed_fare, class_choice) = riders
             .get(rider_entity)
-            .ok()
-            .and_then(|(_, r, _, _)| r.accepted_fare);
+            .map(|(_, r, _, _, choice)| (r.accepted_fare, choice.cloned()))
+            .unwrap_or_default();
 
         let trip_entity = commands
             .spawn((
@@ -133,22 +156,28 @@ pub fn driver_decision_system(
                 TripFinancials {
                     agreed_fare,
                     pickup_distance_km_at_accept,
+                    deadhead_km: 0.0,
+                    trip_km: 0.0,
                 },
                 TripLiveData { pickup_eta_ms: 0 },
             ))
             .id();
 
         // Set trip backlinks on rider and driver for O(1) lookup
-        if let Ok((_entity, mut rider, _pos, _waiting)) = riders.get_mut(rider_entity) {
+        if let Ok((_entity, mut rider, _pos, _waiting, _choice)) = riders.get_mut(rider_entity) {
             rider.assigned_trip = Some(trip_entity);
         }
         driver.assigned_trip = Some(trip_entity);
 
-        clock.schedule_in_secs(
+        let move_step = clock.schedule_in_secs(
             1,
             EventKind::MoveStep,
             Some(EventSubject::Trip(trip_entity)),
         );
+        commands.entity(trip_entity).insert(NextMoveStep(move_step));
+        if let Some(choice) = class_choice {
+            commands.entity(trip_entity).insert(choice);
+        }
     } else {
         let rejected_rider = driver.matched_rider;
         commands.entity(driver_entity).set_driver_state_idle();
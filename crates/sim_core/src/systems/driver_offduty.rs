@@ -2,17 +2,26 @@
 
 use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut};
 
-use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock, ONE_MIN_MS};
+use crate::clock::{
+    CurrentEvent, EventKind, EventSubject, Recurrence, SimulationClock, ONE_MIN_MS,
+};
 use crate::ecs::{Driver, DriverEarnings, DriverFatigue, DriverStateCommands, OffDuty};
+use crate::pricing::earnings_floor::settle_driver_session;
+use crate::pricing::PricingConfig;
+use crate::telemetry::SimTelemetry;
 
 /// Interval for periodic OffDuty checks (5 minutes).
 const CHECK_INTERVAL_MS: u64 = 5 * ONE_MIN_MS;
 
 /// Check a single driver for earnings/fatigue thresholds.
-/// Transitions the driver to OffDuty and sets session_end_time_ms if thresholds are exceeded.
+/// Transitions the driver to OffDuty and sets session_end_time_ms if thresholds are exceeded,
+/// settling any minimum earnings floor top-up for the session.
+#[allow(clippy::too_many_arguments)]
 fn check_driver_offduty(
     commands: &mut Commands,
     now: u64,
+    pricing_config: Option<&PricingConfig>,
+    telemetry: Option<&mut SimTelemetry>,
     driver_entity: Entity,
     _driver: &mut Driver,
     earnings: &mut DriverEarnings,
@@ -35,7 +44,12 @@ fn check_driver_offduty(
     }
 
     if should_go_offduty {
-        earnings.session_end_time_ms = Some(now);
+        match pricing_config {
+            Some(config) => {
+                settle_driver_session(earnings, now, config, telemetry);
+            }
+            None => earnings.session_end_time_ms = Some(now),
+        }
         commands.entity(driver_entity).set_driver_state_off_duty();
     }
 }
@@ -43,15 +57,17 @@ fn check_driver_offduty(
 /// System that checks if drivers should go OffDuty based on earnings targets and fatigue thresholds.
 ///
 /// Supports two modes:
-/// - **Periodic** (no subject): iterates all drivers, then schedules the next periodic check.
+/// - **Periodic** (no subject): iterates all drivers; recurs every 5 minutes.
 /// - **Targeted** (`EventSubject::Driver(entity)`): checks only the specified driver. Used by
 ///   `trip_completed_system` to give immediate feedback after earnings are updated.
 ///
-/// Also bootstraps the periodic check cycle on `SimulationStarted`.
+/// Registers the periodic check as a recurring event on `SimulationStarted`.
 pub fn driver_offduty_check_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
     event: Res<CurrentEvent>,
+    pricing_config: Option<Res<PricingConfig>>,
+    mut telemetry: Option<ResMut<SimTelemetry>>,
     mut drivers: Query<(
         Entity,
         &mut Driver,
@@ -72,6 +88,8 @@ pub fn driver_offduty_check_system(
                     check_driver_offduty(
                         &mut commands,
                         now,
+                        pricing_config.as_deref(),
+                        telemetry.as_deref_mut(),
                         entity,
                         &mut driver,
                         &mut earnings,
@@ -80,12 +98,14 @@ pub fn driver_offduty_check_system(
                     );
                 }
             }
-            // Periodic check: iterate all drivers, then schedule next check
+            // Periodic check: iterate all drivers
             _ => {
                 for (entity, mut driver, mut earnings, fatigue, offduty) in drivers.iter_mut() {
                     check_driver_offduty(
                         &mut commands,
                         now,
+                        pricing_config.as_deref(),
+                        telemetry.as_deref_mut(),
                         entity,
                         &mut driver,
                         &mut earnings,
@@ -93,14 +113,18 @@ pub fn driver_offduty_check_system(
                         offduty.is_some(),
                     );
                 }
-                clock.schedule_in(CHECK_INTERVAL_MS, EventKind::CheckDriverOffDuty, None);
             }
         }
         return;
     }
 
-    // Bootstrap periodic checks on simulation start
+    // Register the periodic checks on simulation start
     if event.0.kind == EventKind::SimulationStarted {
-        clock.schedule_in(CHECK_INTERVAL_MS, EventKind::CheckDriverOffDuty, None);
+        let first_check = clock.now() + CHECK_INTERVAL_MS;
+        clock.schedule_recurring(
+            EventKind::CheckDriverOffDuty,
+            None,
+            Recurrence::every(CHECK_INTERVAL_MS).starting_at(first_check),
+        );
     }
 }
@@ -0,0 +1,56 @@
+use bevy_ecs::prelude::{Added, Entity, Or, Query, Res, ResMut, With};
+
+use crate::clock::SimulationClock;
+use crate::ecs::{Driver, EnRoute, Evaluating, Idle, OffDuty, OnTrip, Position};
+use crate::telemetry::{DriverState, DriverUtilization, SimTelemetry};
+
+use super::telemetry_snapshot::driver_state_from_markers;
+
+/// Record driver state transitions in `SimTelemetry::driver_utilization`, and idle intervals
+/// (in the cell the driver idles in) in `SimTelemetry::cell_hours`.
+///
+/// Runs after the event systems' commands are applied, so a driver whose state marker was
+/// (re)inserted during this step is seen with the current event's timestamp.
+#[allow(clippy::type_complexity)]
+pub fn driver_utilization_system(
+    clock: Res<SimulationClock>,
+    telemetry: Option<ResMut<SimTelemetry>>,
+    drivers: Query<
+        (
+            Entity,
+            Option<&Position>,
+            Option<&Idle>,
+            Option<&Evaluating>,
+            Option<&EnRoute>,
+            Option<&OnTrip>,
+            Option<&OffDuty>,
+        ),
+        (
+            With<Driver>,
+            Or<(
+                Added<Idle>,
+                Added<Evaluating>,
+                Added<EnRoute>,
+                Added<OnTrip>,
+                Added<OffDuty>,
+            )>,
+        ),
+    >,
+) {
+    let Some(mut telemetry) = telemetry else {
+        return;
+    };
+    let now = clock.now();
+    for (entity, position, idle, evaluating, en_route, on_trip, off_duty) in drivers.iter() {
+        let state = driver_state_from_markers(idle, evaluating, en_route, on_trip, off_duty);
+        telemetry.cell_hours.end_idle(entity, now);
+        if let Some(position) = position.filter(|_| state == DriverState::Idle) {
+            telemetry.cell_hours.start_idle(entity, position.0, now);
+        }
+        telemetry
+            .driver_utilization
+            .entry(entity)
+            .and_modify(|utilization| utilization.transition(state, now))
+            .or_insert_with(|| DriverUtilization::new(entity, state, now));
+    }
+}
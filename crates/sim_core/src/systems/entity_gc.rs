@@ -0,0 +1,72 @@
+//! Entity garbage collection: despawn finished trips and riders on long runs.
+//!
+//! Runs every `EntityGcConfig::interval_secs` (see the runner's condition), after snapshots and
+//! streaming for the step. A completed trip's record is pushed to `SimTelemetry` when it
+//! completes, so only the entity is dropped here; trips are kept for `retention_secs` after
+//! drop-off or cancellation so recent snapshots still show them. Despawned counts go to
+//! `SimTelemetry::entity_gc`, which snapshots add back to keep trip counts cumulative.
+
+use bevy_ecs::prelude::{Commands, Entity, Or, Query, Res, ResMut, With};
+
+use crate::clock::SimulationClock;
+use crate::ecs::{Rider, RiderCancelled, RiderCompleted, TripCancelled, TripCompleted, TripTiming};
+use crate::scenario::EntityGcConfig;
+use crate::telemetry::SimTelemetry;
+
+#[allow(clippy::type_complexity)]
+pub fn entity_gc_system(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    config: Res<EntityGcConfig>,
+    mut telemetry: ResMut<SimTelemetry>,
+    trips: Query<(
+        Entity,
+        &TripTiming,
+        Option<&TripCompleted>,
+        Option<&TripCancelled>,
+    )>,
+    riders: Query<
+        (Entity, Option<&RiderCompleted>),
+        (
+            With<Rider>,
+            Or<(With<RiderCompleted>, With<RiderCancelled>)>,
+        ),
+    >,
+) {
+    let now = clock.now();
+    let retention_ms = config.retention_secs.saturating_mul(1000);
+    let totals = &mut telemetry.entity_gc;
+    totals.last_run_at_ms = Some(now);
+
+    for (entity, timing, completed, cancelled) in trips.iter() {
+        let finished_at = if completed.is_some() {
+            timing.dropoff_at
+        } else if cancelled.is_some() {
+            timing.cancelled_at
+        } else {
+            None
+        };
+        let Some(finished_at) = finished_at else {
+            continue;
+        };
+        if now < finished_at.saturating_add(retention_ms) {
+            continue;
+        }
+        if completed.is_some() {
+            totals.trips_completed += 1;
+        } else {
+            totals.trips_cancelled += 1;
+        }
+        commands.entity(entity).despawn();
+    }
+
+    // Riders are normally despawned on their final transition; sweep any that linger.
+    for (entity, completed) in riders.iter() {
+        if completed.is_some() {
+            totals.riders_completed += 1;
+        } else {
+            totals.riders_cancelled += 1;
+        }
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,59 @@
+use bevy_ecs::prelude::{Added, Entity, Query, Res, ResMut, With};
+
+use crate::clock::SimulationClock;
+use crate::ecs::{Position, Rider, RiderQuote, Trip, TripOnTrip, Waiting};
+use crate::telemetry::{RiderFunnel, SimTelemetry};
+
+/// Advance riders through the request funnel in `SimTelemetry::funnel`, counting new requests
+/// in `SimTelemetry::cell_hours`.
+///
+/// Stage entries are detected from components added this step (new rider, first quote, waiting
+/// for a match, trip spawned, pickup); exits are recorded by the systems that end the request.
+#[allow(clippy::type_complexity)]
+pub fn rider_funnel_system(
+    clock: Res<SimulationClock>,
+    telemetry: Option<ResMut<SimTelemetry>>,
+    new_riders: Query<(Entity, &Rider, Option<&Position>), Added<Rider>>,
+    quoted: Query<Entity, (With<Rider>, Added<RiderQuote>)>,
+    accepted: Query<Entity, (With<Rider>, Added<Waiting>)>,
+    matched: Query<&Trip, Added<Trip>>,
+    picked_up: Query<&Trip, Added<TripOnTrip>>,
+) {
+    let Some(mut telemetry) = telemetry else {
+        return;
+    };
+    let now = clock.now();
+
+    for (entity, rider, position) in new_riders.iter() {
+        let requested_at = rider.requested_at.unwrap_or(now);
+        telemetry
+            .funnel
+            .entry(entity)
+            .or_insert_with(|| RiderFunnel::new(entity, requested_at));
+        if let Some(position) = position {
+            telemetry
+                .cell_hours
+                .record_request(entity, position.0, requested_at);
+        }
+    }
+    for entity in quoted.iter() {
+        if let Some(funnel) = telemetry.funnel.get_mut(&entity) {
+            funnel.quoted_at.get_or_insert(now);
+        }
+    }
+    for entity in accepted.iter() {
+        if let Some(funnel) = telemetry.funnel.get_mut(&entity) {
+            funnel.quote_accepted_at.get_or_insert(now);
+        }
+    }
+    for trip in matched.iter() {
+        if let Some(funnel) = telemetry.funnel.get_mut(&trip.rider) {
+            funnel.matched_at.get_or_insert(now);
+        }
+    }
+    for trip in picked_up.iter() {
+        if let Some(funnel) = telemetry.funnel.get_mut(&trip.rider) {
+            funnel.pickup_at.get_or_insert(now);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Location report system: refresh every driver's reported position once per update interval.
+//!
+//! Runs only when `LocationReportingConfig` is present. The ticks are registered as a recurring
+//! event on `SimulationStarted`; each `LocationReport` event samples a (possibly noisy) fix
+//! from the driver's true position. Drivers spawned between ticks have no
+//! report yet and are matched on their true position until the next tick.
+
+use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut, With};
+
+use crate::clock::{CurrentEvent, EventKind, Recurrence, SimulationClock};
+use crate::ecs::{Driver, Position};
+use crate::location::{LocationReportingConfig, ReportedPosition, LOCATION_NOISE_STREAM};
+use crate::seeds::SeedHierarchy;
+
+pub fn location_report_system(
+    mut commands: Commands,
+    mut clock: ResMut<SimulationClock>,
+    event: Res<CurrentEvent>,
+    config: Option<Res<LocationReportingConfig>>,
+    seeds: Option<Res<SeedHierarchy>>,
+    mut drivers: Query<(Entity, &Position, Option<&mut ReportedPosition>), With<Driver>>,
+) {
+    let Some(config) = config else {
+        return;
+    };
+
+    match event.0.kind {
+        EventKind::SimulationStarted => {
+            let interval_ms = config.update_interval_ms();
+            let now = clock.now();
+            clock.schedule_recurring(
+                EventKind::LocationReport,
+                None,
+                Recurrence::every(interval_ms).starting_at(now),
+            );
+        }
+        EventKind::LocationReport => {
+            let now = clock.now();
+            let interval_ms = config.update_interval_ms();
+            let tick = now / interval_ms;
+            let seed = seeds
+                .as_deref()
+                .map(|seeds| seeds.named_seed(LOCATION_NOISE_STREAM))
+                .unwrap_or(0);
+
+            for (entity, position, reported) in drivers.iter_mut() {
+                let report = ReportedPosition {
+                    cell: config.reported_cell(position.0, seed, entity, tick),
+                    reported_at_ms: now,
+                };
+                match reported {
+                    Some(mut reported) => *reported = report,
+                    None => {
+                        commands.entity(entity).insert(report);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -2,11 +2,46 @@ use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use crate::ecs::{Driver, DriverStateCommands, Idle, Position, Rider, Waiting};
-use crate::matching::MatchingAlgorithmResource;
+use crate::location::ReportedPosition;
+use crate::matching::{MatchingAlgorithmResource, ShadowMatchingAlgorithm};
 use crate::scenario::{BatchMatchingConfig, MatchRadius};
+use crate::sim_log::{SimLogCommands, SimLogEvent};
+use crate::spatial::distance_km_between_cells;
+use crate::telemetry::{
+    MatchDiagnosticRecord, MatchDiagnostics, ShadowMatchLog, ShadowMatchRecord, SimTelemetry,
+};
 
 const MATCH_RETRY_SECS: u64 = 30;
 
+/// Compare the live and shadow choices for one rider, with pickup distances from the drivers'
+/// matching positions.
+pub(crate) fn shadow_match_record(
+    timestamp_ms: u64,
+    (rider, rider_pos): (Entity, h3o::CellIndex),
+    batch: bool,
+    chosen_driver: Option<Entity>,
+    shadow_driver: Option<Entity>,
+    available_drivers: &[(Entity, h3o::CellIndex)],
+) -> ShadowMatchRecord {
+    let pickup_km = |driver: Option<Entity>| {
+        let driver = driver?;
+        available_drivers
+            .iter()
+            .find(|(entity, _)| *entity == driver)
+            .map(|(_, cell)| distance_km_between_cells(*cell, rider_pos))
+    };
+    ShadowMatchRecord {
+        timestamp_ms,
+        rider,
+        batch,
+        chosen_driver,
+        chosen_pickup_km: pickup_km(chosen_driver),
+        shadow_driver,
+        shadow_pickup_km: pickup_km(shadow_driver),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn matching_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
@@ -14,8 +49,20 @@ pub fn matching_system(
     batch_config: Option<Res<BatchMatchingConfig>>,
     match_radius: Option<Res<MatchRadius>>,
     matching_algorithm: Res<MatchingAlgorithmResource>,
+    diagnostics: Option<ResMut<MatchDiagnostics>>,
+    shadow: (
+        Option<Res<ShadowMatchingAlgorithm>>,
+        Option<ResMut<ShadowMatchLog>>,
+    ),
+    telemetry: Option<ResMut<SimTelemetry>>,
     mut riders: Query<(Entity, &mut Rider, &Position, Option<&Waiting>)>,
-    mut drivers: Query<(Entity, &mut Driver, &Position, Option<&Idle>)>,
+    mut drivers: Query<(
+        Entity,
+        &mut Driver,
+        &Position,
+        Option<&Idle>,
+        Option<&ReportedPosition>,
+    )>,
 ) {
     if event.0.kind != EventKind::TryMatch {
         return;
@@ -41,10 +88,13 @@ pub fn matching_system(
 
     let radius = match_radius.as_deref().map(|r| r.0).unwrap_or(0);
 
-    // Collect available drivers (idle drivers only; exclude OffDuty drivers)
+    // Collect available drivers (idle drivers only; exclude OffDuty drivers) at the
+    // position they last reported, when location reporting is modeled
     let available_drivers: Vec<(Entity, h3o::CellIndex)> = drivers
         .iter()
-        .filter_map(|(entity, _driver, position, idle)| idle.map(|_| (entity, position.0)))
+        .filter_map(|(entity, _driver, position, idle, reported)| {
+            idle.map(|_| (entity, reported.map_or(position.0, |r| r.cell)))
+        })
         .collect();
 
     // Use the matching algorithm to find a match
@@ -57,7 +107,50 @@ pub fn matching_system(
         clock.now(),
     );
 
+    if let Some(mut diagnostics) = diagnostics {
+        let candidates = matching_algorithm.score_candidates(
+            rider_entity,
+            rider_pos,
+            &available_drivers,
+            radius,
+        );
+        diagnostics.records.push(MatchDiagnosticRecord {
+            timestamp_ms: clock.now(),
+            rider: rider_entity,
+            batch: false,
+            chosen_driver: driver_entity,
+            candidates,
+        });
+    }
+
+    // Counterfactual: what the shadow algorithm would have chosen on the same inputs
+    if let (Some(shadow_algorithm), Some(mut shadow_log)) = shadow {
+        let shadow_driver = shadow_algorithm.0.find_match(
+            rider_entity,
+            rider_pos,
+            rider_destination,
+            &available_drivers,
+            radius,
+            clock.now(),
+        );
+        shadow_log.records.push(shadow_match_record(
+            clock.now(),
+            (rider_entity, rider_pos),
+            false,
+            driver_entity,
+            shadow_driver,
+            &available_drivers,
+        ));
+    }
+
     let Some(driver_entity) = driver_entity else {
+        commands.log_sim_event(
+            clock.now(),
+            SimLogEvent::NoDriverInRadius {
+                rider: rider_entity,
+                idle_drivers: available_drivers.len(),
+            },
+        );
         clock.schedule_in_secs(
             MATCH_RETRY_SECS,
             EventKind::TryMatch,
@@ -70,9 +163,12 @@ pub fn matching_system(
     if let Ok((_entity, mut rider, _, _)) = riders.get_mut(rider_entity) {
         rider.matched_driver = Some(driver_entity);
     }
-    if let Ok((_entity, mut driver, _, _)) = drivers.get_mut(driver_entity) {
+    if let Ok((_entity, mut driver, position, _, reported)) = drivers.get_mut(driver_entity) {
         commands.entity(driver_entity).set_driver_state_evaluating();
         driver.matched_rider = Some(rider_entity);
+        if let (Some(reported), Some(mut telemetry)) = (reported, telemetry) {
+            reported.record_match(position.0, clock.now(), &mut telemetry.location_error);
+        }
     }
 
     clock.schedule_in_secs(
@@ -15,6 +15,10 @@
 pub mod batch_matching;
 pub mod driver_decision;
 pub mod driver_offduty;
+pub mod driver_utilization;
+pub mod entity_gc;
+pub mod funnel;
+pub mod location_report;
 pub mod match_accepted;
 pub mod match_rejected;
 pub mod matching;
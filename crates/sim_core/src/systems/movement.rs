@@ -3,20 +3,27 @@
 //! On the first `MoveStep` for a trip, the route provider is queried and the
 //! result is stored as a [`TripRoute`] component. Subsequent steps advance
 //! along the cached cell path. Travel time per step is adjusted by the traffic
-//! model (time-of-day profile, congestion zones, vehicle density).
+//! model (time-of-day profile, congestion zones, vehicle density) and, on class-annotated
+//! routes, by the segment's road class. With [`RerouteConfig`] enabled, a leg whose traffic
+//! factor has shifted since routing re-queries the provider from the driver's position at
+//! most once per interval, and the pickup ETA is refreshed from the new route. Each step's distance is
+//! charged to the trip (deadhead or on-trip km) and to `SimTelemetry::emissions`.
 
 use bevy_ecs::prelude::{Commands, Entity, ParamSet, Query, Res, ResMut, With};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock, ONE_SEC_MS};
 use crate::ecs::{
-    Driver, EnRoute, GeoPosition, OnTrip, Position, Rider, Trip, TripEnRoute, TripLiveData,
-    TripOnTrip, TripRoute,
+    Driver, DriverVehicle, EnRoute, GeoPosition, NextMoveStep, OnTrip, Position, Rider, Trip,
+    TripEnRoute, TripFinancials, TripLiveData, TripOnTrip, TripRoute,
 };
-use crate::routing::RouteProviderResource;
+use crate::emissions::{DrivingLeg, EmissionFactors};
+use crate::routing::{RoadClass, RouteProviderResource};
+use crate::sim_log::{SimLogCommands, SimLogEvent};
 use crate::spatial::{distance_km_between_cells, grid_path_cells_cached, SpatialIndex};
 use crate::speed::{SpeedFactors, SpeedModel};
+use crate::telemetry::SimTelemetry;
 use crate::traffic::{
-    compute_traffic_factor, CongestionZones, DynamicCongestionConfig, TrafficProfile,
+    compute_traffic_factor, CongestionZones, DynamicCongestionConfig, RerouteConfig, TrafficProfile,
 };
 use h3o::Resolution;
 
@@ -32,6 +39,54 @@ fn travel_time_ms(distance_km: f64, speed_kmh: f64) -> u64 {
 struct RouteStep {
     point: h3o::LatLng,
     distance_km: f64,
+    road_class: Option<RoadClass>,
+    /// The trip's existing route was replaced on this step.
+    rerouted: bool,
+}
+
+/// Time and traffic at which routes are resolved on this step.
+struct RoutingContext<'a> {
+    now_ms: u64,
+    traffic_factor: f64,
+    reroute: Option<&'a RerouteConfig>,
+}
+
+impl RoutingContext<'_> {
+    fn reroute_due(&self, route: &TripRoute) -> bool {
+        self.reroute.is_some_and(|config| {
+            config.should_reroute(
+                route.routed_at_ms,
+                route.traffic_factor_at_routing,
+                self.now_ms,
+                self.traffic_factor,
+            )
+        })
+    }
+
+    /// Stamp a freshly resolved route and take its first step.
+    fn start_route(&self, route: TripRoute) -> Option<(TripRoute, RouteStep)> {
+        let mut route = route.stamp(self.now_ms, self.traffic_factor);
+        let (point, distance_km) = route.advance()?;
+        let road_class = route.current_road_class();
+        Some((
+            route,
+            RouteStep {
+                point,
+                distance_km,
+                road_class,
+                rerouted: false,
+            },
+        ))
+    }
+}
+
+fn advance_route(route: &mut TripRoute) -> Option<RouteStep> {
+    route.advance().map(|(point, distance)| RouteStep {
+        point,
+        distance_km: distance,
+        road_class: route.current_road_class(),
+        rerouted: false,
+    })
 }
 
 fn resolve_next_route_step(
@@ -40,40 +95,53 @@ fn resolve_next_route_step(
     driver_pos_cell: h3o::CellIndex,
     target_cell: h3o::CellIndex,
     route_provider: &RouteProviderResource,
-    trip_route: Option<&mut TripRoute>,
+    mut trip_route: Option<&mut TripRoute>,
+    routing: &RoutingContext,
 ) -> Option<RouteStep> {
-    if let Some(route) = trip_route {
-        return route.advance().map(|(point, distance)| RouteStep {
-            point,
-            distance_km: distance,
-        });
-    }
-
-    if let Some(route_result) = route_provider.0.route(driver_pos_cell, target_cell) {
-        if let Some(mut new_route) = TripRoute::from_route_result(route_result) {
-            if let Some((point, distance)) = new_route.advance() {
-                commands.entity(trip_entity).insert(new_route);
-                return Some(RouteStep {
-                    point,
-                    distance_km: distance,
-                });
-            }
+    if let Some(route) = trip_route.as_deref_mut() {
+        if !routing.reroute_due(route) {
+            return advance_route(route);
         }
     }
 
-    if let Some(path) = grid_path_cells_cached(driver_pos_cell, target_cell) {
-        if let Some(mut new_route) = TripRoute::from_cells(path) {
-            if let Some((point, distance)) = new_route.advance() {
-                commands.entity(trip_entity).insert(new_route);
-                return Some(RouteStep {
-                    point,
-                    distance_km: distance,
-                });
-            }
+    let fresh = route_provider
+        .0
+        .route(driver_pos_cell, target_cell)
+        .and_then(TripRoute::from_route_result)
+        .and_then(|route| routing.start_route(route))
+        .or_else(|| {
+            commands.log_sim_event(
+                routing.now_ms,
+                SimLogEvent::RouteFallback { trip: trip_entity },
+            );
+            grid_path_cells_cached(driver_pos_cell, target_cell)
+                .and_then(TripRoute::from_cells)
+                .and_then(|route| routing.start_route(route))
+        });
+
+    match (trip_route, fresh) {
+        // Reroute: replace the remaining route in place so this step's ETA already uses it.
+        (Some(route), Some((new_route, step))) => {
+            *route = new_route.continue_from(route);
+            Some(RouteStep {
+                rerouted: true,
+                ..step
+            })
+        }
+        // Rerouting found nothing; keep driving the current route.
+        (Some(route), None) => advance_route(route),
+        (None, Some((new_route, step))) => {
+            commands.entity(trip_entity).insert(new_route);
+            Some(step)
+        }
+        (None, None) => {
+            commands.log_sim_event(
+                routing.now_ms,
+                SimLogEvent::RouteUnavailable { trip: trip_entity },
+            );
+            None
         }
     }
-
-    None
 }
 
 fn lat_lng_to_cell(point: h3o::LatLng) -> h3o::CellIndex {
@@ -90,6 +158,7 @@ pub fn movement_system(
     traffic_profile: Res<TrafficProfile>,
     congestion_zones: Res<CongestionZones>,
     dynamic_congestion: Res<DynamicCongestionConfig>,
+    reroute_config: Option<Res<RerouteConfig>>,
     spatial_index: Option<Res<SpatialIndex>>,
     mut trips: Query<(
         &mut Trip,
@@ -98,6 +167,10 @@ pub fn movement_system(
         Option<&TripEnRoute>,
         Option<&TripOnTrip>,
     )>,
+    mut trip_financials: Query<&mut TripFinancials>,
+    vehicles: Query<&DriverVehicle>,
+    emission_factors: Option<Res<EmissionFactors>>,
+    telemetry: Option<ResMut<SimTelemetry>>,
     mut queries: ParamSet<(
         Query<(
             &mut Driver,
@@ -150,7 +223,7 @@ pub fn movement_system(
     };
 
     // Compute traffic-adjusted speed
-    let epoch_ms = clock.epoch_ms();
+    let epoch_ms = clock.local_epoch_ms();
     let sim_time_ms = clock.now();
     let drivers_in_cell = spatial_index
         .as_ref()
@@ -200,12 +273,19 @@ pub fn movement_system(
             target_cell,
             &route_provider,
             trip_route_ref,
+            &RoutingContext {
+                now_ms: sim_time_ms,
+                traffic_factor,
+                reroute: reroute_config.as_deref(),
+            },
         )
     };
 
     let RouteStep {
         point: next_geo,
         distance_km: step_distance_km,
+        road_class,
+        rerouted,
     } = match route_step {
         Some(step) => step,
         None => {
@@ -219,6 +299,7 @@ pub fn movement_system(
         }
     };
 
+    let speed_kmh = speed.adjust_for_road_class(speed_kmh, road_class);
     let next_driver_cell = lat_lng_to_cell(next_geo);
 
     // Update driver position and precise geo location
@@ -231,6 +312,41 @@ pub fn movement_system(
         if let Some(mut geo) = driver_geo {
             geo.0 = next_geo;
         }
+        if let Some(index) = spatial_index.as_deref() {
+            index.mark_moved(driver_entity);
+        }
+    }
+
+    // Attribute driven distance to the trip: empty to pickup, or with the rider on board
+    if let Ok(mut financials) = trip_financials.get_mut(trip_entity) {
+        if is_en_route {
+            financials.deadhead_km += step_distance_km;
+        } else {
+            financials.trip_km += step_distance_km;
+        }
+    }
+
+    if let Some(mut telemetry) = telemetry {
+        if rerouted {
+            telemetry.reroutes_total += 1;
+        }
+        let vehicle = vehicles
+            .get(driver_entity)
+            .map(|vehicle| vehicle.0)
+            .unwrap_or_default();
+        let grams_per_km = emission_factors
+            .as_deref()
+            .copied()
+            .unwrap_or_default()
+            .grams_per_km(vehicle);
+        let leg = if is_en_route {
+            DrivingLeg::Deadhead
+        } else {
+            DrivingLeg::Occupied
+        };
+        telemetry
+            .emissions
+            .record(leg, step_distance_km, grams_per_km);
     }
 
     // If trip is OnTrip, update rider position to match driver (rider is in the vehicle)
@@ -241,6 +357,9 @@ pub fn movement_system(
             if let Some(mut geo) = rider_geo {
                 geo.0 = next_geo;
             }
+            if let Some(index) = spatial_index.as_deref() {
+                index.mark_moved(rider_entity);
+            }
         }
     }
 
@@ -278,13 +397,15 @@ pub fn movement_system(
             EventKind::TripCompleted
         };
         clock.schedule_in_secs(1, kind, Some(EventSubject::Trip(trip_entity)));
+        commands.entity(trip_entity).remove::<NextMoveStep>();
     } else {
         let step_ms = travel_time_ms(step_distance_km, speed_kmh);
-        clock.schedule_in(
+        let move_step = clock.schedule_in(
             step_ms,
             EventKind::MoveStep,
             Some(EventSubject::Trip(trip_entity)),
         );
+        commands.entity(trip_entity).insert(NextMoveStep(move_step));
     }
 }
 
@@ -1,18 +1,18 @@
 use bevy_ecs::prelude::{Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
-use crate::ecs::{Rider, Trip, TripEnRoute, TripLiveData, TripTiming, Waiting};
+use crate::ecs::{PickupTimeout, Rider, Trip, TripEnRoute, TripLiveData, TripTiming, Waiting};
 use crate::scenario::RiderCancelConfig;
 
 /// Pure patience check: if the projected pickup time exceeds the rider's wait
-/// deadline, schedules a `RiderCancel` event at delta 0 so `rider_cancel_system`
-/// handles all cancellation mutations. No direct state changes here.
+/// deadline, moves the rider's pending `RiderCancel` (its `PickupTimeout`) to now so
+/// `rider_cancel_system` handles all cancellation mutations. No direct state changes here.
 pub fn pickup_eta_updated_system(
     event: Res<CurrentEvent>,
     mut clock: ResMut<SimulationClock>,
     cancel_config: Option<Res<RiderCancelConfig>>,
     trips: Query<(&Trip, &TripTiming, &TripLiveData, Option<&TripEnRoute>)>,
-    riders: Query<(&Rider, Option<&Waiting>)>,
+    riders: Query<(&Rider, Option<&Waiting>, Option<&PickupTimeout>)>,
 ) {
     if event.0.kind != EventKind::PickupEtaUpdated {
         return;
@@ -31,7 +31,7 @@ pub fn pickup_eta_updated_system(
 
     let rider_entity = trip.rider;
     let driver_entity = trip.driver;
-    let Ok((rider, waiting)) = riders.get(rider_entity) else {
+    let Ok((rider, waiting, pickup_timeout)) = riders.get(rider_entity) else {
         return;
     };
     if waiting.is_none() {
@@ -60,6 +60,11 @@ pub fn pickup_eta_updated_system(
     }
 
     // Patience exceeded — delegate cancellation to rider_cancel_system
+    if let Some(timeout) = pickup_timeout {
+        if clock.reschedule_in(timeout.0, 0) {
+            return;
+        }
+    }
     clock.schedule_in(
         0,
         EventKind::RiderCancel,
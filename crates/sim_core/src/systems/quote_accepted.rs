@@ -3,8 +3,10 @@ use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
-use crate::ecs::{Browsing, Rider, RiderQuote, Waiting};
+use crate::ecs::{Browsing, DeferredDispatch, DispatchNotBefore, PickupTimeout, Rider, RiderQuote};
 use crate::scenario::{BatchMatchingConfig, RiderCancelConfig};
+use crate::state_machine::StateTransitionCommands;
+use crate::telemetry::RiderState;
 
 pub fn quote_accepted_system(
     mut clock: ResMut<SimulationClock>,
@@ -12,7 +14,13 @@ pub fn quote_accepted_system(
     mut commands: Commands,
     batch_config: Option<Res<BatchMatchingConfig>>,
     cancel_config: Option<Res<RiderCancelConfig>>,
-    mut riders: Query<(Entity, &mut Rider, &RiderQuote, Option<&Browsing>)>,
+    mut riders: Query<(
+        Entity,
+        &mut Rider,
+        &RiderQuote,
+        Option<&DeferredDispatch>,
+        Option<&Browsing>,
+    )>,
 ) {
     if event.0.kind != EventKind::QuoteAccepted {
         return;
@@ -21,28 +29,39 @@ pub fn quote_accepted_system(
     let Some(EventSubject::Rider(rider_entity)) = event.0.subject else {
         return;
     };
-    let Ok((_, mut rider, quote, browsing)) = riders.get_mut(rider_entity) else {
+    let Ok((_, mut rider, quote, deferred, browsing)) = riders.get_mut(rider_entity) else {
         return;
     };
+    // Wait-and-save: hold the rider out of matching for the deferred dispatch window
+    let dispatch_delay_ms = deferred.map(|d| d.delay_ms).unwrap_or(0);
     if browsing.is_some() {
         rider.accepted_fare = Some(quote.fare);
         commands
             .entity(rider_entity)
-            .remove::<Browsing>()
-            .insert(Waiting);
+            .transition_to(RiderState::Waiting);
         commands.entity(rider_entity).remove::<RiderQuote>();
+        if dispatch_delay_ms > 0 {
+            commands
+                .entity(rider_entity)
+                .insert(DispatchNotBefore(clock.now() + dispatch_delay_ms));
+        }
     }
 
     // Only schedule per-rider TryMatch when batch matching is disabled
     let batch_enabled = batch_config.as_deref().is_some_and(|c| c.enabled);
     if !batch_enabled {
-        clock.schedule_in_secs(
-            1,
+        clock.schedule_in(
+            dispatch_delay_ms + 1000,
             EventKind::TryMatch,
             Some(EventSubject::Rider(rider_entity)),
         );
     }
 
+    // Only a rider who just started waiting gets a pickup timeout
+    if browsing.is_none() {
+        return;
+    }
+
     let config = cancel_config.as_deref().copied().unwrap_or_default();
     let min_wait_secs = config.min_wait_secs;
     let max_wait_secs = config.max_wait_secs.max(config.min_wait_secs);
@@ -53,9 +72,11 @@ pub fn quote_accepted_system(
     let mut rng = StdRng::seed_from_u64(seed);
     let wait_secs = rng.gen_range(min_wait_secs..=max_wait_secs);
 
-    clock.schedule_in_secs(
-        wait_secs,
+    // Wait-and-save riders agreed to the window, so their patience starts after it
+    let timeout = clock.schedule_in(
+        wait_secs * 1000 + dispatch_delay_ms,
         EventKind::RiderCancel,
         Some(EventSubject::Rider(rider_entity)),
     );
+    commands.entity(rider_entity).insert(PickupTimeout(timeout));
 }
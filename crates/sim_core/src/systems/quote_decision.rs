@@ -1,19 +1,40 @@
 //! QuoteDecision system: rider stochastically accepts or rejects the shown quote.
+//!
+//! Acceptance follows the logit model in [`RiderQuoteConfig::accept_probability_for`]: price is
+//! compared to a rider-specific reference price (base fare scaled by a seeded per-rider factor).
+//! A trained [`ConversionModel`] resource, when present, replaces the logit.
 
-use bevy_ecs::prelude::{Entity, Query, Res, ResMut};
+use bevy_ecs::prelude::{Entity, Local, Query, Res, ResMut};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
-use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
-use crate::ecs::{Browsing, Rider, RiderQuote};
+use crate::clock::{
+    day_of_week, hour_of_day, CurrentEvent, EventKind, EventSubject, SimulationClock,
+};
+use crate::decision_models::{ConversionFeatures, ConversionModel};
+use crate::ecs::{Browsing, Position, QuoteExpiresAt, Rider, RiderQuote};
+use crate::pricing::{calculate_trip_fare_with_config, PricingConfig};
 use crate::scenario::RiderQuoteConfig;
-use crate::telemetry::RiderAbandonmentReason;
+use crate::spatial::distance_km_between_cells;
+use crate::telemetry::{RiderAbandonmentReason, SimTelemetry};
 
+#[allow(clippy::type_complexity)]
 pub fn quote_decision_system(
     mut clock: ResMut<SimulationClock>,
     event: Res<CurrentEvent>,
     quote_config: Option<Res<RiderQuoteConfig>>,
-    mut riders: Query<(Entity, &mut Rider, &RiderQuote, Option<&Browsing>)>,
+    default_quote_config: Local<RiderQuoteConfig>,
+    pricing_config: Option<Res<PricingConfig>>,
+    conversion_model: Option<Res<ConversionModel>>,
+    mut telemetry: Option<ResMut<SimTelemetry>>,
+    mut riders: Query<(
+        Entity,
+        &mut Rider,
+        &RiderQuote,
+        Option<&Position>,
+        Option<&QuoteExpiresAt>,
+        Option<&Browsing>,
+    )>,
 ) {
     if event.0.kind != EventKind::QuoteDecision {
         return;
@@ -23,14 +44,29 @@ pub fn quote_decision_system(
         return;
     };
 
-    let Ok((_, mut rider, quote, browsing)) = riders.get_mut(rider_entity) else {
+    let Ok((_, mut rider, quote, position, expires_at, browsing)) = riders.get_mut(rider_entity)
+    else {
         return;
     };
     if browsing.is_none() {
         return;
     }
 
-    let config = quote_config.as_deref().copied().unwrap_or_default();
+    if expires_at.is_some_and(|expires_at| clock.now() > expires_at.0) {
+        // Quote expired while the rider was deciding: re-request at current surge
+        if let Some(telemetry) = telemetry.as_deref_mut() {
+            telemetry.quotes_expired_total = telemetry.quotes_expired_total.saturating_add(1);
+        }
+        rider.last_rejection_reason = Some(RiderAbandonmentReason::QuoteExpired);
+        clock.schedule_in_secs(
+            0,
+            EventKind::QuoteRejected,
+            Some(EventSubject::Rider(rider_entity)),
+        );
+        return;
+    }
+
+    let config = quote_config.as_deref().unwrap_or(&default_quote_config);
     let over_price = quote.fare > config.max_willingness_to_pay;
     let over_eta = quote.eta_ms > config.max_acceptable_eta_ms;
     if over_price || over_eta {
@@ -49,7 +85,49 @@ pub fn quote_decision_system(
     }
     let seed = config.seed.wrapping_add(rider_entity.index() as u64);
     let mut rng = StdRng::seed_from_u64(seed);
-    let accept = rng.gen::<f64>() < config.accept_probability;
+    let draw = rng.gen::<f64>();
+
+    // Rider-specific reference price: base (non-surge) fare scaled by a per-rider factor
+    let base_fare = match (position, rider.destination) {
+        (Some(position), Some(destination)) => calculate_trip_fare_with_config(
+            position.0,
+            destination,
+            pricing_config.as_deref().copied().unwrap_or_default(),
+        ),
+        _ => quote.fare,
+    };
+    let spread = config.reference_price_spread.max(0.0);
+    let reference_factor = if spread > 0.0 {
+        1.0 + rng.gen_range(-spread..=spread)
+    } else {
+        1.0
+    };
+    let reference_fare = base_fare * reference_factor;
+    let accept_probability = match conversion_model.as_deref() {
+        Some(model) => {
+            let local_ms = clock.sim_to_local_ms(clock.now());
+            model.accept_probability(&ConversionFeatures {
+                fare: quote.fare,
+                eta_ms: quote.eta_ms,
+                distance_km: match (position, rider.destination) {
+                    (Some(position), Some(destination)) => {
+                        distance_km_between_cells(position.0, destination)
+                    }
+                    _ => 0.0,
+                },
+                local_hour: hour_of_day(local_ms) as u8,
+                local_weekday: day_of_week(local_ms) as u8,
+                quote_index: rider.quote_attempts(),
+                relative_price: if reference_fare > 0.0 {
+                    quote.fare / reference_fare - 1.0
+                } else {
+                    0.0
+                },
+            })
+        }
+        None => config.accept_probability_for(quote.fare, reference_fare, quote.eta_ms),
+    };
+    let accept = draw < accept_probability;
 
     if accept {
         clock.schedule_in_secs(
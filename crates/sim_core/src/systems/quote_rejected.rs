@@ -1,6 +1,6 @@
-//! QuoteRejected system: rider rejected the quote; retry with new quote or give up.
+//! QuoteRejected system: rider rejected the quote (or let it expire); retry with new quote or give up.
 
-use bevy_ecs::prelude::{Commands, Query, Res, ResMut};
+use bevy_ecs::prelude::{Commands, Local, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use crate::ecs::{Browsing, Rider};
@@ -13,6 +13,7 @@ pub fn quote_rejected_system(
     mut commands: Commands,
     mut telemetry: ResMut<SimTelemetry>,
     quote_config: Option<Res<RiderQuoteConfig>>,
+    default_quote_config: Local<RiderQuoteConfig>,
     mut riders: Query<(&mut Rider, Option<&Browsing>)>,
 ) {
     if event.0.kind != EventKind::QuoteRejected {
@@ -30,10 +31,18 @@ pub fn quote_rejected_system(
         return;
     }
 
-    rider.quote_rejections += 1;
-    let config = quote_config.as_deref().copied().unwrap_or_default();
+    // An expired quote was never declined, so it has its own budget instead of counting
+    // toward quote_rejections
+    let attempts = if rider.last_rejection_reason == Some(RiderAbandonmentReason::QuoteExpired) {
+        rider.quote_expiries += 1;
+        rider.quote_expiries
+    } else {
+        rider.quote_rejections += 1;
+        rider.quote_rejections
+    };
+    let config = quote_config.as_deref().unwrap_or(&default_quote_config);
 
-    if rider.quote_rejections <= config.max_quote_rejections {
+    if attempts <= config.max_quote_rejections {
         clock.schedule_in_secs(
             config.re_quote_delay_secs,
             EventKind::ShowQuote,
@@ -44,6 +53,11 @@ pub fn quote_rejected_system(
         telemetry.riders_abandoned_quote_total =
             telemetry.riders_abandoned_quote_total.saturating_add(1);
 
+        let reason = rider
+            .last_rejection_reason
+            .unwrap_or(RiderAbandonmentReason::QuoteStochasticRejection);
+        telemetry.record_funnel_exit(rider_entity, Some(reason), clock.now());
+
         // Track breakdown by reason
         match rider.last_rejection_reason {
             Some(RiderAbandonmentReason::QuotePriceTooHigh) => {
@@ -53,6 +67,10 @@ pub fn quote_rejected_system(
             Some(RiderAbandonmentReason::QuoteEtaTooLong) => {
                 telemetry.riders_abandoned_eta = telemetry.riders_abandoned_eta.saturating_add(1);
             }
+            Some(RiderAbandonmentReason::QuoteExpired) => {
+                telemetry.riders_abandoned_expired =
+                    telemetry.riders_abandoned_expired.saturating_add(1);
+            }
             Some(RiderAbandonmentReason::QuoteStochasticRejection) => {
                 telemetry.riders_abandoned_stochastic =
                     telemetry.riders_abandoned_stochastic.saturating_add(1);
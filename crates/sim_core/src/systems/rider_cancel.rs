@@ -2,19 +2,28 @@ use bevy_ecs::prelude::{Commands, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use crate::ecs::{
-    Driver, DriverStateCommands, EnRoute, Evaluating, Rider, Trip, TripCancelled, TripCompleted,
-    TripEnRoute, TripOnTrip, TripTiming, Waiting,
+    Driver, DriverStateCommands, EnRoute, Evaluating, NextMoveStep, Rider, Trip, TripEnRoute,
+    TripTiming,
 };
-use crate::telemetry::SimTelemetry;
+use crate::state_machine::StateTransitionCommands;
+use crate::telemetry::{RiderAbandonmentReason, SimTelemetry, TripState};
 
+/// Pickup timeout: the rider gives up waiting. The event is the rider's `PickupTimeout`, which
+/// is cancelled at pickup, so the rider is still waiting when it fires.
+#[allow(clippy::type_complexity)]
 pub fn rider_cancel_system(
     event: Res<CurrentEvent>,
-    clock: Res<SimulationClock>,
+    mut clock: ResMut<SimulationClock>,
     mut commands: Commands,
     mut telemetry: ResMut<SimTelemetry>,
-    mut riders: Query<(&mut Rider, Option<&Waiting>)>,
+    mut riders: Query<&mut Rider>,
     mut drivers: Query<(&mut Driver, Option<&EnRoute>, Option<&Evaluating>)>,
-    mut trips: Query<(&mut Trip, &mut TripTiming, Option<&TripEnRoute>)>,
+    mut trips: Query<(
+        &mut Trip,
+        &mut TripTiming,
+        Option<&TripEnRoute>,
+        Option<&NextMoveStep>,
+    )>,
 ) {
     if event.0.kind != EventKind::RiderCancel {
         return;
@@ -23,25 +32,22 @@ pub fn rider_cancel_system(
     let Some(EventSubject::Rider(rider_entity)) = event.0.subject else {
         return;
     };
-    let Ok((mut rider, waiting)) = riders.get_mut(rider_entity) else {
+    let Ok(mut rider) = riders.get_mut(rider_entity) else {
         return;
     };
-    if waiting.is_none() {
-        return;
-    }
 
     if let Some(driver_entity) = rider.matched_driver {
         // Use assigned_trip for O(1) trip lookup instead of scanning all trips
         if let Some(trip_entity) = rider.assigned_trip {
-            if let Ok((_trip, mut timing, en_route)) = trips.get_mut(trip_entity) {
+            if let Ok((_trip, mut timing, en_route, move_step)) = trips.get_mut(trip_entity) {
+                if let Some(move_step) = move_step {
+                    clock.cancel(move_step.0);
+                }
                 if en_route.is_some() {
                     timing.cancelled_at = Some(clock.now());
                     commands
                         .entity(trip_entity)
-                        .remove::<TripEnRoute>()
-                        .remove::<TripOnTrip>()
-                        .remove::<TripCompleted>()
-                        .insert(TripCancelled);
+                        .transition_to(TripState::Cancelled);
                 }
             }
         }
@@ -64,5 +70,10 @@ pub fn rider_cancel_system(
     // Track pickup timeout cancellation
     telemetry.riders_cancelled_pickup_timeout =
         telemetry.riders_cancelled_pickup_timeout.saturating_add(1);
+    telemetry.record_funnel_exit(
+        rider_entity,
+        Some(RiderAbandonmentReason::PickupTimeout),
+        clock.now(),
+    );
     commands.entity(rider_entity).despawn();
 }
@@ -1,31 +1,60 @@
-//! ShowQuote system: compute fare + ETA for a browsing rider and schedule quote decision.
+//! ShowQuote system: compute fare + ETA for a browsing rider and schedule quote decision
+//! (external riders decide through [`crate::sandbox`] instead).
 
-use bevy_ecs::prelude::{Commands, Entity, Query, Res, ResMut};
+use bevy_ecs::prelude::{Commands, Entity, Local, Query, Res, ResMut, With};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
-use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
-use crate::ecs::{Browsing, Driver, Idle, Position, Rider, RiderQuote, Waiting};
+use crate::clock::{
+    day_of_week, hour_of_day, CurrentEvent, EventKind, EventSubject, SimulationClock,
+};
+use crate::ecs::{
+    Browsing, DeferredDispatch, Driver, ExternalRider, Idle, Position, QuoteExpiresAt, Rider,
+    RiderQuote, VehicleClassChoice, Waiting,
+};
+use crate::pricing::guardrails::SurgeRateLimiter;
+use crate::pricing::surge::{
+    SupplyDemandRatioSurge, SurgeContext, SurgeStrategy, SurgeStrategyResource,
+};
+use crate::pricing::tolls::TollZones;
+use crate::pricing::vehicle_classes::VehicleClassMenu;
 use crate::pricing::{calculate_trip_fare_with_config, PricingConfig};
+use crate::routing::RouteProviderResource;
+use crate::scenario::RiderQuoteConfig;
 use crate::spatial::{distance_km_between_cells, grid_disk_cached, SpatialIndex};
+use crate::spawner::SupplyElasticity;
+use crate::telemetry::{QuoteFeatureLog, QuoteFeatureRecord, SimTelemetry};
 
 /// Default ETA in ms when no idle drivers are available (5 minutes).
-const DEFAULT_ETA_MS: u64 = 300 * 1000;
+pub(crate) const DEFAULT_ETA_MS: u64 = 300 * 1000;
 /// Assumed speed for ETA from driver to rider (km/h).
-const ETA_SPEED_KMH: f64 = 40.0;
+pub(crate) const ETA_SPEED_KMH: f64 = 40.0;
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn show_quote_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
     event: Res<CurrentEvent>,
     pricing_config: Res<PricingConfig>,
+    surge_strategy: Option<ResMut<SurgeStrategyResource>>,
+    rate_limiter: Option<ResMut<SurgeRateLimiter>>,
+    (toll_zones, route_provider): (Option<Res<TollZones>>, Option<Res<RouteProviderResource>>),
     spatial_index: Option<Res<SpatialIndex>>,
+    (quote_config, default_quote_config): (Option<Res<RiderQuoteConfig>>, Local<RiderQuoteConfig>),
+    vehicle_classes: Option<Res<VehicleClassMenu>>,
+    supply_elasticity: Option<ResMut<SupplyElasticity>>,
+    mut telemetry: Option<ResMut<SimTelemetry>>,
+    quote_features: Option<ResMut<QuoteFeatureLog>>,
     riders: Query<(
         Entity,
         &Rider,
         &Position,
+        Option<&RiderQuote>,
         Option<&Browsing>,
         Option<&Waiting>,
     )>,
     drivers: Query<(&Driver, &Position, Option<&Idle>)>,
+    external_riders: Query<(), With<ExternalRider>>,
 ) {
     if event.0.kind != EventKind::ShowQuote {
         return;
@@ -35,7 +64,8 @@ pub fn show_quote_system(
         return;
     };
 
-    let Ok((_, rider, position, browsing, _waiting)) = riders.get(rider_entity) else {
+    let Ok((_, rider, position, previous_quote, browsing, _waiting)) = riders.get(rider_entity)
+    else {
         return;
     };
     if browsing.is_none() {
@@ -48,11 +78,28 @@ pub fn show_quote_system(
     let pickup = position.0;
     let base_fare = calculate_trip_fare_with_config(pickup, dropoff, *pricing_config);
 
-    let surge_multiplier = if pricing_config.surge_enabled && pricing_config.surge_radius_k > 0 {
+    let eta_ms = drivers
+        .iter()
+        .filter_map(|(_driver, pos, idle)| {
+            if idle.is_some() {
+                let distance_km = distance_km_between_cells(pos.0, pickup);
+                let hours = distance_km / ETA_SPEED_KMH;
+                Some((hours * 3_600_000.0) as u64)
+            } else {
+                None
+            }
+        })
+        .min()
+        .unwrap_or(DEFAULT_ETA_MS)
+        .max(crate::clock::ONE_SEC_MS);
+
+    let surge_active = pricing_config.surge_enabled && pricing_config.surge_radius_k > 0;
+    // Cluster demand and supply feed the surge strategy and the quote feature log
+    let cluster = (surge_active || quote_features.is_some()).then(|| {
         let cluster_cells = grid_disk_cached(pickup, pricing_config.surge_radius_k);
 
         // Use spatial index if available, otherwise fall back to full scan
-        let (demand, supply) = if let Some(index) = spatial_index.as_deref() {
+        if let Some(index) = spatial_index.as_deref() {
             // Get entities in cluster cells from spatial index
             let rider_entities = index.get_riders_in_cells(&cluster_cells);
             let driver_entities = index.get_drivers_in_cells(&cluster_cells);
@@ -61,7 +108,9 @@ pub fn show_quote_system(
             let demand = rider_entities
                 .iter()
                 .filter_map(|&entity| riders.get(entity).ok())
-                .filter(|(_, _r, _pos, browsing, waiting)| browsing.is_some() || waiting.is_some())
+                .filter(|(_, _r, _pos, _quote, browsing, waiting)| {
+                    browsing.is_some() || waiting.is_some()
+                })
                 .count();
 
             let supply = driver_entities
@@ -75,7 +124,7 @@ pub fn show_quote_system(
             // Fallback to full scan if spatial index not available
             let demand = riders
                 .iter()
-                .filter(|(_, _r, pos, browsing, waiting)| {
+                .filter(|(_, _r, pos, _quote, browsing, waiting)| {
                     (browsing.is_some() || waiting.is_some()) && cluster_cells.contains(&pos.0)
                 })
                 .count();
@@ -84,43 +133,151 @@ pub fn show_quote_system(
                 .filter(|(_d, pos, idle)| idle.is_some() && cluster_cells.contains(&pos.0))
                 .count();
             (demand, supply)
-        };
+        }
+    });
 
-        if demand > supply && supply > 0 {
-            let raw = 1.0 + (demand - supply) as f64 / supply as f64;
-            raw.min(pricing_config.surge_max_multiplier)
-        } else if demand > supply && supply == 0 {
-            pricing_config.surge_max_multiplier
-        } else {
-            1.0
+    let surge_multiplier = if let Some((demand, supply)) = cluster.filter(|_| surge_active) {
+        let ctx = SurgeContext {
+            demand,
+            supply,
+            eta_ms,
+            now_ms: clock.now(),
+            epoch_ms: clock.local_epoch_ms(),
+            max_multiplier: pricing_config.surge_max_multiplier,
+        };
+        match surge_strategy {
+            Some(mut strategy) => strategy.0.multiplier(&ctx),
+            None => SupplyDemandRatioSurge.multiplier(&ctx),
         }
     } else {
         1.0
     };
 
-    let fare = base_fare * surge_multiplier;
+    // Guardrail: limit how fast the multiplier may move in this pickup cell
+    let surge_multiplier = match (pricing_config.surge_rate_limit, rate_limiter) {
+        (Some(limit), Some(mut limiter)) => {
+            limiter.apply(pickup, surge_multiplier, clock.now(), &limit)
+        }
+        _ => surge_multiplier,
+    };
+    if let Some(mut supply) = supply_elasticity {
+        supply.observe_surge(clock.now(), surge_multiplier);
+    }
 
-    let eta_ms = drivers
-        .iter()
-        .filter_map(|(_driver, pos, idle)| {
-            if idle.is_some() {
-                let distance_km = distance_km_between_cells(pos.0, pickup);
-                let hours = distance_km / ETA_SPEED_KMH;
-                Some((hours * 3_600_000.0) as u64)
-            } else {
-                None
-            }
+    // Tolls are pass-through: added after surge so they are never multiplied
+    let tolls = toll_zones
+        .as_deref()
+        .map(|zones| {
+            zones.toll_for_trip(
+                pickup,
+                dropoff,
+                route_provider.as_deref().map(|p| p.0.as_ref()),
+            )
         })
-        .min()
-        .unwrap_or(DEFAULT_ETA_MS)
-        .max(crate::clock::ONE_SEC_MS);
+        .unwrap_or(0.0);
+
+    let quote_config = quote_config.as_deref().unwrap_or(&default_quote_config);
+    let mut fare = base_fare * surge_multiplier + tolls;
+    let mut eta_ms = eta_ms;
+    let mut dispatch_delay_ms = 0;
+    let mut class_choice = None;
+
+    // Comparison shopping: quote every vehicle class and let the rider pick one
+    if let Some(menu) = vehicle_classes.as_deref().filter(|menu| !menu.is_empty()) {
+        let quotes = menu.quotes(base_fare * surge_multiplier, tolls, eta_ms);
+        let seed = menu
+            .seed
+            .wrapping_add(rider_entity.index() as u64)
+            .wrapping_add((rider.quote_attempts() as u64) << 32);
+        let mut rng = StdRng::seed_from_u64(seed);
+        if let Some(chosen) = menu.choose(&quotes, &mut rng) {
+            fare = quotes[chosen].fare;
+            eta_ms = quotes[chosen].eta_ms;
+            dispatch_delay_ms = menu.classes[chosen].dispatch_delay_secs * 1000;
+            class_choice = Some(VehicleClassChoice {
+                chosen: menu.classes[chosen].name.clone(),
+                offered: menu.classes[0].name.clone(),
+            });
+            if let Some(telemetry) = telemetry.as_deref_mut() {
+                *telemetry
+                    .vehicle_class_choices
+                    .entry(menu.classes[chosen].name.clone())
+                    .or_insert(0) += 1;
+                if chosen != 0 {
+                    telemetry.vehicle_class_substitutions =
+                        telemetry.vehicle_class_substitutions.saturating_add(1);
+                }
+            }
+        }
+    }
+
+    if let (Some(mut log), Some((demand, supply))) = (quote_features, cluster) {
+        let local_ms = clock.sim_to_local_ms(clock.now());
+        log.records.push(QuoteFeatureRecord {
+            rider: rider_entity,
+            quoted_at: clock.now(),
+            local_hour: hour_of_day(local_ms) as u8,
+            local_weekday: day_of_week(local_ms) as u8,
+            origin: pickup,
+            destination: dropoff,
+            distance_km: distance_km_between_cells(pickup, dropoff),
+            surge_multiplier,
+            local_demand: demand,
+            local_supply: supply,
+            fare,
+            eta_ms,
+        });
+    }
 
-    commands
-        .entity(rider_entity)
-        .insert(RiderQuote { fare, eta_ms });
+    if let Some(telemetry) = telemetry.as_deref_mut() {
+        telemetry
+            .cell_hours
+            .record_quote(pickup, clock.now(), surge_multiplier);
+    }
+
+    if let (Some(previous), Some(telemetry)) = (previous_quote, telemetry.as_deref_mut()) {
+        telemetry.requotes_total = telemetry.requotes_total.saturating_add(1);
+        telemetry.requote_fare_delta_total += fare - previous.fare;
+    }
+
+    let mut rider_commands = commands.entity(rider_entity);
+    rider_commands.insert(RiderQuote { fare, eta_ms });
+    if dispatch_delay_ms > 0 {
+        rider_commands.insert(DeferredDispatch {
+            delay_ms: dispatch_delay_ms,
+        });
+    } else {
+        rider_commands.remove::<DeferredDispatch>();
+    }
+    if let Some(choice) = class_choice {
+        rider_commands.insert(choice);
+    } else {
+        rider_commands.remove::<VehicleClassChoice>();
+    }
+    if let Some(ttl_secs) = quote_config.quote_ttl_secs {
+        rider_commands.insert(QuoteExpiresAt(
+            clock.now().saturating_add(ttl_secs.saturating_mul(1000)),
+        ));
+    }
 
+    // External riders accept or decline through the sandbox API
+    if external_riders.contains(rider_entity) {
+        return;
+    }
+
+    let decision_delay_secs = if quote_config.decision_delay_jitter_secs > 0 {
+        let seed = quote_config
+            .seed
+            .wrapping_add(rider_entity.index() as u64)
+            .wrapping_add((rider.quote_attempts() as u64) << 32);
+        let mut rng = StdRng::seed_from_u64(seed);
+        quote_config.decision_delay_secs
+            + rng.gen_range(0..=quote_config.decision_delay_jitter_secs)
+    } else {
+        quote_config.decision_delay_secs
+    };
     clock.schedule_in_secs(
-        1,
+        decision_delay_secs,
         EventKind::QuoteDecision,
         Some(EventSubject::Rider(rider_entity)),
     );
@@ -1,110 +1,62 @@
 //! Spatial index update system: maintains H3 cell → entity mappings for efficient spatial queries.
 //!
 //! Updates the spatial index when entities are spawned, despawned, or move to new cells.
+//! Moves are applied from the entities marked via `SpatialIndex::mark_moved` rather than a
+//! `Changed<Position>` scan, which would visit every rider and driver on every event.
 
-use bevy_ecs::prelude::{Added, Changed, Entity, Query, RemovedComponents, ResMut, With};
+use bevy_ecs::prelude::{Added, Entity, Has, Query, RemovedComponents, ResMut, With};
 
 use crate::ecs::{Driver, Position, Rider};
 use crate::spatial::SpatialIndex;
 
-/// Updates the spatial index when riders are added, removed, or move.
-/// Early returns if no changes detected to minimize overhead.
+/// Applies marked moves, new riders/drivers and removals to the spatial index.
+///
+/// New entities are found with an `Added` scan only when the number of riders or drivers in
+/// the world differs from the index after moves and removals, i.e. on events that spawned one.
 /// Only runs if SpatialIndex resource exists (optional for small scenarios).
-pub fn update_spatial_index_riders_system(
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_spatial_index_system(
     spatial_index: Option<ResMut<SpatialIndex>>,
+    positions: Query<(&Position, Has<Rider>, Has<Driver>)>,
+    riders: Query<(), With<Rider>>,
+    drivers: Query<(), With<Driver>>,
     added_riders: Query<(Entity, &Position), Added<Rider>>,
-    changed_positions: Query<(Entity, &Position), (Changed<Position>, With<Rider>)>,
+    added_drivers: Query<(Entity, &Position), Added<Driver>>,
     mut removed_riders: RemovedComponents<Rider>,
+    mut removed_drivers: RemovedComponents<Driver>,
 ) {
     let Some(mut spatial_index) = spatial_index else {
         return; // Spatial index not enabled for this scenario
     };
-    // Early return if no changes (avoid processing overhead when not needed)
-    // Note: is_empty() is cheap for Changed/Added queries (just checks change tracking)
-    let has_added = !added_riders.is_empty();
-    let has_changed = !changed_positions.is_empty();
-    let has_removed = !removed_riders.is_empty();
 
-    if !has_added && !has_changed && !has_removed {
-        return;
+    for entity in removed_riders.read() {
+        spatial_index.remove_rider(entity);
+    }
+    for entity in removed_drivers.read() {
+        spatial_index.remove_driver(entity);
     }
 
-    // Handle newly added riders
-    if has_added {
-        for (entity, position) in added_riders.iter() {
+    for entity in spatial_index.take_moved() {
+        let Ok((position, is_rider, is_driver)) = positions.get(entity) else {
+            continue; // Despawned since it moved
+        };
+        if is_rider && spatial_index.get_rider_cell(entity).is_some() {
             spatial_index.insert_rider(entity, position.0);
         }
-    }
-
-    // Handle position changes
-    if has_changed {
-        for (entity, position) in changed_positions.iter() {
-            if let Some(old_cell) = spatial_index.get_rider_cell(entity) {
-                if old_cell != position.0 {
-                    spatial_index.update_rider_position(entity, old_cell, position.0);
-                }
-            } else {
-                // Entity not in index yet (shouldn't happen, but handle gracefully)
-                spatial_index.insert_rider(entity, position.0);
-            }
+        if is_driver && spatial_index.get_driver_cell(entity).is_some() {
+            spatial_index.insert_driver(entity, position.0);
         }
     }
 
-    // Handle removed riders
-    if has_removed {
-        for entity in removed_riders.read() {
-            spatial_index.remove_rider(entity);
+    // Query length is computed from archetype sizes, without visiting entities
+    if riders.iter().len() != spatial_index.rider_count() {
+        for (entity, position) in added_riders.iter() {
+            spatial_index.insert_rider(entity, position.0);
         }
     }
-}
-
-/// Updates the spatial index when drivers are added, removed, or move.
-/// Early returns if no changes detected to minimize overhead.
-/// Only runs if SpatialIndex resource exists (optional for small scenarios).
-pub fn update_spatial_index_drivers_system(
-    spatial_index: Option<ResMut<SpatialIndex>>,
-    added_drivers: Query<(Entity, &Position), Added<Driver>>,
-    changed_positions: Query<(Entity, &Position), (Changed<Position>, With<Driver>)>,
-    mut removed_drivers: RemovedComponents<Driver>,
-) {
-    let Some(mut spatial_index) = spatial_index else {
-        return; // Spatial index not enabled for this scenario
-    };
-    // Early return if no changes (avoid processing overhead when not needed)
-    // Note: is_empty() is cheap for Changed/Added queries (just checks change tracking)
-    let has_added = !added_drivers.is_empty();
-    let has_changed = !changed_positions.is_empty();
-    let has_removed = !removed_drivers.is_empty();
-
-    if !has_added && !has_changed && !has_removed {
-        return;
-    }
-
-    // Handle newly added drivers
-    if has_added {
+    if drivers.iter().len() != spatial_index.driver_count() {
         for (entity, position) in added_drivers.iter() {
             spatial_index.insert_driver(entity, position.0);
         }
     }
-
-    // Handle position changes
-    if has_changed {
-        for (entity, position) in changed_positions.iter() {
-            if let Some(old_cell) = spatial_index.get_driver_cell(entity) {
-                if old_cell != position.0 {
-                    spatial_index.update_driver_position(entity, old_cell, position.0);
-                }
-            } else {
-                // Entity not in index yet (shouldn't happen, but handle gracefully)
-                spatial_index.insert_driver(entity, position.0);
-            }
-        }
-    }
-
-    // Handle removed drivers
-    if has_removed {
-        for entity in removed_drivers.read() {
-            spatial_index.remove_driver(entity);
-        }
-    }
 }
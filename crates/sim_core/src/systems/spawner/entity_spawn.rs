@@ -1,15 +1,21 @@
 use bevy_ecs::prelude::Commands;
 use rand::Rng;
 
-use crate::clock::{EventKind, EventSubject, SimulationClock, ONE_HOUR_MS};
+use crate::clock::{
+    hour_of_day, EventKind, EventSubject, SimulationClock, ONE_HOUR_MS, ONE_WEEK_MS,
+};
 use crate::ecs::{
-    Browsing, Driver, DriverEarnings, DriverFatigue, GeoPosition, Idle, Position, Rider,
+    Browsing, Driver, DriverEarnings, DriverFatigue, DriverVehicle, GeoPosition, Idle, Position,
+    Rider,
 };
 use crate::scenario::random_destination;
 use crate::spatial::GeoIndex;
-use crate::spawner::{DriverSpawner, RiderSpawner, SpawnWeighting};
+use crate::spawner::{
+    random_cell_in_bounds, DemandEvents, DriverSpawner, RiderSpawner, SpawnWeighting,
+};
+use crate::warm_start::CarriedDriver;
 
-use super::{create_spawn_rng, resolve_spawn_location, MaybeOsrmSpawnMetrics};
+use super::{create_spawn_rng, resolve_spawn_location, MaybeOsrmSpawnMetrics, SpawnLocation};
 
 pub(super) fn spawn_rider(
     commands: &mut Commands,
@@ -20,46 +26,132 @@ pub(super) fn spawn_rider(
     osrm_metrics: MaybeOsrmSpawnMetrics<'_>,
 ) -> bevy_ecs::prelude::Entity {
     let mut rng = create_spawn_rng(spawner.config.seed, spawner.spawned_count());
+    let hour = hour_of_day(clock.sim_to_local_ms(current_time_ms));
 
-    #[cfg(feature = "osrm")]
-    let spawn_location = resolve_spawn_location(
-        &mut rng,
-        weighting,
-        |w, rng| w.sample_rider_cell(rng),
-        spawner.config.lat_min,
-        spawner.config.lat_max,
-        spawner.config.lng_min,
-        spawner.config.lng_max,
-        spawner.osrm_spawn_client(),
-        osrm_metrics,
-    );
-    #[cfg(not(feature = "osrm"))]
-    let spawn_location = resolve_spawn_location(
-        &mut rng,
-        weighting,
-        |w, rng| w.sample_rider_cell(rng),
-        spawner.config.lat_min,
-        spawner.config.lat_max,
-        spawner.config.lng_min,
-        spawner.config.lng_max,
-        (),
-        osrm_metrics,
-    );
+    let od_trip = spawner.config.od_matrix.as_ref().and_then(|matrix| {
+        matrix.sample_trip(
+            hour,
+            &mut rng,
+            spawner.config.lat_min,
+            spawner.config.lat_max,
+            spawner.config.lng_min,
+            spawner.config.lng_max,
+        )
+    });
+
+    let spawn_location = match od_trip {
+        Some((pickup, _)) => SpawnLocation {
+            cell: pickup,
+            geo: pickup.into(),
+        },
+        None => {
+            #[cfg(feature = "osrm")]
+            {
+                resolve_spawn_location(
+                    &mut rng,
+                    weighting,
+                    |w, rng| w.sample_rider_cell_at(hour, rng),
+                    spawner.config.lat_min,
+                    spawner.config.lat_max,
+                    spawner.config.lng_min,
+                    spawner.config.lng_max,
+                    spawner.osrm_spawn_client(),
+                    osrm_metrics,
+                )
+            }
+            #[cfg(not(feature = "osrm"))]
+            {
+                resolve_spawn_location(
+                    &mut rng,
+                    weighting,
+                    |w, rng| w.sample_rider_cell_at(hour, rng),
+                    spawner.config.lat_min,
+                    spawner.config.lat_max,
+                    spawner.config.lng_min,
+                    spawner.config.lng_max,
+                    (),
+                    osrm_metrics,
+                )
+            }
+        }
+    };
     let position = spawn_location.cell;
 
-    let geo = GeoIndex::default();
-    let destination = random_destination(
-        &mut rng,
-        position,
-        &geo,
-        spawner.config.min_trip_cells,
-        spawner.config.max_trip_cells,
-        spawner.config.lat_min,
-        spawner.config.lat_max,
-        spawner.config.lng_min,
-        spawner.config.lng_max,
+    let destination = match od_trip {
+        Some((_, dropoff)) => dropoff,
+        None => {
+            let geo = GeoIndex::default();
+            random_destination(
+                &mut rng,
+                position,
+                &geo,
+                spawner.config.min_trip_cells,
+                spawner.config.max_trip_cells,
+                spawner.config.lat_min,
+                spawner.config.lat_max,
+                spawner.config.lng_min,
+                spawner.config.lng_max,
+            )
+        }
+    };
+
+    insert_rider(
+        commands,
+        clock,
+        spawn_location,
+        destination,
+        current_time_ms,
+    )
+}
+
+/// Spawn one rider at a special-event venue (see [`DemandEvents`]); the dropoff is a random
+/// cell within the rider spawner bounds. Returns None when no arrival is due.
+pub(super) fn spawn_event_rider(
+    commands: &mut Commands,
+    clock: &mut SimulationClock,
+    demand_events: &mut DemandEvents,
+    spawner: &RiderSpawner,
+    current_time_ms: u64,
+) -> Option<(usize, bevy_ecs::prelude::Entity)> {
+    let mut rng = create_spawn_rng(demand_events.rider_seed, demand_events.spawned_count());
+    let index = demand_events.pop_due(current_time_ms)?;
+    let venue = demand_events.events[index].venue_cell()?;
+
+    const MAX_ATTEMPTS: usize = 16;
+    let destination = (0..MAX_ATTEMPTS)
+        .filter_map(|_| {
+            random_cell_in_bounds(
+                &mut rng,
+                spawner.config.lat_min,
+                spawner.config.lat_max,
+                spawner.config.lng_min,
+                spawner.config.lng_max,
+            )
+            .ok()
+        })
+        .find(|cell| *cell != venue)?;
+
+    let spawn_location = SpawnLocation {
+        cell: venue,
+        geo: venue.into(),
+    };
+    let rider_entity = insert_rider(
+        commands,
+        clock,
+        spawn_location,
+        destination,
+        current_time_ms,
     );
+    Some((index, rider_entity))
+}
 
+fn insert_rider(
+    commands: &mut Commands,
+    clock: &mut SimulationClock,
+    spawn_location: SpawnLocation,
+    destination: h3o::CellIndex,
+    current_time_ms: u64,
+) -> bevy_ecs::prelude::Entity {
     let rider_entity = commands
         .spawn((
             Rider {
@@ -68,11 +160,12 @@ pub(super) fn spawn_rider(
                 destination: Some(destination),
                 requested_at: Some(current_time_ms),
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
             Browsing,
-            Position(position),
+            Position(spawn_location.cell),
             GeoPosition(spawn_location.geo),
         ))
         .id();
@@ -90,16 +183,18 @@ pub(super) fn spawn_driver(
     commands: &mut Commands,
     spawner: &mut DriverSpawner,
     current_time_ms: u64,
+    epoch_ms: i64,
     weighting: Option<&SpawnWeighting>,
     osrm_metrics: MaybeOsrmSpawnMetrics<'_>,
 ) {
     let mut rng = create_spawn_rng(spawner.config.seed, spawner.spawned_count());
+    let hour = hour_of_day(epoch_ms.saturating_add(current_time_ms as i64));
 
     #[cfg(feature = "osrm")]
     let spawn_location = resolve_spawn_location(
         &mut rng,
         weighting,
-        |w, rng| w.sample_driver_cell(rng),
+        |w, rng| w.sample_driver_cell_at(hour, rng),
         spawner.config.lat_min,
         spawner.config.lat_max,
         spawner.config.lng_min,
@@ -111,7 +206,7 @@ pub(super) fn spawn_driver(
     let spawn_location = resolve_spawn_location(
         &mut rng,
         weighting,
-        |w, rng| w.sample_driver_cell(rng),
+        |w, rng| w.sample_driver_cell_at(hour, rng),
         spawner.config.lat_min,
         spawner.config.lat_max,
         spawner.config.lng_min,
@@ -123,8 +218,9 @@ pub(super) fn spawn_driver(
     let daily_earnings_target = rng.gen_range(100.0..=300.0);
     let fatigue_hours = rng.gen_range(8.0..=12.0);
     let fatigue_threshold_ms = (fatigue_hours * ONE_HOUR_MS as f64) as u64;
+    let vehicle = spawner.vehicle_mix().sample(&mut rng);
 
-    commands.spawn((
+    let mut driver = commands.spawn((
         Driver {
             matched_rider: None,
             assigned_trip: None,
@@ -137,9 +233,42 @@ pub(super) fn spawn_driver(
             daily_earnings_target,
             session_start_time_ms: current_time_ms,
             session_end_time_ms: None,
+            weekly_trips: 0,
+            week_index: 0,
         },
         DriverFatigue {
             fatigue_threshold_ms,
         },
     ));
+    if let Some(vehicle) = vehicle {
+        driver.insert(DriverVehicle(vehicle));
+    }
+}
+
+/// Spawn a driver carried over from a previous run (see [`WarmStartState`](crate::warm_start::WarmStartState)) as Idle at `now_ms`.
+/// The new session keeps the day's earnings and only the time the driver had left before fatigue.
+pub(super) fn spawn_carried_driver(commands: &mut Commands, carried: &CarriedDriver, now_ms: u64) {
+    let mut driver = commands.spawn((
+        Driver {
+            matched_rider: None,
+            assigned_trip: None,
+        },
+        Idle,
+        Position(carried.cell),
+        GeoPosition(carried.geo),
+        DriverEarnings {
+            daily_earnings: carried.daily_earnings,
+            daily_earnings_target: carried.daily_earnings_target,
+            session_start_time_ms: now_ms,
+            session_end_time_ms: None,
+            weekly_trips: carried.weekly_trips,
+            week_index: now_ms / ONE_WEEK_MS,
+        },
+        DriverFatigue {
+            fatigue_threshold_ms: carried.remaining_fatigue_ms(),
+        },
+    ));
+    if let Some(vehicle) = carried.vehicle {
+        driver.insert(DriverVehicle(vehicle));
+    }
 }
@@ -1,10 +1,27 @@
 use bevy_ecs::prelude::Commands;
 
 use crate::clock::{EventKind, SimulationClock};
+use crate::sim_log::{SimLogCommands, SimLogEvent, SpawnerKind};
 use crate::spawner::{DriverSpawner, RiderSpawner, SpawnWeighting};
 
 use super::{spawn_driver, spawn_rider, MaybeOsrmSpawnMetrics};
 
+/// Log the spawn that brought a spawner to its maximum count.
+fn log_if_exhausted(
+    commands: &mut Commands,
+    current_time_ms: u64,
+    spawner: SpawnerKind,
+    max_count: Option<usize>,
+    spawned: usize,
+) {
+    if max_count == Some(spawned) {
+        commands.log_sim_event(
+            current_time_ms,
+            SimLogEvent::SpawnerExhausted { spawner, spawned },
+        );
+    }
+}
+
 pub(super) fn initialize_rider_spawner(
     spawner: &mut RiderSpawner,
     commands: &mut Commands,
@@ -27,6 +44,13 @@ pub(super) fn initialize_rider_spawner(
             );
             spawner.increment_spawned_count();
         }
+        log_if_exhausted(
+            commands,
+            current_time_ms,
+            SpawnerKind::Rider,
+            spawner.config.max_count,
+            spawner.spawned_count(),
+        );
 
         if spawner.should_spawn(spawner.next_spawn_time_ms()) {
             clock.schedule_at(spawner.next_spawn_time_ms(), EventKind::SpawnRider, None);
@@ -46,9 +70,23 @@ pub(super) fn initialize_driver_spawner(
         spawner.set_initialized(true);
 
         for _ in 0..spawner.config.initial_count {
-            spawn_driver(commands, spawner, current_time_ms, weighting, osrm_metrics);
+            spawn_driver(
+                commands,
+                spawner,
+                current_time_ms,
+                clock.local_epoch_ms(),
+                weighting,
+                osrm_metrics,
+            );
             spawner.increment_spawned_count();
         }
+        log_if_exhausted(
+            commands,
+            current_time_ms,
+            SpawnerKind::Driver,
+            spawner.config.max_count,
+            spawner.spawned_count(),
+        );
 
         if spawner.should_spawn(spawner.next_spawn_time_ms()) {
             clock.schedule_at(spawner.next_spawn_time_ms(), EventKind::SpawnDriver, None);
@@ -83,6 +121,13 @@ pub(super) fn process_rider_spawner_event(
         );
 
         spawner.advance(current_time_ms);
+        log_if_exhausted(
+            commands,
+            current_time_ms,
+            SpawnerKind::Rider,
+            spawner.config.max_count,
+            spawner.spawned_count(),
+        );
 
         if spawner.should_spawn(spawner.next_spawn_time_ms()) {
             clock.schedule_at(spawner.next_spawn_time_ms(), EventKind::SpawnRider, None);
@@ -97,6 +142,7 @@ pub(super) fn process_driver_spawner_event(
     current_time_ms: u64,
     weighting: Option<&SpawnWeighting>,
     osrm_metrics: MaybeOsrmSpawnMetrics<'_>,
+    rate_multiplier: f64,
 ) {
     if let Some(start_time) = spawner.config.start_time_ms {
         if current_time_ms < start_time {
@@ -107,9 +153,23 @@ pub(super) fn process_driver_spawner_event(
     }
 
     if spawner.should_spawn(current_time_ms) {
-        spawn_driver(commands, spawner, current_time_ms, weighting, osrm_metrics);
+        spawn_driver(
+            commands,
+            spawner,
+            current_time_ms,
+            clock.local_epoch_ms(),
+            weighting,
+            osrm_metrics,
+        );
 
-        spawner.advance(current_time_ms);
+        spawner.advance_scaled(current_time_ms, rate_multiplier);
+        log_if_exhausted(
+            commands,
+            current_time_ms,
+            SpawnerKind::Driver,
+            spawner.config.max_count,
+            spawner.spawned_count(),
+        );
 
         if spawner.should_spawn(spawner.next_spawn_time_ms()) {
             clock.schedule_at(spawner.next_spawn_time_ms(), EventKind::SpawnDriver, None);
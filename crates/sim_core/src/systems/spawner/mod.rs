@@ -8,17 +8,20 @@ mod osrm;
 
 use bevy_ecs::prelude::{Commands, Res, ResMut};
 
-use crate::clock::{CurrentEvent, EventKind, SimulationClock, ONE_MIN_MS};
+use crate::clock::{CurrentEvent, EventKind, Recurrence, SimulationClock, ONE_SEC_MS};
+use crate::pricing::PricingConfig;
 use crate::scenario::BatchMatchingConfig;
-use crate::spawner::{DriverSpawner, RiderSpawner, SpawnWeighting};
+use crate::spawner::{DemandEvents, DriverSpawner, RiderSpawner, SpawnWeighting, SupplyElasticity};
+use crate::telemetry::{DemandEventAnnotation, SimTelemetry};
+use crate::warm_start::WarmStartState;
 
 #[cfg(feature = "osrm")]
 use crate::routing::osrm_spawn::OsrmSpawnClient;
 #[cfg(feature = "osrm")]
 use crate::telemetry::OsrmSpawnTelemetry;
 
-use common::{create_spawn_rng, resolve_spawn_location};
-use entity_spawn::{spawn_driver, spawn_rider};
+use common::{create_spawn_rng, resolve_spawn_location, SpawnLocation};
+use entity_spawn::{spawn_carried_driver, spawn_driver, spawn_event_rider, spawn_rider};
 use lifecycle::{
     initialize_driver_spawner, initialize_rider_spawner, process_driver_spawner_event,
     process_rider_spawner_event,
@@ -34,6 +37,7 @@ type MaybeOsrmSpawnMetrics<'a> = Option<&'a OsrmSpawnTelemetry>;
 #[cfg(not(feature = "osrm"))]
 type MaybeOsrmSpawnMetrics<'a> = ();
 
+#[allow(clippy::too_many_arguments)]
 pub fn simulation_started_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
@@ -41,6 +45,9 @@ pub fn simulation_started_system(
     rider_spawner: Option<ResMut<RiderSpawner>>,
     driver_spawner: Option<ResMut<DriverSpawner>>,
     spawn_weighting: Option<Res<SpawnWeighting>>,
+    demand_events: Option<ResMut<DemandEvents>>,
+    telemetry: Option<ResMut<SimTelemetry>>,
+    warm_start: Option<Res<WarmStartState>>,
     #[cfg(feature = "osrm")] osrm_spawn_metrics: Option<Res<OsrmSpawnTelemetry>>,
     event: Res<CurrentEvent>,
 ) {
@@ -57,7 +64,31 @@ pub fn simulation_started_system(
 
     if let Some(cfg) = batch_config.as_deref() {
         if cfg.enabled {
-            clock.schedule_at(0, EventKind::BatchMatchRun, None);
+            clock.schedule_recurring(
+                EventKind::BatchMatchRun,
+                None,
+                Recurrence::every(cfg.interval_secs.saturating_mul(ONE_SEC_MS)),
+            );
+        }
+    }
+
+    if let Some(mut demand_events) = demand_events.filter(|events| !events.is_empty()) {
+        for at in demand_events.schedule_arrivals() {
+            clock.schedule_at(at, EventKind::SpawnEventRider, None);
+        }
+        if let Some(mut telemetry) = telemetry {
+            telemetry.demand_events = demand_events
+                .events
+                .iter()
+                .map(|event| DemandEventAnnotation {
+                    name: event.name.clone(),
+                    start_ms: event.start_ms,
+                    end_ms: event.end_ms(),
+                    venue_cell: event.venue_cell(),
+                    requests_planned: event.request_count,
+                    requests_spawned: 0,
+                })
+                .collect();
         }
     }
 
@@ -72,6 +103,12 @@ pub fn simulation_started_system(
         );
     }
 
+    if let Some(warm_start) = warm_start {
+        for carried in &warm_start.drivers {
+            spawn_carried_driver(&mut commands, carried, current_time_ms);
+        }
+    }
+
     if let Some(mut spawner) = driver_spawner {
         initialize_driver_spawner(
             &mut spawner,
@@ -82,8 +119,6 @@ pub fn simulation_started_system(
             osrm_spawn_metrics_ref,
         );
     }
-
-    clock.schedule_in(5 * ONE_MIN_MS, EventKind::CheckDriverOffDuty, None);
 }
 
 pub fn rider_spawner_system(
@@ -114,11 +149,42 @@ pub fn rider_spawner_system(
     );
 }
 
+pub fn demand_event_spawner_system(
+    mut commands: Commands,
+    mut clock: ResMut<SimulationClock>,
+    mut demand_events: ResMut<DemandEvents>,
+    spawner: Res<RiderSpawner>,
+    telemetry: Option<ResMut<SimTelemetry>>,
+    event: Res<CurrentEvent>,
+) {
+    if event.0.kind != EventKind::SpawnEventRider {
+        return;
+    }
+
+    let current_time_ms = clock.now();
+    let Some((index, _)) = spawn_event_rider(
+        &mut commands,
+        &mut clock,
+        &mut demand_events,
+        &spawner,
+        current_time_ms,
+    ) else {
+        return;
+    };
+    if let Some(mut telemetry) = telemetry {
+        if let Some(annotation) = telemetry.demand_events.get_mut(index) {
+            annotation.requests_spawned += 1;
+        }
+    }
+}
+
 pub fn driver_spawner_system(
     mut commands: Commands,
     mut clock: ResMut<SimulationClock>,
     mut spawner: ResMut<DriverSpawner>,
     spawn_weighting: Option<Res<SpawnWeighting>>,
+    supply_elasticity: Option<ResMut<SupplyElasticity>>,
+    pricing_config: Option<Res<PricingConfig>>,
     #[cfg(feature = "osrm")] osrm_spawn_metrics: Option<Res<OsrmSpawnTelemetry>>,
     event: Res<CurrentEvent>,
 ) {
@@ -127,6 +193,19 @@ pub fn driver_spawner_system(
     }
 
     let current_time_ms = clock.now();
+    // Endogenous supply: lagged surge and active incentives speed up (or slow down) arrivals.
+    let rate_multiplier = match supply_elasticity {
+        Some(mut supply) if supply.is_enabled() => {
+            let floor = pricing_config
+                .as_deref()
+                .map(|pricing| pricing.min_hourly_earnings)
+                .unwrap_or(0.0);
+            let multiplier = supply.rate_multiplier(current_time_ms, floor);
+            supply.record_applied(multiplier);
+            multiplier
+        }
+        _ => 1.0,
+    };
     let weighting = spawn_weighting.as_deref();
     #[cfg(feature = "osrm")]
     let osrm_spawn_metrics_ref: MaybeOsrmSpawnMetrics<'_> = osrm_spawn_metrics.as_deref();
@@ -139,5 +218,6 @@ pub fn driver_spawner_system(
         current_time_ms,
         weighting,
         osrm_spawn_metrics_ref,
+        rate_multiplier,
     );
 }
@@ -1,4 +1,10 @@
-use bevy_ecs::prelude::{Entity, Query, Res, ResMut};
+use std::collections::{BTreeMap, HashSet};
+
+use bevy_ecs::archetype::Archetypes;
+use bevy_ecs::component::Components;
+use bevy_ecs::entity::Entities;
+use bevy_ecs::prelude::{Added, Changed, Entity, Local, Or, Query, Res, ResMut, With};
+use h3o::CellIndex;
 
 use crate::clock::SimulationClock;
 use crate::ecs::{
@@ -6,9 +12,10 @@ use crate::ecs::{
     InTransit, OffDuty, OnTrip, Position, Rider, RiderCancelled, RiderCompleted, Trip,
     TripCancelled, TripCompleted, TripEnRoute, TripFinancials, TripOnTrip, TripTiming, Waiting,
 };
+use crate::profiling::{WorldGrowthMetrics, WorldGrowthSample};
 use crate::telemetry::{
-    DriverSnapshot, DriverState, GeoPoint, RiderSnapshot, RiderState, SimCounts, SimSnapshot,
-    SimSnapshotConfig, SimSnapshots, SimTelemetry, TripSnapshot, TripState,
+    CellAggregate, DriverSnapshot, DriverState, GeoPoint, RiderSnapshot, RiderState, SimCounts,
+    SimSnapshot, SimSnapshotConfig, SimSnapshots, SimTelemetry, TripSnapshot, TripState,
 };
 
 fn rider_state_from_markers(
@@ -33,7 +40,7 @@ fn rider_state_from_markers(
     }
 }
 
-fn driver_state_from_markers(
+pub(crate) fn driver_state_from_markers(
     idle: Option<&Idle>,
     evaluating: Option<&Evaluating>,
     en_route: Option<&EnRoute>,
@@ -74,12 +81,83 @@ fn trip_state_from_markers(
     }
 }
 
+fn cell_aggregate(
+    cells: &mut BTreeMap<CellIndex, CellAggregate>,
+    cell: CellIndex,
+) -> &mut CellAggregate {
+    cells.entry(cell).or_insert(CellAggregate {
+        cell,
+        riders_browsing: 0,
+        riders_waiting: 0,
+        drivers_idle: 0,
+        drivers_busy: 0,
+    })
+}
+
+/// Riders whose snapshot fields may differ from the previous snapshot.
+type RiderChanged = (
+    With<Rider>,
+    Or<(
+        Changed<Rider>,
+        Changed<Position>,
+        Changed<GeoPosition>,
+        Added<Browsing>,
+        Added<Waiting>,
+        Added<InTransit>,
+        Added<RiderCompleted>,
+        Added<RiderCancelled>,
+    )>,
+);
+
+/// Drivers whose snapshot fields may differ from the previous snapshot.
+type DriverChanged = (
+    With<Driver>,
+    Or<(
+        Changed<Driver>,
+        Changed<Position>,
+        Changed<GeoPosition>,
+        Changed<DriverEarnings>,
+        Changed<DriverFatigue>,
+        Added<Idle>,
+        Added<Evaluating>,
+        Added<EnRoute>,
+        Added<OnTrip>,
+        Added<OffDuty>,
+    )>,
+);
+
+/// Trips whose snapshot fields may differ from the previous snapshot.
+type TripChanged = (
+    With<Trip>,
+    Or<(
+        Changed<Trip>,
+        Changed<TripTiming>,
+        Changed<TripFinancials>,
+        Added<TripEnRoute>,
+        Added<TripOnTrip>,
+        Added<TripCompleted>,
+        Added<TripCancelled>,
+    )>,
+);
+
+/// Bookkeeping for delta snapshots (only used when `keyframe_interval > 1`).
+#[derive(Default)]
+pub struct SnapshotDeltaState {
+    /// Entities listed in the reconstructed state of the last snapshot.
+    recorded: HashSet<Entity>,
+    since_keyframe: usize,
+}
+
 #[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn capture_snapshot_system(
     clock: Res<SimulationClock>,
     config: Res<SimSnapshotConfig>,
     mut snapshots: ResMut<SimSnapshots>,
     telemetry: Res<SimTelemetry>,
+    world_growth_metrics: Option<ResMut<WorldGrowthMetrics>>,
+    archetypes: &Archetypes,
+    components: &Components,
+    entities: &Entities,
     rider_query: Query<(
         Entity,
         &Rider,
@@ -104,6 +182,12 @@ pub fn capture_snapshot_system(
     )>,
     driver_earnings_query: Query<&DriverEarnings>,
     driver_fatigue_query: Query<&DriverFatigue>,
+    changed: (
+        Query<(), RiderChanged>,
+        Query<(), DriverChanged>,
+        Query<(), TripChanged>,
+    ),
+    mut delta_state: Local<SnapshotDeltaState>,
     trip_query: Query<(
         Entity,
         &Trip,
@@ -125,13 +209,36 @@ pub fn capture_snapshot_system(
         return;
     }
 
+    // Entities despawned by GC still count towards the terminal states
+    let collected = telemetry.entity_gc;
     let mut counts = SimCounts {
+        riders_completed: collected.riders_completed as usize,
+        riders_cancelled: collected.riders_cancelled as usize,
         riders_cancelled_total: telemetry.riders_cancelled_total,
         riders_completed_total: telemetry.riders_completed_total,
         riders_abandoned_quote_total: telemetry.riders_abandoned_quote_total,
+        trips_completed: collected.trips_completed as usize,
+        trips_cancelled: collected.trips_cancelled as usize,
         ..Default::default()
     };
 
+    let mut cells: BTreeMap<CellIndex, CellAggregate> = BTreeMap::new();
+
+    // Deltas list only entities changed since this system last ran
+    let deltas = config.keyframe_interval > 1;
+    let keyframe = !deltas
+        || snapshots.snapshots.is_empty()
+        || delta_state.since_keyframe + 1 >= config.keyframe_interval;
+    delta_state.since_keyframe = if keyframe {
+        0
+    } else {
+        delta_state.since_keyframe + 1
+    };
+    if keyframe {
+        delta_state.recorded.clear();
+    }
+    let (rider_changed, driver_changed, trip_changed) = changed;
+
     // Remove double iteration: collect riders in single pass
     let mut riders = Vec::new();
     for (entity, rider, position, geo_pos, browsing, waiting, in_transit, completed, cancelled) in
@@ -139,6 +246,22 @@ pub fn capture_snapshot_system(
     {
         let state = rider_state_from_markers(browsing, waiting, in_transit, completed, cancelled);
         counts.add_rider(state);
+        if config.include_cell_aggregates {
+            match state {
+                RiderState::Browsing => cell_aggregate(&mut cells, position.0).riders_browsing += 1,
+                RiderState::Waiting => cell_aggregate(&mut cells, position.0).riders_waiting += 1,
+                _ => {}
+            }
+        }
+        if !config.include_riders || !config.samples(entity) {
+            continue;
+        }
+        if deltas {
+            if !keyframe && !rider_changed.contains(entity) {
+                continue;
+            }
+            delta_state.recorded.insert(entity);
+        }
         riders.push(RiderSnapshot {
             entity,
             cell: position.0,
@@ -158,6 +281,22 @@ pub fn capture_snapshot_system(
     {
         let state = driver_state_from_markers(idle, evaluating, en_route, on_trip, off_duty);
         counts.add_driver(state);
+        if config.include_cell_aggregates {
+            match state {
+                DriverState::Idle => cell_aggregate(&mut cells, position.0).drivers_idle += 1,
+                DriverState::OffDuty => {}
+                _ => cell_aggregate(&mut cells, position.0).drivers_busy += 1,
+            }
+        }
+        if !config.include_drivers || !config.samples(entity) {
+            continue;
+        }
+        if deltas {
+            if !keyframe && !driver_changed.contains(entity) {
+                continue;
+            }
+            delta_state.recorded.insert(entity);
+        }
         let earnings = driver_earnings_query.get(entity).ok().copied();
         let fatigue = driver_fatigue_query.get(entity).ok().copied();
         drivers.push(DriverSnapshot {
@@ -183,6 +322,15 @@ pub fn capture_snapshot_system(
     {
         let state = trip_state_from_markers(tr_en_route, tr_on_trip, tr_completed, tr_cancelled);
         counts.add_trip(state);
+        if !config.include_trips || !config.samples(entity) {
+            continue;
+        }
+        if deltas {
+            if !keyframe && !trip_changed.contains(entity) {
+                continue;
+            }
+            delta_state.recorded.insert(entity);
+        }
         trips.push(TripSnapshot {
             entity,
             rider: trip.rider,
@@ -199,16 +347,48 @@ pub fn capture_snapshot_system(
         });
     }
 
-    snapshots.last_snapshot_at = Some(now);
-    snapshots.snapshots.push_back(SimSnapshot {
-        timestamp_ms: now,
-        counts,
-        riders,
-        drivers,
-        trips,
+    let mut removed = Vec::new();
+    if !keyframe {
+        delta_state.recorded.retain(|&entity| {
+            let exists = rider_query.contains(entity)
+                || driver_query.contains(entity)
+                || trip_query.contains(entity);
+            if !exists {
+                removed.push(entity);
+            }
+            exists
+        });
+        removed.sort_unstable();
+    }
+
+    // Sampled before this snapshot is pushed, so retained bytes cover earlier snapshots only
+    let world_growth = config.include_world_growth.then(|| {
+        let mut sample = WorldGrowthSample::collect(
+            now,
+            archetypes,
+            components,
+            entities,
+            clock.pending_event_count(),
+        );
+        sample.add_retained(&telemetry, &snapshots);
+        if let Some(mut metrics) = world_growth_metrics {
+            metrics.record(sample.clone());
+        }
+        sample
     });
 
-    if snapshots.snapshots.len() > config.max_snapshots {
-        snapshots.snapshots.pop_front();
-    }
+    snapshots.push(
+        SimSnapshot {
+            timestamp_ms: now,
+            counts,
+            riders,
+            drivers,
+            trips,
+            cells: cells.into_values().collect(),
+            world_growth,
+            keyframe,
+            removed,
+        },
+        &config,
+    );
 }
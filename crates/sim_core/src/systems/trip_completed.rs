@@ -2,20 +2,26 @@ use bevy_ecs::prelude::{Commands, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use crate::ecs::{
-    Driver, DriverEarnings, DriverStateCommands, InTransit, OnTrip, Rider, RiderCompleted, Trip,
-    TripCompleted, TripFinancials, TripOnTrip, TripTiming,
+    DispatchNotBefore, Driver, DriverEarnings, DriverStateCommands, InTransit, OnTrip, Rider, Trip,
+    TripFinancials, TripOnTrip, TripRoute, TripTiming, VehicleClassChoice,
 };
+use crate::pricing::tolls::TollZones;
 use crate::pricing::{
-    calculate_driver_earnings, calculate_platform_revenue, calculate_trip_fare_with_config,
-    PricingConfig,
+    calculate_trip_commission, calculate_trip_fare_with_config, calculate_trip_taxes,
+    CommissionZones, PricingConfig, TaxZones,
 };
-use crate::telemetry::{CompletedTripRecord, SimTelemetry};
+use crate::routing::polyline::encode_polyline;
+use crate::state_machine::StateTransitionCommands;
+use crate::telemetry::{CompletedTripRecord, RiderState, SimTelemetry, TripState};
 
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn trip_completed_system(
     event: Res<CurrentEvent>,
     mut clock: ResMut<SimulationClock>,
     pricing_config: Res<PricingConfig>,
+    commission_zones: Option<Res<CommissionZones>>,
+    toll_zones: Option<Res<TollZones>>,
+    tax_zones: Option<Res<TaxZones>>,
     mut telemetry: ResMut<SimTelemetry>,
     mut commands: Commands,
     mut trips: Query<(
@@ -23,8 +29,10 @@ pub fn trip_completed_system(
         &mut TripTiming,
         &TripFinancials,
         Option<&TripOnTrip>,
+        Option<&TripRoute>,
+        Option<&VehicleClassChoice>,
     )>,
-    mut riders: Query<(&mut Rider, Option<&InTransit>)>,
+    mut riders: Query<(&mut Rider, Option<&InTransit>, Option<&DispatchNotBefore>)>,
     mut drivers: Query<(&mut Driver, Option<&OnTrip>)>,
     mut driver_earnings: Query<&mut DriverEarnings>,
 ) {
@@ -36,7 +44,9 @@ pub fn trip_completed_system(
         return;
     };
 
-    let Ok((trip, mut timing, financials, on_trip)) = trips.get_mut(trip_entity) else {
+    let Ok((trip, mut timing, financials, on_trip, route, class_choice)) =
+        trips.get_mut(trip_entity)
+    else {
         return;
     };
     if on_trip.is_none() {
@@ -51,12 +61,41 @@ pub fn trip_completed_system(
         calculate_trip_fare_with_config(trip.pickup, trip.dropoff, *pricing_config)
     });
 
+    // Tolls follow the dropoff leg as driven (reroutes included), or the H3 grid path when the
+    // trip has no route; they are passed through, so they are excluded from surge impact and
+    // commission.
+    let tolls = toll_zones
+        .as_deref()
+        .map(|zones| match route {
+            Some(route) => zones.toll_for_points(&route.driven_points(), trip.pickup.resolution()),
+            None => zones.toll_for_trip(trip.pickup, trip.dropoff, None),
+        })
+        .unwrap_or(0.0)
+        .min(fare);
+    let commissionable_fare = fare - tolls;
+
     // Calculate base fare (without surge) to determine surge impact
     let base_fare = calculate_trip_fare_with_config(trip.pickup, trip.dropoff, *pricing_config);
-    let surge_impact = (fare - base_fare).max(0.0); // Ensure non-negative
+    let surge_impact = (commissionable_fare - base_fare).max(0.0); // Ensure non-negative
 
-    let commission = calculate_platform_revenue(fare, pricing_config.commission_rate);
-    let driver_earnings_amount = calculate_driver_earnings(fare, pricing_config.commission_rate);
+    let completed_at = clock.now();
+    let mut earnings = driver_earnings.get_mut(driver_entity).ok();
+    let prior_weekly_trips = earnings
+        .as_mut()
+        .map(|earnings| earnings.record_completed_trip(completed_at))
+        .unwrap_or(0);
+    let zone_rate = commission_zones
+        .as_deref()
+        .and_then(|zones| zones.rate_for_cell(trip.pickup));
+    // Taxes and regulatory fees are withheld before the driver / platform split
+    let tax_rule = tax_zones
+        .as_deref()
+        .and_then(|zones| zones.rule_for_cell(trip.pickup));
+    let taxes_and_fees = calculate_trip_taxes(commissionable_fare, &pricing_config, tax_rule);
+    let net_fare = commissionable_fare - taxes_and_fees;
+    let commission =
+        calculate_trip_commission(net_fare, &pricing_config, prior_weekly_trips, zone_rate);
+    let driver_earnings_amount = net_fare - commission;
 
     // Update driver state and clear trip backlink
     if let Ok((mut driver, on_trip)) = drivers.get_mut(driver_entity) {
@@ -68,7 +107,7 @@ pub fn trip_completed_system(
     }
 
     // Update earnings
-    if let Ok(mut earnings) = driver_earnings.get_mut(driver_entity) {
+    if let Some(earnings) = earnings.as_mut() {
         earnings.daily_earnings += driver_earnings_amount;
     }
 
@@ -79,24 +118,28 @@ pub fn trip_completed_system(
         Some(EventSubject::Driver(driver_entity)),
     );
 
-    if let Ok((mut rider, in_transit)) = riders.get_mut(rider_entity) {
+    let mut wait_and_save = false;
+    if let Ok((mut rider, in_transit, dispatch_not_before)) = riders.get_mut(rider_entity) {
+        wait_and_save = dispatch_not_before.is_some();
         if in_transit.is_some() {
             commands
                 .entity(rider_entity)
-                .remove::<InTransit>()
-                .insert(RiderCompleted);
+                .transition_to(RiderState::Completed);
         }
         rider.matched_driver = None;
     }
 
-    let completed_at = clock.now();
     let pickup_at = timing.pickup_at.unwrap_or(completed_at);
     timing.dropoff_at = Some(completed_at);
     commands
         .entity(trip_entity)
-        .remove::<TripOnTrip>()
-        .insert(TripCompleted);
+        .transition_to(TripState::Completed);
 
+    let idle_ms = telemetry
+        .driver_utilization
+        .get_mut(&driver_entity)
+        .map(|utilization| utilization.take_unattributed_idle_ms())
+        .unwrap_or(0);
     telemetry.completed_trips.push(CompletedTripRecord {
         trip_entity,
         rider_entity,
@@ -107,10 +150,32 @@ pub fn trip_completed_system(
         pickup_at,
         fare,
         surge_impact,
+        commission,
+        driver_earnings: driver_earnings_amount,
+        tolls,
+        taxes_and_fees,
+        wait_and_save,
+        vehicle_class: class_choice.map(|choice| choice.chosen.clone()),
+        offered_vehicle_class: class_choice.map(|choice| choice.offered.clone()),
+        pickup_km: financials.pickup_distance_km_at_accept,
+        deadhead_km: financials.deadhead_km,
+        trip_km: financials.trip_km,
+        idle_ms,
+        route_polyline: route.filter(|route| route.follows_roads).map(|route| {
+            encode_polyline(
+                route
+                    .driven_points()
+                    .iter()
+                    .map(|point| (point.lat(), point.lng())),
+            )
+        }),
     });
     telemetry.riders_completed_total = telemetry.riders_completed_total.saturating_add(1);
+    telemetry.record_funnel_exit(rider_entity, None, completed_at);
     telemetry.platform_revenue_total += commission;
     telemetry.total_fares_collected += fare;
+    telemetry.tolls_collected_total += tolls;
+    telemetry.taxes_and_fees_total += taxes_and_fees;
 
     commands.entity(rider_entity).despawn();
 }
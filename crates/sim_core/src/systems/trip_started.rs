@@ -2,9 +2,11 @@ use bevy_ecs::prelude::{Commands, ParamSet, Query, Res, ResMut};
 
 use crate::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use crate::ecs::{
-    Driver, DriverStateCommands, EnRoute, InTransit, Position, Rider, Trip, TripEnRoute,
-    TripOnTrip, TripRoute, TripTiming, Waiting,
+    Driver, DriverStateCommands, EnRoute, NextMoveStep, PickupTimeout, Position, Rider, Trip,
+    TripEnRoute, TripRoute, TripTiming, Waiting,
 };
+use crate::state_machine::StateTransitionCommands;
+use crate::telemetry::{RiderState, TripState};
 
 #[allow(clippy::type_complexity)]
 pub fn trip_started_system(
@@ -14,7 +16,12 @@ pub fn trip_started_system(
     mut trips: Query<(&mut Trip, &mut TripTiming, Option<&TripEnRoute>)>,
     mut queries: ParamSet<(
         Query<(&mut Driver, &Position, Option<&EnRoute>)>,
-        Query<(&mut Rider, &mut Position, Option<&Waiting>)>,
+        Query<(
+            &mut Rider,
+            &mut Position,
+            Option<&Waiting>,
+            Option<&PickupTimeout>,
+        )>,
     )>,
 ) {
     if event.0.kind != EventKind::TripStarted {
@@ -48,7 +55,7 @@ pub fn trip_started_system(
 
     let (rider_pos, rider_matched_driver_ok, rider_waiting) = {
         let rider_query = queries.p1();
-        let Ok((rider, rider_pos, waiting)) = rider_query.get(rider_entity) else {
+        let Ok((rider, rider_pos, waiting, _)) = rider_query.get(rider_entity) else {
             return;
         };
         (
@@ -64,13 +71,18 @@ pub fn trip_started_system(
     // Update rider state and position
     {
         let mut rider_query = queries.p1();
-        let Ok((_rider, mut rider_pos, _)) = rider_query.get_mut(rider_entity) else {
+        let Ok((_rider, mut rider_pos, _, pickup_timeout)) = rider_query.get_mut(rider_entity)
+        else {
             return;
         };
+        // Picked up in time: the rider's pickup timeout must not fire
+        if let Some(timeout) = pickup_timeout {
+            clock.cancel(timeout.0);
+            commands.entity(rider_entity).remove::<PickupTimeout>();
+        }
         commands
             .entity(rider_entity)
-            .remove::<Waiting>()
-            .insert(InTransit);
+            .transition_to(RiderState::InTransit);
         // Update rider position to match driver position (rider is now in the vehicle)
         rider_pos.0 = driver_pos;
     }
@@ -86,8 +98,7 @@ pub fn trip_started_system(
     if let Ok((mut _trip, mut timing, _)) = trips.get_mut(trip_entity) {
         commands
             .entity(trip_entity)
-            .remove::<TripEnRoute>()
-            .insert(TripOnTrip);
+            .transition_to(TripState::OnTrip);
         timing.pickup_at = Some(clock.now());
     }
 
@@ -96,9 +107,10 @@ pub fn trip_started_system(
     commands.entity(trip_entity).remove::<TripRoute>();
 
     // Start moving driver toward dropoff; completion is scheduled by movement when driver arrives.
-    clock.schedule_in_secs(
+    let move_step = clock.schedule_in_secs(
         1,
         EventKind::MoveStep,
         Some(EventSubject::Trip(trip_entity)),
     );
+    commands.entity(trip_entity).insert(NextMoveStep(move_step));
 }
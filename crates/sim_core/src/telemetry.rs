@@ -1,14 +1,23 @@
 //! Telemetry / KPIs: records completed trips for analysis.
 
-use std::collections::VecDeque;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 #[cfg(feature = "osrm")]
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use bevy_ecs::prelude::{Entity, Resource};
 use h3o::CellIndex;
+use serde::{Deserialize, Serialize};
 
-/// Rider lifecycle state (for telemetry/snapshot serialization).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::clock::{ONE_DAY_MS, ONE_HOUR_MS, ONE_MIN_MS};
+use crate::emissions::DrivingLeg;
+use crate::matching::MatchCandidate;
+use crate::profiling::WorldGrowthSample;
+use crate::state_machine::IllegalTransitionRecord;
+
+/// Rider lifecycle state (for telemetry/snapshot serialization; transitions are checked by
+/// `state_machine::Lifecycle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiderState {
     Browsing,
     Waiting,
@@ -17,8 +26,9 @@ pub enum RiderState {
     Cancelled,
 }
 
-/// Driver lifecycle state (for telemetry/snapshot serialization).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Driver lifecycle state (for telemetry/snapshot serialization; transitions are checked by
+/// `state_machine::Lifecycle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DriverState {
     Idle,
     Evaluating,
@@ -27,8 +37,9 @@ pub enum DriverState {
     OffDuty,
 }
 
-/// Trip lifecycle state (for telemetry/snapshot serialization).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Trip lifecycle state (for telemetry/snapshot serialization; transitions are checked by
+/// `state_machine::Lifecycle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TripState {
     EnRoute,
     OnTrip,
@@ -37,14 +48,14 @@ pub enum TripState {
 }
 
 /// Simplified geographic point (lat/lng) used in snapshots.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct GeoPoint {
     pub lat: f64,
     pub lng: f64,
 }
 
 /// Reason why a rider abandoned their ride request.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiderAbandonmentReason {
     /// Rider gave up after rejecting too many quotes due to price being too high.
     QuotePriceTooHigh,
@@ -52,16 +63,21 @@ pub enum RiderAbandonmentReason {
     QuoteEtaTooLong,
     /// Rider gave up after rejecting too many quotes due to stochastic rejection (accept_probability).
     QuoteStochasticRejection,
+    /// Rider gave up after too many quotes, the last of which expired before they decided.
+    QuoteExpired,
     /// Rider cancelled while waiting for pickup (timeout).
     PickupTimeout,
 }
 
 /// One completed trip, recorded when the driver reaches dropoff.
 /// Timestamps are simulation ticks; use the helper methods for derived KPIs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompletedTripRecord {
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub trip_entity: Entity,
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub rider_entity: Entity,
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub driver_entity: Entity,
     pub completed_at: u64,
     pub requested_at: u64,
@@ -71,6 +87,36 @@ pub struct CompletedTripRecord {
     pub fare: f64,
     /// Additional cost due to surge pricing (fare - base_fare). Zero if no surge was applied.
     pub surge_impact: f64,
+    /// Platform commission charged on this trip (depends on the commission model).
+    pub commission: f64,
+    /// Driver's share of the fare (fare - tolls - taxes_and_fees - commission).
+    pub driver_earnings: f64,
+    /// Pass-through toll / zone-entry charges included in `fare` (not commissioned).
+    pub tolls: f64,
+    /// Taxes and regulatory fees withheld from `fare` before commission.
+    pub taxes_and_fees: f64,
+    /// Rider chose a wait-and-save (deferred dispatch) class.
+    pub wait_and_save: bool,
+    /// Vehicle class the rider chose from the menu; None without a vehicle class menu.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vehicle_class: Option<String>,
+    /// Class offered first (the menu default); differs from `vehicle_class` on a substitution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offered_vehicle_class: Option<String>,
+    /// Straight-line driver-to-pickup distance at match acceptance (km).
+    pub pickup_km: f64,
+    /// Distance driven empty to the pickup (km). Drivers do not reposition while idle, so this
+    /// is all dead-head distance attributable to the trip.
+    pub deadhead_km: f64,
+    /// Distance driven with the rider on board (km).
+    pub trip_km: f64,
+    /// Driver idle time since their previous completed trip (or coming online) until this match.
+    pub idle_ms: u64,
+    /// Path driven from pickup to dropoff as an encoded polyline
+    /// ([`crate::routing::polyline`]); only set when the leg followed road waypoints from the
+    /// route provider (e.g. OSRM or a precomputed table), not H3 grid paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_polyline: Option<String>,
 }
 
 impl CompletedTripRecord {
@@ -88,6 +134,465 @@ impl CompletedTripRecord {
     pub fn trip_duration(&self) -> u64 {
         self.completed_at.saturating_sub(self.pickup_at)
     }
+
+    /// Total distance driven for this trip (dead-head + with rider).
+    pub fn driven_km(&self) -> f64 {
+        self.deadhead_km + self.trip_km
+    }
+
+    /// Cost of serving the trip at the given per-km driving cost and per-minute driver time
+    /// cost; time covers idle wait, en route and on trip.
+    pub fn cost_of_service(&self, cost_per_km: f64, cost_per_minute: f64) -> f64 {
+        let minutes =
+            (self.idle_ms + self.time_to_pickup() + self.trip_duration()) as f64 / 60_000.0;
+        self.driven_km() * cost_per_km + minutes * cost_per_minute
+    }
+}
+
+/// Cost-of-service totals over completed trips: distance driven and driver time (ms).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ServiceCostTotals {
+    pub trips: usize,
+    pub deadhead_km: f64,
+    pub trip_km: f64,
+    pub idle_ms: u64,
+    pub en_route_ms: u64,
+    pub on_trip_ms: u64,
+}
+
+impl ServiceCostTotals {
+    /// Mean cost per completed trip at the given per-km and per-minute costs; 0.0 without trips.
+    pub fn cost_per_trip(&self, cost_per_km: f64, cost_per_minute: f64) -> f64 {
+        if self.trips == 0 {
+            return 0.0;
+        }
+        let minutes = (self.idle_ms + self.en_route_ms + self.on_trip_ms) as f64 / 60_000.0;
+        let total = (self.deadhead_km + self.trip_km) * cost_per_km + minutes * cost_per_minute;
+        total / self.trips as f64
+    }
+}
+
+/// Distance driven and CO2 emitted, split by [`DrivingLeg`] (includes trips that were later
+/// cancelled, so deadhead here can exceed the completed-trip deadhead).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmissionsTotals {
+    pub occupied_km: f64,
+    pub deadhead_km: f64,
+    pub occupied_g_co2: f64,
+    pub deadhead_g_co2: f64,
+}
+
+impl EmissionsTotals {
+    /// Add `km` driven on `leg` at `grams_per_km`.
+    pub fn record(&mut self, leg: DrivingLeg, km: f64, grams_per_km: f64) {
+        let grams = km * grams_per_km;
+        match leg {
+            DrivingLeg::Occupied => {
+                self.occupied_km += km;
+                self.occupied_g_co2 += grams;
+            }
+            DrivingLeg::Deadhead => {
+                self.deadhead_km += km;
+                self.deadhead_g_co2 += grams;
+            }
+        }
+    }
+
+    pub fn total_km(&self) -> f64 {
+        self.occupied_km + self.deadhead_km
+    }
+
+    pub fn total_g_co2(&self) -> f64 {
+        self.occupied_g_co2 + self.deadhead_g_co2
+    }
+
+    /// Share of driven km without a rider on board (0.0 when nothing was driven).
+    pub fn empty_km_share(&self) -> f64 {
+        let total = self.total_km();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        self.deadhead_km / total
+    }
+}
+
+/// Error of the driver locations matching saw, measured on matched drivers (see
+/// `LocationReportingConfig`): distance from the reported to the true cell and report age.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LocationErrorTotals {
+    /// Matches made on a reported (rather than true) driver position.
+    pub matches: u64,
+    pub error_km_total: f64,
+    pub max_error_km: f64,
+    /// Time since the matched driver's last location report, summed over matches.
+    pub report_age_ms_total: u64,
+}
+
+impl LocationErrorTotals {
+    pub fn record(&mut self, error_km: f64, report_age_ms: u64) {
+        self.matches += 1;
+        self.error_km_total += error_km;
+        self.max_error_km = self.max_error_km.max(error_km);
+        self.report_age_ms_total += report_age_ms;
+    }
+
+    /// Mean reported-vs-true distance per match (0.0 without matches).
+    pub fn mean_error_km(&self) -> f64 {
+        if self.matches == 0 {
+            return 0.0;
+        }
+        self.error_km_total / self.matches as f64
+    }
+
+    /// Mean report age per match in ms (0.0 without matches).
+    pub fn mean_report_age_ms(&self) -> f64 {
+        if self.matches == 0 {
+            return 0.0;
+        }
+        self.report_age_ms_total as f64 / self.matches as f64
+    }
+}
+
+/// Terminal entities despawned by `entity_gc_system`, by final state.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntityGcTotals {
+    pub last_run_at_ms: Option<u64>,
+    pub trips_completed: u64,
+    pub trips_cancelled: u64,
+    pub riders_completed: u64,
+    pub riders_cancelled: u64,
+}
+
+/// Request funnel stages, in order. A rider's stage is the furthest one reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FunnelStage {
+    Requested,
+    Quoted,
+    QuoteAccepted,
+    Matched,
+    PickedUp,
+    Completed,
+}
+
+/// One rider request's path through the funnel (simulation ms timestamps).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RiderFunnel {
+    #[serde(with = "crate::run_bundle::entity_bits")]
+    pub rider: Entity,
+    pub requested_at: u64,
+    /// First quote shown.
+    pub quoted_at: Option<u64>,
+    pub quote_accepted_at: Option<u64>,
+    /// Trip created (driver accepted the match).
+    pub matched_at: Option<u64>,
+    pub pickup_at: Option<u64>,
+    pub completed_at: Option<u64>,
+    /// Why the rider left without completing (`None` if completed or still in progress).
+    pub abandon_reason: Option<RiderAbandonmentReason>,
+    /// When the rider left the funnel (completion, abandonment or cancellation).
+    pub exited_at: Option<u64>,
+}
+
+impl RiderFunnel {
+    pub fn new(rider: Entity, requested_at: u64) -> Self {
+        Self {
+            rider,
+            requested_at,
+            quoted_at: None,
+            quote_accepted_at: None,
+            matched_at: None,
+            pickup_at: None,
+            completed_at: None,
+            abandon_reason: None,
+            exited_at: None,
+        }
+    }
+
+    /// Furthest stage reached (the stage the rider exited at, once `exited_at` is set).
+    pub fn stage(&self) -> FunnelStage {
+        if self.completed_at.is_some() {
+            FunnelStage::Completed
+        } else if self.pickup_at.is_some() {
+            FunnelStage::PickedUp
+        } else if self.matched_at.is_some() {
+            FunnelStage::Matched
+        } else if self.quote_accepted_at.is_some() {
+            FunnelStage::QuoteAccepted
+        } else if self.quoted_at.is_some() {
+            FunnelStage::Quoted
+        } else {
+            FunnelStage::Requested
+        }
+    }
+}
+
+/// Riders reaching each funnel stage, with stage-to-stage conversion rates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FunnelSummary {
+    pub requested: usize,
+    pub quoted: usize,
+    pub quote_accepted: usize,
+    pub matched: usize,
+    pub picked_up: usize,
+    pub completed: usize,
+}
+
+impl FunnelSummary {
+    /// Requests that received at least one quote.
+    pub fn quote_rate(&self) -> f64 {
+        ratio(self.quoted, self.requested)
+    }
+
+    /// Quoted riders who accepted a quote.
+    pub fn quote_acceptance_rate(&self) -> f64 {
+        ratio(self.quote_accepted, self.quoted)
+    }
+
+    /// Accepted quotes that were matched to a driver.
+    pub fn match_rate(&self) -> f64 {
+        ratio(self.matched, self.quote_accepted)
+    }
+
+    /// Matched riders who were picked up.
+    pub fn pickup_rate(&self) -> f64 {
+        ratio(self.picked_up, self.matched)
+    }
+
+    /// Picked-up riders whose trip completed.
+    pub fn completion_rate(&self) -> f64 {
+        ratio(self.completed, self.picked_up)
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Per-driver state-duration accounting (simulation ms), updated on every driver state change.
+///
+/// The current state's interval stays open until the next transition; use
+/// [`DriverUtilization::closed_at`] (or [`SimTelemetry::driver_utilization_at`]) to report it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriverUtilization {
+    pub driver: Entity,
+    /// When the driver first appeared (spawn time).
+    pub first_seen_ms: u64,
+    pub state: DriverState,
+    pub state_since_ms: u64,
+    pub idle_ms: u64,
+    pub evaluating_ms: u64,
+    pub en_route_ms: u64,
+    pub on_trip_ms: u64,
+    pub off_duty_ms: u64,
+    /// Part of `idle_ms` already charged to completed trips (see `take_unattributed_idle_ms`).
+    pub idle_ms_attributed: u64,
+}
+
+impl DriverUtilization {
+    pub fn new(driver: Entity, state: DriverState, now_ms: u64) -> Self {
+        Self {
+            driver,
+            first_seen_ms: now_ms,
+            state,
+            state_since_ms: now_ms,
+            idle_ms: 0,
+            evaluating_ms: 0,
+            en_route_ms: 0,
+            on_trip_ms: 0,
+            off_duty_ms: 0,
+            idle_ms_attributed: 0,
+        }
+    }
+
+    /// Idle time not yet charged to a trip, marking it as charged. Called on trip completion,
+    /// so each trip carries the idle wait that preceded its match.
+    pub fn take_unattributed_idle_ms(&mut self) -> u64 {
+        let idle_ms = self.idle_ms.saturating_sub(self.idle_ms_attributed);
+        self.idle_ms_attributed = self.idle_ms;
+        idle_ms
+    }
+
+    /// Close the current state's interval at `now_ms` and enter `state`.
+    pub fn transition(&mut self, state: DriverState, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.state_since_ms);
+        match self.state {
+            DriverState::Idle => self.idle_ms += elapsed,
+            DriverState::Evaluating => self.evaluating_ms += elapsed,
+            DriverState::EnRoute => self.en_route_ms += elapsed,
+            DriverState::OnTrip => self.on_trip_ms += elapsed,
+            DriverState::OffDuty => self.off_duty_ms += elapsed,
+        }
+        self.state = state;
+        self.state_since_ms = self.state_since_ms.max(now_ms);
+    }
+
+    /// Copy with the open interval accounted up to `now_ms`.
+    pub fn closed_at(&self, now_ms: u64) -> Self {
+        let mut closed = *self;
+        closed.transition(self.state, now_ms);
+        closed
+    }
+
+    /// Time logged on (every state except off duty).
+    pub fn online_ms(&self) -> u64 {
+        self.idle_ms + self.evaluating_ms + self.en_route_ms + self.on_trip_ms
+    }
+
+    /// Share of online time spent carrying a rider; 0.0 before any online time.
+    pub fn utilization(&self) -> f64 {
+        match self.online_ms() {
+            0 => 0.0,
+            online => self.on_trip_ms as f64 / online as f64,
+        }
+    }
+
+    /// Share of driving time spent en route to pickups (dead-heading); 0.0 if never driven.
+    pub fn deadhead_share(&self) -> f64 {
+        match self.en_route_ms + self.on_trip_ms {
+            0 => 0.0,
+            driving => self.en_route_ms as f64 / driving as f64,
+        }
+    }
+}
+
+/// Aggregates for one H3 cell during one simulation hour (see [`CellHourRollup`]).
+///
+/// Requests, exits and quotes count in the hour they happened, keyed by the rider's request
+/// cell; idle driver time is split across the hours and cells it was spent in.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CellHourStats {
+    pub requests: u64,
+    pub completions: u64,
+    /// Riders who cancelled while waiting for pickup.
+    pub cancellations: u64,
+    /// Riders who gave up at the quote stage.
+    pub abandonments: u64,
+    pub quotes: u64,
+    /// Sum of quoted surge multipliers (see [`CellHourStats::avg_surge_multiplier`]).
+    pub surge_multiplier_total: f64,
+    pub idle_driver_ms: u64,
+}
+
+impl CellHourStats {
+    /// Mean surge multiplier over quotes shown in the cell; `None` without quotes.
+    pub fn avg_surge_multiplier(&self) -> Option<f64> {
+        (self.quotes > 0).then(|| self.surge_multiplier_total / self.quotes as f64)
+    }
+
+    pub fn idle_driver_minutes(&self) -> f64 {
+        self.idle_driver_ms as f64 / ONE_MIN_MS as f64
+    }
+}
+
+/// Per-cell, per-hour rollup of demand, outcomes, surge and idle supply.
+///
+/// Hours are `timestamp_ms / ONE_HOUR_MS` in simulation time. Idle intervals still open are
+/// only counted once closed; use [`CellHourRollup::closed_at`] to report them.
+#[derive(Debug, Clone, Default)]
+pub struct CellHourRollup {
+    cells: BTreeMap<(CellIndex, u64), CellHourStats>,
+    /// Request cell of riders whose request has not exited yet.
+    request_cells: BTreeMap<Entity, CellIndex>,
+    /// Cell and start of each idle driver's open interval.
+    idle_since: BTreeMap<Entity, (CellIndex, u64)>,
+}
+
+impl CellHourRollup {
+    fn stats_mut(&mut self, cell: CellIndex, at_ms: u64) -> &mut CellHourStats {
+        self.cells.entry((cell, at_ms / ONE_HOUR_MS)).or_default()
+    }
+
+    pub fn record_request(&mut self, rider: Entity, cell: CellIndex, at_ms: u64) {
+        self.request_cells.insert(rider, cell);
+        self.stats_mut(cell, at_ms).requests += 1;
+    }
+
+    /// Count a request exit (`None` = completed) in the rider's request cell.
+    pub fn record_exit(
+        &mut self,
+        rider: Entity,
+        reason: Option<RiderAbandonmentReason>,
+        at_ms: u64,
+    ) {
+        let Some(cell) = self.request_cells.remove(&rider) else {
+            return;
+        };
+        let stats = self.stats_mut(cell, at_ms);
+        match reason {
+            None => stats.completions += 1,
+            Some(RiderAbandonmentReason::PickupTimeout) => stats.cancellations += 1,
+            Some(_) => stats.abandonments += 1,
+        }
+    }
+
+    pub fn record_quote(&mut self, cell: CellIndex, at_ms: u64, surge_multiplier: f64) {
+        let stats = self.stats_mut(cell, at_ms);
+        stats.quotes += 1;
+        stats.surge_multiplier_total += surge_multiplier;
+    }
+
+    /// Open an idle interval for `driver` in `cell`.
+    pub fn start_idle(&mut self, driver: Entity, cell: CellIndex, at_ms: u64) {
+        self.idle_since.insert(driver, (cell, at_ms));
+    }
+
+    /// Close `driver`'s idle interval at `at_ms`, if one is open.
+    pub fn end_idle(&mut self, driver: Entity, at_ms: u64) {
+        if let Some((cell, since_ms)) = self.idle_since.remove(&driver) {
+            self.add_idle(cell, since_ms, at_ms);
+        }
+    }
+
+    fn add_idle(&mut self, cell: CellIndex, since_ms: u64, until_ms: u64) {
+        let mut start = since_ms;
+        while start < until_ms {
+            let hour_end = (start / ONE_HOUR_MS + 1) * ONE_HOUR_MS;
+            let end = hour_end.min(until_ms);
+            self.stats_mut(cell, start).idle_driver_ms += end - start;
+            start = end;
+        }
+    }
+
+    /// Copy with open idle intervals accounted up to `now_ms`.
+    pub fn closed_at(&self, now_ms: u64) -> Self {
+        let mut closed = self.clone();
+        for (cell, since_ms) in std::mem::take(&mut closed.idle_since).into_values() {
+            closed.add_idle(cell, since_ms, now_ms);
+        }
+        closed
+    }
+
+    /// Rows as `(cell, hour, stats)`, ordered by cell then hour.
+    pub fn iter(&self) -> impl Iterator<Item = (CellIndex, u64, &CellHourStats)> {
+        self.cells
+            .iter()
+            .map(|(&(cell, hour), stats)| (cell, hour, stats))
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+/// Timeline annotation for a special-event demand spike (concert, sports match).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DemandEventAnnotation {
+    pub name: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub venue_cell: Option<CellIndex>,
+    /// Requests configured for the event.
+    pub requests_planned: usize,
+    /// Riders actually spawned at the venue so far.
+    pub requests_spawned: u64,
 }
 
 /// Collects simulation telemetry. Insert as a resource to record completed trips.
@@ -102,12 +607,281 @@ pub struct SimTelemetry {
     pub riders_abandoned_price: u64,
     pub riders_abandoned_eta: u64,
     pub riders_abandoned_stochastic: u64,
+    /// Quote abandonments where the last quote expired (conversions lost to expiry).
+    pub riders_abandoned_expired: u64,
+    /// Quotes that expired before the rider decided.
+    pub quotes_expired_total: u64,
+    /// Quotes shown to riders who had already been quoted (after rejection or expiry).
+    pub requotes_total: u64,
+    /// Sum of `new_fare - previous_fare` over re-quotes (mean delta = total / requotes_total).
+    pub requote_fare_delta_total: f64,
+    /// Quotes where the rider compared vehicle classes, keyed by chosen class name.
+    pub vehicle_class_choices: BTreeMap<String, u64>,
+    /// Class comparisons where the rider chose a class other than the default (first) class.
+    pub vehicle_class_substitutions: u64,
     /// Breakdown of pickup cancellations.
     pub riders_cancelled_pickup_timeout: u64,
     /// Cumulative platform revenue from commission on completed trips.
     pub platform_revenue_total: f64,
     /// Total fares collected from riders (sum of agreed fares for completed trips).
     pub total_fares_collected: f64,
+    /// Toll / zone-entry charges collected from riders and passed through (included in total fares).
+    pub tolls_collected_total: f64,
+    /// Cumulative taxes and regulatory fees withheld from completed trips.
+    pub taxes_and_fees_total: f64,
+    /// Cumulative platform top-ups paid under the minimum earnings floor (included in driver payouts).
+    pub earnings_floor_top_ups_total: f64,
+    /// Driver sessions that received an earnings floor top-up.
+    pub drivers_topped_up_total: u64,
+    /// Special-event demand spikes, in configuration order (for timeline annotations).
+    pub demand_events: Vec<DemandEventAnnotation>,
+    /// State-duration accounting per driver, keyed by driver entity.
+    pub driver_utilization: BTreeMap<Entity, DriverUtilization>,
+    /// Requests, exits, surge and idle driver time per H3 cell and simulation hour.
+    pub cell_hours: CellHourRollup,
+    /// Request funnel per rider, keyed by rider entity (kept after the rider is despawned).
+    pub funnel: BTreeMap<Entity, RiderFunnel>,
+    /// Driven distance and CO2 by leg (recorded per `MoveStep`).
+    pub emissions: EmissionsTotals,
+    /// Mid-route reroutes triggered by traffic changes (see `RerouteConfig`).
+    pub reroutes_total: u64,
+    /// Reported-vs-true location error of matched drivers (see `LocationReportingConfig`).
+    pub location_error: LocationErrorTotals,
+    /// State changes rejected by the lifecycle transition tables (see `state_machine`).
+    pub illegal_transitions: Vec<IllegalTransitionRecord>,
+    /// Finished trips and riders despawned by entity GC (see `EntityGcConfig`).
+    pub entity_gc: EntityGcTotals,
+}
+
+impl SimTelemetry {
+    /// Riders who left the system: completed, cancelled or abandoned after quotes.
+    pub fn riders_resolved_total(&self) -> u64 {
+        self.riders_completed_total
+            + self.riders_cancelled_total
+            + self.riders_abandoned_quote_total
+    }
+
+    /// Share of resolved riders who cancelled or abandoned (0.0 when none are resolved).
+    pub fn rider_cancellation_rate(&self) -> f64 {
+        let resolved = self.riders_resolved_total();
+        if resolved == 0 {
+            0.0
+        } else {
+            (self.riders_cancelled_total + self.riders_abandoned_quote_total) as f64
+                / resolved as f64
+        }
+    }
+
+    /// Completed trips per simulation day (index 0 = first 24 hours), for multi-day runs.
+    pub fn completed_trips_per_day(&self) -> Vec<u64> {
+        let mut per_day = Vec::new();
+        for trip in &self.completed_trips {
+            let day = (trip.completed_at / ONE_DAY_MS) as usize;
+            if per_day.len() <= day {
+                per_day.resize(day + 1, 0);
+            }
+            per_day[day] += 1;
+        }
+        per_day
+    }
+
+    /// Record that `rider` left the funnel at `now_ms`; `reason` is `None` for completed trips.
+    pub fn record_funnel_exit(
+        &mut self,
+        rider: Entity,
+        reason: Option<RiderAbandonmentReason>,
+        now_ms: u64,
+    ) {
+        let funnel = self
+            .funnel
+            .entry(rider)
+            .or_insert_with(|| RiderFunnel::new(rider, now_ms));
+        if reason.is_none() {
+            funnel.completed_at = Some(now_ms);
+        }
+        funnel.abandon_reason = reason;
+        funnel.exited_at = Some(now_ms);
+        self.cell_hours.record_exit(rider, reason, now_ms);
+    }
+
+    /// Distance and driver time summed over completed trips.
+    pub fn service_cost_totals(&self) -> ServiceCostTotals {
+        let mut totals = ServiceCostTotals::default();
+        for trip in &self.completed_trips {
+            totals.trips += 1;
+            totals.deadhead_km += trip.deadhead_km;
+            totals.trip_km += trip.trip_km;
+            totals.idle_ms += trip.idle_ms;
+            totals.en_route_ms += trip.time_to_pickup();
+            totals.on_trip_ms += trip.trip_duration();
+        }
+        totals
+    }
+
+    /// Count of riders reaching each funnel stage.
+    pub fn funnel_summary(&self) -> FunnelSummary {
+        let mut summary = FunnelSummary::default();
+        for funnel in self.funnel.values() {
+            let stage = funnel.stage();
+            summary.requested += 1;
+            summary.quoted += usize::from(stage >= FunnelStage::Quoted);
+            summary.quote_accepted += usize::from(stage >= FunnelStage::QuoteAccepted);
+            summary.matched += usize::from(stage >= FunnelStage::Matched);
+            summary.picked_up += usize::from(stage >= FunnelStage::PickedUp);
+            summary.completed += usize::from(stage >= FunnelStage::Completed);
+        }
+        summary
+    }
+
+    /// Per-driver utilization with open intervals closed at `now_ms` (e.g. the run's end time).
+    pub fn driver_utilization_at(&self, now_ms: u64) -> Vec<DriverUtilization> {
+        self.driver_utilization
+            .values()
+            .map(|utilization| utilization.closed_at(now_ms))
+            .collect()
+    }
+
+    /// Cell-hour rollup with open idle intervals closed at `now_ms` (for export at the end of a run).
+    pub fn cell_hours_at(&self, now_ms: u64) -> CellHourRollup {
+        self.cell_hours.closed_at(now_ms)
+    }
+}
+
+/// One matching decision for a rider: every driver in radius with its score, and the one chosen.
+#[derive(Debug, Clone)]
+pub struct MatchDiagnosticRecord {
+    pub timestamp_ms: u64,
+    pub rider: Entity,
+    /// Whether the decision came from a batch matching pass (vs per-rider `TryMatch`).
+    pub batch: bool,
+    /// Driver the algorithm assigned, or `None` if the rider stayed unmatched.
+    pub chosen_driver: Option<Entity>,
+    /// Drivers within the match radius, scored by the algorithm's cost model.
+    pub candidates: Vec<MatchCandidate>,
+}
+
+/// Optional per-match diagnostics (ECS `Resource`). Matching systems only score and record
+/// candidate sets when this resource is present.
+#[derive(Debug, Default, Resource)]
+pub struct MatchDiagnostics {
+    pub records: Vec<MatchDiagnosticRecord>,
+}
+
+/// One dispatch decision as the live algorithm made it and as the shadow algorithm would have.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadowMatchRecord {
+    pub timestamp_ms: u64,
+    pub rider: Entity,
+    /// Whether the decision came from a batch matching pass (vs per-rider `TryMatch`).
+    pub batch: bool,
+    /// Driver the live algorithm assigned, or `None` if the rider stayed unmatched.
+    pub chosen_driver: Option<Entity>,
+    /// Pickup distance of the assigned driver (km, between cell centers).
+    pub chosen_pickup_km: Option<f64>,
+    /// Driver the shadow algorithm would have assigned.
+    pub shadow_driver: Option<Entity>,
+    pub shadow_pickup_km: Option<f64>,
+}
+
+impl ShadowMatchRecord {
+    pub fn agrees(&self) -> bool {
+        self.chosen_driver == self.shadow_driver
+    }
+}
+
+/// Optional counterfactual matching log (ECS `Resource`). Matching systems evaluate the
+/// `ShadowMatchingAlgorithm` and record a [`ShadowMatchRecord`] per decision only when this
+/// resource is present.
+///
+/// Each decision is counterfactual on its own: the shadow sees the live state, which already
+/// reflects the live algorithm's earlier choices.
+#[derive(Debug, Default, Resource)]
+pub struct ShadowMatchLog {
+    pub records: Vec<ShadowMatchRecord>,
+}
+
+impl ShadowMatchLog {
+    pub fn summary(&self) -> ShadowMatchSummary {
+        let mut summary = ShadowMatchSummary::default();
+        for record in &self.records {
+            summary.decisions += 1;
+            if record.agrees() {
+                summary.agreements += 1;
+            }
+            if let Some(km) = record.chosen_pickup_km {
+                summary.live_matches += 1;
+                summary.live_pickup_km_total += km;
+            }
+            if let Some(km) = record.shadow_pickup_km {
+                summary.shadow_matches += 1;
+                summary.shadow_pickup_km_total += km;
+            }
+        }
+        summary
+    }
+}
+
+/// Live vs shadow matching over all recorded decisions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ShadowMatchSummary {
+    pub decisions: usize,
+    /// Decisions where both algorithms picked the same driver (or both none).
+    pub agreements: usize,
+    pub live_matches: usize,
+    pub shadow_matches: usize,
+    pub live_pickup_km_total: f64,
+    pub shadow_pickup_km_total: f64,
+}
+
+impl ShadowMatchSummary {
+    pub fn agreement_rate(&self) -> f64 {
+        ratio(self.agreements, self.decisions)
+    }
+
+    pub fn live_mean_pickup_km(&self) -> f64 {
+        if self.live_matches == 0 {
+            return 0.0;
+        }
+        self.live_pickup_km_total / self.live_matches as f64
+    }
+
+    pub fn shadow_mean_pickup_km(&self) -> f64 {
+        if self.shadow_matches == 0 {
+            return 0.0;
+        }
+        self.shadow_pickup_km_total / self.shadow_matches as f64
+    }
+}
+
+/// Market features of one quote shown to a rider, for training demand / conversion models.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteFeatureRecord {
+    pub rider: Entity,
+    pub quoted_at: u64,
+    /// Local hour of day (0-23) at quote time.
+    pub local_hour: u8,
+    /// Local day of week at quote time, 0 = Monday.
+    pub local_weekday: u8,
+    pub origin: CellIndex,
+    pub destination: CellIndex,
+    /// Pickup to dropoff distance between cell centers.
+    pub distance_km: f64,
+    /// Surge multiplier after the rate limit (1.0 with surge disabled).
+    pub surge_multiplier: f64,
+    /// Browsing + waiting riders within the surge radius of the origin.
+    pub local_demand: usize,
+    /// Idle drivers within the surge radius of the origin.
+    pub local_supply: usize,
+    /// Quoted fare, including tolls and the chosen vehicle class.
+    pub fare: f64,
+    pub eta_ms: u64,
+}
+
+/// Optional per-quote feature log (ECS `Resource`). `show_quote_system` records a
+/// [`QuoteFeatureRecord`] for every quote only when this resource is present.
+#[derive(Debug, Default, Resource)]
+pub struct QuoteFeatureLog {
+    pub records: Vec<QuoteFeatureRecord>,
 }
 
 #[cfg(feature = "osrm")]
@@ -172,22 +946,98 @@ impl OsrmSpawnTelemetry {
     }
 }
 
+/// Request counters for [`crate::routing::osrm_table::OsrmTableClient`]. Each attempt,
+/// including retries, counts as one request.
+#[cfg(feature = "osrm")]
+#[derive(Debug, Default, Resource)]
+pub struct OsrmTableTelemetry {
+    requests: AtomicU64,
+    request_errors: AtomicU64,
+    retries: AtomicU64,
+    circuit_rejections: AtomicU64,
+    latency_total_us: AtomicU64,
+    latency_max_us: AtomicU64,
+}
+
+/// Point-in-time view of [`OsrmTableTelemetry`].
+#[cfg(feature = "osrm")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OsrmTableTelemetrySnapshot {
+    pub requests: u64,
+    pub request_errors: u64,
+    pub retries: u64,
+    pub circuit_rejections: u64,
+    /// Failed share of sent requests (0 when none were sent).
+    pub error_rate: f64,
+    pub mean_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+#[cfg(feature = "osrm")]
+impl OsrmTableTelemetry {
+    pub fn record_request(&self, latency: std::time::Duration, failed: bool) {
+        let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.request_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.latency_total_us
+            .fetch_add(latency_us, Ordering::Relaxed);
+        self.latency_max_us.fetch_max(latency_us, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_circuit_rejection(&self) {
+        self.circuit_rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> OsrmTableTelemetrySnapshot {
+        let requests = self.requests.load(Ordering::Relaxed);
+        let request_errors = self.request_errors.load(Ordering::Relaxed);
+        let (error_rate, mean_latency_ms) = if requests > 0 {
+            (
+                request_errors as f64 / requests as f64,
+                self.latency_total_us.load(Ordering::Relaxed) as f64 / requests as f64 / 1000.0,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        OsrmTableTelemetrySnapshot {
+            requests,
+            request_errors,
+            retries: self.retries.load(Ordering::Relaxed),
+            circuit_rejections: self.circuit_rejections.load(Ordering::Relaxed),
+            error_rate,
+            mean_latency_ms,
+            max_latency_ms: self.latency_max_us.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
 /// Snapshot of one rider for visualization/export.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RiderSnapshot {
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub entity: Entity,
+    #[serde(with = "crate::run_bundle::cell_index")]
     pub cell: CellIndex,
     pub state: RiderState,
     /// Driver entity if matched (None = waiting for match, Some = waiting for pickup)
+    #[serde(with = "crate::run_bundle::option_entity_bits")]
     pub matched_driver: Option<Entity>,
     /// Lat/lng position (if available) for accurate rendering.
     pub geo: Option<GeoPoint>,
 }
 
 /// Snapshot of one driver for visualization/export.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DriverSnapshot {
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub entity: Entity,
+    #[serde(with = "crate::run_bundle::cell_index")]
     pub cell: CellIndex,
     pub state: DriverState,
     /// Daily earnings (if available)
@@ -205,13 +1055,18 @@ pub struct DriverSnapshot {
 }
 
 /// Snapshot of one trip for visualization/export.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TripSnapshot {
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub entity: Entity,
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub rider: Entity,
+    #[serde(with = "crate::run_bundle::entity_bits")]
     pub driver: Entity,
     pub state: TripState,
+    #[serde(with = "crate::run_bundle::cell_index")]
     pub pickup_cell: CellIndex,
+    #[serde(with = "crate::run_bundle::cell_index")]
     pub dropoff_cell: CellIndex,
     pub pickup_distance_km_at_accept: f64,
     pub requested_at: u64,
@@ -222,7 +1077,7 @@ pub struct TripSnapshot {
 }
 
 /// Aggregated counts at a point in time.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SimCounts {
     pub riders_browsing: usize,
     pub riders_waiting: usize,
@@ -244,20 +1099,175 @@ pub struct SimCounts {
 }
 
 /// Snapshot of simulation state at a specific timestamp (simulation ms).
-#[derive(Debug, Clone)]
+///
+/// A keyframe lists every (sampled) rider, driver and trip. A delta lists only those that
+/// changed since the previous snapshot, plus the `removed` ones (see
+/// `SimSnapshotConfig::keyframe_interval`); `SimSnapshots::reconstruct` rebuilds the full state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimSnapshot {
     pub timestamp_ms: u64,
     pub counts: SimCounts,
     pub riders: Vec<RiderSnapshot>,
     pub drivers: Vec<DriverSnapshot>,
     pub trips: Vec<TripSnapshot>,
+    /// Per-cell agent counts (empty unless `SimSnapshotConfig::include_cell_aggregates`).
+    pub cells: Vec<CellAggregate>,
+    /// World size at capture (None unless `SimSnapshotConfig::include_world_growth`).
+    #[serde(skip)]
+    pub world_growth: Option<WorldGrowthSample>,
+    #[serde(default = "keyframe_by_default")]
+    pub keyframe: bool,
+    /// Entities listed by earlier snapshots that no longer exist (deltas only).
+    #[serde(default, with = "crate::run_bundle::entity_bits_vec")]
+    pub removed: Vec<Entity>,
+}
+
+fn keyframe_by_default() -> bool {
+    true
+}
+
+impl SimSnapshot {
+    /// Bring this snapshot forward to `next`: a keyframe replaces it, a delta updates the listed
+    /// entities and drops the removed ones. Counts and cells are taken from `next`. Applying a
+    /// delta to a delta yields the combined delta.
+    pub fn apply(&mut self, next: &SimSnapshot) {
+        if next.keyframe {
+            *self = next.clone();
+            return;
+        }
+        let removed: HashSet<Entity> = next.removed.iter().copied().collect();
+        upsert_entities(&mut self.riders, &next.riders, &removed, |rider| {
+            rider.entity
+        });
+        upsert_entities(&mut self.drivers, &next.drivers, &removed, |driver| {
+            driver.entity
+        });
+        upsert_entities(&mut self.trips, &next.trips, &removed, |trip| trip.entity);
+        if !self.keyframe {
+            self.removed.extend_from_slice(&next.removed);
+        }
+        self.timestamp_ms = next.timestamp_ms;
+        self.counts = next.counts.clone();
+        self.cells = next.cells.clone();
+        self.world_growth = next.world_growth.clone();
+    }
+
+    /// Merge this (dropped) snapshot into `next` if that is a delta, so `next` still
+    /// reconstructs without it.
+    fn fold_into(self, next: &mut SimSnapshot) {
+        if next.keyframe {
+            return;
+        }
+        let mut merged = self;
+        merged.apply(next);
+        *next = merged;
+    }
+}
+
+/// Replace the entries of `list` whose entity is in `changed`, append the new ones and drop the
+/// `removed` ones.
+fn upsert_entities<T: Clone>(
+    list: &mut Vec<T>,
+    changed: &[T],
+    removed: &HashSet<Entity>,
+    entity: impl Fn(&T) -> Entity,
+) {
+    let mut changed_at: HashMap<Entity, usize> = changed
+        .iter()
+        .enumerate()
+        .map(|(index, item)| (entity(item), index))
+        .collect();
+    list.retain_mut(|item| {
+        let id = entity(item);
+        if removed.contains(&id) {
+            return false;
+        }
+        if let Some(index) = changed_at.remove(&id) {
+            *item = changed[index].clone();
+        }
+        true
+    });
+    list.extend(
+        changed
+            .iter()
+            .filter(|item| changed_at.contains_key(&entity(item)))
+            .cloned(),
+    );
+}
+
+/// Drop the snapshots `keep` rejects. Dropped snapshots are folded into the next kept delta, so
+/// every kept delta still reconstructs from the keyframe before it.
+pub(crate) fn retain_snapshots<C>(snapshots: C, mut keep: impl FnMut(&SimSnapshot) -> bool) -> C
+where
+    C: IntoIterator<Item = SimSnapshot> + FromIterator<SimSnapshot>,
+{
+    let mut dropped: Option<SimSnapshot> = None;
+    snapshots
+        .into_iter()
+        .filter_map(|mut snapshot| {
+            if !keep(&snapshot) {
+                dropped = Some(match dropped.take() {
+                    Some(mut earlier) => {
+                        earlier.apply(&snapshot);
+                        earlier
+                    }
+                    None => snapshot,
+                });
+                return None;
+            }
+            if let Some(earlier) = dropped.take() {
+                earlier.fold_into(&mut snapshot);
+            }
+            Some(snapshot)
+        })
+        .collect()
+}
+
+/// Agents in one H3 cell at snapshot time. Counts all entities, regardless of sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellAggregate {
+    #[serde(with = "crate::run_bundle::cell_index")]
+    pub cell: CellIndex,
+    /// Riders browsing a quote.
+    pub riders_browsing: u32,
+    /// Riders waiting for a driver.
+    pub riders_waiting: u32,
+    pub drivers_idle: u32,
+    pub drivers_busy: u32,
 }
 
-/// Snapshot capture configuration.
-#[derive(Debug, Clone, Copy, Resource)]
+/// What happens when `SimSnapshots` reaches `max_snapshots`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SnapshotRetention {
+    /// Drop the oldest snapshot: the buffer covers only the most recent window.
+    #[default]
+    RingBuffer,
+    /// Drop every other snapshot in the older half: history always spans the whole run, and
+    /// older snapshots end up at exponentially coarser intervals.
+    Thinned,
+}
+
+/// Snapshot capture configuration: how often, and what each snapshot contains.
+///
+/// `counts` are always computed over all entities. The rider/driver/trip lists can be dropped
+/// entirely or thinned to a stable `entity_sample_fraction` (the same entities are kept in
+/// every snapshot, so trails stay continuous).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Resource)]
 pub struct SimSnapshotConfig {
     pub interval_ms: u64,
     pub max_snapshots: usize,
+    pub retention: SnapshotRetention,
+    pub include_riders: bool,
+    pub include_drivers: bool,
+    pub include_trips: bool,
+    pub include_cell_aggregates: bool,
+    /// Fraction (0.0–1.0) of riders, drivers and trips recorded in each snapshot.
+    pub entity_sample_fraction: f64,
+    /// Record entity counts by archetype, event queue length and approximate heap usage.
+    pub include_world_growth: bool,
+    /// Every `keyframe_interval`-th snapshot lists all entities; the ones in between only list
+    /// entities whose components changed since the previous snapshot. 1 = every snapshot is full.
+    pub keyframe_interval: usize,
 }
 
 impl Default for SimSnapshotConfig {
@@ -265,10 +1275,68 @@ impl Default for SimSnapshotConfig {
         Self {
             interval_ms: 1000,
             max_snapshots: 10_000,
+            retention: SnapshotRetention::RingBuffer,
+            include_riders: true,
+            include_drivers: true,
+            include_trips: true,
+            include_cell_aggregates: false,
+            entity_sample_fraction: 1.0,
+            include_world_growth: false,
+            keyframe_interval: 1,
         }
     }
 }
 
+impl SimSnapshotConfig {
+    pub fn with_retention(mut self, retention: SnapshotRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Counts only (plus cell aggregates if enabled): no per-entity lists.
+    pub fn counts_only(mut self) -> Self {
+        self.include_riders = false;
+        self.include_drivers = false;
+        self.include_trips = false;
+        self
+    }
+
+    pub fn with_cell_aggregates(mut self) -> Self {
+        self.include_cell_aggregates = true;
+        self
+    }
+
+    /// Record a `WorldGrowthSample` with each snapshot (see `WorldGrowthMetrics`).
+    pub fn with_world_growth(mut self) -> Self {
+        self.include_world_growth = true;
+        self
+    }
+
+    /// Record delta snapshots between full keyframes taken every `interval` snapshots.
+    pub fn with_keyframe_interval(mut self, interval: usize) -> Self {
+        self.keyframe_interval = interval.max(1);
+        self
+    }
+
+    pub fn with_entity_sample_fraction(mut self, fraction: f64) -> Self {
+        self.entity_sample_fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Whether `entity` is in the sample. Deterministic per entity (hash of its bits).
+    pub fn samples(&self, entity: Entity) -> bool {
+        if self.entity_sample_fraction >= 1.0 {
+            return true;
+        }
+        // splitmix64 finalizer: spreads sequential entity ids uniformly over u64
+        let mut x = entity.to_bits().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^= x >> 31;
+        (x as f64 / u64::MAX as f64) < self.entity_sample_fraction
+    }
+}
+
 /// Rolling snapshot buffer.
 #[derive(Debug, Default, Resource)]
 pub struct SimSnapshots {
@@ -276,6 +1344,91 @@ pub struct SimSnapshots {
     pub last_snapshot_at: Option<u64>,
 }
 
+impl SimSnapshots {
+    /// Append a snapshot, then apply the retention policy so at most `max_snapshots` are kept.
+    pub fn push(&mut self, snapshot: SimSnapshot, config: &SimSnapshotConfig) {
+        self.last_snapshot_at = Some(snapshot.timestamp_ms);
+        self.snapshots.push_back(snapshot);
+        let max = config.max_snapshots.max(1);
+        match config.retention {
+            SnapshotRetention::RingBuffer => {
+                while self.snapshots.len() > max {
+                    self.pop_oldest();
+                }
+            }
+            SnapshotRetention::Thinned => {
+                while self.snapshots.len() > max {
+                    if max < 4 {
+                        self.pop_oldest();
+                        continue;
+                    }
+                    // Keep the first snapshot and every other one in the older half.
+                    let older = self.snapshots.len() / 2;
+                    let mut index = 0;
+                    self.snapshots = retain_snapshots(std::mem::take(&mut self.snapshots), |_| {
+                        let keep = index >= older || index % 2 == 0;
+                        index += 1;
+                        keep
+                    });
+                }
+            }
+        }
+    }
+
+    fn pop_oldest(&mut self) {
+        if let Some(oldest) = self.snapshots.pop_front() {
+            if let Some(next) = self.snapshots.front_mut() {
+                oldest.fold_into(next);
+            }
+        }
+    }
+
+    /// The full state at `index`: the snapshot itself if it is a keyframe, otherwise the
+    /// preceding keyframe with the deltas up to `index` applied.
+    pub fn reconstruct(&self, index: usize) -> Option<Cow<'_, SimSnapshot>> {
+        let target = self.snapshots.get(index)?;
+        if target.keyframe {
+            return Some(Cow::Borrowed(target));
+        }
+        let start = self
+            .snapshots
+            .range(..index)
+            .rposition(|snapshot| snapshot.keyframe)?;
+        let mut full = self.snapshots[start].clone();
+        for delta in self.snapshots.range(start + 1..=index) {
+            full.apply(delta);
+        }
+        Some(Cow::Owned(full))
+    }
+
+    /// Full state of the most recent snapshot.
+    pub fn latest(&self) -> Option<Cow<'_, SimSnapshot>> {
+        self.reconstruct(self.snapshots.len().checked_sub(1)?)
+    }
+
+    /// Every snapshot as full state, oldest first. Keyframes are borrowed; each delta is applied
+    /// once to a running copy.
+    pub fn iter_full(&self) -> impl Iterator<Item = Cow<'_, SimSnapshot>> {
+        let mut current: Option<SimSnapshot> = None;
+        self.snapshots
+            .iter()
+            .enumerate()
+            .map(move |(index, snapshot)| {
+                if snapshot.keyframe {
+                    let delta_follows = self
+                        .snapshots
+                        .get(index + 1)
+                        .is_some_and(|next| !next.keyframe);
+                    current = delta_follows.then(|| snapshot.clone());
+                    return Cow::Borrowed(snapshot);
+                }
+                let full = current.get_or_insert_with(|| snapshot.clone());
+                full.apply(snapshot);
+                Cow::Owned(full.clone())
+            })
+    }
+}
+
 impl SimCounts {
     pub fn add_rider(&mut self, state: RiderState) {
         match state {
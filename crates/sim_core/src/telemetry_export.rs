@@ -1,4 +1,4 @@
-//! Parquet export: write simulation data to Parquet files for analysis.
+//! Parquet and Arrow IPC export: write simulation data to files for analysis.
 //!
 //! Provides functions to export:
 //!
@@ -6,19 +6,61 @@
 //! - All trips (including in-progress and cancelled)
 //! - Time-series snapshot counts
 //! - Agent position snapshots over time
+//! - Per-driver utilization (time spent in each state)
+//! - Per-cell, per-hour aggregates (requests, outcomes, average surge, idle driver minutes)
+//! - Per-rider request funnel (stage timestamps and exit reason)
+//! - Matching diagnostics (scored candidate drivers per matching decision, when enabled)
+//! - Anonymized per-quote market features joined with request outcomes, for ML (when enabled)
+//! - Shadow matching: live vs. alternative algorithm choice per dispatch decision (when enabled)
 //!
 //! All exports use Arrow/Parquet format for efficient storage and compatibility
-//! with data analysis tools (Pandas, Polars, etc.).
+//! with data analysis tools (Pandas, Polars, etc.). Each table also has a `write_*_ipc`
+//! variant with the same schema that writes an Arrow IPC (Feather v2) file, which Python/R
+//! consumers can memory-map without a Parquet decode step. With the `sqlite` feature,
+//! `write_run_sqlite` writes all of a run's tables into a single SQLite database.
+//!
+//! For streaming consumers, `write_*_ndjson` writes completed trips, trips and full snapshots as
+//! newline-delimited JSON, optionally zstd-compressed ([`JsonCompression`]).
+//!
+//! Every Parquet / IPC writer takes an [`ExportProvenance`](crate::provenance::ExportProvenance)
+//! (usually `ExportProvenance::for_world(&world)`) and stamps it on the file as metadata and as
+//! trailing `<crate>_version`, `git_sha` and `scenario_hash` columns.
+//!
+//! Tables are built and written in record batches of at most [`max_batch_bytes`] (one Parquet
+//! row group per batch), so peak memory during export stays bounded for long runs. Lower it with
+//! [`set_max_batch_bytes`] on memory-constrained hosts.
 
 mod agent_positions;
+mod cell_hours;
 mod completed_trips;
+mod driver_utilization;
+mod funnel;
+mod match_diagnostics;
+mod ndjson;
+mod quote_features;
+mod shadow_matches;
 mod snapshot_counts;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod trips;
 mod utils;
 mod validate;
 
-pub use agent_positions::write_agent_positions_parquet;
-pub use completed_trips::write_completed_trips_parquet;
-pub use snapshot_counts::write_snapshot_counts_parquet;
-pub use trips::write_trips_parquet;
+pub use agent_positions::{write_agent_positions_ipc, write_agent_positions_parquet};
+pub use cell_hours::{write_cell_hours_ipc, write_cell_hours_parquet};
+pub use completed_trips::{write_completed_trips_ipc, write_completed_trips_parquet};
+pub use driver_utilization::{write_driver_utilization_ipc, write_driver_utilization_parquet};
+pub use funnel::{write_funnel_ipc, write_funnel_parquet};
+pub use match_diagnostics::{write_match_diagnostics_ipc, write_match_diagnostics_parquet};
+pub use ndjson::{
+    write_completed_trips_ndjson, write_ndjson, write_snapshots_ndjson, write_trips_ndjson,
+    JsonCompression,
+};
+pub use quote_features::{write_quote_features_ipc, write_quote_features_parquet};
+pub use shadow_matches::{write_shadow_matches_ipc, write_shadow_matches_parquet};
+pub use snapshot_counts::{write_snapshot_counts_ipc, write_snapshot_counts_parquet};
+#[cfg(feature = "sqlite")]
+pub use sqlite::write_run_sqlite;
+pub use trips::{write_trips_ipc, write_trips_parquet};
+pub use utils::{max_batch_bytes, set_max_batch_bytes, DEFAULT_MAX_BATCH_BYTES};
 pub use validate::validate_trip_timestamp_ordering;
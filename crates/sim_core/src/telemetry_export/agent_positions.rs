@@ -5,57 +5,82 @@ use std::sync::Arc;
 use arrow::array::{ArrayRef, Float64Array, UInt64Array, UInt8Array};
 use arrow::datatypes::Schema;
 
-use crate::telemetry::SimSnapshots;
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{GeoPoint, SimSnapshot, SimSnapshots};
 
 use super::utils::{
     cell_to_u64, driver_state_code, nullable_f64_field, rider_state_code, u64_field, u8_field,
-    write_record_batch, AGENT_DRIVER, AGENT_RIDER,
+    write_table, TableFormat, AGENT_DRIVER, AGENT_RIDER,
 };
 
 pub fn write_agent_positions_parquet<P: AsRef<Path>>(
     path: P,
     snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
 ) -> Result<(), Box<dyn Error>> {
-    let mut timestamp_ms = Vec::new();
-    let mut entity = Vec::new();
-    let mut agent_type = Vec::new();
-    let mut state = Vec::new();
-    let mut cell = Vec::new();
-    let mut lat = Vec::new();
-    let mut lng = Vec::new();
+    write_agent_positions(path, TableFormat::Parquet, snapshots, provenance)
+}
 
-    for snapshot in &snapshots.snapshots {
-        for rider in &snapshot.riders {
-            timestamp_ms.push(snapshot.timestamp_ms);
-            entity.push(rider.entity.to_bits());
-            agent_type.push(AGENT_RIDER);
-            state.push(rider_state_code(rider.state));
-            cell.push(cell_to_u64(rider.cell));
-            if let Some(geo) = rider.geo {
-                lat.push(Some(geo.lat));
-                lng.push(Some(geo.lng));
-            } else {
-                lat.push(None);
-                lng.push(None);
-            }
-        }
-        for driver in &snapshot.drivers {
-            timestamp_ms.push(snapshot.timestamp_ms);
-            entity.push(driver.entity.to_bits());
-            agent_type.push(AGENT_DRIVER);
-            state.push(driver_state_code(driver.state));
-            cell.push(cell_to_u64(driver.cell));
-            if let Some(geo) = driver.geo {
-                lat.push(Some(geo.lat));
-                lng.push(Some(geo.lng));
-            } else {
-                lat.push(None);
-                lng.push(None);
-            }
-        }
-    }
+/// Same schema as [`write_agent_positions_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_agent_positions_ipc<P: AsRef<Path>>(
+    path: P,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_agent_positions(path, TableFormat::Ipc, snapshots, provenance)
+}
+
+/// One rider or driver in one snapshot.
+struct AgentPositionRow {
+    timestamp_ms: u64,
+    entity: u64,
+    agent_type: u8,
+    state: u8,
+    cell: u64,
+    geo: Option<GeoPoint>,
+}
+
+/// Rows for one full snapshot; only one reconstructed snapshot is held at a time.
+fn snapshot_rows(snapshot: &SimSnapshot) -> Vec<AgentPositionRow> {
+    let riders = snapshot.riders.iter().map(|rider| AgentPositionRow {
+        timestamp_ms: snapshot.timestamp_ms,
+        entity: rider.entity.to_bits(),
+        agent_type: AGENT_RIDER,
+        state: rider_state_code(rider.state),
+        cell: cell_to_u64(rider.cell),
+        geo: rider.geo,
+    });
+    let drivers = snapshot.drivers.iter().map(|driver| AgentPositionRow {
+        timestamp_ms: snapshot.timestamp_ms,
+        entity: driver.entity.to_bits(),
+        agent_type: AGENT_DRIVER,
+        state: driver_state_code(driver.state),
+        cell: cell_to_u64(driver.cell),
+        geo: driver.geo,
+    });
+    riders.chain(drivers).collect()
+}
 
-    let schema = Schema::new(vec![
+fn write_agent_positions<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        agent_positions_schema(),
+        snapshots
+            .iter_full()
+            .flat_map(|snapshot| snapshot_rows(&snapshot)),
+        agent_positions_columns,
+        provenance,
+    )
+}
+
+fn agent_positions_schema() -> Schema {
+    Schema::new(vec![
         u64_field("timestamp_ms"),
         u64_field("entity"),
         u8_field("agent_type"),
@@ -63,9 +88,29 @@ pub fn write_agent_positions_parquet<P: AsRef<Path>>(
         u64_field("cell"),
         nullable_f64_field("lat"),
         nullable_f64_field("lng"),
-    ]);
+    ])
+}
+
+fn agent_positions_columns(rows: &[AgentPositionRow]) -> Vec<ArrayRef> {
+    let mut timestamp_ms = Vec::with_capacity(rows.len());
+    let mut entity = Vec::with_capacity(rows.len());
+    let mut agent_type = Vec::with_capacity(rows.len());
+    let mut state = Vec::with_capacity(rows.len());
+    let mut cell = Vec::with_capacity(rows.len());
+    let mut lat = Vec::with_capacity(rows.len());
+    let mut lng = Vec::with_capacity(rows.len());
 
-    let arrays: Vec<ArrayRef> = vec![
+    for row in rows {
+        timestamp_ms.push(row.timestamp_ms);
+        entity.push(row.entity);
+        agent_type.push(row.agent_type);
+        state.push(row.state);
+        cell.push(row.cell);
+        lat.push(row.geo.map(|geo| geo.lat));
+        lng.push(row.geo.map(|geo| geo.lng));
+    }
+
+    vec![
         Arc::new(UInt64Array::from(timestamp_ms)),
         Arc::new(UInt64Array::from(entity)),
         Arc::new(UInt8Array::from(agent_type)),
@@ -73,7 +118,5 @@ pub fn write_agent_positions_parquet<P: AsRef<Path>>(
         Arc::new(UInt64Array::from(cell)),
         Arc::new(Float64Array::from_iter(lat)),
         Arc::new(Float64Array::from_iter(lng)),
-    ];
-
-    write_record_batch(path, schema, arrays)
+    ]
 }
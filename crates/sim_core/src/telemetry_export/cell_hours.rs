@@ -0,0 +1,107 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, UInt64Array};
+use arrow::datatypes::Schema;
+use h3o::CellIndex;
+
+use crate::clock::ONE_HOUR_MS;
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{CellHourStats, SimTelemetry};
+
+use super::utils::{
+    cell_to_u64, f64_field, nullable_f64_field, u64_field, write_table, TableFormat,
+};
+
+/// One row per H3 cell and simulation hour with request, completion, cancellation and
+/// abandonment counts, quotes and average surge, and idle driver minutes; open idle intervals
+/// are closed at `end_ms`.
+pub fn write_cell_hours_parquet<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_cell_hours(path, TableFormat::Parquet, telemetry, end_ms, provenance)
+}
+
+/// Same schema as [`write_cell_hours_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_cell_hours_ipc<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_cell_hours(path, TableFormat::Ipc, telemetry, end_ms, provenance)
+}
+
+fn write_cell_hours<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    let rollup = telemetry.cell_hours_at(end_ms);
+    write_table(
+        path,
+        format,
+        cell_hours_schema(),
+        rollup
+            .iter()
+            .map(|(cell, hour, stats)| (cell, hour, *stats)),
+        cell_hours_columns,
+        provenance,
+    )
+}
+
+fn cell_hours_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("cell"),
+        u64_field("hour_start_ms"),
+        u64_field("requests"),
+        u64_field("completions"),
+        u64_field("cancellations"),
+        u64_field("abandonments"),
+        u64_field("quotes"),
+        nullable_f64_field("avg_surge_multiplier"),
+        f64_field("idle_driver_minutes"),
+    ])
+}
+
+fn cell_hours_columns(rows: &[(CellIndex, u64, CellHourStats)]) -> Vec<ArrayRef> {
+    let mut cells = Vec::with_capacity(rows.len());
+    let mut hour_start_ms = Vec::with_capacity(rows.len());
+    let mut requests = Vec::with_capacity(rows.len());
+    let mut completions = Vec::with_capacity(rows.len());
+    let mut cancellations = Vec::with_capacity(rows.len());
+    let mut abandonments = Vec::with_capacity(rows.len());
+    let mut quotes = Vec::with_capacity(rows.len());
+    let mut avg_surge_multiplier = Vec::with_capacity(rows.len());
+    let mut idle_driver_minutes = Vec::with_capacity(rows.len());
+
+    for (cell, hour, stats) in rows {
+        cells.push(cell_to_u64(*cell));
+        hour_start_ms.push(hour * ONE_HOUR_MS);
+        requests.push(stats.requests);
+        completions.push(stats.completions);
+        cancellations.push(stats.cancellations);
+        abandonments.push(stats.abandonments);
+        quotes.push(stats.quotes);
+        avg_surge_multiplier.push(stats.avg_surge_multiplier());
+        idle_driver_minutes.push(stats.idle_driver_minutes());
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(cells)),
+        Arc::new(UInt64Array::from(hour_start_ms)),
+        Arc::new(UInt64Array::from(requests)),
+        Arc::new(UInt64Array::from(completions)),
+        Arc::new(UInt64Array::from(cancellations)),
+        Arc::new(UInt64Array::from(abandonments)),
+        Arc::new(UInt64Array::from(quotes)),
+        Arc::new(Float64Array::from(avg_surge_multiplier)),
+        Arc::new(Float64Array::from(idle_driver_minutes)),
+    ]
+}
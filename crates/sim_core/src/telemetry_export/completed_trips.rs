@@ -2,36 +2,61 @@ use std::error::Error;
 use std::path::Path;
 use std::sync::Arc;
 
-use arrow::array::{ArrayRef, UInt64Array};
-use arrow::datatypes::Schema;
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema};
 
-use crate::telemetry::SimTelemetry;
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{CompletedTripRecord, SimTelemetry};
 
-use super::utils::{u64_field, write_record_batch};
+use super::utils::{f64_field, u64_field, write_table, TableFormat};
 
+/// Completed trips, one row per trip. When any trip was quoted from a vehicle class menu,
+/// nullable `vehicle_class` and `offered_vehicle_class` columns follow `idle_ms`. When any trip
+/// has a route geometry (see [`CompletedTripRecord::route_polyline`]), a nullable
+/// `route_polyline` column of encoded polylines comes last.
 pub fn write_completed_trips_parquet<P: AsRef<Path>>(
     path: P,
     telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
 ) -> Result<(), Box<dyn Error>> {
-    let mut trip_entities = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut rider_entities = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut driver_entities = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut completed_at = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut requested_at = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut matched_at = Vec::with_capacity(telemetry.completed_trips.len());
-    let mut pickup_at = Vec::with_capacity(telemetry.completed_trips.len());
-
-    for record in &telemetry.completed_trips {
-        trip_entities.push(record.trip_entity.to_bits());
-        rider_entities.push(record.rider_entity.to_bits());
-        driver_entities.push(record.driver_entity.to_bits());
-        completed_at.push(record.completed_at);
-        requested_at.push(record.requested_at);
-        matched_at.push(record.matched_at);
-        pickup_at.push(record.pickup_at);
-    }
+    write_completed_trips(path, TableFormat::Parquet, telemetry, provenance)
+}
+
+/// Same schema as [`write_completed_trips_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_completed_trips_ipc<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_completed_trips(path, TableFormat::Ipc, telemetry, provenance)
+}
+
+fn write_completed_trips<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    let with_classes = telemetry
+        .completed_trips
+        .iter()
+        .any(|record| record.vehicle_class.is_some());
+    let with_geometry = telemetry
+        .completed_trips
+        .iter()
+        .any(|record| record.route_polyline.is_some());
+    write_table(
+        path,
+        format,
+        completed_trips_schema(with_classes, with_geometry),
+        &telemetry.completed_trips,
+        |records| completed_trips_columns(records, with_classes, with_geometry),
+        provenance,
+    )
+}
 
-    let schema = Schema::new(vec![
+fn completed_trips_schema(with_classes: bool, with_geometry: bool) -> Schema {
+    let mut fields = vec![
         u64_field("trip_entity"),
         u64_field("rider_entity"),
         u64_field("driver_entity"),
@@ -39,9 +64,53 @@ pub fn write_completed_trips_parquet<P: AsRef<Path>>(
         u64_field("requested_at"),
         u64_field("matched_at"),
         u64_field("pickup_at"),
-    ]);
+        f64_field("pickup_km"),
+        f64_field("deadhead_km"),
+        f64_field("trip_km"),
+        u64_field("idle_ms"),
+    ];
+    if with_classes {
+        fields.push(Field::new("vehicle_class", DataType::Utf8, true));
+        fields.push(Field::new("offered_vehicle_class", DataType::Utf8, true));
+    }
+    if with_geometry {
+        fields.push(Field::new("route_polyline", DataType::Utf8, true));
+    }
+    Schema::new(fields)
+}
+
+fn completed_trips_columns(
+    records: &[&CompletedTripRecord],
+    with_classes: bool,
+    with_geometry: bool,
+) -> Vec<ArrayRef> {
+    let mut trip_entities = Vec::with_capacity(records.len());
+    let mut rider_entities = Vec::with_capacity(records.len());
+    let mut driver_entities = Vec::with_capacity(records.len());
+    let mut completed_at = Vec::with_capacity(records.len());
+    let mut requested_at = Vec::with_capacity(records.len());
+    let mut matched_at = Vec::with_capacity(records.len());
+    let mut pickup_at = Vec::with_capacity(records.len());
+    let mut pickup_km = Vec::with_capacity(records.len());
+    let mut deadhead_km = Vec::with_capacity(records.len());
+    let mut trip_km = Vec::with_capacity(records.len());
+    let mut idle_ms = Vec::with_capacity(records.len());
+
+    for record in records {
+        trip_entities.push(record.trip_entity.to_bits());
+        rider_entities.push(record.rider_entity.to_bits());
+        driver_entities.push(record.driver_entity.to_bits());
+        completed_at.push(record.completed_at);
+        requested_at.push(record.requested_at);
+        matched_at.push(record.matched_at);
+        pickup_at.push(record.pickup_at);
+        pickup_km.push(record.pickup_km);
+        deadhead_km.push(record.deadhead_km);
+        trip_km.push(record.trip_km);
+        idle_ms.push(record.idle_ms);
+    }
 
-    let arrays: Vec<ArrayRef> = vec![
+    let mut columns: Vec<ArrayRef> = vec![
         Arc::new(UInt64Array::from(trip_entities)),
         Arc::new(UInt64Array::from(rider_entities)),
         Arc::new(UInt64Array::from(driver_entities)),
@@ -49,7 +118,32 @@ pub fn write_completed_trips_parquet<P: AsRef<Path>>(
         Arc::new(UInt64Array::from(requested_at)),
         Arc::new(UInt64Array::from(matched_at)),
         Arc::new(UInt64Array::from(pickup_at)),
+        Arc::new(Float64Array::from(pickup_km)),
+        Arc::new(Float64Array::from(deadhead_km)),
+        Arc::new(Float64Array::from(trip_km)),
+        Arc::new(UInt64Array::from(idle_ms)),
     ];
-
-    write_record_batch(path, schema, arrays)
+    if with_classes {
+        columns.push(Arc::new(StringArray::from(
+            records
+                .iter()
+                .map(|record| record.vehicle_class.as_deref())
+                .collect::<Vec<_>>(),
+        )));
+        columns.push(Arc::new(StringArray::from(
+            records
+                .iter()
+                .map(|record| record.offered_vehicle_class.as_deref())
+                .collect::<Vec<_>>(),
+        )));
+    }
+    if with_geometry {
+        columns.push(Arc::new(StringArray::from(
+            records
+                .iter()
+                .map(|record| record.route_polyline.as_deref())
+                .collect::<Vec<_>>(),
+        )));
+    }
+    columns
 }
@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, UInt64Array, UInt8Array};
+use arrow::datatypes::Schema;
+
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{DriverUtilization, SimTelemetry};
+
+use super::utils::{driver_state_code, f64_field, u64_field, u8_field, write_table, TableFormat};
+
+/// One row per driver with time spent in each state; open intervals are closed at `end_ms`.
+pub fn write_driver_utilization_parquet<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_driver_utilization(path, TableFormat::Parquet, telemetry, end_ms, provenance)
+}
+
+/// Same schema as [`write_driver_utilization_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_driver_utilization_ipc<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_driver_utilization(path, TableFormat::Ipc, telemetry, end_ms, provenance)
+}
+
+fn write_driver_utilization<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    telemetry: &SimTelemetry,
+    end_ms: u64,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        driver_utilization_schema(),
+        telemetry.driver_utilization_at(end_ms),
+        driver_utilization_columns,
+        provenance,
+    )
+}
+
+fn driver_utilization_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("driver_entity"),
+        u64_field("first_seen_ms"),
+        u8_field("final_state"),
+        u64_field("idle_ms"),
+        u64_field("evaluating_ms"),
+        u64_field("en_route_ms"),
+        u64_field("on_trip_ms"),
+        u64_field("off_duty_ms"),
+        f64_field("utilization"),
+        f64_field("deadhead_share"),
+    ])
+}
+
+fn driver_utilization_columns(rows: &[DriverUtilization]) -> Vec<ArrayRef> {
+    let mut driver_entities = Vec::with_capacity(rows.len());
+    let mut first_seen_ms = Vec::with_capacity(rows.len());
+    let mut final_state = Vec::with_capacity(rows.len());
+    let mut idle_ms = Vec::with_capacity(rows.len());
+    let mut evaluating_ms = Vec::with_capacity(rows.len());
+    let mut en_route_ms = Vec::with_capacity(rows.len());
+    let mut on_trip_ms = Vec::with_capacity(rows.len());
+    let mut off_duty_ms = Vec::with_capacity(rows.len());
+    let mut utilization = Vec::with_capacity(rows.len());
+    let mut deadhead_share = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        driver_entities.push(row.driver.to_bits());
+        first_seen_ms.push(row.first_seen_ms);
+        final_state.push(driver_state_code(row.state));
+        idle_ms.push(row.idle_ms);
+        evaluating_ms.push(row.evaluating_ms);
+        en_route_ms.push(row.en_route_ms);
+        on_trip_ms.push(row.on_trip_ms);
+        off_duty_ms.push(row.off_duty_ms);
+        utilization.push(row.utilization());
+        deadhead_share.push(row.deadhead_share());
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(driver_entities)),
+        Arc::new(UInt64Array::from(first_seen_ms)),
+        Arc::new(UInt8Array::from(final_state)),
+        Arc::new(UInt64Array::from(idle_ms)),
+        Arc::new(UInt64Array::from(evaluating_ms)),
+        Arc::new(UInt64Array::from(en_route_ms)),
+        Arc::new(UInt64Array::from(on_trip_ms)),
+        Arc::new(UInt64Array::from(off_duty_ms)),
+        Arc::new(Float64Array::from(utilization)),
+        Arc::new(Float64Array::from(deadhead_share)),
+    ]
+}
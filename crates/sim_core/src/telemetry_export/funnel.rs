@@ -0,0 +1,102 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, UInt64Array, UInt8Array};
+use arrow::datatypes::Schema;
+
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{RiderFunnel, SimTelemetry};
+
+use super::utils::{
+    abandonment_reason_code, funnel_stage_code, nullable_u64_field, nullable_u8_field, u64_field,
+    u8_field, write_table, TableFormat,
+};
+
+/// One row per rider request with stage timestamps, the stage it exited at and why.
+pub fn write_funnel_parquet<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_funnel(path, TableFormat::Parquet, telemetry, provenance)
+}
+
+/// Same schema as [`write_funnel_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_funnel_ipc<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_funnel(path, TableFormat::Ipc, telemetry, provenance)
+}
+
+fn write_funnel<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        funnel_schema(),
+        telemetry.funnel.values(),
+        funnel_columns,
+        provenance,
+    )
+}
+
+fn funnel_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("rider_entity"),
+        u64_field("requested_at"),
+        nullable_u64_field("quoted_at"),
+        nullable_u64_field("quote_accepted_at"),
+        nullable_u64_field("matched_at"),
+        nullable_u64_field("pickup_at"),
+        nullable_u64_field("completed_at"),
+        nullable_u64_field("exited_at"),
+        u8_field("stage"),
+        nullable_u8_field("abandon_reason"),
+    ])
+}
+
+fn funnel_columns(rows: &[&RiderFunnel]) -> Vec<ArrayRef> {
+    let mut rider_entities = Vec::with_capacity(rows.len());
+    let mut requested_at = Vec::with_capacity(rows.len());
+    let mut quoted_at = Vec::with_capacity(rows.len());
+    let mut quote_accepted_at = Vec::with_capacity(rows.len());
+    let mut matched_at = Vec::with_capacity(rows.len());
+    let mut pickup_at = Vec::with_capacity(rows.len());
+    let mut completed_at = Vec::with_capacity(rows.len());
+    let mut exited_at = Vec::with_capacity(rows.len());
+    let mut stage = Vec::with_capacity(rows.len());
+    let mut abandon_reason = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        rider_entities.push(row.rider.to_bits());
+        requested_at.push(row.requested_at);
+        quoted_at.push(row.quoted_at);
+        quote_accepted_at.push(row.quote_accepted_at);
+        matched_at.push(row.matched_at);
+        pickup_at.push(row.pickup_at);
+        completed_at.push(row.completed_at);
+        exited_at.push(row.exited_at);
+        stage.push(funnel_stage_code(row.stage()));
+        abandon_reason.push(row.abandon_reason.map(abandonment_reason_code));
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(rider_entities)),
+        Arc::new(UInt64Array::from(requested_at)),
+        Arc::new(UInt64Array::from(quoted_at)),
+        Arc::new(UInt64Array::from(quote_accepted_at)),
+        Arc::new(UInt64Array::from(matched_at)),
+        Arc::new(UInt64Array::from(pickup_at)),
+        Arc::new(UInt64Array::from(completed_at)),
+        Arc::new(UInt64Array::from(exited_at)),
+        Arc::new(UInt8Array::from(stage)),
+        Arc::new(UInt8Array::from(abandon_reason)),
+    ]
+}
@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, UInt64Array};
+use arrow::datatypes::Schema;
+
+use crate::matching::MatchCandidate;
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{MatchDiagnosticRecord, MatchDiagnostics};
+
+use super::utils::{bool_field, f64_field, u64_field, write_table, TableFormat};
+
+/// One row per scored candidate per matching decision; `chosen` marks the assigned driver.
+///
+/// Decisions with no driver in radius have no candidates and produce no rows.
+pub fn write_match_diagnostics_parquet<P: AsRef<Path>>(
+    path: P,
+    diagnostics: &MatchDiagnostics,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_match_diagnostics(path, TableFormat::Parquet, diagnostics, provenance)
+}
+
+/// Same schema as [`write_match_diagnostics_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_match_diagnostics_ipc<P: AsRef<Path>>(
+    path: P,
+    diagnostics: &MatchDiagnostics,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_match_diagnostics(path, TableFormat::Ipc, diagnostics, provenance)
+}
+
+fn write_match_diagnostics<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    diagnostics: &MatchDiagnostics,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        match_diagnostics_schema(),
+        diagnostics
+            .records
+            .iter()
+            .enumerate()
+            .flat_map(|(index, record)| {
+                record
+                    .candidates
+                    .iter()
+                    .map(move |candidate| (index, record, candidate))
+            }),
+        match_diagnostics_columns,
+        provenance,
+    )
+}
+
+fn match_diagnostics_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("decision_index"),
+        u64_field("timestamp_ms"),
+        u64_field("rider_entity"),
+        bool_field("batch"),
+        u64_field("driver_entity"),
+        f64_field("pickup_distance_km"),
+        u64_field("pickup_eta_ms"),
+        f64_field("score"),
+        bool_field("chosen"),
+    ])
+}
+
+/// Candidates with their decision and its index in the log.
+fn match_diagnostics_columns(
+    rows: &[(usize, &MatchDiagnosticRecord, &MatchCandidate)],
+) -> Vec<ArrayRef> {
+    let row_count = rows.len();
+    let mut decision_index = Vec::with_capacity(row_count);
+    let mut timestamp_ms = Vec::with_capacity(row_count);
+    let mut rider_entities = Vec::with_capacity(row_count);
+    let mut batch = Vec::with_capacity(row_count);
+    let mut driver_entities = Vec::with_capacity(row_count);
+    let mut pickup_distance_km = Vec::with_capacity(row_count);
+    let mut pickup_eta_ms = Vec::with_capacity(row_count);
+    let mut score = Vec::with_capacity(row_count);
+    let mut chosen = Vec::with_capacity(row_count);
+
+    for &(index, record, candidate) in rows {
+        decision_index.push(index as u64);
+        timestamp_ms.push(record.timestamp_ms);
+        rider_entities.push(record.rider.to_bits());
+        batch.push(record.batch);
+        driver_entities.push(candidate.driver_entity.to_bits());
+        pickup_distance_km.push(candidate.pickup_distance_km);
+        pickup_eta_ms.push(candidate.pickup_eta_ms);
+        score.push(candidate.score);
+        chosen.push(record.chosen_driver == Some(candidate.driver_entity));
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(decision_index)),
+        Arc::new(UInt64Array::from(timestamp_ms)),
+        Arc::new(UInt64Array::from(rider_entities)),
+        Arc::new(BooleanArray::from(batch)),
+        Arc::new(UInt64Array::from(driver_entities)),
+        Arc::new(Float64Array::from(pickup_distance_km)),
+        Arc::new(UInt64Array::from(pickup_eta_ms)),
+        Arc::new(Float64Array::from(score)),
+        Arc::new(BooleanArray::from(chosen)),
+    ]
+}
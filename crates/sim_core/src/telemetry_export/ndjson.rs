@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{SimSnapshots, SimTelemetry};
+
+use super::trips::latest_trips;
+
+/// zstd level for [`JsonCompression::Zstd`]: the library default, a good speed / size trade-off.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Compression of an NDJSON export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonCompression {
+    #[default]
+    None,
+    /// zstd frame around the whole file (conventionally named `*.ndjson.zst`); readable with
+    /// `zstdcat`, `pandas.read_json(..., lines=True, compression="zstd")` or DuckDB.
+    Zstd,
+}
+
+impl JsonCompression {
+    /// [`Zstd`](Self::Zstd) for paths ending in `.zst`, otherwise uncompressed.
+    pub fn for_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension() {
+            Some(extension) if extension == "zst" => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+}
+
+/// Write `records` as newline-delimited JSON: one object per line, each with the known
+/// provenance entries (`<crate>_version`, `git_sha`, `scenario_hash`) added unless the record
+/// already has that field. Records are serialized one at a time, so memory stays flat.
+pub fn write_ndjson<P: AsRef<Path>, T: Serialize>(
+    path: P,
+    records: impl IntoIterator<Item = T>,
+    compression: JsonCompression,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    let file = BufWriter::new(File::create(path)?);
+    match compression {
+        JsonCompression::None => {
+            let mut file = write_lines(file, records, provenance)?;
+            file.flush()?;
+        }
+        JsonCompression::Zstd => {
+            let encoder = zstd::Encoder::new(file, ZSTD_LEVEL)?;
+            let mut file = write_lines(encoder, records, provenance)?.finish()?;
+            file.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_lines<W: Write, T: Serialize>(
+    mut writer: W,
+    records: impl IntoIterator<Item = T>,
+    provenance: &ExportProvenance,
+) -> Result<W, Box<dyn Error>> {
+    let provenance = provenance.metadata();
+    for record in records {
+        let mut line = serde_json::to_value(record)?;
+        if let Some(fields) = line.as_object_mut() {
+            for (key, value) in &provenance {
+                fields
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone().into());
+            }
+        }
+        serde_json::to_writer(&mut writer, &line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(writer)
+}
+
+/// Completed trips as NDJSON, one `CompletedTripRecord` per line.
+pub fn write_completed_trips_ndjson<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    compression: JsonCompression,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_ndjson(path, &telemetry.completed_trips, compression, provenance)
+}
+
+/// Latest state of every trip as NDJSON (the rows of [`write_trips_parquet`](super::write_trips_parquet)),
+/// one `TripSnapshot` per line.
+pub fn write_trips_ndjson<P: AsRef<Path>>(
+    path: P,
+    snapshots: &SimSnapshots,
+    compression: JsonCompression,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_ndjson(path, latest_trips(snapshots), compression, provenance)
+}
+
+/// Every retained snapshot as full state (deltas reconstructed), one `SimSnapshot` per line,
+/// oldest first.
+pub fn write_snapshots_ndjson<P: AsRef<Path>>(
+    path: P,
+    snapshots: &SimSnapshots,
+    compression: JsonCompression,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_ndjson(path, snapshots.iter_full(), compression, provenance)
+}
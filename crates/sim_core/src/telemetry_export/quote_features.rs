@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, UInt64Array, UInt8Array};
+use arrow::datatypes::Schema;
+use bevy_ecs::prelude::Entity;
+
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{QuoteFeatureLog, QuoteFeatureRecord, SimTelemetry};
+
+use super::utils::{
+    bool_field, cell_to_u64, f64_field, u64_field, u8_field, write_table, TableFormat,
+};
+
+/// One row per quote shown to a rider: market features at quote time joined with the
+/// request's outcome, for training demand / conversion models.
+///
+/// Rows are anonymized: `request_id` numbers requests in order of their first quote instead
+/// of exposing entity ids. `accepted` marks the quote the rider accepted (their last quote
+/// before acceptance); `matched` and `completed` are only set on that row.
+pub fn write_quote_features_parquet<P: AsRef<Path>>(
+    path: P,
+    log: &QuoteFeatureLog,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_quote_features(path, TableFormat::Parquet, log, telemetry, provenance)
+}
+
+/// Same schema as [`write_quote_features_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_quote_features_ipc<P: AsRef<Path>>(
+    path: P,
+    log: &QuoteFeatureLog,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_quote_features(path, TableFormat::Ipc, log, telemetry, provenance)
+}
+
+/// A quote with its anonymized request numbering and the request's outcome.
+struct QuoteFeatureRow<'a> {
+    request_id: u64,
+    quote_index: u64,
+    record: &'a QuoteFeatureRecord,
+    accepted: bool,
+    matched: bool,
+    completed: bool,
+}
+
+fn write_quote_features<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    log: &QuoteFeatureLog,
+    telemetry: &SimTelemetry,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    // The accepted quote is the rider's last one at or before acceptance
+    let mut accepted_quote: HashMap<Entity, usize> = HashMap::new();
+    for (index, record) in log.records.iter().enumerate() {
+        let accepted_at = telemetry
+            .funnel
+            .get(&record.rider)
+            .and_then(|funnel| funnel.quote_accepted_at);
+        if accepted_at.is_some_and(|accepted_at| record.quoted_at <= accepted_at) {
+            accepted_quote.insert(record.rider, index);
+        }
+    }
+
+    let mut request_ids: HashMap<Entity, (u64, u64)> = HashMap::new();
+    let rows = log.records.iter().enumerate().map(|(index, record)| {
+        let next_id = request_ids.len() as u64;
+        let (request_id, quotes) = request_ids.entry(record.rider).or_insert((next_id, 0));
+        let quote_index = *quotes;
+        *quotes += 1;
+
+        let accepted = accepted_quote.get(&record.rider) == Some(&index);
+        let funnel = telemetry.funnel.get(&record.rider).filter(|_| accepted);
+        QuoteFeatureRow {
+            request_id: *request_id,
+            quote_index,
+            record,
+            accepted,
+            matched: funnel.is_some_and(|funnel| funnel.matched_at.is_some()),
+            completed: funnel.is_some_and(|funnel| funnel.completed_at.is_some()),
+        }
+    });
+    write_table(
+        path,
+        format,
+        quote_features_schema(),
+        rows,
+        quote_features_columns,
+        provenance,
+    )
+}
+
+fn quote_features_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("request_id"),
+        u64_field("quote_index"),
+        u64_field("quoted_at"),
+        u8_field("local_hour"),
+        u8_field("local_weekday"),
+        u64_field("origin_cell"),
+        u64_field("destination_cell"),
+        f64_field("distance_km"),
+        f64_field("surge_multiplier"),
+        u64_field("local_demand"),
+        u64_field("local_supply"),
+        f64_field("quoted_fare"),
+        u64_field("quoted_eta_ms"),
+        bool_field("accepted"),
+        bool_field("matched"),
+        bool_field("completed"),
+    ])
+}
+
+fn quote_features_columns(rows: &[QuoteFeatureRow]) -> Vec<ArrayRef> {
+    let mut request_id = Vec::with_capacity(rows.len());
+    let mut quote_index = Vec::with_capacity(rows.len());
+    let mut quoted_at = Vec::with_capacity(rows.len());
+    let mut local_hour = Vec::with_capacity(rows.len());
+    let mut local_weekday = Vec::with_capacity(rows.len());
+    let mut origin_cell = Vec::with_capacity(rows.len());
+    let mut destination_cell = Vec::with_capacity(rows.len());
+    let mut distance_km = Vec::with_capacity(rows.len());
+    let mut surge_multiplier = Vec::with_capacity(rows.len());
+    let mut local_demand = Vec::with_capacity(rows.len());
+    let mut local_supply = Vec::with_capacity(rows.len());
+    let mut quoted_fare = Vec::with_capacity(rows.len());
+    let mut quoted_eta_ms = Vec::with_capacity(rows.len());
+    let mut accepted = Vec::with_capacity(rows.len());
+    let mut matched = Vec::with_capacity(rows.len());
+    let mut completed = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let record = row.record;
+        request_id.push(row.request_id);
+        quote_index.push(row.quote_index);
+        quoted_at.push(record.quoted_at);
+        local_hour.push(record.local_hour);
+        local_weekday.push(record.local_weekday);
+        origin_cell.push(cell_to_u64(record.origin));
+        destination_cell.push(cell_to_u64(record.destination));
+        distance_km.push(record.distance_km);
+        surge_multiplier.push(record.surge_multiplier);
+        local_demand.push(record.local_demand as u64);
+        local_supply.push(record.local_supply as u64);
+        quoted_fare.push(record.fare);
+        quoted_eta_ms.push(record.eta_ms);
+
+        accepted.push(row.accepted);
+        matched.push(row.matched);
+        completed.push(row.completed);
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(request_id)),
+        Arc::new(UInt64Array::from(quote_index)),
+        Arc::new(UInt64Array::from(quoted_at)),
+        Arc::new(UInt8Array::from(local_hour)),
+        Arc::new(UInt8Array::from(local_weekday)),
+        Arc::new(UInt64Array::from(origin_cell)),
+        Arc::new(UInt64Array::from(destination_cell)),
+        Arc::new(Float64Array::from(distance_km)),
+        Arc::new(Float64Array::from(surge_multiplier)),
+        Arc::new(UInt64Array::from(local_demand)),
+        Arc::new(UInt64Array::from(local_supply)),
+        Arc::new(Float64Array::from(quoted_fare)),
+        Arc::new(UInt64Array::from(quoted_eta_ms)),
+        Arc::new(BooleanArray::from(accepted)),
+        Arc::new(BooleanArray::from(matched)),
+        Arc::new(BooleanArray::from(completed)),
+    ]
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, UInt64Array};
+use arrow::datatypes::Schema;
+
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{ShadowMatchLog, ShadowMatchRecord};
+
+use super::utils::{
+    bool_field, nullable_f64_field, nullable_u64_field, u64_field, write_table, TableFormat,
+};
+
+/// One row per dispatch decision: the live algorithm's driver next to the shadow algorithm's.
+///
+/// Driver and pickup columns are null when that algorithm left the rider unmatched.
+pub fn write_shadow_matches_parquet<P: AsRef<Path>>(
+    path: P,
+    log: &ShadowMatchLog,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_shadow_matches(path, TableFormat::Parquet, log, provenance)
+}
+
+/// Same schema as [`write_shadow_matches_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_shadow_matches_ipc<P: AsRef<Path>>(
+    path: P,
+    log: &ShadowMatchLog,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_shadow_matches(path, TableFormat::Ipc, log, provenance)
+}
+
+fn write_shadow_matches<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    log: &ShadowMatchLog,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        shadow_matches_schema(),
+        log.records.iter().enumerate(),
+        shadow_matches_columns,
+        provenance,
+    )
+}
+
+fn shadow_matches_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("decision_index"),
+        u64_field("timestamp_ms"),
+        u64_field("rider_entity"),
+        bool_field("batch"),
+        nullable_u64_field("chosen_driver"),
+        nullable_f64_field("chosen_pickup_km"),
+        nullable_u64_field("shadow_driver"),
+        nullable_f64_field("shadow_pickup_km"),
+        bool_field("agrees"),
+    ])
+}
+
+/// Records with their index in the log.
+fn shadow_matches_columns(records: &[(usize, &ShadowMatchRecord)]) -> Vec<ArrayRef> {
+    let mut decision_index = Vec::with_capacity(records.len());
+    let mut timestamp_ms = Vec::with_capacity(records.len());
+    let mut rider_entities = Vec::with_capacity(records.len());
+    let mut batch = Vec::with_capacity(records.len());
+    let mut chosen_driver = Vec::with_capacity(records.len());
+    let mut chosen_pickup_km = Vec::with_capacity(records.len());
+    let mut shadow_driver = Vec::with_capacity(records.len());
+    let mut shadow_pickup_km = Vec::with_capacity(records.len());
+    let mut agrees = Vec::with_capacity(records.len());
+
+    for &(index, record) in records {
+        decision_index.push(index as u64);
+        timestamp_ms.push(record.timestamp_ms);
+        rider_entities.push(record.rider.to_bits());
+        batch.push(record.batch);
+        chosen_driver.push(record.chosen_driver.map(|driver| driver.to_bits()));
+        chosen_pickup_km.push(record.chosen_pickup_km);
+        shadow_driver.push(record.shadow_driver.map(|driver| driver.to_bits()));
+        shadow_pickup_km.push(record.shadow_pickup_km);
+        agrees.push(record.agrees());
+    }
+
+    vec![
+        Arc::new(UInt64Array::from(decision_index)),
+        Arc::new(UInt64Array::from(timestamp_ms)),
+        Arc::new(UInt64Array::from(rider_entities)),
+        Arc::new(BooleanArray::from(batch)),
+        Arc::new(UInt64Array::from(chosen_driver)),
+        Arc::new(Float64Array::from(chosen_pickup_km)),
+        Arc::new(UInt64Array::from(shadow_driver)),
+        Arc::new(Float64Array::from(shadow_pickup_km)),
+        Arc::new(BooleanArray::from(agrees)),
+    ]
+}
@@ -5,49 +5,46 @@ use std::sync::Arc;
 use arrow::array::{ArrayRef, UInt64Array};
 use arrow::datatypes::Schema;
 
-use crate::telemetry::SimSnapshots;
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{SimSnapshot, SimSnapshots};
 
-use super::utils::{u64_field, write_record_batch};
+use super::utils::{nullable_u64_field, u64_field, write_table, TableFormat};
 
 pub fn write_snapshot_counts_parquet<P: AsRef<Path>>(
     path: P,
     snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
 ) -> Result<(), Box<dyn Error>> {
-    let mut timestamp_ms = Vec::with_capacity(snapshots.snapshots.len());
-    let mut riders_browsing = Vec::with_capacity(snapshots.snapshots.len());
-    let mut riders_waiting = Vec::with_capacity(snapshots.snapshots.len());
-    let mut riders_in_transit = Vec::with_capacity(snapshots.snapshots.len());
-    let mut riders_completed = Vec::with_capacity(snapshots.snapshots.len());
-    let mut riders_cancelled = Vec::with_capacity(snapshots.snapshots.len());
-    let mut drivers_idle = Vec::with_capacity(snapshots.snapshots.len());
-    let mut drivers_evaluating = Vec::with_capacity(snapshots.snapshots.len());
-    let mut drivers_en_route = Vec::with_capacity(snapshots.snapshots.len());
-    let mut drivers_on_trip = Vec::with_capacity(snapshots.snapshots.len());
-    let mut drivers_off_duty = Vec::with_capacity(snapshots.snapshots.len());
-    let mut trips_en_route = Vec::with_capacity(snapshots.snapshots.len());
-    let mut trips_on_trip = Vec::with_capacity(snapshots.snapshots.len());
-    let mut trips_completed = Vec::with_capacity(snapshots.snapshots.len());
-    let mut trips_cancelled = Vec::with_capacity(snapshots.snapshots.len());
+    write_snapshot_counts(path, TableFormat::Parquet, snapshots, provenance)
+}
 
-    for snapshot in &snapshots.snapshots {
-        timestamp_ms.push(snapshot.timestamp_ms);
-        riders_browsing.push(snapshot.counts.riders_browsing as u64);
-        riders_waiting.push(snapshot.counts.riders_waiting as u64);
-        riders_in_transit.push(snapshot.counts.riders_in_transit as u64);
-        riders_completed.push(snapshot.counts.riders_completed as u64);
-        riders_cancelled.push(snapshot.counts.riders_cancelled as u64);
-        drivers_idle.push(snapshot.counts.drivers_idle as u64);
-        drivers_evaluating.push(snapshot.counts.drivers_evaluating as u64);
-        drivers_en_route.push(snapshot.counts.drivers_en_route as u64);
-        drivers_on_trip.push(snapshot.counts.drivers_on_trip as u64);
-        drivers_off_duty.push(snapshot.counts.drivers_off_duty as u64);
-        trips_en_route.push(snapshot.counts.trips_en_route as u64);
-        trips_on_trip.push(snapshot.counts.trips_on_trip as u64);
-        trips_completed.push(snapshot.counts.trips_completed as u64);
-        trips_cancelled.push(snapshot.counts.trips_cancelled as u64);
-    }
+/// Same schema as [`write_snapshot_counts_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_snapshot_counts_ipc<P: AsRef<Path>>(
+    path: P,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_snapshot_counts(path, TableFormat::Ipc, snapshots, provenance)
+}
+
+fn write_snapshot_counts<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        snapshot_counts_schema(),
+        &snapshots.snapshots,
+        snapshot_counts_columns,
+        provenance,
+    )
+}
 
-    let schema = Schema::new(vec![
+fn snapshot_counts_schema() -> Schema {
+    Schema::new(vec![
         u64_field("timestamp_ms"),
         u64_field("riders_browsing"),
         u64_field("riders_waiting"),
@@ -63,9 +60,55 @@ pub fn write_snapshot_counts_parquet<P: AsRef<Path>>(
         u64_field("trips_on_trip"),
         u64_field("trips_completed"),
         u64_field("trips_cancelled"),
-    ]);
+        nullable_u64_field("world_entities"),
+        nullable_u64_field("event_queue_len"),
+        nullable_u64_field("approx_heap_bytes"),
+    ])
+}
+
+fn snapshot_counts_columns(snapshots: &[&SimSnapshot]) -> Vec<ArrayRef> {
+    let mut timestamp_ms = Vec::with_capacity(snapshots.len());
+    let mut riders_browsing = Vec::with_capacity(snapshots.len());
+    let mut riders_waiting = Vec::with_capacity(snapshots.len());
+    let mut riders_in_transit = Vec::with_capacity(snapshots.len());
+    let mut riders_completed = Vec::with_capacity(snapshots.len());
+    let mut riders_cancelled = Vec::with_capacity(snapshots.len());
+    let mut drivers_idle = Vec::with_capacity(snapshots.len());
+    let mut drivers_evaluating = Vec::with_capacity(snapshots.len());
+    let mut drivers_en_route = Vec::with_capacity(snapshots.len());
+    let mut drivers_on_trip = Vec::with_capacity(snapshots.len());
+    let mut drivers_off_duty = Vec::with_capacity(snapshots.len());
+    let mut trips_en_route = Vec::with_capacity(snapshots.len());
+    let mut trips_on_trip = Vec::with_capacity(snapshots.len());
+    let mut trips_completed = Vec::with_capacity(snapshots.len());
+    let mut trips_cancelled = Vec::with_capacity(snapshots.len());
+    let mut world_entities = Vec::with_capacity(snapshots.len());
+    let mut event_queue_len = Vec::with_capacity(snapshots.len());
+    let mut approx_heap_bytes = Vec::with_capacity(snapshots.len());
 
-    let arrays: Vec<ArrayRef> = vec![
+    for snapshot in snapshots {
+        timestamp_ms.push(snapshot.timestamp_ms);
+        riders_browsing.push(snapshot.counts.riders_browsing as u64);
+        riders_waiting.push(snapshot.counts.riders_waiting as u64);
+        riders_in_transit.push(snapshot.counts.riders_in_transit as u64);
+        riders_completed.push(snapshot.counts.riders_completed as u64);
+        riders_cancelled.push(snapshot.counts.riders_cancelled as u64);
+        drivers_idle.push(snapshot.counts.drivers_idle as u64);
+        drivers_evaluating.push(snapshot.counts.drivers_evaluating as u64);
+        drivers_en_route.push(snapshot.counts.drivers_en_route as u64);
+        drivers_on_trip.push(snapshot.counts.drivers_on_trip as u64);
+        drivers_off_duty.push(snapshot.counts.drivers_off_duty as u64);
+        trips_en_route.push(snapshot.counts.trips_en_route as u64);
+        trips_on_trip.push(snapshot.counts.trips_on_trip as u64);
+        trips_completed.push(snapshot.counts.trips_completed as u64);
+        trips_cancelled.push(snapshot.counts.trips_cancelled as u64);
+        let growth = snapshot.world_growth.as_ref();
+        world_entities.push(growth.map(|growth| growth.entities));
+        event_queue_len.push(growth.map(|growth| growth.event_queue_len));
+        approx_heap_bytes.push(growth.map(|growth| growth.approx_heap_bytes()));
+    }
+
+    vec![
         Arc::new(UInt64Array::from(timestamp_ms)),
         Arc::new(UInt64Array::from(riders_browsing)),
         Arc::new(UInt64Array::from(riders_waiting)),
@@ -81,7 +124,8 @@ pub fn write_snapshot_counts_parquet<P: AsRef<Path>>(
         Arc::new(UInt64Array::from(trips_on_trip)),
         Arc::new(UInt64Array::from(trips_completed)),
         Arc::new(UInt64Array::from(trips_cancelled)),
-    ];
-
-    write_record_batch(path, schema, arrays)
+        Arc::new(UInt64Array::from(world_entities)),
+        Arc::new(UInt64Array::from(event_queue_len)),
+        Arc::new(UInt64Array::from(approx_heap_bytes)),
+    ]
 }
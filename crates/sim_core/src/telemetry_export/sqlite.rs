@@ -0,0 +1,271 @@
+//! SQLite export: one `.db` file per run for ad-hoc inspection with `sqlite3` or any SQL client.
+//!
+//! Tables: `completed_trips`, `trips`, `snapshot_counts`, `agent_positions`, `driver_ledger`
+//! (one earnings entry per completed trip), `events` (trip lifecycle events derived from trip
+//! timestamps) and `demand_events`. Entity ids and H3 cells are stored as INTEGER bit patterns,
+//! matching the Parquet exports; state columns use the same numeric codes.
+
+use std::error::Error;
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::telemetry::{SimSnapshots, SimTelemetry};
+
+use super::trips::latest_trips;
+use super::utils::{
+    cell_to_u64, driver_state_code, rider_state_code, trip_state_code, AGENT_DRIVER, AGENT_RIDER,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE completed_trips (
+    trip_entity INTEGER NOT NULL,
+    rider_entity INTEGER NOT NULL,
+    driver_entity INTEGER NOT NULL,
+    requested_at INTEGER NOT NULL,
+    matched_at INTEGER NOT NULL,
+    pickup_at INTEGER NOT NULL,
+    completed_at INTEGER NOT NULL,
+    fare REAL NOT NULL,
+    surge_impact REAL NOT NULL,
+    commission REAL NOT NULL,
+    driver_earnings REAL NOT NULL,
+    tolls REAL NOT NULL,
+    taxes_and_fees REAL NOT NULL,
+    wait_and_save INTEGER NOT NULL,
+    pickup_km REAL NOT NULL,
+    deadhead_km REAL NOT NULL,
+    trip_km REAL NOT NULL,
+    idle_ms INTEGER NOT NULL,
+    vehicle_class TEXT,
+    offered_vehicle_class TEXT
+);
+CREATE TABLE trips (
+    trip_entity INTEGER NOT NULL,
+    rider_entity INTEGER NOT NULL,
+    driver_entity INTEGER NOT NULL,
+    state INTEGER NOT NULL,
+    pickup_cell INTEGER NOT NULL,
+    dropoff_cell INTEGER NOT NULL,
+    pickup_distance_km_at_accept REAL NOT NULL,
+    requested_at INTEGER NOT NULL,
+    matched_at INTEGER NOT NULL,
+    pickup_at INTEGER,
+    dropoff_at INTEGER,
+    cancelled_at INTEGER
+);
+CREATE TABLE snapshot_counts (
+    timestamp_ms INTEGER NOT NULL,
+    riders_browsing INTEGER NOT NULL,
+    riders_waiting INTEGER NOT NULL,
+    riders_in_transit INTEGER NOT NULL,
+    riders_completed INTEGER NOT NULL,
+    riders_cancelled INTEGER NOT NULL,
+    drivers_idle INTEGER NOT NULL,
+    drivers_evaluating INTEGER NOT NULL,
+    drivers_en_route INTEGER NOT NULL,
+    drivers_on_trip INTEGER NOT NULL,
+    drivers_off_duty INTEGER NOT NULL,
+    trips_en_route INTEGER NOT NULL,
+    trips_on_trip INTEGER NOT NULL,
+    trips_completed INTEGER NOT NULL,
+    trips_cancelled INTEGER NOT NULL
+);
+CREATE TABLE agent_positions (
+    timestamp_ms INTEGER NOT NULL,
+    entity INTEGER NOT NULL,
+    agent_type INTEGER NOT NULL,
+    state INTEGER NOT NULL,
+    cell INTEGER NOT NULL,
+    lat REAL,
+    lng REAL
+);
+CREATE TABLE driver_ledger (
+    driver_entity INTEGER NOT NULL,
+    timestamp_ms INTEGER NOT NULL,
+    trip_entity INTEGER NOT NULL,
+    fare REAL NOT NULL,
+    commission REAL NOT NULL,
+    tolls REAL NOT NULL,
+    taxes_and_fees REAL NOT NULL,
+    amount REAL NOT NULL
+);
+CREATE TABLE events (
+    timestamp_ms INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    trip_entity INTEGER NOT NULL,
+    rider_entity INTEGER NOT NULL,
+    driver_entity INTEGER NOT NULL
+);
+CREATE TABLE demand_events (
+    name TEXT NOT NULL,
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    venue_cell INTEGER,
+    requests_planned INTEGER NOT NULL,
+    requests_spawned INTEGER NOT NULL
+);
+CREATE INDEX driver_ledger_driver ON driver_ledger (driver_entity);
+CREATE INDEX events_timestamp ON events (timestamp_ms);
+";
+
+/// Write a run's telemetry and snapshots into a fresh SQLite database at `path`
+/// (an existing file is replaced).
+pub fn write_run_sqlite<P: AsRef<Path>>(
+    path: P,
+    telemetry: &SimTelemetry,
+    snapshots: &SimSnapshots,
+) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+
+    {
+        let mut completed = tx.prepare(
+            "INSERT INTO completed_trips VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+        )?;
+        let mut ledger =
+            tx.prepare("INSERT INTO driver_ledger VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+        for record in &telemetry.completed_trips {
+            completed.execute(params![
+                record.trip_entity.to_bits() as i64,
+                record.rider_entity.to_bits() as i64,
+                record.driver_entity.to_bits() as i64,
+                record.requested_at as i64,
+                record.matched_at as i64,
+                record.pickup_at as i64,
+                record.completed_at as i64,
+                record.fare,
+                record.surge_impact,
+                record.commission,
+                record.driver_earnings,
+                record.tolls,
+                record.taxes_and_fees,
+                record.wait_and_save,
+                record.pickup_km,
+                record.deadhead_km,
+                record.trip_km,
+                record.idle_ms as i64,
+                record.vehicle_class,
+                record.offered_vehicle_class,
+            ])?;
+            ledger.execute(params![
+                record.driver_entity.to_bits() as i64,
+                record.completed_at as i64,
+                record.trip_entity.to_bits() as i64,
+                record.fare,
+                record.commission,
+                record.tolls,
+                record.taxes_and_fees,
+                record.driver_earnings,
+            ])?;
+        }
+
+        let mut trips = tx.prepare(
+            "INSERT INTO trips VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        )?;
+        let mut events = tx.prepare("INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5)")?;
+        for trip in latest_trips(snapshots) {
+            trips.execute(params![
+                trip.entity.to_bits() as i64,
+                trip.rider.to_bits() as i64,
+                trip.driver.to_bits() as i64,
+                trip_state_code(trip.state),
+                cell_to_u64(trip.pickup_cell) as i64,
+                cell_to_u64(trip.dropoff_cell) as i64,
+                trip.pickup_distance_km_at_accept,
+                trip.requested_at as i64,
+                trip.matched_at as i64,
+                trip.pickup_at.map(|t| t as i64),
+                trip.dropoff_at.map(|t| t as i64),
+                trip.cancelled_at.map(|t| t as i64),
+            ])?;
+            let lifecycle = [
+                ("requested", Some(trip.requested_at)),
+                ("matched", Some(trip.matched_at)),
+                ("pickup", trip.pickup_at),
+                ("dropoff", trip.dropoff_at),
+                ("cancelled", trip.cancelled_at),
+            ];
+            for (kind, at) in lifecycle {
+                if let Some(at) = at {
+                    events.execute(params![
+                        at as i64,
+                        kind,
+                        trip.entity.to_bits() as i64,
+                        trip.rider.to_bits() as i64,
+                        trip.driver.to_bits() as i64,
+                    ])?;
+                }
+            }
+        }
+
+        let mut counts = tx.prepare(
+            "INSERT INTO snapshot_counts VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        )?;
+        let mut positions =
+            tx.prepare("INSERT INTO agent_positions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+        for snapshot in snapshots.iter_full() {
+            let c = &snapshot.counts;
+            counts.execute(params![
+                snapshot.timestamp_ms as i64,
+                c.riders_browsing as i64,
+                c.riders_waiting as i64,
+                c.riders_in_transit as i64,
+                c.riders_completed as i64,
+                c.riders_cancelled as i64,
+                c.drivers_idle as i64,
+                c.drivers_evaluating as i64,
+                c.drivers_en_route as i64,
+                c.drivers_on_trip as i64,
+                c.drivers_off_duty as i64,
+                c.trips_en_route as i64,
+                c.trips_on_trip as i64,
+                c.trips_completed as i64,
+                c.trips_cancelled as i64,
+            ])?;
+            for rider in &snapshot.riders {
+                positions.execute(params![
+                    snapshot.timestamp_ms as i64,
+                    rider.entity.to_bits() as i64,
+                    AGENT_RIDER,
+                    rider_state_code(rider.state),
+                    cell_to_u64(rider.cell) as i64,
+                    rider.geo.map(|geo| geo.lat),
+                    rider.geo.map(|geo| geo.lng),
+                ])?;
+            }
+            for driver in &snapshot.drivers {
+                positions.execute(params![
+                    snapshot.timestamp_ms as i64,
+                    driver.entity.to_bits() as i64,
+                    AGENT_DRIVER,
+                    driver_state_code(driver.state),
+                    cell_to_u64(driver.cell) as i64,
+                    driver.geo.map(|geo| geo.lat),
+                    driver.geo.map(|geo| geo.lng),
+                ])?;
+            }
+        }
+
+        let mut demand_events =
+            tx.prepare("INSERT INTO demand_events VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+        for annotation in &telemetry.demand_events {
+            demand_events.execute(params![
+                annotation.name,
+                annotation.start_ms as i64,
+                annotation.end_ms as i64,
+                annotation.venue_cell.map(|cell| cell_to_u64(cell) as i64),
+                annotation.requests_planned as i64,
+                annotation.requests_spawned as i64,
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
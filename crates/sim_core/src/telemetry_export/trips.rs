@@ -6,11 +6,12 @@ use std::sync::Arc;
 use arrow::array::{ArrayRef, Float64Array, UInt64Array, UInt8Array};
 use arrow::datatypes::Schema;
 
+use crate::provenance::ExportProvenance;
 use crate::telemetry::{SimSnapshots, TripSnapshot};
 
 use super::utils::{
-    cell_to_u64, f64_field, nullable_u64_field, trip_state_code, u64_field, u8_field,
-    write_record_batch,
+    cell_to_u64, f64_field, nullable_u64_field, trip_state_code, u64_field, u8_field, write_table,
+    TableFormat,
 };
 
 /// Export all trips from snapshots (same data as shown in UI trip table).
@@ -18,7 +19,39 @@ use super::utils::{
 pub fn write_trips_parquet<P: AsRef<Path>>(
     path: P,
     snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
 ) -> Result<(), Box<dyn Error>> {
+    write_trips(path, TableFormat::Parquet, snapshots, provenance)
+}
+
+/// Same schema as [`write_trips_parquet`], written as an Arrow IPC (Feather v2) file.
+pub fn write_trips_ipc<P: AsRef<Path>>(
+    path: P,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_trips(path, TableFormat::Ipc, snapshots, provenance)
+}
+
+fn write_trips<P: AsRef<Path>>(
+    path: P,
+    format: TableFormat,
+    snapshots: &SimSnapshots,
+    provenance: &ExportProvenance,
+) -> Result<(), Box<dyn Error>> {
+    write_table(
+        path,
+        format,
+        trips_schema(),
+        latest_trips(snapshots),
+        trips_columns,
+        provenance,
+    )
+}
+
+/// Latest snapshot of every trip seen across all snapshots (deltas list every trip that changed,
+/// so no reconstruction is needed).
+pub(super) fn latest_trips(snapshots: &SimSnapshots) -> Vec<TripSnapshot> {
     let mut trips_map: HashMap<u64, (u64, TripSnapshot)> = HashMap::new();
 
     for snapshot in &snapshots.snapshots {
@@ -36,20 +69,41 @@ pub fn write_trips_parquet<P: AsRef<Path>>(
         }
     }
 
-    let mut trip_entities = Vec::with_capacity(trips_map.len());
-    let mut rider_entities = Vec::with_capacity(trips_map.len());
-    let mut driver_entities = Vec::with_capacity(trips_map.len());
-    let mut state = Vec::with_capacity(trips_map.len());
-    let mut pickup_cell = Vec::with_capacity(trips_map.len());
-    let mut dropoff_cell = Vec::with_capacity(trips_map.len());
-    let mut pickup_distance_km_at_accept = Vec::with_capacity(trips_map.len());
-    let mut requested_at = Vec::with_capacity(trips_map.len());
-    let mut matched_at = Vec::with_capacity(trips_map.len());
-    let mut pickup_at = Vec::with_capacity(trips_map.len());
-    let mut dropoff_at = Vec::with_capacity(trips_map.len());
-    let mut cancelled_at = Vec::with_capacity(trips_map.len());
+    trips_map.into_values().map(|(_, trip)| trip).collect()
+}
+
+fn trips_schema() -> Schema {
+    Schema::new(vec![
+        u64_field("trip_entity"),
+        u64_field("rider_entity"),
+        u64_field("driver_entity"),
+        u8_field("state"),
+        u64_field("pickup_cell"),
+        u64_field("dropoff_cell"),
+        f64_field("pickup_distance_km_at_accept"),
+        u64_field("requested_at"),
+        u64_field("matched_at"),
+        nullable_u64_field("pickup_at"),
+        nullable_u64_field("dropoff_at"),
+        nullable_u64_field("cancelled_at"),
+    ])
+}
+
+fn trips_columns(trips: &[TripSnapshot]) -> Vec<ArrayRef> {
+    let mut trip_entities = Vec::with_capacity(trips.len());
+    let mut rider_entities = Vec::with_capacity(trips.len());
+    let mut driver_entities = Vec::with_capacity(trips.len());
+    let mut state = Vec::with_capacity(trips.len());
+    let mut pickup_cell = Vec::with_capacity(trips.len());
+    let mut dropoff_cell = Vec::with_capacity(trips.len());
+    let mut pickup_distance_km_at_accept = Vec::with_capacity(trips.len());
+    let mut requested_at = Vec::with_capacity(trips.len());
+    let mut matched_at = Vec::with_capacity(trips.len());
+    let mut pickup_at = Vec::with_capacity(trips.len());
+    let mut dropoff_at = Vec::with_capacity(trips.len());
+    let mut cancelled_at = Vec::with_capacity(trips.len());
 
-    for (_, trip) in trips_map.values() {
+    for trip in trips {
         trip_entities.push(trip.entity.to_bits());
         rider_entities.push(trip.rider.to_bits());
         driver_entities.push(trip.driver.to_bits());
@@ -64,22 +118,7 @@ pub fn write_trips_parquet<P: AsRef<Path>>(
         cancelled_at.push(trip.cancelled_at);
     }
 
-    let schema = Schema::new(vec![
-        u64_field("trip_entity"),
-        u64_field("rider_entity"),
-        u64_field("driver_entity"),
-        u8_field("state"),
-        u64_field("pickup_cell"),
-        u64_field("dropoff_cell"),
-        f64_field("pickup_distance_km_at_accept"),
-        u64_field("requested_at"),
-        u64_field("matched_at"),
-        nullable_u64_field("pickup_at"),
-        nullable_u64_field("dropoff_at"),
-        nullable_u64_field("cancelled_at"),
-    ]);
-
-    let arrays: Vec<ArrayRef> = vec![
+    vec![
         Arc::new(UInt64Array::from(trip_entities)),
         Arc::new(UInt64Array::from(rider_entities)),
         Arc::new(UInt64Array::from(driver_entities)),
@@ -92,7 +131,5 @@ pub fn write_trips_parquet<P: AsRef<Path>>(
         Arc::new(UInt64Array::from_iter(pickup_at.iter().copied())),
         Arc::new(UInt64Array::from_iter(dropoff_at.iter().copied())),
         Arc::new(UInt64Array::from_iter(cancelled_at.iter().copied())),
-    ];
-
-    write_record_batch(path, schema, arrays)
+    ]
 }
@@ -1,14 +1,37 @@
 use std::error::Error;
 use std::fs::File;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-use arrow::array::ArrayRef;
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{ArrayRef, DictionaryArray, StringArray, UInt8Array};
+use arrow::datatypes::{DataType, Field, Schema, UInt8Type};
+use arrow::ipc::writer::FileWriter;
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
 
-use crate::telemetry::{DriverState, RiderState, TripState};
+use crate::provenance::ExportProvenance;
+use crate::telemetry::{DriverState, FunnelStage, RiderAbandonmentReason, RiderState, TripState};
+
+/// Default for [`set_max_batch_bytes`]: 64 MiB.
+pub const DEFAULT_MAX_BATCH_BYTES: usize = 64 * 1024 * 1024;
+
+static MAX_BATCH_BYTES: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BATCH_BYTES);
+
+/// Cap the memory a Parquet / IPC export buffers before writing a batch (one Parquet row group
+/// or IPC record batch), for this process. Tables are written in as many batches as needed, so
+/// exports of very large runs hold one batch of rows, its columns and its encoded row group at a
+/// time instead of whole tables. The estimate is approximate: it counts fixed column widths.
+pub fn set_max_batch_bytes(bytes: usize) {
+    MAX_BATCH_BYTES.store(bytes.max(1), Ordering::Relaxed);
+}
+
+/// Current export batch memory cap (see [`set_max_batch_bytes`]).
+pub fn max_batch_bytes() -> usize {
+    MAX_BATCH_BYTES.load(Ordering::Relaxed)
+}
 
 pub(super) const AGENT_RIDER: u8 = 0;
 pub(super) const AGENT_DRIVER: u8 = 1;
@@ -25,6 +48,14 @@ pub(super) fn u8_field(name: &'static str) -> Field {
     Field::new(name, DataType::UInt8, false)
 }
 
+pub(super) fn nullable_u8_field(name: &'static str) -> Field {
+    Field::new(name, DataType::UInt8, true)
+}
+
+pub(super) fn bool_field(name: &'static str) -> Field {
+    Field::new(name, DataType::Boolean, false)
+}
+
 pub(super) fn f64_field(name: &'static str) -> Field {
     Field::new(name, DataType::Float64, false)
 }
@@ -33,17 +64,120 @@ pub(super) fn nullable_f64_field(name: &'static str) -> Field {
     Field::new(name, DataType::Float64, true)
 }
 
-pub(super) fn write_record_batch<P: AsRef<Path>>(
+/// Add the provenance columns (dictionary-encoded, one value repeated on every row) to the
+/// schema and attach the provenance as schema metadata.
+fn provenance_schema(schema: Schema, provenance: &ExportProvenance) -> Arc<Schema> {
+    let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+    for (key, _) in provenance.entries() {
+        let dictionary = DataType::Dictionary(Box::new(DataType::UInt8), Box::new(DataType::Utf8));
+        fields.push(Field::new(key, dictionary, true));
+    }
+    Arc::new(Schema::new(fields).with_metadata(provenance.metadata().into_iter().collect()))
+}
+
+fn provenance_columns(rows: usize, provenance: &ExportProvenance) -> Vec<ArrayRef> {
+    provenance
+        .entries()
+        .into_iter()
+        .map(|(_, value)| {
+            let keys = UInt8Array::from(vec![value.as_ref().map(|_| 0); rows]);
+            let values = StringArray::from(vec![value.unwrap_or_default()]);
+            Arc::new(DictionaryArray::<UInt8Type>::new(keys, Arc::new(values))) as ArrayRef
+        })
+        .collect()
+}
+
+/// In-memory bytes of one value of a column.
+fn column_width(data_type: &DataType) -> usize {
+    match data_type {
+        DataType::UInt8 | DataType::Boolean | DataType::Dictionary(..) => 1,
+        _ => 8,
+    }
+}
+
+/// Rows per batch so the buffered rows, their column arrays and the encoded batch fit in
+/// `max_bytes`.
+fn batch_rows(schema: &Schema, row_bytes: usize, max_bytes: usize) -> usize {
+    let column_bytes: usize = schema
+        .fields()
+        .iter()
+        .map(|field| column_width(field.data_type()))
+        .sum();
+    (max_bytes / (row_bytes + 2 * column_bytes).max(1)).max(1)
+}
+
+/// File format of an exported table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum TableFormat {
+    Parquet,
+    /// Arrow IPC file (Feather v2), which readers can memory-map.
+    Ipc,
+}
+
+enum BatchWriter {
+    Parquet(ArrowWriter<File>),
+    Ipc(FileWriter<File>),
+}
+
+/// Write `rows` as a table, converting and writing them in batches bounded by
+/// [`max_batch_bytes`]. A table without rows is written as one empty batch.
+pub(super) fn write_table<P: AsRef<Path>, R>(
     path: P,
+    format: TableFormat,
     schema: Schema,
-    arrays: Vec<ArrayRef>,
+    rows: impl IntoIterator<Item = R>,
+    columns: impl Fn(&[R]) -> Vec<ArrayRef>,
+    provenance: &ExportProvenance,
 ) -> Result<(), Box<dyn Error>> {
-    let schema = Arc::new(schema);
-    let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+    let schema = provenance_schema(schema, provenance);
+    let batch_rows = batch_rows(&schema, size_of::<R>(), max_batch_bytes());
     let file = File::create(path)?;
-    let mut writer = ArrowWriter::try_new(file, schema, None)?;
-    writer.write(&batch)?;
-    writer.close()?;
+    let mut writer = match format {
+        TableFormat::Parquet => {
+            let key_value_metadata = provenance
+                .metadata()
+                .into_iter()
+                .map(|(key, value)| KeyValue::new(key, value))
+                .collect();
+            let props = WriterProperties::builder()
+                .set_key_value_metadata(Some(key_value_metadata))
+                .set_max_row_group_size(batch_rows)
+                .build();
+            BatchWriter::Parquet(ArrowWriter::try_new(file, schema.clone(), Some(props))?)
+        }
+        TableFormat::Ipc => BatchWriter::Ipc(FileWriter::try_new(file, &schema)?),
+    };
+
+    let mut write_batch = |rows: &[R]| -> Result<(), Box<dyn Error>> {
+        let mut arrays = columns(rows);
+        arrays.extend(provenance_columns(rows.len(), provenance));
+        let batch = RecordBatch::try_new(schema.clone(), arrays)?;
+        match &mut writer {
+            BatchWriter::Parquet(writer) => writer.write(&batch)?,
+            BatchWriter::Ipc(writer) => writer.write(&batch)?,
+        }
+        Ok(())
+    };
+    let mut buffer = Vec::new();
+    let mut written = false;
+    for row in rows {
+        buffer.push(row);
+        if buffer.len() >= batch_rows {
+            write_batch(&buffer)?;
+            buffer.clear();
+            written = true;
+        }
+    }
+    if !buffer.is_empty() || !written {
+        write_batch(&buffer)?;
+    }
+
+    match writer {
+        BatchWriter::Parquet(writer) => {
+            writer.close()?;
+        }
+        BatchWriter::Ipc(mut writer) => writer.finish()?,
+    }
     Ok(())
 }
 
@@ -79,3 +213,24 @@ pub(super) fn trip_state_code(state: TripState) -> u8 {
         TripState::Cancelled => 3,
     }
 }
+
+pub(super) fn funnel_stage_code(stage: FunnelStage) -> u8 {
+    match stage {
+        FunnelStage::Requested => 0,
+        FunnelStage::Quoted => 1,
+        FunnelStage::QuoteAccepted => 2,
+        FunnelStage::Matched => 3,
+        FunnelStage::PickedUp => 4,
+        FunnelStage::Completed => 5,
+    }
+}
+
+pub(super) fn abandonment_reason_code(reason: RiderAbandonmentReason) -> u8 {
+    match reason {
+        RiderAbandonmentReason::QuotePriceTooHigh => 0,
+        RiderAbandonmentReason::QuoteEtaTooLong => 1,
+        RiderAbandonmentReason::QuoteStochasticRejection => 2,
+        RiderAbandonmentReason::QuoteExpired => 3,
+        RiderAbandonmentReason::PickupTimeout => 4,
+    }
+}
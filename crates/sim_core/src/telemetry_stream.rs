@@ -0,0 +1,309 @@
+//! Streaming telemetry: publish trip lifecycle events and snapshots while a run is in progress.
+//!
+//! Insert a [`TelemetryStream`] resource (after `build_scenario`) wrapping any
+//! [`StreamPublisher`]. [`stream_telemetry_system`] then publishes one JSON [`StreamMessage`] per
+//! processed lifecycle event to `topics.events`, and the counts of every new snapshot to
+//! `topics.snapshots` (with every agent's position when built `with_agent_positions`, for map
+//! viewers). Broker clients are feature-gated: `kafka` enables `KafkaPublisher`,
+//! `nats` enables `NatsPublisher`. With `websocket`, `WebSocketPublisher` serves the same
+//! messages to live dashboards over WebSocket. [`RecordingPublisher`] keeps messages in memory.
+//!
+//! Publishing is best-effort: failures are counted and reported once, never abort the run.
+
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaPublisher;
+#[cfg(feature = "nats")]
+pub use nats::NatsPublisher;
+#[cfg(feature = "websocket")]
+pub use websocket::WebSocketPublisher;
+
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+
+use bevy_ecs::prelude::{Query, Res, ResMut, Resource};
+use h3o::{CellIndex, LatLng};
+use serde::Serialize;
+
+use crate::clock::{CurrentEvent, EventKind, EventSubject};
+use crate::ecs::Trip;
+use crate::telemetry::{GeoPoint, SimCounts, SimSnapshot, SimSnapshots};
+
+pub type PublishError = Box<dyn Error + Send + Sync>;
+
+/// Destination for serialized telemetry messages (a Kafka topic, NATS subject, ...).
+pub trait StreamPublisher: Send + Sync {
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), PublishError>;
+
+    /// Flush buffered messages. Default: nothing buffered.
+    fn flush(&mut self) -> Result<(), PublishError> {
+        Ok(())
+    }
+}
+
+/// Topic (Kafka) or subject (NATS) names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamTopics {
+    pub events: String,
+    pub snapshots: String,
+}
+
+impl Default for StreamTopics {
+    fn default() -> Self {
+        Self {
+            events: "sim.events".to_string(),
+            snapshots: "sim.snapshots".to_string(),
+        }
+    }
+}
+
+/// One published message, serialized as JSON with a `type` tag.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamMessage {
+    /// A processed lifecycle event. Entity ids are `Entity::to_bits()`.
+    TripEvent {
+        timestamp_ms: u64,
+        kind: String,
+        rider: Option<u64>,
+        driver: Option<u64>,
+        trip: Option<u64>,
+    },
+    Snapshot {
+        timestamp_ms: u64,
+        counts: SimCounts,
+        /// Riders and drivers in the snapshot; only with `TelemetryStream::with_agent_positions`.
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        agents: Vec<StreamAgent>,
+    },
+}
+
+/// One rider or driver position in a snapshot message.
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamAgent {
+    /// `rider` or `driver`.
+    pub kind: &'static str,
+    /// `Entity::to_bits()`.
+    pub id: u64,
+    /// State name, e.g. `Waiting` or `EnRoute`.
+    pub state: String,
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// Agents of a snapshot, at their exact position or else their cell center.
+fn snapshot_agents(snapshot: &SimSnapshot) -> Vec<StreamAgent> {
+    let position = |geo: Option<GeoPoint>, cell: CellIndex| {
+        geo.map(|geo| (geo.lat, geo.lng)).unwrap_or_else(|| {
+            let center = LatLng::from(cell);
+            (center.lat(), center.lng())
+        })
+    };
+    let riders = snapshot.riders.iter().map(|rider| {
+        let (lat, lng) = position(rider.geo, rider.cell);
+        StreamAgent {
+            kind: "rider",
+            id: rider.entity.to_bits(),
+            state: format!("{:?}", rider.state),
+            lat,
+            lng,
+        }
+    });
+    let drivers = snapshot.drivers.iter().map(|driver| {
+        let (lat, lng) = position(driver.geo, driver.cell);
+        StreamAgent {
+            kind: "driver",
+            id: driver.entity.to_bits(),
+            state: format!("{:?}", driver.state),
+            lat,
+            lng,
+        }
+    });
+    riders.chain(drivers).collect()
+}
+
+/// Events published on the events topic: the rider request → match → trip → completion funnel.
+pub fn is_lifecycle_event(kind: EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::QuoteAccepted
+            | EventKind::MatchAccepted
+            | EventKind::MatchRejected
+            | EventKind::TripStarted
+            | EventKind::TripCompleted
+            | EventKind::RiderCancel
+    )
+}
+
+/// Streaming sink state. Not inserted by `build_scenario`; streaming is off unless present.
+#[derive(Resource)]
+pub struct TelemetryStream {
+    publisher: Box<dyn StreamPublisher>,
+    pub topics: StreamTopics,
+    /// Include agent positions in snapshot messages.
+    pub agent_positions: bool,
+    last_snapshot_at: Option<u64>,
+    pub published: u64,
+    pub failed: u64,
+}
+
+impl TelemetryStream {
+    pub fn new(publisher: Box<dyn StreamPublisher>) -> Self {
+        Self {
+            publisher,
+            topics: StreamTopics::default(),
+            agent_positions: false,
+            last_snapshot_at: None,
+            published: 0,
+            failed: 0,
+        }
+    }
+
+    pub fn with_topics(mut self, topics: StreamTopics) -> Self {
+        self.topics = topics;
+        self
+    }
+
+    /// Add every rider's and driver's position to snapshot messages (for live map viewers).
+    pub fn with_agent_positions(mut self) -> Self {
+        self.agent_positions = true;
+        self
+    }
+
+    fn send(&mut self, message: &StreamMessage) {
+        let topic = match message {
+            StreamMessage::TripEvent { .. } => &self.topics.events,
+            StreamMessage::Snapshot { .. } => &self.topics.snapshots,
+        };
+        let result = serde_json::to_vec(message)
+            .map_err(PublishError::from)
+            .and_then(|payload| self.publisher.publish(topic, &payload));
+        match result {
+            Ok(()) => self.published += 1,
+            Err(err) => {
+                if self.failed == 0 {
+                    eprintln!("WARNING: telemetry stream publish to {topic} failed: {err}");
+                }
+                self.failed += 1;
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<(), PublishError> {
+        self.publisher.flush()
+    }
+}
+
+/// In-memory publisher; clones share the same message log (handy for tests and debugging).
+#[derive(Debug, Clone, Default)]
+pub struct RecordingPublisher {
+    messages: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
+}
+
+impl RecordingPublisher {
+    /// Recorded (topic, payload) pairs in publish order.
+    pub fn messages(&self) -> Vec<(String, Vec<u8>)> {
+        self.messages.lock().map(|m| m.clone()).unwrap_or_default()
+    }
+}
+
+impl StreamPublisher for RecordingPublisher {
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), PublishError> {
+        self.messages
+            .lock()
+            .map_err(|_| PublishError::from("recording publisher poisoned"))?
+            .push((topic.to_string(), payload.to_vec()));
+        Ok(())
+    }
+}
+
+/// Publish the current lifecycle event and any snapshot captured since the last call.
+pub fn stream_telemetry_system(
+    event: Res<CurrentEvent>,
+    stream: Option<ResMut<TelemetryStream>>,
+    snapshots: Option<Res<SimSnapshots>>,
+    trips: Query<&Trip>,
+) {
+    let Some(mut stream) = stream else {
+        return;
+    };
+
+    if is_lifecycle_event(event.0.kind) {
+        let (rider, driver, trip) = match event.0.subject {
+            Some(EventSubject::Rider(rider)) => (Some(rider.to_bits()), None, None),
+            Some(EventSubject::Driver(driver)) => (None, Some(driver.to_bits()), None),
+            Some(EventSubject::Trip(entity)) => match trips.get(entity) {
+                Ok(trip) => (
+                    Some(trip.rider.to_bits()),
+                    Some(trip.driver.to_bits()),
+                    Some(entity.to_bits()),
+                ),
+                Err(_) => (None, None, Some(entity.to_bits())),
+            },
+            None => (None, None, None),
+        };
+        let message = StreamMessage::TripEvent {
+            timestamp_ms: event.0.timestamp,
+            kind: format!("{:?}", event.0.kind),
+            rider,
+            driver,
+            trip,
+        };
+        stream.send(&message);
+    }
+
+    let Some(snapshots) = snapshots else {
+        return;
+    };
+    if snapshots.last_snapshot_at == stream.last_snapshot_at {
+        return;
+    }
+    stream.last_snapshot_at = snapshots.last_snapshot_at;
+    if let Some(snapshot) = snapshots.snapshots.back() {
+        let message = StreamMessage::Snapshot {
+            timestamp_ms: snapshot.timestamp_ms,
+            counts: snapshot.counts.clone(),
+            agents: if stream.agent_positions {
+                snapshots
+                    .latest()
+                    .map(|full| snapshot_agents(&full))
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            },
+        };
+        stream.send(&message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_serializes_tagged_json_to_topic() {
+        let recorder = RecordingPublisher::default();
+        let mut stream = TelemetryStream::new(Box::new(recorder.clone()));
+        stream.send(&StreamMessage::TripEvent {
+            timestamp_ms: 5,
+            kind: "TripCompleted".to_string(),
+            rider: Some(1),
+            driver: Some(2),
+            trip: Some(3),
+        });
+
+        let messages = recorder.messages();
+        assert_eq!(stream.published, 1);
+        assert_eq!(messages[0].0, "sim.events");
+        let json: serde_json::Value = serde_json::from_slice(&messages[0].1).expect("json");
+        assert_eq!(json["type"], "trip_event");
+        assert_eq!(json["kind"], "TripCompleted");
+        assert_eq!(json["driver"], 2);
+    }
+}
@@ -0,0 +1,37 @@
+//! Kafka publisher (feature `kafka`), using the pure-Rust synchronous `kafka` client.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use kafka::producer::{Producer, Record, RequiredAcks};
+
+use super::{PublishError, StreamPublisher};
+
+/// Publishes each message as one record to the topic, waiting for the leader's ack.
+pub struct KafkaPublisher {
+    producer: Mutex<Producer>,
+}
+
+impl KafkaPublisher {
+    /// Connect to the given bootstrap brokers (`host:port`).
+    pub fn connect(brokers: Vec<String>) -> Result<Self, PublishError> {
+        let producer = Producer::from_hosts(brokers)
+            .with_ack_timeout(Duration::from_secs(1))
+            .with_required_acks(RequiredAcks::One)
+            .create()?;
+        Ok(Self {
+            producer: Mutex::new(producer),
+        })
+    }
+}
+
+impl StreamPublisher for KafkaPublisher {
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), PublishError> {
+        let producer = self
+            .producer
+            .get_mut()
+            .map_err(|_| PublishError::from("kafka producer poisoned"))?;
+        producer.send(&Record::from_value(topic, payload))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+//! NATS publisher (feature `nats`), using the synchronous `nats` client.
+
+use super::{PublishError, StreamPublisher};
+
+/// Publishes each message to the subject named by the topic.
+pub struct NatsPublisher {
+    connection: nats::Connection,
+}
+
+impl NatsPublisher {
+    /// Connect to a NATS server, e.g. `nats://localhost:4222`.
+    pub fn connect(url: &str) -> Result<Self, PublishError> {
+        Ok(Self {
+            connection: nats::connect(url)?,
+        })
+    }
+}
+
+impl StreamPublisher for NatsPublisher {
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), PublishError> {
+        self.connection.publish(topic, payload)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), PublishError> {
+        self.connection.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+//! WebSocket broadcast publisher (feature `websocket`), using the synchronous `tungstenite` server.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use tungstenite::handshake::server::{Request, Response};
+use tungstenite::{Message, WebSocket};
+
+use super::{PublishError, StreamPublisher};
+
+/// Clients that cannot take a message within this time are disconnected rather than stalling
+/// the run.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Client {
+    socket: WebSocket<TcpStream>,
+    /// Topic named by the request path (`/sim.events`); `None` for `/`, which receives all.
+    topic: Option<String>,
+}
+
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+/// Serves a WebSocket endpoint and pushes each message as a JSON text frame to every connected
+/// client. Dashboards connect to `/` for everything or to `/<topic>` for one topic.
+pub struct WebSocketPublisher {
+    local_addr: SocketAddr,
+    clients: Clients,
+    /// Set on drop; the accept thread exits on the next connection (drop makes one to wake it).
+    shutdown: Arc<AtomicBool>,
+    acceptor: Option<JoinHandle<()>>,
+}
+
+impl WebSocketPublisher {
+    /// Listen on `addr`, e.g. `0.0.0.0:9001`. Connections are accepted on a background thread
+    /// and each WebSocket handshake runs on its own short-lived thread, so a client that stalls
+    /// mid-handshake does not hold up the others. Dropping the publisher closes the listener.
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, PublishError> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let clients = Clients::default();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let accepted = Arc::clone(&clients);
+        let stop = Arc::clone(&shutdown);
+        let acceptor = thread::Builder::new()
+            .name("telemetry-websocket".to_string())
+            .spawn(move || accept_clients(listener, accepted, stop))?;
+        Ok(Self {
+            local_addr,
+            clients,
+            shutdown,
+            acceptor: Some(acceptor),
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .map(|clients| clients.len())
+            .unwrap_or(0)
+    }
+
+    /// Block until at least one client is connected or `timeout` passes; returns whether one is.
+    pub fn wait_for_client(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while self.client_count() == 0 {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        true
+    }
+}
+
+fn accept_clients(listener: TcpListener, clients: Clients, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::Acquire) {
+            return;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let clients = Arc::clone(&clients);
+        let shutdown = Arc::clone(&shutdown);
+        // If no thread can be spawned the connection is simply dropped.
+        let _ = thread::Builder::new()
+            .name("telemetry-websocket-handshake".to_string())
+            .spawn(move || {
+                let Some(client) = handshake(stream) else {
+                    return;
+                };
+                if shutdown.load(Ordering::Acquire) {
+                    return;
+                }
+                if let Ok(mut clients) = clients.lock() {
+                    clients.push(client);
+                }
+            });
+    }
+}
+
+/// Upgrade one connection; `None` if it times out or is not a WebSocket request.
+// The handshake callback's error type is fixed by tungstenite.
+#[allow(clippy::result_large_err)]
+fn handshake(stream: TcpStream) -> Option<Client> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    let mut path = String::new();
+    let socket = tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+        path = request.uri().path().to_string();
+        Ok(response)
+    })
+    .ok()?;
+    let topic = path.trim_start_matches('/');
+    Some(Client {
+        socket,
+        topic: (!topic.is_empty()).then(|| topic.to_string()),
+    })
+}
+
+/// Address to connect to in order to wake the accept loop; a wildcard bind is reached over
+/// loopback.
+fn wakeup_addr(local_addr: SocketAddr) -> SocketAddr {
+    let ip = match local_addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    SocketAddr::new(ip, local_addr.port())
+}
+
+impl StreamPublisher for WebSocketPublisher {
+    /// Sends to the subscribed clients and drops the ones that fail. Having no clients is not
+    /// an error.
+    fn publish(&mut self, topic: &str, payload: &[u8]) -> Result<(), PublishError> {
+        let text = std::str::from_utf8(payload)?;
+        let mut clients = self
+            .clients
+            .lock()
+            .map_err(|_| PublishError::from("websocket client list poisoned"))?;
+        clients.retain_mut(|client| {
+            if client
+                .topic
+                .as_deref()
+                .is_some_and(|wanted| wanted != topic)
+            {
+                return true;
+            }
+            client.socket.send(Message::text(text)).is_ok()
+        });
+        Ok(())
+    }
+}
+
+impl Drop for WebSocketPublisher {
+    fn drop(&mut self) {
+        if let Ok(mut clients) = self.clients.lock() {
+            for client in clients.iter_mut() {
+                let _ = client.socket.close(None);
+                let _ = client.socket.flush();
+            }
+            clients.clear();
+        }
+        self.shutdown.store(true, Ordering::Release);
+        let Some(acceptor) = self.acceptor.take() else {
+            return;
+        };
+        // If the wakeup connection fails the listener is already unusable; do not block on it.
+        if TcpStream::connect_timeout(&wakeup_addr(self.local_addr), CLIENT_TIMEOUT).is_ok() {
+            let _ = acceptor.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_clients_only_receive_their_topic() {
+        let mut publisher = WebSocketPublisher::bind("127.0.0.1:0").expect("bind");
+        let url = format!("ws://{}/sim.snapshots", publisher.local_addr());
+        let (mut client, _) = tungstenite::connect(url).expect("connect");
+        assert!(publisher.wait_for_client(Duration::from_secs(5)));
+
+        publisher
+            .publish("sim.events", br#"{"type":"trip_event"}"#)
+            .expect("publish event");
+        publisher
+            .publish("sim.snapshots", br#"{"type":"snapshot"}"#)
+            .expect("publish snapshot");
+
+        let message = client.read().expect("read");
+        assert_eq!(
+            message.into_text().expect("text").as_str(),
+            r#"{"type":"snapshot"}"#
+        );
+    }
+
+    #[test]
+    fn stalled_handshake_does_not_block_other_clients() {
+        let publisher = WebSocketPublisher::bind("127.0.0.1:0").expect("bind");
+        let _silent = TcpStream::connect(publisher.local_addr()).expect("raw connect");
+        let url = format!("ws://{}/", publisher.local_addr());
+        let _client = tungstenite::connect(url).expect("connect");
+        assert!(publisher.wait_for_client(CLIENT_TIMEOUT / 2));
+    }
+
+    #[test]
+    fn dropping_the_publisher_releases_the_port() {
+        let publisher = WebSocketPublisher::bind("127.0.0.1:0").expect("bind");
+        let addr = publisher.local_addr();
+        drop(publisher);
+        assert!(WebSocketPublisher::bind(addr).is_ok());
+    }
+}
@@ -84,11 +84,11 @@ impl TrafficProfile {
     /// Look up the speed multiplier for a given simulation time.
     ///
     /// `sim_time_ms` is the current simulation clock value.
-    /// `epoch_ms` is the real-world epoch (Unix ms) for simulation time 0.
+    /// `epoch_ms` is the local wall-clock epoch for simulation time 0
+    /// (`SimulationClock::local_epoch_ms`, which includes the city's UTC offset).
     pub fn factor_at(&self, sim_time_ms: u64, epoch_ms: i64) -> f64 {
-        let real_ms = epoch_ms + sim_time_ms as i64;
-        // Convert to hour-of-day (UTC). For Berlin, epoch_ms should include timezone offset.
-        let hour = ((real_ms / 3_600_000) % 24) as usize;
+        let local_ms = epoch_ms + sim_time_ms as i64;
+        let hour = ((local_ms / 3_600_000) % 24) as usize;
         self.hourly_factors[hour]
     }
 }
@@ -133,6 +133,52 @@ pub struct DynamicCongestionConfig {
     pub enabled: bool,
 }
 
+// ---------------------------------------------------------------------------
+// Mid-route rerouting
+// ---------------------------------------------------------------------------
+
+/// Mid-route rerouting: while a vehicle is driving a trip leg, the remaining route is
+/// re-queried when the traffic factor has moved far enough from its value at routing time.
+/// Disabled by default, so a leg keeps the route resolved on its first move step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Resource)]
+pub struct RerouteConfig {
+    pub enabled: bool,
+    /// Minimum time between reroutes of one trip leg, in seconds.
+    pub interval_secs: u64,
+    /// Relative traffic factor change since routing that triggers a reroute (0.1 = 10%).
+    pub min_traffic_change: f64,
+}
+
+impl Default for RerouteConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 120,
+            min_traffic_change: 0.1,
+        }
+    }
+}
+
+impl RerouteConfig {
+    /// Whether a route computed at `routed_at_ms` under `factor_at_routing` is due for a
+    /// reroute at `now_ms` under `factor`.
+    pub fn should_reroute(
+        &self,
+        routed_at_ms: u64,
+        factor_at_routing: f64,
+        now_ms: u64,
+        factor: f64,
+    ) -> bool {
+        if !self.enabled || now_ms < routed_at_ms.saturating_add(self.interval_secs * 1000) {
+            return false;
+        }
+        if factor_at_routing <= 0.0 {
+            return factor > 0.0;
+        }
+        ((factor - factor_at_routing) / factor_at_routing).abs() >= self.min_traffic_change
+    }
+}
+
 /// Compute a speed factor based on the number of drivers occupying a cell.
 ///
 /// More drivers in the same ~240m hex → slower movement for everyone.
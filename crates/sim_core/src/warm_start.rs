@@ -0,0 +1,102 @@
+//! Warm start: seed a new run with the fleet a previous run ended with.
+//!
+//! [`WarmStartState::capture`] reads the on-duty drivers out of a finished world (position,
+//! earnings, time left before fatigue, vehicle). Passing it to
+//! `ScenarioParams::with_warm_start` spawns those drivers as Idle when the new run starts, in
+//! addition to any drivers its own spawner creates, so consecutive days or before/after policy
+//! runs start from the same city state instead of an empty one. Riders and trips in flight are
+//! not carried over: drivers that were en route or on a trip start Idle where they were.
+
+use bevy_ecs::prelude::{Entity, Resource, With, Without, World};
+use h3o::{CellIndex, LatLng};
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SimulationClock;
+use crate::ecs::{
+    Driver, DriverEarnings, DriverFatigue, DriverVehicle, GeoPosition, OffDuty, Position,
+};
+use crate::emissions::VehicleType;
+
+/// One on-duty driver at the end of the previous run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CarriedDriver {
+    #[serde(with = "crate::run_bundle::cell_index")]
+    pub cell: CellIndex,
+    #[serde(with = "crate::run_bundle::lat_lng")]
+    pub geo: LatLng,
+    pub daily_earnings: f64,
+    pub daily_earnings_target: f64,
+    /// Time already spent on duty in the current session.
+    pub session_elapsed_ms: u64,
+    pub fatigue_threshold_ms: u64,
+    pub weekly_trips: u32,
+    pub vehicle: Option<VehicleType>,
+}
+
+impl CarriedDriver {
+    /// Fatigue threshold for the new session: the time this driver had left before fatigue.
+    pub fn remaining_fatigue_ms(&self) -> u64 {
+        self.fatigue_threshold_ms
+            .saturating_sub(self.session_elapsed_ms)
+    }
+}
+
+/// Fleet state carried into a new run (see module docs).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Resource)]
+pub struct WarmStartState {
+    /// Simulation time of the previous run when the state was captured.
+    pub captured_at_ms: u64,
+    pub drivers: Vec<CarriedDriver>,
+}
+
+impl WarmStartState {
+    /// Capture the on-duty drivers of `world` (OffDuty drivers have left supply and are
+    /// skipped). Drivers are ordered by entity so the next run spawns them deterministically.
+    pub fn capture(world: &mut World) -> Self {
+        let captured_at_ms = world
+            .get_resource::<SimulationClock>()
+            .map(|clock| clock.now())
+            .unwrap_or(0);
+        let mut query = world.query_filtered::<(
+            Entity,
+            &Position,
+            Option<&GeoPosition>,
+            Option<&DriverEarnings>,
+            Option<&DriverFatigue>,
+            Option<&DriverVehicle>,
+        ), (With<Driver>, Without<OffDuty>)>();
+        let mut drivers: Vec<_> = query
+            .iter(world)
+            .map(|(entity, position, geo, earnings, fatigue, vehicle)| {
+                let driver = CarriedDriver {
+                    cell: position.0,
+                    geo: geo.map(|geo| geo.0).unwrap_or_else(|| position.0.into()),
+                    daily_earnings: earnings.map_or(0.0, |e| e.daily_earnings),
+                    daily_earnings_target: earnings.map_or(0.0, |e| e.daily_earnings_target),
+                    session_elapsed_ms: earnings.map_or(0, |e| {
+                        captured_at_ms.saturating_sub(e.session_start_time_ms)
+                    }),
+                    fatigue_threshold_ms: fatigue.map_or(u64::MAX, |f| f.fatigue_threshold_ms),
+                    weekly_trips: earnings.map_or(0, |e| e.weekly_trips),
+                    vehicle: vehicle.map(|vehicle| vehicle.0),
+                };
+                (entity, driver)
+            })
+            .collect();
+        drivers.sort_by_key(|(entity, _)| *entity);
+        Self {
+            captured_at_ms,
+            drivers: drivers.into_iter().map(|(_, driver)| driver).collect(),
+        }
+    }
+
+    /// Start every carried driver on a fresh shift: keep positions, targets and vehicles, but
+    /// reset the day's earnings and the time on duty (e.g. when the next run is the next day).
+    pub fn starting_new_shift(mut self) -> Self {
+        for driver in &mut self.drivers {
+            driver.daily_earnings = 0.0;
+            driver.session_elapsed_ms = 0;
+        }
+        self
+    }
+}
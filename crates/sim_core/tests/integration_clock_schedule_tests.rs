@@ -1,6 +1,10 @@
 mod support;
 
-use sim_core::clock::{EventKind, SimulationClock, ONE_SEC_MS};
+use bevy_ecs::prelude::Entity;
+use sim_core::clock::{
+    day_of_week, hour_of_day, EventKind, EventPriorities, EventSubject, Recurrence,
+    SimulationClock, ONE_DAY_MS, ONE_HOUR_MS, ONE_MIN_MS, ONE_SEC_MS,
+};
 
 #[test]
 fn clock_pops_events_in_time_order() {
@@ -40,3 +44,218 @@ fn schedule_in_and_conversion() {
     assert_eq!(clock.real_to_sim_ms(1_700_000_001_000), Some(1000));
     assert_eq!(clock.real_to_sim_ms(1_699_999_999_000), None);
 }
+
+#[test]
+fn cancelled_events_never_fire() {
+    let mut clock = SimulationClock::default();
+    let first = clock.schedule_at(5, EventKind::RiderCancel, None);
+    let second = clock.schedule_at(10, EventKind::SpawnRider, None);
+    assert_eq!(clock.pending_event_count(), 2);
+
+    assert!(clock.cancel(first));
+    assert!(!clock.cancel(first), "second cancel is a no-op");
+    assert!(!clock.is_pending(first));
+    assert!(!clock.has_pending(EventKind::RiderCancel));
+    assert_eq!(clock.pending_event_count(), 1);
+    assert_eq!(clock.next_event_time(), Some(10));
+
+    let event = clock.pop_next().expect("remaining event");
+    assert_eq!(event.kind, EventKind::SpawnRider);
+    assert!(!clock.cancel(second), "fired events cannot be cancelled");
+    assert!(clock.pop_next().is_none());
+    assert!(clock.is_empty());
+}
+
+#[test]
+fn rescheduled_events_fire_once_at_the_new_time() {
+    let mut clock = SimulationClock::default();
+    let timeout = clock.schedule_at(60 * ONE_SEC_MS, EventKind::RiderCancel, None);
+    clock.schedule_at(10 * ONE_SEC_MS, EventKind::MoveStep, None);
+    clock.pop_next().expect("move step");
+
+    assert!(clock.reschedule_in(timeout, 0));
+    assert_eq!(clock.scheduled_time(timeout), Some(10 * ONE_SEC_MS));
+    assert_eq!(clock.pending_event_count(), 1);
+    let event = clock.pop_next().expect("rescheduled timeout");
+    assert_eq!(
+        (event.timestamp, event.kind),
+        (10 * ONE_SEC_MS, EventKind::RiderCancel)
+    );
+    assert!(clock.pop_next().is_none(), "the original slot is dropped");
+
+    let late = clock.schedule_in_secs(5, EventKind::RiderCancel, None);
+    assert!(clock.reschedule_at(late, 30 * ONE_SEC_MS));
+    assert_eq!(clock.next_event_time(), Some(30 * ONE_SEC_MS));
+    clock.pop_next().expect("pushed back timeout");
+    assert!(!clock.reschedule_in(late, 0));
+}
+
+#[test]
+fn pending_kinds_follow_schedule_reschedule_cancel_and_recurrence() {
+    let mut clock = SimulationClock::default();
+    let spawn = clock.schedule_at(10, EventKind::SpawnRider, None);
+    let timeout = clock.schedule_at(20, EventKind::RiderCancel, None);
+    let series = clock
+        .schedule_recurring(EventKind::BatchMatchRun, None, Recurrence::every(5))
+        .expect("series");
+
+    assert!(clock.reschedule_at(spawn, 30));
+    assert!(clock.has_pending(EventKind::SpawnRider));
+    assert!(clock.cancel(timeout));
+    assert!(!clock.has_pending(EventKind::RiderCancel));
+
+    assert_eq!(
+        clock.pop_next().map(|event| event.kind),
+        Some(EventKind::BatchMatchRun)
+    );
+    assert!(
+        clock.has_pending(EventKind::BatchMatchRun),
+        "next occurrence queued"
+    );
+    assert!(clock.cancel(series));
+    assert!(!clock.has_pending(EventKind::BatchMatchRun));
+
+    assert_eq!(clock.pop_next().map(|event| event.timestamp), Some(30));
+    assert!(!clock.has_pending(EventKind::SpawnRider));
+    assert!(clock.is_empty());
+}
+
+#[test]
+fn recurring_events_repeat_between_start_and_end() {
+    let mut clock = SimulationClock::default();
+    let series = clock
+        .schedule_recurring(
+            EventKind::BatchMatchRun,
+            None,
+            Recurrence::every_mins(5)
+                .starting_at(10 * ONE_MIN_MS)
+                .until(25 * ONE_MIN_MS),
+        )
+        .expect("series has occurrences");
+    clock.schedule_at(12 * ONE_MIN_MS, EventKind::SpawnRider, None);
+
+    let mut fired = Vec::new();
+    while let Some(event) = clock.pop_next() {
+        if event.kind == EventKind::BatchMatchRun {
+            fired.push(event.timestamp / ONE_MIN_MS);
+        }
+    }
+    assert_eq!(fired, vec![10, 15, 20, 25]);
+    assert!(!clock.is_pending(series));
+    assert!(clock
+        .schedule_recurring(
+            EventKind::BatchMatchRun,
+            None,
+            Recurrence::every_mins(5).until(ONE_MIN_MS),
+        )
+        .is_none());
+}
+
+#[test]
+fn cancelling_a_recurring_event_ends_the_series() {
+    let mut clock = SimulationClock::default();
+    let series = clock
+        .schedule_recurring(
+            EventKind::CheckDriverOffDuty,
+            None,
+            Recurrence::every(ONE_SEC_MS),
+        )
+        .expect("series");
+    assert_eq!(clock.pop_next().map(|event| event.timestamp), Some(0));
+    assert_eq!(
+        clock.pop_next().map(|event| event.timestamp),
+        Some(ONE_SEC_MS)
+    );
+    assert!(clock.cancel(series));
+    assert!(clock.pop_next().is_none());
+}
+
+#[test]
+fn daily_recurrence_fires_at_local_times_of_day() {
+    // Sim time 0 is 06:00 on the epoch day.
+    let mut clock = SimulationClock::with_epoch(6 * ONE_HOUR_MS as i64);
+    clock
+        .schedule_recurring(
+            EventKind::CheckDriverOffDuty,
+            None,
+            Recurrence::daily_at(&[(17, 30), (8, 0)]).until(2 * 24 * ONE_HOUR_MS),
+        )
+        .expect("series");
+
+    let fired: Vec<u64> = std::iter::from_fn(|| clock.pop_next())
+        .map(|event| event.timestamp)
+        .collect();
+    assert_eq!(
+        fired,
+        vec![
+            2 * ONE_HOUR_MS,
+            11 * ONE_HOUR_MS + 30 * ONE_MIN_MS,
+            26 * ONE_HOUR_MS,
+            35 * ONE_HOUR_MS + 30 * ONE_MIN_MS,
+        ]
+    );
+}
+
+#[test]
+fn same_timestamp_events_fire_by_priority_then_in_scheduling_order() {
+    let mut clock = SimulationClock::default();
+    let rider = |index| Some(EventSubject::Rider(Entity::from_raw(index)));
+    clock.schedule_at(100, EventKind::SpawnRider, rider(1));
+    clock.schedule_at(100, EventKind::TryMatch, rider(2));
+    clock.schedule_at(100, EventKind::TripCompleted, rider(3));
+    clock.schedule_at(100, EventKind::TryMatch, rider(4));
+    clock.schedule_at(100, EventKind::TryMatch, rider(5));
+
+    let fired: Vec<(EventKind, Option<EventSubject>)> = std::iter::from_fn(|| clock.pop_next())
+        .map(|event| (event.kind, event.subject))
+        .collect();
+    assert_eq!(
+        fired,
+        vec![
+            (EventKind::TripCompleted, rider(3)),
+            (EventKind::TryMatch, rider(2)),
+            (EventKind::TryMatch, rider(4)),
+            (EventKind::TryMatch, rider(5)),
+            (EventKind::SpawnRider, rider(1)),
+        ]
+    );
+}
+
+#[test]
+fn event_priorities_can_be_overridden_per_kind() {
+    let mut clock = SimulationClock::default();
+    clock.schedule_at(50, EventKind::TripCompleted, None);
+    clock.schedule_at(50, EventKind::SpawnRider, None);
+    // Re-ranks events that are already queued.
+    clock.set_event_priorities(EventPriorities::default().with(EventKind::SpawnRider, 0));
+    clock.schedule_at(50, EventKind::TryMatch, None);
+
+    let kinds: Vec<EventKind> = std::iter::from_fn(|| clock.pop_next())
+        .map(|event| event.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            EventKind::SpawnRider,
+            EventKind::TripCompleted,
+            EventKind::TryMatch
+        ]
+    );
+    assert_eq!(clock.event_priorities().get(EventKind::SpawnRider), 0);
+    assert_eq!(
+        clock.event_priorities().get(EventKind::TryMatch),
+        EventKind::TryMatch.default_priority()
+    );
+}
+
+#[test]
+fn calendar_helpers_count_from_the_thursday_epoch() {
+    assert_eq!(hour_of_day(25 * ONE_HOUR_MS as i64), 1);
+    assert_eq!(day_of_week(0), 3);
+    assert_eq!(day_of_week(4 * ONE_DAY_MS as i64), 0);
+    // 2024-01-06 was a Saturday.
+    assert_eq!(day_of_week(1_704_499_200_000), 5);
+    // The last hour before the epoch, a Wednesday.
+    assert_eq!(hour_of_day(-1), 23);
+    assert_eq!(day_of_week(-1), 2);
+}
@@ -0,0 +1,204 @@
+//! Completed trip records: cost of service, emissions, route geometry and vehicle classes.
+
+mod support;
+
+use std::fs::File;
+
+use arrow::array::{Array, StringArray};
+use bevy_ecs::prelude::World;
+use h3o::CellIndex;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sim_core::clock::SimulationClock;
+use sim_core::ecs::DriverVehicle;
+use sim_core::emissions::{EmissionFactors, VehicleMix, VehicleType};
+use sim_core::pricing::vehicle_classes::VehicleClassMenu;
+use sim_core::provenance::ExportProvenance;
+use sim_core::routing::polyline::decode_polyline;
+use sim_core::routing::{H3GridRouteProvider, RouteProvider, RouteProviderResource, RouteResult};
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::build_scenario;
+use sim_core::telemetry::{SimSnapshotConfig, SimTelemetry};
+use sim_core::telemetry_export::write_completed_trips_parquet;
+use support::export::{parquet_field_specs, temp_parquet_path};
+use support::{run_scenario, run_with_snapshots, small_completing_scenario};
+
+#[test]
+fn completed_trips_decompose_cost_of_service() {
+    let world = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    let end_ms = world.resource::<SimulationClock>().now();
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!(!telemetry.completed_trips.is_empty());
+
+    for trip in &telemetry.completed_trips {
+        assert!(trip.trip_km > 0.0);
+        assert!(trip.deadhead_km >= 0.0);
+        assert_eq!(trip.driven_km(), trip.deadhead_km + trip.trip_km);
+        assert_eq!(trip.cost_of_service(1.0, 0.0), trip.driven_km());
+        assert!(trip.cost_of_service(0.5, 0.2) > trip.cost_of_service(0.5, 0.0));
+    }
+    assert!(telemetry
+        .completed_trips
+        .iter()
+        .any(|trip| trip.deadhead_km > 0.0));
+
+    // Idle time is charged to at most one trip, so per-driver sums stay within idle time
+    for driver in telemetry.driver_utilization_at(end_ms) {
+        let charged: u64 = telemetry
+            .completed_trips
+            .iter()
+            .filter(|trip| trip.driver_entity == driver.driver)
+            .map(|trip| trip.idle_ms)
+            .sum();
+        assert!(charged <= driver.idle_ms);
+        assert_eq!(charged, driver.idle_ms_attributed);
+    }
+}
+
+#[test]
+fn emissions_split_driven_km_by_leg_at_vehicle_factors() {
+    let factors = EmissionFactors::default();
+    let mut world = run_scenario(
+        small_completing_scenario(3)
+            .with_emission_factors(factors)
+            .with_vehicle_mix(VehicleMix::default().with_share(VehicleType::Electric, 1.0)),
+    );
+
+    let drivers: Vec<_> = world
+        .query::<&DriverVehicle>()
+        .iter(&world)
+        .map(|vehicle| vehicle.0)
+        .collect();
+    assert!(!drivers.is_empty());
+    assert!(drivers.iter().all(|v| *v == VehicleType::Electric));
+
+    let telemetry = world.resource::<SimTelemetry>();
+    let emissions = telemetry.emissions;
+    let completed_trip_km: f64 = telemetry.completed_trips.iter().map(|t| t.trip_km).sum();
+    let completed_deadhead_km: f64 = telemetry
+        .completed_trips
+        .iter()
+        .map(|t| t.deadhead_km)
+        .sum();
+    assert!(emissions.occupied_km >= completed_trip_km - 1e-9);
+    assert!(emissions.deadhead_km >= completed_deadhead_km - 1e-9);
+    assert!(emissions.empty_km_share() > 0.0 && emissions.empty_km_share() < 1.0);
+
+    let electric = factors.grams_per_km(VehicleType::Electric);
+    assert!((emissions.total_g_co2() - emissions.total_km() * electric).abs() < 1e-6);
+}
+
+/// Grid routes reported as road waypoints (cell centres), standing in for OSRM.
+struct WaypointRouteProvider;
+
+impl RouteProvider for WaypointRouteProvider {
+    fn route(&self, from: CellIndex, to: CellIndex) -> Option<RouteResult> {
+        let mut route = H3GridRouteProvider.route(from, to)?;
+        route.waypoints = route
+            .cells
+            .iter()
+            .map(|cell| {
+                let point = h3o::LatLng::from(*cell);
+                (point.lat(), point.lng())
+            })
+            .collect();
+        Some(route)
+    }
+}
+
+#[test]
+fn completed_trips_export_route_polylines_from_road_routes() {
+    let mut world = World::new();
+    build_scenario(&mut world, small_completing_scenario(3));
+    world.insert_resource(RouteProviderResource(Box::new(WaypointRouteProvider)));
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, 100_000);
+
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!(!telemetry.completed_trips.is_empty());
+    for trip in &telemetry.completed_trips {
+        let polyline = trip.route_polyline.as_deref().expect("road route geometry");
+        let points = decode_polyline(polyline).expect("valid polyline");
+        assert!(points.len() >= 2);
+        let driven_km: f64 = points
+            .windows(2)
+            .map(|pair| {
+                let from = h3o::LatLng::new(pair[0].0, pair[0].1).unwrap();
+                let to = h3o::LatLng::new(pair[1].0, pair[1].1).unwrap();
+                from.distance_km(to)
+            })
+            .sum();
+        assert!((driven_km - trip.trip_km).abs() < 0.01);
+    }
+
+    let path = temp_parquet_path("completed_trips_polyline");
+    write_completed_trips_parquet(&path, telemetry, &ExportProvenance::current())
+        .expect("parquet should write");
+    assert!(parquet_field_specs(&path).contains(&(
+        "route_polyline".to_string(),
+        "Utf8".to_string(),
+        true
+    )));
+    let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let polylines = batch
+        .column_by_name("route_polyline")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        polylines.value(0),
+        telemetry.completed_trips[0]
+            .route_polyline
+            .as_deref()
+            .unwrap()
+    );
+    std::fs::remove_file(path).expect("temp parquet file should be removable");
+}
+
+#[test]
+fn completed_trips_record_chosen_and_offered_vehicle_class() {
+    let world = run_scenario(
+        small_completing_scenario(3)
+            .with_vehicle_classes(VehicleClassMenu::standard_pool_premium()),
+    );
+
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!(!telemetry.completed_trips.is_empty());
+    for trip in &telemetry.completed_trips {
+        assert_eq!(trip.offered_vehicle_class.as_deref(), Some("standard"));
+        assert!(matches!(
+            trip.vehicle_class.as_deref(),
+            Some("standard" | "pool" | "premium")
+        ));
+    }
+
+    let path = temp_parquet_path("completed_trips_vehicle_class");
+    write_completed_trips_parquet(&path, telemetry, &ExportProvenance::current())
+        .expect("parquet should write");
+    let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let classes = batch
+        .column_by_name("vehicle_class")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(
+        Some(classes.value(0)),
+        telemetry.completed_trips[0].vehicle_class.as_deref()
+    );
+    assert!(batch.column_by_name("offered_vehicle_class").is_some());
+    std::fs::remove_file(path).expect("temp parquet file should be removable");
+}
@@ -0,0 +1,102 @@
+//! Batched export writing. Kept in its own test binary because the batch size cap is
+//! process-wide.
+
+mod support;
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use arrow::compute::concat_batches;
+use arrow::ipc::reader::FileReader;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sim_core::provenance::ExportProvenance;
+use sim_core::telemetry::{SimSnapshots, SimTelemetry};
+use sim_core::telemetry_export::{
+    max_batch_bytes, set_max_batch_bytes, write_agent_positions_ipc, write_agent_positions_parquet,
+    write_funnel_ipc, write_funnel_parquet, DEFAULT_MAX_BATCH_BYTES,
+};
+use support::{run_scenario, small_completing_scenario};
+
+fn temp_export_path(prefix: &str, extension: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock should be after epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}_{nanos}.{extension}"))
+}
+
+/// Row group count and all rows as one batch.
+fn read_parquet(path: &Path) -> (usize, RecordBatch) {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+        .expect("parquet reader should build");
+    let row_groups = builder.metadata().num_row_groups();
+    let schema = builder.schema().clone();
+    let batches: Vec<RecordBatch> = builder
+        .build()
+        .expect("parquet reader should build")
+        .map(|batch| batch.expect("batch should read"))
+        .collect();
+    (row_groups, concat_batches(&schema, &batches).unwrap())
+}
+
+/// Record batch count and all rows as one batch.
+fn read_ipc(path: &Path) -> (usize, RecordBatch) {
+    let reader = FileReader::try_new(File::open(path).unwrap(), None).expect("ipc reader");
+    let schema = reader.schema();
+    let batches: Vec<RecordBatch> = reader
+        .map(|batch| batch.expect("batch should read"))
+        .collect();
+    (batches.len(), concat_batches(&schema, &batches).unwrap())
+}
+
+#[test]
+fn small_batch_cap_splits_exports_without_changing_their_rows() {
+    let world = run_scenario(small_completing_scenario(3));
+    let telemetry = world.resource::<SimTelemetry>();
+    let snapshots = world.resource::<SimSnapshots>();
+    let provenance = ExportProvenance::for_world(&world);
+
+    let write_all = |label: &str| {
+        let paths = [
+            temp_export_path(&format!("funnel_{label}"), "parquet"),
+            temp_export_path(&format!("funnel_{label}"), "arrow"),
+            temp_export_path(&format!("positions_{label}"), "parquet"),
+            temp_export_path(&format!("positions_{label}"), "arrow"),
+        ];
+        write_funnel_parquet(&paths[0], telemetry, &provenance).unwrap();
+        write_funnel_ipc(&paths[1], telemetry, &provenance).unwrap();
+        write_agent_positions_parquet(&paths[2], snapshots, &provenance).unwrap();
+        write_agent_positions_ipc(&paths[3], snapshots, &provenance).unwrap();
+        paths
+    };
+
+    assert_eq!(max_batch_bytes(), DEFAULT_MAX_BATCH_BYTES);
+    let whole = write_all("whole");
+    set_max_batch_bytes(1024);
+    let batched = write_all("batched");
+    set_max_batch_bytes(DEFAULT_MAX_BATCH_BYTES);
+
+    for (whole, batched) in whole.iter().zip(&batched) {
+        let ((whole_batches, whole_rows), (batches, rows)) = if whole
+            .extension()
+            .is_some_and(|extension| extension == "parquet")
+        {
+            (read_parquet(whole), read_parquet(batched))
+        } else {
+            (read_ipc(whole), read_ipc(batched))
+        };
+        assert_eq!(whole_batches, 1, "{whole:?} fits one batch by default");
+        assert!(
+            batches > 1,
+            "{batched:?} should be split into batches, got {batches}"
+        );
+        assert!(whole_rows.num_rows() > 0);
+        assert_eq!(rows, whole_rows, "{batched:?} rows differ from {whole:?}");
+    }
+
+    for path in whole.into_iter().chain(batched) {
+        std::fs::remove_file(path).expect("temp export file should be removable");
+    }
+}
@@ -0,0 +1,16 @@
+//! Golden-run regression tests. Each test reruns the scenario stored in its recorded bundle in
+//! `tests/golden/` and checks the run against it; see `support::golden`.
+
+mod support;
+
+use support::golden::check_golden;
+
+#[test]
+fn golden_batch_hungarian_matching() {
+    check_golden("batch_hungarian");
+}
+
+#[test]
+fn golden_continuous_simple_matching() {
+    check_golden("continuous_simple");
+}
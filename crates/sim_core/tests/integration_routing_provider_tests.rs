@@ -42,3 +42,69 @@ fn build_route_provider_h3grid() {
         .expect("neighbor");
     assert!(provider.route(cell, neighbor).is_some());
 }
+
+#[cfg(feature = "precomputed")]
+#[test]
+fn precomputed_route_table_round_trips_with_metadata() {
+    use std::collections::HashMap;
+
+    use sim_core::routing::precomputed::{
+        CellPair, PrecomputedRouteProvider, RegionBounds, RouteTable, RouteTableMetadata,
+    };
+
+    let from = CellIndex::try_from(0x8a1fb46622dffff_u64).expect("valid cell");
+    let to = from
+        .grid_disk::<Vec<_>>(2)
+        .into_iter()
+        .find(|c| *c != from)
+        .expect("neighbor");
+    let route = H3GridRouteProvider.route(from, to).expect("route");
+    let metadata = RouteTableMetadata {
+        provider: "H3GridRouteProvider".to_string(),
+        osrm_version: None,
+        bounds: RegionBounds {
+            lat_min: 52.34,
+            lat_max: 52.68,
+            lng_min: 13.08,
+            lng_max: 13.76,
+        },
+        sample_seed: 42,
+        sample_cells: 2,
+    };
+    let routes = HashMap::from([(CellPair::new(from, to), route.clone())]);
+
+    let path = std::env::temp_dir().join(format!("route_table_{}.bin", std::process::id()));
+    let path = path.to_str().expect("utf-8 temp path");
+    RouteTable::new(metadata.clone(), routes.clone())
+        .write(path)
+        .expect("write table");
+    let provider = PrecomputedRouteProvider::from_file(path).expect("load table");
+    std::fs::remove_file(path).expect("remove table");
+
+    assert_eq!(provider.metadata(), Some(&metadata));
+    assert_eq!(provider.len(), 1);
+    let loaded = provider.route(from, to).expect("stored route");
+    assert_eq!(loaded.cells, route.cells);
+    assert_eq!(loaded.distance_km, route.distance_km);
+    assert!(provider.route(to, from).is_none());
+
+    let legacy = RouteTable::from_bytes(&bincode_legacy(&routes)).expect("legacy table");
+    assert!(legacy.metadata.is_none());
+    assert_eq!(legacy.routes.len(), 1);
+}
+
+#[cfg(feature = "precomputed")]
+fn bincode_legacy(
+    routes: &std::collections::HashMap<
+        sim_core::routing::precomputed::CellPair,
+        sim_core::routing::RouteResult,
+    >,
+) -> Vec<u8> {
+    let path = std::env::temp_dir().join(format!("route_table_legacy_{}.bin", std::process::id()));
+    let path = path.to_str().expect("utf-8 temp path");
+    sim_core::routing::precomputed::PrecomputedRouteProvider::save_to_file(routes, path)
+        .expect("write legacy table");
+    let data = std::fs::read(path).expect("read legacy table");
+    std::fs::remove_file(path).expect("remove legacy table");
+    data
+}
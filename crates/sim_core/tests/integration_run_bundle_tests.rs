@@ -0,0 +1,177 @@
+use bevy_ecs::prelude::World;
+use sim_core::ecs::DriverEarnings;
+use sim_core::run_bundle::{RunBundle, RUN_BUNDLE_FORMAT_VERSION};
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::{SimSnapshotConfig, SimSnapshots, SimTelemetry};
+
+fn finished_run() -> World {
+    finished_run_with_snapshots(SimSnapshotConfig::default())
+}
+
+fn finished_run_with_snapshots(snapshot_config: SimSnapshotConfig) -> World {
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        ScenarioParams {
+            num_riders: 20,
+            num_drivers: 10,
+            initial_rider_count: 10,
+            initial_driver_count: 10,
+            lat_min: 52.50,
+            lat_max: 52.52,
+            lng_min: 13.38,
+            lng_max: 13.41,
+            ..Default::default()
+        }
+        .with_seed(11)
+        .with_request_window_hours(1)
+        .with_match_radius(5)
+        .with_trip_duration_cells(5, 20)
+        .with_simulation_end_time_ms(2 * 3_600_000)
+        .with_snapshot_config(snapshot_config),
+    );
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, 100_000);
+    world
+}
+
+fn bundle_path(prefix: &str) -> std::path::PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("clock")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}_{nanos}.simrun"))
+}
+
+#[test]
+fn bundle_round_trips_and_restores_recorded_telemetry() {
+    let mut world = finished_run();
+    let bundle = RunBundle::capture(&mut world).with_label("seed-11");
+    let path = bundle_path("bundle");
+    bundle.write(&path).expect("write bundle");
+    let loaded = RunBundle::read(&path).expect("read bundle");
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(loaded.format_version, RUN_BUNDLE_FORMAT_VERSION);
+    assert_eq!(loaded.label.as_deref(), Some("seed-11"));
+    let scenario = loaded.scenario.as_ref().expect("scenario record");
+    assert_eq!(scenario.seed, Some(11));
+    assert_eq!(scenario.num_riders, 20);
+    let params = scenario.params.as_ref().expect("scenario params");
+    assert_eq!(params.seed, Some(11));
+    assert_eq!(params.num_drivers, 10);
+    assert_eq!(params.simulation_end_time_ms, Some(2 * 3_600_000));
+    assert_eq!(loaded.seeds.as_ref().map(|seeds| seeds.master), Some(11));
+    assert_eq!(loaded.snapshots.len(), bundle.snapshots.len());
+
+    let telemetry = world.resource::<SimTelemetry>();
+    let mut restored = loaded.restore_world();
+    let restored_telemetry = restored.resource::<SimTelemetry>();
+    assert!(!telemetry.completed_trips.is_empty());
+    assert_eq!(
+        restored_telemetry.completed_trips.len(),
+        telemetry.completed_trips.len()
+    );
+    assert_eq!(
+        restored_telemetry.completed_trips[0].trip_entity,
+        telemetry.completed_trips[0].trip_entity
+    );
+    assert_eq!(
+        restored_telemetry.riders_resolved_total(),
+        telemetry.riders_resolved_total()
+    );
+    assert_eq!(
+        restored_telemetry.funnel_summary(),
+        telemetry.funnel_summary()
+    );
+    assert_eq!(restored_telemetry.emissions, telemetry.emissions);
+
+    let original = world.resource::<SimSnapshots>().snapshots.back().cloned();
+    let replayed = restored
+        .resource::<SimSnapshots>()
+        .snapshots
+        .back()
+        .cloned();
+    let (original, replayed) = (original.expect("snapshot"), replayed.expect("snapshot"));
+    assert_eq!(replayed.timestamp_ms, original.timestamp_ms);
+    assert_eq!(replayed.counts, original.counts);
+    assert_eq!(replayed.drivers.len(), original.drivers.len());
+    assert_eq!(replayed.drivers[0].cell, original.drivers[0].cell);
+
+    let payouts: f64 = restored
+        .query::<&DriverEarnings>()
+        .iter(&restored)
+        .map(|earnings| earnings.daily_earnings)
+        .sum();
+    let recorded: f64 = world
+        .query::<&DriverEarnings>()
+        .iter(&world)
+        .map(|earnings| earnings.daily_earnings)
+        .sum();
+    assert!((payouts - recorded).abs() < 1e-9);
+}
+
+#[test]
+fn snapshot_interval_thins_bundle_and_keeps_last_snapshot() {
+    let mut world = finished_run();
+    let bundle = RunBundle::capture(&mut world);
+    let last = bundle
+        .snapshots
+        .last()
+        .map(|snapshot| snapshot.timestamp_ms);
+    let thinned = bundle.clone().with_snapshot_interval_ms(10 * 60_000);
+
+    assert!(thinned.snapshots.len() < bundle.snapshots.len());
+    assert_eq!(thinned.snapshots.last().map(|s| s.timestamp_ms), last);
+    for pair in thinned.snapshots[..thinned.snapshots.len() - 1].windows(2) {
+        assert!(pair[1].timestamp_ms - pair[0].timestamp_ms >= 10 * 60_000);
+    }
+}
+
+#[test]
+fn thinned_delta_snapshots_still_reconstruct() {
+    let mut world = finished_run_with_snapshots(SimSnapshotConfig {
+        interval_ms: 60_000,
+        ..SimSnapshotConfig::default().with_keyframe_interval(10)
+    });
+    let bundle = RunBundle::capture(&mut world);
+    assert!(bundle.snapshots.iter().any(|snapshot| !snapshot.keyframe));
+    let thinned = bundle.clone().with_snapshot_interval_ms(7 * 60_000);
+
+    let full = bundle.restore_world();
+    let full = full.resource::<SimSnapshots>();
+    let restored = thinned.restore_world();
+    let restored = restored.resource::<SimSnapshots>();
+    for (index, snapshot) in restored.snapshots.iter().enumerate() {
+        let expected = full
+            .snapshots
+            .iter()
+            .position(|recorded| recorded.timestamp_ms == snapshot.timestamp_ms)
+            .and_then(|at| full.reconstruct(at))
+            .expect("kept snapshot was recorded");
+        let reconstructed = restored.reconstruct(index).expect("in range");
+        let mut expected_drivers = expected.drivers.clone();
+        let mut drivers = reconstructed.drivers.clone();
+        expected_drivers.sort_by_key(|driver| driver.entity);
+        drivers.sort_by_key(|driver| driver.entity);
+        assert_eq!(drivers, expected_drivers);
+        assert_eq!(reconstructed.trips.len(), expected.trips.len());
+        assert_eq!(reconstructed.riders.len(), expected.riders.len());
+    }
+}
+
+#[test]
+fn newer_bundle_format_is_rejected() {
+    let mut world = finished_run();
+    let mut bundle = RunBundle::capture(&mut world);
+    bundle.format_version = RUN_BUNDLE_FORMAT_VERSION + 1;
+    let path = bundle_path("future_bundle");
+    bundle.write(&path).expect("write bundle");
+    let result = RunBundle::read(&path);
+    std::fs::remove_file(&path).ok();
+
+    let error = result.expect_err("newer format");
+    assert!(error.to_string().contains("newer than supported"));
+}
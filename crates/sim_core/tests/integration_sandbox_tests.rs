@@ -0,0 +1,108 @@
+use sim_core::clock::ONE_HOUR_MS;
+use sim_core::sandbox::{Location, RiderStatus, Sandbox, SandboxError, TripStatus};
+use sim_core::scenario::{DriverDecisionConfig, RiderCancelConfig, ScenarioParams};
+
+fn sandbox() -> Sandbox {
+    let params = ScenarioParams {
+        num_riders: 0,
+        num_drivers: 5,
+        initial_driver_count: 5,
+        lat_min: 52.50,
+        lat_max: 52.52,
+        lng_min: 13.40,
+        lng_max: 13.42,
+        driver_decision_config: Some(DriverDecisionConfig {
+            base_acceptance_score: 10.0,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .with_seed(7)
+    .with_match_radius(10)
+    .with_simulation_end_time_ms(4 * ONE_HOUR_MS);
+    let mut sandbox = Sandbox::new(params);
+    sandbox.world_mut().insert_resource(RiderCancelConfig {
+        min_wait_secs: 3600,
+        max_wait_secs: 3600,
+        seed: 0,
+    });
+    sandbox
+}
+
+const PICKUP: Location = Location {
+    lat: 52.51,
+    lng: 13.41,
+};
+const DROPOFF: Location = Location {
+    lat: 52.515,
+    lng: 13.415,
+};
+
+#[test]
+fn external_rider_is_quoted_matched_and_driven_to_dropoff() {
+    let mut sandbox = sandbox();
+    let quoted = sandbox.request_quote(PICKUP, DROPOFF).expect("quote");
+    assert_eq!(quoted.status, RiderStatus::Quoted);
+    let quote = quoted.quote.expect("quote view");
+    assert!(quote.fare > 0.0);
+
+    // The rider choice model leaves external quotes alone
+    sandbox.advance_to(10 * 60 * 1000);
+    assert_eq!(
+        sandbox.rider(quoted.id).expect("rider").status,
+        RiderStatus::Quoted
+    );
+
+    let accepted = sandbox.accept_quote(quoted.id).expect("accept");
+    assert_eq!(accepted.status, RiderStatus::Waiting);
+    assert_eq!(
+        sandbox.accept_quote(quoted.id),
+        Err(SandboxError::NoQuote(quoted.id))
+    );
+
+    sandbox.advance_to(3 * ONE_HOUR_MS);
+    let done = sandbox.rider(quoted.id).expect("rider");
+    assert_eq!(done.status, RiderStatus::Completed);
+    let trip = done.trip.expect("trip");
+    assert_eq!(trip.status, TripStatus::Completed);
+    assert!(trip.dropoff_at_ms.is_some());
+}
+
+#[test]
+fn declined_quotes_are_requoted_then_abandoned() {
+    let mut sandbox = sandbox();
+    let rider = sandbox.request_quote(PICKUP, DROPOFF).expect("quote").id;
+
+    let declined = sandbox.decline_quote(rider).expect("decline");
+    assert_eq!(declined.status, RiderStatus::Quoting);
+    assert!(declined.quote.is_none());
+
+    // Default config: re-quote after 10 s, give up after the fourth decline
+    for _ in 0..3 {
+        let now = sandbox.now_ms();
+        sandbox.advance_to(now + 10_000);
+        assert_eq!(
+            sandbox.rider(rider).expect("rider").status,
+            RiderStatus::Quoted
+        );
+        sandbox.decline_quote(rider).expect("decline");
+    }
+    assert_eq!(
+        sandbox.rider(rider).expect("rider").status,
+        RiderStatus::Abandoned
+    );
+}
+
+#[test]
+fn sandbox_rejects_unknown_riders_and_bad_locations() {
+    let mut sandbox = sandbox();
+    assert_eq!(sandbox.rider(12345), Err(SandboxError::UnknownRider(12345)));
+    let bad = Location {
+        lat: f64::NAN,
+        lng: 13.41,
+    };
+    assert!(matches!(
+        sandbox.request_quote(bad, DROPOFF),
+        Err(SandboxError::InvalidLocation(_))
+    ));
+}
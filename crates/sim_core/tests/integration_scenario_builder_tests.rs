@@ -1,8 +1,21 @@
 mod support;
 
-use bevy_ecs::prelude::World;
-use sim_core::scenario::{build_scenario, MatchRadius, ScenarioParams};
-use sim_core::spawner::{DriverSpawner, RiderSpawner};
+use bevy_ecs::prelude::{Entity, With, Without, World};
+use sim_core::clock::{SimulationClock, ONE_DAY_MS};
+use sim_core::decision_models::{ConversionFeature, ConversionModel, DriverAcceptanceModel};
+use sim_core::ecs::{Driver, DriverEarnings, DriverFatigue, OffDuty, Position};
+use sim_core::patterns::WeeklyPattern;
+use sim_core::pricing::vehicle_classes::VehicleClassMenu;
+use sim_core::runner::initialize_simulation;
+use sim_core::scenario::{
+    build_scenario, DriverDecisionConfig, MatchRadius, RiderCancelConfig, RiderQuoteConfig,
+    ScenarioParams, SimulationEndTimeMs,
+};
+use sim_core::seeds::{SeedHierarchy, SeedStream};
+use sim_core::sim_log::{LogSeverity, SimLog, SimLogEvent};
+use sim_core::spawner::{DemandEvents, DriverSpawner, RiderSpawner, SpawnWeighting};
+use sim_core::warm_start::WarmStartState;
+use support::schedule::ScheduleRunner;
 
 #[test]
 fn build_scenario_configures_spawners() {
@@ -26,6 +39,48 @@ fn build_scenario_configures_spawners() {
     assert_eq!(driver_spawner.spawned_count(), 0);
 }
 
+#[test]
+fn build_scenario_seeds_configs_from_named_streams() {
+    let mut world = World::new();
+    build_scenario(&mut world, ScenarioParams::default().with_seed(42));
+
+    let seeds = *world.resource::<SeedHierarchy>();
+    assert_eq!(seeds, SeedHierarchy::new(42));
+    assert_eq!(
+        world.resource::<RiderSpawner>().config.seed,
+        seeds.seed(SeedStream::RiderSpawning)
+    );
+    assert_eq!(
+        world.resource::<DriverSpawner>().config.seed,
+        seeds.seed(SeedStream::DriverSpawning)
+    );
+    assert_eq!(
+        world.resource::<RiderCancelConfig>().seed,
+        seeds.seed(SeedStream::Cancellation)
+    );
+    assert_eq!(
+        world.resource::<RiderQuoteConfig>().seed,
+        seeds.seed(SeedStream::Quotes)
+    );
+    assert_eq!(
+        world.resource::<DriverDecisionConfig>().seed,
+        seeds.seed(SeedStream::DriverDecisions)
+    );
+    assert_eq!(
+        world.resource::<VehicleClassMenu>().seed,
+        seeds.seed(SeedStream::VehicleClassChoice)
+    );
+    let demand_events = world.resource::<DemandEvents>();
+    assert_eq!(
+        demand_events.arrival_seed,
+        seeds.seed(SeedStream::DemandEvents)
+    );
+    assert_eq!(
+        demand_events.rider_seed,
+        seeds.seed(SeedStream::EventRiders)
+    );
+}
+
 #[test]
 fn build_scenario_handles_empty_scenarios() {
     let mut world = World::new();
@@ -87,3 +142,176 @@ fn build_scenario_handles_initial_counts() {
     assert_eq!(driver_spawner.config.max_count, Some(3));
     assert_eq!(driver_spawner.config.initial_count, 2);
 }
+
+#[test]
+fn build_scenario_loads_hotspot_file() {
+    let path = std::env::temp_dir().join(format!("hotspots_{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "lat,lng,weight,role\n40.758,-73.985,3.0,rider\n40.750,-73.993,1.0,driver\n",
+    )
+    .expect("write hotspot file");
+
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        ScenarioParams::default().with_hotspot_file(path.to_string_lossy()),
+    );
+    let weighting = world.resource::<SpawnWeighting>();
+    assert_eq!(weighting.rider_cells.len(), 1);
+    assert_eq!(weighting.driver_cells.len(), 1);
+    std::fs::remove_file(&path).ok();
+
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        ScenarioParams::default().with_hotspot_file("/nonexistent/hotspots.json"),
+    );
+    assert!(world.resource::<SpawnWeighting>().rider_cells.is_empty());
+    let log = world.resource::<SimLog>();
+    let entry = log.entries().next().expect("fallback logged");
+    assert!(matches!(
+        entry.event,
+        SimLogEvent::SpawnWeightingFallback { .. }
+    ));
+    assert_eq!(entry.severity(), LogSeverity::Error);
+}
+
+#[test]
+fn build_scenario_loads_decision_models() {
+    let path = std::env::temp_dir().join(format!("conversion_{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{ "intercept": 2.0, "weights": { "relative_price": -3.0 } }"#,
+    )
+    .expect("write model file");
+    let with_model = |path: &str| {
+        ScenarioParams::default().with_rider_quote_config(RiderQuoteConfig {
+            conversion_model_path: Some(path.to_string()),
+            ..Default::default()
+        })
+    };
+
+    let mut world = World::new();
+    build_scenario(&mut world, with_model(&path.to_string_lossy()));
+    let model = world.resource::<ConversionModel>();
+    assert_eq!(model.intercept, 2.0);
+    assert_eq!(model.weights[&ConversionFeature::RelativePrice], -3.0);
+    std::fs::remove_file(&path).ok();
+
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        with_model("/nonexistent/conversion.json").with_driver_decision_config(
+            DriverDecisionConfig {
+                acceptance_model_path: Some("/nonexistent/acceptance.json".to_string()),
+                ..Default::default()
+            },
+        ),
+    );
+    assert!(world.get_resource::<ConversionModel>().is_none());
+    assert!(world.get_resource::<DriverAcceptanceModel>().is_none());
+    let log = world.resource::<SimLog>();
+    let fallbacks = log
+        .entries()
+        .filter(|entry| matches!(entry.event, SimLogEvent::DecisionModelFallback { .. }))
+        .count();
+    assert_eq!(fallbacks, 2);
+}
+
+#[test]
+fn build_scenario_supports_multi_day_runs_with_weekly_patterns() {
+    let mut world = World::new();
+    let rider_pattern = WeeklyPattern::rider_default().with_day_scale(5, 1.5);
+    build_scenario(
+        &mut world,
+        ScenarioParams {
+            num_riders: 70,
+            num_drivers: 14,
+            seed: Some(3),
+            ..Default::default()
+        }
+        .with_simulation_days(7)
+        .with_weekly_patterns(rider_pattern, WeeklyPattern::driver_default()),
+    );
+
+    let rider_spawner = world.resource::<RiderSpawner>();
+    assert_eq!(rider_spawner.config.end_time_ms, Some(7 * ONE_DAY_MS));
+    let driver_spawner = world.resource::<DriverSpawner>();
+    assert_eq!(driver_spawner.config.end_time_ms, Some(7 * ONE_DAY_MS));
+    assert_eq!(world.resource::<SimulationEndTimeMs>().0, 7 * ONE_DAY_MS);
+}
+
+#[test]
+fn warm_start_carries_the_fleet_into_the_next_run() {
+    let day_params = || {
+        ScenarioParams {
+            num_riders: 40,
+            num_drivers: 10,
+            initial_driver_count: 10,
+            lat_min: 52.50,
+            lat_max: 52.52,
+            lng_min: 13.38,
+            lng_max: 13.41,
+            ..Default::default()
+        }
+        .with_seed(11)
+        .with_request_window_hours(2)
+        .with_match_radius(5)
+        .with_simulation_end_time_ms(2 * 3_600_000)
+    };
+    let mut world = World::new();
+    build_scenario(&mut world, day_params());
+    initialize_simulation(&mut world);
+    ScheduleRunner::new().run_full(&mut world);
+
+    let state = WarmStartState::capture(&mut world);
+    let on_duty = world
+        .query_filtered::<(), (With<Driver>, Without<OffDuty>)>()
+        .iter(&world)
+        .count();
+    assert_eq!(
+        state.captured_at_ms,
+        world.resource::<SimulationClock>().now()
+    );
+    assert_eq!(state.drivers.len(), on_duty);
+    assert!(!state.drivers.is_empty());
+    assert!(state
+        .drivers
+        .iter()
+        .any(|driver| driver.daily_earnings > 0.0));
+
+    let mut next = World::new();
+    build_scenario(
+        &mut next,
+        ScenarioParams {
+            initial_driver_count: 0,
+            num_drivers: 0,
+            ..day_params()
+        }
+        .with_warm_start(state.clone()),
+    );
+    initialize_simulation(&mut next);
+    ScheduleRunner::new().run_one(&mut next);
+
+    let mut carried: Vec<_> = next
+        .query::<(Entity, &Position, &DriverEarnings, &DriverFatigue)>()
+        .iter(&next)
+        .map(|(entity, position, earnings, fatigue)| (entity, *position, *earnings, *fatigue))
+        .collect();
+    carried.sort_by_key(|(entity, ..)| *entity);
+    assert_eq!(carried.len(), state.drivers.len());
+    for ((_, position, earnings, fatigue), driver) in carried.iter().zip(&state.drivers) {
+        assert_eq!(position.0, driver.cell);
+        assert_eq!(earnings.daily_earnings, driver.daily_earnings);
+        assert_eq!(earnings.session_start_time_ms, 0);
+        assert_eq!(fatigue.fatigue_threshold_ms, driver.remaining_fatigue_ms());
+    }
+
+    let fresh = state.starting_new_shift();
+    assert!(fresh
+        .drivers
+        .iter()
+        .all(|driver| driver.daily_earnings == 0.0
+            && driver.remaining_fatigue_ms() == driver.fatigue_threshold_ms));
+}
@@ -0,0 +1,216 @@
+//! Snapshot content, sampling, retention, delta encoding and world-growth sampling.
+
+mod support;
+
+use bevy_ecs::prelude::World;
+use sim_core::profiling::WorldGrowthMetrics;
+use sim_core::provenance::ExportProvenance;
+use sim_core::scenario::EntityGcConfig;
+use sim_core::telemetry::{SimSnapshot, SimSnapshotConfig, SimSnapshots, SnapshotRetention};
+use sim_core::telemetry_export::write_snapshot_counts_parquet;
+use support::export::{parquet_field_specs, provenance_field_specs, temp_parquet_path};
+use support::{run_scenario, small_completing_scenario};
+
+fn run_with_snapshot_config(config: SimSnapshotConfig) -> World {
+    run_with_snapshot_config_and_gc(config, None)
+}
+
+fn run_with_snapshot_config_and_gc(
+    config: SimSnapshotConfig,
+    entity_gc: Option<EntityGcConfig>,
+) -> World {
+    let mut params = small_completing_scenario(3).with_snapshot_config(config);
+    params.entity_gc = entity_gc;
+    run_scenario(params)
+}
+
+#[test]
+fn snapshot_content_and_sampling_are_configurable() {
+    let full = run_with_snapshot_config(SimSnapshotConfig::default());
+    let counts_only = run_with_snapshot_config(
+        SimSnapshotConfig::default()
+            .counts_only()
+            .with_cell_aggregates(),
+    );
+    let sampled =
+        run_with_snapshot_config(SimSnapshotConfig::default().with_entity_sample_fraction(0.5));
+
+    let full = &full.resource::<SimSnapshots>().snapshots;
+    let counts_only = &counts_only.resource::<SimSnapshots>().snapshots;
+    let sampled = &sampled.resource::<SimSnapshots>().snapshots;
+    assert_eq!(full.len(), counts_only.len());
+    assert_eq!(full.len(), sampled.len());
+
+    for ((full, lean), sampled) in full.iter().zip(counts_only).zip(sampled) {
+        // Counts always cover every entity.
+        assert_eq!(full.counts, lean.counts);
+        assert_eq!(full.counts, sampled.counts);
+        assert!(full.cells.is_empty());
+
+        assert!(lean.riders.is_empty() && lean.drivers.is_empty() && lean.trips.is_empty());
+        let idle: u32 = lean.cells.iter().map(|cell| cell.drivers_idle).sum();
+        assert_eq!(idle as usize, lean.counts.drivers_idle);
+        let browsing: u32 = lean.cells.iter().map(|cell| cell.riders_browsing).sum();
+        assert_eq!(browsing as usize, lean.counts.riders_browsing);
+        let waiting: u32 = lean.cells.iter().map(|cell| cell.riders_waiting).sum();
+        assert_eq!(waiting as usize, lean.counts.riders_waiting);
+
+        assert!(sampled.drivers.len() <= full.drivers.len());
+        assert!(sampled.riders.len() <= full.riders.len());
+    }
+    let last_full = full.back().expect("snapshots");
+    let last_sampled = sampled.back().expect("snapshots");
+    assert!(!last_sampled.drivers.is_empty());
+    assert!(last_sampled.drivers.len() < last_full.drivers.len());
+}
+
+#[test]
+fn thinned_snapshot_retention_spans_whole_run() {
+    let config = SimSnapshotConfig {
+        interval_ms: 10_000,
+        max_snapshots: 40,
+        ..Default::default()
+    };
+    let ring = run_with_snapshot_config(config);
+    let thinned = run_with_snapshot_config(
+        config
+            .with_retention(SnapshotRetention::Thinned)
+            .counts_only(),
+    );
+
+    let ring = &ring.resource::<SimSnapshots>().snapshots;
+    let thinned = &thinned.resource::<SimSnapshots>().snapshots;
+    assert_eq!(ring.len(), 40);
+    assert!(thinned.len() <= 40);
+    assert!(ring.front().unwrap().timestamp_ms > 0);
+    assert_eq!(thinned.front().unwrap().timestamp_ms, 0);
+    assert_eq!(
+        thinned.back().unwrap().timestamp_ms,
+        ring.back().unwrap().timestamp_ms
+    );
+    // Older history is kept at coarser intervals than the most recent snapshots.
+    let gaps: Vec<u64> = thinned
+        .iter()
+        .zip(thinned.iter().skip(1))
+        .map(|(a, b)| b.timestamp_ms - a.timestamp_ms)
+        .collect();
+    assert!(gaps.first().unwrap() > gaps.last().unwrap());
+}
+
+#[test]
+fn delta_snapshots_reconstruct_the_full_snapshots() {
+    let entity_lists = |snapshot: &SimSnapshot| {
+        let mut riders = snapshot.riders.clone();
+        riders.sort_by_key(|rider| rider.entity);
+        let mut drivers = snapshot.drivers.clone();
+        drivers.sort_by_key(|driver| driver.entity);
+        let mut trips = snapshot.trips.clone();
+        trips.sort_by_key(|trip| trip.entity);
+        (riders, drivers, trips)
+    };
+    let rows = |snapshots: &SimSnapshots| -> usize {
+        snapshots
+            .snapshots
+            .iter()
+            .map(|snapshot| snapshot.riders.len() + snapshot.drivers.len() + snapshot.trips.len())
+            .sum()
+    };
+
+    for retention in [SnapshotRetention::RingBuffer, SnapshotRetention::Thinned] {
+        // Both retention policies drop the oldest of the 120 snapshots
+        let config = SimSnapshotConfig {
+            interval_ms: 60_000,
+            max_snapshots: 100,
+            ..Default::default()
+        }
+        .with_retention(retention);
+        // Collected riders and trips show up as removals
+        let gc = Some(EntityGcConfig {
+            interval_secs: 60,
+            retention_secs: 120,
+        });
+        let full = run_with_snapshot_config_and_gc(config, gc);
+        let delta = run_with_snapshot_config_and_gc(config.with_keyframe_interval(5), gc);
+        let full = full.resource::<SimSnapshots>();
+        let delta = delta.resource::<SimSnapshots>();
+
+        assert_eq!(full.snapshots.len(), delta.snapshots.len());
+        assert!(delta.snapshots.front().expect("snapshots").keyframe);
+        assert!(delta.snapshots.iter().any(|snapshot| !snapshot.keyframe));
+        assert!(rows(delta) < rows(full), "deltas list fewer entities");
+        assert!(delta
+            .snapshots
+            .iter()
+            .any(|snapshot| !snapshot.removed.is_empty()));
+
+        for (index, (expected, reconstructed)) in
+            full.snapshots.iter().zip(delta.iter_full()).enumerate()
+        {
+            assert_eq!(expected.timestamp_ms, reconstructed.timestamp_ms);
+            assert_eq!(expected.counts, reconstructed.counts);
+            assert_eq!(
+                entity_lists(expected),
+                entity_lists(&reconstructed),
+                "{retention:?} snapshot {index}"
+            );
+            let at_index = delta.reconstruct(index).expect("in range");
+            assert_eq!(entity_lists(&at_index), entity_lists(expected));
+        }
+    }
+}
+
+#[test]
+fn world_growth_is_sampled_with_snapshots_and_exported() {
+    let config = SimSnapshotConfig {
+        interval_ms: 60_000,
+        ..Default::default()
+    }
+    .counts_only();
+    let plain = run_with_snapshot_config(config);
+    assert!(plain.get_resource::<WorldGrowthMetrics>().is_none());
+    assert!(plain
+        .resource::<SimSnapshots>()
+        .snapshots
+        .iter()
+        .all(|snapshot| snapshot.world_growth.is_none()));
+
+    let world = run_with_snapshot_config(config.with_world_growth());
+    let snapshots = world.resource::<SimSnapshots>();
+    for snapshot in &snapshots.snapshots {
+        let growth = snapshot.world_growth.as_ref().expect("world growth sample");
+        assert_eq!(growth.timestamp_ms, snapshot.timestamp_ms);
+        assert_eq!(growth.archetypes.values().sum::<u64>(), growth.entities);
+        assert_eq!(growth.component_bytes > 0, growth.entities > 0);
+    }
+    let samples: Vec<_> = snapshots
+        .snapshots
+        .iter()
+        .filter_map(|snapshot| snapshot.world_growth.as_ref())
+        .collect();
+    assert!(samples.iter().any(|growth| growth.event_queue_len > 0));
+    assert!(samples.iter().any(|growth| growth
+        .archetypes
+        .keys()
+        .any(|archetype| archetype.split('+').any(|name| name == "Driver"))));
+
+    let metrics = world.resource::<WorldGrowthMetrics>();
+    assert_eq!(metrics.samples, snapshots.snapshots.len() as u64);
+    let latest = metrics.latest.as_ref().expect("latest sample");
+    assert!(metrics.peak_entities >= latest.entities);
+    assert!(metrics.peak_heap_bytes >= latest.approx_heap_bytes());
+
+    let path = temp_parquet_path("snapshot_counts_growth");
+    let provenance = ExportProvenance::for_world(&world);
+    write_snapshot_counts_parquet(&path, snapshots, &provenance).expect("snapshot counts parquet");
+    let specs = parquet_field_specs(&path);
+    let data_columns = specs.len() - provenance_field_specs().len();
+    assert_eq!(
+        specs[data_columns - 3..data_columns],
+        [
+            ("world_entities".to_string(), "UInt64".to_string(), true),
+            ("event_queue_len".to_string(), "UInt64".to_string(), true),
+            ("approx_heap_bytes".to_string(), "UInt64".to_string(), true),
+        ]
+    );
+    std::fs::remove_file(path).expect("temp parquet file should be removable");
+}
@@ -3,7 +3,10 @@ mod support;
 use sim_core::ecs::{Driver, Position, Rider};
 use sim_core::runner::initialize_simulation;
 use sim_core::scenario::{build_scenario, ScenarioParams};
-use sim_core::spawner::{DriverSpawner, RiderSpawner};
+use sim_core::spawner::{
+    DemandEvent, DriverSpawner, OdMatrix, OdZone, RiderSpawner, SupplyElasticity,
+};
+use sim_core::telemetry::SimTelemetry;
 use support::schedule::ScheduleRunner;
 use support::world::TestWorldBuilder;
 
@@ -80,3 +83,118 @@ fn spawner_generates_entities_within_configured_bounds() {
         )
     }));
 }
+
+#[test]
+fn od_matrix_spawns_riders_on_zone_flows() {
+    let mut world = TestWorldBuilder::default().with_seed(7).build();
+    let origin = OdZone::new("suburb", 52.45, 13.30, 1);
+    let destination = OdZone::new("center", 52.52, 13.40, 1);
+    let origin_cell = h3o::LatLng::new(origin.lat, origin.lng)
+        .unwrap()
+        .to_cell(h3o::Resolution::Nine);
+    let destination_cell = h3o::LatLng::new(destination.lat, destination.lng)
+        .unwrap()
+        .to_cell(h3o::Resolution::Nine);
+    let matrix = OdMatrix::new(vec![origin, destination])
+        .with_flow(0, 1, 1.0)
+        .expect("both zones exist");
+
+    let params = ScenarioParams {
+        num_riders: 5,
+        num_drivers: 0,
+        initial_rider_count: 5,
+        seed: Some(7),
+        ..Default::default()
+    }
+    .with_od_matrix(matrix);
+
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    let mut runner = ScheduleRunner::new();
+    assert!(runner.run_one(&mut world));
+
+    let trips: Vec<_> = world
+        .query::<(&Rider, &Position)>()
+        .iter(&world)
+        .map(|(rider, position)| (position.0, rider.destination.expect("destination")))
+        .collect();
+    assert_eq!(trips.len(), 5);
+    for (pickup, dropoff) in trips {
+        assert!(origin_cell.grid_distance(pickup).unwrap() <= 1);
+        assert!(destination_cell.grid_distance(dropoff).unwrap() <= 1);
+    }
+}
+
+#[test]
+fn demand_event_spawns_burst_at_venue() {
+    let mut world = TestWorldBuilder::default().with_seed(9).build();
+    let concert = DemandEvent::new("concert", 52.52, 13.41, 60_000, 10, 6);
+    let venue = concert.venue_cell().expect("venue cell");
+
+    let params = ScenarioParams {
+        num_riders: 0,
+        num_drivers: 0,
+        seed: Some(9),
+        simulation_end_time_ms: Some(60_000 + 10 * 60_000),
+        ..Default::default()
+    }
+    .with_demand_event(concert);
+
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    let mut runner = ScheduleRunner::new();
+    // Event riders leave the world once they abandon or complete, so look while stepping.
+    let mut spawned_at_venue = 0;
+    for _ in 0..10_000 {
+        if !runner.run_one(&mut world) {
+            break;
+        }
+        let at_venue = world
+            .query::<(&Rider, &Position)>()
+            .iter(&world)
+            .filter(|(_, position)| position.0 == venue)
+            .count();
+        spawned_at_venue = spawned_at_venue.max(at_venue);
+    }
+
+    let telemetry = world.resource::<SimTelemetry>();
+    assert_eq!(telemetry.demand_events.len(), 1);
+    let annotation = &telemetry.demand_events[0];
+    assert_eq!(annotation.venue_cell, Some(venue));
+    assert_eq!(annotation.requests_spawned, 6);
+    assert!(spawned_at_venue > 0);
+}
+
+fn drivers_spawned_within(window_ms: u64, supply: Option<SupplyElasticity>) -> usize {
+    let mut world = TestWorldBuilder::default().with_seed(5).build();
+    let mut params = ScenarioParams {
+        num_riders: 0,
+        num_drivers: 200,
+        seed: Some(5),
+        simulation_end_time_ms: Some(window_ms),
+        ..Default::default()
+    };
+    if let Some(supply) = supply {
+        params = params.with_supply_elasticity(supply);
+    }
+
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    let mut runner = ScheduleRunner::new();
+    runner.run_until_empty(&mut world, 10_000);
+    world.resource::<DriverSpawner>().spawned_count()
+}
+
+#[test]
+fn incentives_bring_drivers_online_faster() {
+    let window_ms = 30 * 60_000;
+    let baseline = drivers_spawned_within(window_ms, None);
+    let incentivised = drivers_spawned_within(
+        window_ms,
+        Some(SupplyElasticity::default().with_incentive(25.0, 1.0)),
+    );
+    assert!(
+        incentivised > baseline,
+        "incentive {incentivised} vs baseline {baseline}"
+    );
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "sqlite")]
+
+mod support;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::Connection;
+use sim_core::telemetry::{SimSnapshots, SimTelemetry};
+use sim_core::telemetry_export::write_run_sqlite;
+use support::{run_scenario, small_completing_scenario};
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+        row.get(0)
+    })
+    .expect("count query should run")
+}
+
+#[test]
+fn sqlite_export_writes_all_tables_for_a_run() {
+    let world = run_scenario(small_completing_scenario(3));
+    let telemetry = world.resource::<SimTelemetry>();
+    let snapshots = world.resource::<SimSnapshots>();
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock should be after epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("run_{nanos}.db"));
+    write_run_sqlite(&path, telemetry, snapshots).expect("sqlite export should write");
+    // Writing again replaces the file instead of appending rows.
+    write_run_sqlite(&path, telemetry, snapshots).expect("sqlite export should overwrite");
+
+    let conn = Connection::open(&path).expect("db should open");
+    let completed = telemetry.completed_trips.len() as i64;
+    assert!(completed > 0);
+    assert_eq!(count(&conn, "completed_trips"), completed);
+    assert_eq!(count(&conn, "driver_ledger"), completed);
+    assert_eq!(
+        count(&conn, "snapshot_counts"),
+        snapshots.snapshots.len() as i64
+    );
+    assert!(count(&conn, "events") >= 2 * count(&conn, "trips"));
+    let earnings: f64 = conn
+        .query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM driver_ledger",
+            [],
+            |row| row.get(0),
+        )
+        .expect("ledger sum should run");
+    let expected: f64 = telemetry
+        .completed_trips
+        .iter()
+        .map(|trip| trip.driver_earnings)
+        .sum();
+    assert!((earnings - expected).abs() < 1e-6);
+
+    drop(conn);
+    std::fs::remove_file(path).expect("temp db should be removable");
+}
@@ -1,14 +1,16 @@
 mod support;
 
 use bevy_ecs::prelude::{Entity, World};
-use sim_core::clock::{SimulationClock, ONE_SEC_MS};
+use sim_core::clock::{EventKind, EventSubject, SimulationClock, ONE_SEC_MS};
 use sim_core::distributions::UniformInterArrival;
 use sim_core::ecs::{Driver, Idle, OffDuty, Trip, TripCompleted};
+use sim_core::event_trace::EventTrace;
 use sim_core::pricing::PricingConfig;
 use sim_core::routing::{H3GridRouteProvider, RouteProviderResource};
-use sim_core::runner::initialize_simulation;
+use sim_core::runner::{initialize_simulation, SimulationStopped};
 use sim_core::scenario::{
     create_simple_matching, MatchRadius, RiderCancelConfig, RiderQuoteConfig, SimulationEndTimeMs,
+    StopCondition, StopConditions,
 };
 use sim_core::spatial::SpatialIndex;
 use sim_core::spawner::{DriverSpawner, DriverSpawnerConfig, RiderSpawner, RiderSpawnerConfig};
@@ -52,6 +54,7 @@ fn setup_end_to_end_world(speed_seed: u64, rider_count: usize, rider_end_time_ms
         lng_max: lng + spread,
         min_trip_cells: 2,
         max_trip_cells: 5,
+        od_matrix: None,
         start_time_ms: Some(1000),
         end_time_ms: Some(rider_end_time_ms),
         max_count: Some(rider_count),
@@ -163,3 +166,117 @@ fn simulates_two_concurrent_rides_end_to_end() {
         assert!(record.completed_at >= ONE_SEC_MS);
     }
 }
+
+#[test]
+fn stop_conditions_end_the_run_early() {
+    let mut world = setup_end_to_end_world(2, 2, 3000);
+    world.insert_resource(StopConditions(vec![StopCondition::CompletedTrips(1)]));
+    let mut runner = ScheduleRunner::new();
+    let steps = runner.run_until_empty(&mut world, 1000);
+    assert!(steps < 1000, "runner did not converge");
+
+    let stopped = *world.resource::<SimulationStopped>();
+    assert_eq!(stopped.condition, StopCondition::CompletedTrips(1));
+    assert_eq!(world.resource::<SimTelemetry>().completed_trips.len(), 1);
+    assert!(
+        !runner.run_one(&mut world),
+        "stopped runs take no more steps"
+    );
+
+    let mut world = setup_end_to_end_world(2, 2, 3000);
+    world.insert_resource(StopConditions(vec![
+        StopCondition::CancellationRateAbove {
+            threshold: 0.5,
+            min_resolved: 1,
+        },
+        StopCondition::AllRidersResolved,
+    ]));
+    ScheduleRunner::new().run_until_empty(&mut world, 1000);
+
+    let stopped = *world.resource::<SimulationStopped>();
+    assert_eq!(stopped.condition, StopCondition::AllRidersResolved);
+    assert!(stopped.at_ms < 3_600_000);
+    let telemetry = world.resource::<SimTelemetry>();
+    assert_eq!(telemetry.riders_resolved_total(), 2);
+    assert_eq!(telemetry.rider_cancellation_rate(), 0.0);
+}
+
+#[test]
+fn event_trace_records_a_riders_timeline() {
+    let mut world = setup_end_to_end_world(1, 1, 2000);
+    world.insert_resource(EventTrace::sampled(1));
+    let mut runner = ScheduleRunner::new();
+    runner.run_until_empty(&mut world, 1000);
+
+    // The rider is despawned on completion; its trip still names it.
+    let rider_entity = world
+        .query::<&Trip>()
+        .iter(&world)
+        .map(|trip| trip.rider)
+        .next()
+        .expect("trip");
+    let trace = world.resource::<EventTrace>();
+    let timeline: Vec<_> = trace.timeline(rider_entity).collect();
+    assert_eq!(timeline[0].kind, EventKind::ShowQuote);
+    assert_eq!(timeline[0].subject, EventSubject::Rider(rider_entity));
+    assert!(timeline
+        .windows(2)
+        .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+    let kinds: Vec<EventKind> = timeline.iter().map(|traced| traced.kind).collect();
+    assert!(kinds.contains(&EventKind::TripStarted));
+    assert_eq!(kinds.last(), Some(&EventKind::TripCompleted));
+}
+
+#[test]
+fn spatial_index_tracks_positions_through_a_run() {
+    use sim_core::ecs::{Position, Rider};
+    use sim_core::runner::{run_next_event, simulation_schedule};
+    use sim_core::scenario::{build_scenario, ScenarioParams};
+
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        ScenarioParams {
+            // Scenarios above 200 agents get a spatial index
+            num_riders: 150,
+            num_drivers: 60,
+            ..Default::default()
+        }
+        .with_seed(7)
+        .with_match_radius(3)
+        .with_simulation_end_time_ms(60 * 60 * 1000),
+    );
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+
+    let mut steps = 0;
+    loop {
+        let ran = run_next_event(&mut world, &mut schedule);
+        steps += 1;
+        if steps % 50 == 0 || !ran {
+            let riders: Vec<_> = world
+                .query_filtered::<(Entity, &Position), bevy_ecs::prelude::With<Rider>>()
+                .iter(&world)
+                .map(|(entity, position)| (entity, position.0))
+                .collect();
+            let drivers: Vec<_> = world
+                .query_filtered::<(Entity, &Position), bevy_ecs::prelude::With<Driver>>()
+                .iter(&world)
+                .map(|(entity, position)| (entity, position.0))
+                .collect();
+            let index = world.resource::<SpatialIndex>();
+            assert_eq!(index.rider_count(), riders.len(), "step {steps}");
+            assert_eq!(index.driver_count(), drivers.len(), "step {steps}");
+            for (entity, cell) in riders {
+                assert_eq!(index.get_rider_cell(entity), Some(cell), "step {steps}");
+            }
+            for (entity, cell) in drivers {
+                assert_eq!(index.get_driver_cell(entity), Some(cell), "step {steps}");
+            }
+        }
+        if !ran {
+            break;
+        }
+    }
+    assert!(steps > 100, "scenario should move agents");
+}
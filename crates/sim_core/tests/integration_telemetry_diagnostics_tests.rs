@@ -0,0 +1,360 @@
+//! Per-run analytics telemetry: driver utilization, the request funnel, cell-hour rollups,
+//! match diagnostics, quote features and shadow matching.
+
+mod support;
+
+use arrow::array::BooleanArray;
+use bevy_ecs::prelude::World;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sim_core::clock::SimulationClock;
+use sim_core::provenance::ExportProvenance;
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::{build_scenario, MatchingAlgorithmType, ScenarioParams};
+use sim_core::telemetry::{
+    CellHourStats, FunnelStage, MatchDiagnostics, QuoteFeatureLog, RiderAbandonmentReason,
+    ShadowMatchLog, SimSnapshotConfig, SimTelemetry,
+};
+use sim_core::telemetry_export::{
+    write_cell_hours_parquet, write_driver_utilization_parquet, write_funnel_parquet,
+    write_match_diagnostics_parquet, write_quote_features_parquet, write_shadow_matches_parquet,
+};
+use std::fs::File;
+use support::export::{
+    parquet_field_specs, parquet_row_count, provenance_field_specs, temp_parquet_path,
+};
+use support::{run_scenario, run_with_snapshots, small_completing_scenario};
+
+#[test]
+fn driver_utilization_accounts_for_all_driver_time() {
+    let world = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    let end_ms = world.resource::<SimulationClock>().now();
+    let telemetry = world.resource::<SimTelemetry>();
+    let utilization = telemetry.driver_utilization_at(end_ms);
+
+    assert_eq!(utilization.len(), 30);
+    for driver in &utilization {
+        assert_eq!(
+            driver.online_ms() + driver.off_duty_ms,
+            end_ms - driver.first_seen_ms
+        );
+        assert!((0.0..=1.0).contains(&driver.utilization()));
+    }
+    let on_trip_ms: u64 = utilization.iter().map(|driver| driver.on_trip_ms).sum();
+    assert!(on_trip_ms > 0);
+    assert!(utilization
+        .iter()
+        .any(|driver| driver.deadhead_share() > 0.0));
+
+    let path = temp_parquet_path("driver_utilization");
+    let provenance = ExportProvenance::for_world(&world);
+    write_driver_utilization_parquet(&path, telemetry, end_ms, &provenance)
+        .expect("write utilization");
+    let rows = parquet_row_count(&path);
+    assert_eq!(rows, 30);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn funnel_tracks_every_request_to_its_exit() {
+    let world = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    let telemetry = world.resource::<SimTelemetry>();
+    let summary = telemetry.funnel_summary();
+
+    assert_eq!(summary.requested, telemetry.funnel.len());
+    assert!(summary.requested >= summary.quoted);
+    assert!(summary.quoted >= summary.quote_accepted);
+    assert!(summary.quote_accepted >= summary.matched);
+    assert!(summary.matched >= summary.picked_up);
+    assert!(summary.picked_up >= summary.completed);
+    assert_eq!(summary.completed as u64, telemetry.riders_completed_total);
+    assert!(summary.completed > 0);
+    assert!((0.0..=1.0).contains(&summary.completion_rate()));
+
+    let pickup_timeouts = telemetry
+        .funnel
+        .values()
+        .filter(|funnel| funnel.abandon_reason == Some(RiderAbandonmentReason::PickupTimeout))
+        .count();
+    assert_eq!(
+        pickup_timeouts as u64,
+        telemetry.riders_cancelled_pickup_timeout
+    );
+    let quote_abandons = telemetry
+        .funnel
+        .values()
+        .filter(|funnel| {
+            funnel.abandon_reason.is_some()
+                && funnel.abandon_reason != Some(RiderAbandonmentReason::PickupTimeout)
+        })
+        .count();
+    assert_eq!(
+        quote_abandons as u64,
+        telemetry.riders_abandoned_quote_total
+    );
+
+    for funnel in telemetry.funnel.values() {
+        let stamps = [
+            Some(funnel.requested_at),
+            funnel.quoted_at,
+            funnel.quote_accepted_at,
+            funnel.matched_at,
+            funnel.pickup_at,
+            funnel.completed_at,
+        ];
+        let reached: Vec<u64> = stamps.iter().map_while(|stamp| *stamp).collect();
+        assert!(reached.windows(2).all(|pair| pair[0] <= pair[1]));
+        if funnel.stage() == FunnelStage::Completed {
+            assert_eq!(funnel.exited_at, funnel.completed_at);
+            assert_eq!(funnel.abandon_reason, None);
+        }
+    }
+
+    let path = temp_parquet_path("funnel");
+    let provenance = ExportProvenance::for_world(&world);
+    write_funnel_parquet(&path, telemetry, &provenance).expect("write funnel");
+    let rows = parquet_row_count(&path);
+    assert_eq!(rows, telemetry.funnel.len());
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn cell_hours_roll_up_requests_outcomes_and_idle_time() {
+    let world = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    let end_ms = world.resource::<SimulationClock>().now();
+    let telemetry = world.resource::<SimTelemetry>();
+    let rollup = telemetry.cell_hours_at(end_ms);
+    let summary = telemetry.funnel_summary();
+
+    let total = |field: fn(&CellHourStats) -> u64| -> u64 {
+        rollup.iter().map(|(_, _, stats)| field(stats)).sum()
+    };
+    assert_eq!(total(|stats| stats.requests), summary.requested as u64);
+    assert_eq!(
+        total(|stats| stats.completions),
+        telemetry.riders_completed_total
+    );
+    assert_eq!(
+        total(|stats| stats.cancellations),
+        telemetry.riders_cancelled_pickup_timeout
+    );
+    assert_eq!(
+        total(|stats| stats.abandonments),
+        telemetry.riders_abandoned_quote_total
+    );
+    assert!(total(|stats| stats.quotes) >= summary.quoted as u64);
+    let idle_ms: u64 = telemetry
+        .driver_utilization_at(end_ms)
+        .iter()
+        .map(|driver| driver.idle_ms)
+        .sum();
+    assert_eq!(total(|stats| stats.idle_driver_ms), idle_ms);
+    assert!(rollup.iter().all(|(_, _, stats)| stats
+        .avg_surge_multiplier()
+        .is_none_or(|surge| surge >= 1.0)));
+
+    let path = temp_parquet_path("cell_hours");
+    let provenance = ExportProvenance::for_world(&world);
+    write_cell_hours_parquet(&path, telemetry, end_ms, &provenance).expect("write cell hours");
+    let mut expected = vec![
+        ("cell", "UInt64", false),
+        ("hour_start_ms", "UInt64", false),
+        ("requests", "UInt64", false),
+        ("completions", "UInt64", false),
+        ("cancellations", "UInt64", false),
+        ("abandonments", "UInt64", false),
+        ("quotes", "UInt64", false),
+        ("avg_surge_multiplier", "Float64", true),
+        ("idle_driver_minutes", "Float64", false),
+    ]
+    .into_iter()
+    .map(|(name, data_type, nullable)| (name.to_string(), data_type.to_string(), nullable))
+    .collect::<Vec<_>>();
+    expected.extend(provenance_field_specs());
+    assert_eq!(parquet_field_specs(&path), expected);
+    let rows = parquet_row_count(&path);
+    assert_eq!(rows, rollup.len());
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn match_diagnostics_record_chosen_driver_among_candidates() {
+    let world = run_scenario(small_completing_scenario(3).with_match_diagnostics());
+
+    let diagnostics = world.resource::<MatchDiagnostics>();
+    assert!(!diagnostics.records.is_empty());
+    assert!(diagnostics.records.iter().all(|record| record.batch));
+    let mut decisions_with_match = 0;
+    for record in &diagnostics.records {
+        assert!(record
+            .candidates
+            .iter()
+            .all(|candidate| candidate.rider_entity == record.rider));
+        if let Some(driver) = record.chosen_driver {
+            decisions_with_match += 1;
+            assert!(record
+                .candidates
+                .iter()
+                .any(|candidate| candidate.driver_entity == driver));
+        }
+    }
+    assert!(decisions_with_match > 0);
+
+    let path = temp_parquet_path("match_diagnostics");
+    let provenance = ExportProvenance::for_world(&world);
+    write_match_diagnostics_parquet(&path, diagnostics, &provenance).expect("write diagnostics");
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    let candidates: usize = diagnostics
+        .records
+        .iter()
+        .map(|record| record.candidates.len())
+        .sum();
+    assert_eq!(rows, candidates);
+    let chosen_rows: usize = batches
+        .iter()
+        .map(|batch| {
+            batch
+                .column_by_name("chosen")
+                .unwrap()
+                .as_any()
+                .downcast_ref::<BooleanArray>()
+                .unwrap()
+                .true_count()
+        })
+        .sum();
+    assert_eq!(chosen_rows, decisions_with_match);
+    let _ = std::fs::remove_file(path);
+
+    let without = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    assert!(without.get_resource::<MatchDiagnostics>().is_none());
+}
+
+#[test]
+fn quote_features_join_each_quote_with_its_request_outcome() {
+    let world = run_scenario(small_completing_scenario(3).with_quote_features());
+
+    let log = world.resource::<QuoteFeatureLog>();
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!(!log.records.is_empty());
+    for record in &log.records {
+        assert!(record.local_hour < 24 && record.local_weekday < 7);
+        assert!(record.fare > 0.0 && record.distance_km >= 0.0);
+    }
+
+    let path = temp_parquet_path("quote_features");
+    let provenance = ExportProvenance::for_world(&world);
+    write_quote_features_parquet(&path, log, telemetry, &provenance).expect("write features");
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap())
+        .collect();
+    let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+    assert_eq!(rows, log.records.len());
+    let true_count = |name: &str| -> usize {
+        batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .column_by_name(name)
+                    .unwrap()
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .unwrap()
+                    .true_count()
+            })
+            .sum()
+    };
+    let accepted_requests = telemetry
+        .funnel
+        .values()
+        .filter(|funnel| funnel.quote_accepted_at.is_some())
+        .count();
+    assert!(accepted_requests > 0);
+    assert_eq!(true_count("accepted"), accepted_requests);
+    assert!(true_count("completed") <= true_count("matched"));
+    assert!(true_count("matched") <= true_count("accepted"));
+    let fields = parquet_field_specs(&path);
+    assert!(!fields.iter().any(|(name, _, _)| name.contains("entity")));
+    let _ = std::fs::remove_file(path);
+
+    let without = run_with_snapshots(SimSnapshotConfig::default().counts_only());
+    assert!(without.get_resource::<QuoteFeatureLog>().is_none());
+}
+
+#[test]
+fn shadow_matching_logs_alternative_choices_without_changing_the_run() {
+    let run = |shadow: Option<MatchingAlgorithmType>| {
+        let mut params = ScenarioParams {
+            num_riders: 30,
+            num_drivers: 20,
+            initial_rider_count: 15,
+            initial_driver_count: 20,
+            matching_algorithm_type: Some(MatchingAlgorithmType::Hungarian),
+            ..Default::default()
+        }
+        .with_seed(5)
+        .with_request_window_hours(1)
+        .with_match_radius(5)
+        .with_trip_duration_cells(5, 20)
+        .with_simulation_end_time_ms(2 * 3_600_000);
+        if let Some(kind) = shadow {
+            params = params.with_shadow_matching(kind);
+        }
+        let mut world = World::new();
+        build_scenario(&mut world, params);
+        initialize_simulation(&mut world);
+        let mut schedule = simulation_schedule();
+        run_until_empty(&mut world, &mut schedule, 100_000);
+        world
+    };
+
+    let baseline = run(None);
+    assert!(baseline.get_resource::<ShadowMatchLog>().is_none());
+    let world = run(Some(MatchingAlgorithmType::Simple));
+    let telemetry = world.resource::<SimTelemetry>();
+    let baseline_telemetry = baseline.resource::<SimTelemetry>();
+    assert_eq!(
+        telemetry.completed_trips.len(),
+        baseline_telemetry.completed_trips.len()
+    );
+    assert_eq!(
+        telemetry.funnel_summary(),
+        baseline_telemetry.funnel_summary()
+    );
+
+    let log = world.resource::<ShadowMatchLog>();
+    assert!(!log.records.is_empty());
+    let summary = log.summary();
+    assert_eq!(summary.decisions, log.records.len());
+    assert!(summary.live_matches > 0 && summary.shadow_matches > 0);
+    for record in &log.records {
+        assert_eq!(
+            record.chosen_driver.is_some(),
+            record.chosen_pickup_km.is_some()
+        );
+        assert_eq!(
+            record.shadow_driver.is_some(),
+            record.shadow_pickup_km.is_some()
+        );
+    }
+
+    let path = temp_parquet_path("shadow_matches");
+    let provenance = ExportProvenance::for_world(&world);
+    write_shadow_matches_parquet(&path, log, &provenance).expect("write shadow matches");
+    let rows = parquet_row_count(&path);
+    assert_eq!(rows, log.records.len());
+    let _ = std::fs::remove_file(path);
+
+    // The same algorithm in the shadow agrees with every live decision
+    let same = run(Some(MatchingAlgorithmType::Hungarian));
+    let summary = same.resource::<ShadowMatchLog>().summary();
+    assert!(summary.decisions > 0);
+    assert_eq!(summary.agreements, summary.decisions);
+}
@@ -1,44 +1,32 @@
+//! Trip timestamp validation and the shape of the Parquet, Arrow IPC and NDJSON exports.
+
 mod support;
 
 use std::fs::File;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use arrow::array::{Array, DictionaryArray, StringArray};
+use arrow::datatypes::UInt8Type;
+use arrow::ipc::reader::FileReader;
 use bevy_ecs::prelude::World;
 use h3o::CellIndex;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sim_core::provenance::{ExportProvenance, GIT_SHA, SCENARIO_HASH_KEY};
 use sim_core::runner::{run_until_empty, simulation_schedule};
 use sim_core::scenario::{build_scenario, ScenarioParams};
-use sim_core::telemetry::{SimSnapshots, SimTelemetry, TripSnapshot, TripState};
+use sim_core::telemetry::{
+    SimSnapshot, SimSnapshotConfig, SimSnapshots, SimTelemetry, TripSnapshot, TripState,
+};
 use sim_core::telemetry_export::{
-    validate_trip_timestamp_ordering, write_completed_trips_parquet, write_trips_parquet,
+    validate_trip_timestamp_ordering, write_completed_trips_ipc, write_completed_trips_ndjson,
+    write_completed_trips_parquet, write_snapshot_counts_ipc, write_snapshot_counts_parquet,
+    write_snapshots_ndjson, write_trips_ipc, write_trips_ndjson, write_trips_parquet,
+    JsonCompression,
 };
-
-fn temp_parquet_path(prefix: &str) -> PathBuf {
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("system clock should be after epoch")
-        .as_nanos();
-    std::env::temp_dir().join(format!("{prefix}_{nanos}.parquet"))
-}
-
-fn parquet_field_specs(path: &PathBuf) -> Vec<(String, String, bool)> {
-    let file = File::open(path).expect("parquet file should exist");
-    let builder =
-        ParquetRecordBatchReaderBuilder::try_new(file).expect("parquet reader should build");
-    builder
-        .schema()
-        .fields()
-        .iter()
-        .map(|field| {
-            (
-                field.name().to_string(),
-                field.data_type().to_string(),
-                field.is_nullable(),
-            )
-        })
-        .collect()
-}
+use support::export::{
+    ipc_field_specs, parquet_field_specs, provenance_field_specs, temp_parquet_path,
+};
+use support::{run_scenario, run_with_snapshots, small_completing_scenario};
 
 fn make_test_trip(
     state: TripState,
@@ -198,8 +186,10 @@ fn validate_all_trips_in_snapshots() {
 fn completed_trip_export_schema_matches_expected_columns() {
     let telemetry = SimTelemetry::default();
     let path = temp_parquet_path("completed_trips_schema");
+    let provenance = ExportProvenance::current();
 
-    write_completed_trips_parquet(&path, &telemetry).expect("completed trips parquet should write");
+    write_completed_trips_parquet(&path, &telemetry, &provenance)
+        .expect("completed trips parquet should write");
 
     let specs = parquet_field_specs(&path);
     assert_eq!(
@@ -212,7 +202,14 @@ fn completed_trip_export_schema_matches_expected_columns() {
             ("requested_at".to_string(), "UInt64".to_string(), false),
             ("matched_at".to_string(), "UInt64".to_string(), false),
             ("pickup_at".to_string(), "UInt64".to_string(), false),
+            ("pickup_km".to_string(), "Float64".to_string(), false),
+            ("deadhead_km".to_string(), "Float64".to_string(), false),
+            ("trip_km".to_string(), "Float64".to_string(), false),
+            ("idle_ms".to_string(), "UInt64".to_string(), false),
         ]
+        .into_iter()
+        .chain(provenance_field_specs())
+        .collect::<Vec<_>>()
     );
 
     std::fs::remove_file(path).expect("temp parquet file should be removable");
@@ -222,8 +219,9 @@ fn completed_trip_export_schema_matches_expected_columns() {
 fn trip_export_schema_matches_expected_columns() {
     let snapshots = SimSnapshots::default();
     let path = temp_parquet_path("trips_schema");
+    let provenance = ExportProvenance::current();
 
-    write_trips_parquet(&path, &snapshots).expect("trips parquet should write");
+    write_trips_parquet(&path, &snapshots, &provenance).expect("trips parquet should write");
 
     let specs = parquet_field_specs(&path);
     assert_eq!(
@@ -246,7 +244,196 @@ fn trip_export_schema_matches_expected_columns() {
             ("dropoff_at".to_string(), "UInt64".to_string(), true),
             ("cancelled_at".to_string(), "UInt64".to_string(), true),
         ]
+        .into_iter()
+        .chain(provenance_field_specs())
+        .collect::<Vec<_>>()
     );
 
     std::fs::remove_file(path).expect("temp parquet file should be removable");
 }
+
+#[test]
+fn ipc_exports_share_parquet_schemas_and_rows() {
+    let world = run_scenario(small_completing_scenario(3));
+    let telemetry = world.resource::<SimTelemetry>();
+    let snapshots = world.resource::<SimSnapshots>();
+    let provenance = ExportProvenance::for_world(&world);
+
+    let parquet_path = temp_parquet_path("completed_trips_parquet");
+    let ipc_path = temp_parquet_path("completed_trips_ipc").with_extension("arrow");
+    write_completed_trips_parquet(&parquet_path, telemetry, &provenance)
+        .expect("parquet should write");
+    write_completed_trips_ipc(&ipc_path, telemetry, &provenance).expect("ipc should write");
+    assert_eq!(
+        ipc_field_specs(&ipc_path),
+        parquet_field_specs(&parquet_path)
+    );
+    let rows: usize = FileReader::try_new(File::open(&ipc_path).unwrap(), None)
+        .expect("ipc reader should build")
+        .map(|batch| batch.expect("batch should read").num_rows())
+        .sum();
+    assert_eq!(rows, telemetry.completed_trips.len());
+    assert!(rows > 0);
+
+    let scenario_hash = provenance.scenario_hash.clone().expect("scenario hash");
+    let parquet = ParquetRecordBatchReaderBuilder::try_new(File::open(&parquet_path).unwrap())
+        .expect("parquet reader should build");
+    let key_values = parquet
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .expect("provenance metadata");
+    assert!(key_values.iter().any(|entry| entry.key == SCENARIO_HASH_KEY
+        && entry.value.as_deref() == Some(scenario_hash.as_str())));
+    let ipc = FileReader::try_new(File::open(&ipc_path).unwrap(), None).expect("ipc reader");
+    assert_eq!(
+        ipc.schema().metadata().get("git_sha").map(String::as_str),
+        Some(GIT_SHA)
+    );
+    let batch = ipc.map(|batch| batch.unwrap()).next().expect("batch");
+    let hashes = batch
+        .column_by_name(SCENARIO_HASH_KEY)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<DictionaryArray<UInt8Type>>()
+        .unwrap()
+        .downcast_dict::<StringArray>()
+        .unwrap();
+    assert!(hashes
+        .into_iter()
+        .all(|hash| hash == Some(scenario_hash.as_str())));
+
+    let counts_parquet = temp_parquet_path("counts_parquet");
+    let counts_ipc = temp_parquet_path("counts_ipc").with_extension("arrow");
+    write_snapshot_counts_parquet(&counts_parquet, snapshots, &provenance)
+        .expect("parquet should write");
+    write_snapshot_counts_ipc(&counts_ipc, snapshots, &provenance).expect("ipc should write");
+    assert_eq!(
+        ipc_field_specs(&counts_ipc),
+        parquet_field_specs(&counts_parquet)
+    );
+
+    let trips_parquet = temp_parquet_path("trips_parquet");
+    let trips_ipc = temp_parquet_path("trips_ipc").with_extension("arrow");
+    write_trips_parquet(&trips_parquet, snapshots, &provenance).expect("parquet should write");
+    write_trips_ipc(&trips_ipc, snapshots, &provenance).expect("ipc should write");
+    assert_eq!(
+        ipc_field_specs(&trips_ipc),
+        parquet_field_specs(&trips_parquet)
+    );
+
+    for path in [
+        parquet_path,
+        ipc_path,
+        counts_parquet,
+        counts_ipc,
+        trips_parquet,
+        trips_ipc,
+    ] {
+        std::fs::remove_file(path).expect("temp export file should be removable");
+    }
+}
+
+#[test]
+fn ndjson_exports_write_one_record_per_line_with_optional_zstd() {
+    let config = SimSnapshotConfig {
+        interval_ms: 300_000,
+        ..Default::default()
+    }
+    .with_keyframe_interval(3);
+    let world = run_with_snapshots(config);
+    let telemetry = world.resource::<SimTelemetry>();
+    let snapshots = world.resource::<SimSnapshots>();
+    let provenance = ExportProvenance::for_world(&world);
+    let read_lines = |path: &PathBuf| -> Vec<String> {
+        let bytes = std::fs::read(path).expect("ndjson file should exist");
+        let bytes = match JsonCompression::for_path(path) {
+            JsonCompression::Zstd => zstd::decode_all(bytes.as_slice()).expect("zstd frame"),
+            JsonCompression::None => bytes,
+        };
+        String::from_utf8(bytes)
+            .expect("utf-8")
+            .lines()
+            .map(str::to_string)
+            .collect()
+    };
+
+    let trips_path = temp_parquet_path("trips").with_extension("ndjson");
+    let trips_zst = temp_parquet_path("trips").with_extension("ndjson.zst");
+    write_trips_ndjson(&trips_path, snapshots, JsonCompression::None, &provenance).unwrap();
+    write_trips_ndjson(&trips_zst, snapshots, JsonCompression::Zstd, &provenance).unwrap();
+    let trip_lines = read_lines(&trips_path);
+    assert_eq!(read_lines(&trips_zst), trip_lines);
+    assert!(
+        std::fs::metadata(&trips_zst).unwrap().len()
+            < std::fs::metadata(&trips_path).unwrap().len()
+    );
+    let trips: Vec<TripSnapshot> = trip_lines
+        .iter()
+        .map(|line| serde_json::from_str(line).expect("trip line"))
+        .collect();
+    assert!(!trips.is_empty());
+    assert!(trips
+        .iter()
+        .all(|trip| validate_trip_timestamp_ordering(trip).is_none()));
+    let first: serde_json::Value = serde_json::from_str(&trip_lines[0]).unwrap();
+    assert_eq!(
+        first[SCENARIO_HASH_KEY].as_str(),
+        provenance.scenario_hash.as_deref()
+    );
+
+    let completed_path = temp_parquet_path("completed_trips").with_extension("ndjson");
+    write_completed_trips_ndjson(
+        &completed_path,
+        telemetry,
+        JsonCompression::None,
+        &provenance,
+    )
+    .unwrap();
+    assert_eq!(
+        read_lines(&completed_path).len(),
+        telemetry.completed_trips.len()
+    );
+
+    let snapshots_path = temp_parquet_path("snapshots").with_extension("ndjson.zst");
+    write_snapshots_ndjson(
+        &snapshots_path,
+        snapshots,
+        JsonCompression::for_path(&snapshots_path),
+        &provenance,
+    )
+    .unwrap();
+    let snapshot_lines = read_lines(&snapshots_path);
+    assert_eq!(snapshot_lines.len(), snapshots.snapshots.len());
+    assert!(snapshots
+        .snapshots
+        .iter()
+        .any(|snapshot| !snapshot.keyframe));
+    for (index, line) in snapshot_lines.iter().enumerate() {
+        let written: SimSnapshot = serde_json::from_str(line).expect("snapshot line");
+        let full = snapshots.reconstruct(index).expect("snapshot");
+        // Coordinates may differ in the last bit after a JSON round trip
+        let riders = |snapshot: &SimSnapshot| -> Vec<_> {
+            snapshot
+                .riders
+                .iter()
+                .map(|rider| (rider.entity, rider.cell, rider.state))
+                .collect()
+        };
+        let drivers = |snapshot: &SimSnapshot| -> Vec<_> {
+            snapshot
+                .drivers
+                .iter()
+                .map(|driver| (driver.entity, driver.cell, driver.state))
+                .collect()
+        };
+        assert_eq!(written.timestamp_ms, full.timestamp_ms);
+        assert_eq!(riders(&written), riders(&full));
+        assert_eq!(drivers(&written), drivers(&full));
+        assert_eq!(written.trips, full.trips);
+    }
+
+    for path in [trips_path, trips_zst, completed_path, snapshots_path] {
+        std::fs::remove_file(path).expect("temp export file should be removable");
+    }
+}
@@ -0,0 +1,85 @@
+mod support;
+
+use bevy_ecs::prelude::World;
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::SimTelemetry;
+use sim_core::telemetry_stream::{RecordingPublisher, StreamTopics, TelemetryStream};
+use support::small_completing_scenario;
+
+#[test]
+fn stream_publishes_lifecycle_events_and_snapshots_during_run() {
+    let mut world = World::new();
+    build_scenario(&mut world, small_completing_scenario(3));
+    let recorder = RecordingPublisher::default();
+    world.insert_resource(
+        TelemetryStream::new(Box::new(recorder.clone())).with_topics(StreamTopics {
+            events: "rides.events".to_string(),
+            snapshots: "rides.snapshots".to_string(),
+        }),
+    );
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, 100_000);
+
+    let messages = recorder.messages();
+    let completed_events = messages
+        .iter()
+        .filter(|(topic, _)| topic == "rides.events")
+        .map(|(_, payload)| serde_json::from_slice::<serde_json::Value>(payload).unwrap())
+        .filter(|json| json["kind"] == "TripCompleted")
+        .count();
+    let snapshots = messages
+        .iter()
+        .filter(|(topic, _)| topic == "rides.snapshots")
+        .count();
+
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!(completed_events > 0);
+    assert_eq!(completed_events, telemetry.completed_trips.len());
+    assert!(snapshots > 0);
+    let stream = world.resource::<TelemetryStream>();
+    assert_eq!(stream.published as usize, messages.len());
+    assert_eq!(stream.failed, 0);
+}
+
+#[test]
+fn snapshots_carry_agent_positions_when_enabled() {
+    let mut world = World::new();
+    build_scenario(
+        &mut world,
+        ScenarioParams {
+            num_riders: 10,
+            num_drivers: 10,
+            initial_rider_count: 5,
+            initial_driver_count: 10,
+            ..Default::default()
+        }
+        .with_seed(5)
+        .with_request_window_hours(1)
+        .with_simulation_end_time_ms(3_600_000),
+    );
+    let recorder = RecordingPublisher::default();
+    world.insert_resource(TelemetryStream::new(Box::new(recorder.clone())).with_agent_positions());
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, 100_000);
+
+    let snapshots: Vec<serde_json::Value> = recorder
+        .messages()
+        .iter()
+        .filter(|(topic, _)| topic == "sim.snapshots")
+        .map(|(_, payload)| serde_json::from_slice(payload).unwrap())
+        .collect();
+    let agents = snapshots
+        .iter()
+        .find_map(|json| json["agents"].as_array())
+        .expect("a snapshot with agents");
+    let driver = agents
+        .iter()
+        .find(|agent| agent["kind"] == "driver")
+        .expect("a driver");
+    assert!(driver["id"].is_u64());
+    assert!(driver["state"].is_string());
+    assert!(driver["lat"].as_f64().is_some_and(|lat| lat.abs() <= 90.0));
+}
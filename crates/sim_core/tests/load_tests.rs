@@ -4,9 +4,23 @@ mod support;
 
 use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
 use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::SimSnapshotConfig;
 use std::time::Instant;
 use support::world::TestWorldBuilder;
 
+/// Peak resident set size of this process in KiB (`VmHWM`), where the platform exposes it.
+fn peak_rss_kib() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
 #[test]
 #[ignore] // Only run explicitly: cargo test --package sim_core --test load_tests -- --ignored
 fn test_sustained_load() {
@@ -123,3 +137,54 @@ fn test_long_running() {
         events_per_sec
     );
 }
+
+#[test]
+#[ignore]
+fn test_100k_agents() {
+    // Scale target: 100k riders and 30k drivers over a 4-hour window. Snapshots keep counts
+    // only; per-entity lists every second would dominate memory at this size.
+    let mut world = TestWorldBuilder::default().with_seed(42).build();
+    let params = ScenarioParams {
+        num_drivers: 30_000,
+        num_riders: 100_000,
+        ..Default::default()
+    }
+    .with_seed(42)
+    .with_request_window_hours(4)
+    .with_driver_spread_hours(4)
+    .with_match_radius(5)
+    .with_snapshot_config(SimSnapshotConfig {
+        interval_ms: 60_000,
+        include_riders: false,
+        include_drivers: false,
+        include_trips: false,
+        ..Default::default()
+    })
+    .with_simulation_end_time_ms(4 * 60 * 60 * 1000);
+
+    build_scenario(&mut world, params);
+
+    let start = Instant::now();
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    let events = run_until_empty(&mut world, &mut schedule, 100_000_000);
+    let duration = start.elapsed();
+
+    let events_per_sec = events as f64 / duration.as_secs_f64();
+    let peak_rss = peak_rss_kib().map_or("n/a".to_string(), |kib| {
+        format!("{:.0} MiB", kib as f64 / 1024.0)
+    });
+    println!(
+        "100k-agent test: {} events in {:.2}s ({:.0} events/sec, peak RSS {})",
+        events,
+        duration.as_secs_f64(),
+        events_per_sec,
+        peak_rss
+    );
+
+    assert!(
+        events_per_sec > 1000.0,
+        "Should process >1000 events/sec with 100k riders / 30k drivers, got {:.0}",
+        events_per_sec
+    );
+}
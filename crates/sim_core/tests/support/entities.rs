@@ -127,6 +127,7 @@ impl RiderBuilder {
                 destination: self.destination,
                 requested_at: Some(self.requested_at),
                 quote_rejections: self.quote_rejections,
+                quote_expiries: 0,
                 accepted_fare: self.accepted_fare,
                 last_rejection_reason: self.last_rejection_reason,
             },
@@ -215,6 +216,8 @@ impl DriverBuilder {
                 daily_earnings_target: self.earnings_target,
                 session_start_time_ms: 0,
                 session_end_time_ms: None,
+                weekly_trips: 0,
+                week_index: 0,
             },
             DriverFatigue {
                 fatigue_threshold_ms: self.fatigue_threshold_ms,
@@ -0,0 +1,77 @@
+#![allow(dead_code)]
+
+//! Temp paths and schema readers for export tests.
+
+use std::fs::File;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use arrow::ipc::reader::FileReader;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+pub fn temp_parquet_path(prefix: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock should be after epoch")
+        .as_nanos();
+    std::env::temp_dir().join(format!("{prefix}_{nanos}.parquet"))
+}
+
+pub fn parquet_field_specs(path: &PathBuf) -> Vec<(String, String, bool)> {
+    let file = File::open(path).expect("parquet file should exist");
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(file).expect("parquet reader should build");
+    builder
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            (
+                field.name().to_string(),
+                field.data_type().to_string(),
+                field.is_nullable(),
+            )
+        })
+        .collect()
+}
+
+pub fn ipc_field_specs(path: &PathBuf) -> Vec<(String, String, bool)> {
+    let file = File::open(path).expect("ipc file should exist");
+    let reader = FileReader::try_new(file, None).expect("ipc reader should build");
+    reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            (
+                field.name().to_string(),
+                field.data_type().to_string(),
+                field.is_nullable(),
+            )
+        })
+        .collect()
+}
+
+/// Trailing provenance columns of every export, as `(name, type, nullable)`.
+pub fn provenance_field_specs() -> Vec<(String, String, bool)> {
+    ["sim_core_version", "git_sha", "scenario_hash"]
+        .into_iter()
+        .map(|name| {
+            (
+                name.to_string(),
+                "Dictionary(UInt8, Utf8)".to_string(),
+                true,
+            )
+        })
+        .collect()
+}
+
+/// Number of rows in a Parquet file.
+pub fn parquet_row_count(path: &PathBuf) -> usize {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).expect("parquet file should exist"))
+        .expect("parquet reader should build")
+        .build()
+        .expect("parquet reader should build")
+        .map(|batch| batch.expect("batch should read").num_rows())
+        .sum()
+}
@@ -0,0 +1,187 @@
+#![allow(dead_code)]
+
+//! Golden runs: small scenarios whose run bundle is checked in under `tests/golden/`.
+//!
+//! [`check_golden`] reruns the scenario stored in a checked-in bundle, replays both the fresh
+//! bundle and the checked-in one and compares their key aggregates within per-aggregate
+//! tolerances. A behaviour change that moves an aggregate fails the test until the bundle is
+//! re-recorded with `SIM_UPDATE_GOLDEN=1` and the updated file is committed alongside the
+//! change. Re-recording reruns the stored scenario, so the parameters only live in the bundle;
+//! a new golden run starts from a bundle written with [`record_golden`].
+
+use std::path::PathBuf;
+
+use bevy_ecs::prelude::World;
+use sim_core::ecs::DriverEarnings;
+use sim_core::run_bundle::{RunBundle, RUN_BUNDLE_EXTENSION};
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::{CompletedTripRecord, SimTelemetry};
+
+/// Set to re-record golden bundles instead of checking against them.
+pub const UPDATE_GOLDEN_ENV: &str = "SIM_UPDATE_GOLDEN";
+
+const MAX_STEPS: usize = 1_000_000;
+
+/// Allowed difference between a recorded and a fresh aggregate: within `abs`, or within `rel`
+/// of the recorded value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub abs: f64,
+    pub rel: f64,
+}
+
+impl Tolerance {
+    pub const EXACT: Self = Self { abs: 0.0, rel: 0.0 };
+
+    pub const fn relative(rel: f64) -> Self {
+        Self { abs: 1e-9, rel }
+    }
+
+    pub fn accepts(&self, expected: f64, actual: f64) -> bool {
+        let diff = (actual - expected).abs();
+        diff <= self.abs || diff <= self.rel * expected.abs()
+    }
+}
+
+/// Tolerance for sums and means of floating-point quantities (fares, distances, durations).
+const FLOAT: Tolerance = Tolerance::relative(1e-6);
+
+/// One compared aggregate.
+#[derive(Debug, Clone, Copy)]
+pub struct Aggregate {
+    pub name: &'static str,
+    pub value: f64,
+    pub tolerance: Tolerance,
+}
+
+pub fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.{RUN_BUNDLE_EXTENSION}"))
+}
+
+/// Key aggregates of a recorded run, read from the world the bundle replays into.
+pub fn aggregates(bundle: &RunBundle) -> Vec<Aggregate> {
+    let mut world = bundle.restore_world();
+    let driver_earnings_total: f64 = world
+        .query::<&DriverEarnings>()
+        .iter(&world)
+        .map(|earnings| earnings.daily_earnings)
+        .sum();
+    let telemetry = world.resource::<SimTelemetry>();
+    let trips = &telemetry.completed_trips;
+    let emissions = telemetry.emissions;
+    let count = |name, value: u64| Aggregate {
+        name,
+        value: value as f64,
+        tolerance: Tolerance::EXACT,
+    };
+    let float = |name, value: f64| Aggregate {
+        name,
+        value,
+        tolerance: FLOAT,
+    };
+
+    vec![
+        count("end_time_ms", bundle.end_time_ms),
+        count("completed_trips", trips.len() as u64),
+        count("riders_completed_total", telemetry.riders_completed_total),
+        count("riders_cancelled_total", telemetry.riders_cancelled_total),
+        count(
+            "riders_abandoned_quote_total",
+            telemetry.riders_abandoned_quote_total,
+        ),
+        count("requotes_total", telemetry.requotes_total),
+        float(
+            "mean_time_to_match_ms",
+            mean_ms(trips, CompletedTripRecord::time_to_match),
+        ),
+        float(
+            "mean_time_to_pickup_ms",
+            mean_ms(trips, CompletedTripRecord::time_to_pickup),
+        ),
+        float(
+            "mean_trip_duration_ms",
+            mean_ms(trips, CompletedTripRecord::trip_duration),
+        ),
+        float("total_fares_collected", telemetry.total_fares_collected),
+        float("platform_revenue_total", telemetry.platform_revenue_total),
+        float("driver_earnings_total", driver_earnings_total),
+        float("driven_km", emissions.total_km()),
+        float("g_co2", emissions.total_g_co2()),
+    ]
+}
+
+fn mean_ms(trips: &[CompletedTripRecord], duration: fn(&CompletedTripRecord) -> u64) -> f64 {
+    if trips.is_empty() {
+        return 0.0;
+    }
+    trips.iter().map(duration).sum::<u64>() as f64 / trips.len() as f64
+}
+
+/// Run `params` to completion and capture the bundle checked in as golden run `name`.
+fn run_golden(name: &str, params: ScenarioParams) -> RunBundle {
+    let mut world = World::new();
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, MAX_STEPS);
+    // First and last snapshot only; aggregates come from telemetry.
+    RunBundle::capture(&mut world)
+        .with_label(name)
+        .with_snapshot_interval_ms(u64::MAX)
+}
+
+/// Run `params` and write the result as golden bundle `name`, creating a new golden run.
+pub fn record_golden(name: &str, params: ScenarioParams) {
+    let path = golden_path(name);
+    std::fs::create_dir_all(path.parent().expect("golden dir")).expect("create golden dir");
+    run_golden(name, params)
+        .write(&path)
+        .expect("write golden bundle");
+}
+
+/// Rerun the scenario stored in golden bundle `name` and compare the fresh run with it, or
+/// re-record the bundle from that scenario when [`UPDATE_GOLDEN_ENV`] is set.
+pub fn check_golden(name: &str) {
+    let path = golden_path(name);
+    let golden = RunBundle::read(&path)
+        .unwrap_or_else(|err| panic!("golden bundle {}: {err}", path.display()));
+    let params = golden
+        .scenario
+        .as_ref()
+        .and_then(|scenario| scenario.params.clone())
+        .unwrap_or_else(|| {
+            panic!(
+                "golden bundle {} stores no scenario parameters; record it with record_golden",
+                path.display()
+            )
+        });
+
+    if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+        record_golden(name, params);
+        return;
+    }
+
+    let bundle = run_golden(name, params);
+    let mismatches: Vec<String> = aggregates(&golden)
+        .into_iter()
+        .zip(aggregates(&bundle))
+        .filter(|(expected, actual)| !expected.tolerance.accepts(expected.value, actual.value))
+        .map(|(expected, actual)| {
+            format!(
+                "  {}: recorded {}, now {}",
+                expected.name, expected.value, actual.value
+            )
+        })
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "golden run {name} changed:\n{}\nIf the change is intended, re-record with \
+         {UPDATE_GOLDEN_ENV}=1 and commit {}",
+        mismatches.join("\n"),
+        path.display()
+    );
+}
@@ -1,6 +1,48 @@
 //! Shared helpers for integration tests.
-//! Shared helpers for integration tests.
 
 pub mod entities;
+pub mod export;
+pub mod golden;
 pub mod schedule;
 pub mod world;
+
+use bevy_ecs::prelude::World;
+use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::scenario::{build_scenario, ScenarioParams};
+use sim_core::telemetry::SimSnapshotConfig;
+
+/// Small scenario that completes trips well within its two simulated hours: 30 riders
+/// (15 at start), 30 drivers, a one-hour request window and 5–20 cell trips. Chain further
+/// `with_*` calls for the feature under test.
+#[allow(dead_code)]
+pub fn small_completing_scenario(seed: u64) -> ScenarioParams {
+    ScenarioParams {
+        num_riders: 30,
+        num_drivers: 30,
+        initial_rider_count: 15,
+        initial_driver_count: 30,
+        ..Default::default()
+    }
+    .with_seed(seed)
+    .with_request_window_hours(1)
+    .with_match_radius(5)
+    .with_trip_duration_cells(5, 20)
+    .with_simulation_end_time_ms(2 * 3_600_000)
+}
+
+/// Build `params` into a fresh world and run it until the event queue drains.
+#[allow(dead_code)]
+pub fn run_scenario(params: ScenarioParams) -> World {
+    let mut world = World::new();
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+    let mut schedule = simulation_schedule();
+    run_until_empty(&mut world, &mut schedule, 100_000);
+    world
+}
+
+/// [`small_completing_scenario`] (seed 3) run with `config` snapshots.
+#[allow(dead_code)]
+pub fn run_with_snapshots(config: SimSnapshotConfig) -> World {
+    run_scenario(small_completing_scenario(3).with_snapshot_config(config))
+}
@@ -3,6 +3,7 @@ mod support;
 use bevy_ecs::prelude::{Entity, Schedule, World};
 use bevy_ecs::schedule::apply_deferred;
 use sim_core::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
+use sim_core::decision_models::{DriverAcceptanceFeature, DriverAcceptanceModel};
 use sim_core::ecs::{
     Driver, DriverEarnings, DriverFatigue, EnRoute, Evaluating, GeoPosition, Idle, Position, Rider,
     Trip, TripEnRoute, Waiting,
@@ -32,6 +33,7 @@ fn setup_world(
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: Some(accepted_fare),
                 last_rejection_reason: None,
             },
@@ -55,6 +57,8 @@ fn setup_world(
                 daily_earnings_target: 200.0,
                 session_start_time_ms: 0,
                 session_end_time_ms: None,
+                weekly_trips: 0,
+                week_index: 0,
             },
             DriverFatigue {
                 fatigue_threshold_ms: 8 * 3600 * 1000,
@@ -173,3 +177,24 @@ fn driver_decision_is_reproducible_with_same_seed() {
         world2.entity(driver_entity2).contains::<EnRoute>()
     );
 }
+
+#[test]
+fn acceptance_model_replaces_the_fixed_weights() {
+    // Fixed weights that would always reject
+    let (mut world, _, driver_entity, _, _) = setup_world(
+        DriverDecisionConfig {
+            seed: 42,
+            base_acceptance_score: -100.0,
+            ..Default::default()
+        },
+        20.0,
+    );
+    world.insert_resource(DriverAcceptanceModel {
+        intercept: -5.0,
+        weights: [(DriverAcceptanceFeature::Fare, 1.0)].into(),
+    });
+
+    run_driver_decision(&mut world, driver_entity);
+
+    assert!(world.entity(driver_entity).contains::<EnRoute>());
+}
@@ -4,7 +4,9 @@ use bevy_ecs::prelude::{Entity, Schedule, World};
 use bevy_ecs::schedule::apply_deferred;
 use sim_core::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock, ONE_MIN_MS};
 use sim_core::ecs::{Driver, DriverEarnings, DriverFatigue, EnRoute, Idle, OffDuty};
+use sim_core::pricing::PricingConfig;
 use sim_core::systems::driver_offduty::driver_offduty_check_system;
+use sim_core::telemetry::SimTelemetry;
 
 fn spawn_driver(
     world: &mut World,
@@ -26,6 +28,8 @@ fn spawn_driver(
                     daily_earnings_target: earnings_target,
                     session_start_time_ms: 0,
                     session_end_time_ms: None,
+                    weekly_trips: 0,
+                    week_index: 0,
                 },
                 DriverFatigue {
                     fatigue_threshold_ms,
@@ -45,6 +49,8 @@ fn spawn_driver(
                     daily_earnings_target: earnings_target,
                     session_start_time_ms: 0,
                     session_end_time_ms: None,
+                    weekly_trips: 0,
+                    week_index: 0,
                 },
                 DriverFatigue {
                     fatigue_threshold_ms,
@@ -141,3 +147,37 @@ fn driver_goes_offduty_when_fatigue_exceeded_while_en_route() {
         "driver over fatigue threshold should go OffDuty even when EnRoute"
     );
 }
+
+#[test]
+fn earnings_floor_tops_up_driver_when_going_offduty() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(PricingConfig {
+        min_hourly_earnings: 20.0,
+        ..Default::default()
+    });
+    world.insert_resource(SimTelemetry::default());
+
+    let target_time = 9 * 60 * 60 * 1000;
+    let driver_entity = spawn_driver(&mut world, 50.0, 200.0, 8 * 60 * 60 * 1000, false);
+
+    world.resource_mut::<SimulationClock>().schedule_at(
+        target_time,
+        EventKind::CheckDriverOffDuty,
+        Some(EventSubject::Driver(driver_entity)),
+    );
+    let event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("check driver offduty event");
+    run_for_event(&mut world, event);
+
+    assert!(world.entity(driver_entity).contains::<OffDuty>());
+    let earnings = world
+        .get::<DriverEarnings>(driver_entity)
+        .expect("earnings");
+    assert!((earnings.daily_earnings - 180.0).abs() < 1e-9);
+    let telemetry = world.resource::<SimTelemetry>();
+    assert!((telemetry.earnings_floor_top_ups_total - 130.0).abs() < 1e-9);
+    assert_eq!(telemetry.drivers_topped_up_total, 1);
+}
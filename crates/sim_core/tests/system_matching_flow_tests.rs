@@ -2,13 +2,19 @@ mod support;
 
 use bevy_ecs::prelude::{Entity, Schedule, World};
 use bevy_ecs::schedule::apply_deferred;
-use sim_core::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock, ONE_SEC_MS};
+use sim_core::clock::{
+    CurrentEvent, EventKind, EventSubject, Recurrence, SimulationClock, ONE_SEC_MS,
+};
 use sim_core::ecs::{Driver, Evaluating, GeoPosition, Idle, Position, Rider, Waiting};
+use sim_core::location::{LocationReportingConfig, ReportedPosition};
 use sim_core::matching::{MatchingAlgorithmResource, SimpleMatching};
 use sim_core::scenario::BatchMatchingConfig;
+use sim_core::sim_log::{SimLog, SimLogEvent};
+use sim_core::systems::location_report::location_report_system;
 use sim_core::systems::match_accepted::match_accepted_system;
 use sim_core::systems::match_rejected::match_rejected_system;
 use sim_core::systems::matching::matching_system;
+use sim_core::telemetry::SimTelemetry;
 
 fn seed_cell() -> h3o::CellIndex {
     h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell")
@@ -39,6 +45,7 @@ fn setup_world_with_rejected_rider(batch_config: Option<BatchMatchingConfig>) ->
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: Some(10.0),
                 last_rejection_reason: None,
             },
@@ -78,6 +85,7 @@ fn matches_waiting_rider_to_idle_driver() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -145,6 +153,158 @@ fn matches_waiting_rider_to_idle_driver() {
     );
 }
 
+#[test]
+fn unmatched_rider_is_logged_and_retried() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimLog::default());
+    world.insert_resource(MatchingAlgorithmResource::new(Box::new(SimpleMatching)));
+    let cell = seed_cell();
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(neighbor_cell(cell)),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Waiting,
+            Position(cell),
+            GeoPosition(cell.into()),
+        ))
+        .id();
+
+    world.resource_mut::<SimulationClock>().schedule_at_secs(
+        0,
+        EventKind::TryMatch,
+        Some(EventSubject::Rider(rider_entity)),
+    );
+    let event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("try match event");
+    world.insert_resource(CurrentEvent(event));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((matching_system, apply_deferred));
+    schedule.run(&mut world);
+
+    let entries: Vec<_> = world.resource::<SimLog>().entries().cloned().collect();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].event,
+        SimLogEvent::NoDriverInRadius {
+            rider: rider_entity,
+            idle_drivers: 0,
+        }
+    );
+    assert_eq!(entries[0].event.entity(), Some(rider_entity));
+
+    let retry = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("retry event");
+    assert_eq!(retry.kind, EventKind::TryMatch);
+}
+
+#[test]
+fn matches_on_stale_reported_position_and_refreshes_on_report_tick() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimTelemetry::default());
+    world.insert_resource(LocationReportingConfig::default());
+    world.insert_resource(MatchingAlgorithmResource::new(Box::new(SimpleMatching)));
+    let cell = seed_cell();
+    let moved_to = neighbor_cell(cell);
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(moved_to),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Waiting,
+            Position(cell),
+            GeoPosition(cell.into()),
+        ))
+        .id();
+    // The driver last reported from the rider's cell, then moved on.
+    let driver_entity = world
+        .spawn((
+            Driver {
+                matched_rider: None,
+                assigned_trip: None,
+            },
+            Idle,
+            Position(moved_to),
+            GeoPosition(moved_to.into()),
+            ReportedPosition {
+                cell,
+                reported_at_ms: 0,
+            },
+        ))
+        .id();
+
+    world.resource_mut::<SimulationClock>().schedule_at_secs(
+        3,
+        EventKind::TryMatch,
+        Some(EventSubject::Rider(rider_entity)),
+    );
+    let event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("try match event");
+    world.insert_resource(CurrentEvent(event));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((matching_system, location_report_system, apply_deferred));
+    schedule.run(&mut world);
+
+    let rider = world.entity(rider_entity).get::<Rider>().expect("rider");
+    assert_eq!(rider.matched_driver, Some(driver_entity));
+    let location_error = world.resource::<SimTelemetry>().location_error;
+    assert_eq!(location_error.matches, 1);
+    assert!(location_error.error_km_total > 0.0);
+    assert_eq!(location_error.report_age_ms_total, 3 * ONE_SEC_MS);
+
+    // The next report tick replaces the stale fix with the driver's true cell.
+    world.resource_mut::<SimulationClock>().pop_next();
+    world.resource_mut::<SimulationClock>().schedule_recurring(
+        EventKind::LocationReport,
+        None,
+        Recurrence::every(4 * ONE_SEC_MS).starting_at(4 * ONE_SEC_MS),
+    );
+    let event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("location report event");
+    world.insert_resource(CurrentEvent(event));
+    schedule.run(&mut world);
+
+    let reported = world
+        .entity(driver_entity)
+        .get::<ReportedPosition>()
+        .expect("reported position");
+    assert_eq!(reported.cell, moved_to);
+    assert_eq!(reported.reported_at_ms, 4 * ONE_SEC_MS);
+    let next_event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("next location report");
+    assert_eq!(next_event.kind, EventKind::LocationReport);
+    assert_eq!(next_event.timestamp, 8 * ONE_SEC_MS);
+}
+
 #[test]
 fn match_accepted_schedules_driver_decision() {
     let mut world = World::new();
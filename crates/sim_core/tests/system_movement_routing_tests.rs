@@ -4,9 +4,11 @@ use bevy_ecs::prelude::Schedule;
 use sim_core::clock::{CurrentEvent, EventKind, EventSubject};
 use sim_core::ecs::{
     Driver, EnRoute, GeoPosition, Position, Rider, Trip, TripEnRoute, TripFinancials, TripLiveData,
-    TripTiming, Waiting,
+    TripRoute, TripTiming, Waiting,
 };
 use sim_core::systems::movement::movement_system;
+use sim_core::telemetry::SimTelemetry;
+use sim_core::traffic::{CongestionZones, RerouteConfig};
 
 use support::world::TestWorldBuilder;
 
@@ -34,6 +36,7 @@ fn movement_steps_toward_rider_and_schedules_trip_start() {
                 destination: Some(dropoff),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -72,6 +75,8 @@ fn movement_steps_toward_rider_and_schedules_trip_start() {
             TripFinancials {
                 agreed_fare: None,
                 pickup_distance_km_at_accept: 0.0,
+                deadhead_km: 0.0,
+                trip_km: 0.0,
             },
             TripLiveData { pickup_eta_ms: 0 },
         ))
@@ -125,3 +130,115 @@ fn movement_steps_toward_rider_and_schedules_trip_start() {
     );
     assert_eq!(next_event.subject, Some(EventSubject::Trip(trip_entity)));
 }
+
+#[test]
+fn movement_reroutes_when_traffic_changed_since_routing() {
+    let origin = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+    let pickup = origin
+        .grid_disk::<Vec<_>>(3)
+        .into_iter()
+        .find(|cell| origin.grid_distance(*cell).ok() == Some(3))
+        .expect("pickup cell");
+    let mut zones = CongestionZones::default();
+    zones.cell_factors.insert(origin, 0.5);
+    let mut world = TestWorldBuilder::new()
+        .with_seed(1)
+        .with_congestion_zones(zones)
+        .build();
+    world.insert_resource(RerouteConfig {
+        enabled: true,
+        interval_secs: 60,
+        min_traffic_change: 0.1,
+    });
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(origin),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Waiting,
+            Position(pickup),
+            GeoPosition(pickup.into()),
+        ))
+        .id();
+    let driver_entity = world
+        .spawn((
+            Driver {
+                matched_rider: Some(rider_entity),
+                assigned_trip: None,
+            },
+            EnRoute,
+            Position(origin),
+            GeoPosition(origin.into()),
+        ))
+        .id();
+    // Route resolved at t=0 under free-flow traffic; the driver now sits in a 0.5x zone.
+    let stale_route = TripRoute::from_cells(vec![origin, pickup])
+        .expect("route")
+        .stamp(0, 1.0);
+    let trip_entity = world
+        .spawn((
+            Trip {
+                rider: rider_entity,
+                driver: driver_entity,
+                pickup,
+                dropoff: origin,
+            },
+            TripEnRoute,
+            TripTiming {
+                requested_at: 0,
+                matched_at: 0,
+                pickup_at: None,
+                dropoff_at: None,
+                cancelled_at: None,
+            },
+            TripFinancials {
+                agreed_fare: None,
+                pickup_distance_km_at_accept: 0.0,
+                deadhead_km: 0.0,
+                trip_km: 0.0,
+            },
+            TripLiveData { pickup_eta_ms: 0 },
+            stale_route,
+        ))
+        .id();
+
+    world
+        .resource_mut::<sim_core::clock::SimulationClock>()
+        .schedule_at_secs(
+            120,
+            EventKind::MoveStep,
+            Some(EventSubject::Trip(trip_entity)),
+        );
+    let event = world
+        .resource_mut::<sim_core::clock::SimulationClock>()
+        .pop_next()
+        .expect("move step event");
+    world.insert_resource(CurrentEvent(event));
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(movement_system);
+    schedule.run(&mut world);
+
+    assert_eq!(world.resource::<SimTelemetry>().reroutes_total, 1);
+    let route = world
+        .query::<&TripRoute>()
+        .get(&world, trip_entity)
+        .expect("route");
+    assert_eq!(route.routed_at_ms, 120_000);
+    assert_eq!(route.traffic_factor_at_routing, 0.5);
+    assert!(route.points.len() > 2, "rerouted along the H3 grid path");
+    let eta = world
+        .query::<&TripLiveData>()
+        .get(&world, trip_entity)
+        .expect("live data")
+        .pickup_eta_ms;
+    assert!(eta > 0);
+}
@@ -3,14 +3,23 @@ mod support;
 use bevy_ecs::prelude::{Schedule, World};
 use bevy_ecs::schedule::apply_deferred;
 use sim_core::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock, ONE_SEC_MS};
-use sim_core::ecs::{Browsing, GeoPosition, Position, Rider, RiderQuote, Waiting};
+use sim_core::decision_models::{ConversionFeature, ConversionModel};
+use sim_core::ecs::{
+    Browsing, DeferredDispatch, DispatchNotBefore, GeoPosition, Position, QuoteExpiresAt, Rider,
+    RiderQuote, VehicleClassChoice, Waiting,
+};
+use sim_core::pricing::guardrails::{SurgeRateLimit, SurgeRateLimiter};
+use sim_core::pricing::probe::probe_pricing;
+use sim_core::pricing::vehicle_classes::{VehicleClass, VehicleClassMenu};
 use sim_core::pricing::{PricingConfig, BASE_FARE};
 use sim_core::scenario::{BatchMatchingConfig, RiderCancelConfig, RiderQuoteConfig};
+use sim_core::spatial::distance_km_between_cells;
 use sim_core::systems::quote_accepted::quote_accepted_system;
 use sim_core::systems::quote_decision::quote_decision_system;
 use sim_core::systems::quote_rejected::quote_rejected_system;
 use sim_core::systems::show_quote::show_quote_system;
-use sim_core::telemetry::SimTelemetry;
+use sim_core::telemetry::{RiderAbandonmentReason, SimTelemetry};
+use support::entities::{DriverBuilder, RiderBuilder};
 
 fn seed_cell() -> h3o::CellIndex {
     h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell")
@@ -45,6 +54,7 @@ fn quote_decision_with_accept_probability_one_schedules_accepted() {
         seed: 42,
         max_willingness_to_pay: 100.0,
         max_acceptable_eta_ms: 600_000,
+        ..Default::default()
     });
     let cell = seed_cell();
     let destination = neighbor_cell(cell);
@@ -57,6 +67,7 @@ fn quote_decision_with_accept_probability_one_schedules_accepted() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -100,6 +111,7 @@ fn quote_decision_with_accept_probability_zero_schedules_rejected() {
         seed: 42,
         max_willingness_to_pay: 100.0,
         max_acceptable_eta_ms: 600_000,
+        ..Default::default()
     });
     let cell = seed_cell();
     let destination = neighbor_cell(cell);
@@ -112,6 +124,7 @@ fn quote_decision_with_accept_probability_zero_schedules_rejected() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -144,6 +157,51 @@ fn quote_decision_with_accept_probability_zero_schedules_rejected() {
     assert_eq!(next_event.subject, Some(EventSubject::Rider(rider_entity)));
 }
 
+#[test]
+fn conversion_model_replaces_the_configured_accept_probability() {
+    let decide = |model: ConversionModel| {
+        let mut world = World::new();
+        world.insert_resource(SimulationClock::default());
+        world.insert_resource(RiderQuoteConfig {
+            accept_probability: 1.0,
+            seed: 42,
+            ..Default::default()
+        });
+        world.insert_resource(model);
+        let rider_entity = RiderBuilder::new().spawn(&mut world);
+        world.entity_mut(rider_entity).insert(RiderQuote {
+            fare: 5.0,
+            eta_ms: 60_000,
+        });
+        schedule_current_event(
+            &mut world,
+            EventKind::QuoteDecision,
+            EventSubject::Rider(rider_entity),
+            1,
+        );
+        let mut schedule = Schedule::default();
+        schedule.add_systems(quote_decision_system);
+        schedule.run(&mut world);
+        world
+            .resource_mut::<SimulationClock>()
+            .pop_next()
+            .expect("next event")
+            .kind
+    };
+
+    // A steep fare penalty rejects a quote the flat model would always accept
+    let pricey = ConversionModel {
+        intercept: 0.0,
+        weights: [(ConversionFeature::QuotedFare, -10.0)].into(),
+    };
+    assert_eq!(decide(pricey), EventKind::QuoteRejected);
+    let eager = ConversionModel {
+        intercept: 20.0,
+        ..Default::default()
+    };
+    assert_eq!(decide(eager), EventKind::QuoteAccepted);
+}
+
 #[test]
 fn quote_rejected_under_limit_reschedules_show_quote() {
     let mut world = World::new();
@@ -156,6 +214,7 @@ fn quote_rejected_under_limit_reschedules_show_quote() {
         seed: 42,
         max_willingness_to_pay: 100.0,
         max_acceptable_eta_ms: 600_000,
+        ..Default::default()
     });
     let cell = seed_cell();
     let destination = neighbor_cell(cell);
@@ -168,6 +227,7 @@ fn quote_rejected_under_limit_reschedules_show_quote() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 1,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -219,6 +279,7 @@ fn quote_rejected_at_limit_despawns_and_increments_telemetry() {
         seed: 42,
         max_willingness_to_pay: 100.0,
         max_acceptable_eta_ms: 600_000,
+        ..Default::default()
     });
     let cell = seed_cell();
     let destination = neighbor_cell(cell);
@@ -231,6 +292,7 @@ fn quote_rejected_at_limit_despawns_and_increments_telemetry() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 2,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -275,6 +337,7 @@ fn quote_accepted_transitions_rider_state() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -324,6 +387,60 @@ fn quote_accepted_transitions_rider_state() {
     );
 }
 
+#[test]
+fn quote_accepted_with_deferred_dispatch_delays_try_match() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(RiderCancelConfig::default());
+    let cell = seed_cell();
+    let destination = neighbor_cell(cell);
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(destination),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Browsing,
+            RiderQuote {
+                fare: 9.0,
+                eta_ms: 360_000,
+            },
+            DeferredDispatch { delay_ms: 300_000 },
+        ))
+        .id();
+
+    schedule_current_event(
+        &mut world,
+        EventKind::QuoteAccepted,
+        EventSubject::Rider(rider_entity),
+        1,
+    );
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((quote_accepted_system, apply_deferred));
+    schedule.run(&mut world);
+
+    let not_before = world
+        .entity(rider_entity)
+        .get::<DispatchNotBefore>()
+        .expect("dispatch not before");
+    assert_eq!(not_before.0, 301_000);
+
+    let next_event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("try match event");
+    assert_eq!(next_event.kind, EventKind::TryMatch);
+    assert_eq!(next_event.timestamp, 302_000);
+}
+
 #[test]
 fn show_quote_computes_quote_and_schedules_quote_decision() {
     let mut world = World::new();
@@ -340,6 +457,7 @@ fn show_quote_computes_quote_and_schedules_quote_decision() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -392,6 +510,7 @@ fn quote_accepted_does_not_schedule_try_match_when_batch_matching_enabled() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -421,3 +540,250 @@ fn quote_accepted_does_not_schedule_try_match_when_batch_matching_enabled() {
     assert_eq!(first.kind, EventKind::RiderCancel);
     assert!(world.resource::<SimulationClock>().is_empty());
 }
+
+#[test]
+fn quote_accept_probability_reduces_to_flat_and_responds_to_price_and_eta() {
+    let flat = RiderQuoteConfig {
+        accept_probability: 0.8,
+        ..Default::default()
+    };
+    assert_eq!(flat.accept_probability_for(50.0, 10.0, 900_000), 0.8);
+
+    let logit = RiderQuoteConfig {
+        accept_probability: 0.8,
+        price_sensitivity: -3.0,
+        eta_sensitivity_per_min: -0.1,
+        ..Default::default()
+    };
+    let at_reference = logit.accept_probability_for(10.0, 10.0, 0);
+    assert!((at_reference - 0.8).abs() < 1e-9);
+    let surged = logit.accept_probability_for(15.0, 10.0, 0);
+    let slow = logit.accept_probability_for(10.0, 10.0, 600_000);
+    assert!(surged < at_reference);
+    assert!(slow < at_reference);
+}
+
+#[test]
+fn quote_decision_after_expiry_schedules_rejected_as_expired() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimTelemetry::default());
+    world.insert_resource(RiderQuoteConfig {
+        accept_probability: 1.0,
+        quote_ttl_secs: Some(1),
+        ..Default::default()
+    });
+    let cell = seed_cell();
+    let destination = neighbor_cell(cell);
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(destination),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Browsing,
+            Position(cell),
+            GeoPosition(cell.into()),
+            RiderQuote {
+                fare: 5.0,
+                eta_ms: 60_000,
+            },
+            QuoteExpiresAt(ONE_SEC_MS),
+        ))
+        .id();
+
+    schedule_current_event(
+        &mut world,
+        EventKind::QuoteDecision,
+        EventSubject::Rider(rider_entity),
+        5,
+    );
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(quote_decision_system);
+    schedule.run(&mut world);
+
+    let next_event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("next event");
+    assert_eq!(next_event.kind, EventKind::QuoteRejected);
+    let rider = world.get::<Rider>(rider_entity).expect("rider");
+    assert_eq!(
+        rider.last_rejection_reason,
+        Some(RiderAbandonmentReason::QuoteExpired)
+    );
+    assert_eq!(world.resource::<SimTelemetry>().quotes_expired_total, 1);
+}
+
+#[test]
+fn expired_quote_does_not_count_toward_quote_rejections() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimTelemetry::default());
+    world.insert_resource(RiderQuoteConfig {
+        max_quote_rejections: 2,
+        re_quote_delay_secs: 10,
+        ..Default::default()
+    });
+    let cell = seed_cell();
+    let destination = neighbor_cell(cell);
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(destination),
+                requested_at: None,
+                quote_rejections: 2,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: Some(RiderAbandonmentReason::QuoteExpired),
+            },
+            Browsing,
+            Position(cell),
+            GeoPosition(cell.into()),
+        ))
+        .id();
+
+    schedule_current_event(
+        &mut world,
+        EventKind::QuoteRejected,
+        EventSubject::Rider(rider_entity),
+        1,
+    );
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((quote_rejected_system, apply_deferred));
+    schedule.run(&mut world);
+
+    let rider = world.get::<Rider>(rider_entity).expect("rider re-quotes");
+    assert_eq!(rider.quote_rejections, 2);
+    assert_eq!(rider.quote_expiries, 1);
+    let next_event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("next event");
+    assert_eq!(next_event.kind, EventKind::ShowQuote);
+    assert_eq!(
+        world
+            .resource::<SimTelemetry>()
+            .riders_abandoned_quote_total,
+        0
+    );
+}
+
+#[test]
+fn show_quote_with_vehicle_classes_quotes_chosen_class() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(PricingConfig::default());
+    world.insert_resource(SimTelemetry::default());
+    // Only the premium class is ever chosen: its constant dominates the utility
+    world.insert_resource(
+        VehicleClassMenu::default()
+            .with_class(VehicleClass::new("standard", 1.0, 1.0))
+            .with_class(VehicleClass::new("premium", 2.0, 1.0).with_utility_constant(100.0)),
+    );
+    let cell = seed_cell();
+    let destination = neighbor_cell(cell);
+
+    let rider_entity = world
+        .spawn((
+            Rider {
+                matched_driver: None,
+                assigned_trip: None,
+                destination: Some(destination),
+                requested_at: None,
+                quote_rejections: 0,
+                quote_expiries: 0,
+                accepted_fare: None,
+                last_rejection_reason: None,
+            },
+            Browsing,
+            Position(cell),
+            GeoPosition(cell.into()),
+        ))
+        .id();
+
+    schedule_current_event(
+        &mut world,
+        EventKind::ShowQuote,
+        EventSubject::Rider(rider_entity),
+        1,
+    );
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems(show_quote_system);
+    schedule.run(&mut world);
+
+    let rider_quote = world.get::<RiderQuote>(rider_entity).expect("RiderQuote");
+    assert!(rider_quote.fare >= 2.0 * BASE_FARE);
+    assert_eq!(
+        world.get::<VehicleClassChoice>(rider_entity),
+        Some(&VehicleClassChoice {
+            chosen: "premium".to_string(),
+            offered: "standard".to_string(),
+        })
+    );
+    let telemetry = world.resource::<SimTelemetry>();
+    assert_eq!(telemetry.vehicle_class_choices.get("premium"), Some(&1));
+    assert_eq!(telemetry.vehicle_class_substitutions, 1);
+}
+
+#[test]
+fn pricing_probe_predicts_the_next_quote_without_changing_state() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(PricingConfig {
+        surge_enabled: true,
+        surge_rate_limit: Some(SurgeRateLimit::default()),
+        ..PricingConfig::default()
+    });
+    world.insert_resource(SurgeRateLimiter::default());
+    // Surge clusters are resolution-9 grid disks.
+    let cell = seed_cell()
+        .parent(h3o::Resolution::Nine)
+        .expect("resolution-9 parent");
+    let destination = neighbor_cell(cell);
+    let rider_entity = RiderBuilder::new()
+        .with_position(cell)
+        .with_destination(destination)
+        .spawn(&mut world);
+    for _ in 0..2 {
+        RiderBuilder::new().with_position(cell).spawn(&mut world);
+    }
+    DriverBuilder::new().with_position(cell).spawn(&mut world);
+
+    let probe = probe_pricing(&world, cell).expect("pricing probe");
+    assert_eq!((probe.demand, probe.supply), (3, 1));
+    assert_eq!(probe.raw_multiplier, 2.0);
+    assert!((probe.surge_multiplier - 1.2).abs() < 1e-9);
+    assert_eq!(probe.last_quoted_multiplier, None);
+    assert_eq!(probe_pricing(&world, cell), Some(probe));
+
+    schedule_current_event(
+        &mut world,
+        EventKind::ShowQuote,
+        EventSubject::Rider(rider_entity),
+        0,
+    );
+    let mut schedule = Schedule::default();
+    schedule.add_systems(show_quote_system);
+    schedule.run(&mut world);
+
+    let rider_quote = world.get::<RiderQuote>(rider_entity).expect("RiderQuote");
+    let what_if = probe.fare_for_distance(distance_km_between_cells(cell, destination));
+    assert!((rider_quote.fare - what_if.fare).abs() < 1e-9);
+    assert_eq!(rider_quote.eta_ms, probe.eta_ms);
+    let probe = probe_pricing(&world, cell).expect("pricing probe");
+    assert_eq!(probe.last_quoted_multiplier, Some(1.2));
+}
@@ -37,6 +37,7 @@ fn rider_cancel_resets_driver_and_trip() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -75,6 +76,8 @@ fn rider_cancel_resets_driver_and_trip() {
             TripFinancials {
                 agreed_fare: None,
                 pickup_distance_km_at_accept: 0.0,
+                deadhead_km: 0.0,
+                trip_km: 0.0,
             },
             TripLiveData { pickup_eta_ms: 0 },
         ))
@@ -136,6 +139,7 @@ fn rider_cancel_without_match_despawns_rider() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -4,10 +4,13 @@ use bevy_ecs::prelude::{Schedule, World};
 use bevy_ecs::schedule::apply_deferred;
 use sim_core::clock::{CurrentEvent, EventKind, EventSubject, SimulationClock};
 use sim_core::ecs::{
-    Driver, GeoPosition, InTransit, OnTrip, Position, Rider, Trip, TripCompleted, TripEnRoute,
-    TripOnTrip, TripTiming, Waiting,
+    Driver, GeoPosition, InTransit, OnTrip, Position, Rider, Trip, TripCancelled, TripCompleted,
+    TripEnRoute, TripOnTrip, TripRoute, TripTiming, Waiting,
 };
+use sim_core::pricing::tolls::{TollZone, TollZones};
 use sim_core::pricing::PricingConfig;
+use sim_core::scenario::EntityGcConfig;
+use sim_core::systems::entity_gc::entity_gc_system;
 use sim_core::systems::trip_completed::trip_completed_system;
 use sim_core::systems::trip_started::trip_started_system;
 use sim_core::telemetry::SimTelemetry;
@@ -31,6 +34,7 @@ fn trip_started_transitions_and_schedules_completion() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -69,6 +73,8 @@ fn trip_started_transitions_and_schedules_completion() {
             sim_core::ecs::TripFinancials {
                 agreed_fare: None,
                 pickup_distance_km_at_accept: 0.0,
+                deadhead_km: 0.0,
+                trip_km: 0.0,
             },
             sim_core::ecs::TripLiveData { pickup_eta_ms: 0 },
         ))
@@ -131,6 +137,7 @@ fn trip_completed_transitions_driver_and_rider() {
                 destination: Some(destination),
                 requested_at: None,
                 quote_rejections: 0,
+                quote_expiries: 0,
                 accepted_fare: None,
                 last_rejection_reason: None,
             },
@@ -149,6 +156,8 @@ fn trip_completed_transitions_driver_and_rider() {
                 daily_earnings_target: 100.0,
                 session_start_time_ms: 0,
                 session_end_time_ms: None,
+                weekly_trips: 0,
+                week_index: 0,
             },
         ))
         .id();
@@ -171,6 +180,8 @@ fn trip_completed_transitions_driver_and_rider() {
             sim_core::ecs::TripFinancials {
                 agreed_fare: None,
                 pickup_distance_km_at_accept: 0.0,
+                deadhead_km: 0.0,
+                trip_km: 0.0,
             },
             sim_core::ecs::TripLiveData { pickup_eta_ms: 0 },
         ))
@@ -212,3 +223,156 @@ fn trip_completed_transitions_driver_and_rider() {
     assert_eq!(driver.matched_rider, None);
     assert!(world.entity(trip_entity).contains::<TripCompleted>());
 }
+
+/// Complete a trip from `pickup` to its neighbour `dropoff`, optionally driven along `route`,
+/// with a toll zone on `toll_cell`; returns the recorded tolls.
+fn completed_trip_tolls(
+    pickup: h3o::CellIndex,
+    dropoff: h3o::CellIndex,
+    toll_cell: h3o::CellIndex,
+    route: Option<TripRoute>,
+) -> f64 {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimTelemetry::default());
+    world.insert_resource(PricingConfig::default());
+    world.insert_resource(
+        TollZones::default().with_zone(TollZone::disk("bridge", toll_cell, 0, 3.0)),
+    );
+    let rider_entity = world.spawn(InTransit).id();
+    let driver_entity = world
+        .spawn((
+            Driver {
+                matched_rider: Some(rider_entity),
+                assigned_trip: None,
+            },
+            OnTrip,
+        ))
+        .id();
+    let mut trip = world.spawn((
+        Trip {
+            rider: rider_entity,
+            driver: driver_entity,
+            pickup,
+            dropoff,
+        },
+        TripOnTrip,
+        TripTiming {
+            requested_at: 0,
+            matched_at: 1,
+            pickup_at: Some(2),
+            dropoff_at: None,
+            cancelled_at: None,
+        },
+        sim_core::ecs::TripFinancials {
+            agreed_fare: Some(20.0),
+            pickup_distance_km_at_accept: 0.0,
+            deadhead_km: 0.0,
+            trip_km: 0.0,
+        },
+    ));
+    if let Some(route) = route {
+        trip.insert(route);
+    }
+    let trip_entity = trip.id();
+
+    world.resource_mut::<SimulationClock>().schedule_at_secs(
+        2,
+        EventKind::TripCompleted,
+        Some(EventSubject::Trip(trip_entity)),
+    );
+    let event = world
+        .resource_mut::<SimulationClock>()
+        .pop_next()
+        .expect("trip completed event");
+    world.insert_resource(CurrentEvent(event));
+    let mut schedule = Schedule::default();
+    schedule.add_systems((trip_completed_system, apply_deferred));
+    schedule.run(&mut world);
+
+    world.resource::<SimTelemetry>().completed_trips[0].tolls
+}
+
+#[test]
+fn trip_completed_charges_tolls_on_the_driven_route() {
+    let pickup = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+    let ring = |k: u32| {
+        pickup
+            .grid_disk::<Vec<_>>(k)
+            .into_iter()
+            .find(|cell| pickup.grid_distance(*cell) == Ok(k as i32))
+            .expect("cell at distance k")
+    };
+    let dropoff = ring(1);
+    let detour = ring(2);
+
+    // The direct grid path misses the zone; the driven detour passes through it.
+    assert_eq!(completed_trip_tolls(pickup, dropoff, detour, None), 0.0);
+    let mut route = TripRoute::from_cells(vec![pickup, detour, dropoff]).expect("route");
+    route.next_segment_index = 2;
+    assert_eq!(
+        completed_trip_tolls(pickup, dropoff, detour, Some(route)),
+        3.0
+    );
+}
+
+#[test]
+fn entity_gc_despawns_finished_trips_after_retention() {
+    let mut world = World::new();
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(SimTelemetry::default());
+    world.insert_resource(EntityGcConfig {
+        interval_secs: 60,
+        retention_secs: 600,
+    });
+    world.resource_mut::<SimulationClock>().schedule_at_secs(
+        1_000,
+        EventKind::SimulationStarted,
+        None,
+    );
+    world.resource_mut::<SimulationClock>().pop_next();
+
+    let cell = h3o::CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+    let fake_rider = world.spawn_empty().id();
+    let fake_driver = world.spawn_empty().id();
+    let mut spawn_trip = |dropoff_at: Option<u64>, cancelled_at: Option<u64>| {
+        let mut trip = world.spawn((
+            Trip {
+                rider: fake_rider,
+                driver: fake_driver,
+                pickup: cell,
+                dropoff: cell,
+            },
+            TripTiming {
+                requested_at: 0,
+                matched_at: 0,
+                pickup_at: None,
+                dropoff_at,
+                cancelled_at,
+            },
+        ));
+        match (dropoff_at, cancelled_at) {
+            (Some(_), _) => trip.insert(TripCompleted),
+            (None, Some(_)) => trip.insert(TripCancelled),
+            (None, None) => trip.insert(TripEnRoute),
+        };
+        trip.id()
+    };
+    let expired_completed = spawn_trip(Some(100_000), None);
+    let expired_cancelled = spawn_trip(None, Some(200_000));
+    let retained_completed = spawn_trip(Some(900_000), None);
+    let active = spawn_trip(None, None);
+
+    let mut schedule = Schedule::default();
+    schedule.add_systems((entity_gc_system, apply_deferred));
+    schedule.run(&mut world);
+
+    assert!(world.get_entity(expired_completed).is_none());
+    assert!(world.get_entity(expired_cancelled).is_none());
+    assert!(world.get_entity(retained_completed).is_some());
+    assert!(world.get_entity(active).is_some());
+    let totals = world.resource::<SimTelemetry>().entity_gc;
+    assert_eq!(totals.trips_completed, 1);
+    assert_eq!(totals.trips_cancelled, 1);
+    assert_eq!(totals.last_run_at_ms, Some(1_000_000));
+}
@@ -8,18 +8,33 @@
 //! 5. Export results to Parquet/JSON
 //!
 //! To use a different parameter space, change the function call in main().
+//! Pass `--no-progress` to hide the progress bar (e.g. when logging to a file) and
+//! `--memory-budget-mb <MB>` to run fewer simulations at once on small machines.
+
+use std::time::Duration;
 
 use sim_core::scenario::MatchingAlgorithmType;
 use sim_experiments::{
-    export_to_csv,
+    calculate_health_breakdowns,
+    export_to_csv_with_health,
     // export_to_json, export_to_parquet,
     find_best_parameters,
     find_best_result_index,
-    run_parallel_experiments,
+    partition_outcomes,
+    rank_results,
+    ranking_stability,
+    run_parallel_experiments_isolated,
     HealthWeights,
+    RunLimits,
+    SweepConcurrency,
 };
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let show_progress = !std::env::args().any(|arg| arg == "--no-progress");
+    let memory_budget_mb: Option<u64> = std::env::args()
+        .skip_while(|arg| arg != "--memory-budget-mb")
+        .nth(1)
+        .and_then(|value| value.parse().ok());
     println!("Starting parameter sweep experiment...");
 
     // Select which parameter space to use:
@@ -37,10 +52,29 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         parameter_sets.len()
     );
 
-    // Run experiments in parallel (uses all available CPU cores by default)
-    println!("Running simulations in parallel...");
-    let results = run_parallel_experiments(parameter_sets.clone(), None);
+    // Run experiments in parallel (all available CPU cores unless the memory budget allows fewer)
+    let mut concurrency = SweepConcurrency::default();
+    if let Some(megabytes) = memory_budget_mb {
+        concurrency = concurrency.with_memory_budget_mb(megabytes);
+    }
+    let num_threads = concurrency.thread_count(&parameter_sets);
+    println!("Running simulations in parallel ({num_threads} at a time)...");
+    // A run that panics or exceeds 10 minutes is recorded as failed; the sweep carries on
+    let limits = RunLimits::default().with_wall_clock_timeout(Duration::from_secs(600));
+    let outcomes = run_parallel_experiments_isolated(
+        parameter_sets.clone(),
+        Some(num_threads),
+        limits,
+        show_progress,
+    );
+    let (parameter_sets, results, failures) = partition_outcomes(parameter_sets, outcomes);
     println!("Completed {} simulations", results.len());
+    for failed in &failures {
+        println!(
+            "Run {} (seed {}) failed: {}",
+            failed.experiment_id, failed.seed, failed.failure
+        );
+    }
 
     // Calculate health scores
     println!("Calculating health scores...");
@@ -65,6 +99,58 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
     println!("Abandoned riders: {}", best_result.abandoned_quote_riders);
 
+    // Why it won: health score terms, and where it beat the runner-up
+    let breakdowns = calculate_health_breakdowns(&results, &weights);
+    let best_breakdown = &breakdowns[best_idx];
+    println!("\n=== Health Score {:.3} ===", best_breakdown.score);
+    for score in &best_breakdown.components {
+        println!(
+            "{:<15} raw {:>12.2}  normalized {:.2} x weight {:>5.2} = {:>6.3}",
+            score.component.name(),
+            score.raw,
+            score.normalized,
+            score.weight,
+            score.contribution
+        );
+    }
+    let runner_up = breakdowns
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != best_idx)
+        .max_by(|(_, a), (_, b)| a.score.total_cmp(&b.score));
+    if let Some((_, runner_up)) = runner_up {
+        println!("Ahead of the runner-up ({:.3}) by:", runner_up.score);
+        for (component, difference) in best_breakdown
+            .contribution_differences(runner_up)
+            .into_iter()
+            .filter(|(_, difference)| *difference != 0.0)
+        {
+            println!("  {:<15} {:+.3}", component.name(), difference);
+        }
+    }
+
+    // Top 5, and whether they hold up under ±10% weight noise
+    println!("\n=== Top 5 ===");
+    for ranked in rank_results(&results, &weights, 5) {
+        println!(
+            "#{} run {:>4}  score {:.3}",
+            ranked.rank, ranked.index, ranked.score
+        );
+    }
+    let stability = ranking_stability(&results, &weights, 5, 0.1, 100, 42);
+    println!(
+        "Under ±10% weight noise: best unchanged in {:.0}%, top 5 unchanged in {:.0}% of {} samples",
+        stability.best_unchanged_share * 100.0,
+        stability.top_k_unchanged_share * 100.0,
+        stability.samples
+    );
+    for range in &stability.rank_ranges {
+        println!(
+            "  run {:>4}  rank {} (ranges {}..={})",
+            range.index, range.rank, range.best_rank, range.worst_rank
+        );
+    }
+
     if let Some(best_params) = find_best_parameters(&results, &parameter_sets, &weights) {
         println!("\n=== Best Parameters ===");
         if let Some(pricing) = &best_params.params.pricing_config {
@@ -103,7 +189,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // export_to_parquet(&results, "experiment_results.parquet")?;
     // println!("Exported to experiment_results.parquet");
 
-    export_to_csv(&results, &parameter_sets, "experiment_results.csv")?;
+    export_to_csv_with_health(
+        &results,
+        &parameter_sets,
+        &weights,
+        "experiment_results.csv",
+    )?;
     println!("Exported to experiment_results.csv");
 
     println!("\nExperiment complete!");
@@ -0,0 +1,49 @@
+//! Render sweep results into a self-contained HTML report.
+//!
+//! Usage: `results_report --input <results.parquet> [--output <report.html>]
+//! [--title <text>] [--rank-by <metric>] [--metrics <a,b,...>]
+//! [--filter <key=value,...>] [--group-by <tag key>]`
+//! (or `cargo run -p xtask -- report --input results.parquet --output report.html`).
+
+use std::process::exit;
+
+use sim_experiments::report::{write_report, ReportOptions};
+
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let Some(input) = arg_value("--input") else {
+        eprintln!("missing required argument --input");
+        exit(2);
+    };
+    let output = arg_value("--output").unwrap_or_else(|| "report.html".to_string());
+    let options = ReportOptions {
+        title: arg_value("--title").unwrap_or_else(|| format!("Sweep results: {input}")),
+        rank_by: arg_value("--rank-by"),
+        chart_metrics: arg_value("--metrics")
+            .map(|metrics| {
+                metrics
+                    .split(',')
+                    .map(|metric| metric.trim().to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        tag_filters: arg_value("--filter")
+            .map(|filters| {
+                filters
+                    .split(',')
+                    .filter_map(|filter| filter.split_once('='))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        group_by: arg_value("--group-by"),
+        ..ReportOptions::default()
+    };
+
+    write_report(&input, &output, &options)?;
+    println!("Wrote report to {output}");
+    Ok(())
+}
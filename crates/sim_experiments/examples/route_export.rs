@@ -4,6 +4,16 @@
 //! Berlin bounds by calling the H3GridRouteProvider (or OSRM when available),
 //! then serializes the results to a JSON file that can be loaded for analysis.
 //!
+//! With `--format bin` (the default for `.bin` outputs; needs the `precomputed`
+//! feature) the full routes are written as a `sim_core::routing::precomputed::RouteTable`
+//! with region bounds, provider and sample seed embedded, ready to load through
+//! `RouteProviderKind::Precomputed { path }`:
+//!
+//! ```sh
+//! cargo run --example route_export -p sim_experiments --features precomputed -- \
+//!     --output route_table.bin
+//! ```
+//!
 //! This example demonstrates the route export concept. For full OSRM-based
 //! pre-computation, enable the `osrm` and `precomputed` features on `sim_core`:
 //!
@@ -17,8 +27,13 @@
 //!     --features sim_core/osrm,sim_core/precomputed
 //! ```
 
+#[cfg(feature = "precomputed")]
+use std::collections::HashMap;
+
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+#[cfg(feature = "precomputed")]
+use sim_core::routing::precomputed::{CellPair, RegionBounds, RouteTable, RouteTableMetadata};
 use sim_core::routing::{H3GridRouteProvider, RouteProvider};
 
 /// Berlin bounding box.
@@ -26,6 +41,7 @@ const LAT_MIN: f64 = 52.34;
 const LAT_MAX: f64 = 52.68;
 const LNG_MIN: f64 = 13.08;
 const LNG_MAX: f64 = 13.76;
+const SAMPLE_SEED: u64 = 42;
 
 /// Sample N random H3 cells within Berlin bounds.
 fn sample_cells(count: usize, seed: u64) -> Vec<h3o::CellIndex> {
@@ -60,14 +76,38 @@ fn main() {
         .nth(1)
         .unwrap_or_else(|| "route_table.json".to_string());
 
+    let format = std::env::args()
+        .skip_while(|a| a != "--format")
+        .nth(1)
+        .unwrap_or_else(|| {
+            if output_path.ends_with(".bin") {
+                "bin".to_string()
+            } else {
+                "json".to_string()
+            }
+        });
+    let binary = match format.as_str() {
+        "json" => false,
+        "bin" if cfg!(feature = "precomputed") => true,
+        "bin" => {
+            eprintln!("--format bin needs the precomputed feature (--features precomputed)");
+            std::process::exit(2);
+        }
+        other => {
+            eprintln!("unknown --format {other} (expected json or bin)");
+            std::process::exit(2);
+        }
+    };
+
     println!("Route Table Export Tool");
     println!("=======================");
     println!("Sample cells: {}", sample_count);
     println!("Output file:  {}", output_path);
+    println!("Format:       {}", format);
     println!();
 
     // Sample cells within Berlin bounds
-    let cells = sample_cells(sample_count, 42);
+    let cells = sample_cells(sample_count, SAMPLE_SEED);
     println!("Sampled {} unique cells within Berlin bounds.", cells.len());
 
     // Use H3GridRouteProvider (always available)
@@ -85,6 +125,8 @@ fn main() {
 
     // Build a simple JSON-serializable table (distance + duration only)
     let mut entries: Vec<serde_json::Value> = Vec::new();
+    #[cfg(feature = "precomputed")]
+    let mut routes = HashMap::new();
 
     for (i, &from) in cells.iter().enumerate() {
         if i % 10 == 0 {
@@ -109,6 +151,10 @@ fn main() {
                     "duration_secs": route.duration_secs,
                     "num_cells": route.cells.len(),
                 }));
+                #[cfg(feature = "precomputed")]
+                if binary {
+                    routes.insert(CellPair::new(from, to), route);
+                }
             }
         }
     }
@@ -126,10 +172,34 @@ fn main() {
         }
     );
 
+    if binary {
+        #[cfg(feature = "precomputed")]
+        {
+            let metadata = RouteTableMetadata {
+                provider: "H3GridRouteProvider".to_string(),
+                osrm_version: None,
+                bounds: RegionBounds {
+                    lat_min: LAT_MIN,
+                    lat_max: LAT_MAX,
+                    lng_min: LNG_MIN,
+                    lng_max: LNG_MAX,
+                },
+                sample_seed: SAMPLE_SEED,
+                sample_cells: cells.len(),
+            };
+            match RouteTable::new(metadata, routes).write(&output_path) {
+                Ok(()) => println!("  Written to: {}", output_path),
+                Err(e) => eprintln!("  ERROR writing file: {}", e),
+            }
+        }
+        return;
+    }
+
     // Write to JSON
     let table = serde_json::json!({
         "metadata": {
             "provider": "H3GridRouteProvider",
+            "sample_seed": SAMPLE_SEED,
             "sample_cells": cells.len(),
             "total_pairs": total_pairs,
             "routes_found": routes_found,
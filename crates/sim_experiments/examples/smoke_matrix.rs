@@ -0,0 +1,190 @@
+//! Smoke matrix: run small scenarios across every matching algorithm × routing mode ×
+//! batch on/off in parallel and check basic invariants on each result.
+//!
+//! Catches integration regressions (a combination that panics, stalls or stops completing
+//! trips) that per-module unit tests miss. Exits non-zero when any scenario fails.
+//!
+//! Run with: cargo run -p sim_experiments --example smoke_matrix --release
+//! (or `cargo run -p xtask -- smoke`)
+
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
+use sim_core::traffic::TrafficProfileKind;
+use sim_experiments::{
+    partition_outcomes, run_parallel_experiments_isolated, ParameterSet, RunLimits,
+    SimulationResult,
+};
+
+const NUM_RIDERS: usize = 80;
+const NUM_DRIVERS: usize = 30;
+const REQUEST_WINDOW_HOURS: u64 = 1;
+const END_TIME_MS: u64 = (REQUEST_WINDOW_HOURS + 1) * 3_600_000;
+const SEED: u64 = 42;
+
+const ALGORITHMS: [(MatchingAlgorithmType, &str); 3] = [
+    (MatchingAlgorithmType::Simple, "simple"),
+    (MatchingAlgorithmType::CostBased, "cost_based"),
+    (MatchingAlgorithmType::Hungarian, "hungarian"),
+];
+
+/// Routing modes available without external services. OSRM and precomputed route tables
+/// need a server or a route file and are left to their own integration tests.
+#[derive(Clone, Copy)]
+enum RoutingMode {
+    /// H3 grid paths at free-flow speed.
+    FreeFlow,
+    /// H3 grid paths with the Berlin traffic profile and congestion.
+    Traffic,
+}
+
+impl RoutingMode {
+    const ALL: [RoutingMode; 2] = [RoutingMode::FreeFlow, RoutingMode::Traffic];
+
+    fn name(self) -> &'static str {
+        match self {
+            RoutingMode::FreeFlow => "free_flow",
+            RoutingMode::Traffic => "traffic",
+        }
+    }
+
+    fn apply(self, params: &mut ScenarioParams) {
+        if let RoutingMode::Traffic = self {
+            params.traffic_profile = TrafficProfileKind::Berlin;
+            params.congestion_zones_enabled = true;
+            params.dynamic_congestion_enabled = true;
+        }
+    }
+}
+
+fn smoke_parameter_sets() -> Vec<ParameterSet> {
+    let mut sets = Vec::new();
+    for (algorithm, algorithm_name) in ALGORITHMS {
+        for routing in RoutingMode::ALL {
+            for batch in [true, false] {
+                let mut params = ScenarioParams {
+                    num_riders: NUM_RIDERS,
+                    num_drivers: NUM_DRIVERS,
+                    matching_algorithm_type: Some(algorithm),
+                    batch_matching_enabled: Some(batch),
+                    ..Default::default()
+                }
+                .with_request_window_hours(REQUEST_WINDOW_HOURS)
+                .with_match_radius(5)
+                .with_trip_duration_cells(5, 30)
+                .with_simulation_end_time_ms(END_TIME_MS);
+                routing.apply(&mut params);
+
+                let name = format!(
+                    "{algorithm_name}/{}/batch_{}",
+                    routing.name(),
+                    if batch { "on" } else { "off" }
+                );
+                let run_id = sets.len();
+                sets.push(ParameterSet::new(params, name, run_id, SEED));
+            }
+        }
+    }
+    sets
+}
+
+/// Invariants every scenario in the matrix must satisfy; returns the violated ones.
+fn invariant_violations(result: &SimulationResult) -> Vec<String> {
+    let mut violations = Vec::new();
+    let mut check = |ok: bool, message: String| {
+        if !ok {
+            violations.push(message);
+        }
+    };
+
+    check(
+        result.total_riders > 0 && result.total_drivers > 0,
+        format!(
+            "no agents spawned ({} riders, {} drivers)",
+            result.total_riders, result.total_drivers
+        ),
+    );
+    let resolved =
+        result.completed_riders + result.abandoned_quote_riders + result.cancelled_riders;
+    check(
+        resolved <= result.total_riders,
+        format!(
+            "{resolved} resolved riders exceed {} spawned",
+            result.total_riders
+        ),
+    );
+    check(result.completed_trips > 0, "no trips completed".to_string());
+    for (name, rate) in [
+        ("conversion_rate", result.conversion_rate),
+        ("funnel_quote_rate", result.funnel_quote_rate),
+        ("funnel_match_rate", result.funnel_match_rate),
+        ("funnel_pickup_rate", result.funnel_pickup_rate),
+        ("funnel_completion_rate", result.funnel_completion_rate),
+    ] {
+        check(
+            (0.0..=1.0).contains(&rate),
+            format!("{name} {rate} outside [0, 1]"),
+        );
+    }
+    for (name, amount) in [
+        ("platform_revenue", result.platform_revenue),
+        ("driver_payouts", result.driver_payouts),
+        ("total_fares_collected", result.total_fares_collected),
+        ("avg_time_to_match_ms", result.avg_time_to_match_ms),
+        ("avg_time_to_pickup_ms", result.avg_time_to_pickup_ms),
+    ] {
+        check(
+            amount.is_finite() && amount >= 0.0,
+            format!("{name} {amount} is negative or not finite"),
+        );
+    }
+    check(
+        result.p90_time_to_match_ms >= result.median_time_to_match_ms,
+        format!(
+            "p90 time to match {} below median {}",
+            result.p90_time_to_match_ms, result.median_time_to_match_ms
+        ),
+    );
+    violations
+}
+
+fn main() {
+    let parameter_sets = smoke_parameter_sets();
+    println!("Running {} smoke scenarios...", parameter_sets.len());
+
+    let started = Instant::now();
+    // A combination that stalls is a failure, not a hung CI job
+    let limits = RunLimits::default().with_wall_clock_timeout(Duration::from_secs(120));
+    let outcomes = run_parallel_experiments_isolated(parameter_sets.clone(), None, limits, false);
+    let (succeeded_sets, results, failures) = partition_outcomes(parameter_sets, outcomes);
+
+    let mut failed = failures.len();
+    for run in &failures {
+        println!("FAIL {}: {}", run.experiment_id, run.failure);
+    }
+    for (set, result) in succeeded_sets.iter().zip(&results) {
+        let violations = invariant_violations(result);
+        if violations.is_empty() {
+            println!(
+                "ok   {}: {} trips, conversion {:.0}%",
+                set.experiment_id,
+                result.completed_trips,
+                result.conversion_rate * 100.0
+            );
+        } else {
+            failed += 1;
+            println!("FAIL {}: {}", set.experiment_id, violations.join("; "));
+        }
+    }
+
+    println!(
+        "\n{} of {} scenarios passed in {:.1?}",
+        results.len() + failures.len() - failed,
+        results.len() + failures.len(),
+        started.elapsed()
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Concurrency throttling for sweeps: caps how many simulations run at once, by an explicit
+//! limit and by a memory budget divided by a per-run memory estimate.
+//!
+//! The resulting thread count is passed as `num_threads` to any `run_parallel_experiments*`
+//! function, so a sweep of large scenarios runs fewer simulations at a time instead of
+//! exhausting RAM.
+
+use sim_core::scenario::ScenarioParams;
+use sim_core::telemetry::SimSnapshotConfig;
+
+use crate::parameters::ParameterSet;
+
+/// Fixed cost of a simulation world (schedule, resources, spatial indexes).
+const BASE_RUN_BYTES: u64 = 16 * 1024 * 1024;
+/// Per spawned rider/driver: components, telemetry records and export buffers.
+const ENTITY_BYTES: u64 = 2 * 1024;
+/// Per entity recorded in one snapshot (rider, driver or trip row).
+const SNAPSHOT_ENTITY_BYTES: u64 = 96;
+/// Share of all riders present in the world at a typical moment (the rest are yet to spawn or
+/// already resolved).
+const LIVE_RIDER_SHARE: f64 = 0.25;
+/// Simulated time runners add after the request window when no end time is set.
+const DEFAULT_DRAIN_MS: u64 = 2 * 60 * 60 * 1000;
+
+/// Rough peak memory of one simulation run in bytes. Dominated by retained snapshots, so it
+/// scales with fleet size, snapshot count and snapshot sampling. Intended for throttling, not
+/// accounting: expect it to be within a factor of about two.
+pub fn estimate_run_memory_bytes(params: &ScenarioParams) -> u64 {
+    let entities = (params.num_riders + params.num_drivers) as u64;
+    let snapshot_config = params.snapshot_config.unwrap_or_default();
+    let snapshot_bytes = snapshot_count(params, &snapshot_config)
+        * live_snapshot_rows(params, &snapshot_config)
+        * SNAPSHOT_ENTITY_BYTES;
+    BASE_RUN_BYTES + entities * ENTITY_BYTES + snapshot_bytes
+}
+
+fn snapshot_count(params: &ScenarioParams, config: &SimSnapshotConfig) -> u64 {
+    let duration_ms = params
+        .simulation_end_time_ms
+        .unwrap_or_else(|| params.request_window_ms.saturating_add(DEFAULT_DRAIN_MS));
+    (duration_ms / config.interval_ms.max(1)).min(config.max_snapshots as u64)
+}
+
+fn live_snapshot_rows(params: &ScenarioParams, config: &SimSnapshotConfig) -> u64 {
+    let live_riders = params.num_riders as f64 * LIVE_RIDER_SHARE;
+    let mut rows = 0.0;
+    if config.include_riders {
+        rows += live_riders;
+    }
+    if config.include_drivers {
+        rows += params.num_drivers as f64;
+    }
+    if config.include_trips {
+        rows += live_riders;
+    }
+    (rows * config.entity_sample_fraction.clamp(0.0, 1.0)).ceil() as u64
+}
+
+/// How many simulations a sweep may run at once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepConcurrency {
+    /// Hard cap on concurrent simulations (None = one per available core).
+    pub max_concurrent: Option<usize>,
+    /// Memory the sweep may use, in bytes; divided by the largest per-run estimate
+    /// (see [`estimate_run_memory_bytes`]). None = unlimited.
+    pub memory_budget_bytes: Option<u64>,
+}
+
+impl SweepConcurrency {
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(max_concurrent);
+        self
+    }
+
+    pub fn with_memory_budget_mb(mut self, megabytes: u64) -> Self {
+        self.memory_budget_bytes = Some(megabytes.saturating_mul(1024 * 1024));
+        self
+    }
+
+    /// Thread count for running `parameter_sets` on this machine; at least 1, so a budget
+    /// smaller than one run still makes progress (serially).
+    pub fn thread_count(&self, parameter_sets: &[ParameterSet]) -> usize {
+        let available = std::thread::available_parallelism()
+            .map(|cores| cores.get())
+            .unwrap_or(1);
+        self.thread_count_for(parameter_sets, available)
+    }
+
+    fn thread_count_for(&self, parameter_sets: &[ParameterSet], available: usize) -> usize {
+        let mut threads = available.max(1);
+        if let Some(max_concurrent) = self.max_concurrent {
+            threads = threads.min(max_concurrent);
+        }
+        if let Some(budget) = self.memory_budget_bytes {
+            let largest_run = parameter_sets
+                .iter()
+                .map(|set| estimate_run_memory_bytes(&set.params))
+                .max()
+                .unwrap_or(0);
+            if let Some(fit) = budget.checked_div(largest_run) {
+                threads = threads.min(fit as usize);
+            }
+        }
+        threads.clamp(1, parameter_sets.len().max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::ParameterSpace;
+
+    #[test]
+    fn estimate_grows_with_fleet_size() {
+        let small = ScenarioParams {
+            num_riders: 100,
+            num_drivers: 20,
+            ..Default::default()
+        };
+        let large = ScenarioParams {
+            num_riders: 10_000,
+            num_drivers: 2_000,
+            ..Default::default()
+        };
+        assert!(estimate_run_memory_bytes(&large) > 10 * estimate_run_memory_bytes(&small));
+    }
+
+    #[test]
+    fn memory_budget_caps_threads_by_largest_run() {
+        let sets = ParameterSpace::grid()
+            .num_riders(vec![100, 200, 300, 10_000])
+            .num_drivers(vec![2_000])
+            .generate();
+        let largest = estimate_run_memory_bytes(&sets[3].params);
+
+        let budget = SweepConcurrency {
+            max_concurrent: None,
+            memory_budget_bytes: Some(3 * largest + 1),
+        };
+        assert_eq!(budget.thread_count_for(&sets, 16), 3);
+        assert_eq!(budget.with_max_concurrent(1).thread_count_for(&sets, 16), 1);
+
+        let tiny = SweepConcurrency::default().with_memory_budget_mb(1);
+        assert_eq!(tiny.thread_count_for(&sets, 16), 1);
+        // Never more threads than runs
+        assert_eq!(SweepConcurrency::default().thread_count_for(&sets, 16), 4);
+    }
+}
@@ -5,7 +5,10 @@
 
 use std::path::Path;
 
-use crate::health::HealthWeights;
+use sim_core::provenance::ExportProvenance;
+use sim_core::telemetry_export::{write_ndjson, JsonCompression};
+
+use crate::health::{calculate_health_breakdowns, HealthWeights};
 use crate::metrics::SimulationResult;
 use crate::parameters::ParameterSet;
 
@@ -20,9 +23,21 @@ mod ranking;
 #[path = "export/writer_utils.rs"]
 mod writer_utils;
 
+pub use ranking::{RankRange, RankedResult, RankingStability};
+pub use writer_utils::TAG_COLUMN_PREFIX;
+
+/// Crate versions and git commit of this build, stamped on every export. Results carry their
+/// own scenario hash, so it is left unset here.
+pub(crate) fn build_provenance() -> ExportProvenance {
+    ExportProvenance::current().with_crate_version("sim_experiments", env!("CARGO_PKG_VERSION"))
+}
+
 /// Export simulation results to Parquet format.
 ///
-/// Creates a Parquet file with columns for all metrics in `SimulationResult`.
+/// Creates a Parquet file with columns for all metrics in `SimulationResult`, followed by
+/// one nullable `tag_<key>` column per tag key used by any result, then `sim_core_version`,
+/// `sim_experiments_version`, `git_sha` and `scenario_hash`; the versions and git commit are
+/// also written as file key-value metadata.
 ///
 /// # Arguments
 ///
@@ -38,12 +53,35 @@ pub fn export_to_parquet(
 ) -> Result<(), Box<dyn std::error::Error>> {
     writer_utils::ensure_not_empty(results)?;
     let file = writer_utils::create_output_file(path)?;
-    parquet::export_to_parquet_impl(results, file)
+    parquet::export_to_parquet_impl(results, None, file)
+}
+
+/// Export simulation results to Parquet with their health score breakdown.
+///
+/// Same as [`export_to_parquet`], plus `health_score` and, per
+/// [`HealthComponent`](crate::health::HealthComponent), `health_<component>_normalized` and
+/// `health_<component>_contribution` columns after the metrics. The weights are stored as
+/// `health_<component>_weight` key-value metadata. Scores are normalized across `results`.
+///
+/// # Errors
+///
+/// Returns an error if file creation or Parquet writing fails.
+pub fn export_to_parquet_with_health(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    writer_utils::ensure_not_empty(results)?;
+    let breakdowns = calculate_health_breakdowns(results, weights);
+    let file = writer_utils::create_output_file(path)?;
+    parquet::export_to_parquet_impl(results, Some((&breakdowns, weights)), file)
 }
 
 /// Export simulation results to JSON format.
 ///
-/// Creates a JSON file with an array of all results (serialized as JSON objects).
+/// Creates a JSON file with an array of all results (serialized as JSON objects), each with
+/// `sim_core_version`, `sim_experiments_version` and `git_sha` fields next to its
+/// `scenario_hash` (and a `tags` object when the run has tags).
 ///
 /// # Arguments
 ///
@@ -58,12 +96,48 @@ pub fn export_to_json(
     path: impl AsRef<Path>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let file = writer_utils::create_output_file(path)?;
-    json::export_to_json_impl(results, file)
+    json::export_to_json_impl(results, None, file)
+}
+
+/// Export simulation results to JSON with their health score breakdown.
+///
+/// Same as [`export_to_json`], with a `health` object on each result: its `score` and one
+/// entry per component with the `raw` metric, `normalized` value, `weight` and `contribution`.
+///
+/// # Errors
+///
+/// Returns an error if file creation or JSON serialization fails.
+pub fn export_to_json_with_health(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let breakdowns = calculate_health_breakdowns(results, weights);
+    let file = writer_utils::create_output_file(path)?;
+    json::export_to_json_impl(results, Some(&breakdowns), file)
+}
+
+/// Export simulation results as newline-delimited JSON, optionally zstd-compressed.
+///
+/// Writes one result object per line with the same fields as [`export_to_json`], so results
+/// can be streamed line by line instead of parsing one large array. Use
+/// `JsonCompression::for_path(&path)` to compress `*.ndjson.zst` paths.
+///
+/// # Errors
+///
+/// Returns an error if file creation, serialization or compression fails.
+pub fn export_to_ndjson(
+    results: &[SimulationResult],
+    path: impl AsRef<Path>,
+    compression: JsonCompression,
+) -> Result<(), Box<dyn std::error::Error>> {
+    write_ndjson(path, results, compression, &build_provenance())
 }
 
 /// Export simulation results with parameters to CSV format.
 ///
-/// Creates a CSV file with columns for all parameters and all metrics.
+/// Creates a CSV file with columns for all parameters and all metrics, followed by the
+/// `tag_<key>` and provenance columns of [`export_to_parquet`].
 /// Parameters and results are paired by index (results[i] corresponds to parameter_sets[i]).
 ///
 /// # Arguments
@@ -82,7 +156,27 @@ pub fn export_to_csv(
 ) -> Result<(), Box<dyn std::error::Error>> {
     writer_utils::ensure_not_empty(results)?;
     let file = writer_utils::create_output_file(path)?;
-    csv::export_to_csv_impl(results, parameter_sets, file)
+    csv::export_to_csv_impl(results, parameter_sets, None, file)
+}
+
+/// Export simulation results with parameters to CSV with their health score breakdown.
+///
+/// Same as [`export_to_csv`], plus the health columns of [`export_to_parquet_with_health`]
+/// after the metrics.
+///
+/// # Errors
+///
+/// Returns an error if file creation or CSV writing fails, or if results and parameter_sets lengths don't match.
+pub fn export_to_csv_with_health(
+    results: &[SimulationResult],
+    parameter_sets: &[ParameterSet],
+    weights: &HealthWeights,
+    path: impl AsRef<Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    writer_utils::ensure_not_empty(results)?;
+    let breakdowns = calculate_health_breakdowns(results, weights);
+    let file = writer_utils::create_output_file(path)?;
+    csv::export_to_csv_impl(results, parameter_sets, Some(&breakdowns), file)
 }
 
 /// Find the parameter set with the highest health score.
@@ -127,15 +221,63 @@ pub fn find_best_result_index(
     ranking::find_best_index_by_health(results, weights)
 }
 
+/// Rank results by health score and return the top `k` (all results if fewer).
+///
+/// Ties in score are broken by higher conversion rate, then lower average time to match, then
+/// input order, so the ranking is deterministic. NaN scores rank last.
+///
+/// # Arguments
+///
+/// * `results` - Vector of simulation results
+/// * `weights` - Health weights for score calculation
+/// * `k` - Number of top results to return
+///
+/// # Returns
+///
+/// Up to `k` results with their 1-based rank, index into `results` and score, best first.
+pub fn rank_results(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    k: usize,
+) -> Vec<RankedResult> {
+    ranking::rank_results_impl(results, weights, k)
+}
+
+/// Check whether the top-`k` ranking survives small changes to the weights.
+///
+/// Re-ranks `samples` times with every weight multiplied by an independent factor drawn
+/// uniformly from `[1 - perturbation, 1 + perturbation]` (seeded by `seed`, so the check is
+/// reproducible), and reports how often the best result and the top-`k` set stay the same and
+/// the rank range of each top-`k` result. A ranking that flips under a few percent of weight
+/// noise should not be read as a clear winner.
+///
+/// # Arguments
+///
+/// * `results` - Vector of simulation results
+/// * `weights` - Health weights for score calculation
+/// * `k` - Size of the top-k set to track
+/// * `perturbation` - Maximum relative change per weight (e.g. `0.1` for ±10%)
+/// * `samples` - Number of perturbed weightings to evaluate
+/// * `seed` - Seed for the perturbations
+pub fn ranking_stability(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    k: usize,
+    perturbation: f64,
+    samples: usize,
+    seed: u64,
+) -> RankingStability {
+    ranking::ranking_stability_impl(results, weights, k, perturbation, samples, seed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::metrics::SimulationResult;
     use tempfile::NamedTempFile;
 
-    #[test]
-    fn test_export_to_json() {
-        let results = vec![SimulationResult {
+    fn sample_result() -> SimulationResult {
+        SimulationResult {
             total_riders: 100,
             total_drivers: 20,
             completed_riders: 80,
@@ -155,13 +297,233 @@ mod tests {
             riders_abandoned_price: 5,
             riders_abandoned_eta: 3,
             riders_abandoned_stochastic: 2,
-        }];
+            scenario_hash: Some("0123456789abcdef".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_export_to_json() {
+        let results = vec![sample_result()];
 
         let file = NamedTempFile::new().unwrap();
         export_to_json(&results, file.path()).unwrap();
 
         let contents = std::fs::read_to_string(file.path()).unwrap();
         assert!(contents.contains("conversion_rate"));
+        let rows: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(rows[0]["scenario_hash"], "0123456789abcdef");
+        assert_eq!(rows[0]["git_sha"], sim_core::provenance::GIT_SHA);
+        assert_eq!(
+            rows[0]["sim_experiments_version"],
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    #[test]
+    fn test_export_to_ndjson_writes_one_result_per_line() {
+        let mut unhashed = sample_result();
+        unhashed.scenario_hash = None;
+        let results = vec![sample_result(), unhashed];
+
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("results.ndjson");
+        let compressed = dir.path().join("results.ndjson.zst");
+        export_to_ndjson(&results, &plain, JsonCompression::for_path(&plain)).unwrap();
+        export_to_ndjson(
+            &results,
+            &compressed,
+            JsonCompression::for_path(&compressed),
+        )
+        .unwrap();
+
+        let contents = std::fs::read_to_string(&plain).unwrap();
+        let decompressed =
+            String::from_utf8(zstd::decode_all(std::fs::File::open(&compressed).unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(decompressed, contents);
+        let rows: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["scenario_hash"], "0123456789abcdef");
+        assert_eq!(rows[1]["scenario_hash"], serde_json::Value::Null);
+        assert_eq!(rows[1]["git_sha"], sim_core::provenance::GIT_SHA);
+    }
+
+    #[test]
+    fn test_export_to_parquet_stamps_provenance() {
+        let mut unhashed = sample_result();
+        unhashed.scenario_hash = None;
+        let results = vec![sample_result(), unhashed];
+
+        let file = NamedTempFile::new().unwrap();
+        export_to_parquet(&results, file.path()).unwrap();
+
+        let builder = ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            std::fs::File::open(file.path()).unwrap(),
+        )
+        .unwrap();
+        let key_values = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap();
+        assert!(key_values
+            .iter()
+            .any(|entry| entry.key == "sim_core_version" && entry.value.is_some()));
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let hashes = batch
+            .column_by_name("scenario_hash")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow::array::StringArray>()
+            .unwrap();
+        assert_eq!(
+            hashes.iter().collect::<Vec<_>>(),
+            [Some("0123456789abcdef"), None]
+        );
+        assert!(batch.column_by_name("sim_experiments_version").is_some());
+    }
+
+    #[test]
+    fn test_exports_carry_tags() {
+        let mut tagged = sample_result();
+        tagged
+            .tags
+            .insert("policy".to_string(), "baseline".to_string());
+        let results = vec![tagged, sample_result()];
+
+        let file = NamedTempFile::new().unwrap();
+        export_to_json(&results, file.path()).unwrap();
+        let rows: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file.path()).unwrap()).unwrap();
+        assert_eq!(rows[0]["tags"]["policy"], "baseline");
+        assert!(rows[1].get("tags").is_none());
+
+        let parameter_sets: Vec<ParameterSet> = (0..2)
+            .map(|run_id| ParameterSet::new(Default::default(), "exp".to_string(), run_id, 1))
+            .collect();
+        let file = NamedTempFile::new().unwrap();
+        export_to_csv(&results, &parameter_sets, file.path()).unwrap();
+        let mut reader = ::csv::Reader::from_path(file.path()).unwrap();
+        let column = reader
+            .headers()
+            .unwrap()
+            .iter()
+            .position(|name| name == "tag_policy")
+            .unwrap();
+        let values: Vec<String> = reader
+            .records()
+            .map(|record| record.unwrap()[column].to_string())
+            .collect();
+        assert_eq!(values, ["baseline", ""]);
+    }
+
+    #[test]
+    fn test_exports_with_health_include_the_breakdown() {
+        let mut worse = sample_result();
+        worse.conversion_rate = 0.5;
+        let results = vec![sample_result(), worse];
+        let weights = HealthWeights::default();
+        let breakdowns = crate::health::calculate_health_breakdowns(&results, &weights);
+
+        let file = NamedTempFile::new().unwrap();
+        export_to_parquet_with_health(&results, &weights, file.path()).unwrap();
+        let builder = ::parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            std::fs::File::open(file.path()).unwrap(),
+        )
+        .unwrap();
+        let key_values = builder
+            .metadata()
+            .file_metadata()
+            .key_value_metadata()
+            .unwrap();
+        assert!(key_values
+            .iter()
+            .any(|entry| entry.key == "health_conversion_weight"
+                && entry.value.as_deref() == Some("0.3")));
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+        let column = |name: &str| -> Vec<f64> {
+            batch
+                .column_by_name(name)
+                .unwrap()
+                .as_any()
+                .downcast_ref::<arrow::array::Float64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        };
+        assert_eq!(
+            column("health_score"),
+            [breakdowns[0].score, breakdowns[1].score]
+        );
+        assert_eq!(column("health_conversion_normalized"), [1.0, 0.0]);
+        assert_eq!(column("health_conversion_contribution"), [0.3, 0.0]);
+
+        let file = NamedTempFile::new().unwrap();
+        export_to_json_with_health(&results, &weights, file.path()).unwrap();
+        let rows: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file.path()).unwrap()).unwrap();
+        let conversion = &rows[1]["health"]["components"][0];
+        assert_eq!(conversion["component"], "conversion");
+        assert_eq!(conversion["raw"], 0.5);
+        assert_eq!(conversion["weight"], 0.3);
+        assert_eq!(rows[1]["health"]["score"], breakdowns[1].score);
+    }
+
+    #[test]
+    fn test_rank_results_orders_by_score_and_breaks_ties() {
+        let mut slow = sample_result();
+        slow.avg_time_to_match_ms = 3000.0;
+        let mut low_conversion = sample_result();
+        low_conversion.conversion_rate = 0.5;
+        let results = vec![low_conversion, sample_result(), slow, sample_result()];
+        // Only conversion counts, so results 1, 2 and 3 tie on score
+        let weights = HealthWeights::new(1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+        let ranked = rank_results(&results, &weights, 3);
+        let order: Vec<(usize, usize)> = ranked.iter().map(|r| (r.rank, r.index)).collect();
+        // Same conversion: faster matching wins, then input order
+        assert_eq!(order, [(1, 1), (2, 3), (3, 2)]);
+        assert!(ranked.iter().all(|r| r.score == 1.0));
+        assert_eq!(rank_results(&results, &weights, 10).len(), 4);
+        assert_eq!(rank_results(&results, &weights, 10)[3].index, 0);
+        assert!(rank_results(&[], &weights, 3).is_empty());
+    }
+
+    #[test]
+    fn test_ranking_stability_detects_fragile_winners() {
+        let mut fast = sample_result();
+        fast.conversion_rate = 0.74;
+        fast.avg_time_to_match_ms = 500.0;
+        let mut poor = sample_result();
+        poor.conversion_rate = 0.2;
+        poor.avg_time_to_match_ms = 5000.0;
+        let results = vec![sample_result(), fast, poor];
+
+        // Conversion and match time weighted almost equally: the winner depends on noise
+        let balanced = HealthWeights::new(0.5, 0.0, 0.0, 0.49, 0.0, 0.0);
+        let fragile = ranking_stability(&results, &balanced, 2, 0.2, 200, 7);
+        assert_eq!(fragile.samples, 200);
+        assert!(!fragile.is_stable());
+        assert!(fragile.best_unchanged_share > 0.0 && fragile.best_unchanged_share < 1.0);
+        // Both leaders stay ahead of the poor result
+        assert_eq!(fragile.top_k_unchanged_share, 1.0);
+        assert!(fragile
+            .rank_ranges
+            .iter()
+            .all(|range| range.best_rank == 1 && range.worst_rank == 2));
+        assert_eq!(
+            fragile,
+            ranking_stability(&results, &balanced, 2, 0.2, 200, 7)
+        );
+
+        let conversion_heavy = HealthWeights::new(1.0, 0.0, 0.0, 0.1, 0.0, 0.0);
+        let stable = ranking_stability(&results, &conversion_heavy, 2, 0.2, 200, 7);
+        assert!(stable.is_stable());
+        assert_eq!(stable.rank_ranges[0].index, 0);
     }
 
     #[test]
@@ -187,6 +549,7 @@ mod tests {
                 riders_abandoned_price: 15,
                 riders_abandoned_eta: 10,
                 riders_abandoned_stochastic: 5,
+                ..Default::default()
             },
             SimulationResult {
                 total_riders: 100,
@@ -208,6 +571,7 @@ mod tests {
                 riders_abandoned_price: 5,
                 riders_abandoned_eta: 3,
                 riders_abandoned_stochastic: 2,
+                ..Default::default()
             },
         ];
 
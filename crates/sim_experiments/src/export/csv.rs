@@ -1,12 +1,18 @@
+use sim_core::provenance::SCENARIO_HASH_KEY;
 use sim_core::scenario::MatchingAlgorithmType;
 use sim_core::traffic::TrafficProfileKind;
 
+use crate::health::HealthBreakdown;
 use crate::metrics::SimulationResult;
 use crate::parameters::ParameterSet;
 
+use super::build_provenance;
+use super::writer_utils::{health_column_names, health_column_values, tag_keys, TAG_COLUMN_PREFIX};
+
 pub(crate) fn export_to_csv_impl(
     results: &[SimulationResult],
     parameter_sets: &[ParameterSet],
+    health: Option<&[HealthBreakdown]>,
     file: std::fs::File,
 ) -> Result<(), Box<dyn std::error::Error>> {
     if results.len() != parameter_sets.len() {
@@ -20,20 +26,28 @@ pub(crate) fn export_to_csv_impl(
 
     let mut wtr = csv::Writer::from_writer(file);
 
-    wtr.write_record([
+    let header = [
         "experiment_id",
         "run_id",
         "seed",
+        "geography",
         "commission_rate",
         "base_fare",
         "per_km_rate",
         "surge_enabled",
         "surge_radius_k",
         "surge_max_multiplier",
+        "surge_strategy",
+        "commission_model",
+        "tax_rate",
+        "regulatory_fee_per_trip",
+        "min_hourly_earnings",
+        "vehicle_classes",
         "num_riders",
         "num_drivers",
         "match_radius",
         "epoch_ms",
+        "time_zone",
         "matching_algorithm_type",
         "batch_matching_enabled",
         "batch_interval_secs",
@@ -47,9 +61,19 @@ pub(crate) fn export_to_csv_impl(
         "abandoned_quote_riders",
         "cancelled_riders",
         "conversion_rate",
+        "funnel_requests",
+        "funnel_quote_rate",
+        "funnel_quote_acceptance_rate",
+        "funnel_match_rate",
+        "funnel_pickup_rate",
+        "funnel_completion_rate",
         "platform_revenue",
         "driver_payouts",
         "total_fares_collected",
+        "taxes_and_fees",
+        "earnings_floor_top_ups",
+        "wait_and_save_trips",
+        "wait_and_save_avg_time_to_match_ms",
         "avg_time_to_match_ms",
         "median_time_to_match_ms",
         "p90_time_to_match_ms",
@@ -57,12 +81,39 @@ pub(crate) fn export_to_csv_impl(
         "median_time_to_pickup_ms",
         "p90_time_to_pickup_ms",
         "completed_trips",
+        "deadhead_km",
+        "trip_km",
+        "idle_minutes",
+        "en_route_minutes",
+        "on_trip_minutes",
+        "co2_kg",
+        "co2_g_per_trip",
         "riders_abandoned_price",
         "riders_abandoned_eta",
         "riders_abandoned_stochastic",
-    ])?;
+    ];
+    let provenance = build_provenance().metadata();
+    let tag_keys = tag_keys(results);
+    let header = header
+        .into_iter()
+        .map(String::from)
+        .chain(
+            tag_keys
+                .iter()
+                .map(|key| format!("{TAG_COLUMN_PREFIX}{key}")),
+        )
+        .chain(health.map(|_| health_column_names()).unwrap_or_default())
+        .chain(provenance.iter().map(|(key, _)| key.clone()))
+        .chain([SCENARIO_HASH_KEY.to_string()]);
+    wtr.write_record(header)?;
 
-    for (result, param_set) in results.iter().zip(parameter_sets.iter()) {
+    for (index, (result, param_set)) in results.iter().zip(parameter_sets.iter()).enumerate() {
+        let health_values: Vec<String> = health
+            .map(|breakdowns| health_column_values(&breakdowns[index]))
+            .unwrap_or_default()
+            .iter()
+            .map(f64::to_string)
+            .collect();
         let pricing = param_set.params.pricing_config.as_ref();
         let matching_alg = param_set.params.matching_algorithm_type.as_ref();
         let matching_alg_str = match matching_alg {
@@ -72,81 +123,134 @@ pub(crate) fn export_to_csv_impl(
             None => "",
         };
 
-        wtr.write_record([
-            &param_set.experiment_id,
-            &param_set.run_id.to_string(),
-            &param_set.seed.to_string(),
-            &pricing
-                .map(|p| p.commission_rate.to_string())
-                .unwrap_or_default(),
-            &pricing.map(|p| p.base_fare.to_string()).unwrap_or_default(),
-            &pricing
-                .map(|p| p.per_km_rate.to_string())
-                .unwrap_or_default(),
-            &pricing
-                .map(|p| p.surge_enabled.to_string())
-                .unwrap_or_default(),
-            &pricing
-                .map(|p| p.surge_radius_k.to_string())
-                .unwrap_or_default(),
-            &pricing
-                .map(|p| p.surge_max_multiplier.to_string())
-                .unwrap_or_default(),
-            &param_set.params.num_riders.to_string(),
-            &param_set.params.num_drivers.to_string(),
-            &param_set.params.match_radius.to_string(),
-            &param_set
-                .params
-                .epoch_ms
-                .map(|e| e.to_string())
-                .unwrap_or_default(),
-            matching_alg_str,
-            &param_set
-                .params
-                .batch_matching_enabled
-                .map(|b| b.to_string())
-                .unwrap_or_default(),
-            &param_set
-                .params
-                .batch_interval_secs
-                .map(|i| i.to_string())
-                .unwrap_or_default(),
-            &param_set
-                .params
-                .eta_weight
-                .map(|w| w.to_string())
-                .unwrap_or_default(),
-            &match &param_set.params.traffic_profile {
-                TrafficProfileKind::None => "None".to_string(),
-                TrafficProfileKind::Berlin => "Berlin".to_string(),
-                TrafficProfileKind::Custom(_) => "Custom".to_string(),
-            },
-            &param_set.params.dynamic_congestion_enabled.to_string(),
-            &param_set
-                .params
-                .base_speed_kmh
-                .map(|s| s.to_string())
-                .unwrap_or_default(),
-            &result.total_riders.to_string(),
-            &result.total_drivers.to_string(),
-            &result.completed_riders.to_string(),
-            &result.abandoned_quote_riders.to_string(),
-            &result.cancelled_riders.to_string(),
-            &result.conversion_rate.to_string(),
-            &result.platform_revenue.to_string(),
-            &result.driver_payouts.to_string(),
-            &result.total_fares_collected.to_string(),
-            &result.avg_time_to_match_ms.to_string(),
-            &result.median_time_to_match_ms.to_string(),
-            &result.p90_time_to_match_ms.to_string(),
-            &result.avg_time_to_pickup_ms.to_string(),
-            &result.median_time_to_pickup_ms.to_string(),
-            &result.p90_time_to_pickup_ms.to_string(),
-            &result.completed_trips.to_string(),
-            &result.riders_abandoned_price.to_string(),
-            &result.riders_abandoned_eta.to_string(),
-            &result.riders_abandoned_stochastic.to_string(),
-        ])?;
+        wtr.write_record(
+            [
+                &param_set.experiment_id,
+                &param_set.run_id.to_string(),
+                &param_set.seed.to_string(),
+                param_set.geography.as_deref().unwrap_or_default(),
+                &pricing
+                    .map(|p| p.commission_rate.to_string())
+                    .unwrap_or_default(),
+                &pricing.map(|p| p.base_fare.to_string()).unwrap_or_default(),
+                &pricing
+                    .map(|p| p.per_km_rate.to_string())
+                    .unwrap_or_default(),
+                &pricing
+                    .map(|p| p.surge_enabled.to_string())
+                    .unwrap_or_default(),
+                &pricing
+                    .map(|p| p.surge_radius_k.to_string())
+                    .unwrap_or_default(),
+                &pricing
+                    .map(|p| p.surge_max_multiplier.to_string())
+                    .unwrap_or_default(),
+                param_set.params.surge_strategy.name(),
+                pricing
+                    .map(|p| p.commission_model.name())
+                    .unwrap_or_default(),
+                &pricing.map(|p| p.tax_rate.to_string()).unwrap_or_default(),
+                &pricing
+                    .map(|p| p.regulatory_fee_per_trip.to_string())
+                    .unwrap_or_default(),
+                &pricing
+                    .map(|p| p.min_hourly_earnings.to_string())
+                    .unwrap_or_default(),
+                &param_set
+                    .params
+                    .vehicle_classes
+                    .as_ref()
+                    .map(|menu| {
+                        menu.classes
+                            .iter()
+                            .map(|class| class.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join("|")
+                    })
+                    .unwrap_or_default(),
+                &param_set.params.num_riders.to_string(),
+                &param_set.params.num_drivers.to_string(),
+                &param_set.params.match_radius.to_string(),
+                &param_set
+                    .params
+                    .epoch_ms
+                    .map(|e| e.to_string())
+                    .unwrap_or_default(),
+                param_set.params.local_time.zone_name(),
+                matching_alg_str,
+                &param_set
+                    .params
+                    .batch_matching_enabled
+                    .map(|b| b.to_string())
+                    .unwrap_or_default(),
+                &param_set
+                    .params
+                    .batch_interval_secs
+                    .map(|i| i.to_string())
+                    .unwrap_or_default(),
+                &param_set
+                    .params
+                    .eta_weight
+                    .map(|w| w.to_string())
+                    .unwrap_or_default(),
+                &match &param_set.params.traffic_profile {
+                    TrafficProfileKind::None => "None".to_string(),
+                    TrafficProfileKind::Berlin => "Berlin".to_string(),
+                    TrafficProfileKind::Custom(_) => "Custom".to_string(),
+                },
+                &param_set.params.dynamic_congestion_enabled.to_string(),
+                &param_set
+                    .params
+                    .base_speed_kmh
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+                &result.total_riders.to_string(),
+                &result.total_drivers.to_string(),
+                &result.completed_riders.to_string(),
+                &result.abandoned_quote_riders.to_string(),
+                &result.cancelled_riders.to_string(),
+                &result.conversion_rate.to_string(),
+                &result.funnel_requests.to_string(),
+                &result.funnel_quote_rate.to_string(),
+                &result.funnel_quote_acceptance_rate.to_string(),
+                &result.funnel_match_rate.to_string(),
+                &result.funnel_pickup_rate.to_string(),
+                &result.funnel_completion_rate.to_string(),
+                &result.platform_revenue.to_string(),
+                &result.driver_payouts.to_string(),
+                &result.total_fares_collected.to_string(),
+                &result.taxes_and_fees.to_string(),
+                &result.earnings_floor_top_ups.to_string(),
+                &result.wait_and_save_trips.to_string(),
+                &result.wait_and_save_avg_time_to_match_ms.to_string(),
+                &result.avg_time_to_match_ms.to_string(),
+                &result.median_time_to_match_ms.to_string(),
+                &result.p90_time_to_match_ms.to_string(),
+                &result.avg_time_to_pickup_ms.to_string(),
+                &result.median_time_to_pickup_ms.to_string(),
+                &result.p90_time_to_pickup_ms.to_string(),
+                &result.completed_trips.to_string(),
+                &result.deadhead_km.to_string(),
+                &result.trip_km.to_string(),
+                &result.idle_minutes.to_string(),
+                &result.en_route_minutes.to_string(),
+                &result.on_trip_minutes.to_string(),
+                &result.co2_kg.to_string(),
+                &result.co2_g_per_trip.to_string(),
+                &result.riders_abandoned_price.to_string(),
+                &result.riders_abandoned_eta.to_string(),
+                &result.riders_abandoned_stochastic.to_string(),
+            ]
+            .into_iter()
+            .chain(
+                tag_keys
+                    .iter()
+                    .map(|key| result.tag(key).unwrap_or_default()),
+            )
+            .chain(health_values.iter().map(String::as_str))
+            .chain(provenance.iter().map(|(_, value)| value.as_str()))
+            .chain([result.scenario_hash.as_deref().unwrap_or_default()]),
+        )?;
     }
 
     wtr.flush()?;
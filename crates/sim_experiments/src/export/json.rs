@@ -1,9 +1,33 @@
+use crate::health::HealthBreakdown;
 use crate::metrics::SimulationResult;
 
+use super::build_provenance;
+
 pub(crate) fn export_to_json_impl(
     results: &[SimulationResult],
+    health: Option<&[HealthBreakdown]>,
     file: std::fs::File,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    serde_json::to_writer_pretty(file, results)?;
+    let provenance = build_provenance().metadata();
+    let rows = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let mut row = serde_json::to_value(result)?;
+            if let Some(fields) = row.as_object_mut() {
+                for (key, value) in &provenance {
+                    fields.insert(key.clone(), value.clone().into());
+                }
+                if let Some(breakdowns) = health {
+                    fields.insert(
+                        "health".to_string(),
+                        serde_json::to_value(&breakdowns[index])?,
+                    );
+                }
+            }
+            Ok(row)
+        })
+        .collect::<Result<Vec<_>, serde_json::Error>>()?;
+    serde_json::to_writer_pretty(file, &rows)?;
     Ok(())
 }
@@ -1,19 +1,48 @@
 use std::sync::Arc;
 
-use arrow::array::{ArrayRef, Float64Array, UInt64Array};
+use arrow::array::{ArrayRef, Float64Array, StringArray, UInt64Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::ArrowWriter;
+use parquet::file::metadata::KeyValue;
 use parquet::file::properties::WriterProperties;
+use sim_core::provenance::SCENARIO_HASH_KEY;
 
+use crate::health::{HealthBreakdown, HealthComponent, HealthWeights};
 use crate::metrics::SimulationResult;
 
+use super::build_provenance;
+use super::writer_utils::{health_column_names, health_column_values, tag_keys, TAG_COLUMN_PREFIX};
+
+/// Breakdowns of every result and the weights that produced them.
+pub(crate) type HealthColumns<'a> = Option<(&'a [HealthBreakdown], &'a HealthWeights)>;
+
 pub(crate) fn export_to_parquet_impl(
     results: &[SimulationResult],
+    health: HealthColumns,
     file: std::fs::File,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let batch = build_record_batch(results)?;
-    let props = WriterProperties::builder().build();
+    let batch = build_record_batch(results, health)?;
+    let mut key_value_metadata: Vec<KeyValue> = build_provenance()
+        .metadata()
+        .into_iter()
+        .map(|(key, value)| KeyValue::new(key, value))
+        .collect();
+    if let Some((_, weights)) = health {
+        key_value_metadata.push(KeyValue::new(
+            "health_normalization".to_string(),
+            weights.normalization.name().to_string(),
+        ));
+        for component in HealthComponent::ALL {
+            key_value_metadata.push(KeyValue::new(
+                format!("health_{}_weight", component.name()),
+                component.weight(weights).to_string(),
+            ));
+        }
+    }
+    let props = WriterProperties::builder()
+        .set_key_value_metadata(Some(key_value_metadata))
+        .build();
     let mut writer = ArrowWriter::try_new(file, batch.schema(), Some(props))?;
     writer.write(&batch)?;
     writer.close()?;
@@ -23,23 +52,60 @@ pub(crate) fn export_to_parquet_impl(
 
 fn build_record_batch(
     results: &[SimulationResult],
+    health: HealthColumns,
 ) -> Result<RecordBatch, arrow::error::ArrowError> {
-    let schema = Arc::new(parquet_schema());
-    let arrays = build_arrays(results);
+    let tag_keys = tag_keys(results);
+    let schema = Arc::new(parquet_schema(&tag_keys, health.is_some()));
+    let arrays = build_arrays(results, &tag_keys, health);
 
     RecordBatch::try_new(schema, arrays)
 }
 
-fn parquet_schema() -> Schema {
-    Schema::new(vec![
+fn parquet_schema(tag_keys: &[String], with_health: bool) -> Schema {
+    let mut fields = metric_fields();
+    for key in tag_keys {
+        fields.push(Field::new(
+            format!("{TAG_COLUMN_PREFIX}{key}"),
+            DataType::Utf8,
+            true,
+        ));
+    }
+    if with_health {
+        for name in health_column_names() {
+            fields.push(Field::new(name, DataType::Float64, false));
+        }
+    }
+    for (key, _) in build_provenance().metadata() {
+        fields.push(Field::new(key, DataType::Utf8, false));
+    }
+    fields.push(Field::new(SCENARIO_HASH_KEY, DataType::Utf8, true));
+    Schema::new(fields).with_metadata(build_provenance().metadata().into_iter().collect())
+}
+
+fn metric_fields() -> Vec<Field> {
+    vec![
         Field::new("total_riders", DataType::UInt64, false),
         Field::new("completed_riders", DataType::UInt64, false),
         Field::new("abandoned_quote_riders", DataType::UInt64, false),
         Field::new("cancelled_riders", DataType::UInt64, false),
         Field::new("conversion_rate", DataType::Float64, false),
+        Field::new("funnel_requests", DataType::UInt64, false),
+        Field::new("funnel_quote_rate", DataType::Float64, false),
+        Field::new("funnel_quote_acceptance_rate", DataType::Float64, false),
+        Field::new("funnel_match_rate", DataType::Float64, false),
+        Field::new("funnel_pickup_rate", DataType::Float64, false),
+        Field::new("funnel_completion_rate", DataType::Float64, false),
         Field::new("platform_revenue", DataType::Float64, false),
         Field::new("driver_payouts", DataType::Float64, false),
         Field::new("total_fares_collected", DataType::Float64, false),
+        Field::new("taxes_and_fees", DataType::Float64, false),
+        Field::new("earnings_floor_top_ups", DataType::Float64, false),
+        Field::new("wait_and_save_trips", DataType::UInt64, false),
+        Field::new(
+            "wait_and_save_avg_time_to_match_ms",
+            DataType::Float64,
+            false,
+        ),
         Field::new("avg_time_to_match_ms", DataType::Float64, false),
         Field::new("median_time_to_match_ms", DataType::Float64, false),
         Field::new("p90_time_to_match_ms", DataType::Float64, false),
@@ -47,13 +113,51 @@ fn parquet_schema() -> Schema {
         Field::new("median_time_to_pickup_ms", DataType::Float64, false),
         Field::new("p90_time_to_pickup_ms", DataType::Float64, false),
         Field::new("completed_trips", DataType::UInt64, false),
+        Field::new("deadhead_km", DataType::Float64, false),
+        Field::new("trip_km", DataType::Float64, false),
+        Field::new("idle_minutes", DataType::Float64, false),
+        Field::new("en_route_minutes", DataType::Float64, false),
+        Field::new("on_trip_minutes", DataType::Float64, false),
+        Field::new("co2_kg", DataType::Float64, false),
+        Field::new("co2_g_per_trip", DataType::Float64, false),
         Field::new("riders_abandoned_price", DataType::UInt64, false),
         Field::new("riders_abandoned_eta", DataType::UInt64, false),
         Field::new("riders_abandoned_stochastic", DataType::UInt64, false),
-    ])
+    ]
 }
 
-fn build_arrays(results: &[SimulationResult]) -> Vec<ArrayRef> {
+fn build_arrays(
+    results: &[SimulationResult],
+    tag_keys: &[String],
+    health: HealthColumns,
+) -> Vec<ArrayRef> {
+    let mut arrays = metric_arrays(results);
+    for key in tag_keys {
+        arrays.push(Arc::new(StringArray::from(
+            results.iter().map(|r| r.tag(key)).collect::<Vec<_>>(),
+        )));
+    }
+    if let Some((breakdowns, _)) = health {
+        let rows: Vec<Vec<f64>> = breakdowns.iter().map(health_column_values).collect();
+        for column in 0..health_column_names().len() {
+            arrays.push(Arc::new(Float64Array::from(
+                rows.iter().map(|row| row[column]).collect::<Vec<_>>(),
+            )));
+        }
+    }
+    for (_, value) in build_provenance().metadata() {
+        arrays.push(Arc::new(StringArray::from(vec![value; results.len()])));
+    }
+    arrays.push(Arc::new(StringArray::from(
+        results
+            .iter()
+            .map(|r| r.scenario_hash.clone())
+            .collect::<Vec<_>>(),
+    )));
+    arrays
+}
+
+fn metric_arrays(results: &[SimulationResult]) -> Vec<ArrayRef> {
     vec![
         Arc::new(UInt64Array::from(
             results
@@ -85,6 +189,42 @@ fn build_arrays(results: &[SimulationResult]) -> Vec<ArrayRef> {
                 .map(|r| r.conversion_rate)
                 .collect::<Vec<_>>(),
         )),
+        Arc::new(UInt64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_requests as u64)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_quote_rate)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_quote_acceptance_rate)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_match_rate)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_pickup_rate)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.funnel_completion_rate)
+                .collect::<Vec<_>>(),
+        )),
         Arc::new(Float64Array::from(
             results
                 .iter()
@@ -100,6 +240,27 @@ fn build_arrays(results: &[SimulationResult]) -> Vec<ArrayRef> {
                 .map(|r| r.total_fares_collected)
                 .collect::<Vec<_>>(),
         )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.taxes_and_fees).collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.earnings_floor_top_ups)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(UInt64Array::from(
+            results
+                .iter()
+                .map(|r| r.wait_and_save_trips as u64)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.wait_and_save_avg_time_to_match_ms)
+                .collect::<Vec<_>>(),
+        )),
         Arc::new(Float64Array::from(
             results
                 .iter()
@@ -142,6 +303,33 @@ fn build_arrays(results: &[SimulationResult]) -> Vec<ArrayRef> {
                 .map(|r| r.completed_trips as u64)
                 .collect::<Vec<_>>(),
         )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.deadhead_km).collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.trip_km).collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.idle_minutes).collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.en_route_minutes)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results
+                .iter()
+                .map(|r| r.on_trip_minutes)
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.co2_kg).collect::<Vec<_>>(),
+        )),
+        Arc::new(Float64Array::from(
+            results.iter().map(|r| r.co2_g_per_trip).collect::<Vec<_>>(),
+        )),
         Arc::new(UInt64Array::from(
             results
                 .iter()
@@ -1,7 +1,166 @@
-use crate::health::{calculate_health_scores, HealthWeights};
+use std::cmp::Ordering;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::health::{calculate_health_scores, HealthComponent, HealthWeights};
 use crate::metrics::SimulationResult;
 use crate::parameters::ParameterSet;
 
+/// One entry of a ranking (see `rank_results`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankedResult {
+    /// 1-based position in the ranking.
+    pub rank: usize,
+    /// Index into the ranked results.
+    pub index: usize,
+    pub score: f64,
+}
+
+/// How a ranking's top entries move when the weights are perturbed (see `ranking_stability`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankingStability {
+    /// Perturbed weightings evaluated.
+    pub samples: usize,
+    /// Share of samples whose best result is the unperturbed best.
+    pub best_unchanged_share: f64,
+    /// Share of samples whose top-k holds the same results (in any order).
+    pub top_k_unchanged_share: f64,
+    /// Rank range of each unperturbed top-k result across the samples, in ranking order.
+    pub rank_ranges: Vec<RankRange>,
+}
+
+impl RankingStability {
+    /// Whether no sample changed the top-k set or its best result.
+    pub fn is_stable(&self) -> bool {
+        self.best_unchanged_share == 1.0 && self.top_k_unchanged_share == 1.0
+    }
+}
+
+/// Best and worst rank one result reached under perturbed weights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RankRange {
+    pub index: usize,
+    /// Rank under the unperturbed weights.
+    pub rank: usize,
+    pub best_rank: usize,
+    pub worst_rank: usize,
+}
+
+/// Order of two results in a ranking: higher score first (NaN last), then higher conversion
+/// rate, then lower average time to match, then input order.
+fn ranking_order(
+    (a, a_score): (usize, f64),
+    (b, b_score): (usize, f64),
+    results: &[SimulationResult],
+) -> Ordering {
+    let score = |value: f64| {
+        if value.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            value
+        }
+    };
+    score(b_score)
+        .total_cmp(&score(a_score))
+        .then_with(|| {
+            results[b]
+                .conversion_rate
+                .total_cmp(&results[a].conversion_rate)
+        })
+        .then_with(|| {
+            results[a]
+                .avg_time_to_match_ms
+                .total_cmp(&results[b].avg_time_to_match_ms)
+        })
+        .then_with(|| a.cmp(&b))
+}
+
+/// Indices of all results in ranking order.
+fn ranked_indices(results: &[SimulationResult], weights: &HealthWeights) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = calculate_health_scores(results, weights)
+        .into_iter()
+        .enumerate()
+        .collect();
+    ranked.sort_by(|&a, &b| ranking_order(a, b, results));
+    ranked
+}
+
+pub(crate) fn rank_results_impl(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    k: usize,
+) -> Vec<RankedResult> {
+    ranked_indices(results, weights)
+        .into_iter()
+        .take(k)
+        .enumerate()
+        .map(|(position, (index, score))| RankedResult {
+            rank: position + 1,
+            index,
+            score,
+        })
+        .collect()
+}
+
+pub(crate) fn ranking_stability_impl(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+    k: usize,
+    perturbation: f64,
+    samples: usize,
+    seed: u64,
+) -> RankingStability {
+    let top_k = rank_results_impl(results, weights, k);
+    let mut rank_ranges: Vec<RankRange> = top_k
+        .iter()
+        .map(|ranked| RankRange {
+            index: ranked.index,
+            rank: ranked.rank,
+            best_rank: ranked.rank,
+            worst_rank: ranked.rank,
+        })
+        .collect();
+    let mut best_unchanged = 0;
+    let mut top_k_unchanged = 0;
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..samples {
+        let mut perturbed = *weights;
+        for component in HealthComponent::ALL {
+            let factor = 1.0 + rng.gen_range(-perturbation..=perturbation);
+            *component.weight_mut(&mut perturbed) *= factor;
+        }
+        let ranked = ranked_indices(results, &perturbed);
+        let mut ranks = vec![0; results.len()];
+        for (position, (index, _)) in ranked.iter().enumerate() {
+            ranks[*index] = position + 1;
+        }
+        if top_k.first().map(|best| best.index) == ranked.first().map(|(index, _)| *index) {
+            best_unchanged += 1;
+        }
+        if rank_ranges.iter().all(|range| ranks[range.index] <= k) {
+            top_k_unchanged += 1;
+        }
+        for range in &mut rank_ranges {
+            range.best_rank = range.best_rank.min(ranks[range.index]);
+            range.worst_rank = range.worst_rank.max(ranks[range.index]);
+        }
+    }
+    let share = |count: usize| {
+        if samples == 0 {
+            1.0
+        } else {
+            count as f64 / samples as f64
+        }
+    };
+    RankingStability {
+        samples,
+        best_unchanged_share: share(best_unchanged),
+        top_k_unchanged_share: share(top_k_unchanged),
+        rank_ranges,
+    }
+}
+
 pub(crate) fn find_best_index_by_health(
     results: &[SimulationResult],
     weights: &HealthWeights,
@@ -1,6 +1,12 @@
 use std::fs::File;
 use std::path::Path;
 
+use crate::health::{HealthBreakdown, HealthComponent};
+use crate::metrics::SimulationResult;
+
+/// Prefix of the tabular export column holding a tag's value (`tag_<key>`).
+pub const TAG_COLUMN_PREFIX: &str = "tag_";
+
 pub(crate) fn ensure_not_empty<T>(items: &[T]) -> Result<(), Box<dyn std::error::Error>> {
     if items.is_empty() {
         return Err("No results to export".into());
@@ -14,3 +20,33 @@ pub(crate) fn create_output_file(
 ) -> Result<File, Box<dyn std::error::Error>> {
     Ok(File::create(path)?)
 }
+
+/// Tag keys used by any of `results`, sorted; tabular exports write one `tag_<key>` column each.
+pub(crate) fn tag_keys(results: &[SimulationResult]) -> Vec<String> {
+    let keys: std::collections::BTreeSet<&String> = results
+        .iter()
+        .flat_map(|result| result.tags.keys())
+        .collect();
+    keys.into_iter().cloned().collect()
+}
+
+/// Names of the health columns of tabular exports: `health_score`, then
+/// `health_<component>_normalized` and `health_<component>_contribution` per component.
+pub(crate) fn health_column_names() -> Vec<String> {
+    let mut names = vec!["health_score".to_string()];
+    for component in HealthComponent::ALL {
+        names.push(format!("health_{}_normalized", component.name()));
+        names.push(format!("health_{}_contribution", component.name()));
+    }
+    names
+}
+
+/// Values of [`health_column_names`] for one result.
+pub(crate) fn health_column_values(breakdown: &HealthBreakdown) -> Vec<f64> {
+    let mut values = vec![breakdown.score];
+    for score in &breakdown.components {
+        values.push(score.normalized);
+        values.push(score.contribution);
+    }
+    values
+}
@@ -4,6 +4,8 @@
 //! from simulation results, combining multiple metrics into a single
 //! score that represents overall marketplace health.
 
+use serde::Serialize;
+
 use crate::metrics::SimulationResult;
 
 /// Configurable weights for marketplace health score calculation.
@@ -19,6 +21,10 @@ use crate::metrics::SimulationResult;
 /// - Time to match: 0.15 (15%, inverted - lower is better)
 /// - Time to pickup: 0.15 (15%, inverted - lower is better)
 /// - Abandoned rides: -0.2 (20% penalty - lower is better)
+/// - Emissions per trip: 0.0 (off; inverted - lower is better when set)
+///
+/// Metrics are normalized with [`HealthNormalization::MinMax`] unless
+/// [`with_normalization`](Self::with_normalization) picks another strategy.
 #[derive(Debug, Clone, Copy)]
 pub struct HealthWeights {
     /// Weight for conversion rate (higher is better).
@@ -33,6 +39,10 @@ pub struct HealthWeights {
     pub time_to_pickup_weight: f64,
     /// Penalty weight for abandoned rides (negative - lower is better).
     pub abandoned_penalty: f64,
+    /// Weight for CO2 per completed trip (inverted - lower is better).
+    pub emissions_weight: f64,
+    /// How each metric is scaled to [0, 1] before weighting.
+    pub normalization: HealthNormalization,
 }
 
 impl Default for HealthWeights {
@@ -44,6 +54,8 @@ impl Default for HealthWeights {
             time_to_match_weight: 0.15,
             time_to_pickup_weight: 0.15,
             abandoned_penalty: -0.2,
+            emissions_weight: 0.0,
+            normalization: HealthNormalization::MinMax,
         }
     }
 }
@@ -65,6 +77,149 @@ impl HealthWeights {
             time_to_match_weight,
             time_to_pickup_weight,
             abandoned_penalty,
+            emissions_weight: 0.0,
+            normalization: HealthNormalization::MinMax,
+        }
+    }
+
+    /// Add CO2 per completed trip to the score (lower emissions score higher).
+    pub fn with_emissions_weight(mut self, emissions_weight: f64) -> Self {
+        self.emissions_weight = emissions_weight;
+        self
+    }
+
+    /// Normalize metrics with `normalization` instead of min-max across the scored results.
+    pub fn with_normalization(mut self, normalization: HealthNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+}
+
+/// Standard deviations from the mean that [`HealthNormalization::ZScore`] maps to 0 and 1.
+pub const Z_SCORE_CLIP: f64 = 3.0;
+
+/// How metrics are scaled to [0, 1] before weighting.
+///
+/// With min-max, a result's score depends on the best and worst results it is scored with, so
+/// scores from sweeps of different sizes are not comparable; the other strategies reduce
+/// ([`ZScore`](Self::ZScore)) or remove ([`Fixed`](Self::Fixed)) that dependency.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HealthNormalization {
+    /// `(value - min) / (max - min)` across the scored results (0.5 when all are equal).
+    #[default]
+    MinMax,
+    /// Standard score across the scored results, clipped to ±[`Z_SCORE_CLIP`] and mapped
+    /// linearly to [0, 1] (the mean scores 0.5). Less sensitive to a single extreme result.
+    ZScore,
+    /// `(value - min) / (max - min)` against fixed reference bounds, clamped to [0, 1]. Scores
+    /// only depend on the result itself, so they compare across sweeps.
+    Fixed(HealthReferenceBounds),
+}
+
+impl HealthNormalization {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MinMax => "min_max",
+            Self::ZScore => "z_score",
+            Self::Fixed(_) => "fixed",
+        }
+    }
+}
+
+/// Reference `(min, max)` per health metric for [`HealthNormalization::Fixed`], in the
+/// metric's raw units (see [`HealthComponent::metric_name`]).
+///
+/// Default bounds are `(0, 1)` for conversion and unset (every value scores 0.5) for the
+/// other metrics; set them with [`with_bounds`](Self::with_bounds) or freeze a baseline
+/// sweep's ranges with [`from_results`](Self::from_results).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthReferenceBounds {
+    /// Indexed like [`HealthComponent::ALL`].
+    bounds: [(f64, f64); 7],
+}
+
+impl Default for HealthReferenceBounds {
+    fn default() -> Self {
+        Self {
+            bounds: HealthComponent::ALL.map(|component| match component {
+                HealthComponent::Conversion => (0.0, 1.0),
+                _ => (0.0, 0.0),
+            }),
+        }
+    }
+}
+
+impl HealthReferenceBounds {
+    /// The min and max of each metric across `results`, e.g. a baseline sweep that later
+    /// sweeps are scored against.
+    pub fn from_results(results: &[SimulationResult]) -> Self {
+        Self {
+            bounds: HealthComponent::ALL.map(|component| metric_range(results, component)),
+        }
+    }
+
+    pub fn with_bounds(mut self, component: HealthComponent, min: f64, max: f64) -> Self {
+        self.bounds[component.index()] = (min, max);
+        self
+    }
+
+    pub fn bounds(&self, component: HealthComponent) -> (f64, f64) {
+        self.bounds[component.index()]
+    }
+}
+
+fn metric_range(results: &[SimulationResult], component: HealthComponent) -> (f64, f64) {
+    results
+        .iter()
+        .map(|result| component.metric(result))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        })
+}
+
+/// Per-metric scaling derived from the normalization strategy and the scored results.
+#[derive(Debug, Clone, Copy)]
+enum MetricScale {
+    Range { min: f64, max: f64 },
+    ZScore { mean: f64, std_dev: f64 },
+}
+
+impl MetricScale {
+    fn new(
+        normalization: &HealthNormalization,
+        results: &[SimulationResult],
+        component: HealthComponent,
+    ) -> Self {
+        match normalization {
+            HealthNormalization::MinMax => {
+                let (min, max) = metric_range(results, component);
+                Self::Range { min, max }
+            }
+            HealthNormalization::ZScore => {
+                let count = results.len().max(1) as f64;
+                let values = results.iter().map(|result| component.metric(result));
+                let mean = values.clone().sum::<f64>() / count;
+                let variance = values.map(|v| (v - mean).powi(2)).sum::<f64>() / count;
+                Self::ZScore {
+                    mean,
+                    std_dev: variance.sqrt(),
+                }
+            }
+            HealthNormalization::Fixed(bounds) => {
+                let (min, max) = bounds.bounds(component);
+                Self::Range { min, max }
+            }
+        }
+    }
+
+    fn normalize(self, value: f64) -> f64 {
+        match self {
+            Self::Range { min, max } => normalize_metric(value, min, max),
+            Self::ZScore { std_dev: 0.0, .. } => 0.5,
+            Self::ZScore { mean, std_dev } => {
+                let z = ((value - mean) / std_dev).clamp(-Z_SCORE_CLIP, Z_SCORE_CLIP);
+                0.5 + z / (2.0 * Z_SCORE_CLIP)
+            }
         }
     }
 }
@@ -81,6 +236,154 @@ fn normalize_metric(value: f64, min: f64, max: f64) -> f64 {
     }
 }
 
+/// One term of the health score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthComponent {
+    Conversion,
+    Revenue,
+    DriverPayouts,
+    TimeToMatch,
+    TimeToPickup,
+    Abandoned,
+    Emissions,
+}
+
+impl HealthComponent {
+    /// Every component, in the order they are summed.
+    pub const ALL: [Self; 7] = [
+        Self::Conversion,
+        Self::Revenue,
+        Self::DriverPayouts,
+        Self::TimeToMatch,
+        Self::TimeToPickup,
+        Self::Abandoned,
+        Self::Emissions,
+    ];
+
+    /// Snake-case name, used in export column names (`health_<name>_contribution`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Conversion => "conversion",
+            Self::Revenue => "revenue",
+            Self::DriverPayouts => "driver_payouts",
+            Self::TimeToMatch => "time_to_match",
+            Self::TimeToPickup => "time_to_pickup",
+            Self::Abandoned => "abandoned",
+            Self::Emissions => "emissions",
+        }
+    }
+
+    /// `SimulationResult` field the component scores.
+    pub fn metric_name(self) -> &'static str {
+        match self {
+            Self::Conversion => "conversion_rate",
+            Self::Revenue => "platform_revenue",
+            Self::DriverPayouts => "driver_payouts",
+            Self::TimeToMatch => "avg_time_to_match_ms",
+            Self::TimeToPickup => "avg_time_to_pickup_ms",
+            Self::Abandoned => "abandoned_quote_riders",
+            Self::Emissions => "co2_g_per_trip",
+        }
+    }
+
+    /// Whether lower raw values score higher (the normalized value is inverted).
+    pub fn lower_is_better(self) -> bool {
+        matches!(
+            self,
+            Self::TimeToMatch | Self::TimeToPickup | Self::Abandoned | Self::Emissions
+        )
+    }
+
+    fn metric(self, result: &SimulationResult) -> f64 {
+        match self {
+            Self::Conversion => result.conversion_rate,
+            Self::Revenue => result.platform_revenue,
+            Self::DriverPayouts => result.driver_payouts,
+            Self::TimeToMatch => result.avg_time_to_match_ms,
+            Self::TimeToPickup => result.avg_time_to_pickup_ms,
+            Self::Abandoned => result.abandoned_quote_riders as f64,
+            Self::Emissions => result.co2_g_per_trip,
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|component| *component == self)
+            .expect("every component is in ALL")
+    }
+
+    pub(crate) fn weight_mut(self, weights: &mut HealthWeights) -> &mut f64 {
+        match self {
+            Self::Conversion => &mut weights.conversion_weight,
+            Self::Revenue => &mut weights.revenue_weight,
+            Self::DriverPayouts => &mut weights.driver_payouts_weight,
+            Self::TimeToMatch => &mut weights.time_to_match_weight,
+            Self::TimeToPickup => &mut weights.time_to_pickup_weight,
+            Self::Abandoned => &mut weights.abandoned_penalty,
+            Self::Emissions => &mut weights.emissions_weight,
+        }
+    }
+
+    pub fn weight(self, weights: &HealthWeights) -> f64 {
+        match self {
+            Self::Conversion => weights.conversion_weight,
+            Self::Revenue => weights.revenue_weight,
+            Self::DriverPayouts => weights.driver_payouts_weight,
+            Self::TimeToMatch => weights.time_to_match_weight,
+            Self::TimeToPickup => weights.time_to_pickup_weight,
+            Self::Abandoned => weights.abandoned_penalty,
+            Self::Emissions => weights.emissions_weight,
+        }
+    }
+}
+
+/// How one component contributed to a result's health score.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HealthComponentScore {
+    pub component: HealthComponent,
+    /// The result's metric value (see [`HealthComponent::metric_name`]).
+    pub raw: f64,
+    /// Min-max normalized across the scored results, in [0, 1], inverted for lower-is-better
+    /// metrics so 1 is always the best result.
+    pub normalized: f64,
+    pub weight: f64,
+    /// `normalized * weight`; the contributions sum to the score.
+    pub contribution: f64,
+}
+
+/// A result's health score with its per-component terms.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthBreakdown {
+    pub score: f64,
+    /// One entry per [`HealthComponent::ALL`], in that order.
+    pub components: Vec<HealthComponentScore>,
+}
+
+impl HealthBreakdown {
+    pub fn component(&self, component: HealthComponent) -> Option<&HealthComponentScore> {
+        self.components
+            .iter()
+            .find(|score| score.component == component)
+    }
+
+    /// Contribution differences to `other` (`self - other`), largest absolute difference
+    /// first: why this result scored higher or lower than `other`.
+    pub fn contribution_differences(&self, other: &HealthBreakdown) -> Vec<(HealthComponent, f64)> {
+        let mut differences: Vec<(HealthComponent, f64)> = self
+            .components
+            .iter()
+            .filter_map(|score| {
+                let other = other.component(score.component)?;
+                Some((score.component, score.contribution - other.contribution))
+            })
+            .collect();
+        differences.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        differences
+    }
+}
+
 /// Calculate health scores for all simulation results.
 ///
 /// Normalizes metrics across all results and calculates weighted health scores.
@@ -95,88 +398,50 @@ fn normalize_metric(value: f64, min: f64, max: f64) -> f64 {
 ///
 /// Vector of health scores in the same order as input results.
 pub fn calculate_health_scores(results: &[SimulationResult], weights: &HealthWeights) -> Vec<f64> {
-    if results.is_empty() {
-        return vec![];
-    }
-
-    // Find min/max for each metric across all results
-    let (conversion_min, conversion_max) = results
-        .iter()
-        .map(|r| r.conversion_rate)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
-
-    let (revenue_min, revenue_max) = results
-        .iter()
-        .map(|r| r.platform_revenue)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
-
-    let (payouts_min, payouts_max) = results
-        .iter()
-        .map(|r| r.driver_payouts)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
-
-    let (match_time_min, match_time_max) = results
-        .iter()
-        .map(|r| r.avg_time_to_match_ms)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
-
-    let (pickup_time_min, pickup_time_max) = results
-        .iter()
-        .map(|r| r.avg_time_to_pickup_ms)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
+    calculate_health_breakdowns(results, weights)
+        .into_iter()
+        .map(|breakdown| breakdown.score)
+        .collect()
+}
 
-    let (abandoned_min, abandoned_max) = results
-        .iter()
-        .map(|r| r.abandoned_quote_riders as f64)
-        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
-            (min.min(v), max.max(v))
-        });
+/// Calculate health scores with their per-component breakdown (raw metric, normalized value,
+/// weight and contribution), in the same order as `results`.
+///
+/// Metrics are normalized with `weights.normalization`; with the default min-max (and with
+/// z-scores) a breakdown only compares results scored together.
+pub fn calculate_health_breakdowns(
+    results: &[SimulationResult],
+    weights: &HealthWeights,
+) -> Vec<HealthBreakdown> {
+    let scales = HealthComponent::ALL
+        .map(|component| MetricScale::new(&weights.normalization, results, component));
 
-    // Calculate health score for each result
     results
         .iter()
         .map(|result| {
-            // Normalize metrics (higher is better for conversion, revenue, payouts)
-            let conversion_norm =
-                normalize_metric(result.conversion_rate, conversion_min, conversion_max);
-            let revenue_norm = normalize_metric(result.platform_revenue, revenue_min, revenue_max);
-            let payouts_norm = normalize_metric(result.driver_payouts, payouts_min, payouts_max);
-
-            // Normalize timing metrics (lower is better, so invert)
-            let match_time_norm =
-                1.0 - normalize_metric(result.avg_time_to_match_ms, match_time_min, match_time_max);
-            let pickup_time_norm = 1.0
-                - normalize_metric(
-                    result.avg_time_to_pickup_ms,
-                    pickup_time_min,
-                    pickup_time_max,
-                );
-
-            // Normalize abandoned rides (lower is better, so invert)
-            let abandoned_norm = 1.0
-                - normalize_metric(
-                    result.abandoned_quote_riders as f64,
-                    abandoned_min,
-                    abandoned_max,
-                );
-
-            // Calculate weighted sum
-            conversion_norm * weights.conversion_weight
-                + revenue_norm * weights.revenue_weight
-                + payouts_norm * weights.driver_payouts_weight
-                + match_time_norm * weights.time_to_match_weight
-                + pickup_time_norm * weights.time_to_pickup_weight
-                + abandoned_norm * weights.abandoned_penalty
+            let components: Vec<HealthComponentScore> = HealthComponent::ALL
+                .iter()
+                .zip(scales)
+                .map(|(&component, scale)| {
+                    let raw = component.metric(result);
+                    let normalized = scale.normalize(raw);
+                    let normalized = if component.lower_is_better() {
+                        1.0 - normalized
+                    } else {
+                        normalized
+                    };
+                    let weight = component.weight(weights);
+                    HealthComponentScore {
+                        component,
+                        raw,
+                        normalized,
+                        weight,
+                        contribution: normalized * weight,
+                    }
+                })
+                .collect();
+            let score = components.iter().map(|score| score.contribution).sum();
+            HealthBreakdown { score, components }
         })
         .collect()
 }
@@ -186,6 +451,31 @@ mod tests {
     use super::*;
     use crate::metrics::SimulationResult;
 
+    fn sample_result() -> SimulationResult {
+        SimulationResult {
+            total_riders: 100,
+            total_drivers: 20,
+            completed_riders: 80,
+            abandoned_quote_riders: 10,
+            cancelled_riders: 10,
+            conversion_rate: 0.8,
+            platform_revenue: 1000.0,
+            driver_payouts: 5000.0,
+            total_fares_collected: 6000.0,
+            avg_time_to_match_ms: 1000.0,
+            median_time_to_match_ms: 1000.0,
+            p90_time_to_match_ms: 2000.0,
+            avg_time_to_pickup_ms: 5000.0,
+            median_time_to_pickup_ms: 5000.0,
+            p90_time_to_pickup_ms: 10000.0,
+            completed_trips: 80,
+            riders_abandoned_price: 5,
+            riders_abandoned_eta: 3,
+            riders_abandoned_stochastic: 2,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_normalize_metric() {
         assert_eq!(normalize_metric(50.0, 0.0, 100.0), 0.5);
@@ -217,6 +507,7 @@ mod tests {
                 riders_abandoned_price: 5,
                 riders_abandoned_eta: 3,
                 riders_abandoned_stochastic: 2,
+                ..Default::default()
             },
             SimulationResult {
                 total_riders: 100,
@@ -238,6 +529,7 @@ mod tests {
                 riders_abandoned_price: 15,
                 riders_abandoned_eta: 10,
                 riders_abandoned_stochastic: 5,
+                ..Default::default()
             },
         ];
 
@@ -249,6 +541,160 @@ mod tests {
         assert!(scores[0] > scores[1]);
     }
 
+    #[test]
+    fn test_emissions_weight_prefers_cleaner_run() {
+        let clean = SimulationResult {
+            co2_g_per_trip: 400.0,
+            ..Default::default()
+        };
+        let mut dirty = clean.clone();
+        dirty.co2_g_per_trip = 1200.0;
+
+        let results = vec![clean, dirty];
+        let neutral = calculate_health_scores(&results, &HealthWeights::default());
+        assert_eq!(neutral[0], neutral[1]);
+
+        let weights = HealthWeights::default().with_emissions_weight(0.2);
+        let scores = calculate_health_scores(&results, &weights);
+        assert!(scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn test_breakdown_contributions_sum_to_score() {
+        let mut worse = sample_result();
+        worse.conversion_rate = 0.5;
+        worse.avg_time_to_match_ms = 4000.0;
+        let results = vec![sample_result(), worse];
+        let weights = HealthWeights::default().with_emissions_weight(0.1);
+
+        let breakdowns = calculate_health_breakdowns(&results, &weights);
+        let scores = calculate_health_scores(&results, &weights);
+        for (breakdown, score) in breakdowns.iter().zip(&scores) {
+            assert_eq!(breakdown.score, *score);
+            assert_eq!(breakdown.components.len(), HealthComponent::ALL.len());
+            let sum: f64 = breakdown.components.iter().map(|c| c.contribution).sum();
+            assert!((sum - breakdown.score).abs() < 1e-12);
+        }
+
+        let best = &breakdowns[0];
+        let conversion = best.component(HealthComponent::Conversion).unwrap();
+        assert_eq!(conversion.raw, 0.8);
+        assert_eq!(conversion.normalized, 1.0);
+        assert_eq!(conversion.weight, 0.3);
+        // Lower match time is better, so the faster run normalizes to 1
+        let match_time = best.component(HealthComponent::TimeToMatch).unwrap();
+        assert_eq!(match_time.raw, 1000.0);
+        assert_eq!(match_time.normalized, 1.0);
+        // Equal metrics normalize to 0.5 for every result
+        let emissions = best.component(HealthComponent::Emissions).unwrap();
+        assert_eq!(emissions.normalized, 0.5);
+
+        let differences = best.contribution_differences(&breakdowns[1]);
+        assert_eq!(differences[0].0, HealthComponent::Conversion);
+        assert!((differences[0].1 - 0.3).abs() < 1e-12);
+        assert_eq!(differences[1].0, HealthComponent::TimeToMatch);
+        assert!(differences[2..]
+            .iter()
+            .all(|(_, difference)| *difference == 0.0));
+    }
+
+    #[test]
+    fn test_z_score_normalization_centers_on_the_mean() {
+        let conversions = [0.2, 0.4, 0.6, 0.8, 1.0];
+        let results: Vec<SimulationResult> = conversions
+            .iter()
+            .map(|&conversion_rate| SimulationResult {
+                conversion_rate,
+                ..sample_result()
+            })
+            .collect();
+        let weights = HealthWeights::default().with_normalization(HealthNormalization::ZScore);
+        let breakdowns = calculate_health_breakdowns(&results, &weights);
+        let normalized: Vec<f64> = breakdowns
+            .iter()
+            .map(|b| b.component(HealthComponent::Conversion).unwrap().normalized)
+            .collect();
+        assert!((normalized[2] - 0.5).abs() < 1e-12);
+        assert!((normalized[0] + normalized[4] - 1.0).abs() < 1e-12);
+        assert!(normalized.windows(2).all(|pair| pair[0] < pair[1]));
+        // Unlike min-max, the extremes are not pinned to 0 and 1
+        assert!(normalized[0] > 0.0 && normalized[4] < 1.0);
+        // Equal metrics are neutral
+        assert_eq!(
+            breakdowns[0]
+                .component(HealthComponent::Revenue)
+                .unwrap()
+                .normalized,
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_fixed_bounds_make_scores_independent_of_the_sweep() {
+        let baseline = vec![
+            sample_result(),
+            SimulationResult {
+                conversion_rate: 0.4,
+                platform_revenue: 200.0,
+                avg_time_to_match_ms: 8000.0,
+                ..sample_result()
+            },
+        ];
+        let bounds = HealthReferenceBounds::from_results(&baseline);
+        assert_eq!(bounds.bounds(HealthComponent::Conversion), (0.4, 0.8));
+
+        // Freezing a sweep's own ranges reproduces its min-max scores
+        let fixed = HealthWeights::default().with_normalization(HealthNormalization::Fixed(bounds));
+        assert_eq!(
+            calculate_health_scores(&baseline, &fixed),
+            calculate_health_scores(&baseline, &HealthWeights::default())
+        );
+
+        // A result scores the same alone and within a larger sweep
+        let candidate = SimulationResult {
+            conversion_rate: 0.6,
+            ..sample_result()
+        };
+        let alone = calculate_health_scores(std::slice::from_ref(&candidate), &fixed);
+        let mut sweep = baseline.clone();
+        sweep.push(candidate);
+        let together = calculate_health_scores(&sweep, &fixed);
+        assert_eq!(alone[0], together[2]);
+        let min_max_alone = calculate_health_scores(&sweep[2..], &HealthWeights::default())[0];
+        assert_ne!(
+            min_max_alone,
+            calculate_health_scores(&sweep, &HealthWeights::default())[2]
+        );
+
+        // Values outside the bounds clamp
+        let custom =
+            HealthReferenceBounds::default().with_bounds(HealthComponent::Revenue, 0.0, 500.0);
+        let weights =
+            HealthWeights::default().with_normalization(HealthNormalization::Fixed(custom));
+        let breakdown = &calculate_health_breakdowns(&baseline, &weights)[0];
+        assert_eq!(
+            breakdown
+                .component(HealthComponent::Revenue)
+                .unwrap()
+                .normalized,
+            1.0
+        );
+        assert_eq!(
+            breakdown
+                .component(HealthComponent::Conversion)
+                .unwrap()
+                .normalized,
+            0.8
+        );
+        assert_eq!(
+            breakdown
+                .component(HealthComponent::DriverPayouts)
+                .unwrap()
+                .normalized,
+            0.5
+        );
+    }
+
     #[test]
     fn test_calculate_health_scores_empty() {
         let scores = calculate_health_scores(&[], &HealthWeights::default());
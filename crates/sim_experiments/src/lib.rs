@@ -35,25 +35,47 @@
 //! - [`metrics`]: Metrics extraction from simulation results
 //! - [`health`]: Marketplace health score calculation
 //! - [`export`]: Result export to Parquet/JSON
+//! - [`report`]: Self-contained HTML reports of results Parquet files
 //!
 //! # Scaling to Multiple Machines
 //!
 //! See the [README.md](../README.md) for information on scaling experiments across
 //! multiple machines using a coordinator/worker architecture.
 
+pub mod concurrency;
 pub mod export;
 pub mod health;
 pub mod metrics;
+pub mod paired;
 pub mod parameter_spaces;
 pub mod parameters;
+pub mod report;
 pub mod runner;
 
+pub use concurrency::{estimate_run_memory_bytes, SweepConcurrency};
 pub use export::{
-    export_to_csv, export_to_json, export_to_parquet, find_best_parameters, find_best_result_index,
+    export_to_csv, export_to_csv_with_health, export_to_json, export_to_json_with_health,
+    export_to_ndjson, export_to_parquet, export_to_parquet_with_health, find_best_parameters,
+    find_best_result_index, rank_results, ranking_stability, RankRange, RankedResult,
+    RankingStability,
+};
+pub use health::{
+    calculate_health_breakdowns, calculate_health_scores, HealthBreakdown, HealthComponent,
+    HealthComponentScore, HealthNormalization, HealthReferenceBounds, HealthWeights,
+};
+pub use metrics::{filter_by_tag, group_by_tag, metrics_from_bundle, SimulationResult};
+pub use paired::{
+    compare_paired, paired_differences, paired_differences_by_tag, PairedComparison,
+    PairedDifference,
+};
+pub use parameters::{
+    CityGeometry, GenerationReport, ParameterDistribution, ParameterSet, ParameterSpace,
+    SampledParameter,
 };
-pub use health::{calculate_health_scores, HealthWeights};
-pub use metrics::SimulationResult;
-pub use parameters::{ParameterSet, ParameterSpace};
 pub use runner::{
-    run_parallel_experiments, run_single_simulation_with_artifacts, SimulationArtifacts,
+    partition_outcomes, record_single_simulation, run_parallel_experiments,
+    run_parallel_experiments_isolated, run_parallel_experiments_with_callback,
+    run_parallel_experiments_with_progress, run_single_simulation_isolated,
+    run_single_simulation_with_artifacts, ExperimentProgress, FailedRun, RunFailure, RunLimits,
+    SimulationArtifacts,
 };
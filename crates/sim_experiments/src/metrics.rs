@@ -1,14 +1,17 @@
 //! Metrics extraction from simulation results.
 //!
 //! This module extracts comprehensive metrics from completed simulations,
-//! including conversion rates, revenue, driver payouts, and timing statistics.
+//! including conversion rates, revenue, driver payouts, timing statistics and emissions.
+
+use std::collections::BTreeMap;
 
 use bevy_ecs::prelude::World;
 use sim_core::ecs::DriverEarnings;
+use sim_core::run_bundle::{RunBundle, ScenarioRecord};
 use sim_core::telemetry::SimTelemetry;
 
 /// Aggregated metrics from a single simulation run.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct SimulationResult {
     /// Total number of riders spawned.
     pub total_riders: usize,
@@ -22,12 +25,32 @@ pub struct SimulationResult {
     pub cancelled_riders: usize,
     /// Conversion rate (completed / total resolved).
     pub conversion_rate: f64,
+    /// Rider requests tracked by the funnel (see `SimTelemetry::funnel`).
+    pub funnel_requests: usize,
+    /// Funnel conversion: requests that received a quote.
+    pub funnel_quote_rate: f64,
+    /// Funnel conversion: quoted riders who accepted a quote.
+    pub funnel_quote_acceptance_rate: f64,
+    /// Funnel conversion: accepted quotes matched to a driver.
+    pub funnel_match_rate: f64,
+    /// Funnel conversion: matched riders who were picked up.
+    pub funnel_pickup_rate: f64,
+    /// Funnel conversion: picked-up riders whose trip completed.
+    pub funnel_completion_rate: f64,
     /// Total platform revenue from commissions.
     pub platform_revenue: f64,
     /// Total driver payouts (sum of all driver earnings).
     pub driver_payouts: f64,
     /// Total fares collected from riders.
     pub total_fares_collected: f64,
+    /// Total taxes and regulatory fees withheld from fares.
+    pub taxes_and_fees: f64,
+    /// Total platform top-ups paid under the minimum earnings floor (included in driver payouts).
+    pub earnings_floor_top_ups: f64,
+    /// Completed trips where the rider chose a wait-and-save (deferred dispatch) class.
+    pub wait_and_save_trips: usize,
+    /// Average time to match in milliseconds for wait-and-save trips.
+    pub wait_and_save_avg_time_to_match_ms: f64,
     /// Average time to match in milliseconds.
     pub avg_time_to_match_ms: f64,
     /// Median time to match in milliseconds.
@@ -42,13 +65,38 @@ pub struct SimulationResult {
     pub p90_time_to_pickup_ms: f64,
     /// Total number of completed trips.
     pub completed_trips: usize,
+    /// Km driven empty to pickups, summed over completed trips.
+    pub deadhead_km: f64,
+    /// Km driven with riders on board, summed over completed trips.
+    pub trip_km: f64,
+    /// Driver idle minutes preceding each match, summed over completed trips.
+    pub idle_minutes: f64,
+    /// Driver minutes en route to pickups, summed over completed trips.
+    pub en_route_minutes: f64,
+    /// Driver minutes with riders on board, summed over completed trips.
+    pub on_trip_minutes: f64,
+    /// CO2 emitted by all driving (occupied and deadhead), in kg.
+    pub co2_kg: f64,
+    /// CO2 per completed trip in grams (0.0 without completed trips).
+    pub co2_g_per_trip: f64,
     /// Breakdown of abandonment reasons.
     pub riders_abandoned_price: usize,
     pub riders_abandoned_eta: usize,
     pub riders_abandoned_stochastic: usize,
+    /// Hash of the scenario the run was built from (`ScenarioRecord::scenario_hash`).
+    pub scenario_hash: Option<String>,
+    /// Tags of the [`ParameterSet`](crate::parameters::ParameterSet) that produced the run
+    /// (empty for results not produced by the runner).
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
 }
 
 impl SimulationResult {
+    /// Value of tag `key`, if the run has it.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags.get(key).map(String::as_str)
+    }
+
     /// Calculate statistics from a vector of values.
     fn calculate_stats(values: &[u64]) -> (f64, f64, f64) {
         if values.is_empty() {
@@ -70,14 +118,26 @@ impl SimulationResult {
 
         (avg, median, p90)
     }
+
+    /// Mean cost of serving a completed trip at an assumed per-km driving cost and per-minute
+    /// driver time cost (idle, en route and on trip); 0.0 without completed trips.
+    pub fn cost_per_trip(&self, cost_per_km: f64, cost_per_minute: f64) -> f64 {
+        if self.completed_trips == 0 {
+            return 0.0;
+        }
+        let minutes = self.idle_minutes + self.en_route_minutes + self.on_trip_minutes;
+        let total = (self.deadhead_km + self.trip_km) * cost_per_km + minutes * cost_per_minute;
+        total / self.completed_trips as f64
+    }
 }
 
 /// Extract metrics from a completed simulation world.
 ///
 /// Queries the world for telemetry data and driver earnings to compute
 /// comprehensive metrics including conversion rates, revenue, payouts,
-/// and timing statistics.
-pub fn extract_metrics(world: &mut World) -> SimulationResult {
+/// and timing statistics. Read-only: the runner settles the earnings floor
+/// when the run ends, so calling this twice yields the same result.
+pub fn extract_metrics(world: &World) -> SimulationResult {
     // Extract telemetry data first (immutable borrow)
     let (
         riders_completed_total,
@@ -85,9 +145,14 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
         riders_abandoned_quote_total,
         platform_revenue_total,
         total_fares_collected,
+        taxes_and_fees_total,
+        earnings_floor_top_ups_total,
         riders_abandoned_price,
         riders_abandoned_eta,
         riders_abandoned_stochastic,
+        funnel,
+        service_cost,
+        emissions,
         completed_trips_data,
     ) = {
         let telemetry = world
@@ -95,10 +160,17 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
             .expect("SimTelemetry resource not found");
 
         // Clone the completed trips data we need
-        let trips_data: Vec<(u64, u64, u64)> = telemetry
+        let trips_data: Vec<(u64, u64, u64, bool)> = telemetry
             .completed_trips
             .iter()
-            .map(|trip| (trip.requested_at, trip.matched_at, trip.pickup_at))
+            .map(|trip| {
+                (
+                    trip.requested_at,
+                    trip.matched_at,
+                    trip.pickup_at,
+                    trip.wait_and_save,
+                )
+            })
             .collect();
 
         (
@@ -107,16 +179,23 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
             telemetry.riders_abandoned_quote_total,
             telemetry.platform_revenue_total,
             telemetry.total_fares_collected,
+            telemetry.taxes_and_fees_total,
+            telemetry.earnings_floor_top_ups_total,
             telemetry.riders_abandoned_price,
             telemetry.riders_abandoned_eta,
             telemetry.riders_abandoned_stochastic,
+            telemetry.funnel_summary(),
+            telemetry.service_cost_totals(),
+            telemetry.emissions,
             trips_data,
         )
     };
 
-    // Now we can do mutable queries (telemetry borrow is dropped)
     let (driver_payouts, total_drivers) = {
-        let drivers: Vec<_> = world.query::<&DriverEarnings>().iter(world).collect();
+        let drivers: Vec<_> = world
+            .iter_entities()
+            .filter_map(|entity| entity.get::<DriverEarnings>())
+            .collect();
         let payouts: f64 = drivers.iter().map(|earnings| earnings.daily_earnings).sum();
         (payouts, drivers.len())
     };
@@ -136,20 +215,33 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
     let mut time_to_match_values: Vec<u64> = Vec::new();
     let mut time_to_pickup_values: Vec<u64> = Vec::new();
 
-    for (requested_at, matched_at, pickup_at) in &completed_trips_data {
+    let mut wait_and_save_time_to_match_values: Vec<u64> = Vec::new();
+
+    for (requested_at, matched_at, pickup_at, wait_and_save) in &completed_trips_data {
         time_to_match_values.push(matched_at.saturating_sub(*requested_at));
         time_to_pickup_values.push(pickup_at.saturating_sub(*matched_at));
+        if *wait_and_save {
+            wait_and_save_time_to_match_values.push(matched_at.saturating_sub(*requested_at));
+        }
     }
 
     let (avg_time_to_match, median_time_to_match, p90_time_to_match) =
         SimulationResult::calculate_stats(&time_to_match_values);
     let (avg_time_to_pickup, median_time_to_pickup, p90_time_to_pickup) =
         SimulationResult::calculate_stats(&time_to_pickup_values);
+    let (wait_and_save_avg_time_to_match, _, _) =
+        SimulationResult::calculate_stats(&wait_and_save_time_to_match_values);
 
     // Estimate total riders (use resolved count as proxy if we don't have exact spawn count)
     // In a real scenario, we'd track this, but for now we use resolved count
     let total_riders = total_resolved as usize;
 
+    let co2_g_per_trip = if completed_trips_data.is_empty() {
+        0.0
+    } else {
+        emissions.total_g_co2() / completed_trips_data.len() as f64
+    };
+
     SimulationResult {
         total_riders,
         total_drivers,
@@ -157,9 +249,19 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
         abandoned_quote_riders: riders_abandoned_quote_total as usize,
         cancelled_riders: riders_cancelled_total as usize,
         conversion_rate,
+        funnel_requests: funnel.requested,
+        funnel_quote_rate: funnel.quote_rate(),
+        funnel_quote_acceptance_rate: funnel.quote_acceptance_rate(),
+        funnel_match_rate: funnel.match_rate(),
+        funnel_pickup_rate: funnel.pickup_rate(),
+        funnel_completion_rate: funnel.completion_rate(),
         platform_revenue: platform_revenue_total,
         driver_payouts,
         total_fares_collected,
+        taxes_and_fees: taxes_and_fees_total,
+        earnings_floor_top_ups: earnings_floor_top_ups_total,
+        wait_and_save_trips: wait_and_save_time_to_match_values.len(),
+        wait_and_save_avg_time_to_match_ms: wait_and_save_avg_time_to_match,
         avg_time_to_match_ms: avg_time_to_match,
         median_time_to_match_ms: median_time_to_match,
         p90_time_to_match_ms: p90_time_to_match,
@@ -167,10 +269,58 @@ pub fn extract_metrics(world: &mut World) -> SimulationResult {
         median_time_to_pickup_ms: median_time_to_pickup,
         p90_time_to_pickup_ms: p90_time_to_pickup,
         completed_trips: completed_trips_data.len(),
+        deadhead_km: service_cost.deadhead_km,
+        trip_km: service_cost.trip_km,
+        idle_minutes: service_cost.idle_ms as f64 / 60_000.0,
+        en_route_minutes: service_cost.en_route_ms as f64 / 60_000.0,
+        on_trip_minutes: service_cost.on_trip_ms as f64 / 60_000.0,
+        co2_kg: emissions.total_g_co2() / 1000.0,
+        co2_g_per_trip,
         riders_abandoned_price: riders_abandoned_price as usize,
         riders_abandoned_eta: riders_abandoned_eta as usize,
         riders_abandoned_stochastic: riders_abandoned_stochastic as usize,
+        scenario_hash: world
+            .get_resource::<ScenarioRecord>()
+            .and_then(ScenarioRecord::scenario_hash),
+        tags: BTreeMap::new(),
+    }
+}
+
+/// Results whose tag `key` equals `value`, in input order.
+pub fn filter_by_tag(
+    results: &[SimulationResult],
+    key: &str,
+    value: &str,
+) -> Vec<SimulationResult> {
+    results
+        .iter()
+        .filter(|result| result.tag(key) == Some(value))
+        .cloned()
+        .collect()
+}
+
+/// Results grouped by the value of tag `key` (groups sorted by value, results in input order).
+/// Results without the tag are left out.
+pub fn group_by_tag(
+    results: &[SimulationResult],
+    key: &str,
+) -> BTreeMap<String, Vec<SimulationResult>> {
+    let mut groups: BTreeMap<String, Vec<SimulationResult>> = BTreeMap::new();
+    for result in results {
+        if let Some(value) = result.tag(key) {
+            groups
+                .entry(value.to_string())
+                .or_default()
+                .push(result.clone());
+        }
     }
+    groups
+}
+
+/// Metrics of a recorded run, computed from the bundle's telemetry as [`extract_metrics`]
+/// would on the finished world.
+pub fn metrics_from_bundle(bundle: &RunBundle) -> SimulationResult {
+    extract_metrics(&bundle.restore_world())
 }
 
 #[cfg(test)]
@@ -0,0 +1,240 @@
+//! Paired-seed policy comparison (common random numbers).
+//!
+//! Both policies run once per seed with identical seeds. Because every random stream is
+//! derived from the seed (`sim_core::seeds::SeedHierarchy`), the two runs of a pair see the
+//! same arrivals, positions and decisions wherever the policies do not diverge, so the
+//! per-seed difference in a metric has far lower variance than the difference of two
+//! independent samples.
+
+use sim_core::scenario::ScenarioParams;
+
+use crate::metrics::{filter_by_tag, SimulationResult};
+use crate::parameters::ParameterSet;
+use crate::runner::run_parallel_experiments_with_progress;
+
+/// Metrics compared by [`paired_differences`], as `(name, extractor)`.
+pub const PAIRED_METRICS: [(&str, fn(&SimulationResult) -> f64); 9] = [
+    ("conversion_rate", |r| r.conversion_rate),
+    ("completed_trips", |r| r.completed_trips as f64),
+    ("platform_revenue", |r| r.platform_revenue),
+    ("driver_payouts", |r| r.driver_payouts),
+    ("avg_time_to_match_ms", |r| r.avg_time_to_match_ms),
+    ("avg_time_to_pickup_ms", |r| r.avg_time_to_pickup_ms),
+    ("abandoned_quote_riders", |r| {
+        r.abandoned_quote_riders as f64
+    }),
+    ("deadhead_km", |r| r.deadhead_km),
+    ("co2_kg", |r| r.co2_kg),
+];
+
+/// Paired difference (policy B minus policy A) of one metric across seeds.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct PairedDifference {
+    pub metric: &'static str,
+    pub mean_a: f64,
+    pub mean_b: f64,
+    /// Mean of per-seed differences `b - a`.
+    pub mean_difference: f64,
+    /// Sample standard deviation of the per-seed differences (0.0 with fewer than two seeds).
+    pub std_dev_difference: f64,
+    /// Standard error of `mean_difference`.
+    pub std_error: f64,
+    /// Two-sided 95% confidence interval of `mean_difference` (Student's t).
+    pub ci95_low: f64,
+    pub ci95_high: f64,
+}
+
+impl PairedDifference {
+    /// True when the 95% confidence interval excludes zero.
+    pub fn is_significant(&self) -> bool {
+        self.ci95_low > 0.0 || self.ci95_high < 0.0
+    }
+}
+
+/// Results of a paired comparison, with per-seed results aligned by index.
+#[derive(Debug, Clone)]
+pub struct PairedComparison {
+    pub seeds: Vec<u64>,
+    pub results_a: Vec<SimulationResult>,
+    pub results_b: Vec<SimulationResult>,
+    pub differences: Vec<PairedDifference>,
+}
+
+impl PairedComparison {
+    pub fn difference(&self, metric: &str) -> Option<&PairedDifference> {
+        self.differences.iter().find(|diff| diff.metric == metric)
+    }
+}
+
+/// Run `policy_a` and `policy_b` once per seed with identical seeds (in parallel) and compute
+/// paired differences for [`PAIRED_METRICS`]. Any seed set on the policies is overridden.
+/// Runs are tagged `policy=a` / `policy=b`.
+pub fn compare_paired(
+    policy_a: &ScenarioParams,
+    policy_b: &ScenarioParams,
+    seeds: &[u64],
+) -> PairedComparison {
+    let parameter_sets: Vec<ParameterSet> = [(policy_a, "a"), (policy_b, "b")]
+        .into_iter()
+        .flat_map(|(policy, label)| {
+            seeds.iter().enumerate().map(move |(run_id, seed)| {
+                ParameterSet::new(policy.clone(), format!("paired_{label}"), run_id, *seed)
+                    .with_tag("policy", label)
+            })
+        })
+        .collect();
+
+    let mut results_a = run_parallel_experiments_with_progress(parameter_sets, None, false);
+    let results_b = results_a.split_off(seeds.len());
+    let differences = paired_differences(&results_a, &results_b);
+
+    PairedComparison {
+        seeds: seeds.to_vec(),
+        results_a,
+        results_b,
+        differences,
+    }
+}
+
+/// Paired differences (`b - a`) for [`PAIRED_METRICS`] from results aligned by seed.
+/// Extra results on the longer side are ignored.
+pub fn paired_differences(
+    results_a: &[SimulationResult],
+    results_b: &[SimulationResult],
+) -> Vec<PairedDifference> {
+    PAIRED_METRICS
+        .iter()
+        .map(|(metric, extract)| {
+            let pairs: Vec<(f64, f64)> = results_a
+                .iter()
+                .zip(results_b)
+                .map(|(a, b)| (extract(a), extract(b)))
+                .collect();
+            summarize(metric, &pairs)
+        })
+        .collect()
+}
+
+/// Paired differences between the results tagged `key=value_b` and those tagged
+/// `key=value_a` (e.g. `policy=candidate` vs `policy=baseline`), paired in input order, so
+/// each group must list its runs in the same seed order.
+pub fn paired_differences_by_tag(
+    results: &[SimulationResult],
+    key: &str,
+    value_a: &str,
+    value_b: &str,
+) -> Vec<PairedDifference> {
+    paired_differences(
+        &filter_by_tag(results, key, value_a),
+        &filter_by_tag(results, key, value_b),
+    )
+}
+
+fn summarize(metric: &'static str, pairs: &[(f64, f64)]) -> PairedDifference {
+    let n = pairs.len();
+    let mean = |values: &mut dyn Iterator<Item = f64>| {
+        if n == 0 {
+            0.0
+        } else {
+            values.sum::<f64>() / n as f64
+        }
+    };
+    let mean_a = mean(&mut pairs.iter().map(|(a, _)| *a));
+    let mean_b = mean(&mut pairs.iter().map(|(_, b)| *b));
+    let mean_difference = mean(&mut pairs.iter().map(|(a, b)| b - a));
+
+    let std_dev_difference = if n < 2 {
+        0.0
+    } else {
+        let squared: f64 = pairs
+            .iter()
+            .map(|(a, b)| (b - a - mean_difference).powi(2))
+            .sum();
+        (squared / (n - 1) as f64).sqrt()
+    };
+    let std_error = if n == 0 {
+        0.0
+    } else {
+        std_dev_difference / (n as f64).sqrt()
+    };
+    let half_width = t_critical_95(n.saturating_sub(1)) * std_error;
+
+    PairedDifference {
+        metric,
+        mean_a,
+        mean_b,
+        mean_difference,
+        std_dev_difference,
+        std_error,
+        ci95_low: mean_difference - half_width,
+        ci95_high: mean_difference + half_width,
+    }
+}
+
+/// Two-sided 95% critical value of Student's t for `df` degrees of freedom.
+fn t_critical_95(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match df {
+        0 => 0.0,
+        1..=30 => TABLE[df - 1],
+        _ => 1.96,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paired_summary_uses_per_seed_differences() {
+        // Large spread across seeds, constant +2 effect: paired CI is tight around 2
+        let pairs = [(10.0, 12.0), (100.0, 102.0), (55.0, 57.0)];
+        let diff = summarize("metric", &pairs);
+        assert!((diff.mean_difference - 2.0).abs() < 1e-9);
+        assert!(diff.std_dev_difference.abs() < 1e-9);
+        assert!(diff.is_significant());
+        assert!((diff.mean_b - diff.mean_a - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn confidence_interval_widens_with_noisy_differences() {
+        let pairs = [(0.0, 1.0), (0.0, -1.0), (0.0, 3.0), (0.0, -3.0)];
+        let diff = summarize("metric", &pairs);
+        assert_eq!(diff.mean_difference, 0.0);
+        assert!(diff.ci95_low < 0.0 && diff.ci95_high > 0.0);
+        assert!(!diff.is_significant());
+    }
+
+    #[test]
+    fn compare_paired_runs_both_policies_per_seed() {
+        let policy_a = ScenarioParams {
+            num_riders: 10,
+            num_drivers: 3,
+            ..Default::default()
+        };
+        let policy_b = ScenarioParams {
+            num_drivers: 6,
+            ..policy_a.clone()
+        };
+        let comparison = compare_paired(&policy_a, &policy_b, &[1, 2]);
+
+        assert_eq!(comparison.seeds, vec![1, 2]);
+        assert_eq!(comparison.results_a.len(), 2);
+        assert_eq!(comparison.results_b.len(), 2);
+        assert_eq!(comparison.differences.len(), PAIRED_METRICS.len());
+        let trips = comparison.difference("completed_trips").unwrap();
+        assert!((trips.mean_difference - (trips.mean_b - trips.mean_a)).abs() < 1e-9);
+
+        assert_eq!(comparison.results_b[0].tag("policy"), Some("b"));
+        let mut mixed = comparison.results_b.clone();
+        mixed.extend(comparison.results_a.clone());
+        assert_eq!(
+            paired_differences_by_tag(&mixed, "policy", "a", "b"),
+            comparison.differences
+        );
+    }
+}
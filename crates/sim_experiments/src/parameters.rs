@@ -4,14 +4,26 @@
 //! parameter sets for parallel experimentation. Supports grid search and
 //! random sampling strategies.
 
+use std::collections::BTreeMap;
+
+use sim_core::clock::LocalTime;
+use sim_core::pricing::surge::SurgeStrategyKind;
+use sim_core::pricing::vehicle_classes::VehicleClassMenu;
+use sim_core::pricing::CommissionModel;
 use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
 use sim_core::traffic::TrafficProfileKind;
 
 mod combinations;
 mod constraints;
 mod conversion;
+mod geography;
+mod random;
 mod sampling;
 
+pub use constraints::GenerationReport;
+pub use geography::CityGeometry;
+pub use random::{ParameterDistribution, SampledParameter};
+
 #[cfg(test)]
 mod tests;
 
@@ -29,6 +41,11 @@ pub struct ParameterSet {
     pub run_id: usize,
     /// Seed used for this run (ensures reproducibility).
     pub seed: u64,
+    /// Name of the city geometry applied to `params` (see [`ParameterSpace::cities`]).
+    pub geography: Option<String>,
+    /// Free-form key/value labels (e.g. `policy=baseline`), copied onto the run's
+    /// [`SimulationResult::tags`](crate::metrics::SimulationResult::tags) and every export row.
+    pub tags: BTreeMap<String, String>,
 }
 
 impl ParameterSet {
@@ -39,9 +56,23 @@ impl ParameterSet {
             experiment_id,
             run_id,
             seed,
+            geography: None,
+            tags: BTreeMap::new(),
         }
     }
 
+    /// Label this set with the city geometry its params were built for.
+    pub fn with_geography(mut self, name: impl Into<String>) -> Self {
+        self.geography = Some(name.into());
+        self
+    }
+
+    /// Attach a tag, replacing any earlier value for `key`.
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
     /// Get the scenario params with seed applied.
     pub fn scenario_params(&self) -> ScenarioParams {
         let mut params = self.params.clone();
@@ -52,13 +83,16 @@ impl ParameterSet {
 
 /// Defines a parameter space for exploration.
 ///
-/// Supports grid search (Cartesian product) and random sampling strategies.
+/// Supports grid search (Cartesian product) and random sampling strategies
+/// ([`ParameterSpace::random`] with per-dimension distributions, or [`ParameterSpace::sample_random`]).
 #[derive(Debug, Clone)]
 pub struct ParameterSpace {
     /// Base parameters (used as defaults for unspecified parameters).
     pub(super) base: ScenarioParams,
     /// Commission rates to explore.
     pub(super) commission_rates: Vec<f64>,
+    /// Commission models to explore.
+    pub(super) commission_models: Vec<CommissionModel>,
     /// Base fares to explore.
     pub(super) base_fares: Vec<f64>,
     /// Per-km rates to explore.
@@ -69,6 +103,14 @@ pub struct ParameterSpace {
     pub(super) surge_radius_k: Vec<u32>,
     /// Surge max multipliers to explore.
     pub(super) surge_max_multipliers: Vec<f64>,
+    /// Surge strategies to explore.
+    pub(super) surge_strategies: Vec<SurgeStrategyKind>,
+    /// Tax rates (fraction of fare withheld as tax) to explore.
+    pub(super) tax_rates: Vec<f64>,
+    /// Minimum hourly driver earnings floors to explore (0.0 = no floor).
+    pub(super) min_hourly_earnings: Vec<f64>,
+    /// Vehicle class menus to explore (None = single quote, no comparison shopping).
+    pub(super) vehicle_class_menus: Vec<Option<VehicleClassMenu>>,
     /// Number of riders to explore.
     pub(super) num_riders: Vec<usize>,
     /// Number of drivers to explore.
@@ -77,6 +119,8 @@ pub struct ParameterSpace {
     pub(super) match_radii: Vec<u32>,
     /// Epoch values (start datetime in ms) to explore.
     pub(super) epoch_ms: Vec<Option<i64>>,
+    /// City wall clocks (time zones) to explore.
+    pub(super) local_times: Vec<LocalTime>,
     /// Simulation duration in hours to explore.
     pub(super) simulation_duration_hours: Vec<Option<u64>>,
     /// Matching algorithm types to explore.
@@ -93,6 +137,14 @@ pub struct ParameterSpace {
     pub(super) dynamic_congestion_enabled: Vec<bool>,
     /// Base speed (km/h) values to explore.
     pub(super) base_speed_kmh: Vec<Option<f64>>,
+    /// Per-parameter distributions for random sampling mode.
+    pub(super) distributions: Vec<(SampledParameter, ParameterDistribution)>,
+    /// Set count and seed when created with [`ParameterSpace::random`]; None for grid search.
+    random_sampling: Option<random::RandomSampling>,
+    /// City geometries to compare (empty = the base geometry only).
+    pub(super) cities: Vec<CityGeometry>,
+    /// Predicates every generated scenario must satisfy (see [`ParameterSpace::filter`]).
+    filters: Vec<constraints::ParameterFilter>,
 }
 
 impl ParameterSpace {
@@ -101,15 +153,21 @@ impl ParameterSpace {
         Self {
             base: ScenarioParams::default(),
             commission_rates: vec![],
+            commission_models: vec![],
             base_fares: vec![],
             per_km_rates: vec![],
             surge_enabled: vec![],
             surge_radius_k: vec![],
             surge_max_multipliers: vec![],
+            surge_strategies: vec![],
+            tax_rates: vec![],
+            min_hourly_earnings: vec![],
+            vehicle_class_menus: vec![],
             num_riders: vec![],
             num_drivers: vec![],
             match_radii: vec![],
             epoch_ms: vec![],
+            local_times: vec![],
             simulation_duration_hours: vec![],
             matching_algorithm_types: vec![],
             batch_matching_enabled: vec![],
@@ -118,6 +176,10 @@ impl ParameterSpace {
             traffic_profiles: vec![],
             dynamic_congestion_enabled: vec![],
             base_speed_kmh: vec![],
+            distributions: vec![],
+            random_sampling: None,
+            cities: vec![],
+            filters: vec![],
         }
     }
 
@@ -132,6 +194,12 @@ impl ParameterSpace {
         self
     }
 
+    /// Set commission models to explore (`commission_rate` is their flat / default rate).
+    pub fn commission_model(mut self, models: Vec<CommissionModel>) -> Self {
+        self.commission_models = models;
+        self
+    }
+
     /// Set base fares to explore.
     pub fn base_fare(mut self, fares: Vec<f64>) -> Self {
         self.base_fares = fares;
@@ -162,6 +230,30 @@ impl ParameterSpace {
         self
     }
 
+    /// Set surge strategies to explore.
+    pub fn surge_strategy(mut self, strategies: Vec<SurgeStrategyKind>) -> Self {
+        self.surge_strategies = strategies;
+        self
+    }
+
+    /// Set tax rates to explore (e.g. to compare cities with different tax regimes).
+    pub fn tax_rate(mut self, rates: Vec<f64>) -> Self {
+        self.tax_rates = rates;
+        self
+    }
+
+    /// Set minimum hourly driver earnings floors to explore (0.0 disables the floor).
+    pub fn min_hourly_earnings(mut self, floors: Vec<f64>) -> Self {
+        self.min_hourly_earnings = floors;
+        self
+    }
+
+    /// Set vehicle class menus to explore (e.g. with and without a wait-and-save class).
+    pub fn vehicle_classes(mut self, menus: Vec<Option<VehicleClassMenu>>) -> Self {
+        self.vehicle_class_menus = menus;
+        self
+    }
+
     /// Set number of riders to explore.
     pub fn num_riders(mut self, counts: Vec<usize>) -> Self {
         self.num_riders = counts;
@@ -186,6 +278,14 @@ impl ParameterSpace {
         self
     }
 
+    /// Set city wall clocks to explore, e.g. `LocalTime::zone(CityTimeZone::berlin())` with
+    /// the `time-zones` feature. Time-of-day patterns, traffic and scheduled surge follow
+    /// each zone's local hour.
+    pub fn local_time(mut self, local_times: Vec<LocalTime>) -> Self {
+        self.local_times = local_times;
+        self
+    }
+
     /// Set simulation duration (in hours) values to explore.
     pub fn simulation_duration_hours(mut self, durations: Vec<Option<u64>>) -> Self {
         self.simulation_duration_hours = durations;
@@ -234,29 +334,92 @@ impl ParameterSpace {
         self
     }
 
+    /// Set city geometries to explore: every combination runs once per city, with the city's
+    /// bounds, spawn weighting and zone maps replacing the base geometry.
+    pub fn cities(mut self, cities: Vec<CityGeometry>) -> Self {
+        self.cities = cities;
+        self
+    }
+
     /// Set base parameters (used as defaults).
     pub fn with_base(mut self, base: ScenarioParams) -> Self {
         self.base = base;
         self
     }
 
+    /// Grid search over a built-in scenario preset (see `ScenarioParams::preset`).
+    /// Returns None for unknown preset names.
+    pub fn from_preset(name: &str) -> Option<Self> {
+        ScenarioParams::preset(name).map(|base| Self::new().with_base(base))
+    }
+
+    /// Drop generated scenarios for which `predicate` returns false (e.g. surge settings
+    /// varied while surge is disabled, or fewer riders than drivers). Filters apply to both
+    /// grid search and random sampling; all registered filters must pass.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&ScenarioParams) -> bool + Send + Sync + 'static,
+    {
+        self.filters
+            .push(constraints::ParameterFilter::new(predicate));
+        self
+    }
+
+    pub(super) fn passes_filters(&self, params: &ScenarioParams) -> bool {
+        self.filters.iter().all(|filter| filter.keeps(params))
+    }
+
     /// Generate all parameter sets using grid search (Cartesian product).
     ///
     /// Each combination of specified parameters will be generated.
     /// Parameters not specified will use values from the base configuration.
-    /// Invalid combinations (e.g., Hungarian matching without batch matching) are filtered out.
+    /// Invalid combinations (e.g., Hungarian matching without batch matching) and combinations
+    /// rejected by [`ParameterSpace::filter`] are dropped.
+    /// Spaces created with [`ParameterSpace::random`] sample their configured count instead.
     pub fn generate(&self) -> Vec<ParameterSet> {
+        self.generate_with_report().0
+    }
+
+    /// Like [`ParameterSpace::generate`], also reporting how many combinations were dropped.
+    pub fn generate_with_report(&self) -> (Vec<ParameterSet>, GenerationReport) {
+        if let Some(sampling) = self.random_sampling {
+            return self.generate_random(sampling);
+        }
+
         let variations = combinations::ParameterVariations::from_space(self);
+        let combinations = variations.generate_combinations();
+        let mut report = GenerationReport::default();
+        let mut parameter_sets = Vec::new();
+
+        let cities: Vec<Option<&CityGeometry>> = if self.cities.is_empty() {
+            vec![None]
+        } else {
+            self.cities.iter().map(Some).collect()
+        };
+        for city in cities {
+            for combo in combinations.iter().cloned() {
+                if !constraints::is_valid_combination(&combo) {
+                    report.dropped_invalid += 1;
+                    continue;
+                }
+                let mut params = conversion::combination_to_params(&self.base, combo);
+                if let Some(city) = city {
+                    city.apply(&mut params);
+                }
+                if !self.passes_filters(&params) {
+                    report.dropped_by_filter += 1;
+                    continue;
+                }
+                let parameter_set = conversion::grid_parameter_set(params, parameter_sets.len());
+                parameter_sets.push(match city {
+                    Some(city) => parameter_set.with_geography(&city.name),
+                    None => parameter_set,
+                });
+            }
+        }
 
-        variations
-            .generate_combinations()
-            .into_iter()
-            .filter(constraints::is_valid_combination)
-            .enumerate()
-            .map(|(experiment_id, combo)| {
-                conversion::combination_to_parameter_set(&self.base, combo, experiment_id)
-            })
-            .collect()
+        report.generated = parameter_sets.len();
+        (parameter_sets, report)
     }
 }
 
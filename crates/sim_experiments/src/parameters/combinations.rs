@@ -1,19 +1,29 @@
 use super::ParameterSpace;
+use sim_core::clock::LocalTime;
+use sim_core::pricing::surge::SurgeStrategyKind;
+use sim_core::pricing::vehicle_classes::VehicleClassMenu;
+use sim_core::pricing::CommissionModel;
 use sim_core::scenario::MatchingAlgorithmType;
 
 /// Represents a single parameter combination.
 #[derive(Debug, Clone)]
 pub(super) struct ParameterCombination {
     pub(super) commission_rate: f64,
+    pub(super) commission_model: CommissionModel,
     pub(super) base_fare: f64,
     pub(super) per_km_rate: f64,
     pub(super) surge_enabled: bool,
     pub(super) surge_radius_k: u32,
     pub(super) surge_max_multiplier: f64,
+    pub(super) surge_strategy: SurgeStrategyKind,
+    pub(super) tax_rate: f64,
+    pub(super) min_hourly_earnings: f64,
+    pub(super) vehicle_classes: Option<VehicleClassMenu>,
     pub(super) num_riders: usize,
     pub(super) num_drivers: usize,
     pub(super) match_radius: u32,
     pub(super) epoch_ms: Option<i64>,
+    pub(super) local_time: LocalTime,
     pub(super) simulation_duration_hours: Option<u64>,
     pub(super) matching_algorithm_type: MatchingAlgorithmType,
     pub(super) batch_matching_enabled: bool,
@@ -25,15 +35,21 @@ pub(super) struct ParameterCombination {
 #[derive(Debug, Clone, Default)]
 struct PartialCombination {
     commission_rate: Option<f64>,
+    commission_model: Option<CommissionModel>,
     base_fare: Option<f64>,
     per_km_rate: Option<f64>,
     surge_enabled: Option<bool>,
     surge_radius_k: Option<u32>,
     surge_max_multiplier: Option<f64>,
+    surge_strategy: Option<SurgeStrategyKind>,
+    tax_rate: Option<f64>,
+    min_hourly_earnings: Option<f64>,
+    vehicle_classes: Option<Option<VehicleClassMenu>>,
     num_riders: Option<usize>,
     num_drivers: Option<usize>,
     match_radius: Option<u32>,
     epoch_ms: Option<Option<i64>>,
+    local_time: Option<LocalTime>,
     simulation_duration_hours: Option<Option<u64>>,
     matching_algorithm_type: Option<MatchingAlgorithmType>,
     batch_matching_enabled: Option<bool>,
@@ -46,6 +62,11 @@ impl PartialCombination {
         self
     }
 
+    fn with_commission_model(mut self, value: CommissionModel) -> Self {
+        self.commission_model = Some(value);
+        self
+    }
+
     fn with_base_fare(mut self, value: f64) -> Self {
         self.base_fare = Some(value);
         self
@@ -71,6 +92,26 @@ impl PartialCombination {
         self
     }
 
+    fn with_surge_strategy(mut self, value: SurgeStrategyKind) -> Self {
+        self.surge_strategy = Some(value);
+        self
+    }
+
+    fn with_tax_rate(mut self, value: f64) -> Self {
+        self.tax_rate = Some(value);
+        self
+    }
+
+    fn with_min_hourly_earnings(mut self, value: f64) -> Self {
+        self.min_hourly_earnings = Some(value);
+        self
+    }
+
+    fn with_vehicle_classes(mut self, value: Option<VehicleClassMenu>) -> Self {
+        self.vehicle_classes = Some(value);
+        self
+    }
+
     fn with_num_riders(mut self, value: usize) -> Self {
         self.num_riders = Some(value);
         self
@@ -91,6 +132,11 @@ impl PartialCombination {
         self
     }
 
+    fn with_local_time(mut self, value: LocalTime) -> Self {
+        self.local_time = Some(value);
+        self
+    }
+
     fn with_simulation_duration_hours(mut self, value: Option<u64>) -> Self {
         self.simulation_duration_hours = Some(value);
         self
@@ -114,15 +160,21 @@ impl PartialCombination {
     fn into_combination(self, eta_weight: f64) -> ParameterCombination {
         ParameterCombination {
             commission_rate: self.commission_rate.unwrap(),
+            commission_model: self.commission_model.unwrap(),
             base_fare: self.base_fare.unwrap(),
             per_km_rate: self.per_km_rate.unwrap(),
             surge_enabled: self.surge_enabled.unwrap(),
             surge_radius_k: self.surge_radius_k.unwrap(),
             surge_max_multiplier: self.surge_max_multiplier.unwrap(),
+            surge_strategy: self.surge_strategy.unwrap(),
+            tax_rate: self.tax_rate.unwrap(),
+            min_hourly_earnings: self.min_hourly_earnings.unwrap(),
+            vehicle_classes: self.vehicle_classes.unwrap(),
             num_riders: self.num_riders.unwrap(),
             num_drivers: self.num_drivers.unwrap(),
             match_radius: self.match_radius.unwrap(),
             epoch_ms: self.epoch_ms.unwrap(),
+            local_time: self.local_time.unwrap(),
             simulation_duration_hours: self.simulation_duration_hours.unwrap(),
             matching_algorithm_type: self.matching_algorithm_type.unwrap(),
             batch_matching_enabled: self.batch_matching_enabled.unwrap(),
@@ -135,15 +187,21 @@ impl PartialCombination {
 /// Holds all parameter variations to explore.
 pub(super) struct ParameterVariations {
     commission_rates: Vec<f64>,
+    commission_models: Vec<CommissionModel>,
     base_fares: Vec<f64>,
     per_km_rates: Vec<f64>,
     surge_enabled: Vec<bool>,
     surge_radius_k: Vec<u32>,
     surge_max_multipliers: Vec<f64>,
+    surge_strategies: Vec<SurgeStrategyKind>,
+    tax_rates: Vec<f64>,
+    min_hourly_earnings: Vec<f64>,
+    vehicle_class_menus: Vec<Option<VehicleClassMenu>>,
     num_riders: Vec<usize>,
     num_drivers: Vec<usize>,
     match_radii: Vec<u32>,
     epoch_ms_values: Vec<Option<i64>>,
+    local_times: Vec<LocalTime>,
     simulation_duration_hours: Vec<Option<u64>>,
     matching_algorithm_types: Vec<MatchingAlgorithmType>,
     batch_matching_enabled: Vec<bool>,
@@ -161,6 +219,13 @@ impl ParameterVariations {
             } else {
                 space.commission_rates.clone()
             },
+            commission_models: if space.commission_models.is_empty() {
+                vec![default_pricing
+                    .map(|p| p.commission_model)
+                    .unwrap_or_default()]
+            } else {
+                space.commission_models.clone()
+            },
             base_fares: if space.base_fares.is_empty() {
                 vec![default_pricing.map(|p| p.base_fare).unwrap_or(2.50)]
             } else {
@@ -188,6 +253,28 @@ impl ParameterVariations {
             } else {
                 space.surge_max_multipliers.clone()
             },
+            surge_strategies: if space.surge_strategies.is_empty() {
+                vec![space.base.surge_strategy.clone()]
+            } else {
+                space.surge_strategies.clone()
+            },
+            tax_rates: if space.tax_rates.is_empty() {
+                vec![default_pricing.map(|p| p.tax_rate).unwrap_or(0.0)]
+            } else {
+                space.tax_rates.clone()
+            },
+            min_hourly_earnings: if space.min_hourly_earnings.is_empty() {
+                vec![default_pricing
+                    .map(|p| p.min_hourly_earnings)
+                    .unwrap_or(0.0)]
+            } else {
+                space.min_hourly_earnings.clone()
+            },
+            vehicle_class_menus: if space.vehicle_class_menus.is_empty() {
+                vec![space.base.vehicle_classes.clone()]
+            } else {
+                space.vehicle_class_menus.clone()
+            },
             num_riders: if space.num_riders.is_empty() {
                 vec![space.base.num_riders]
             } else {
@@ -208,6 +295,11 @@ impl ParameterVariations {
             } else {
                 space.epoch_ms.clone()
             },
+            local_times: if space.local_times.is_empty() {
+                vec![space.base.local_time]
+            } else {
+                space.local_times.clone()
+            },
             simulation_duration_hours: if space.simulation_duration_hours.is_empty() {
                 vec![None]
             } else {
@@ -255,6 +347,16 @@ impl ParameterVariations {
             })
             .collect();
 
+        partial = self
+            .commission_models
+            .iter()
+            .flat_map(|&model| {
+                partial
+                    .iter()
+                    .map(move |p| p.clone().with_commission_model(model))
+            })
+            .collect();
+
         partial = self
             .base_fares
             .iter()
@@ -301,6 +403,42 @@ impl ParameterVariations {
             })
             .collect();
 
+        partial = self
+            .surge_strategies
+            .iter()
+            .flat_map(|strategy| {
+                partial
+                    .iter()
+                    .map(move |p| p.clone().with_surge_strategy(strategy.clone()))
+            })
+            .collect();
+
+        partial = self
+            .tax_rates
+            .iter()
+            .flat_map(|&rate| partial.iter().map(move |p| p.clone().with_tax_rate(rate)))
+            .collect();
+
+        partial = self
+            .min_hourly_earnings
+            .iter()
+            .flat_map(|&floor| {
+                partial
+                    .iter()
+                    .map(move |p| p.clone().with_min_hourly_earnings(floor))
+            })
+            .collect();
+
+        partial = self
+            .vehicle_class_menus
+            .iter()
+            .flat_map(|menu| {
+                partial
+                    .iter()
+                    .map(move |p| p.clone().with_vehicle_classes(menu.clone()))
+            })
+            .collect();
+
         partial = self
             .num_riders
             .iter()
@@ -337,6 +475,16 @@ impl ParameterVariations {
             .flat_map(|&epoch| partial.iter().map(move |p| p.clone().with_epoch_ms(epoch)))
             .collect();
 
+        partial = self
+            .local_times
+            .iter()
+            .flat_map(|&local_time| {
+                partial
+                    .iter()
+                    .map(move |p| p.clone().with_local_time(local_time))
+            })
+            .collect();
+
         partial = self
             .simulation_duration_hours
             .iter()
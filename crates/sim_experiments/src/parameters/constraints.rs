@@ -1,5 +1,8 @@
+use std::fmt;
+use std::sync::Arc;
+
 use super::combinations::ParameterCombination;
-use sim_core::scenario::MatchingAlgorithmType;
+use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
 
 pub(super) fn is_valid_matching_config(
     matching_algorithm_type: MatchingAlgorithmType,
@@ -12,3 +15,44 @@ pub(super) fn is_valid_matching_config(
 pub(super) fn is_valid_combination(combo: &ParameterCombination) -> bool {
     is_valid_matching_config(combo.matching_algorithm_type, combo.batch_matching_enabled)
 }
+
+/// User predicate registered with `ParameterSpace::filter`; false drops the scenario.
+#[derive(Clone)]
+pub(super) struct ParameterFilter(Arc<dyn Fn(&ScenarioParams) -> bool + Send + Sync>);
+
+impl ParameterFilter {
+    pub(super) fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&ScenarioParams) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    pub(super) fn keeps(&self, params: &ScenarioParams) -> bool {
+        (self.0)(params)
+    }
+}
+
+impl fmt::Debug for ParameterFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ParameterFilter(..)")
+    }
+}
+
+/// How many candidate combinations generation kept and dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GenerationReport {
+    /// Parameter sets returned.
+    pub generated: usize,
+    /// Dropped by built-in validity rules (e.g. Hungarian matching without batch matching).
+    pub dropped_invalid: usize,
+    /// Dropped by `ParameterSpace::filter` predicates.
+    pub dropped_by_filter: usize,
+}
+
+impl GenerationReport {
+    /// Candidate combinations considered (kept + dropped).
+    pub fn candidates(&self) -> usize {
+        self.generated + self.dropped_invalid + self.dropped_by_filter
+    }
+}
@@ -3,16 +3,16 @@ use super::ParameterSet;
 use sim_core::pricing::PricingConfig;
 use sim_core::scenario::ScenarioParams;
 
-pub(super) fn combination_to_parameter_set(
+pub(super) fn combination_to_params(
     base: &ScenarioParams,
     combo: ParameterCombination,
-    experiment_id: usize,
-) -> ParameterSet {
+) -> ScenarioParams {
     let mut params = base.clone();
     params.num_riders = combo.num_riders;
     params.num_drivers = combo.num_drivers;
     params.match_radius = combo.match_radius;
     params.epoch_ms = combo.epoch_ms;
+    params.local_time = combo.local_time;
 
     if let Some(duration_hours) = combo.simulation_duration_hours {
         let request_window_ms = params.request_window_ms;
@@ -24,16 +24,25 @@ pub(super) fn combination_to_parameter_set(
         base_fare: combo.base_fare,
         per_km_rate: combo.per_km_rate,
         commission_rate: combo.commission_rate,
+        commission_model: combo.commission_model,
         surge_enabled: combo.surge_enabled,
         surge_radius_k: combo.surge_radius_k,
         surge_max_multiplier: combo.surge_max_multiplier,
+        tax_rate: combo.tax_rate,
+        min_hourly_earnings: combo.min_hourly_earnings,
+        ..base.pricing_config.unwrap_or_default()
     });
 
+    params.surge_strategy = combo.surge_strategy;
+    params.vehicle_classes = combo.vehicle_classes;
     params.matching_algorithm_type = Some(combo.matching_algorithm_type);
     params.batch_matching_enabled = Some(combo.batch_matching_enabled);
     params.batch_interval_secs = Some(combo.batch_interval_secs);
     params.eta_weight = Some(combo.eta_weight);
+    params
+}
 
+pub(super) fn grid_parameter_set(params: ScenarioParams, experiment_id: usize) -> ParameterSet {
     let seed = (experiment_id as u64).wrapping_mul(0x9e3779b9);
 
     ParameterSet::new(params, format!("exp_{experiment_id}"), 0, seed)
@@ -0,0 +1,116 @@
+use sim_core::pricing::tolls::TollZones;
+use sim_core::pricing::{CommissionZones, TaxZones};
+use sim_core::scenario::ScenarioParams;
+use sim_core::spawner::{OdMatrix, SpawnWeightingKind};
+
+/// Spatial configuration of one city, swept as a dimension so a single sweep compares the
+/// same policies across several geometries (see `ParameterSpace::cities`).
+///
+/// Applying a geometry replaces every spatial field of the scenario; non-spatial settings
+/// (fleet sizes, pricing, matching) still come from the space.
+#[derive(Debug, Clone)]
+pub struct CityGeometry {
+    /// Label recorded on each generated `ParameterSet` (and exported as `geography`).
+    pub name: String,
+    pub lat_min: f64,
+    pub lat_max: f64,
+    pub lng_min: f64,
+    pub lng_max: f64,
+    pub spawn_weighting: SpawnWeightingKind,
+    pub congestion_zones_enabled: bool,
+    pub commission_zones: Option<CommissionZones>,
+    pub tax_zones: Option<TaxZones>,
+    pub toll_zones: Option<TollZones>,
+    pub od_matrix: Option<OdMatrix>,
+}
+
+impl CityGeometry {
+    /// City with the given bounds, uniform spawning and no zone maps.
+    pub fn new(
+        name: impl Into<String>,
+        lat_min: f64,
+        lat_max: f64,
+        lng_min: f64,
+        lng_max: f64,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            lat_min,
+            lat_max,
+            lng_min,
+            lng_max,
+            spawn_weighting: SpawnWeightingKind::default(),
+            congestion_zones_enabled: false,
+            commission_zones: None,
+            tax_zones: None,
+            toll_zones: None,
+            od_matrix: None,
+        }
+    }
+
+    /// Capture the spatial configuration of an existing scenario (e.g. a preset).
+    pub fn from_params(name: impl Into<String>, params: &ScenarioParams) -> Self {
+        Self {
+            name: name.into(),
+            lat_min: params.lat_min,
+            lat_max: params.lat_max,
+            lng_min: params.lng_min,
+            lng_max: params.lng_max,
+            spawn_weighting: params.spawn_weighting.clone(),
+            congestion_zones_enabled: params.congestion_zones_enabled,
+            commission_zones: params.commission_zones.clone(),
+            tax_zones: params.tax_zones.clone(),
+            toll_zones: params.toll_zones.clone(),
+            od_matrix: params.od_matrix.clone(),
+        }
+    }
+
+    /// Spawn riders/drivers from weighted hotspots in a JSON or CSV file.
+    pub fn with_hotspot_file(mut self, path: impl Into<String>) -> Self {
+        self.spawn_weighting = SpawnWeightingKind::HotspotFile(path.into());
+        self
+    }
+
+    pub fn with_spawn_weighting(mut self, spawn_weighting: SpawnWeightingKind) -> Self {
+        self.spawn_weighting = spawn_weighting;
+        self
+    }
+
+    pub fn with_congestion_zones(mut self, enabled: bool) -> Self {
+        self.congestion_zones_enabled = enabled;
+        self
+    }
+
+    pub fn with_commission_zones(mut self, commission_zones: CommissionZones) -> Self {
+        self.commission_zones = Some(commission_zones);
+        self
+    }
+
+    pub fn with_tax_zones(mut self, tax_zones: TaxZones) -> Self {
+        self.tax_zones = Some(tax_zones);
+        self
+    }
+
+    pub fn with_toll_zones(mut self, toll_zones: TollZones) -> Self {
+        self.toll_zones = Some(toll_zones);
+        self
+    }
+
+    pub fn with_od_matrix(mut self, od_matrix: OdMatrix) -> Self {
+        self.od_matrix = Some(od_matrix);
+        self
+    }
+
+    pub(super) fn apply(&self, params: &mut ScenarioParams) {
+        params.lat_min = self.lat_min;
+        params.lat_max = self.lat_max;
+        params.lng_min = self.lng_min;
+        params.lng_max = self.lng_max;
+        params.spawn_weighting = self.spawn_weighting.clone();
+        params.congestion_zones_enabled = self.congestion_zones_enabled;
+        params.commission_zones = self.commission_zones.clone();
+        params.tax_zones = self.tax_zones.clone();
+        params.toll_zones = self.toll_zones.clone();
+        params.od_matrix = self.od_matrix.clone();
+    }
+}
@@ -0,0 +1,171 @@
+use super::constraints::is_valid_matching_config;
+use super::{GenerationReport, ParameterSet, ParameterSpace};
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
+
+/// Numeric scenario parameters that can be sampled from a [`ParameterDistribution`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampledParameter {
+    CommissionRate,
+    BaseFare,
+    PerKmRate,
+    SurgeMaxMultiplier,
+    TaxRate,
+    MinHourlyEarnings,
+    EtaWeight,
+    BaseSpeedKmh,
+    /// Rounded to the nearest whole rider.
+    NumRiders,
+    /// Rounded to the nearest whole driver.
+    NumDrivers,
+    /// Rounded to the nearest whole H3 cell.
+    MatchRadius,
+    /// Rounded to the nearest whole second.
+    BatchIntervalSecs,
+}
+
+/// Distribution one [`SampledParameter`] is drawn from in random sampling mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterDistribution {
+    /// Uniform over `[min, max]`.
+    Uniform { min: f64, max: f64 },
+    /// Uniform in log space over `[min, max]` (both > 0); suits scale parameters such as
+    /// fleet sizes where 100 → 200 matters as much as 1000 → 2000.
+    LogUniform { min: f64, max: f64 },
+    /// One of the listed values, each equally likely.
+    Choice(Vec<f64>),
+}
+
+impl ParameterDistribution {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match self {
+            ParameterDistribution::Uniform { min, max } => {
+                if max > min {
+                    rng.gen_range(*min..=*max)
+                } else {
+                    *min
+                }
+            }
+            ParameterDistribution::LogUniform { min, max } => {
+                let (low, high) = (min.max(f64::MIN_POSITIVE).ln(), max.max(*min).ln());
+                if high > low {
+                    rng.gen_range(low..=high).exp()
+                } else {
+                    *min
+                }
+            }
+            ParameterDistribution::Choice(values) => {
+                if values.is_empty() {
+                    0.0
+                } else {
+                    values[rng.gen_range(0..values.len())]
+                }
+            }
+        }
+    }
+}
+
+/// Number of parameter sets and RNG seed for random sampling mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RandomSampling {
+    pub(super) count: usize,
+    pub(super) seed: u64,
+}
+
+impl ParameterSpace {
+    /// Create a parameter space that samples `count` parameter sets instead of enumerating
+    /// the grid. Dimensions with a [`ParameterDistribution`] (see [`Self::distribution`]) are
+    /// drawn from it; list-valued dimensions pick one of their values uniformly. The same
+    /// `seed` always yields the same sets.
+    pub fn random(count: usize, seed: u64) -> Self {
+        let mut space = Self::new();
+        space.random_sampling = Some(RandomSampling { count, seed });
+        space
+    }
+
+    /// Sample `parameter` from `distribution` in random sampling mode (replaces any earlier
+    /// distribution for the same parameter). Ignored by grid search.
+    pub fn distribution(
+        mut self,
+        parameter: SampledParameter,
+        distribution: ParameterDistribution,
+    ) -> Self {
+        self.distributions
+            .retain(|(existing, _)| *existing != parameter);
+        self.distributions.push((parameter, distribution));
+        self
+    }
+
+    pub(super) fn generate_random(
+        &self,
+        sampling: RandomSampling,
+    ) -> (Vec<ParameterSet>, GenerationReport) {
+        const MAX_ATTEMPTS_PER_SET: usize = 100;
+
+        let mut rng = StdRng::seed_from_u64(sampling.seed);
+        let mut parameter_sets = Vec::with_capacity(sampling.count);
+        let mut report = GenerationReport::default();
+        let mut attempts = 0;
+
+        while parameter_sets.len() < sampling.count
+            && attempts < sampling.count.saturating_mul(MAX_ATTEMPTS_PER_SET)
+        {
+            attempts += 1;
+            let (mut params, city) = self.draw_from_lists(&mut rng);
+            for (parameter, distribution) in &self.distributions {
+                apply_sample(&mut params, *parameter, distribution.sample(&mut rng));
+            }
+
+            if !is_valid_matching_config(
+                params
+                    .matching_algorithm_type
+                    .unwrap_or(MatchingAlgorithmType::Hungarian),
+                params.batch_matching_enabled.unwrap_or(true),
+            ) {
+                report.dropped_invalid += 1;
+                continue;
+            }
+            if !self.passes_filters(&params) {
+                report.dropped_by_filter += 1;
+                continue;
+            }
+
+            let experiment_id = parameter_sets.len();
+            let seed = sampling
+                .seed
+                .wrapping_add(experiment_id as u64)
+                .wrapping_mul(0x9e3779b9);
+            let parameter_set =
+                ParameterSet::new(params, format!("random_{experiment_id}"), 0, seed);
+            parameter_sets.push(match city {
+                Some(city) => parameter_set.with_geography(city),
+                None => parameter_set,
+            });
+        }
+
+        report.generated = parameter_sets.len();
+        (parameter_sets, report)
+    }
+}
+
+fn apply_sample(params: &mut ScenarioParams, parameter: SampledParameter, value: f64) {
+    let rounded = value.round().max(0.0);
+    // draw_from_lists always sets a pricing config
+    let pricing = params.pricing_config.get_or_insert_with(Default::default);
+    match parameter {
+        SampledParameter::CommissionRate => pricing.commission_rate = value,
+        SampledParameter::BaseFare => pricing.base_fare = value,
+        SampledParameter::PerKmRate => pricing.per_km_rate = value,
+        SampledParameter::SurgeMaxMultiplier => pricing.surge_max_multiplier = value,
+        SampledParameter::TaxRate => pricing.tax_rate = value,
+        SampledParameter::MinHourlyEarnings => pricing.min_hourly_earnings = value,
+        SampledParameter::EtaWeight => params.eta_weight = Some(value),
+        SampledParameter::BaseSpeedKmh => params.base_speed_kmh = Some(value),
+        SampledParameter::NumRiders => params.num_riders = rounded as usize,
+        SampledParameter::NumDrivers => params.num_drivers = rounded as usize,
+        SampledParameter::MatchRadius => params.match_radius = rounded as u32,
+        SampledParameter::BatchIntervalSecs => params.batch_interval_secs = Some(rounded as u64),
+    }
+}
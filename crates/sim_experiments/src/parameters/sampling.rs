@@ -4,7 +4,7 @@ use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
 use sim_core::pricing::PricingConfig;
-use sim_core::scenario::MatchingAlgorithmType;
+use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
 use std::collections::HashSet;
 
 impl ParameterSpace {
@@ -22,147 +22,7 @@ impl ParameterSpace {
 
         while parameter_sets.len() < count && attempts < MAX_ATTEMPTS {
             attempts += 1;
-            let mut params = self.base.clone();
-
-            let commission_rate = if !self.commission_rates.is_empty() {
-                self.commission_rates[rng.gen_range(0..self.commission_rates.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.commission_rate)
-                    .unwrap_or(0.0)
-            };
-
-            let base_fare = if !self.base_fares.is_empty() {
-                self.base_fares[rng.gen_range(0..self.base_fares.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.base_fare)
-                    .unwrap_or(2.50)
-            };
-
-            let per_km_rate = if !self.per_km_rates.is_empty() {
-                self.per_km_rates[rng.gen_range(0..self.per_km_rates.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.per_km_rate)
-                    .unwrap_or(1.50)
-            };
-
-            let surge_enabled = if !self.surge_enabled.is_empty() {
-                self.surge_enabled[rng.gen_range(0..self.surge_enabled.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.surge_enabled)
-                    .unwrap_or(false)
-            };
-
-            let surge_radius_k = if !self.surge_radius_k.is_empty() {
-                self.surge_radius_k[rng.gen_range(0..self.surge_radius_k.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.surge_radius_k)
-                    .unwrap_or(1)
-            };
-
-            let surge_max_multiplier = if !self.surge_max_multipliers.is_empty() {
-                self.surge_max_multipliers[rng.gen_range(0..self.surge_max_multipliers.len())]
-            } else {
-                self.base
-                    .pricing_config
-                    .as_ref()
-                    .map(|p| p.surge_max_multiplier)
-                    .unwrap_or(2.0)
-            };
-
-            params.num_riders = if !self.num_riders.is_empty() {
-                self.num_riders[rng.gen_range(0..self.num_riders.len())]
-            } else {
-                self.base.num_riders
-            };
-
-            params.num_drivers = if !self.num_drivers.is_empty() {
-                self.num_drivers[rng.gen_range(0..self.num_drivers.len())]
-            } else {
-                self.base.num_drivers
-            };
-
-            params.match_radius = if !self.match_radii.is_empty() {
-                self.match_radii[rng.gen_range(0..self.match_radii.len())]
-            } else {
-                self.base.match_radius
-            };
-
-            params.epoch_ms = if !self.epoch_ms.is_empty() {
-                self.epoch_ms[rng.gen_range(0..self.epoch_ms.len())]
-            } else {
-                self.base.epoch_ms
-            };
-
-            let simulation_duration_hours = if !self.simulation_duration_hours.is_empty() {
-                self.simulation_duration_hours
-                    [rng.gen_range(0..self.simulation_duration_hours.len())]
-            } else {
-                None
-            };
-
-            if let Some(duration_hours) = simulation_duration_hours {
-                let request_window_ms = params.request_window_ms;
-                let end_time_ms = request_window_ms.saturating_add(duration_hours * 60 * 60 * 1000);
-                params.simulation_end_time_ms = Some(end_time_ms);
-            }
-
-            params.matching_algorithm_type = if !self.matching_algorithm_types.is_empty() {
-                Some(
-                    self.matching_algorithm_types
-                        [rng.gen_range(0..self.matching_algorithm_types.len())],
-                )
-            } else {
-                self.base
-                    .matching_algorithm_type
-                    .or(Some(MatchingAlgorithmType::Hungarian))
-            };
-
-            params.batch_matching_enabled = if !self.batch_matching_enabled.is_empty() {
-                Some(
-                    self.batch_matching_enabled
-                        [rng.gen_range(0..self.batch_matching_enabled.len())],
-                )
-            } else {
-                self.base.batch_matching_enabled.or(Some(true))
-            };
-
-            params.batch_interval_secs = if !self.batch_interval_secs.is_empty() {
-                Some(self.batch_interval_secs[rng.gen_range(0..self.batch_interval_secs.len())])
-            } else {
-                self.base.batch_interval_secs.or(Some(5))
-            };
-
-            params.eta_weight = if !self.eta_weights.is_empty() {
-                Some(self.eta_weights[rng.gen_range(0..self.eta_weights.len())])
-            } else {
-                self.base
-                    .eta_weight
-                    .or(Some(sim_core::matching::DEFAULT_ETA_WEIGHT))
-            };
-
-            params.pricing_config = Some(PricingConfig {
-                base_fare,
-                per_km_rate,
-                commission_rate,
-                surge_enabled,
-                surge_radius_k,
-                surge_max_multiplier,
-            });
+            let (params, city) = self.draw_from_lists(&mut rng);
 
             if !is_valid_matching_config(
                 params
@@ -183,14 +43,222 @@ impl ParameterSpace {
                 .wrapping_add(parameter_sets.len() as u64)
                 .wrapping_mul(0x9e3779b9);
 
-            parameter_sets.push(ParameterSet::new(
+            let parameter_set = ParameterSet::new(
                 params,
                 format!("random_{}", parameter_sets.len()),
                 0,
                 seed_value,
-            ));
+            );
+            parameter_sets.push(match city {
+                Some(city) => parameter_set.with_geography(city),
+                None => parameter_set,
+            });
         }
 
         parameter_sets
     }
+
+    /// Draw one scenario from the base, picking uniformly among the values of every
+    /// dimension that lists any (dimensions without values keep the base value). Returns the
+    /// drawn city's name when cities are configured.
+    pub(super) fn draw_from_lists(&self, rng: &mut StdRng) -> (ScenarioParams, Option<String>) {
+        let mut params = self.base.clone();
+
+        let commission_rate = if !self.commission_rates.is_empty() {
+            self.commission_rates[rng.gen_range(0..self.commission_rates.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.commission_rate)
+                .unwrap_or(0.0)
+        };
+
+        let base_fare = if !self.base_fares.is_empty() {
+            self.base_fares[rng.gen_range(0..self.base_fares.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.base_fare)
+                .unwrap_or(2.50)
+        };
+
+        let per_km_rate = if !self.per_km_rates.is_empty() {
+            self.per_km_rates[rng.gen_range(0..self.per_km_rates.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.per_km_rate)
+                .unwrap_or(1.50)
+        };
+
+        let surge_enabled = if !self.surge_enabled.is_empty() {
+            self.surge_enabled[rng.gen_range(0..self.surge_enabled.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.surge_enabled)
+                .unwrap_or(false)
+        };
+
+        let surge_radius_k = if !self.surge_radius_k.is_empty() {
+            self.surge_radius_k[rng.gen_range(0..self.surge_radius_k.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.surge_radius_k)
+                .unwrap_or(1)
+        };
+
+        let surge_max_multiplier = if !self.surge_max_multipliers.is_empty() {
+            self.surge_max_multipliers[rng.gen_range(0..self.surge_max_multipliers.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.surge_max_multiplier)
+                .unwrap_or(2.0)
+        };
+
+        let tax_rate = if !self.tax_rates.is_empty() {
+            self.tax_rates[rng.gen_range(0..self.tax_rates.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.tax_rate)
+                .unwrap_or(0.0)
+        };
+
+        let min_hourly_earnings = if !self.min_hourly_earnings.is_empty() {
+            self.min_hourly_earnings[rng.gen_range(0..self.min_hourly_earnings.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.min_hourly_earnings)
+                .unwrap_or(0.0)
+        };
+
+        let commission_model = if !self.commission_models.is_empty() {
+            self.commission_models[rng.gen_range(0..self.commission_models.len())]
+        } else {
+            self.base
+                .pricing_config
+                .as_ref()
+                .map(|p| p.commission_model)
+                .unwrap_or_default()
+        };
+
+        if !self.vehicle_class_menus.is_empty() {
+            params.vehicle_classes =
+                self.vehicle_class_menus[rng.gen_range(0..self.vehicle_class_menus.len())].clone();
+        }
+
+        if !self.surge_strategies.is_empty() {
+            params.surge_strategy =
+                self.surge_strategies[rng.gen_range(0..self.surge_strategies.len())].clone();
+        }
+
+        params.num_riders = if !self.num_riders.is_empty() {
+            self.num_riders[rng.gen_range(0..self.num_riders.len())]
+        } else {
+            self.base.num_riders
+        };
+
+        params.num_drivers = if !self.num_drivers.is_empty() {
+            self.num_drivers[rng.gen_range(0..self.num_drivers.len())]
+        } else {
+            self.base.num_drivers
+        };
+
+        params.match_radius = if !self.match_radii.is_empty() {
+            self.match_radii[rng.gen_range(0..self.match_radii.len())]
+        } else {
+            self.base.match_radius
+        };
+
+        params.epoch_ms = if !self.epoch_ms.is_empty() {
+            self.epoch_ms[rng.gen_range(0..self.epoch_ms.len())]
+        } else {
+            self.base.epoch_ms
+        };
+
+        params.local_time = if !self.local_times.is_empty() {
+            self.local_times[rng.gen_range(0..self.local_times.len())]
+        } else {
+            self.base.local_time
+        };
+
+        let simulation_duration_hours = if !self.simulation_duration_hours.is_empty() {
+            self.simulation_duration_hours[rng.gen_range(0..self.simulation_duration_hours.len())]
+        } else {
+            None
+        };
+
+        if let Some(duration_hours) = simulation_duration_hours {
+            let request_window_ms = params.request_window_ms;
+            let end_time_ms = request_window_ms.saturating_add(duration_hours * 60 * 60 * 1000);
+            params.simulation_end_time_ms = Some(end_time_ms);
+        }
+
+        params.matching_algorithm_type = if !self.matching_algorithm_types.is_empty() {
+            Some(
+                self.matching_algorithm_types
+                    [rng.gen_range(0..self.matching_algorithm_types.len())],
+            )
+        } else {
+            self.base
+                .matching_algorithm_type
+                .or(Some(MatchingAlgorithmType::Hungarian))
+        };
+
+        params.batch_matching_enabled = if !self.batch_matching_enabled.is_empty() {
+            Some(self.batch_matching_enabled[rng.gen_range(0..self.batch_matching_enabled.len())])
+        } else {
+            self.base.batch_matching_enabled.or(Some(true))
+        };
+
+        params.batch_interval_secs = if !self.batch_interval_secs.is_empty() {
+            Some(self.batch_interval_secs[rng.gen_range(0..self.batch_interval_secs.len())])
+        } else {
+            self.base.batch_interval_secs.or(Some(5))
+        };
+
+        params.eta_weight = if !self.eta_weights.is_empty() {
+            Some(self.eta_weights[rng.gen_range(0..self.eta_weights.len())])
+        } else {
+            self.base
+                .eta_weight
+                .or(Some(sim_core::matching::DEFAULT_ETA_WEIGHT))
+        };
+
+        params.pricing_config = Some(PricingConfig {
+            base_fare,
+            per_km_rate,
+            commission_rate,
+            commission_model,
+            surge_enabled,
+            surge_radius_k,
+            surge_max_multiplier,
+            tax_rate,
+            min_hourly_earnings,
+            ..self.base.pricing_config.unwrap_or_default()
+        });
+
+        // Drawn last so spaces without cities keep their sample sequence
+        let city = if self.cities.is_empty() {
+            None
+        } else {
+            let city = &self.cities[rng.gen_range(0..self.cities.len())];
+            city.apply(&mut params);
+            Some(city.name.clone())
+        };
+
+        (params, city)
+    }
 }
@@ -16,6 +16,87 @@ fn test_grid_search_multiple_parameters() {
     assert_eq!(sets.len(), 4);
 }
 
+#[test]
+fn test_grid_search_surge_strategies() {
+    use sim_core::pricing::surge::SurgeStrategyKind;
+
+    let space = ParameterSpace::grid()
+        .surge_enabled(vec![true])
+        .surge_strategy(vec![
+            SurgeStrategyKind::SupplyDemandRatio,
+            SurgeStrategyKind::default_stepwise(),
+            SurgeStrategyKind::default_pid(),
+        ]);
+    let sets = space.generate();
+    assert_eq!(sets.len(), 3);
+    let names: Vec<&str> = sets
+        .iter()
+        .map(|set| set.params.surge_strategy.name())
+        .collect();
+    assert!(names.contains(&"stepwise"));
+    assert!(names.contains(&"pid_target_wait"));
+}
+
+#[test]
+fn test_grid_search_commission_models() {
+    use sim_core::pricing::CommissionModel;
+
+    let space = ParameterSpace::grid()
+        .commission_rate(vec![0.2])
+        .commission_model(vec![
+            CommissionModel::Flat,
+            CommissionModel::FixedFee { fee_per_trip: 2.0 },
+        ]);
+    let sets = space.generate();
+    assert_eq!(sets.len(), 2);
+    let models: Vec<CommissionModel> = sets
+        .iter()
+        .map(|set| set.params.pricing_config.unwrap().commission_model)
+        .collect();
+    assert!(models.contains(&CommissionModel::FixedFee { fee_per_trip: 2.0 }));
+
+    let sampled = ParameterSpace::grid()
+        .commission_model(vec![CommissionModel::PerZone])
+        .sample_random(3, 7);
+    assert!(
+        sampled
+            .iter()
+            .all(|set| set.params.pricing_config.unwrap().commission_model
+                == CommissionModel::PerZone)
+    );
+}
+
+#[test]
+fn test_grid_search_tax_rates() {
+    let space = ParameterSpace::grid()
+        .commission_rate(vec![0.1, 0.2])
+        .tax_rate(vec![0.0, 0.19]);
+    let sets = space.generate();
+    assert_eq!(sets.len(), 4);
+    assert!(sets.iter().any(|set| set
+        .params
+        .pricing_config
+        .is_some_and(|p| (p.tax_rate - 0.19).abs() < 1e-9)));
+}
+
+#[test]
+fn test_grid_search_wait_and_save_menus() {
+    use sim_core::pricing::vehicle_classes::{VehicleClass, VehicleClassMenu};
+
+    let with_wait_and_save = VehicleClassMenu::default()
+        .with_class(VehicleClass::new("standard", 1.0, 1.0))
+        .with_class(VehicleClass::wait_and_save(0.2, 300));
+    let space = ParameterSpace::grid().vehicle_classes(vec![None, Some(with_wait_and_save)]);
+    let sets = space.generate();
+    assert_eq!(sets.len(), 2);
+    assert!(sets.iter().any(|set| set.params.vehicle_classes.is_none()));
+    assert!(sets.iter().any(|set| set
+        .params
+        .vehicle_classes
+        .as_ref()
+        .is_some_and(|menu| menu.classes[1].dispatch_delay_secs == 300)));
+}
+
 #[test]
 fn test_random_sampling() {
     let space = ParameterSpace::grid()
@@ -102,3 +183,146 @@ fn test_invalid_combinations_filtered() {
         }
     }
 }
+
+#[test]
+fn test_preset_space_uses_preset_base() {
+    let space = ParameterSpace::from_preset("small-test")
+        .expect("small-test preset should exist")
+        .commission_rate(vec![0.1, 0.2]);
+    let sets = space.generate();
+    assert_eq!(sets.len(), 2);
+    for set in &sets {
+        assert_eq!(set.params.num_riders, 50);
+        assert_eq!(set.params.num_drivers, 20);
+    }
+    assert!(ParameterSpace::from_preset("no-such-preset").is_none());
+}
+
+#[test]
+fn test_random_space_samples_distributions_reproducibly() {
+    let space = ParameterSpace::random(25, 7)
+        .distribution(
+            SampledParameter::CommissionRate,
+            ParameterDistribution::Uniform {
+                min: 0.05,
+                max: 0.3,
+            },
+        )
+        .distribution(
+            SampledParameter::NumDrivers,
+            ParameterDistribution::LogUniform {
+                min: 50.0,
+                max: 5000.0,
+            },
+        )
+        .distribution(
+            SampledParameter::MatchRadius,
+            ParameterDistribution::Choice(vec![3.0, 5.0]),
+        );
+
+    let sets = space.generate();
+    assert_eq!(sets.len(), 25);
+    for set in &sets {
+        let commission = set.params.pricing_config.unwrap().commission_rate;
+        assert!((0.05..=0.3).contains(&commission));
+        assert!((50..=5000).contains(&set.params.num_drivers));
+        assert!([3, 5].contains(&set.params.match_radius));
+    }
+
+    let again = space.generate();
+    assert!(sets
+        .iter()
+        .zip(&again)
+        .all(|(a, b)| a.params.num_drivers == b.params.num_drivers && a.seed == b.seed));
+}
+
+#[test]
+fn test_filters_prune_combinations_and_report_drops() {
+    let space = ParameterSpace::grid()
+        .surge_enabled(vec![false, true])
+        .surge_max_multiplier(vec![1.5, 2.0, 3.0])
+        .num_riders(vec![50, 500])
+        .num_drivers(vec![100])
+        .filter(|params| {
+            // Varying the surge cap only matters with surge on
+            let pricing = params.pricing_config.unwrap_or_default();
+            pricing.surge_enabled || pricing.surge_max_multiplier == 1.5
+        })
+        .filter(|params| params.num_riders >= params.num_drivers);
+
+    let (sets, report) = space.generate_with_report();
+    // 12 candidates: all 6 with 50 riders and 2 surge-off caps with 500 riders are dropped
+    assert_eq!(report.candidates(), 12);
+    assert_eq!(report.dropped_invalid, 0);
+    assert_eq!(report.dropped_by_filter, 8);
+    assert_eq!(report.generated, 4);
+    assert_eq!(sets.len(), 4);
+    assert_eq!(sets[3].experiment_id, "exp_3");
+}
+
+#[test]
+fn test_cities_multiply_combinations_with_distinct_geometries() {
+    let berlin = CityGeometry::from_params(
+        "berlin",
+        &ScenarioParams::preset("berlin-weekday-rush").expect("preset exists"),
+    );
+    let suburb = CityGeometry::new("suburb", 52.2, 52.8, 12.8, 14.0).with_congestion_zones(false);
+    let space = ParameterSpace::grid()
+        .commission_rate(vec![0.1, 0.2])
+        .cities(vec![berlin, suburb]);
+
+    let sets = space.generate();
+    assert_eq!(sets.len(), 4);
+    let suburb_sets: Vec<_> = sets
+        .iter()
+        .filter(|set| set.geography.as_deref() == Some("suburb"))
+        .collect();
+    assert_eq!(suburb_sets.len(), 2);
+    for set in &suburb_sets {
+        assert_eq!(set.params.lat_min, 52.2);
+        assert!(!set.params.congestion_zones_enabled);
+    }
+    assert!(sets
+        .iter()
+        .filter(|set| set.geography.as_deref() == Some("berlin"))
+        .all(|set| set.params.congestion_zones_enabled));
+
+    let sampled = ParameterSpace::random(10, 3)
+        .cities(vec![CityGeometry::new("only", 1.0, 2.0, 3.0, 4.0)])
+        .generate();
+    assert!(sampled
+        .iter()
+        .all(|set| set.geography.as_deref() == Some("only") && set.params.lng_max == 4.0));
+}
+
+#[cfg(feature = "time-zones")]
+#[test]
+fn test_time_zone_dimension() {
+    use sim_core::clock::civil::CityTimeZone;
+    use sim_core::clock::LocalTime;
+
+    let zones = vec![
+        LocalTime::zone(CityTimeZone::berlin()),
+        LocalTime::zone(CityTimeZone::new_york()),
+    ];
+    let sets = ParameterSpace::grid()
+        .commission_rate(vec![0.1, 0.2])
+        .local_time(zones.clone())
+        .generate();
+    assert_eq!(sets.len(), 4);
+    for zone in &zones {
+        assert_eq!(
+            sets.iter()
+                .filter(|set| set.params.local_time == *zone)
+                .count(),
+            2
+        );
+    }
+
+    let sampled = ParameterSpace::random(5, 7)
+        .local_time(vec![LocalTime::zone(CityTimeZone::london())])
+        .generate();
+    assert!(sampled
+        .iter()
+        .all(|set| set.params.local_time.zone_name() == "Europe/London"));
+}
@@ -0,0 +1,393 @@
+//! Self-contained HTML reports of sweep results.
+//!
+//! [`read_results_parquet`] loads any results Parquet file column by column (numeric columns
+//! are treated as metrics, others as labels), and [`render_report`] turns it into a single
+//! HTML page: a summary, the best run by a ranking metric, per-metric statistics, inline SVG
+//! histograms and the full results table. The page has no external assets, so it can be
+//! attached to an issue or shared as is.
+//!
+//! Runs can be narrowed to those with given tag values and summarized per value of one tag
+//! (tags are exported as `tag_<key>` columns, see
+//! [`ParameterSet::with_tag`](crate::parameters::ParameterSet::with_tag)).
+
+use std::path::Path;
+
+use crate::export::TAG_COLUMN_PREFIX;
+
+#[path = "report/charts.rs"]
+mod charts;
+#[path = "report/table.rs"]
+mod table;
+
+pub use table::{read_results_parquet, ColumnSummary, ColumnValues, ResultsColumn, ResultsTable};
+
+/// Metrics charted and ranked by default, in order of preference, when present.
+pub const DEFAULT_CHART_METRICS: [&str; 6] = [
+    "conversion_rate",
+    "platform_revenue",
+    "driver_payouts",
+    "avg_time_to_match_ms",
+    "avg_time_to_pickup_ms",
+    "completed_trips",
+];
+const DEFAULT_RANK_METRICS: [&str; 2] = ["health_score", "conversion_rate"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportOptions {
+    pub title: String,
+    /// Metric whose highest value marks the best run; defaults to `health_score`, then
+    /// `conversion_rate`, when present.
+    pub rank_by: Option<String>,
+    /// Metrics to chart; empty charts [`DEFAULT_CHART_METRICS`] (or the first numeric columns).
+    pub chart_metrics: Vec<String>,
+    /// Rows shown in the results table; the rest are summarized by count.
+    pub max_table_rows: usize,
+    /// Only report runs with every one of these `(tag key, value)` pairs.
+    pub tag_filters: Vec<(String, String)>,
+    /// Tag key whose values split the runs into groups, summarized side by side.
+    pub group_by: Option<String>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            title: "Sweep results".to_string(),
+            rank_by: None,
+            chart_metrics: Vec::new(),
+            max_table_rows: 500,
+            tag_filters: Vec::new(),
+            group_by: None,
+        }
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn rank_metric(table: &ResultsTable, options: &ReportOptions) -> Option<String> {
+    match &options.rank_by {
+        Some(metric) => Some(metric.clone()),
+        None => DEFAULT_RANK_METRICS
+            .iter()
+            .find(|metric| table.numeric(metric).is_some())
+            .map(|metric| metric.to_string()),
+    }
+}
+
+fn chart_metrics(table: &ResultsTable, options: &ReportOptions) -> Vec<String> {
+    if !options.chart_metrics.is_empty() {
+        return options.chart_metrics.clone();
+    }
+    let defaults: Vec<String> = DEFAULT_CHART_METRICS
+        .iter()
+        .filter(|metric| table.numeric(metric).is_some())
+        .map(|metric| metric.to_string())
+        .collect();
+    if !defaults.is_empty() {
+        return defaults;
+    }
+    table
+        .numeric_column_names()
+        .into_iter()
+        .take(DEFAULT_CHART_METRICS.len())
+        .map(str::to_string)
+        .collect()
+}
+
+fn tag_column(table: &ResultsTable, key: &str) -> Result<String, String> {
+    let name = format!("{TAG_COLUMN_PREFIX}{key}");
+    match table.text(&name) {
+        Some(_) => Ok(name),
+        None => Err(format!("results have no '{key}' tag")),
+    }
+}
+
+/// Rows of `table` that carry every tag value in `filters`.
+fn filter_by_tags(
+    table: &ResultsTable,
+    filters: &[(String, String)],
+) -> Result<ResultsTable, String> {
+    let mut rows: Vec<usize> = (0..table.row_count()).collect();
+    for (key, value) in filters {
+        let values = table.text(&tag_column(table, key)?).unwrap_or_default();
+        rows.retain(|&row| values[row] == *value);
+    }
+    Ok(table.select_rows(&rows))
+}
+
+fn group_section(table: &ResultsTable, key: &str, metrics: &[String]) -> Result<String, String> {
+    let groups = table
+        .group_rows(&tag_column(table, key)?)
+        .unwrap_or_default();
+    let mut html = format!(
+        "<h2>Runs by {}</h2>\n<table>\n<tr><th class=\"label\">{}</th><th>runs</th>",
+        escape_html(key),
+        escape_html(key)
+    );
+    for metric in metrics {
+        html.push_str(&format!("<th>mean {}</th>", escape_html(metric)));
+    }
+    html.push_str("</tr>\n");
+    for (value, rows) in groups {
+        let group = table.select_rows(&rows);
+        html.push_str(&format!(
+            "<tr><td class=\"label\">{}</td><td>{}</td>",
+            escape_html(&value),
+            rows.len()
+        ));
+        for metric in metrics {
+            let mean = group
+                .summary(metric)
+                .map(|summary| table::format_number(summary.mean))
+                .unwrap_or_default();
+            html.push_str(&format!("<td>{mean}</td>"));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    Ok(html)
+}
+
+/// Render `table` as a standalone HTML page.
+pub fn render_report(table: &ResultsTable, options: &ReportOptions) -> Result<String, String> {
+    let filtered;
+    let table = if options.tag_filters.is_empty() {
+        table
+    } else {
+        filtered = filter_by_tags(table, &options.tag_filters)?;
+        &filtered
+    };
+    if table.row_count() == 0 {
+        return Err("results contain no rows".to_string());
+    }
+    let mut html = String::new();
+    html.push_str(&format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\n<style>\
+         body{{font-family:sans-serif;margin:2em;color:#222}}\
+         table{{border-collapse:collapse;margin:1em 0;font-size:13px}}\
+         th,td{{border:1px solid #ccc;padding:4px 8px;text-align:right}}\
+         th{{background:#f0f0f0}}td.label,th.label{{text-align:left}}\
+         .charts{{display:flex;flex-wrap:wrap;gap:12px}}\
+         </style></head><body>\n<h1>{title}</h1>\n<p>{rows} runs, {columns} columns.</p>\n",
+        title = escape_html(&options.title),
+        rows = table.row_count(),
+        columns = table.columns.len(),
+    ));
+
+    if let Some(metric) = rank_metric(table, options) {
+        let best = table
+            .best_row(&metric)
+            .ok_or_else(|| format!("rank metric '{metric}' has no numeric values"))?;
+        html.push_str(&format!(
+            "<h2>Best run by {}</h2>\n<table>\n",
+            escape_html(&metric)
+        ));
+        for column in &table.columns {
+            html.push_str(&format!(
+                "<tr><th class=\"label\">{}</th><td>{}</td></tr>\n",
+                escape_html(&column.name),
+                escape_html(&table.cell(column, best))
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if let Some(key) = &options.group_by {
+        let mut metrics: Vec<String> = rank_metric(table, options).into_iter().collect();
+        for metric in chart_metrics(table, options) {
+            if !metrics.contains(&metric) {
+                metrics.push(metric);
+            }
+        }
+        html.push_str(&group_section(table, key, &metrics)?);
+    }
+
+    html.push_str(
+        "<h2>Metric summary</h2>\n<table>\n<tr><th class=\"label\">metric</th>\
+         <th>runs</th><th>min</th><th>mean</th><th>max</th></tr>\n",
+    );
+    for name in table.numeric_column_names() {
+        if let Some(summary) = table.summary(name) {
+            html.push_str(&format!(
+                "<tr><td class=\"label\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(name),
+                summary.count,
+                table::format_number(summary.min),
+                table::format_number(summary.mean),
+                table::format_number(summary.max),
+            ));
+        }
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Distributions</h2>\n<div class=\"charts\">\n");
+    for metric in chart_metrics(table, options) {
+        let values: Vec<f64> = table
+            .numeric(&metric)
+            .ok_or_else(|| format!("chart metric '{metric}' is not a numeric column"))?
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect();
+        if !values.is_empty() {
+            html.push_str(&charts::histogram_svg(&metric, &values)?);
+            html.push('\n');
+        }
+    }
+    html.push_str("</div>\n");
+
+    let shown = table.row_count().min(options.max_table_rows);
+    html.push_str("<h2>Results</h2>\n<table>\n<tr><th>#</th>");
+    for column in &table.columns {
+        html.push_str(&format!("<th>{}</th>", escape_html(&column.name)));
+    }
+    html.push_str("</tr>\n");
+    for row in 0..shown {
+        html.push_str(&format!("<tr><td>{row}</td>"));
+        for column in &table.columns {
+            let class = match column.values {
+                ColumnValues::Text(_) => " class=\"label\"",
+                ColumnValues::Numeric(_) => "",
+            };
+            html.push_str(&format!(
+                "<td{class}>{}</td>",
+                escape_html(&table.cell(column, row))
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    if shown < table.row_count() {
+        html.push_str(&format!(
+            "<p>{} more runs not shown.</p>\n",
+            table.row_count() - shown
+        ));
+    }
+    html.push_str("</body></html>\n");
+    Ok(html)
+}
+
+/// Read a results Parquet file and write its HTML report.
+pub fn write_report(
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &ReportOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let table = read_results_parquet(input)?;
+    let html = render_report(&table, options)?;
+    std::fs::write(output, html)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export_to_parquet;
+    use crate::metrics::SimulationResult;
+    use tempfile::NamedTempFile;
+
+    fn result(conversion_rate: f64, platform_revenue: f64) -> SimulationResult {
+        SimulationResult {
+            total_riders: 100,
+            total_drivers: 20,
+            completed_riders: (conversion_rate * 100.0) as usize,
+            conversion_rate,
+            platform_revenue,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reads_exported_results_and_ranks_best_run() {
+        let file = NamedTempFile::new().unwrap();
+        export_to_parquet(&[result(0.5, 10.0), result(0.8, 7.5)], file.path()).unwrap();
+
+        let table = read_results_parquet(file.path()).unwrap();
+        assert_eq!(table.row_count(), 2);
+        assert_eq!(table.best_row("conversion_rate"), Some(1));
+        let summary = table.summary("platform_revenue").unwrap();
+        assert_eq!((summary.min, summary.max), (7.5, 10.0));
+    }
+
+    #[test]
+    fn renders_self_contained_report_with_charts() {
+        let table = ResultsTable {
+            columns: vec![
+                ResultsColumn {
+                    name: "experiment_id".to_string(),
+                    values: ColumnValues::Text(vec!["a<1>".to_string(), "b".to_string()]),
+                },
+                ResultsColumn {
+                    name: "conversion_rate".to_string(),
+                    values: ColumnValues::Numeric(vec![Some(0.4), Some(0.9)]),
+                },
+            ],
+        };
+
+        let html = render_report(&table, &ReportOptions::default()).unwrap();
+        assert!(html.contains("<h2>Best run by conversion_rate</h2>"));
+        assert!(html.contains("a&lt;1&gt;"));
+        assert!(html.contains("<svg"));
+        assert!(!html.contains("src=\"http"));
+
+        let options = ReportOptions {
+            chart_metrics: vec!["experiment_id".to_string()],
+            ..ReportOptions::default()
+        };
+        assert!(render_report(&table, &options).is_err());
+    }
+
+    #[test]
+    fn filters_and_groups_runs_by_exported_tags() {
+        let tagged = |conversion_rate, policy: &str, city: &str| SimulationResult {
+            tags: [("policy", policy), ("city", city)]
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..result(conversion_rate, 1.0)
+        };
+        let file = NamedTempFile::new().unwrap();
+        export_to_parquet(
+            &[
+                tagged(0.4, "baseline", "berlin"),
+                tagged(0.6, "surge", "berlin"),
+                tagged(0.8, "surge", "paris"),
+                result(0.9, 1.0),
+            ],
+            file.path(),
+        )
+        .unwrap();
+        let table = read_results_parquet(file.path()).unwrap();
+        assert_eq!(
+            table.group_rows("tag_policy").unwrap(),
+            vec![
+                ("baseline".to_string(), vec![0]),
+                ("surge".to_string(), vec![1, 2])
+            ]
+        );
+
+        let options = ReportOptions {
+            tag_filters: vec![("city".to_string(), "berlin".to_string())],
+            group_by: Some("policy".to_string()),
+            ..ReportOptions::default()
+        };
+        let html = render_report(&table, &options).unwrap();
+        assert!(html.contains("<p>2 runs,"));
+        assert!(html.contains("<h2>Runs by policy</h2>"));
+        assert!(html.contains("<td class=\"label\">surge</td><td>1</td><td>0.6000</td>"));
+
+        let options = ReportOptions {
+            group_by: Some("operator".to_string()),
+            ..ReportOptions::default()
+        };
+        assert_eq!(
+            render_report(&table, &options),
+            Err("results have no 'operator' tag".to_string())
+        );
+    }
+}
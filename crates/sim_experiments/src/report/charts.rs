@@ -0,0 +1,62 @@
+use plotters::prelude::*;
+
+use super::table::format_number;
+
+const CHART_WIDTH: u32 = 480;
+const CHART_HEIGHT: u32 = 280;
+const HISTOGRAM_BINS: usize = 12;
+
+/// Inline SVG histogram of `values` across runs.
+pub(crate) fn histogram_svg(metric: &str, values: &[f64]) -> Result<String, String> {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if !min.is_finite() || !max.is_finite() {
+        return Err(format!("no finite values for {metric}"));
+    }
+    // Constant columns get a unit-wide range so the single bar is visible
+    let (low, high) = if max > min {
+        (min, max)
+    } else {
+        (min - 0.5, max + 0.5)
+    };
+    let bin_width = (high - low) / HISTOGRAM_BINS as f64;
+    let mut counts = [0u32; HISTOGRAM_BINS];
+    for value in values {
+        let bin = (((value - low) / bin_width) as usize).min(HISTOGRAM_BINS - 1);
+        counts[bin] += 1;
+    }
+    let max_count = counts.iter().copied().max().unwrap_or(1);
+
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, (CHART_WIDTH, CHART_HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(|error| error.to_string())?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption(metric, ("sans-serif", 16))
+            .margin(8)
+            .x_label_area_size(32)
+            .y_label_area_size(40)
+            .build_cartesian_2d(low..high, 0u32..max_count + 1)
+            .map_err(|error| error.to_string())?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_labels(5)
+            .x_label_formatter(&|value| format_number(*value))
+            .y_desc("runs")
+            .draw()
+            .map_err(|error| error.to_string())?;
+        chart
+            .draw_series(counts.iter().enumerate().map(|(bin, count)| {
+                let start = low + bin as f64 * bin_width;
+                Rectangle::new(
+                    [(start, 0), (start + bin_width, *count)],
+                    RGBColor(70, 130, 180).filled(),
+                )
+            }))
+            .map_err(|error| error.to_string())?;
+        root.present().map_err(|error| error.to_string())?;
+    }
+    Ok(svg)
+}
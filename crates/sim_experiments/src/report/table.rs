@@ -0,0 +1,214 @@
+use std::fs::File;
+use std::path::Path;
+
+use arrow::array::Array;
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+/// Values of one results column: numeric columns are metrics, anything else is a label.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValues {
+    Numeric(Vec<Option<f64>>),
+    Text(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultsColumn {
+    pub name: String,
+    pub values: ColumnValues,
+}
+
+/// Sweep results loaded column-wise, one row per run.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResultsTable {
+    pub columns: Vec<ResultsColumn>,
+}
+
+/// Min, mean and max of a numeric column over its non-null values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSummary {
+    pub count: usize,
+    pub min: f64,
+    pub mean: f64,
+    pub max: f64,
+}
+
+impl ResultsTable {
+    pub fn row_count(&self) -> usize {
+        self.columns
+            .first()
+            .map(|column| match &column.values {
+                ColumnValues::Numeric(values) => values.len(),
+                ColumnValues::Text(values) => values.len(),
+            })
+            .unwrap_or(0)
+    }
+
+    pub fn column(&self, name: &str) -> Option<&ResultsColumn> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    pub fn numeric(&self, name: &str) -> Option<&[Option<f64>]> {
+        match &self.column(name)?.values {
+            ColumnValues::Numeric(values) => Some(values),
+            ColumnValues::Text(_) => None,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> Option<&[String]> {
+        match &self.column(name)?.values {
+            ColumnValues::Text(values) => Some(values),
+            ColumnValues::Numeric(_) => None,
+        }
+    }
+
+    pub fn numeric_column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .filter(|column| matches!(column.values, ColumnValues::Numeric(_)))
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+
+    /// Cell rendered for display (empty for nulls).
+    pub fn cell(&self, column: &ResultsColumn, row: usize) -> String {
+        match &column.values {
+            ColumnValues::Numeric(values) => values[row].map(format_number).unwrap_or_default(),
+            ColumnValues::Text(values) => values[row].clone(),
+        }
+    }
+
+    pub fn summary(&self, name: &str) -> Option<ColumnSummary> {
+        let values: Vec<f64> = self
+            .numeric(name)?
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        Some(ColumnSummary {
+            count: values.len(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// Table of the given rows only, in the given order.
+    pub fn select_rows(&self, rows: &[usize]) -> ResultsTable {
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| ResultsColumn {
+                name: column.name.clone(),
+                values: match &column.values {
+                    ColumnValues::Numeric(values) => {
+                        ColumnValues::Numeric(rows.iter().map(|&row| values[row]).collect())
+                    }
+                    ColumnValues::Text(values) => {
+                        ColumnValues::Text(rows.iter().map(|&row| values[row].clone()).collect())
+                    }
+                },
+            })
+            .collect();
+        ResultsTable { columns }
+    }
+
+    /// Rows grouped by the value of text column `name`, groups sorted by value (rows with an
+    /// empty value are left out).
+    pub fn group_rows(&self, name: &str) -> Option<Vec<(String, Vec<usize>)>> {
+        let mut groups: std::collections::BTreeMap<&str, Vec<usize>> = Default::default();
+        for (row, value) in self.text(name)?.iter().enumerate() {
+            if !value.is_empty() {
+                groups.entry(value).or_default().push(row);
+            }
+        }
+        Some(
+            groups
+                .into_iter()
+                .map(|(value, rows)| (value.to_string(), rows))
+                .collect(),
+        )
+    }
+
+    /// Row with the highest value of `metric`.
+    pub fn best_row(&self, metric: &str) -> Option<usize> {
+        self.numeric(metric)?
+            .iter()
+            .enumerate()
+            .filter_map(|(row, value)| value.filter(|value| value.is_finite()).map(|v| (row, v)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(row, _)| row)
+    }
+}
+
+pub(crate) fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else if value.abs() >= 100.0 {
+        format!("{value:.1}")
+    } else {
+        format!("{value:.4}")
+    }
+}
+
+fn is_numeric(data_type: &DataType) -> bool {
+    data_type.is_numeric() || matches!(data_type, DataType::Boolean)
+}
+
+/// Load every column of a results Parquet file (e.g. from `export_to_parquet`).
+pub fn read_results_parquet(
+    path: impl AsRef<Path>,
+) -> Result<ResultsTable, Box<dyn std::error::Error>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?.build()?;
+    let mut table = ResultsTable::default();
+
+    for batch in reader {
+        let batch = batch?;
+        if table.columns.is_empty() {
+            table.columns = batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| ResultsColumn {
+                    name: field.name().clone(),
+                    values: if is_numeric(field.data_type()) {
+                        ColumnValues::Numeric(Vec::new())
+                    } else {
+                        ColumnValues::Text(Vec::new())
+                    },
+                })
+                .collect();
+        }
+
+        for (column, array) in table.columns.iter_mut().zip(batch.columns()) {
+            match &mut column.values {
+                ColumnValues::Numeric(values) => {
+                    let floats = cast(array, &DataType::Float64)?;
+                    let floats = floats
+                        .as_any()
+                        .downcast_ref::<arrow::array::Float64Array>()
+                        .ok_or("numeric column did not cast to Float64")?;
+                    values.extend(floats.iter());
+                }
+                ColumnValues::Text(values) => {
+                    let formatter = ArrayFormatter::try_new(array, &FormatOptions::default())?;
+                    values.extend((0..array.len()).map(|row| {
+                        if array.is_null(row) {
+                            String::new()
+                        } else {
+                            formatter.value(row).to_string()
+                        }
+                    }));
+                }
+            }
+        }
+    }
+
+    Ok(table)
+}
@@ -3,16 +3,25 @@
 //! This module provides functions to run single simulations and execute
 //! multiple simulations in parallel for parameter sweeps.
 
-use bevy_ecs::prelude::World;
+use bevy_ecs::prelude::{Schedule, World};
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
-use sim_core::runner::{initialize_simulation, run_until_empty, simulation_schedule};
+use sim_core::pricing::earnings_floor::settle_earnings_floor;
+use sim_core::provenance::ExportProvenance;
+use sim_core::run_bundle::RunBundle;
+use sim_core::runner::{
+    initialize_simulation, run_next_event, run_until_empty, simulation_schedule,
+};
 use sim_core::scenario::build_scenario;
 use sim_core::telemetry::{SimSnapshots, SimTelemetry};
 use sim_core::telemetry_export::{write_snapshot_counts_parquet, write_trips_parquet};
+use std::any::Any;
 use std::fs;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use crate::export::build_provenance;
 use crate::metrics::{extract_metrics, SimulationResult};
 use crate::parameters::ParameterSet;
 
@@ -23,6 +32,66 @@ pub struct SimulationArtifacts {
     pub snapshot_counts_parquet: Vec<u8>,
 }
 
+/// Per-run safeguards for sweeps (see [`run_parallel_experiments_isolated`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunLimits {
+    /// Abort a run whose event loop exceeds this wall-clock time (None = no limit).
+    /// Checked between events, so a single event that never returns is not interrupted.
+    pub wall_clock_timeout: Option<Duration>,
+    /// Maximum events processed per run; a run stops (successfully) once reached.
+    pub max_steps: usize,
+}
+
+impl Default for RunLimits {
+    fn default() -> Self {
+        Self {
+            wall_clock_timeout: None,
+            max_steps: 2_000_000,
+        }
+    }
+}
+
+impl RunLimits {
+    pub fn with_wall_clock_timeout(mut self, timeout: Duration) -> Self {
+        self.wall_clock_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Why a simulation run produced no result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunFailure {
+    /// The run panicked; carries the panic message.
+    Panicked(String),
+    /// The run exceeded [`RunLimits::wall_clock_timeout`] after processing `steps` events.
+    TimedOut { limit: Duration, steps: usize },
+    /// Telemetry could not be read or exported after the run.
+    Export(String),
+}
+
+impl std::fmt::Display for RunFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunFailure::Panicked(message) => write!(f, "simulation panicked: {message}"),
+            RunFailure::TimedOut { limit, steps } => write!(
+                f,
+                "simulation timed out after {:.1}s ({steps} events)",
+                limit.as_secs_f64()
+            ),
+            RunFailure::Export(message) => f.write_str(message),
+        }
+    }
+}
+
+/// A parameter set whose run failed, recorded in place of its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedRun {
+    pub experiment_id: String,
+    pub run_id: usize,
+    pub seed: u64,
+    pub failure: RunFailure,
+}
+
 /// Shared simulation primitive used by local sweeps and serverless workers.
 ///
 /// Runs one parameter set to completion and returns metrics plus exported
@@ -30,41 +99,52 @@ pub struct SimulationArtifacts {
 pub fn run_single_simulation_with_artifacts(
     param_set: &ParameterSet,
 ) -> Result<SimulationArtifacts, String> {
-    let mut world = World::new();
-    let mut params = param_set.scenario_params();
-
-    if params.simulation_end_time_ms.is_none() {
-        let request_window_ms = params.request_window_ms;
-        let end_time_ms = request_window_ms.saturating_add(2 * 60 * 60 * 1000);
-        params.simulation_end_time_ms = Some(end_time_ms);
-    }
+    simulate(param_set, &RunLimits::default()).map_err(|failure| failure.to_string())
+}
 
-    build_scenario(&mut world, params);
-    initialize_simulation(&mut world);
+/// Run one parameter set to completion and record it as a [`RunBundle`] (labelled
+/// `<experiment_id>/<run_id>`), for replay in the UI or re-analysis with
+/// [`metrics_from_bundle`](crate::metrics::metrics_from_bundle).
+pub fn record_single_simulation(param_set: &ParameterSet) -> Result<RunBundle, String> {
+    let mut world =
+        run_world(param_set, &RunLimits::default()).map_err(|failure| failure.to_string())?;
+    Ok(RunBundle::capture(&mut world)
+        .with_label(format!("{}/{}", param_set.experiment_id, param_set.run_id)))
+}
 
-    let mut schedule = simulation_schedule();
-    let _steps = run_until_empty(&mut world, &mut schedule, 2_000_000);
+fn simulate(
+    param_set: &ParameterSet,
+    limits: &RunLimits,
+) -> Result<SimulationArtifacts, RunFailure> {
+    let world = run_world(param_set, limits)?;
 
-    let metrics = extract_metrics(&mut world);
+    let mut metrics = extract_metrics(&world);
+    metrics.tags = param_set.tags.clone();
     world
         .get_resource::<SimTelemetry>()
-        .ok_or_else(|| "SimTelemetry resource not found".to_string())?;
+        .ok_or_else(|| RunFailure::Export("SimTelemetry resource not found".to_string()))?;
     let snapshots = world
         .get_resource::<SimSnapshots>()
-        .ok_or_else(|| "SimSnapshots resource not found".to_string())?;
+        .ok_or_else(|| RunFailure::Export("SimSnapshots resource not found".to_string()))?;
+    let provenance = ExportProvenance {
+        scenario_hash: metrics.scenario_hash.clone(),
+        ..build_provenance()
+    };
 
     let trip_data_parquet = serialize_to_parquet_bytes(
-        |path| write_trips_parquet(path, snapshots),
+        |path| write_trips_parquet(path, snapshots, &provenance),
         &param_set.experiment_id,
         param_set.run_id,
         "trip-data",
-    )?;
+    )
+    .map_err(RunFailure::Export)?;
     let snapshot_counts_parquet = serialize_to_parquet_bytes(
-        |path| write_snapshot_counts_parquet(path, snapshots),
+        |path| write_snapshot_counts_parquet(path, snapshots, &provenance),
         &param_set.experiment_id,
         param_set.run_id,
         "snapshot-counts",
-    )?;
+    )
+    .map_err(RunFailure::Export)?;
 
     Ok(SimulationArtifacts {
         metrics,
@@ -73,6 +153,75 @@ pub fn run_single_simulation_with_artifacts(
     })
 }
 
+/// Build and run one parameter set's world to completion (or its limits).
+fn run_world(param_set: &ParameterSet, limits: &RunLimits) -> Result<World, RunFailure> {
+    let mut world = World::new();
+    let mut params = param_set.scenario_params();
+
+    if params.simulation_end_time_ms.is_none() {
+        let request_window_ms = params.request_window_ms;
+        let end_time_ms = request_window_ms.saturating_add(2 * 60 * 60 * 1000);
+        params.simulation_end_time_ms = Some(end_time_ms);
+    }
+
+    build_scenario(&mut world, params);
+    initialize_simulation(&mut world);
+
+    let mut schedule = simulation_schedule();
+    run_within_limits(&mut world, &mut schedule, limits)?;
+    // Drivers still on duty are settled against the earnings floor once, when the run ends
+    settle_earnings_floor(&mut world);
+    Ok(world)
+}
+
+fn run_within_limits(
+    world: &mut World,
+    schedule: &mut Schedule,
+    limits: &RunLimits,
+) -> Result<usize, RunFailure> {
+    /// Events between wall-clock checks; keeps `Instant::now` off the hot path.
+    const TIMEOUT_CHECK_INTERVAL: usize = 1024;
+
+    let Some(limit) = limits.wall_clock_timeout else {
+        return Ok(run_until_empty(world, schedule, limits.max_steps));
+    };
+    let started = Instant::now();
+    let mut steps = 0;
+    while steps < limits.max_steps && run_next_event(world, schedule) {
+        steps += 1;
+        if steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL) && started.elapsed() > limit {
+            return Err(RunFailure::TimedOut { limit, steps });
+        }
+    }
+    Ok(steps)
+}
+
+/// Run one simulation, turning panics and timeouts into a [`FailedRun`] instead of
+/// unwinding into the caller.
+pub fn run_single_simulation_isolated(
+    param_set: &ParameterSet,
+    limits: &RunLimits,
+) -> Result<SimulationResult, FailedRun> {
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| simulate(param_set, limits)))
+        .unwrap_or_else(|payload| Err(RunFailure::Panicked(panic_message(payload.as_ref()))));
+    outcome
+        .map(|artifacts| artifacts.metrics)
+        .map_err(|failure| FailedRun {
+            experiment_id: param_set.experiment_id.clone(),
+            run_id: param_set.run_id,
+            seed: param_set.seed,
+            failure,
+        })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
 /// Run a single simulation with the given parameter set.
 ///
 /// Creates a new world, builds the scenario, runs the simulation to completion,
@@ -136,6 +285,32 @@ pub fn run_parallel_experiments(
     run_parallel_experiments_with_progress(parameter_sets, num_threads, true)
 }
 
+/// Progress of a parallel experiment run, reported after each simulation finishes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExperimentProgress {
+    /// Simulations finished so far.
+    pub completed: usize,
+    /// Simulations in the run.
+    pub total: usize,
+    /// Wall-clock time since the run started.
+    pub elapsed: Duration,
+    /// Mean wall-clock runtime of a single finished simulation.
+    pub avg_run_time: Duration,
+    /// Estimated time until the remaining simulations finish, from the throughput so far.
+    pub eta: Duration,
+}
+
+impl ExperimentProgress {
+    /// Fraction of simulations finished (1.0 for an empty run).
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.completed as f64 / self.total as f64
+        }
+    }
+}
+
 /// Run multiple simulations in parallel with optional progress bar.
 ///
 /// Uses rayon to execute simulations concurrently across available CPU cores.
@@ -156,12 +331,95 @@ pub fn run_parallel_experiments_with_progress(
     show_progress: bool,
 ) -> Vec<SimulationResult> {
     let total = parameter_sets.len();
+    with_progress_bar(total, show_progress, |on_progress| {
+        run_parallel(
+            &parameter_sets,
+            num_threads,
+            run_single_simulation,
+            on_progress,
+        )
+    })
+}
+
+/// Run multiple simulations in parallel, calling `on_progress` after each one finishes.
+///
+/// The callback runs on rayon worker threads, one call at a time, so it must be cheap; forward
+/// to a channel (e.g. `std::sync::mpsc::Sender`) to consume progress elsewhere.
+///
+/// # Returns
+///
+/// Vector of `SimulationResult` in the same order as input parameter sets.
+pub fn run_parallel_experiments_with_callback<F>(
+    parameter_sets: Vec<ParameterSet>,
+    num_threads: Option<usize>,
+    on_progress: F,
+) -> Vec<SimulationResult>
+where
+    F: Fn(ExperimentProgress) + Send + Sync,
+{
+    run_parallel(
+        &parameter_sets,
+        num_threads,
+        run_single_simulation,
+        &on_progress,
+    )
+}
+
+/// Run multiple simulations in parallel, isolating each run: a run that panics or exceeds
+/// `limits` becomes a [`FailedRun`] entry while the rest of the sweep carries on.
+///
+/// # Returns
+///
+/// One entry per input parameter set, in the same order.
+pub fn run_parallel_experiments_isolated(
+    parameter_sets: Vec<ParameterSet>,
+    num_threads: Option<usize>,
+    limits: RunLimits,
+    show_progress: bool,
+) -> Vec<Result<SimulationResult, FailedRun>> {
+    let total = parameter_sets.len();
+    with_progress_bar(total, show_progress, |on_progress| {
+        run_parallel(
+            &parameter_sets,
+            num_threads,
+            |param_set| run_single_simulation_isolated(param_set, &limits),
+            on_progress,
+        )
+    })
+}
+
+/// Split isolated sweep outcomes into the successful runs (with their parameter sets, aligned
+/// for export) and the failures.
+pub fn partition_outcomes(
+    parameter_sets: Vec<ParameterSet>,
+    outcomes: Vec<Result<SimulationResult, FailedRun>>,
+) -> (Vec<ParameterSet>, Vec<SimulationResult>, Vec<FailedRun>) {
+    let mut succeeded_sets = Vec::new();
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    for (param_set, outcome) in parameter_sets.into_iter().zip(outcomes) {
+        match outcome {
+            Ok(result) => {
+                succeeded_sets.push(param_set);
+                results.push(result);
+            }
+            Err(failure) => failures.push(failure),
+        }
+    }
+    (succeeded_sets, results, failures)
+}
+
+fn with_progress_bar<T>(
+    total: usize,
+    show_progress: bool,
+    body: impl FnOnce(&(dyn Fn(ExperimentProgress) + Sync)) -> T,
+) -> T {
     let pb = if show_progress && total > 0 {
         let bar = ProgressBar::new(total as u64);
         bar.set_style(
             ProgressStyle::default_bar()
                 .template(
-                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}",
                 )
                 .unwrap()
                 .progress_chars("#>-"),
@@ -171,6 +429,34 @@ pub fn run_parallel_experiments_with_progress(
         None
     };
 
+    let output = body(&|progress| {
+        if let Some(ref progress_bar) = pb {
+            progress_bar.set_position(progress.completed as u64);
+            progress_bar.set_message(format!(
+                "avg {:.1}s/run",
+                progress.avg_run_time.as_secs_f64()
+            ));
+        }
+    });
+
+    if let Some(ref progress_bar) = pb {
+        progress_bar.finish_with_message("Completed");
+    }
+
+    output
+}
+
+fn run_parallel<T, R>(
+    parameter_sets: &[ParameterSet],
+    num_threads: Option<usize>,
+    run: R,
+    on_progress: &(dyn Fn(ExperimentProgress) + Sync),
+) -> Vec<T>
+where
+    T: Send,
+    R: Fn(&ParameterSet) -> T + Send + Sync,
+{
+    let total = parameter_sets.len();
     let pool = if let Some(threads) = num_threads {
         rayon::ThreadPoolBuilder::new()
             .num_threads(threads)
@@ -182,25 +468,51 @@ pub fn run_parallel_experiments_with_progress(
             .expect("Failed to create thread pool")
     };
 
-    let pb_clone = pb.clone();
-    let results = pool.install(|| {
+    let started = Instant::now();
+    // (completed, summed single-run time)
+    let tally = Mutex::new((0usize, Duration::ZERO));
+
+    pool.install(|| {
         parameter_sets
             .par_iter()
             .map(|param_set| {
-                let result = run_single_simulation(param_set);
-                if let Some(ref progress_bar) = pb_clone {
-                    progress_bar.inc(1);
-                }
+                let run_started = Instant::now();
+                let result = run(param_set);
+                let run_time = run_started.elapsed();
+
+                // Report under the lock so callbacks see `completed` in increasing order
+                let mut tally = tally.lock().expect("progress tally poisoned");
+                tally.0 += 1;
+                tally.1 += run_time;
+                on_progress(progress_snapshot(
+                    tally.0,
+                    total,
+                    started.elapsed(),
+                    tally.1,
+                ));
+                drop(tally);
                 result
             })
             .collect()
-    });
+    })
+}
 
-    if let Some(ref progress_bar) = pb {
-        progress_bar.finish_with_message("Completed");
+fn progress_snapshot(
+    completed: usize,
+    total: usize,
+    elapsed: Duration,
+    summed_run_time: Duration,
+) -> ExperimentProgress {
+    let avg_run_time = summed_run_time / completed.max(1) as u32;
+    let remaining = total.saturating_sub(completed);
+    let eta = elapsed.mul_f64(remaining as f64 / completed.max(1) as f64);
+    ExperimentProgress {
+        completed,
+        total,
+        elapsed,
+        avg_run_time,
+        eta,
     }
-
-    results
 }
 
 #[cfg(test)]
@@ -221,6 +533,43 @@ mod tests {
         assert!(result.total_drivers > 0);
     }
 
+    #[test]
+    fn test_recorded_simulation_reproduces_metrics() {
+        let space = ParameterSpace::grid()
+            .num_riders(vec![10])
+            .num_drivers(vec![3])
+            .min_hourly_earnings(vec![1_000.0]);
+        let sets = space.generate();
+        let result = run_single_simulation(&sets[0]);
+        let bundle = record_single_simulation(&sets[0]).expect("recorded run");
+        let replayed = crate::metrics::metrics_from_bundle(&bundle);
+
+        assert_eq!(replayed.completed_riders, result.completed_riders);
+        assert_eq!(replayed.total_drivers, result.total_drivers);
+        assert_eq!(replayed.funnel_requests, result.funnel_requests);
+        assert!((replayed.driver_payouts - result.driver_payouts).abs() < 1e-9);
+        assert!((replayed.platform_revenue - result.platform_revenue).abs() < 1e-9);
+        assert!(result.earnings_floor_top_ups > 0.0);
+        assert!((replayed.earnings_floor_top_ups - result.earnings_floor_top_ups).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_extract_metrics_does_not_settle_again() {
+        let sets = ParameterSpace::grid()
+            .num_riders(vec![10])
+            .num_drivers(vec![3])
+            .min_hourly_earnings(vec![1_000.0])
+            .generate();
+        let world = run_world(&sets[0], &RunLimits::default()).expect("run completes");
+
+        let first = extract_metrics(&world);
+        let second = extract_metrics(&world);
+
+        assert!(first.earnings_floor_top_ups > 0.0);
+        assert_eq!(first.earnings_floor_top_ups, second.earnings_floor_top_ups);
+        assert_eq!(first.driver_payouts, second.driver_payouts);
+    }
+
     #[test]
     fn test_parallel_experiments() {
         let space = ParameterSpace::grid()
@@ -234,4 +583,60 @@ mod tests {
             assert!(result.total_riders > 0);
         }
     }
+
+    #[test]
+    fn test_parallel_experiments_report_progress() {
+        let sets = ParameterSpace::grid()
+            .num_riders(vec![10])
+            .num_drivers(vec![3, 5])
+            .generate();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let results = run_parallel_experiments_with_callback(sets, Some(2), |progress| {
+            sender.send(progress).expect("receiver alive");
+        });
+        drop(sender);
+
+        let completed: Vec<usize> = receiver.iter().map(|p| p.completed).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(completed, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_isolated_runs_record_timeouts_without_aborting() {
+        let mut sets = ParameterSpace::grid()
+            .num_riders(vec![10, 500])
+            .num_drivers(vec![100])
+            .generate();
+        // A zero timeout trips at the first wall-clock check (after 1024 events); ending the
+        // small run after a minute keeps it below that, so only the 500-rider run reaches it
+        sets[0].params.simulation_end_time_ms = Some(60_000);
+        let limits = RunLimits::default().with_wall_clock_timeout(Duration::ZERO);
+        let outcomes = run_parallel_experiments_isolated(sets.clone(), Some(2), limits, false);
+
+        assert!(outcomes[0].is_ok());
+        let failure = outcomes[1].as_ref().expect_err("large run should time out");
+        assert_eq!(failure.experiment_id, sets[1].experiment_id);
+        assert!(matches!(
+            failure.failure,
+            RunFailure::TimedOut { steps: 1024, .. }
+        ));
+
+        let (ok_sets, results, failures) = partition_outcomes(sets, outcomes);
+        assert_eq!((ok_sets.len(), results.len(), failures.len()), (1, 1, 1));
+        assert_eq!(ok_sets[0].params.num_riders, 10);
+    }
+
+    #[test]
+    fn test_panic_message_from_payload() {
+        let payload = panic::catch_unwind(|| panic!("diverged at {}", 42)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "diverged at 42");
+    }
+
+    #[test]
+    fn test_progress_eta_from_throughput() {
+        let progress = progress_snapshot(2, 6, Duration::from_secs(10), Duration::from_secs(16));
+        assert_eq!(progress.avg_run_time, Duration::from_secs(8));
+        assert_eq!(progress.eta, Duration::from_secs(20));
+        assert!((progress.fraction() - 1.0 / 3.0).abs() < 1e-9);
+    }
 }
@@ -4,10 +4,14 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
 
+use crate::estimate::{RunBenchmark, SweepCostEstimate};
+use crate::pacing::DispatchPacing;
+
 pub const ORCHESTRATION_SCHEMA_VERSION: &str = "v1";
 pub const OUTCOME_RECORD_SCHEMA_VERSION: &str = "v1";
 pub const RUN_CONTEXT_RECORD_SCHEMA_VERSION: &str = "v1";
 pub const EFFECTIVE_PARAMETER_RECORD_SCHEMA_VERSION: &str = "v1";
+pub const PART_MANIFEST_SCHEMA_VERSION: &str = "v1";
 pub const MAX_DIMENSION_VALUES: usize = 10_000;
 pub const MAX_TOTAL_PARAMETER_POINTS: usize = 200_000;
 pub const DEFAULT_MAX_SHARDS: usize = 1_000;
@@ -66,6 +70,15 @@ pub struct SweepRequest {
     pub seed: i64,
     #[serde(default)]
     pub failure_injection_shards: Vec<usize>,
+    /// Per-run timings; when present the parent returns a cost estimate with the response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<RunBenchmark>,
+    /// Return the cost estimate without dispatching any shards (requires `benchmark`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub estimate_only: bool,
+    /// Concurrency waves and enqueue rate limit for shard dispatch (default: unpaced).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pacing: Option<DispatchPacing>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -91,15 +104,74 @@ pub struct ChildShardPayload {
     pub end_index_exclusive: usize,
     pub seed: i64,
     pub failure_injection_shards: Vec<usize>,
+    /// Token identifying this shard's work (see [`shard_idempotency_key`]); payloads queued
+    /// before tokens existed omit it and the child derives it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+/// Marker the child writes after a shard's success outcome; a redelivered message with the
+/// same idempotency key finds it and skips recomputation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardIdempotencyRecord {
+    pub idempotency_key: String,
+    pub run_id: String,
+    pub shard_id: usize,
+    pub outcome_key: String,
+    pub points_processed: usize,
+    pub event_time: String,
+}
+
+/// Progress of a shard that stopped before its invocation deadline. Points before
+/// `next_point_index` are already stored; a retry with the same idempotency key resumes there.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShardCheckpointRecord {
+    pub idempotency_key: String,
+    pub run_id: String,
+    pub shard_id: usize,
+    pub next_point_index: usize,
+    pub points_completed: usize,
+    pub event_time: String,
+}
+
+/// One Parquet part file of a chunked point output.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PartFile {
+    pub key: String,
+    pub rows: u64,
+    pub bytes: usize,
+}
+
+/// Lists the part files of one point's chunked output (written next to them as
+/// `_manifest.json`). Readers should take exactly these parts: a retried shard that produced
+/// fewer parts can leave stale higher-numbered part files behind.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PartManifest {
+    pub run_id: String,
+    pub shard_id: usize,
+    pub point_index: usize,
+    pub dataset: String,
+    pub record_schema: String,
+    pub total_rows: u64,
+    pub parts: Vec<PartFile>,
+}
+
+impl PartManifest {
+    pub fn part_keys(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().map(|part| part.key.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DispatchRecord {
     pub shard_id: usize,
     pub status_code: u16,
+    /// Queue delay the shard was sent with (see [`crate::pacing`]).
+    #[serde(default)]
+    pub delay_seconds: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ParentAcceptedResponse {
     pub run_id: String,
     pub total_points: usize,
@@ -107,6 +179,8 @@ pub struct ParentAcceptedResponse {
     pub dispatches: Vec<DispatchRecord>,
     pub status: String,
     pub schema_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<SweepCostEstimate>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -258,6 +332,29 @@ pub fn config_fingerprint(request: &NormalizedSweepRequest) -> String {
     })
 }
 
+/// Idempotency key of a shard: a fingerprint of everything that determines its results
+/// (run, shard bounds, dimensions, seed), so every delivery of the same shard shares it.
+pub fn shard_idempotency_key(payload: &ChildShardPayload) -> String {
+    #[derive(Serialize)]
+    struct ShardIdentity<'a> {
+        run_id: &'a str,
+        shard_id: usize,
+        start_index: usize,
+        end_index_exclusive: usize,
+        dimensions: &'a Dimensions,
+        seed: i64,
+    }
+
+    contract_fingerprint(ShardIdentity {
+        run_id: &payload.run_id,
+        shard_id: payload.shard_id,
+        start_index: payload.start_index,
+        end_index_exclusive: payload.end_index_exclusive,
+        dimensions: &payload.dimensions,
+        seed: payload.seed,
+    })
+}
+
 pub fn contract_fingerprint(value: impl Serialize) -> String {
     let mut hasher = Sha256::new();
     hasher.update(stable_contract_json(value));
@@ -282,6 +379,9 @@ mod tests {
             max_shards: 10,
             seed: 0,
             failure_injection_shards: Vec::new(),
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
 
         let error = normalize_request(request).expect_err("request should fail");
@@ -304,6 +404,9 @@ mod tests {
             max_shards: 10,
             seed: 7,
             failure_injection_shards: vec![3, 1, 3],
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
 
         let normalized = normalize_request(request).expect("request should pass");
@@ -327,6 +430,9 @@ mod tests {
             max_shards: 10,
             seed: 11,
             failure_injection_shards: vec![1],
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
         let request_b = SweepRequest {
             run_id: "run-b".to_string(),
@@ -342,6 +448,9 @@ mod tests {
             max_shards: 99,
             seed: 11,
             failure_injection_shards: vec![7, 8],
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
 
         let normalized_a = normalize_request(request_a).expect("request a should pass");
@@ -0,0 +1,222 @@
+//! Pre-launch cost estimation for serverless sweeps.
+//!
+//! [`estimate_sweep`] projects a normalized request onto its shard plan and, from per-run
+//! benchmark timings, predicts Lambda duration, S3 objects and bytes, and an approximate
+//! dollar cost. Prices default to AWS on-demand list prices (us-east-1, x86) and the
+//! deployed runtime Lambda settings; they are estimates, not a bill.
+
+use serde::{Deserialize, Serialize};
+
+use crate::contract::{NormalizedSweepRequest, ValidationError};
+use crate::sharding::compute_shard_plan;
+
+/// Objects written per successful point: metrics, snapshot counts, effective parameters,
+/// one trip data part and its part manifest.
+pub const OBJECTS_PER_POINT: u64 = 5;
+/// Objects written per shard: the outcome record and the idempotency marker.
+pub const OBJECTS_PER_SHARD: u64 = 2;
+/// Objects written once per run: the run context record.
+pub const OBJECTS_PER_RUN: u64 = 1;
+
+/// Measured cost of one simulation run, e.g. from a local benchmark of the sweep's points.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RunBenchmark {
+    /// Wall-clock seconds for one run on the runtime Lambda's CPU share.
+    pub seconds_per_run: f64,
+    /// Bytes uploaded per run (trip data, snapshot counts and metrics).
+    #[serde(default)]
+    pub output_bytes_per_run: u64,
+}
+
+/// Pricing and deployment settings the estimate is computed against.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct CostModel {
+    pub lambda_memory_mb: u64,
+    pub lambda_timeout_seconds: f64,
+    /// Per-shard startup and upload time on top of the runs themselves.
+    pub shard_overhead_seconds: f64,
+    pub lambda_usd_per_gb_second: f64,
+    pub lambda_usd_per_million_requests: f64,
+    pub s3_usd_per_thousand_puts: f64,
+    pub s3_usd_per_gb_month: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            lambda_memory_mb: 1024,
+            lambda_timeout_seconds: 900.0,
+            shard_overhead_seconds: 2.0,
+            lambda_usd_per_gb_second: 0.000_016_666_7,
+            lambda_usd_per_million_requests: 0.20,
+            s3_usd_per_thousand_puts: 0.005,
+            s3_usd_per_gb_month: 0.023,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SweepCostEstimate {
+    pub total_points: usize,
+    pub shard_count: usize,
+    pub max_points_per_shard: usize,
+    /// Parent invocation plus one child invocation per shard.
+    pub lambda_invocations: usize,
+    pub total_lambda_seconds: f64,
+    /// Duration of the largest shard; the sweep cannot finish faster than this.
+    pub longest_shard_seconds: f64,
+    pub s3_objects: u64,
+    pub s3_bytes: u64,
+    pub lambda_cost_usd: f64,
+    pub s3_request_cost_usd: f64,
+    pub s3_storage_usd_per_month: f64,
+    /// Lambda and S3 request cost plus one month of storage.
+    pub total_cost_usd: f64,
+    pub warnings: Vec<String>,
+}
+
+/// Estimate duration, storage and cost of running `request` with the given per-run timings.
+pub fn estimate_sweep(
+    request: &NormalizedSweepRequest,
+    benchmark: &RunBenchmark,
+    model: &CostModel,
+) -> Result<SweepCostEstimate, ValidationError> {
+    if !benchmark.seconds_per_run.is_finite() || benchmark.seconds_per_run <= 0.0 {
+        return Err(ValidationError::new(
+            "benchmark.seconds_per_run must be a positive number",
+        ));
+    }
+
+    let plan = compute_shard_plan(request)?;
+    let shard_count = plan.len();
+    let max_points_per_shard = plan
+        .iter()
+        .map(|assignment| assignment.end_index_exclusive - assignment.start_index)
+        .max()
+        .unwrap_or(0);
+    let total_points = request.total_points;
+
+    let total_lambda_seconds = total_points as f64 * benchmark.seconds_per_run
+        + shard_count as f64 * model.shard_overhead_seconds;
+    let longest_shard_seconds =
+        max_points_per_shard as f64 * benchmark.seconds_per_run + model.shard_overhead_seconds;
+    let lambda_invocations = shard_count + 1;
+
+    let s3_objects = total_points as u64 * OBJECTS_PER_POINT
+        + shard_count as u64 * OBJECTS_PER_SHARD
+        + OBJECTS_PER_RUN;
+    let s3_bytes = total_points as u64 * benchmark.output_bytes_per_run;
+
+    let gb_seconds = total_lambda_seconds * model.lambda_memory_mb as f64 / 1024.0;
+    let lambda_cost_usd = gb_seconds * model.lambda_usd_per_gb_second
+        + lambda_invocations as f64 / 1_000_000.0 * model.lambda_usd_per_million_requests;
+    let s3_request_cost_usd = s3_objects as f64 / 1_000.0 * model.s3_usd_per_thousand_puts;
+    let s3_storage_usd_per_month =
+        s3_bytes as f64 / (1024.0 * 1024.0 * 1024.0) * model.s3_usd_per_gb_month;
+
+    let mut warnings = Vec::new();
+    if longest_shard_seconds > model.lambda_timeout_seconds {
+        let seconds_per_shard = (model.lambda_timeout_seconds - model.shard_overhead_seconds)
+            .max(benchmark.seconds_per_run);
+        let points_per_shard = ((seconds_per_shard / benchmark.seconds_per_run) as usize).max(1);
+        warnings.push(format!(
+            "Largest shard ({max_points_per_shard} points) needs ~{longest_shard_seconds:.0}s, above the {:.0}s Lambda timeout; use shard_size <= {points_per_shard} ({} shards)",
+            model.lambda_timeout_seconds,
+            total_points.div_ceil(points_per_shard)
+        ));
+    }
+    if benchmark.output_bytes_per_run == 0 {
+        warnings.push(
+            "benchmark.output_bytes_per_run not set; S3 storage is not estimated".to_string(),
+        );
+    }
+
+    Ok(SweepCostEstimate {
+        total_points,
+        shard_count,
+        max_points_per_shard,
+        lambda_invocations,
+        total_lambda_seconds,
+        longest_shard_seconds,
+        s3_objects,
+        s3_bytes,
+        lambda_cost_usd,
+        s3_request_cost_usd,
+        s3_storage_usd_per_month,
+        total_cost_usd: lambda_cost_usd + s3_request_cost_usd + s3_storage_usd_per_month,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::contract::{normalize_request, SweepRequest};
+
+    fn request(shard_count: usize) -> NormalizedSweepRequest {
+        normalize_request(SweepRequest {
+            run_id: "estimate-1".to_string(),
+            dimensions: BTreeMap::from([(
+                "num_riders".to_string(),
+                (1..=10).map(Value::from).collect(),
+            )]),
+            shard_count: Some(shard_count),
+            shard_size: None,
+            max_shards: 10,
+            seed: 1,
+            failure_injection_shards: Vec::new(),
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
+        })
+        .expect("request should pass")
+    }
+
+    #[test]
+    fn estimates_duration_objects_and_cost_from_shard_plan() {
+        let benchmark = RunBenchmark {
+            seconds_per_run: 3.0,
+            output_bytes_per_run: 1024 * 1024,
+        };
+        let model = CostModel {
+            shard_overhead_seconds: 1.0,
+            ..CostModel::default()
+        };
+
+        let estimate = estimate_sweep(&request(3), &benchmark, &model).unwrap();
+        assert_eq!(estimate.shard_count, 3);
+        assert_eq!(estimate.max_points_per_shard, 4);
+        assert_eq!(estimate.lambda_invocations, 4);
+        assert_eq!(estimate.total_lambda_seconds, 33.0);
+        assert_eq!(estimate.longest_shard_seconds, 13.0);
+        assert_eq!(estimate.s3_objects, 10 * 5 + 3 * 2 + 1);
+        assert_eq!(estimate.s3_bytes, 10 * 1024 * 1024);
+        assert!(estimate.lambda_cost_usd > 0.0);
+        assert!(estimate.warnings.is_empty());
+    }
+
+    #[test]
+    fn warns_when_a_shard_exceeds_the_lambda_timeout() {
+        let benchmark = RunBenchmark {
+            seconds_per_run: 400.0,
+            output_bytes_per_run: 0,
+        };
+
+        let estimate = estimate_sweep(&request(2), &benchmark, &CostModel::default()).unwrap();
+        assert_eq!(estimate.warnings.len(), 2);
+        assert!(estimate.warnings[0].contains("use shard_size <= 2 (5 shards)"));
+        assert!(estimate_sweep(
+            &request(2),
+            &RunBenchmark {
+                seconds_per_run: 0.0,
+                output_bytes_per_run: 0
+            },
+            &CostModel::default()
+        )
+        .is_err());
+    }
+}
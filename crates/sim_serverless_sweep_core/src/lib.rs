@@ -5,5 +5,8 @@
 //! See `crates/sim_serverless_sweep_core/README.md` for ownership boundaries.
 
 pub mod contract;
+pub mod estimate;
+pub mod pacing;
 pub mod sharding;
 pub mod storage_keys;
+pub mod validation;
@@ -0,0 +1,149 @@
+//! Dispatch pacing for shard messages.
+//!
+//! The parent runs behind API Gateway and cannot wait while a large sweep trickles onto the
+//! queue, so pacing is expressed as a per-shard queue delay instead: [`release_offsets`]
+//! combines concurrency waves (`max_in_flight_shards`) with a token bucket on the enqueue
+//! rate, and the parent sends each shard with its offset as the message delay.
+
+use serde::{Deserialize, Serialize};
+
+/// Longest delay a queue message can carry (SQS `DelaySeconds`).
+pub const MAX_QUEUE_DELAY_SECONDS: u32 = 900;
+pub const DEFAULT_WAVE_INTERVAL_SECONDS: u32 = 60;
+pub const DEFAULT_ENQUEUE_BURST: usize = 10;
+
+/// Request-level pacing controls. The default releases every shard immediately.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DispatchPacing {
+    /// Shards released per wave; each later wave is delayed by `wave_interval_seconds`, so
+    /// about this many shards run at once when a shard finishes within one interval.
+    #[serde(default)]
+    pub max_in_flight_shards: Option<usize>,
+    #[serde(default = "default_wave_interval_seconds")]
+    pub wave_interval_seconds: u32,
+    /// Token bucket refill rate: sustained shards released per second.
+    #[serde(default)]
+    pub max_enqueue_per_second: Option<f64>,
+    /// Token bucket capacity: shards released at once before the rate applies.
+    #[serde(default = "default_enqueue_burst")]
+    pub enqueue_burst: usize,
+}
+
+impl Default for DispatchPacing {
+    fn default() -> Self {
+        Self {
+            max_in_flight_shards: None,
+            wave_interval_seconds: DEFAULT_WAVE_INTERVAL_SECONDS,
+            max_enqueue_per_second: None,
+            enqueue_burst: DEFAULT_ENQUEUE_BURST,
+        }
+    }
+}
+
+fn default_wave_interval_seconds() -> u32 {
+    DEFAULT_WAVE_INTERVAL_SECONDS
+}
+
+fn default_enqueue_burst() -> usize {
+    DEFAULT_ENQUEUE_BURST
+}
+
+impl DispatchPacing {
+    pub fn is_unpaced(&self) -> bool {
+        self.max_in_flight_shards.is_none() && self.max_enqueue_per_second.is_none()
+    }
+}
+
+/// Token bucket that starts full; each shard takes one token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenBucket {
+    rate_per_second: f64,
+    capacity: usize,
+    taken: usize,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_second: f64, capacity: usize) -> Self {
+        Self {
+            rate_per_second,
+            capacity: capacity.max(1),
+            taken: 0,
+        }
+    }
+
+    /// Seconds after the start at which the next token is available, then take it.
+    pub fn take(&mut self) -> f64 {
+        let waiting = (self.taken + 1).saturating_sub(self.capacity);
+        self.taken += 1;
+        waiting as f64 / self.rate_per_second
+    }
+}
+
+/// Queue delay (whole seconds, rounded up) for each of `shard_count` shards in dispatch
+/// order. Offsets past [`MAX_QUEUE_DELAY_SECONDS`] are returned as computed; callers must
+/// reject such plans (see [`dispatch_window_seconds`]).
+pub fn release_offsets(pacing: &DispatchPacing, shard_count: usize) -> Vec<u32> {
+    let mut bucket = pacing
+        .max_enqueue_per_second
+        .map(|rate| TokenBucket::new(rate, pacing.enqueue_burst));
+
+    (0..shard_count)
+        .map(|index| {
+            let wave_offset = pacing
+                .max_in_flight_shards
+                .map(|in_flight| (index / in_flight.max(1)) as f64)
+                .unwrap_or(0.0)
+                * f64::from(pacing.wave_interval_seconds);
+            let bucket_offset = bucket.as_mut().map(TokenBucket::take).unwrap_or(0.0);
+            wave_offset.max(bucket_offset).ceil() as u32
+        })
+        .collect()
+}
+
+/// Delay of the last shard released, i.e. how long dispatch is spread over.
+pub fn dispatch_window_seconds(pacing: &DispatchPacing, shard_count: usize) -> u32 {
+    release_offsets(pacing, shard_count)
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_pacing_releases_everything_immediately() {
+        let pacing = DispatchPacing::default();
+        assert!(pacing.is_unpaced());
+        assert_eq!(release_offsets(&pacing, 4), vec![0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn waves_delay_shards_beyond_the_in_flight_budget() {
+        let pacing = DispatchPacing {
+            max_in_flight_shards: Some(2),
+            wave_interval_seconds: 30,
+            ..DispatchPacing::default()
+        };
+        assert_eq!(release_offsets(&pacing, 5), vec![0, 0, 30, 30, 60]);
+        assert_eq!(dispatch_window_seconds(&pacing, 5), 60);
+    }
+
+    #[test]
+    fn token_bucket_limits_enqueue_rate_after_burst() {
+        let pacing = DispatchPacing {
+            max_enqueue_per_second: Some(0.5),
+            enqueue_burst: 2,
+            ..DispatchPacing::default()
+        };
+        assert_eq!(release_offsets(&pacing, 5), vec![0, 0, 2, 4, 6]);
+
+        let combined = DispatchPacing {
+            max_in_flight_shards: Some(4),
+            wave_interval_seconds: 10,
+            ..pacing
+        };
+        assert_eq!(release_offsets(&combined, 5), vec![0, 0, 2, 4, 10]);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::contract::{NormalizedSweepRequest, ShardAssignment, ValidationError};
+use crate::contract::{
+    shard_idempotency_key, ChildShardPayload, NormalizedSweepRequest, ShardAssignment,
+    ValidationError,
+};
 
 pub fn compute_shard_plan(
     request: &NormalizedSweepRequest,
@@ -47,6 +50,45 @@ pub fn compute_shard_plan(
     Ok(assignments)
 }
 
+/// Assignment of one shard in the plan for `request`, as the parent dispatched it.
+pub fn shard_assignment(
+    request: &NormalizedSweepRequest,
+    shard_id: usize,
+) -> Result<ShardAssignment, ValidationError> {
+    let plan = compute_shard_plan(request)?;
+    let shard_count = plan.len();
+    plan.into_iter()
+        .find(|assignment| assignment.shard_id == shard_id)
+        .ok_or_else(|| {
+            ValidationError::new(format!(
+                "shard_id {shard_id} is out of range (plan has {shard_count} shards)"
+            ))
+        })
+}
+
+/// Child payload for one shard; the parent dispatches exactly this payload, so rebuilding it
+/// from the original request reproduces the shard's parameter points and seeds.
+pub fn shard_payload(
+    request: &NormalizedSweepRequest,
+    assignment: &ShardAssignment,
+    run_date: Option<String>,
+) -> ChildShardPayload {
+    let mut payload = ChildShardPayload {
+        run_id: request.run_id.clone(),
+        run_date,
+        dimensions: request.dimensions.clone(),
+        total_points: request.total_points,
+        shard_id: assignment.shard_id,
+        start_index: assignment.start_index,
+        end_index_exclusive: assignment.end_index_exclusive,
+        seed: request.seed,
+        failure_injection_shards: request.failure_injection_shards.clone(),
+        idempotency_key: None,
+    };
+    payload.idempotency_key = Some(shard_idempotency_key(&payload));
+    payload
+}
+
 fn validate_assignments(
     total_points: usize,
     assignments: &[ShardAssignment],
@@ -103,6 +145,9 @@ mod tests {
             max_shards: 10,
             seed: 42,
             failure_injection_shards: Vec::new(),
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
 
         let normalized = normalize_request(request).expect("request should pass");
@@ -117,6 +162,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shard_payload_matches_planned_assignment() {
+        let request = SweepRequest {
+            run_id: "reproduce-001".to_string(),
+            dimensions: BTreeMap::from([(
+                "num_riders".to_string(),
+                vec![Value::from(10), Value::from(20), Value::from(30)],
+            )]),
+            shard_count: Some(2),
+            shard_size: None,
+            max_shards: 10,
+            seed: 9,
+            failure_injection_shards: vec![1],
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
+        };
+
+        let normalized = normalize_request(request).expect("request should pass");
+        let assignment = shard_assignment(&normalized, 1).expect("shard should exist");
+        assert_eq!(assignment, compute_shard_plan(&normalized).unwrap()[1]);
+
+        let payload = shard_payload(&normalized, &assignment, None);
+        assert_eq!(payload.run_id, "reproduce-001");
+        assert_eq!(payload.start_index, 2);
+        assert_eq!(payload.end_index_exclusive, 3);
+        assert_eq!(payload.seed, 9);
+        assert_eq!(payload.failure_injection_shards, vec![1]);
+        // Same shard, same token regardless of the dispatch date
+        assert_eq!(
+            payload.idempotency_key,
+            shard_payload(&normalized, &assignment, Some("2026-02-14".to_string())).idempotency_key
+        );
+        assert_ne!(
+            payload.idempotency_key,
+            shard_payload(
+                &normalized,
+                &shard_assignment(&normalized, 0).unwrap(),
+                None
+            )
+            .idempotency_key
+        );
+
+        let error = shard_assignment(&normalized, 2).expect_err("shard should not exist");
+        assert_eq!(
+            error.message(),
+            "shard_id 2 is out of range (plan has 2 shards)"
+        );
+    }
+
     #[test]
     fn compute_shard_plan_rejects_excessive_shards() {
         let request = SweepRequest {
@@ -136,6 +231,9 @@ mod tests {
             max_shards: 2,
             seed: 0,
             failure_injection_shards: Vec::new(),
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
         };
 
         let normalized = normalize_request(request).expect("request should pass");
@@ -79,9 +79,46 @@ pub fn trip_data_object_key(
     status: &str,
     shard_id: usize,
     point_index: usize,
+) -> String {
+    trip_data_part_object_key(
+        base_prefix,
+        run_date,
+        run_id,
+        status,
+        shard_id,
+        point_index,
+        0,
+    )
+}
+
+/// Part `part` of a point's chunked trip data; part 0 is [`trip_data_object_key`].
+pub fn trip_data_part_object_key(
+    base_prefix: &str,
+    run_date: &str,
+    run_id: &str,
+    status: &str,
+    shard_id: usize,
+    point_index: usize,
+    part: usize,
 ) -> String {
     format!(
-        "{}/shard_id={shard_id}/point_index={point_index}/part-0.parquet",
+        "{}/shard_id={shard_id}/point_index={point_index}/part-{part}.parquet",
+        partition_prefix(base_prefix, DatasetKind::TripData, run_date, run_id, status,),
+    )
+}
+
+/// Part manifest of a point's trip data. The leading underscore keeps Athena from reading it
+/// as table data.
+pub fn trip_data_manifest_object_key(
+    base_prefix: &str,
+    run_date: &str,
+    run_id: &str,
+    status: &str,
+    shard_id: usize,
+    point_index: usize,
+) -> String {
+    format!(
+        "{}/shard_id={shard_id}/point_index={point_index}/_manifest.json",
         partition_prefix(base_prefix, DatasetKind::TripData, run_date, run_id, status,),
     )
 }
@@ -124,6 +161,25 @@ pub fn success_outcome_object_key(
     )
 }
 
+/// Outcome of a shard that stopped early and was checkpointed for resumption.
+pub fn partial_outcome_object_key(
+    base_prefix: &str,
+    run_date: &str,
+    run_id: &str,
+    shard_id: usize,
+) -> String {
+    format!(
+        "{}/shard_id_partition={shard_id}/part-0.parquet",
+        partition_prefix(
+            base_prefix,
+            DatasetKind::ShardOutcomes,
+            run_date,
+            run_id,
+            "partial",
+        ),
+    )
+}
+
 pub fn error_object_key(
     base_prefix: &str,
     run_date: &str,
@@ -180,6 +236,33 @@ pub fn effective_parameters_object_key(
     )
 }
 
+/// Idempotency marker of a completed shard. Lives outside the `dataset=` tables (leading
+/// underscore) so Athena never scans it.
+pub fn idempotency_marker_object_key(
+    base_prefix: &str,
+    run_date: &str,
+    run_id: &str,
+    shard_id: usize,
+) -> String {
+    format!(
+        "{}/_idempotency/run_date={run_date}/run_id={run_id}/shard_id={shard_id}.json",
+        base_prefix.trim_matches('/'),
+    )
+}
+
+/// Checkpoint of a partially completed shard; kept outside the `dataset=` tables.
+pub fn checkpoint_object_key(
+    base_prefix: &str,
+    run_date: &str,
+    run_id: &str,
+    shard_id: usize,
+) -> String {
+    format!(
+        "{}/_checkpoints/run_date={run_date}/run_id={run_id}/shard_id={shard_id}.json",
+        base_prefix.trim_matches('/'),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +311,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn builds_trip_data_part_and_manifest_keys_under_point_prefix() {
+        let part =
+            trip_data_part_object_key("outcomes", "2026-02-14", "run-123", "success", 2, 11, 3);
+        assert_eq!(
+            part,
+            "outcomes/dataset=trip_data/run_date=2026-02-14/run_id=run-123/status=success/shard_id=2/point_index=11/part-3.parquet"
+        );
+        let manifest =
+            trip_data_manifest_object_key("outcomes", "2026-02-14", "run-123", "success", 2, 11);
+        assert_eq!(
+            manifest,
+            "outcomes/dataset=trip_data/run_date=2026-02-14/run_id=run-123/status=success/shard_id=2/point_index=11/_manifest.json"
+        );
+    }
+
     #[test]
     fn builds_snapshot_counts_key_with_point_partition() {
         let key = snapshot_counts_object_key("outcomes", "2026-02-14", "run-123", "success", 2, 11);
@@ -255,4 +354,25 @@ mod tests {
             "outcomes/dataset=effective_parameters/run_date=2026-02-14/run_id_partition=run-123/status_partition=success/shard_id_partition=2/point_index_partition=11/part-0.parquet"
         );
     }
+
+    #[test]
+    fn builds_idempotency_marker_key_outside_datasets() {
+        let key = idempotency_marker_object_key("outcomes/", "2026-02-14", "run-123", 4);
+        assert_eq!(
+            key,
+            "outcomes/_idempotency/run_date=2026-02-14/run_id=run-123/shard_id=4.json"
+        );
+    }
+
+    #[test]
+    fn builds_partial_outcome_and_checkpoint_keys() {
+        assert_eq!(
+            partial_outcome_object_key("outcomes", "2026-02-14", "run-123", 4),
+            "outcomes/dataset=shard_outcomes/run_date=2026-02-14/run_id_partition=run-123/status_partition=partial/shard_id_partition=4/part-0.parquet"
+        );
+        assert_eq!(
+            checkpoint_object_key("outcomes/", "2026-02-14", "run-123", 4),
+            "outcomes/_checkpoints/run_date=2026-02-14/run_id=run-123/shard_id=4.json"
+        );
+    }
 }
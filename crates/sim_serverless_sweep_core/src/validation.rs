@@ -0,0 +1,535 @@
+//! Manifest validation for incoming parent requests.
+//!
+//! [`validate_manifest`] checks parameter space bounds, shard count limits and storage key
+//! sanity, and ranks every problem as rejectable (the run cannot be accepted) or
+//! auto-correctable (a corrected request is derived and the run proceeds with it).
+
+use std::collections::HashSet;
+
+use crate::contract::{
+    stable_contract_json, SweepRequest, ValidationError, MAX_DIMENSION_VALUES,
+    MAX_TOTAL_PARAMETER_POINTS,
+};
+use crate::pacing::{dispatch_window_seconds, MAX_QUEUE_DELAY_SECONDS};
+
+/// Longest accepted `run_id`; it is embedded in every storage key of the run.
+pub const MAX_RUN_ID_LEN: usize = 128;
+
+/// How a manifest issue is handled. Orders rejectable issues first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    /// The request cannot be accepted as sent.
+    Rejectable,
+    /// The request is accepted after an unambiguous correction.
+    AutoCorrectable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestIssue {
+    pub severity: IssueSeverity,
+    /// Request field the issue refers to (e.g. `run_id`, `dimensions.num_riders`).
+    pub field: String,
+    pub message: String,
+}
+
+impl ManifestIssue {
+    fn rejectable(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Rejectable,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    fn corrected(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: IssueSeverity::AutoCorrectable,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Outcome of [`validate_manifest`]: ranked issues plus the request with every
+/// auto-correctable issue fixed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestValidation {
+    /// Rejectable issues first, then corrections, each in detection order.
+    pub issues: Vec<ManifestIssue>,
+    pub corrected: SweepRequest,
+}
+
+impl ManifestValidation {
+    pub fn is_rejected(&self) -> bool {
+        self.rejections().next().is_some()
+    }
+
+    pub fn rejections(&self) -> impl Iterator<Item = &ManifestIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Rejectable)
+    }
+
+    pub fn corrections(&self) -> impl Iterator<Item = &ManifestIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::AutoCorrectable)
+    }
+
+    /// The corrected request, or an error listing every rejectable issue.
+    pub fn into_request(self) -> Result<SweepRequest, ValidationError> {
+        let rejections: Vec<&str> = self
+            .rejections()
+            .map(|issue| issue.message.as_str())
+            .collect();
+        if rejections.is_empty() {
+            Ok(self.corrected)
+        } else {
+            Err(ValidationError::new(rejections.join("; ")))
+        }
+    }
+}
+
+/// Validate a parent request before normalization and sharding.
+pub fn validate_manifest(request: &SweepRequest) -> ManifestValidation {
+    let mut corrected = request.clone();
+    let mut issues = Vec::new();
+
+    validate_run_id(&mut corrected, &mut issues);
+    let total_points = validate_dimensions(&mut corrected, &mut issues);
+    let shard_count = validate_sharding(&mut corrected, total_points, &mut issues);
+    validate_benchmark(&corrected, &mut issues);
+    validate_pacing(&mut corrected, shard_count, &mut issues);
+
+    issues.sort_by_key(|issue| issue.severity);
+    ManifestValidation { issues, corrected }
+}
+
+fn validate_run_id(request: &mut SweepRequest, issues: &mut Vec<ManifestIssue>) {
+    let trimmed = request.run_id.trim();
+    if trimmed.len() != request.run_id.len() {
+        issues.push(ManifestIssue::corrected(
+            "run_id",
+            "run_id has surrounding whitespace; trimmed",
+        ));
+        request.run_id = trimmed.to_string();
+    }
+
+    let run_id = &request.run_id;
+    if run_id.is_empty() {
+        issues.push(ManifestIssue::rejectable(
+            "run_id",
+            "run_id cannot be empty",
+        ));
+    } else if run_id.len() > MAX_RUN_ID_LEN {
+        issues.push(ManifestIssue::rejectable(
+            "run_id",
+            format!("run_id exceeds {MAX_RUN_ID_LEN} characters"),
+        ));
+    } else if run_id == "." || run_id == ".." || !run_id.chars().all(is_storage_key_char) {
+        issues.push(ManifestIssue::rejectable(
+            "run_id",
+            format!(
+                "run_id '{run_id}' is not a valid storage key segment (use letters, digits, '-', '_' or '.')"
+            ),
+        ));
+    }
+}
+
+fn is_storage_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+/// Total parameter points, or None when the space is invalid.
+fn validate_dimensions(
+    request: &mut SweepRequest,
+    issues: &mut Vec<ManifestIssue>,
+) -> Option<usize> {
+    if request.dimensions.is_empty() {
+        issues.push(ManifestIssue::rejectable(
+            "dimensions",
+            "dimensions cannot be empty",
+        ));
+        return None;
+    }
+
+    let mut valid = true;
+    let mut total_points = 1usize;
+    for (name, values) in request.dimensions.iter_mut() {
+        let field = format!("dimensions.{name}");
+        if name.trim().is_empty() {
+            issues.push(ManifestIssue::rejectable(
+                field,
+                "dimension names must be non-empty strings",
+            ));
+            valid = false;
+            continue;
+        }
+        if values.is_empty() {
+            issues.push(ManifestIssue::rejectable(
+                field,
+                format!("Dimension '{name}' must be a non-empty list"),
+            ));
+            valid = false;
+            continue;
+        }
+
+        let mut seen = HashSet::new();
+        let before = values.len();
+        values.retain(|value| seen.insert(stable_contract_json(value)));
+        if values.len() != before {
+            issues.push(ManifestIssue::corrected(
+                field.clone(),
+                format!(
+                    "Dimension '{name}' has {} duplicate values; removed",
+                    before - values.len()
+                ),
+            ));
+        }
+
+        if values.len() > MAX_DIMENSION_VALUES {
+            issues.push(ManifestIssue::rejectable(
+                field,
+                format!("Dimension '{name}' exceeds MAX_DIMENSION_VALUES={MAX_DIMENSION_VALUES}"),
+            ));
+            valid = false;
+            continue;
+        }
+        total_points = total_points.saturating_mul(values.len());
+    }
+
+    if !valid {
+        return None;
+    }
+    if total_points > MAX_TOTAL_PARAMETER_POINTS {
+        issues.push(ManifestIssue::rejectable(
+            "dimensions",
+            format!(
+                "Parameter space is too large for this deployment (>{MAX_TOTAL_PARAMETER_POINTS} points)"
+            ),
+        ));
+        return None;
+    }
+    Some(total_points)
+}
+
+/// Planned shard count, or None when it cannot be derived.
+fn validate_sharding(
+    request: &mut SweepRequest,
+    total_points: Option<usize>,
+    issues: &mut Vec<ManifestIssue>,
+) -> Option<usize> {
+    if request.max_shards == 0 {
+        issues.push(ManifestIssue::rejectable(
+            "max_shards",
+            "max_shards must be a positive integer",
+        ));
+    }
+    if request.shard_count == Some(0) {
+        issues.push(ManifestIssue::rejectable(
+            "shard_count",
+            "shard_count must be a positive integer",
+        ));
+    }
+    if request.shard_size == Some(0) {
+        issues.push(ManifestIssue::rejectable(
+            "shard_size",
+            "shard_size must be a positive integer",
+        ));
+    }
+    if request.shard_count.is_some() && request.shard_size.is_some() {
+        // compute_shard_plan gives shard_count precedence
+        issues.push(ManifestIssue::corrected(
+            "shard_size",
+            "Both shard_count and shard_size are set; shard_size ignored",
+        ));
+        request.shard_size = None;
+    }
+
+    let shard_count = match (request.shard_count, request.shard_size) {
+        (None, None) => {
+            issues.push(ManifestIssue::rejectable(
+                "shard_count",
+                "Either shard_count or shard_size is required",
+            ));
+            return None;
+        }
+        (Some(0), _) | (_, Some(0)) => return None,
+        (Some(count), _) => {
+            let total_points = total_points?;
+            if count > total_points {
+                issues.push(ManifestIssue::corrected(
+                    "shard_count",
+                    format!(
+                        "shard_count {count} exceeds total points {total_points}; clamped to {total_points}"
+                    ),
+                ));
+                request.shard_count = Some(total_points);
+            }
+            count.min(total_points)
+        }
+        (None, Some(size)) => match total_points {
+            Some(total_points) => total_points.div_ceil(size),
+            None => return None,
+        },
+    };
+
+    if request.max_shards > 0 && shard_count > request.max_shards {
+        issues.push(ManifestIssue::rejectable(
+            "max_shards",
+            format!(
+                "Computed shard count {shard_count} exceeds max_shards={}",
+                request.max_shards
+            ),
+        ));
+    }
+
+    let before = request.failure_injection_shards.len();
+    let failures = &mut request.failure_injection_shards;
+    failures.sort_unstable();
+    failures.dedup();
+    failures.retain(|shard_id| *shard_id < shard_count);
+    if failures.len() != before {
+        issues.push(ManifestIssue::corrected(
+            "failure_injection_shards",
+            format!(
+                "failure_injection_shards had duplicate or out-of-range shard ids (plan has {shard_count} shards); removed"
+            ),
+        ));
+    }
+    Some(shard_count)
+}
+
+fn validate_benchmark(request: &SweepRequest, issues: &mut Vec<ManifestIssue>) {
+    match &request.benchmark {
+        Some(benchmark)
+            if !benchmark.seconds_per_run.is_finite() || benchmark.seconds_per_run <= 0.0 =>
+        {
+            issues.push(ManifestIssue::rejectable(
+                "benchmark.seconds_per_run",
+                "benchmark.seconds_per_run must be a positive number",
+            ));
+        }
+        None if request.estimate_only => {
+            issues.push(ManifestIssue::rejectable(
+                "benchmark",
+                "estimate_only requires benchmark timings",
+            ));
+        }
+        _ => {}
+    }
+}
+
+fn validate_pacing(
+    request: &mut SweepRequest,
+    shard_count: Option<usize>,
+    issues: &mut Vec<ManifestIssue>,
+) {
+    let Some(pacing) = request.pacing.as_mut() else {
+        return;
+    };
+
+    let mut valid = true;
+    if pacing.max_in_flight_shards == Some(0) {
+        issues.push(ManifestIssue::rejectable(
+            "pacing.max_in_flight_shards",
+            "pacing.max_in_flight_shards must be a positive integer",
+        ));
+        valid = false;
+    }
+    if pacing.max_in_flight_shards.is_some() && pacing.wave_interval_seconds == 0 {
+        issues.push(ManifestIssue::rejectable(
+            "pacing.wave_interval_seconds",
+            "pacing.wave_interval_seconds must be positive when max_in_flight_shards is set",
+        ));
+        valid = false;
+    }
+    if pacing
+        .max_enqueue_per_second
+        .is_some_and(|rate| !rate.is_finite() || rate <= 0.0)
+    {
+        issues.push(ManifestIssue::rejectable(
+            "pacing.max_enqueue_per_second",
+            "pacing.max_enqueue_per_second must be a positive number",
+        ));
+        valid = false;
+    }
+    if pacing.enqueue_burst == 0 {
+        issues.push(ManifestIssue::corrected(
+            "pacing.enqueue_burst",
+            "pacing.enqueue_burst is 0; raised to 1",
+        ));
+        pacing.enqueue_burst = 1;
+    }
+
+    let Some(shard_count) = shard_count else {
+        return;
+    };
+    if !valid {
+        return;
+    }
+    let window = dispatch_window_seconds(pacing, shard_count);
+    if window > MAX_QUEUE_DELAY_SECONDS {
+        issues.push(ManifestIssue::rejectable(
+            "pacing",
+            format!(
+                "Pacing spreads {shard_count} shards over {window}s, beyond the {MAX_QUEUE_DELAY_SECONDS}s queue delay limit; raise max_in_flight_shards or max_enqueue_per_second"
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::estimate::RunBenchmark;
+    use crate::pacing::DispatchPacing;
+
+    fn request() -> SweepRequest {
+        SweepRequest {
+            run_id: "run-1".to_string(),
+            dimensions: BTreeMap::from([
+                (
+                    "num_riders".to_string(),
+                    vec![Value::from(100), Value::from(200)],
+                ),
+                ("num_drivers".to_string(), vec![Value::from(20)]),
+            ]),
+            shard_count: Some(2),
+            shard_size: None,
+            max_shards: 10,
+            seed: 7,
+            failure_injection_shards: Vec::new(),
+            benchmark: None,
+            estimate_only: false,
+            pacing: None,
+        }
+    }
+
+    #[test]
+    fn valid_request_has_no_issues() {
+        let validation = validate_manifest(&request());
+        assert!(validation.issues.is_empty());
+        assert_eq!(validation.into_request().unwrap(), request());
+    }
+
+    #[test]
+    fn corrects_unambiguous_issues() {
+        let mut raw = request();
+        raw.run_id = " run-1 ".to_string();
+        raw.dimensions.insert(
+            "num_riders".to_string(),
+            vec![Value::from(100), Value::from(200), Value::from(100)],
+        );
+        raw.shard_count = Some(5);
+        raw.shard_size = Some(1);
+        raw.failure_injection_shards = vec![1, 1, 9];
+
+        let validation = validate_manifest(&raw);
+        assert!(!validation.is_rejected());
+        assert_eq!(validation.corrections().count(), 5);
+
+        let corrected = validation.into_request().unwrap();
+        assert_eq!(corrected.run_id, "run-1");
+        assert_eq!(corrected.dimensions["num_riders"].len(), 2);
+        assert_eq!(corrected.shard_count, Some(2));
+        assert_eq!(corrected.shard_size, None);
+        assert_eq!(corrected.failure_injection_shards, vec![1]);
+    }
+
+    #[test]
+    fn ranks_rejections_before_corrections() {
+        let mut raw = request();
+        raw.run_id = " run/1 ".to_string();
+        raw.shard_size = Some(1);
+        raw.max_shards = 1;
+
+        let validation = validate_manifest(&raw);
+        let severities: Vec<IssueSeverity> = validation
+            .issues
+            .iter()
+            .map(|issue| issue.severity)
+            .collect();
+        assert_eq!(
+            severities,
+            vec![
+                IssueSeverity::Rejectable,
+                IssueSeverity::Rejectable,
+                IssueSeverity::AutoCorrectable,
+                IssueSeverity::AutoCorrectable,
+            ]
+        );
+
+        let error = validation.into_request().unwrap_err();
+        assert_eq!(
+            error.message(),
+            "run_id 'run/1' is not a valid storage key segment (use letters, digits, '-', '_' or '.'); \
+             Computed shard count 2 exceeds max_shards=1"
+        );
+    }
+
+    #[test]
+    fn rejects_parameter_space_bounds() {
+        let mut raw = request();
+        raw.dimensions
+            .insert("commission_rate".to_string(), Vec::new());
+        raw.shard_count = None;
+
+        let validation = validate_manifest(&raw);
+        let fields: Vec<&str> = validation
+            .rejections()
+            .map(|issue| issue.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["dimensions.commission_rate", "shard_count"]);
+    }
+
+    #[test]
+    fn rejects_estimate_without_usable_benchmark() {
+        let mut raw = request();
+        raw.estimate_only = true;
+        let validation = validate_manifest(&raw);
+        assert_eq!(
+            validation.into_request().unwrap_err().message(),
+            "estimate_only requires benchmark timings"
+        );
+
+        raw.benchmark = Some(RunBenchmark {
+            seconds_per_run: f64::NAN,
+            output_bytes_per_run: 0,
+        });
+        assert!(validate_manifest(&raw).is_rejected());
+    }
+
+    #[test]
+    fn validates_pacing_against_queue_delay_limit() {
+        let mut raw = request();
+        raw.pacing = Some(DispatchPacing {
+            max_in_flight_shards: Some(1),
+            wave_interval_seconds: 1000,
+            enqueue_burst: 0,
+            ..DispatchPacing::default()
+        });
+
+        let validation = validate_manifest(&raw);
+        let fields: Vec<&str> = validation
+            .issues
+            .iter()
+            .map(|issue| issue.field.as_str())
+            .collect();
+        assert_eq!(fields, vec!["pacing", "pacing.enqueue_burst"]);
+        assert!(validation.issues[0]
+            .message
+            .contains("spreads 2 shards over 1000s"));
+
+        raw.pacing = Some(DispatchPacing {
+            max_in_flight_shards: Some(1),
+            wave_interval_seconds: 300,
+            ..DispatchPacing::default()
+        });
+        assert!(!validate_manifest(&raw).is_rejected());
+    }
+}
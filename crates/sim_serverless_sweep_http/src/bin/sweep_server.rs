@@ -0,0 +1,167 @@
+//! HTTP server running the sweep parent and child handlers on Cloud Run or as an Azure
+//! Functions custom handler.
+//!
+//! Routes: `POST /sweep-run` accepts a sweep request (the API Gateway contract) and
+//! dispatches shards; `POST /run_shard` executes one pushed shard and answers 5xx on failure
+//! or checkpoint, so the queue redelivers it.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::Utc;
+use serde_json::{json, Value};
+use sim_serverless_sweep_http::blob_store::BlobOutcomeStore;
+use sim_serverless_sweep_http::dispatch::{AzureQueueDispatcher, CloudTasksDispatcher};
+use sim_serverless_sweep_http::push::decode_shard_push;
+use sim_serverless_sweep_lambda::adapters::dispatch::ShardDispatcher;
+use sim_serverless_sweep_lambda::adapters::object_store::OutcomeStore;
+use sim_serverless_sweep_lambda::adapters::parquet_output::ParquetOutputConfig;
+use sim_serverless_sweep_lambda::adapters::parquet_parts::DEFAULT_MAX_PART_BYTES;
+use sim_serverless_sweep_lambda::handlers::child::{
+    handle_child_payload_with_sim_runtime, ChildHandlerConfig,
+};
+use sim_serverless_sweep_lambda::handlers::parent::{
+    handle_parent_event_with_context_export, ApiGatewayResponse, RunContextExportConfig,
+};
+
+struct ServerState {
+    dispatch_target: String,
+    dispatcher: Box<dyn ShardDispatcher + Send + Sync>,
+    results_url: String,
+    prefix: String,
+    max_part_bytes: usize,
+    parquet_output: ParquetOutputConfig,
+    /// Request timeout of the platform; shards checkpoint before it runs out.
+    shard_timeout: Option<Duration>,
+    store: BlobOutcomeStore,
+}
+
+fn required_env(name: &str) -> Result<String, String> {
+    std::env::var(name).map_err(|_| format!("{name} must be configured"))
+}
+
+/// Select the shard transport from `SHARD_DISPATCHER` (`cloud_tasks` or `azure_queue`).
+fn shard_dispatcher() -> Result<(String, Box<dyn ShardDispatcher + Send + Sync>), String> {
+    match required_env("SHARD_DISPATCHER")?.as_str() {
+        "cloud_tasks" => {
+            let dispatcher = CloudTasksDispatcher::new(
+                required_env("SHARD_TASK_QUEUE")?,
+                required_env("SHARD_WORKER_URL")?,
+                std::env::var("SHARD_TASK_SERVICE_ACCOUNT").ok(),
+            );
+            Ok((dispatcher.queue().to_string(), Box::new(dispatcher)))
+        }
+        "azure_queue" => {
+            let dispatcher = AzureQueueDispatcher::new(
+                required_env("SHARD_QUEUE_URL")?,
+                required_env("SHARD_QUEUE_SAS_TOKEN")?,
+            );
+            Ok((dispatcher.queue_url().to_string(), Box::new(dispatcher)))
+        }
+        other => Err(format!(
+            "unsupported SHARD_DISPATCHER '{other}' (expected cloud_tasks or azure_queue)"
+        )),
+    }
+}
+
+impl ServerState {
+    fn from_env() -> Result<Self, String> {
+        let (dispatch_target, dispatcher) = shard_dispatcher()?;
+        let results_url = required_env("SWEEP_RESULTS_URL")?;
+        Ok(Self {
+            dispatch_target,
+            dispatcher,
+            store: BlobOutcomeStore::from_url(&results_url)?,
+            results_url,
+            prefix: std::env::var("SWEEP_RESULTS_PREFIX")
+                .unwrap_or_else(|_| "serverless-sweeps/outcomes".to_string()),
+            max_part_bytes: std::env::var("SWEEP_MAX_PART_BYTES")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_MAX_PART_BYTES),
+            parquet_output: ParquetOutputConfig::from_env()?,
+            shard_timeout: std::env::var("SHARD_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs),
+        })
+    }
+}
+
+async fn sweep_run(State(state): State<Arc<ServerState>>, body: String) -> Response {
+    let response = tokio::task::block_in_place(|| {
+        handle_parent_event_with_context_export(
+            json!({ "body": body }),
+            Some(&state.dispatch_target),
+            state.dispatcher.as_ref(),
+            Some(RunContextExportConfig {
+                prefix: &state.prefix,
+                persist_object: &|key, body| state.store.write_object(key, body),
+            }),
+        )
+    });
+    http_response(response)
+}
+
+async fn run_shard(State(state): State<Arc<ServerState>>, Json(body): Json<Value>) -> Response {
+    let started_at = Instant::now();
+    let (payload, source) = match decode_shard_push(&body) {
+        Ok(value) => value,
+        Err(error) => return (StatusCode::BAD_REQUEST, error).into_response(),
+    };
+
+    let now = Utc::now();
+    let config = ChildHandlerConfig {
+        bucket: state.results_url.clone(),
+        prefix: state.prefix.clone(),
+        run_date: payload
+            .run_date
+            .clone()
+            .unwrap_or_else(|| now.format("%Y-%m-%d").to_string()),
+        event_time: now.to_rfc3339(),
+        max_part_bytes: state.max_part_bytes,
+        parquet_output: state.parquet_output.clone(),
+        deadline: state.shard_timeout.map(|timeout| started_at + timeout),
+    };
+    match tokio::task::block_in_place(|| {
+        handle_child_payload_with_sim_runtime(&payload, &config, &state.store)
+    }) {
+        Ok(success) => Json(source.response_body(json!(success))).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.message).into_response(),
+    }
+}
+
+fn http_response(response: ApiGatewayResponse) -> Response {
+    (
+        StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        [(header::CONTENT_TYPE, "application/json")],
+        response.body,
+    )
+        .into_response()
+}
+
+/// Cloud Run sets `PORT`; Azure Functions custom handlers get `FUNCTIONS_CUSTOMHANDLER_PORT`.
+fn listen_port() -> u16 {
+    ["PORT", "FUNCTIONS_CUSTOMHANDLER_PORT"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok()?.parse().ok())
+        .unwrap_or(8080)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let state = Arc::new(ServerState::from_env()?);
+    let app = Router::new()
+        .route("/sweep-run", post(sweep_run))
+        .route("/run_shard", post(run_shard))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", listen_port())).await?;
+    axum::serve(listener, app).await?;
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! Shard dispatchers for Cloud Tasks and Azure Storage queues.
+//!
+//! Both services support delayed delivery (`scheduleTime`, `visibilitytimeout`), so paced
+//! requests behave as they do with SQS.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Utc;
+use serde_json::{json, Value};
+use sim_serverless_sweep_lambda::adapters::dispatch::ShardDispatcher;
+
+const METADATA_TOKEN_URL: &str =
+    "http://metadata.google.internal/computeMetadata/v1/instance/service-accounts/default/token";
+const CLOUD_TASKS_API: &str = "https://cloudtasks.googleapis.com/v2";
+/// Refresh cached access tokens this long before they expire.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Creates one Cloud Tasks HTTP task per shard, targeting the worker's `/run_shard` route.
+pub struct CloudTasksDispatcher {
+    client: reqwest::Client,
+    /// `projects/<project>/locations/<location>/queues/<queue>`.
+    queue: String,
+    target_url: String,
+    /// Service account the task's OIDC token is minted for, when the worker requires auth.
+    service_account_email: Option<String>,
+    access_token: Mutex<Option<(String, Instant)>>,
+}
+
+impl CloudTasksDispatcher {
+    pub fn new(
+        queue: impl Into<String>,
+        target_url: impl Into<String>,
+        service_account_email: Option<String>,
+    ) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            queue: queue.into(),
+            target_url: target_url.into(),
+            service_account_email,
+            access_token: Mutex::new(None),
+        }
+    }
+
+    pub fn queue(&self) -> &str {
+        &self.queue
+    }
+
+    /// Access token of the runtime service account, from the metadata server.
+    async fn token(&self) -> Result<String, String> {
+        if let Some((token, expires_at)) = self.access_token.lock().expect("poisoned mutex").clone()
+        {
+            if Instant::now() + TOKEN_REFRESH_MARGIN < expires_at {
+                return Ok(token);
+            }
+        }
+
+        let response: Value = self
+            .client
+            .get(METADATA_TOKEN_URL)
+            .header("Metadata-Flavor", "Google")
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|error| format!("failed to fetch access token: {error}"))?
+            .json()
+            .await
+            .map_err(|error| format!("invalid access token response: {error}"))?;
+        let token = response["access_token"]
+            .as_str()
+            .ok_or("access token response has no access_token")?
+            .to_string();
+        let expires_in = response["expires_in"].as_u64().unwrap_or(0);
+        *self.access_token.lock().expect("poisoned mutex") = Some((
+            token.clone(),
+            Instant::now() + Duration::from_secs(expires_in),
+        ));
+        Ok(token)
+    }
+}
+
+/// Cloud Tasks `CreateTask` body delivering `payload` to `target_url` after `delay_seconds`.
+pub fn cloud_task_body(
+    payload: &[u8],
+    delay_seconds: u32,
+    target_url: &str,
+    service_account_email: Option<&str>,
+) -> Value {
+    let mut http_request = json!({
+        "url": target_url,
+        "httpMethod": "POST",
+        "headers": {"Content-Type": "application/json"},
+        "body": BASE64.encode(payload),
+    });
+    if let Some(email) = service_account_email {
+        http_request["oidcToken"] = json!({"serviceAccountEmail": email});
+    }
+    let schedule_time = Utc::now() + chrono::Duration::seconds(i64::from(delay_seconds));
+    json!({
+        "task": {
+            "scheduleTime": schedule_time.to_rfc3339(),
+            "httpRequest": http_request,
+        }
+    })
+}
+
+impl ShardDispatcher for CloudTasksDispatcher {
+    fn dispatch(&self, payload: &[u8], delay_seconds: u32) -> Result<(), String> {
+        let body = cloud_task_body(
+            payload,
+            delay_seconds,
+            &self.target_url,
+            self.service_account_email.as_deref(),
+        );
+        let url = format!("{CLOUD_TASKS_API}/{}/tasks", self.queue);
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let token = self.token().await?;
+                self.client
+                    .post(url)
+                    .bearer_auth(token)
+                    .json(&body)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .map(|_| ())
+                    .map_err(|error| format!("failed to create shard task: {error}"))
+            })
+        })
+    }
+}
+
+/// Puts one message per shard on an Azure Storage queue, authenticated with a SAS token.
+/// A queue-triggered function (`run_shard`) consumes it.
+pub struct AzureQueueDispatcher {
+    client: reqwest::Client,
+    /// `https://<account>.queue.core.windows.net/<queue>`.
+    queue_url: String,
+    sas_token: String,
+}
+
+impl AzureQueueDispatcher {
+    pub fn new(queue_url: impl Into<String>, sas_token: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            queue_url: queue_url.into(),
+            sas_token: sas_token.into().trim_start_matches('?').to_string(),
+        }
+    }
+
+    pub fn queue_url(&self) -> &str {
+        &self.queue_url
+    }
+}
+
+/// Queue message XML. The text is base64 encoded, the Functions host's default encoding.
+pub fn azure_queue_message(payload: &[u8]) -> String {
+    format!(
+        "<QueueMessage><MessageText>{}</MessageText></QueueMessage>",
+        BASE64.encode(payload)
+    )
+}
+
+impl ShardDispatcher for AzureQueueDispatcher {
+    fn dispatch(&self, payload: &[u8], delay_seconds: u32) -> Result<(), String> {
+        let url = format!(
+            "{}/messages?visibilitytimeout={delay_seconds}&{}",
+            self.queue_url.trim_end_matches('/'),
+            self.sas_token
+        );
+        let body = azure_queue_message(payload);
+        let client = self.client.clone();
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                client
+                    .post(url)
+                    .header("Content-Type", "application/xml")
+                    .body(body)
+                    .send()
+                    .await
+                    .and_then(reqwest::Response::error_for_status)
+                    .map(|_| ())
+                    .map_err(|error| format!("failed to enqueue shard message: {error}"))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_task_schedules_delayed_authenticated_delivery() {
+        let before = Utc::now();
+        let body = cloud_task_body(
+            b"{\"shard_id\":3}",
+            120,
+            "https://worker.example/run_shard",
+            Some("sweep@project.iam.gserviceaccount.com"),
+        );
+
+        let request = &body["task"]["httpRequest"];
+        assert_eq!(request["url"], "https://worker.example/run_shard");
+        assert_eq!(
+            BASE64.decode(request["body"].as_str().unwrap()).unwrap(),
+            b"{\"shard_id\":3}"
+        );
+        assert_eq!(
+            request["oidcToken"]["serviceAccountEmail"],
+            "sweep@project.iam.gserviceaccount.com"
+        );
+        let scheduled =
+            chrono::DateTime::parse_from_rfc3339(body["task"]["scheduleTime"].as_str().unwrap())
+                .unwrap();
+        assert!(scheduled >= before + chrono::Duration::seconds(120));
+    }
+
+    #[test]
+    fn azure_queue_message_is_base64_encoded() {
+        assert_eq!(
+            azure_queue_message(b"{}"),
+            "<QueueMessage><MessageText>e30=</MessageText></QueueMessage>"
+        );
+    }
+}
@@ -0,0 +1,12 @@
+//! HTTP adapter for running serverless sweeps on GCP Cloud Run or Azure Functions
+//! (custom handlers).
+//!
+//! Both platforms deliver work as HTTP requests, so one server (`sweep_server`) serves the
+//! parent and child handlers of `sim_serverless_sweep_lambda` unchanged. Shards are
+//! dispatched through Cloud Tasks or an Azure Storage queue and outcomes are written to
+//! Google Cloud Storage or Azure Blob Storage with the same keys as the AWS path.
+//! See `crates/sim_serverless_sweep_http/README.md` for deployment wiring.
+
+pub mod blob_store;
+pub mod dispatch;
+pub mod push;
@@ -0,0 +1,90 @@
+//! Decoding of shards pushed to the worker route.
+
+use serde_json::{json, Value};
+use sim_serverless_sweep_lambda::runtime::contract::ChildShardPayload;
+
+/// Where a pushed shard came from; decides the response shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardPushSource {
+    /// Cloud Tasks (or any caller) posting the raw `ChildShardPayload`.
+    Direct,
+    /// Azure Functions custom handler invocation of a queue-triggered function.
+    AzureFunctions,
+}
+
+impl ShardPushSource {
+    /// Response body for a handled shard. Custom handlers must answer with an invocation
+    /// result; any other caller gets `result` as is.
+    pub fn response_body(self, result: Value) -> Value {
+        match self {
+            Self::Direct => result,
+            Self::AzureFunctions => json!({
+                "Outputs": {},
+                "Logs": [],
+                "ReturnValue": result,
+            }),
+        }
+    }
+}
+
+/// Decode a pushed shard: either a raw payload, or a custom handler invocation
+/// `{"Data": {"<binding>": <message>}, "Metadata": {...}}` whose single queue binding holds the
+/// payload as an object or as JSON text.
+pub fn decode_shard_push(body: &Value) -> Result<(ChildShardPayload, ShardPushSource), String> {
+    let Some(data) = body.get("Data").and_then(Value::as_object) else {
+        let payload = serde_json::from_value(body.clone())
+            .map_err(|error| format!("invalid child shard payload: {error}"))?;
+        return Ok((payload, ShardPushSource::Direct));
+    };
+
+    let message = data
+        .values()
+        .next()
+        .ok_or("Azure Functions invocation has no input binding data")?;
+    let payload = match message {
+        Value::String(text) => serde_json::from_str(text),
+        other => serde_json::from_value(other.clone()),
+    }
+    .map_err(|error| format!("invalid child shard payload: {error}"))?;
+    Ok((payload, ShardPushSource::AzureFunctions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload_json() -> Value {
+        json!({
+            "run_id": "run-1",
+            "dimensions": {"num_drivers": [100]},
+            "total_points": 1,
+            "shard_id": 0,
+            "start_index": 0,
+            "end_index_exclusive": 1,
+            "seed": 7,
+            "failure_injection_shards": []
+        })
+    }
+
+    #[test]
+    fn decodes_direct_and_azure_function_pushes() {
+        let (payload, source) = decode_shard_push(&payload_json()).unwrap();
+        assert_eq!(payload.run_id, "run-1");
+        assert_eq!(source, ShardPushSource::Direct);
+
+        let invocation = json!({
+            "Data": {"shard": payload_json().to_string()},
+            "Metadata": {"DequeueCount": 1}
+        });
+        let (payload, source) = decode_shard_push(&invocation).unwrap();
+        assert_eq!(payload.end_index_exclusive, 1);
+        assert_eq!(source, ShardPushSource::AzureFunctions);
+        assert_eq!(
+            source.response_body(json!({"status": "ok"}))["ReturnValue"]["status"],
+            "ok"
+        );
+
+        let error = decode_shard_push(&json!({"Data": {}})).unwrap_err();
+        assert!(error.contains("no input binding data"));
+    }
+}
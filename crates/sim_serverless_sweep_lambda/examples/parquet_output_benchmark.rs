@@ -0,0 +1,125 @@
+//! Compare shard output size, encode time and estimated Athena scan cost across Parquet
+//! output settings (codec, dictionary encoding, column pruning).
+//!
+//! Runs one simulation, then re-encodes its trip data and snapshot counts with each
+//! `ParquetOutputConfig` the child handler could use:
+//!
+//! ```bash
+//! cargo run -p sim_serverless_sweep_lambda --example parquet_output_benchmark --release
+//! ```
+
+use std::time::Instant;
+
+use bytes::Bytes;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sim_core::scenario::ScenarioParams;
+use sim_experiments::{run_single_simulation_with_artifacts, ParameterSet};
+use sim_serverless_sweep_lambda::adapters::parquet_output::{ParquetCodec, ParquetOutputConfig};
+use sim_serverless_sweep_lambda::adapters::parquet_parts::split_parquet;
+
+/// Athena on-demand price per TB scanned (USD).
+const ATHENA_USD_PER_TB: f64 = 5.0;
+/// Athena bills at least this much per query.
+const ATHENA_MIN_BYTES_PER_QUERY: f64 = 10.0 * 1024.0 * 1024.0;
+/// Sweep size used to project costs.
+const PROJECTED_POINTS: f64 = 10_000.0;
+/// Columns read by the typical funnel/latency queries (see the Athena query files).
+const QUERIED_TRIP_COLUMNS: [&str; 4] = ["state", "requested_at", "matched_at", "pickup_at"];
+/// Trip columns rarely queried; candidates for pruning.
+const PRUNABLE_TRIP_COLUMNS: [&str; 2] = ["pickup_cell", "dropoff_cell"];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let params = ScenarioParams {
+        num_riders: 2_000,
+        num_drivers: 400,
+        ..Default::default()
+    };
+    let parameter_set = ParameterSet::new(params, "parquet_output_benchmark".to_string(), 0, 42);
+    println!("Running simulation (2000 riders, 400 drivers)...");
+    let artifacts = run_single_simulation_with_artifacts(&parameter_set)?;
+
+    let configs = [
+        ("as exported", ParquetOutputConfig::default()),
+        (
+            "uncompressed",
+            ParquetOutputConfig::default().with_codec(ParquetCodec::Uncompressed),
+        ),
+        (
+            "snappy",
+            ParquetOutputConfig::default().with_codec(ParquetCodec::Snappy),
+        ),
+        (
+            "zstd:1",
+            ParquetOutputConfig::default().with_codec(ParquetCodec::Zstd(1)),
+        ),
+        (
+            "zstd:3",
+            ParquetOutputConfig::default().with_codec(ParquetCodec::Zstd(3)),
+        ),
+        (
+            "zstd:9",
+            ParquetOutputConfig::default().with_codec(ParquetCodec::Zstd(9)),
+        ),
+        (
+            "zstd:3, no dictionary",
+            ParquetOutputConfig::default()
+                .with_codec(ParquetCodec::Zstd(3))
+                .with_dictionary(false),
+        ),
+        (
+            "zstd:3, pruned cells",
+            ParquetOutputConfig::default()
+                .with_codec(ParquetCodec::Zstd(3))
+                .with_pruned_columns(&PRUNABLE_TRIP_COLUMNS),
+        ),
+    ];
+
+    println!(
+        "\n{:<24} {:>12} {:>12} {:>10} {:>14} {:>14}",
+        "config", "trips (B)", "snapshots (B)", "encode", "full scan $", "funnel scan $"
+    );
+    for (label, config) in &configs {
+        let started = Instant::now();
+        let trips = encode(&artifacts.trip_data_parquet, config)?;
+        let snapshots = encode(&artifacts.snapshot_counts_parquet, config)?;
+        let elapsed = started.elapsed();
+
+        let queried = column_bytes(&trips, &QUERIED_TRIP_COLUMNS)?;
+        println!(
+            "{:<24} {:>12} {:>12} {:>10.1?} {:>14.4} {:>14.4}",
+            label,
+            trips.len(),
+            snapshots.len(),
+            elapsed,
+            scan_cost_usd((trips.len() + snapshots.len()) as f64 * PROJECTED_POINTS),
+            scan_cost_usd(queried as f64 * PROJECTED_POINTS),
+        );
+    }
+    println!(
+        "\nScan costs are per query over a projected {PROJECTED_POINTS} point sweep at ${ATHENA_USD_PER_TB}/TB \
+         (full scan = all trip + snapshot bytes; funnel scan = trip columns {QUERIED_TRIP_COLUMNS:?})."
+    );
+    Ok(())
+}
+
+fn encode(parquet: &[u8], config: &ParquetOutputConfig) -> Result<Vec<u8>, String> {
+    let mut parts = split_parquet(parquet.to_vec(), usize::MAX, config)?;
+    Ok(parts.remove(0).body)
+}
+
+/// Compressed bytes Athena reads for a query touching only `columns`.
+fn column_bytes(parquet: &[u8], columns: &[&str]) -> Result<i64, Box<dyn std::error::Error>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(parquet.to_vec()))?;
+    Ok(builder
+        .metadata()
+        .row_groups()
+        .iter()
+        .flat_map(|row_group| row_group.columns())
+        .filter(|column| columns.contains(&column.column_path().string().as_str()))
+        .map(|column| column.compressed_size())
+        .sum())
+}
+
+fn scan_cost_usd(bytes: f64) -> f64 {
+    bytes.max(ATHENA_MIN_BYTES_PER_QUERY) / 1e12 * ATHENA_USD_PER_TB
+}
@@ -0,0 +1,80 @@
+//! Shard dispatch transports.
+//!
+//! The parent handler hands every serialized [`ChildShardPayload`] to a [`ShardDispatcher`].
+//! The deployed runtime implements it for SQS and EventBridge (see `sweep_runtime`);
+//! [`ChannelDispatcher`] keeps shards in process so orchestration can be driven end to end
+//! in tests and local runs without AWS.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use crate::runtime::contract::ChildShardPayload;
+use crate::runtime::pacing::MAX_QUEUE_DELAY_SECONDS;
+
+pub trait ShardDispatcher {
+    /// Send one shard payload, to be delivered after `delay_seconds`.
+    fn dispatch(&self, payload: &[u8], delay_seconds: u32) -> Result<(), String>;
+
+    /// Longest delivery delay the transport supports; paced plans beyond it are rejected
+    /// before any shard is sent.
+    fn max_delay_seconds(&self) -> u32 {
+        MAX_QUEUE_DELAY_SECONDS
+    }
+}
+
+/// A shard captured by [`ChannelDispatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchedShard {
+    pub body: Vec<u8>,
+    pub delay_seconds: u32,
+}
+
+impl DispatchedShard {
+    pub fn payload(&self) -> Result<ChildShardPayload, String> {
+        serde_json::from_slice(&self.body)
+            .map_err(|error| format!("invalid child shard payload: {error}"))
+    }
+}
+
+/// In-process dispatcher backed by an `mpsc` channel. Delays are recorded, not waited on.
+pub struct ChannelDispatcher {
+    sender: Sender<DispatchedShard>,
+}
+
+impl ChannelDispatcher {
+    pub fn new() -> (Self, Receiver<DispatchedShard>) {
+        let (sender, receiver) = channel();
+        (Self { sender }, receiver)
+    }
+}
+
+impl ShardDispatcher for ChannelDispatcher {
+    fn dispatch(&self, payload: &[u8], delay_seconds: u32) -> Result<(), String> {
+        self.sender
+            .send(DispatchedShard {
+                body: payload.to_vec(),
+                delay_seconds,
+            })
+            .map_err(|_| "shard channel receiver was dropped".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_dispatcher_delivers_shards_in_order() {
+        let (dispatcher, receiver) = ChannelDispatcher::new();
+        dispatcher.dispatch(b"first", 0).unwrap();
+        dispatcher.dispatch(b"second", 30).unwrap();
+
+        let shards: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(shards.len(), 2);
+        assert_eq!(shards[0].body, b"first");
+        assert_eq!(shards[1].delay_seconds, 30);
+        assert!(shards[0].payload().is_err());
+
+        drop(receiver);
+        assert!(dispatcher.dispatch(b"third", 0).is_err());
+    }
+}
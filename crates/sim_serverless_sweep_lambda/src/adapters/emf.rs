@@ -0,0 +1,128 @@
+//! CloudWatch Embedded Metric Format (EMF) records.
+//!
+//! Lambda ships every stdout line to CloudWatch Logs, and CloudWatch extracts metrics from
+//! lines carrying an `_aws` EMF block, so handlers publish sweep health metrics by printing
+//! one JSON object per event; no agent or `PutMetricData` permission is needed.
+
+use serde_json::{json, Map, Value};
+
+/// CloudWatch namespace for all sweep metrics.
+pub const EMF_NAMESPACE: &str = "RideHailingSweep";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricUnit {
+    Count,
+    CountPerSecond,
+    Milliseconds,
+    Megabytes,
+}
+
+impl MetricUnit {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Count => "Count",
+            Self::CountPerSecond => "Count/Second",
+            Self::Milliseconds => "Milliseconds",
+            Self::Megabytes => "Megabytes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmfMetric {
+    pub name: &'static str,
+    pub unit: MetricUnit,
+    pub value: f64,
+}
+
+impl EmfMetric {
+    pub fn new(name: &'static str, unit: MetricUnit, value: f64) -> Self {
+        Self { name, unit, value }
+    }
+}
+
+/// Build an EMF record. Metrics are aggregated by `Component` only, so dashboards stay cheap;
+/// `properties` (run id, shard id, ...) are kept on the log line for Logs Insights drill-down.
+pub fn emf_record(
+    component: &str,
+    metrics: &[EmfMetric],
+    properties: Value,
+    timestamp_ms: i64,
+) -> Value {
+    let mut record = match properties {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    record.insert(
+        "_aws".to_string(),
+        json!({
+            "Timestamp": timestamp_ms,
+            "CloudWatchMetrics": [{
+                "Namespace": EMF_NAMESPACE,
+                "Dimensions": [["Component"]],
+                "Metrics": metrics
+                    .iter()
+                    .map(|metric| json!({"Name": metric.name, "Unit": metric.unit.as_str()}))
+                    .collect::<Vec<_>>(),
+            }],
+        }),
+    );
+    record.insert("Component".to_string(), Value::from(component));
+    for metric in metrics {
+        record.insert(metric.name.to_string(), Value::from(metric.value));
+    }
+    Value::Object(record)
+}
+
+/// Print an EMF record for `component` to stdout, where Lambda forwards it to CloudWatch.
+pub fn emit_metrics(component: &str, metrics: &[EmfMetric], properties: Value) {
+    println!(
+        "{}",
+        emf_record(
+            component,
+            metrics,
+            properties,
+            chrono::Utc::now().timestamp_millis()
+        )
+    );
+}
+
+/// Peak resident memory of this process in MB (`VmHWM`), or None off Linux.
+pub fn peak_memory_mb() -> Option<f64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: f64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_declares_metrics_and_keeps_properties() {
+        let record = emf_record(
+            "child_handler",
+            &[
+                EmfMetric::new("ShardDuration", MetricUnit::Milliseconds, 1500.0),
+                EmfMetric::new("ShardFailures", MetricUnit::Count, 0.0),
+            ],
+            json!({"run_id": "run-1", "shard_id": 3}),
+            1_700_000_000_000,
+        );
+
+        let directive = &record["_aws"]["CloudWatchMetrics"][0];
+        assert_eq!(record["_aws"]["Timestamp"], 1_700_000_000_000i64);
+        assert_eq!(directive["Namespace"], EMF_NAMESPACE);
+        assert_eq!(directive["Dimensions"], json!([["Component"]]));
+        assert_eq!(
+            directive["Metrics"][0],
+            json!({"Name": "ShardDuration", "Unit": "Milliseconds"})
+        );
+        assert_eq!(record["Component"], "child_handler");
+        assert_eq!(record["ShardDuration"], 1500.0);
+        assert_eq!(record["ShardFailures"], 0.0);
+        assert_eq!(record["run_id"], "run-1");
+        assert_eq!(record["shard_id"], 3);
+    }
+}
@@ -1,2 +1,6 @@
+pub mod dispatch;
+pub mod emf;
 pub mod object_store;
+pub mod parquet_output;
+pub mod parquet_parts;
 pub mod shard_execution;
@@ -1,3 +1,6 @@
 pub trait OutcomeStore {
     fn write_object(&self, key: &str, body: &[u8]) -> Result<(), String>;
+
+    /// Body of an existing object, or None when the key does not exist.
+    fn read_object(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
 }
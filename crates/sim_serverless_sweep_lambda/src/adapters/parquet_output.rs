@@ -0,0 +1,151 @@
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+/// Compression codec for shard Parquet outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetCodec {
+    Uncompressed,
+    Snappy,
+    /// Zstandard at the given level (1-22; 3 is a good size/CPU default).
+    Zstd(i32),
+}
+
+impl ParquetCodec {
+    /// Parse `none`, `snappy`, `zstd` or `zstd:<level>`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim().to_ascii_lowercase();
+        match value.split_once(':') {
+            None if value == "none" || value == "uncompressed" => Ok(Self::Uncompressed),
+            None if value == "snappy" => Ok(Self::Snappy),
+            None if value == "zstd" => Ok(Self::Zstd(3)),
+            Some(("zstd", level)) => level
+                .parse()
+                .map(Self::Zstd)
+                .map_err(|_| format!("Invalid zstd level '{level}'")),
+            _ => Err(format!(
+                "Unsupported parquet codec '{value}' (expected none, snappy, zstd or zstd:<level>)"
+            )),
+        }
+    }
+
+    fn compression(self) -> Result<Compression, String> {
+        match self {
+            Self::Uncompressed => Ok(Compression::UNCOMPRESSED),
+            Self::Snappy => Ok(Compression::SNAPPY),
+            Self::Zstd(level) => ZstdLevel::try_new(level)
+                .map(Compression::ZSTD)
+                .map_err(|error| format!("Invalid zstd level {level}: {error}")),
+        }
+    }
+}
+
+/// Encoding settings for trip data and snapshot count outputs. The default keeps the files
+/// exactly as the simulation exported them; any setting makes the child re-encode them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParquetOutputConfig {
+    pub codec: Option<ParquetCodec>,
+    pub dictionary_enabled: Option<bool>,
+    /// Columns dropped before upload. Athena reads missing columns as NULL, so pruning a
+    /// column nobody queries only saves storage and scan cost.
+    pub pruned_columns: Vec<String>,
+}
+
+impl ParquetOutputConfig {
+    /// Read `SWEEP_PARQUET_CODEC`, `SWEEP_PARQUET_DICTIONARY` and
+    /// `SWEEP_PARQUET_PRUNE_COLUMNS` (comma separated); unset variables keep the default.
+    pub fn from_env() -> Result<Self, String> {
+        let codec = std::env::var("SWEEP_PARQUET_CODEC")
+            .ok()
+            .map(|value| ParquetCodec::parse(&value))
+            .transpose()?;
+        let dictionary_enabled = std::env::var("SWEEP_PARQUET_DICTIONARY")
+            .ok()
+            .map(|value| {
+                value.trim().parse().map_err(|_| {
+                    format!("SWEEP_PARQUET_DICTIONARY must be true or false, got '{value}'")
+                })
+            })
+            .transpose()?;
+        let pruned_columns = std::env::var("SWEEP_PARQUET_PRUNE_COLUMNS")
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|column| !column.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            codec,
+            dictionary_enabled,
+            pruned_columns,
+        })
+    }
+
+    pub fn with_codec(mut self, codec: ParquetCodec) -> Self {
+        self.codec = Some(codec);
+        self
+    }
+
+    pub fn with_dictionary(mut self, enabled: bool) -> Self {
+        self.dictionary_enabled = Some(enabled);
+        self
+    }
+
+    pub fn with_pruned_columns(mut self, columns: &[&str]) -> Self {
+        self.pruned_columns = columns.iter().map(|column| column.to_string()).collect();
+        self
+    }
+
+    /// True when outputs are uploaded exactly as exported.
+    pub fn is_passthrough(&self) -> bool {
+        self.codec.is_none() && self.dictionary_enabled.is_none() && self.pruned_columns.is_empty()
+    }
+
+    pub fn keeps_column(&self, name: &str) -> bool {
+        !self.pruned_columns.iter().any(|pruned| pruned == name)
+    }
+
+    pub fn writer_properties(&self) -> Result<WriterProperties, String> {
+        let mut builder = WriterProperties::builder();
+        if let Some(codec) = self.codec {
+            builder = builder.set_compression(codec.compression()?);
+        }
+        if let Some(enabled) = self.dictionary_enabled {
+            builder = builder.set_dictionary_enabled(enabled);
+        }
+        Ok(builder.build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_codec_names_and_levels() {
+        assert_eq!(ParquetCodec::parse("zstd:9"), Ok(ParquetCodec::Zstd(9)));
+        assert_eq!(ParquetCodec::parse(" ZSTD "), Ok(ParquetCodec::Zstd(3)));
+        assert_eq!(ParquetCodec::parse("snappy"), Ok(ParquetCodec::Snappy));
+        assert_eq!(ParquetCodec::parse("none"), Ok(ParquetCodec::Uncompressed));
+        assert!(ParquetCodec::parse("zstd:x").is_err());
+        assert!(ParquetCodec::parse("gzip").is_err());
+    }
+
+    #[test]
+    fn default_config_is_passthrough() {
+        let config = ParquetOutputConfig::default();
+        assert!(config.is_passthrough());
+        assert!(!config.clone().with_dictionary(false).is_passthrough());
+
+        let pruned = config.with_pruned_columns(&["cancelled_at"]);
+        assert!(!pruned.keeps_column("cancelled_at"));
+        assert!(pruned.keeps_column("trip_entity"));
+        assert!(ParquetOutputConfig::default()
+            .with_codec(ParquetCodec::Zstd(99))
+            .writer_properties()
+            .is_err());
+    }
+}
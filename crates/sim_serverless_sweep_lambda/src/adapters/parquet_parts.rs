@@ -0,0 +1,173 @@
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use bytes::Bytes;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::file::properties::WriterProperties;
+
+use crate::adapters::parquet_output::ParquetOutputConfig;
+
+/// Default upper bound for one uploaded part file; larger outputs are split.
+pub const DEFAULT_MAX_PART_BYTES: usize = 64 * 1024 * 1024;
+
+/// One self-contained Parquet file holding a contiguous slice of the original rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetPart {
+    pub body: Vec<u8>,
+    pub rows: u64,
+}
+
+/// Split a Parquet file into parts of roughly `max_part_bytes` each (by row count, assuming
+/// evenly sized rows), encoding them with `output`. A file within the limit is returned
+/// unchanged as a single part when `output` is passthrough.
+pub fn split_parquet(
+    parquet: Vec<u8>,
+    max_part_bytes: usize,
+    output: &ParquetOutputConfig,
+) -> Result<Vec<ParquetPart>, String> {
+    let bytes = Bytes::from(parquet);
+    let builder = ParquetRecordBatchReaderBuilder::try_new(bytes.clone())
+        .map_err(|error| format!("Failed to read parquet footer: {error}"))?;
+    let total_rows = u64::try_from(builder.metadata().file_metadata().num_rows()).unwrap_or(0);
+
+    let part_count = bytes.len().div_ceil(max_part_bytes.max(1));
+    if output.is_passthrough() && (part_count <= 1 || total_rows <= 1) {
+        return Ok(vec![ParquetPart {
+            body: bytes.to_vec(),
+            rows: total_rows,
+        }]);
+    }
+
+    let kept_columns: Vec<usize> = builder
+        .schema()
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| output.keeps_column(field.name()))
+        .map(|(index, _)| index)
+        .collect();
+    let projection = ProjectionMask::roots(builder.parquet_schema(), kept_columns);
+    let rows_per_part = total_rows.div_ceil(part_count.max(1) as u64).max(1);
+    let reader = builder
+        .with_projection(projection)
+        .with_batch_size(usize::try_from(rows_per_part).unwrap_or(usize::MAX))
+        .build()
+        .map_err(|error| format!("Failed to open parquet reader: {error}"))?;
+    let schema = reader.schema();
+    let properties = output.writer_properties()?;
+
+    let mut parts = Vec::with_capacity(part_count);
+    for batch in reader {
+        let batch = batch.map_err(|error| format!("Failed to read parquet rows: {error}"))?;
+        parts.push(ParquetPart {
+            rows: batch.num_rows() as u64,
+            body: write_part(&batch, schema.clone(), properties.clone())?,
+        });
+    }
+    if parts.is_empty() {
+        // Keep one (empty) part so every point has part-0 with the schema
+        parts.push(ParquetPart {
+            rows: 0,
+            body: write_part(&RecordBatch::new_empty(schema.clone()), schema, properties)?,
+        });
+    }
+    Ok(parts)
+}
+
+fn write_part(
+    batch: &RecordBatch,
+    schema: SchemaRef,
+    properties: WriterProperties,
+) -> Result<Vec<u8>, String> {
+    let mut writer = ArrowWriter::try_new(Vec::new(), schema, Some(properties))
+        .map_err(|error| format!("Failed to create parquet part writer: {error}"))?;
+    writer
+        .write(batch)
+        .map_err(|error| format!("Failed to write parquet part: {error}"))?;
+    writer
+        .into_inner()
+        .map_err(|error| format!("Failed to finish parquet part: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow::array::{ArrayRef, UInt64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::adapters::parquet_output::ParquetCodec;
+
+    fn sample_parquet(rows: u64) -> Vec<u8> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("trip_entity", DataType::UInt64, false),
+            Field::new("cancelled_at", DataType::UInt64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(UInt64Array::from_iter_values(0..rows)) as ArrayRef,
+                Arc::new(UInt64Array::from_iter_values((0..rows).map(|row| row * 7))),
+            ],
+        )
+        .expect("batch should build");
+        write_part(&batch, schema, WriterProperties::default()).expect("parquet should serialize")
+    }
+
+    fn passthrough() -> ParquetOutputConfig {
+        ParquetOutputConfig::default()
+    }
+
+    #[test]
+    fn small_file_is_a_single_unchanged_part() {
+        let parquet = sample_parquet(10);
+        let parts = split_parquet(parquet.clone(), DEFAULT_MAX_PART_BYTES, &passthrough()).unwrap();
+        assert_eq!(
+            parts,
+            vec![ParquetPart {
+                body: parquet,
+                rows: 10
+            }]
+        );
+    }
+
+    #[test]
+    fn large_file_splits_into_readable_parts_preserving_rows() {
+        let parquet = sample_parquet(10_000);
+        let parts = split_parquet(parquet.clone(), parquet.len() / 4, &passthrough()).unwrap();
+
+        assert!(parts.len() >= 4);
+        assert_eq!(parts.iter().map(|part| part.rows).sum::<u64>(), 10_000);
+        for part in &parts {
+            let reread = split_parquet(part.body.clone(), usize::MAX, &passthrough()).unwrap();
+            assert_eq!(reread[0].rows, part.rows);
+        }
+    }
+
+    #[test]
+    fn output_config_reencodes_and_prunes_columns() {
+        let parquet = sample_parquet(1_000);
+        let output = ParquetOutputConfig::default()
+            .with_codec(ParquetCodec::Zstd(3))
+            .with_pruned_columns(&["cancelled_at"]);
+        let parts = split_parquet(parquet, DEFAULT_MAX_PART_BYTES, &output).unwrap();
+
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].rows, 1_000);
+        let reread = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(parts[0].body.clone()))
+            .expect("part should be valid parquet");
+        let columns: Vec<&str> = reread
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect();
+        assert_eq!(columns, vec!["trip_entity"]);
+        // The zstd level is not stored in the file, only the codec
+        assert!(matches!(
+            reread.metadata().row_group(0).column(0).compression(),
+            parquet::basic::Compression::ZSTD(_)
+        ));
+    }
+}
@@ -1,9 +1,13 @@
 use std::collections::BTreeMap;
+use std::ops::ControlFlow;
 
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::clock::LocalTime;
 use sim_core::matching::DEFAULT_ETA_WEIGHT;
-use sim_core::pricing::PricingConfig;
+use sim_core::pricing::surge::SurgeStrategyKind;
+use sim_core::pricing::{CommissionModel, PricingConfig};
 use sim_core::routing::RouteProviderKind;
-use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams};
+use sim_core::scenario::{MatchingAlgorithmType, ScenarioParams, PRESET_NAMES};
 use sim_core::spawner::SpawnWeightingKind;
 use sim_core::traffic::TrafficProfileKind;
 use sim_experiments::{run_single_simulation_with_artifacts, ParameterSet};
@@ -19,7 +23,7 @@ impl ShardExecutor for SimExperimentsShardExecutor {
     fn execute_shard(
         &self,
         payload: &ChildShardPayload,
-        on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<(), String>,
+        on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<ControlFlow<()>, String>,
     ) -> Result<usize, String> {
         if payload.failure_injection_shards.contains(&payload.shard_id) {
             return Err("Injected shard failure for verification".to_string());
@@ -34,7 +38,7 @@ impl ShardExecutor for SimExperimentsShardExecutor {
             let resolved_parameters = resolve_effective_parameters(payload, point_index)?;
             let parameter_set = resolved_parameters.parameter_set;
             let artifacts = run_single_simulation_with_artifacts(&parameter_set)?;
-            on_point_result(ShardPointResult {
+            let control = on_point_result(ShardPointResult {
                 point_index,
                 metrics: artifacts.metrics,
                 trip_data_parquet: artifacts.trip_data_parquet,
@@ -43,12 +47,59 @@ impl ShardExecutor for SimExperimentsShardExecutor {
                 parameter_fingerprint: resolved_parameters.parameter_fingerprint,
             })?;
             points_processed += 1;
+            if control.is_break() {
+                break;
+            }
         }
 
         Ok(points_processed)
     }
 }
 
+/// Parameter sets of every point in the shard, with the seeds the child runtime uses.
+/// Lets a failed shard be re-run locally (see the `reproduce_shard` binary).
+pub fn shard_parameter_sets(payload: &ChildShardPayload) -> Result<Vec<ParameterSet>, String> {
+    if payload.end_index_exclusive > payload.total_points {
+        return Err("end_index_exclusive exceeds total_points".to_string());
+    }
+
+    (payload.start_index..payload.end_index_exclusive)
+        .map(|point_index| {
+            resolve_effective_parameters(payload, point_index)
+                .map(|resolved| resolved.parameter_set)
+        })
+        .collect()
+}
+
+/// The single parameter point described by a scenario file: a JSON object of sweep dimension
+/// values plus an optional integer `seed` (the sweep seed), e.g.
+/// `{"preset": "berlin-weekday-rush", "num_riders": 2000, "seed": 7}`. Used by the local
+/// `determinism_check` binary and the xtask `profile_scenario` binary.
+pub fn scenario_parameter_set(scenario_json: &str) -> Result<ParameterSet, String> {
+    let mut scenario: BTreeMap<String, serde_json::Value> = serde_json::from_str(scenario_json)
+        .map_err(|error| format!("invalid scenario JSON: {error}"))?;
+    let seed = match scenario.remove("seed") {
+        Some(value) => value.as_i64().ok_or("seed must be an integer")?,
+        None => 0,
+    };
+    let payload = ChildShardPayload {
+        run_id: "scenario".to_string(),
+        run_date: None,
+        dimensions: scenario
+            .into_iter()
+            .map(|(name, value)| (name, vec![value]))
+            .collect(),
+        total_points: 1,
+        shard_id: 0,
+        start_index: 0,
+        end_index_exclusive: 1,
+        seed,
+        failure_injection_shards: Vec::new(),
+        idempotency_key: None,
+    };
+    Ok(shard_parameter_sets(&payload)?.remove(0))
+}
+
 struct ResolvedEffectiveParameters {
     parameter_set: ParameterSet,
     effective_parameters_json: String,
@@ -78,6 +129,7 @@ struct ResolvedScenarioParameters {
     min_trip_cells: u32,
     max_trip_cells: u32,
     epoch_ms: i64,
+    time_zone: &'static str,
     pricing_config: PricingConfigPayload,
     simulation_end_time_ms: u64,
     matching_algorithm_type: String,
@@ -90,6 +142,7 @@ struct ResolvedScenarioParameters {
     dynamic_congestion_enabled: bool,
     base_speed_kmh: Option<f64>,
     spawn_weighting: SpawnWeightingKind,
+    surge_strategy: SurgeStrategyKind,
 }
 
 #[derive(serde::Serialize)]
@@ -97,9 +150,13 @@ struct PricingConfigPayload {
     base_fare: f64,
     per_km_rate: f64,
     commission_rate: f64,
+    commission_model: CommissionModel,
     surge_enabled: bool,
     surge_radius_k: u32,
     surge_max_multiplier: f64,
+    tax_rate: f64,
+    regulatory_fee_per_trip: f64,
+    min_hourly_earnings: f64,
 }
 
 fn resolve_effective_parameters(
@@ -150,13 +207,18 @@ fn resolve_scenario_parameters(parameter_set: &ParameterSet) -> ResolvedScenario
         min_trip_cells: params.min_trip_cells,
         max_trip_cells: params.max_trip_cells,
         epoch_ms: params.epoch_ms.unwrap_or(0),
+        time_zone: params.local_time.zone_name(),
         pricing_config: PricingConfigPayload {
             base_fare: pricing.base_fare,
             per_km_rate: pricing.per_km_rate,
             commission_rate: pricing.commission_rate,
+            commission_model: pricing.commission_model,
             surge_enabled: pricing.surge_enabled,
             surge_radius_k: pricing.surge_radius_k,
             surge_max_multiplier: pricing.surge_max_multiplier,
+            tax_rate: pricing.tax_rate,
+            regulatory_fee_per_trip: pricing.regulatory_fee_per_trip,
+            min_hourly_earnings: pricing.min_hourly_earnings,
         },
         simulation_end_time_ms: params
             .simulation_end_time_ms
@@ -176,6 +238,7 @@ fn resolve_scenario_parameters(parameter_set: &ParameterSet) -> ResolvedScenario
         dynamic_congestion_enabled: params.dynamic_congestion_enabled,
         base_speed_kmh: params.base_speed_kmh,
         spawn_weighting: params.spawn_weighting.clone(),
+        surge_strategy: params.surge_strategy.clone(),
     }
 }
 
@@ -187,12 +250,14 @@ fn matching_algorithm_type_name(value: MatchingAlgorithmType) -> &'static str {
     }
 }
 
+/// Dimension selecting a built-in `ScenarioParams::preset` as the base scenario.
+const PRESET_DIMENSION: &str = "preset";
+
 fn parameter_set_for_index(
     payload: &ChildShardPayload,
     index: usize,
     selected_dimensions: &mut BTreeMap<String, serde_json::Value>,
 ) -> Result<ParameterSet, String> {
-    let mut params = ScenarioParams::default();
     let dims: Vec<(&str, &Vec<serde_json::Value>)> = payload
         .dimensions
         .iter()
@@ -208,14 +273,24 @@ fn parameter_set_for_index(
         let value_idx = remainder % radix;
         remainder /= radix;
         let selected_value = values[value_idx].clone();
-        selected_dimensions.insert((*name).to_string(), selected_value.clone());
-        apply_dimension(&mut params, name, &selected_value)?;
+        selected_dimensions.insert((*name).to_string(), selected_value);
     }
 
     if remainder != 0 {
         return Err("Index exceeds dimension product".to_string());
     }
 
+    // A preset replaces the whole base scenario, so it is applied before the other dimensions
+    let mut params = match selected_dimensions.get(PRESET_DIMENSION) {
+        Some(value) => parse_preset(value)?,
+        None => ScenarioParams::default(),
+    };
+    for (name, value) in selected_dimensions.iter() {
+        if name != PRESET_DIMENSION {
+            apply_dimension(&mut params, name, value)?;
+        }
+    }
+
     let seed = payload.seed as u64 ^ (index as u64);
     Ok(ParameterSet::new(
         params,
@@ -240,6 +315,12 @@ fn apply_dimension(
                 .get_or_insert_with(PricingConfig::default);
             pricing.commission_rate = as_f64(value, name)?;
         }
+        "commission_model" => {
+            let pricing = params
+                .pricing_config
+                .get_or_insert_with(PricingConfig::default);
+            pricing.commission_model = parse_commission_model(value)?;
+        }
         "base_fare" => {
             let pricing = params
                 .pricing_config
@@ -270,12 +351,32 @@ fn apply_dimension(
                 .get_or_insert_with(PricingConfig::default);
             pricing.surge_max_multiplier = as_f64(value, name)?;
         }
+        "tax_rate" => {
+            let pricing = params
+                .pricing_config
+                .get_or_insert_with(PricingConfig::default);
+            pricing.tax_rate = as_f64(value, name)?;
+        }
+        "regulatory_fee_per_trip" => {
+            let pricing = params
+                .pricing_config
+                .get_or_insert_with(PricingConfig::default);
+            pricing.regulatory_fee_per_trip = as_f64(value, name)?;
+        }
+        "min_hourly_earnings" => {
+            let pricing = params
+                .pricing_config
+                .get_or_insert_with(PricingConfig::default);
+            pricing.min_hourly_earnings = as_f64(value, name)?;
+        }
+        "surge_strategy" => params.surge_strategy = parse_surge_strategy(value)?,
         "epoch_ms" => {
             params.epoch_ms = match value {
                 serde_json::Value::Null => None,
                 _ => Some(as_i64(value, name)?),
             }
         }
+        "time_zone" => params.local_time = LocalTime::zone(parse_time_zone(value)?),
         "simulation_duration_hours" => {
             params.simulation_end_time_ms = match value {
                 serde_json::Value::Null => None,
@@ -339,6 +440,19 @@ fn as_bool(value: &serde_json::Value, name: &str) -> Result<bool, String> {
         .ok_or_else(|| format!("Dimension '{name}' must be boolean"))
 }
 
+fn parse_preset(value: &serde_json::Value) -> Result<ScenarioParams, String> {
+    let Some(raw) = value.as_str() else {
+        return Err(format!("Dimension '{PRESET_DIMENSION}' must be a string"));
+    };
+
+    ScenarioParams::preset(raw).ok_or_else(|| {
+        format!(
+            "Unsupported preset '{raw}' (expected one of: {})",
+            PRESET_NAMES.join(", ")
+        )
+    })
+}
+
 fn parse_matching_algorithm(value: &serde_json::Value) -> Result<MatchingAlgorithmType, String> {
     let Some(raw) = value.as_str() else {
         return Err("Dimension 'matching_algorithm_type' must be a string".to_string());
@@ -354,6 +468,26 @@ fn parse_matching_algorithm(value: &serde_json::Value) -> Result<MatchingAlgorit
     }
 }
 
+fn parse_commission_model(value: &serde_json::Value) -> Result<CommissionModel, String> {
+    serde_json::from_value(value.clone()).map_err(|err| {
+        format!(
+            "Unsupported commission_model {value} (expected \"Flat\", \"PerZone\", {{\"Tiered\": {{...}}}} or {{\"FixedFee\": {{...}}}}): {err}"
+        )
+    })
+}
+
+fn parse_surge_strategy(value: &serde_json::Value) -> Result<SurgeStrategyKind, String> {
+    let Some(raw) = value.as_str() else {
+        return Err("Dimension 'surge_strategy' must be a string".to_string());
+    };
+
+    SurgeStrategyKind::from_name(raw).ok_or_else(|| {
+        format!(
+            "Unsupported surge_strategy '{raw}' (expected supply_demand_ratio, stepwise, pid_target_wait, or scheduled)"
+        )
+    })
+}
+
 fn parse_traffic_profile(value: &serde_json::Value) -> Result<TrafficProfileKind, String> {
     let Some(raw) = value.as_str() else {
         return Err("Dimension 'traffic_profile' must be a string".to_string());
@@ -368,6 +502,14 @@ fn parse_traffic_profile(value: &serde_json::Value) -> Result<TrafficProfileKind
     }
 }
 
+fn parse_time_zone(value: &serde_json::Value) -> Result<CityTimeZone, String> {
+    let Some(raw) = value.as_str() else {
+        return Err("Dimension 'time_zone' must be a string".to_string());
+    };
+
+    CityTimeZone::from_name(raw)
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -395,6 +537,7 @@ mod tests {
             end_index_exclusive: 1,
             seed: 1,
             failure_injection_shards: vec![],
+            idempotency_key: None,
         }
     }
 
@@ -406,7 +549,7 @@ mod tests {
         let summary = executor
             .execute_shard(&payload, &mut |result| {
                 collected_results.push(result);
-                Ok(())
+                Ok(ControlFlow::Continue(()))
             })
             .expect("shard execution should succeed");
 
@@ -414,6 +557,82 @@ mod tests {
         assert_eq!(collected_results.len(), 1);
     }
 
+    #[test]
+    fn shard_parameter_sets_use_runtime_seeds() {
+        let mut payload = sample_payload();
+        payload.shard_id = 1;
+        payload.start_index = 1;
+        payload.end_index_exclusive = 2;
+
+        let sets = shard_parameter_sets(&payload).expect("parameter sets should resolve");
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].run_id, 1);
+        // Request seed 1 xor point index 1
+        assert_eq!(sets[0].seed, 0);
+        assert_eq!(sets[0].experiment_id, "run-456-shard-1");
+        assert_eq!(
+            sets[0]
+                .params
+                .pricing_config
+                .map(|pricing| pricing.commission_rate),
+            Some(0.2)
+        );
+    }
+
+    #[test]
+    fn commission_model_dimension_deserializes_models() {
+        let mut payload = sample_payload();
+        payload.dimensions.insert(
+            "commission_model".to_string(),
+            vec![serde_json::json!({"FixedFee": {"fee_per_trip": 2.0}})],
+        );
+
+        let sets = shard_parameter_sets(&payload).expect("parameter sets should resolve");
+        assert_eq!(
+            sets[0]
+                .params
+                .pricing_config
+                .map(|pricing| pricing.commission_model),
+            Some(CommissionModel::FixedFee { fee_per_trip: 2.0 })
+        );
+
+        payload.dimensions.insert(
+            "commission_model".to_string(),
+            vec![Value::from("Percentage")],
+        );
+        let error = shard_parameter_sets(&payload).expect_err("unknown model should fail");
+        assert!(error.contains("Unsupported commission_model"));
+    }
+
+    #[test]
+    fn time_zone_dimension_sets_the_city_wall_clock() {
+        let mut payload = sample_payload();
+        payload.dimensions.insert(
+            "time_zone".to_string(),
+            vec![Value::from("America/Sao_Paulo")],
+        );
+
+        let resolved =
+            resolve_effective_parameters(&payload, 0).expect("effective parameters should resolve");
+        assert_eq!(
+            resolved.parameter_set.params.local_time.zone_name(),
+            "America/Sao_Paulo"
+        );
+        let effective_json: Value = serde_json::from_str(&resolved.effective_parameters_json)
+            .expect("effective payload should be valid json");
+        assert_eq!(
+            effective_json["resolved_scenario_parameters"]["time_zone"],
+            Value::from("America/Sao_Paulo")
+        );
+
+        payload.dimensions.insert(
+            "time_zone".to_string(),
+            vec![Value::from("Atlantis/Capital")],
+        );
+        let error = shard_parameter_sets(&payload).expect_err("unknown zone should fail");
+        assert!(error.contains("Unknown IANA time zone 'Atlantis/Capital'"));
+    }
+
     #[test]
     fn rejects_unsupported_dimension_name() {
         let mut payload = sample_payload();
@@ -425,12 +644,37 @@ mod tests {
 
         let executor = SimExperimentsShardExecutor;
         let error = executor
-            .execute_shard(&payload, &mut |_| Ok(()))
+            .execute_shard(&payload, &mut |_| Ok(ControlFlow::Continue(())))
             .expect_err("unsupported dimension should fail");
 
         assert!(error.contains("Unsupported dimension 'unknown_dimension'"));
     }
 
+    #[test]
+    fn preset_dimension_sets_base_scenario_before_other_dimensions() {
+        let mut payload = sample_payload();
+        payload
+            .dimensions
+            .insert("preset".to_string(), vec![Value::from("suburban-sprawl")]);
+
+        let resolved =
+            resolve_effective_parameters(&payload, 0).expect("effective parameters should resolve");
+        let params = resolved.parameter_set.scenario_params();
+        let preset = ScenarioParams::preset("suburban-sprawl").expect("preset should exist");
+        assert_eq!(params.num_riders, 4);
+        assert_eq!(params.match_radius, preset.match_radius);
+        assert_eq!(params.max_trip_cells, preset.max_trip_cells);
+        assert_eq!(params.base_speed_kmh, preset.base_speed_kmh);
+
+        payload
+            .dimensions
+            .insert("preset".to_string(), vec![Value::from("no-such-preset")]);
+        let error = resolve_effective_parameters(&payload, 0)
+            .err()
+            .expect("unknown preset should fail");
+        assert!(error.contains("Unsupported preset 'no-such-preset'"));
+    }
+
     #[test]
     fn effective_parameters_include_resolved_runtime_defaults() {
         let payload = sample_payload();
@@ -0,0 +1,129 @@
+//! Run one seeded scenario several times and fail on the first metric that differs.
+//!
+//! Usage: `determinism_check --scenario <scenario.json> [--runs <n>] [--threads <t1,t2,...>]`.
+//! The scenario file has the same format as for `profile_scenario`. Each run is a separate
+//! process whose ECS compute task pool has the next thread count from `--threads` (cycled;
+//! default `1,2,<available cores>`), so system scheduling differs between runs. Every run's final
+//! metrics are compared with the first run's; the first divergent metric is reported and the
+//! command exits non-zero.
+
+use std::fs;
+use std::process::{exit, Command};
+use std::thread;
+
+use bevy_tasks::{ComputeTaskPool, TaskPoolBuilder};
+use serde_json::Value;
+use sim_experiments::run_single_simulation_with_artifacts;
+use sim_serverless_sweep_lambda::adapters::shard_execution::scenario_parameter_set;
+use sim_serverless_sweep_lambda::cli::{arg_value, required_arg};
+
+const DEFAULT_RUNS: usize = 5;
+
+/// Internal flag: run the scenario once in this process and print its metrics as JSON.
+const CHILD_RUN_ARG: &str = "--child-run";
+
+fn default_thread_counts() -> Vec<usize> {
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+    let mut counts = vec![1, 2, cores];
+    counts.sort_unstable();
+    counts.dedup();
+    counts
+}
+
+/// Run once with `threads` compute threads and print the final metrics.
+fn child_run(scenario_path: &str, threads: usize) -> Result<(), Box<dyn std::error::Error>> {
+    // Must happen before the first schedule run; the pool is process-wide
+    ComputeTaskPool::get_or_init(|| TaskPoolBuilder::new().num_threads(threads).build());
+    let parameter_set = scenario_parameter_set(&fs::read_to_string(scenario_path)?)?;
+    let artifacts = run_single_simulation_with_artifacts(&parameter_set)?;
+    println!("{}", serde_json::to_string(&artifacts.metrics)?);
+    Ok(())
+}
+
+/// Spawn this binary for one run and parse the metrics it prints.
+fn spawn_run(scenario_path: &str, threads: usize) -> Result<Value, Box<dyn std::error::Error>> {
+    let output = Command::new(std::env::current_exe()?)
+        .args([
+            CHILD_RUN_ARG,
+            "--scenario",
+            scenario_path,
+            "--threads",
+            &threads.to_string(),
+        ])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "run with {threads} threads failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// First metric (by name) whose value differs between the two runs, with both values.
+fn first_divergence(reference: &Value, run: &Value) -> Option<(String, Value, Value)> {
+    let (Some(reference), Some(run)) = (reference.as_object(), run.as_object()) else {
+        return Some(("<metrics>".to_string(), reference.clone(), run.clone()));
+    };
+    reference
+        .keys()
+        .chain(run.keys().filter(|key| !reference.contains_key(*key)))
+        .find_map(|metric| {
+            let expected = reference.get(metric).cloned().unwrap_or(Value::Null);
+            let actual = run.get(metric).cloned().unwrap_or(Value::Null);
+            (expected != actual).then(|| (metric.clone(), expected, actual))
+        })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let scenario_path = required_arg("--scenario");
+    if std::env::args().any(|arg| arg == CHILD_RUN_ARG) {
+        let threads = required_arg("--threads").parse()?;
+        return child_run(&scenario_path, threads);
+    }
+
+    let runs: usize = arg_value("--runs")
+        .map(|value| value.parse())
+        .transpose()?
+        .unwrap_or(DEFAULT_RUNS);
+    let thread_counts = match arg_value("--threads") {
+        Some(list) => list
+            .split(',')
+            .map(|count| count.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?,
+        None => default_thread_counts(),
+    };
+    if runs < 2 || thread_counts.is_empty() || thread_counts.contains(&0) {
+        return Err("need --runs >= 2 and positive --threads counts".into());
+    }
+
+    let mut reference: Option<Value> = None;
+    for (run, &threads) in thread_counts.iter().cycle().take(runs).enumerate() {
+        let metrics = spawn_run(&scenario_path, threads)?;
+        let Some(reference) = &reference else {
+            println!(
+                "Run 1/{runs} (threads: {threads}): {} of {} riders completed",
+                metrics["completed_riders"], metrics["total_riders"]
+            );
+            reference = Some(metrics);
+            continue;
+        };
+        if let Some((metric, expected, actual)) = first_divergence(reference, &metrics) {
+            eprintln!(
+                "NON-DETERMINISTIC: run {}/{runs} (threads: {threads}) diverged from run 1 at `{metric}`: {expected} (run 1) vs {actual}",
+                run + 1
+            );
+            exit(1);
+        }
+        println!(
+            "Run {}/{runs} (threads: {threads}): identical to run 1",
+            run + 1
+        );
+    }
+    println!(
+        "{scenario_path} is deterministic across {runs} runs (thread counts {thread_counts:?})"
+    );
+    Ok(())
+}
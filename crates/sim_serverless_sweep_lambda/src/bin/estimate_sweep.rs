@@ -0,0 +1,106 @@
+//! Estimate shard count, Lambda duration, S3 storage and cost of a sweep request before
+//! launching it.
+//!
+//! Usage: `estimate_sweep --request <sweep_request.json> [--seconds-per-run <s>]
+//! [--bytes-per-run <n>] [--benchmark-points <n>]`. Without `--seconds-per-run` the first
+//! `--benchmark-points` points of the request (default 1) are run locally to measure timings;
+//! local CPUs are usually faster than a 1024 MB Lambda, so treat those as a lower bound.
+
+use std::fs;
+use std::time::Instant;
+
+use sim_experiments::run_single_simulation_with_artifacts;
+use sim_serverless_sweep_lambda::adapters::shard_execution::shard_parameter_sets;
+use sim_serverless_sweep_lambda::cli::{arg_value, required_arg};
+use sim_serverless_sweep_lambda::runtime::contract::{
+    normalize_request, NormalizedSweepRequest, ShardAssignment, SweepRequest,
+};
+use sim_serverless_sweep_lambda::runtime::estimate::{estimate_sweep, CostModel, RunBenchmark};
+use sim_serverless_sweep_lambda::runtime::sharding::shard_payload;
+
+/// Run the first `points` points of the request and average their time and output size.
+fn measure_benchmark(
+    request: &NormalizedSweepRequest,
+    points: usize,
+) -> Result<RunBenchmark, Box<dyn std::error::Error>> {
+    let assignment = ShardAssignment {
+        shard_id: 0,
+        start_index: 0,
+        end_index_exclusive: points.clamp(1, request.total_points),
+    };
+    let parameter_sets = shard_parameter_sets(&shard_payload(request, &assignment, None))?;
+
+    let mut total_seconds = 0.0;
+    let mut total_bytes = 0u64;
+    for parameter_set in &parameter_sets {
+        let started = Instant::now();
+        let artifacts = run_single_simulation_with_artifacts(parameter_set)?;
+        let seconds = started.elapsed().as_secs_f64();
+        let bytes =
+            (artifacts.trip_data_parquet.len() + artifacts.snapshot_counts_parquet.len()) as u64;
+        println!(
+            "benchmark point {}: {seconds:.2}s, {bytes} bytes",
+            parameter_set.run_id
+        );
+        total_seconds += seconds;
+        total_bytes += bytes;
+    }
+
+    let runs = parameter_sets.len() as u64;
+    Ok(RunBenchmark {
+        seconds_per_run: total_seconds / runs as f64,
+        output_bytes_per_run: total_bytes / runs,
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let request_path = required_arg("--request");
+    let request: SweepRequest = serde_json::from_str(&fs::read_to_string(&request_path)?)?;
+    let normalized = normalize_request(request)?;
+
+    let benchmark = match arg_value("--seconds-per-run") {
+        Some(seconds) => RunBenchmark {
+            seconds_per_run: seconds.parse()?,
+            output_bytes_per_run: arg_value("--bytes-per-run")
+                .map(|bytes| bytes.parse())
+                .transpose()?
+                .unwrap_or(0),
+        },
+        None => {
+            let points = arg_value("--benchmark-points")
+                .map(|points| points.parse())
+                .transpose()?
+                .unwrap_or(1);
+            measure_benchmark(&normalized, points)?
+        }
+    };
+
+    let estimate = estimate_sweep(&normalized, &benchmark, &CostModel::default())?;
+    println!(
+        "\nRun {}: {} points in {} shards (up to {} points per shard)",
+        normalized.run_id,
+        estimate.total_points,
+        estimate.shard_count,
+        estimate.max_points_per_shard
+    );
+    println!(
+        "Lambda: {} invocations, {:.0}s total, longest shard {:.0}s",
+        estimate.lambda_invocations, estimate.total_lambda_seconds, estimate.longest_shard_seconds
+    );
+    println!(
+        "S3: {} objects, {:.1} MiB",
+        estimate.s3_objects,
+        estimate.s3_bytes as f64 / (1024.0 * 1024.0)
+    );
+    println!(
+        "Cost: ${:.4} Lambda + ${:.4} S3 requests + ${:.4}/month storage = ${:.4}",
+        estimate.lambda_cost_usd,
+        estimate.s3_request_cost_usd,
+        estimate.s3_storage_usd_per_month,
+        estimate.total_cost_usd
+    );
+    for warning in &estimate.warnings {
+        println!("Warning: {warning}");
+    }
+    Ok(())
+}
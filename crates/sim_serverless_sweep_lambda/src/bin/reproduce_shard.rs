@@ -0,0 +1,98 @@
+//! Re-run one serverless shard locally with the exact parameter points and seeds the child
+//! runtime used, for debugging a failed shard outcome.
+//!
+//! Usage: `reproduce_shard --request <sweep_request.json> --run-id <id> --shard-id <n>
+//! [--outcome <shard_outcome.json>]`. The request is the original body posted to the API; the
+//! optional outcome record is checked against the recomputed shard boundaries.
+
+use std::fs;
+use std::process::exit;
+use std::time::Instant;
+
+use sim_experiments::run_single_simulation_with_artifacts;
+use sim_serverless_sweep_lambda::adapters::shard_execution::shard_parameter_sets;
+use sim_serverless_sweep_lambda::cli::{arg_value, required_arg};
+use sim_serverless_sweep_lambda::runtime::contract::{
+    normalize_request, ShardOutcomeRecord, SweepRequest,
+};
+use sim_serverless_sweep_lambda::runtime::sharding::{shard_assignment, shard_payload};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let request_path = required_arg("--request");
+    let run_id = required_arg("--run-id");
+    let shard_id: usize = required_arg("--shard-id").parse()?;
+
+    let mut request: SweepRequest = serde_json::from_str(&fs::read_to_string(&request_path)?)?;
+    // Seeds and shard boundaries do not depend on the run id; it only labels the runs
+    request.run_id = run_id;
+    let normalized = normalize_request(request)?;
+    let assignment = shard_assignment(&normalized, shard_id)?;
+
+    if let Some(outcome_path) = arg_value("--outcome") {
+        let outcome: ShardOutcomeRecord =
+            serde_json::from_str(&fs::read_to_string(&outcome_path)?)?;
+        if outcome.shard_id != shard_id
+            || outcome.start_index != assignment.start_index
+            || outcome.end_index_exclusive != assignment.end_index_exclusive
+        {
+            return Err(format!(
+                "outcome record covers shard {} [{}, {}) but the request plans shard {shard_id} as [{}, {}); is this the original request?",
+                outcome.shard_id,
+                outcome.start_index,
+                outcome.end_index_exclusive,
+                assignment.start_index,
+                assignment.end_index_exclusive
+            )
+            .into());
+        }
+        if let Some(error) = &outcome.error {
+            println!(
+                "Outcome error: {} ({})",
+                error.error_message, error.error_code
+            );
+        }
+    }
+
+    let payload = shard_payload(&normalized, &assignment, None);
+    if payload.failure_injection_shards.contains(&shard_id) {
+        println!("Note: shard {shard_id} is listed in failure_injection_shards; the injected failure is not reproduced locally.");
+    }
+    let parameter_sets = shard_parameter_sets(&payload)?;
+    println!(
+        "Reproducing run {} shard {shard_id}: points [{}, {}) ({} runs)",
+        payload.run_id,
+        assignment.start_index,
+        assignment.end_index_exclusive,
+        parameter_sets.len()
+    );
+
+    let mut failures = 0usize;
+    for parameter_set in &parameter_sets {
+        let started = Instant::now();
+        match run_single_simulation_with_artifacts(parameter_set) {
+            Ok(artifacts) => println!(
+                "point {} seed {}: ok in {:.1?} (completed trips {}, conversion {:.3})",
+                parameter_set.run_id,
+                parameter_set.seed,
+                started.elapsed(),
+                artifacts.metrics.completed_trips,
+                artifacts.metrics.conversion_rate
+            ),
+            Err(error) => {
+                failures += 1;
+                println!(
+                    "point {} seed {}: FAILED in {:.1?}: {error}",
+                    parameter_set.run_id,
+                    parameter_set.seed,
+                    started.elapsed()
+                );
+            }
+        }
+    }
+
+    if failures > 0 {
+        eprintln!("{failures} of {} points failed", parameter_sets.len());
+        exit(1);
+    }
+    Ok(())
+}
@@ -2,7 +2,10 @@ use aws_sdk_s3::primitives::ByteStream;
 use chrono::Utc;
 use lambda_runtime::{service_fn, Error, LambdaEvent};
 use serde_json::{json, Value};
+use sim_serverless_sweep_lambda::adapters::dispatch::ShardDispatcher;
 use sim_serverless_sweep_lambda::adapters::object_store::OutcomeStore;
+use sim_serverless_sweep_lambda::adapters::parquet_output::ParquetOutputConfig;
+use sim_serverless_sweep_lambda::adapters::parquet_parts::DEFAULT_MAX_PART_BYTES;
 use sim_serverless_sweep_lambda::handlers::child::{
     handle_child_payload_with_sim_runtime, ChildHandlerConfig,
 };
@@ -10,7 +13,13 @@ use sim_serverless_sweep_lambda::handlers::parent::{
     handle_parent_event_with_context_export, ApiGatewayResponse, RunContextExportConfig,
 };
 use sim_serverless_sweep_lambda::runtime::contract::ChildShardPayload;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// EventBridge `source` and `detail-type` of shard events; the rule targeting the runtime
+/// Lambda must match both.
+const SHARD_EVENT_SOURCE: &str = "ride-hailing-sweep";
+const SHARD_EVENT_DETAIL_TYPE: &str = "SweepShard";
 
 struct S3OutcomeStore {
     bucket: String,
@@ -38,15 +47,157 @@ impl OutcomeStore for S3OutcomeStore {
             })
         })
     }
+
+    fn read_object(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let bucket = self.bucket.clone();
+        let object_key = key.to_string();
+        let client = self.s3_client.clone();
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                let output = match client
+                    .get_object()
+                    .bucket(bucket)
+                    .key(object_key)
+                    .send()
+                    .await
+                {
+                    Ok(output) => output,
+                    Err(error)
+                        if error
+                            .as_service_error()
+                            .is_some_and(|service_error| service_error.is_no_such_key()) =>
+                    {
+                        return Ok(None);
+                    }
+                    Err(error) => return Err(format!("failed to read object from s3: {error}")),
+                };
+                output
+                    .body
+                    .collect()
+                    .await
+                    .map(|bytes| Some(bytes.into_bytes().to_vec()))
+                    .map_err(|error| format!("failed to read object body from s3: {error}"))
+            })
+        })
+    }
 }
 
-#[derive(Clone)]
-struct RuntimeDependencies {
+struct SqsShardDispatcher {
     queue_url: String,
+    sqs_client: aws_sdk_sqs::Client,
+}
+
+impl ShardDispatcher for SqsShardDispatcher {
+    fn dispatch(&self, payload: &[u8], delay_seconds: u32) -> Result<(), String> {
+        let body = String::from_utf8(payload.to_vec())
+            .map_err(|error| format!("invalid UTF-8 shard payload: {error}"))?;
+        let client = self.sqs_client.clone();
+        let queue_url = self.queue_url.clone();
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                client
+                    .send_message()
+                    .queue_url(queue_url)
+                    .message_body(body)
+                    .delay_seconds(delay_seconds as i32)
+                    .send()
+                    .await
+                    .map(|_| ())
+                    .map_err(|error| format!("failed to enqueue shard message: {error}"))
+            })
+        })
+    }
+}
+
+/// Publishes shards as EventBridge events. Events are delivered immediately, so paced
+/// requests are rejected by the parent before dispatch.
+struct EventBridgeShardDispatcher {
+    event_bus_name: String,
+    eventbridge_client: aws_sdk_eventbridge::Client,
+}
+
+impl ShardDispatcher for EventBridgeShardDispatcher {
+    fn dispatch(&self, payload: &[u8], _delay_seconds: u32) -> Result<(), String> {
+        let detail = String::from_utf8(payload.to_vec())
+            .map_err(|error| format!("invalid UTF-8 shard payload: {error}"))?;
+        let entry = aws_sdk_eventbridge::types::PutEventsRequestEntry::builder()
+            .event_bus_name(self.event_bus_name.clone())
+            .source(SHARD_EVENT_SOURCE)
+            .detail_type(SHARD_EVENT_DETAIL_TYPE)
+            .detail(detail)
+            .build();
+        let client = self.eventbridge_client.clone();
+
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                let output = client
+                    .put_events()
+                    .entries(entry)
+                    .send()
+                    .await
+                    .map_err(|error| format!("failed to publish shard event: {error}"))?;
+                if output.failed_entry_count() > 0 {
+                    let reason = output
+                        .entries()
+                        .iter()
+                        .find_map(|entry| entry.error_message())
+                        .unwrap_or("unknown error");
+                    return Err(format!("failed to publish shard event: {reason}"));
+                }
+                Ok(())
+            })
+        })
+    }
+
+    fn max_delay_seconds(&self) -> u32 {
+        0
+    }
+}
+
+struct RuntimeDependencies {
+    dispatch_target: String,
+    dispatcher: Arc<dyn ShardDispatcher + Send + Sync>,
     bucket: String,
     prefix: String,
+    max_part_bytes: usize,
+    parquet_output: ParquetOutputConfig,
     s3_client: aws_sdk_s3::Client,
-    sqs_client: aws_sdk_sqs::Client,
+}
+
+/// Select the shard transport from `SHARD_DISPATCHER` (`sqs`, the default, or `eventbridge`).
+fn shard_dispatcher(
+    aws_config: &aws_config::SdkConfig,
+) -> Result<(String, Arc<dyn ShardDispatcher + Send + Sync>), Error> {
+    let kind = std::env::var("SHARD_DISPATCHER").unwrap_or_else(|_| "sqs".to_string());
+    match kind.as_str() {
+        "sqs" => {
+            let queue_url = std::env::var("SHARD_QUEUE_URL")
+                .map_err(|_| Error::from("SHARD_QUEUE_URL must be configured"))?;
+            Ok((
+                queue_url.clone(),
+                Arc::new(SqsShardDispatcher {
+                    queue_url,
+                    sqs_client: aws_sdk_sqs::Client::new(aws_config),
+                }),
+            ))
+        }
+        "eventbridge" => {
+            let event_bus_name =
+                std::env::var("SHARD_EVENT_BUS_NAME").unwrap_or_else(|_| "default".to_string());
+            Ok((
+                event_bus_name.clone(),
+                Arc::new(EventBridgeShardDispatcher {
+                    event_bus_name,
+                    eventbridge_client: aws_sdk_eventbridge::Client::new(aws_config),
+                }),
+            ))
+        }
+        other => Err(Error::from(format!(
+            "unsupported SHARD_DISPATCHER '{other}' (expected sqs or eventbridge)"
+        ))),
+    }
 }
 
 async fn handle_request(event: LambdaEvent<Value>) -> Result<Value, Error> {
@@ -54,6 +205,8 @@ async fn handle_request(event: LambdaEvent<Value>) -> Result<Value, Error> {
     let request_id = event.context.request_id.clone();
     let event_kind = if is_sqs_event(&event.payload) {
         "sqs_batch"
+    } else if is_shard_event(&event.payload) {
+        "shard_event"
     } else {
         "api_gateway"
     };
@@ -66,71 +219,64 @@ async fn handle_request(event: LambdaEvent<Value>) -> Result<Value, Error> {
     );
 
     let aws_config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+    let (dispatch_target, dispatcher) = shard_dispatcher(&aws_config)?;
     let deps = RuntimeDependencies {
-        queue_url: std::env::var("SHARD_QUEUE_URL")
-            .map_err(|_| Error::from("SHARD_QUEUE_URL must be configured"))?,
+        dispatch_target,
+        dispatcher,
         bucket: std::env::var("SWEEP_RESULTS_BUCKET")
             .map_err(|_| Error::from("SWEEP_RESULTS_BUCKET must be configured"))?,
         prefix: std::env::var("SWEEP_RESULTS_PREFIX")
             .unwrap_or_else(|_| "serverless-sweeps/outcomes".to_string()),
+        max_part_bytes: std::env::var("SWEEP_MAX_PART_BYTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MAX_PART_BYTES),
+        parquet_output: ParquetOutputConfig::from_env().map_err(Error::from)?,
         s3_client: aws_sdk_s3::Client::new(&aws_config),
-        sqs_client: aws_sdk_sqs::Client::new(&aws_config),
     };
 
-    if is_sqs_event(&event.payload) {
-        let decoded_payloads = match handle_sqs_event(&event.payload, &deps) {
-            Ok(value) => value,
-            Err(error) => {
-                log_runtime_error(
-                    "request_failed",
-                    json!({
-                        "request_id": event.context.request_id,
-                        "event_kind": "sqs_batch",
-                        "duration_ms": started_at.elapsed().as_millis(),
-                        "error": error.to_string(),
-                    }),
-                );
-                return Err(error);
-            }
+    if event_kind != "api_gateway" {
+        let deadline = invocation_deadline(event.context.deadline);
+        let payloads = if event_kind == "sqs_batch" {
+            decode_sqs_payloads(&event.payload)
+        } else {
+            decode_shard_event(&event.payload).map(|payload| vec![payload])
         };
+        let decoded_payloads =
+            match payloads.and_then(|payloads| handle_child_payloads(payloads, &deps, deadline)) {
+                Ok(value) => value,
+                Err(error) => {
+                    log_runtime_error(
+                        "request_failed",
+                        json!({
+                            "request_id": event.context.request_id,
+                            "event_kind": event_kind,
+                            "duration_ms": started_at.elapsed().as_millis(),
+                            "error": error.to_string(),
+                        }),
+                    );
+                    return Err(error);
+                }
+            };
         log_runtime_info(
             "request_completed",
             json!({
                 "request_id": event.context.request_id,
-                "event_kind": "sqs_batch",
+                "event_kind": event_kind,
                 "decoded_payloads": decoded_payloads,
                 "duration_ms": started_at.elapsed().as_millis(),
             }),
         );
         Ok(json!({ "status": "ok" }))
     } else {
-        let sqs_client = deps.sqs_client.clone();
-        let queue_url = deps.queue_url.clone();
         let outcome_store = S3OutcomeStore {
             bucket: deps.bucket.clone(),
             s3_client: deps.s3_client.clone(),
         };
         let response: ApiGatewayResponse = handle_parent_event_with_context_export(
             event.payload,
-            Some(&deps.queue_url),
-            &move |payload| {
-                let body = String::from_utf8(payload.to_vec())
-                    .map_err(|error| format!("invalid UTF-8 shard payload: {error}"))?;
-                let client = sqs_client.clone();
-                let target_queue_url = queue_url.clone();
-                tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current().block_on(async move {
-                        client
-                            .send_message()
-                            .queue_url(target_queue_url)
-                            .message_body(body)
-                            .send()
-                            .await
-                            .map(|_| ())
-                            .map_err(|error| format!("failed to enqueue shard message: {error}"))
-                    })
-                })
-            },
+            Some(&deps.dispatch_target),
+            deps.dispatcher.as_ref(),
             Some(RunContextExportConfig {
                 prefix: &deps.prefix,
                 persist_object: &|key, body| outcome_store.write_object(key, body),
@@ -177,11 +323,39 @@ fn is_sqs_event(event: &Value) -> bool {
         .unwrap_or(false)
 }
 
-fn handle_sqs_event(event: &Value, deps: &RuntimeDependencies) -> Result<usize, Error> {
-    let payloads = decode_sqs_payloads(event)?;
+/// Convert the Lambda context deadline (epoch milliseconds; 0 when unset) into an `Instant`.
+fn invocation_deadline(deadline_epoch_ms: u64) -> Option<Instant> {
+    if deadline_epoch_ms == 0 {
+        return None;
+    }
+    let now_ms = u64::try_from(Utc::now().timestamp_millis()).ok()?;
+    let remaining_ms = deadline_epoch_ms.saturating_sub(now_ms);
+    Some(Instant::now() + Duration::from_millis(remaining_ms))
+}
+
+/// EventBridge event published by [`EventBridgeShardDispatcher`].
+fn is_shard_event(event: &Value) -> bool {
+    event.get("source").and_then(Value::as_str) == Some(SHARD_EVENT_SOURCE)
+        && event.get("detail-type").and_then(Value::as_str) == Some(SHARD_EVENT_DETAIL_TYPE)
+}
+
+fn decode_shard_event(event: &Value) -> Result<ChildShardPayload, Error> {
+    let detail = event
+        .get("detail")
+        .cloned()
+        .ok_or_else(|| Error::from("shard event must include detail"))?;
+    serde_json::from_value(detail)
+        .map_err(|error| Error::from(format!("invalid child shard payload: {error}")))
+}
+
+fn handle_child_payloads(
+    payloads: Vec<ChildShardPayload>,
+    deps: &RuntimeDependencies,
+    deadline: Option<Instant>,
+) -> Result<usize, Error> {
     let payload_count = payloads.len();
     log_runtime_info(
-        "sqs_payloads_decoded",
+        "shard_payloads_decoded",
         json!({
             "payload_count": payload_count,
         }),
@@ -202,6 +376,9 @@ fn handle_sqs_event(event: &Value, deps: &RuntimeDependencies) -> Result<usize,
             prefix: deps.prefix.clone(),
             run_date: resolve_run_date(&payload, &fallback_run_date),
             event_time: event_time.clone(),
+            max_part_bytes: deps.max_part_bytes,
+            parquet_output: deps.parquet_output.clone(),
+            deadline,
         };
         handle_child_payload_with_sim_runtime(&payload, &config, &outcome_store)
             .map_err(|error| Error::from(error.message))?;
@@ -291,6 +468,20 @@ mod tests {
         assert!(!is_sqs_event(&event));
     }
 
+    #[test]
+    fn decodes_shard_event_detail() {
+        let event = json!({
+            "source": SHARD_EVENT_SOURCE,
+            "detail-type": SHARD_EVENT_DETAIL_TYPE,
+            "detail": {"run_id": "x"}
+        });
+        assert!(is_shard_event(&event));
+        assert!(!is_sqs_event(&event));
+
+        let error = decode_shard_event(&event).expect_err("invalid child payload should fail");
+        assert!(error.to_string().contains("invalid child shard payload"));
+    }
+
     #[test]
     fn rejects_record_without_body_string() {
         let event = json!({
@@ -329,6 +520,7 @@ mod tests {
             end_index_exclusive: 1,
             seed: 0,
             failure_injection_shards: Vec::new(),
+            idempotency_key: None,
         };
 
         let resolved = resolve_run_date(&payload, "2026-02-15");
@@ -347,6 +539,7 @@ mod tests {
             end_index_exclusive: 1,
             seed: 0,
             failure_injection_shards: Vec::new(),
+            idempotency_key: None,
         };
 
         let resolved = resolve_run_date(&payload, "2026-02-15");
@@ -0,0 +1,17 @@
+//! Argument helpers shared by the local tooling binaries (`reproduce_shard`, `estimate_sweep`,
+//! `determinism_check`).
+
+use std::process::exit;
+
+/// Value following the flag `name` on the command line, e.g. `--runs 5`.
+pub fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+/// Like [`arg_value`], but prints an error and exits with status 2 when the flag is missing.
+pub fn required_arg(name: &str) -> String {
+    arg_value(name).unwrap_or_else(|| {
+        eprintln!("missing required argument {name}");
+        exit(2);
+    })
+}
@@ -1,14 +1,19 @@
 use std::fs;
-use std::time::Instant;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::runtime::contract::{
-    ChildShardPayload, EffectiveParameterRecord, OutcomeError, ShardOutcomeRecord,
+    shard_idempotency_key, ChildShardPayload, EffectiveParameterRecord, OutcomeError, PartFile,
+    PartManifest, ShardCheckpointRecord, ShardIdempotencyRecord, ShardOutcomeRecord,
     ShardOutputMetadata, EFFECTIVE_PARAMETER_RECORD_SCHEMA_VERSION, OUTCOME_RECORD_SCHEMA_VERSION,
+    PART_MANIFEST_SCHEMA_VERSION,
 };
 use crate::runtime::storage_keys::{
-    effective_parameters_object_key, error_object_key, metrics_object_key,
-    snapshot_counts_object_key, success_outcome_object_key, trip_data_object_key,
+    checkpoint_object_key, effective_parameters_object_key, error_object_key,
+    idempotency_marker_object_key, metrics_object_key, partial_outcome_object_key,
+    snapshot_counts_object_key, success_outcome_object_key, trip_data_manifest_object_key,
+    trip_data_part_object_key,
 };
 use arrow::array::{ArrayRef, StringArray, UInt64Array};
 use arrow::datatypes::{DataType, Field, Schema};
@@ -19,7 +24,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sim_experiments::{export_to_parquet, SimulationResult};
 
+use crate::adapters::emf::{emit_metrics, peak_memory_mb, EmfMetric, MetricUnit};
 use crate::adapters::object_store::OutcomeStore;
+use crate::adapters::parquet_output::ParquetOutputConfig;
+use crate::adapters::parquet_parts::split_parquet;
 use crate::adapters::shard_execution::SimExperimentsShardExecutor;
 
 #[derive(Debug, Clone)]
@@ -32,11 +40,16 @@ pub struct ShardPointResult {
     pub parameter_fingerprint: String,
 }
 
+/// Time kept free after the last point of an invocation for its uploads and outcome record.
+pub const DEADLINE_MARGIN: Duration = Duration::from_secs(30);
+
 pub trait ShardExecutor {
+    /// Run the payload's points in order, passing each result to `on_point_result`; stop
+    /// after a point whose callback returns `ControlFlow::Break`. Returns points run.
     fn execute_shard(
         &self,
         payload: &ChildShardPayload,
-        on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<(), String>,
+        on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<ControlFlow<()>, String>,
     ) -> Result<usize, String>;
 }
 
@@ -46,6 +59,13 @@ pub struct ChildHandlerConfig {
     pub prefix: String,
     pub run_date: String,
     pub event_time: String,
+    /// Trip data larger than this is uploaded as several part files plus a manifest.
+    pub max_part_bytes: usize,
+    /// Codec, dictionary encoding and column pruning for trip data and snapshot counts.
+    pub parquet_output: ParquetOutputConfig,
+    /// Invocation deadline. When the next point would not finish before it, the shard
+    /// checkpoints its progress instead and fails so the queue retries the rest.
+    pub deadline: Option<Instant>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -93,8 +113,84 @@ pub fn handle_child_payload(
         });
     }
 
-    match write_success(payload, config, executor, outcome_store) {
-        Ok((response, points_processed)) => {
+    // At-least-once delivery: a redelivered shard that already succeeded is not recomputed
+    let idempotency_key = payload
+        .idempotency_key
+        .clone()
+        .unwrap_or_else(|| shard_idempotency_key(payload));
+    let marker_key = idempotency_marker_object_key(
+        &config.prefix,
+        &config.run_date,
+        &payload.run_id,
+        payload.shard_id,
+    );
+    if let Some(response) =
+        completed_shard_response(payload, &idempotency_key, &marker_key, outcome_store)?
+    {
+        log_child_info(
+            "shard_already_completed",
+            json!({
+                "run_id": payload.run_id.clone(),
+                "shard_id": payload.shard_id,
+                "idempotency_key": idempotency_key,
+                "outcome_key": response.outcome_key.clone(),
+            }),
+        );
+        emit_shard_metrics(
+            payload,
+            "already_completed",
+            &[EmfMetric::new(
+                "ShardsAlreadyCompleted",
+                MetricUnit::Count,
+                1.0,
+            )],
+        );
+        return Ok(response);
+    }
+
+    let resume_from = checkpoint_resume_index(payload, config, &idempotency_key, outcome_store)?;
+    if resume_from > payload.start_index {
+        log_child_info(
+            "shard_resumed",
+            json!({
+                "run_id": payload.run_id.clone(),
+                "shard_id": payload.shard_id,
+                "resume_from": resume_from,
+            }),
+        );
+    }
+
+    match write_success(payload, resume_from, config, executor, outcome_store) {
+        Ok(ShardProgress::Checkpointed {
+            next_point_index,
+            points_processed,
+        }) => Err(checkpoint_shard(
+            payload,
+            config,
+            ShardCheckpointRecord {
+                idempotency_key,
+                run_id: payload.run_id.clone(),
+                shard_id: payload.shard_id,
+                next_point_index,
+                points_completed: next_point_index - payload.start_index,
+                event_time: config.event_time.clone(),
+            },
+            points_processed,
+            started_at,
+            outcome_store,
+        )),
+        Ok(ShardProgress::Completed(response, points_processed)) => {
+            write_idempotency_marker(
+                payload,
+                config,
+                IdempotencyMarker {
+                    key: &marker_key,
+                    idempotency_key: &idempotency_key,
+                    outcome_key: &response.outcome_key,
+                    points_processed,
+                },
+                outcome_store,
+            );
             let elapsed_ms = started_at.elapsed().as_millis();
             let points_per_second = if elapsed_ms == 0 {
                 points_processed as f64
@@ -112,6 +208,24 @@ pub fn handle_child_payload(
                     "outcome_key": response.outcome_key.clone(),
                 }),
             );
+            emit_shard_metrics(
+                payload,
+                "ok",
+                &[
+                    EmfMetric::new("ShardDuration", MetricUnit::Milliseconds, elapsed_ms as f64),
+                    EmfMetric::new(
+                        "SimulationsPerShard",
+                        MetricUnit::Count,
+                        points_processed as f64,
+                    ),
+                    EmfMetric::new(
+                        "SimulationsPerSecond",
+                        MetricUnit::CountPerSecond,
+                        points_per_second,
+                    ),
+                    EmfMetric::new("ShardFailures", MetricUnit::Count, 0.0),
+                ],
+            );
             Ok(response)
         }
         Err(success_error) => {
@@ -126,6 +240,18 @@ pub fn handle_child_payload(
                     "error": error_message.clone(),
                 }),
             );
+            emit_shard_metrics(
+                payload,
+                "failed",
+                &[
+                    EmfMetric::new(
+                        "ShardDuration",
+                        MetricUnit::Milliseconds,
+                        started_at.elapsed().as_millis() as f64,
+                    ),
+                    EmfMetric::new("ShardFailures", MetricUnit::Count, 1.0),
+                ],
+            );
             Err(ChildHandlerError {
                 message: error_message,
                 failure_key: Some(error_object_key(
@@ -139,6 +265,259 @@ pub fn handle_child_payload(
     }
 }
 
+/// Success response for a shard whose idempotency marker matches `idempotency_key`.
+fn completed_shard_response(
+    payload: &ChildShardPayload,
+    idempotency_key: &str,
+    marker_key: &str,
+    outcome_store: &impl OutcomeStore,
+) -> Result<Option<ChildSuccessResponse>, ChildHandlerError> {
+    let marker = outcome_store
+        .read_object(marker_key)
+        .map_err(|error| ChildHandlerError {
+            message: format!("Failed to read idempotency marker: {error}"),
+            failure_key: None,
+        })?;
+    let Some(marker) = marker else {
+        return Ok(None);
+    };
+
+    let record: ShardIdempotencyRecord = match serde_json::from_slice(&marker) {
+        Ok(value) => value,
+        Err(error) => {
+            // Unreadable marker: recompute, which rewrites the same deterministic keys
+            log_child_error(
+                "idempotency_marker_invalid",
+                json!({
+                    "run_id": payload.run_id.clone(),
+                    "shard_id": payload.shard_id,
+                    "marker_key": marker_key,
+                    "error": error.to_string(),
+                }),
+            );
+            return Ok(None);
+        }
+    };
+
+    if record.idempotency_key != idempotency_key {
+        return Err(ChildHandlerError {
+            message: format!(
+                "Shard {} of run {} already completed with a different idempotency key; refusing to overwrite its results",
+                payload.shard_id, payload.run_id
+            ),
+            failure_key: None,
+        });
+    }
+
+    Ok(Some(ChildSuccessResponse {
+        status: "already_completed".to_string(),
+        shard_id: payload.shard_id,
+        outcome_key: record.outcome_key,
+    }))
+}
+
+struct IdempotencyMarker<'a> {
+    key: &'a str,
+    idempotency_key: &'a str,
+    outcome_key: &'a str,
+    points_processed: usize,
+}
+
+/// Record a completed shard. A failed write is logged but does not fail the shard: its
+/// outputs are already durable, and a redelivery only rewrites the same keys.
+fn write_idempotency_marker(
+    payload: &ChildShardPayload,
+    config: &ChildHandlerConfig,
+    marker: IdempotencyMarker<'_>,
+    outcome_store: &impl OutcomeStore,
+) {
+    let record = ShardIdempotencyRecord {
+        idempotency_key: marker.idempotency_key.to_string(),
+        run_id: payload.run_id.clone(),
+        shard_id: payload.shard_id,
+        outcome_key: marker.outcome_key.to_string(),
+        points_processed: marker.points_processed,
+        event_time: config.event_time.clone(),
+    };
+    let result = serde_json::to_vec(&record)
+        .map_err(|error| error.to_string())
+        .and_then(|body| outcome_store.write_object(marker.key, &body));
+    if let Err(error) = result {
+        log_child_error(
+            "idempotency_marker_persist_failed",
+            json!({
+                "run_id": payload.run_id.clone(),
+                "shard_id": payload.shard_id,
+                "marker_key": marker.key,
+                "error": error,
+            }),
+        );
+    }
+}
+
+/// First point still to run: where a checkpoint with this idempotency key left off, or the
+/// shard start.
+fn checkpoint_resume_index(
+    payload: &ChildShardPayload,
+    config: &ChildHandlerConfig,
+    idempotency_key: &str,
+    outcome_store: &impl OutcomeStore,
+) -> Result<usize, ChildHandlerError> {
+    let key = checkpoint_object_key(
+        &config.prefix,
+        &config.run_date,
+        &payload.run_id,
+        payload.shard_id,
+    );
+    let checkpoint = outcome_store
+        .read_object(&key)
+        .map_err(|error| ChildHandlerError {
+            message: format!("Failed to read shard checkpoint: {error}"),
+            failure_key: None,
+        })?;
+    let Some(checkpoint) = checkpoint else {
+        return Ok(payload.start_index);
+    };
+
+    match serde_json::from_slice::<ShardCheckpointRecord>(&checkpoint) {
+        Ok(record)
+            if record.idempotency_key == idempotency_key
+                && record.next_point_index > payload.start_index
+                && record.next_point_index < payload.end_index_exclusive =>
+        {
+            Ok(record.next_point_index)
+        }
+        Ok(_) => Ok(payload.start_index),
+        Err(error) => {
+            log_child_error(
+                "checkpoint_invalid",
+                json!({
+                    "run_id": payload.run_id.clone(),
+                    "shard_id": payload.shard_id,
+                    "checkpoint_key": key,
+                    "error": error.to_string(),
+                }),
+            );
+            Ok(payload.start_index)
+        }
+    }
+}
+
+/// Persist the checkpoint and a `partial` outcome for a shard that stopped before its
+/// deadline. Always returns an error so the message is retried and resumes.
+fn checkpoint_shard(
+    payload: &ChildShardPayload,
+    config: &ChildHandlerConfig,
+    checkpoint: ShardCheckpointRecord,
+    points_processed: usize,
+    started_at: Instant,
+    outcome_store: &impl OutcomeStore,
+) -> ChildHandlerError {
+    let checkpoint_key = checkpoint_object_key(
+        &config.prefix,
+        &config.run_date,
+        &payload.run_id,
+        payload.shard_id,
+    );
+    let outcome_key = partial_outcome_object_key(
+        &config.prefix,
+        &config.run_date,
+        &payload.run_id,
+        payload.shard_id,
+    );
+    let record = ShardOutcomeRecord {
+        run_id: payload.run_id.clone(),
+        shard_id: payload.shard_id,
+        status: "partial".to_string(),
+        start_index: payload.start_index,
+        end_index_exclusive: payload.end_index_exclusive,
+        event_time: config.event_time.clone(),
+        record_schema: OUTCOME_RECORD_SCHEMA_VERSION.to_string(),
+        output_metadata: Some(ShardOutputMetadata {
+            result_key: first_metrics_key(payload, config),
+            points_processed: checkpoint.points_completed,
+            format: "parquet".to_string(),
+        }),
+        error: None,
+    };
+
+    let persisted = serde_json::to_vec(&checkpoint)
+        .map_err(|error| error.to_string())
+        .and_then(|body| outcome_store.write_object(&checkpoint_key, &body))
+        .and_then(|()| serialize_outcome_parquet(&record))
+        .and_then(|body| outcome_store.write_object(&outcome_key, &body));
+
+    log_child_info(
+        "shard_checkpointed",
+        json!({
+            "run_id": payload.run_id.clone(),
+            "shard_id": payload.shard_id,
+            "points_processed": points_processed,
+            "next_point_index": checkpoint.next_point_index,
+            "duration_ms": started_at.elapsed().as_millis(),
+            "checkpoint_key": checkpoint_key.clone(),
+        }),
+    );
+    emit_shard_metrics(
+        payload,
+        "partial",
+        &[
+            EmfMetric::new(
+                "ShardDuration",
+                MetricUnit::Milliseconds,
+                started_at.elapsed().as_millis() as f64,
+            ),
+            EmfMetric::new(
+                "SimulationsPerShard",
+                MetricUnit::Count,
+                points_processed as f64,
+            ),
+            EmfMetric::new("ShardCheckpoints", MetricUnit::Count, 1.0),
+        ],
+    );
+
+    let message = match persisted {
+        Ok(()) => format!(
+            "Shard {} of run {} stopped at point {} of [{}, {}) before the invocation deadline; checkpointed for retry",
+            payload.shard_id,
+            payload.run_id,
+            checkpoint.next_point_index,
+            payload.start_index,
+            payload.end_index_exclusive
+        ),
+        Err(error) => format!(
+            "Shard {} of run {} stopped before the invocation deadline but its checkpoint could not be persisted: {error}",
+            payload.shard_id, payload.run_id
+        ),
+    };
+    ChildHandlerError {
+        message,
+        failure_key: Some(outcome_key),
+    }
+}
+
+/// Metrics key of the shard's first point, recorded as the outcome's `result_key`.
+fn first_metrics_key(payload: &ChildShardPayload, config: &ChildHandlerConfig) -> String {
+    metrics_object_key(
+        &config.prefix,
+        &config.run_date,
+        &payload.run_id,
+        "success",
+        payload.shard_id,
+        payload.start_index,
+    )
+}
+
+/// How far one invocation got through a shard.
+enum ShardProgress {
+    Completed(ChildSuccessResponse, usize),
+    /// Stopped near the deadline; points from `next_point_index` on have not run.
+    Checkpointed {
+        next_point_index: usize,
+        points_processed: usize,
+    },
+}
+
 pub fn handle_child_payload_with_sim_runtime(
     payload: &ChildShardPayload,
     config: &ChildHandlerConfig,
@@ -149,13 +528,18 @@ pub fn handle_child_payload_with_sim_runtime(
 
 fn write_success(
     payload: &ChildShardPayload,
+    resume_from: usize,
     config: &ChildHandlerConfig,
     executor: &impl ShardExecutor,
     outcome_store: &impl OutcomeStore,
-) -> Result<(ChildSuccessResponse, usize), ChildHandlerError> {
-    let mut first_metrics_key: Option<String> = None;
-
-    let mut on_point_result = |point_result: ShardPointResult| -> Result<(), String> {
+) -> Result<ShardProgress, ChildHandlerError> {
+    let mut remaining = payload.clone();
+    remaining.start_index = resume_from;
+    let mut stopped_at: Option<usize> = None;
+    let mut point_started_at = Instant::now();
+    let mut longest_point = Duration::ZERO;
+
+    let mut on_point_result = |point_result: ShardPointResult| -> Result<ControlFlow<()>, String> {
         let metrics_key = metrics_object_key(
             &config.prefix,
             &config.run_date,
@@ -164,14 +548,6 @@ fn write_success(
             payload.shard_id,
             point_result.point_index,
         );
-        let trip_data_key = trip_data_object_key(
-            &config.prefix,
-            &config.run_date,
-            &payload.run_id,
-            "success",
-            payload.shard_id,
-            point_result.point_index,
-        );
         let snapshot_counts_key = snapshot_counts_object_key(
             &config.prefix,
             &config.run_date,
@@ -200,12 +576,28 @@ fn write_success(
             .write_object(&metrics_key, &parquet_body)
             .map_err(|error| format!("Failed to persist shard metrics artifact: {error}"))?;
 
+        write_trip_data_parts(
+            payload,
+            config,
+            point_result.point_index,
+            point_result.trip_data_parquet,
+            outcome_store,
+        )?;
+
+        let snapshot_counts_body = if config.parquet_output.is_passthrough() {
+            point_result.snapshot_counts_parquet
+        } else {
+            split_parquet(
+                point_result.snapshot_counts_parquet,
+                usize::MAX,
+                &config.parquet_output,
+            )
+            .map_err(|error| format!("Failed to encode snapshot counts: {error}"))?
+            .remove(0)
+            .body
+        };
         outcome_store
-            .write_object(&trip_data_key, &point_result.trip_data_parquet)
-            .map_err(|error| format!("Failed to persist trip data artifact: {error}"))?;
-
-        outcome_store
-            .write_object(&snapshot_counts_key, &point_result.snapshot_counts_parquet)
+            .write_object(&snapshot_counts_key, &snapshot_counts_body)
             .map_err(|error| format!("Failed to persist snapshot counts artifact: {error}"))?;
 
         let effective_parameters_record = EffectiveParameterRecord {
@@ -225,30 +617,33 @@ fn write_success(
             .write_object(&effective_parameters_key, &effective_parameters_body)
             .map_err(|error| format!("Failed to persist effective-parameter artifact: {error}"))?;
 
-        if first_metrics_key.is_none() {
-            first_metrics_key = Some(metrics_key);
+        // Stop while the next point (assumed no faster than the slowest so far) still fits
+        longest_point = longest_point.max(point_started_at.elapsed());
+        point_started_at = Instant::now();
+        let next_point_index = point_result.point_index + 1;
+        let out_of_time = config.deadline.is_some_and(|deadline| {
+            deadline.saturating_duration_since(Instant::now()) < longest_point + DEADLINE_MARGIN
+        });
+        if out_of_time && next_point_index < payload.end_index_exclusive {
+            stopped_at = Some(next_point_index);
+            return Ok(ControlFlow::Break(()));
         }
-
-        Ok(())
+        Ok(ControlFlow::Continue(()))
     };
 
     let points_processed = executor
-        .execute_shard(payload, &mut on_point_result)
+        .execute_shard(&remaining, &mut on_point_result)
         .map_err(|error| ChildHandlerError {
             message: error,
             failure_key: None,
         })?;
-
-    let metrics_prefix = first_metrics_key.unwrap_or_else(|| {
-        metrics_object_key(
-            &config.prefix,
-            &config.run_date,
-            &payload.run_id,
-            "success",
-            payload.shard_id,
-            payload.start_index,
-        )
-    });
+    if let Some(next_point_index) = stopped_at {
+        return Ok(ShardProgress::Checkpointed {
+            next_point_index,
+            points_processed,
+        });
+    }
+    let points_processed = points_processed + (resume_from - payload.start_index);
 
     let outcome_key = success_outcome_object_key(
         &config.prefix,
@@ -266,7 +661,7 @@ fn write_success(
         event_time: config.event_time.clone(),
         record_schema: OUTCOME_RECORD_SCHEMA_VERSION.to_string(),
         output_metadata: Some(ShardOutputMetadata {
-            result_key: metrics_prefix,
+            result_key: first_metrics_key(payload, config),
             points_processed,
             format: "parquet".to_string(),
         }),
@@ -285,7 +680,7 @@ fn write_success(
             failure_key: None,
         })?;
 
-    Ok((
+    Ok(ShardProgress::Completed(
         ChildSuccessResponse {
             status: "ok".to_string(),
             shard_id: payload.shard_id,
@@ -295,6 +690,85 @@ fn write_success(
     ))
 }
 
+/// Upload a point's trip data as one or more part files, then the manifest listing them.
+fn write_trip_data_parts(
+    payload: &ChildShardPayload,
+    config: &ChildHandlerConfig,
+    point_index: usize,
+    trip_data_parquet: Vec<u8>,
+    outcome_store: &impl OutcomeStore,
+) -> Result<(), String> {
+    let parts = split_parquet(
+        trip_data_parquet,
+        config.max_part_bytes,
+        &config.parquet_output,
+    )
+    .map_err(|error| format!("Failed to split trip data into parts: {error}"))?;
+
+    let mut part_files = Vec::with_capacity(parts.len());
+    for (part_index, part) in parts.into_iter().enumerate() {
+        let key = trip_data_part_object_key(
+            &config.prefix,
+            &config.run_date,
+            &payload.run_id,
+            "success",
+            payload.shard_id,
+            point_index,
+            part_index,
+        );
+        outcome_store
+            .write_object(&key, &part.body)
+            .map_err(|error| format!("Failed to persist trip data part {part_index}: {error}"))?;
+        part_files.push(PartFile {
+            key,
+            rows: part.rows,
+            bytes: part.body.len(),
+        });
+    }
+
+    let manifest = PartManifest {
+        run_id: payload.run_id.clone(),
+        shard_id: payload.shard_id,
+        point_index,
+        dataset: "trip_data".to_string(),
+        record_schema: PART_MANIFEST_SCHEMA_VERSION.to_string(),
+        total_rows: part_files.iter().map(|part| part.rows).sum(),
+        parts: part_files,
+    };
+    let manifest_body = serde_json::to_vec(&manifest)
+        .map_err(|error| format!("Failed to serialize trip data manifest: {error}"))?;
+    outcome_store
+        .write_object(
+            &trip_data_manifest_object_key(
+                &config.prefix,
+                &config.run_date,
+                &payload.run_id,
+                "success",
+                payload.shard_id,
+                point_index,
+            ),
+            &manifest_body,
+        )
+        .map_err(|error| format!("Failed to persist trip data manifest: {error}"))
+}
+
+/// Publish shard health metrics (plus the process memory peak) as an EMF log line.
+fn emit_shard_metrics(payload: &ChildShardPayload, status: &str, metrics: &[EmfMetric]) {
+    let mut metrics = metrics.to_vec();
+    if let Some(peak_mb) = peak_memory_mb() {
+        metrics.push(EmfMetric::new("PeakMemory", MetricUnit::Megabytes, peak_mb));
+    }
+    emit_metrics(
+        "child_handler",
+        &metrics,
+        json!({
+            "run_id": payload.run_id.clone(),
+            "shard_id": payload.shard_id,
+            "status": status,
+        }),
+    );
+}
+
 fn log_child_info(event: &str, details: serde_json::Value) {
     eprintln!(
         "{}",
@@ -577,40 +1051,71 @@ mod tests {
                 .insert(key.to_string(), body.to_vec());
             Ok(())
         }
+
+        fn read_object(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.body(key))
+        }
+    }
+
+    struct PassExecutor {
+        trip_rows: u64,
     }
 
-    struct PassExecutor;
+    const PASS: PassExecutor = PassExecutor { trip_rows: 3 };
 
     impl ShardExecutor for PassExecutor {
         fn execute_shard(
             &self,
             payload: &ChildShardPayload,
-            on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<(), String>,
+            on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<ControlFlow<()>, String>,
         ) -> Result<usize, String> {
             let mut points_processed = 0usize;
             for point_index in payload.start_index..payload.end_index_exclusive {
-                on_point_result(ShardPointResult {
+                let control = on_point_result(ShardPointResult {
                     point_index,
                     metrics: sample_simulation_result(),
-                    trip_data_parquet: b"PAR1-trip".to_vec(),
+                    trip_data_parquet: sample_trip_parquet(self.trip_rows),
                     snapshot_counts_parquet: b"PAR1-snap".to_vec(),
                     effective_parameters_json: format!("{{\"point_index\":{point_index}}}"),
                     parameter_fingerprint: format!("fingerprint-{point_index}"),
                 })?;
                 points_processed += 1;
+                if control.is_break() {
+                    break;
+                }
             }
 
             Ok(points_processed)
         }
     }
 
+    /// Runs like [`PASS`] and records where each invocation started.
+    #[derive(Default)]
+    struct ResumeTrackingExecutor {
+        starts: Mutex<Vec<usize>>,
+    }
+
+    impl ShardExecutor for ResumeTrackingExecutor {
+        fn execute_shard(
+            &self,
+            payload: &ChildShardPayload,
+            on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<ControlFlow<()>, String>,
+        ) -> Result<usize, String> {
+            self.starts
+                .lock()
+                .expect("poisoned mutex")
+                .push(payload.start_index);
+            PASS.execute_shard(payload, on_point_result)
+        }
+    }
+
     struct FailingExecutor;
 
     impl ShardExecutor for FailingExecutor {
         fn execute_shard(
             &self,
             _payload: &ChildShardPayload,
-            _on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<(), String>,
+            _on_point_result: &mut dyn FnMut(ShardPointResult) -> Result<ControlFlow<()>, String>,
         ) -> Result<usize, String> {
             Err("Injected shard failure for verification".to_string())
         }
@@ -659,6 +1164,27 @@ mod tests {
                 .insert(key.to_string(), body.to_vec());
             Ok(())
         }
+
+        fn read_object(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.body(key))
+        }
+    }
+
+    fn sample_trip_parquet(rows: u64) -> Vec<u8> {
+        let schema = std::sync::Arc::new(Schema::new(vec![Field::new(
+            "trip_entity",
+            DataType::UInt64,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![std::sync::Arc::new(UInt64Array::from_iter_values(0..rows)) as ArrayRef],
+        )
+        .expect("trip batch should build");
+        let mut writer =
+            ArrowWriter::try_new(Vec::new(), schema, None).expect("writer should open");
+        writer.write(&batch).expect("trip batch should write");
+        writer.into_inner().expect("trip parquet should finish")
     }
 
     fn sample_simulation_result() -> SimulationResult {
@@ -682,6 +1208,7 @@ mod tests {
             riders_abandoned_price: 5,
             riders_abandoned_eta: 3,
             riders_abandoned_stochastic: 2,
+            ..Default::default()
         }
     }
 
@@ -705,6 +1232,7 @@ mod tests {
             end_index_exclusive: 4,
             seed: 42,
             failure_injection_shards: Vec::new(),
+            idempotency_key: None,
         }
     }
 
@@ -714,18 +1242,20 @@ mod tests {
             prefix: "serverless-sweeps/outcomes".to_string(),
             run_date: "2026-02-14".to_string(),
             event_time: "2026-02-14T00:00:00Z".to_string(),
+            max_part_bytes: crate::adapters::parquet_parts::DEFAULT_MAX_PART_BYTES,
+            parquet_output: ParquetOutputConfig::default(),
+            deadline: None,
         }
     }
 
     #[test]
     fn child_writes_success_outcome_envelope() {
         let store = RecordingStore::new();
-        let response =
-            handle_child_payload(&sample_payload(), &sample_config(), &PassExecutor, &store)
-                .expect("child should succeed");
+        let response = handle_child_payload(&sample_payload(), &sample_config(), &PASS, &store)
+            .expect("child should succeed");
 
         assert_eq!(response.status, "ok");
-        assert_eq!(store.keys().len(), 9);
+        assert_eq!(store.keys().len(), 12);
         assert!(store
             .keys()
             .iter()
@@ -758,6 +1288,117 @@ mod tests {
         assert!(parquet.starts_with(b"PAR1"));
     }
 
+    #[test]
+    fn large_trip_data_is_uploaded_as_parts_with_manifest() {
+        let store = RecordingStore::new();
+        let mut payload = sample_payload();
+        payload.end_index_exclusive = payload.start_index + 1;
+        let mut config = sample_config();
+        config.max_part_bytes = sample_trip_parquet(5_000).len() / 3;
+
+        handle_child_payload(
+            &payload,
+            &config,
+            &PassExecutor { trip_rows: 5_000 },
+            &store,
+        )
+        .expect("child should succeed");
+
+        let manifest_key = trip_data_manifest_object_key(
+            &config.prefix,
+            &config.run_date,
+            &payload.run_id,
+            "success",
+            payload.shard_id,
+            payload.start_index,
+        );
+        let manifest: PartManifest =
+            serde_json::from_slice(&store.body(&manifest_key).expect("manifest should exist"))
+                .expect("manifest should be valid json");
+        assert!(manifest.parts.len() >= 3);
+        assert_eq!(manifest.total_rows, 5_000);
+        for key in manifest.part_keys() {
+            assert!(key.contains("dataset=trip_data") && key.ends_with(".parquet"));
+            assert!(store
+                .body(key)
+                .expect("part should exist")
+                .starts_with(b"PAR1"));
+        }
+    }
+
+    #[test]
+    fn redelivered_shard_is_not_recomputed() {
+        let store = RecordingStore::new();
+        let payload = sample_payload();
+        let first = handle_child_payload(&payload, &sample_config(), &PASS, &store)
+            .expect("first delivery should succeed");
+        let writes_after_first = store.keys().len();
+
+        // FailingExecutor would fail if the shard were executed again
+        let second = handle_child_payload(&payload, &sample_config(), &FailingExecutor, &store)
+            .expect("redelivery should succeed without recomputing");
+        assert_eq!(second.status, "already_completed");
+        assert_eq!(second.outcome_key, first.outcome_key);
+        assert_eq!(store.keys().len(), writes_after_first);
+        assert!(!store
+            .keys()
+            .iter()
+            .any(|key| key.contains("status_partition=failure")));
+
+        let mut rerun = payload.clone();
+        rerun.idempotency_key = Some("different-request".to_string());
+        let error = handle_child_payload(&rerun, &sample_config(), &PASS, &store)
+            .expect_err("conflicting idempotency key should fail");
+        assert!(error.message.contains("different idempotency key"));
+        assert_eq!(error.failure_key, None);
+    }
+
+    #[test]
+    fn deadline_checkpoints_shard_and_retry_resumes() {
+        let store = RecordingStore::new();
+        let payload = sample_payload();
+        let config = ChildHandlerConfig {
+            deadline: Some(Instant::now()),
+            ..sample_config()
+        };
+
+        let error = handle_child_payload(&payload, &config, &PASS, &store)
+            .expect_err("shard should stop at the deadline");
+        assert!(error.message.contains("stopped at point 3 of [2, 4)"));
+        let partial_key =
+            partial_outcome_object_key(&config.prefix, &config.run_date, &payload.run_id, 1);
+        assert_eq!(error.failure_key.as_deref(), Some(partial_key.as_str()));
+        assert!(store.body(&partial_key).is_some());
+
+        let checkpoint_key =
+            checkpoint_object_key(&config.prefix, &config.run_date, &payload.run_id, 1);
+        let checkpoint: ShardCheckpointRecord = serde_json::from_slice(
+            &store
+                .body(&checkpoint_key)
+                .expect("checkpoint should be stored"),
+        )
+        .expect("checkpoint should parse");
+        assert_eq!(checkpoint.next_point_index, 3);
+        assert_eq!(checkpoint.points_completed, 1);
+
+        let last_point_metrics = metrics_object_key(
+            &config.prefix,
+            &config.run_date,
+            &payload.run_id,
+            "success",
+            1,
+            3,
+        );
+        assert!(store.body(&last_point_metrics).is_none());
+
+        let retry = ResumeTrackingExecutor::default();
+        let response = handle_child_payload(&payload, &sample_config(), &retry, &store)
+            .expect("retry should finish the shard");
+        assert_eq!(response.status, "ok");
+        assert_eq!(*retry.starts.lock().expect("poisoned mutex"), vec![3]);
+        assert!(store.body(&last_point_metrics).is_some());
+    }
+
     #[test]
     fn child_writes_failure_outcome_envelope() {
         let store = RecordingStore::new();
@@ -835,7 +1476,7 @@ mod tests {
         let payload = sample_payload();
         let config = sample_config();
 
-        let error = handle_child_payload(&payload, &config, &PassExecutor, &store)
+        let error = handle_child_payload(&payload, &config, &PASS, &store)
             .expect_err("child should return failure when success outcome write fails");
 
         assert!(error.message.contains("Failed to persist success outcome"));
@@ -859,8 +1500,7 @@ mod tests {
         let store = RecordingStore::new();
         let payload = sample_payload();
         let config = sample_config();
-        handle_child_payload(&payload, &config, &PassExecutor, &store)
-            .expect("child should succeed");
+        handle_child_payload(&payload, &config, &PASS, &store).expect("child should succeed");
 
         let mut metric_suffixes = Vec::new();
         let mut effective_suffixes = Vec::new();
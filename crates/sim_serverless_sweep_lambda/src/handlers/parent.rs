@@ -2,13 +2,18 @@ use std::fs;
 use std::time::Instant;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use crate::adapters::dispatch::ShardDispatcher;
+use crate::adapters::emf::{emit_metrics, EmfMetric, MetricUnit};
 use crate::runtime::contract::{
-    config_fingerprint, normalize_request, request_fingerprint, ChildShardPayload, DispatchRecord,
+    config_fingerprint, normalize_request, request_fingerprint, DispatchRecord,
     ParentAcceptedResponse, RunContext, RunContextRecord, SweepRequest,
     ORCHESTRATION_SCHEMA_VERSION, RUN_CONTEXT_RECORD_SCHEMA_VERSION,
 };
-use crate::runtime::sharding::compute_shard_plan;
+use crate::runtime::estimate::{estimate_sweep, CostModel};
+use crate::runtime::pacing::release_offsets;
+use crate::runtime::sharding::{compute_shard_plan, shard_payload};
 use crate::runtime::storage_keys::run_context_object_key;
+use crate::runtime::validation::validate_manifest;
 use arrow::array::{ArrayRef, StringArray, UInt64Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
@@ -42,19 +47,19 @@ pub fn build_run_context(run_id: impl Into<String>, request_fingerprint: String)
 pub fn handle_parent_event(
     event: Value,
     dispatch_target: Option<&str>,
-    dispatch: &dyn Fn(&[u8]) -> Result<(), String>,
+    dispatcher: &dyn ShardDispatcher,
 ) -> ApiGatewayResponse {
-    handle_parent_event_with_context_export(event, dispatch_target, dispatch, None)
+    handle_parent_event_with_context_export(event, dispatch_target, dispatcher, None)
 }
 
 pub fn handle_parent_event_with_context_export(
     event: Value,
     dispatch_target: Option<&str>,
-    dispatch: &dyn Fn(&[u8]) -> Result<(), String>,
+    dispatcher: &dyn ShardDispatcher,
     run_context_export: Option<RunContextExportConfig<'_>>,
 ) -> ApiGatewayResponse {
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-        handle_parent_event_impl(event, dispatch_target, dispatch, run_context_export)
+        handle_parent_event_impl(event, dispatch_target, dispatcher, run_context_export)
     }));
 
     match result {
@@ -73,7 +78,7 @@ pub fn handle_parent_event_with_context_export(
 fn handle_parent_event_impl(
     event: Value,
     dispatch_target: Option<&str>,
-    dispatch: &dyn Fn(&[u8]) -> Result<(), String>,
+    dispatcher: &dyn ShardDispatcher,
     run_context_export: Option<RunContextExportConfig<'_>>,
 ) -> ApiGatewayResponse {
     let dispatch_started_at = Instant::now();
@@ -87,11 +92,61 @@ fn handle_parent_event_impl(
         Err(error) => return validation_error_response(&format!("Malformed request: {error}")),
     };
 
+    let validation = validate_manifest(&request);
+    for correction in validation.corrections() {
+        log_parent_info(
+            "manifest_corrected",
+            json!({
+                "run_id": validation.corrected.run_id.clone(),
+                "field": correction.field.clone(),
+                "message": correction.message.clone(),
+            }),
+        );
+    }
+    let request = match validation.into_request() {
+        Ok(value) => value,
+        Err(error) => return validation_error_response(error.message()),
+    };
+
+    let benchmark = request.benchmark;
+    let estimate_only = request.estimate_only;
+    let pacing = request.pacing.unwrap_or_default();
     let normalized = match normalize_request(request) {
         Ok(value) => value,
         Err(error) => return validation_error_response(error.message()),
     };
 
+    let estimate = match benchmark
+        .map(|benchmark| estimate_sweep(&normalized, &benchmark, &CostModel::default()))
+        .transpose()
+    {
+        Ok(value) => value,
+        Err(error) => return validation_error_response(error.message()),
+    };
+    if let Some(estimate) = &estimate {
+        log_parent_info(
+            "run_estimated",
+            json!({
+                "run_id": normalized.run_id.clone(),
+                "estimate": estimate,
+            }),
+        );
+    }
+    if estimate_only {
+        return success_response(
+            200,
+            ParentAcceptedResponse {
+                run_id: normalized.run_id,
+                total_points: normalized.total_points,
+                shards_dispatched: 0,
+                dispatches: Vec::new(),
+                status: "estimated".to_string(),
+                schema_version: ORCHESTRATION_SCHEMA_VERSION.to_string(),
+                estimate,
+            },
+        );
+    }
+
     let dispatch_target = match dispatch_target {
         Some(value) if !value.trim().is_empty() => value,
         _ => {
@@ -119,6 +174,14 @@ fn handle_parent_event_impl(
         Ok(value) => value,
         Err(error) => return validation_error_response(error.message()),
     };
+    let shard_delays = release_offsets(&pacing, shard_plan.len());
+    let dispatch_window_seconds = shard_delays.iter().max().copied().unwrap_or(0);
+    if dispatch_window_seconds > dispatcher.max_delay_seconds() {
+        return validation_error_response(&format!(
+            "pacing spreads dispatch over {dispatch_window_seconds}s but {dispatch_target} supports delays of at most {}s",
+            dispatcher.max_delay_seconds()
+        ));
+    }
 
     let run_context_record = RunContextRecord {
         run_id: normalized.run_id.clone(),
@@ -146,6 +209,7 @@ fn handle_parent_event_impl(
             "shard_count": run_context_record.shard_count,
             "shard_strategy": run_context_record.shard_strategy.clone(),
             "max_shards": run_context_record.max_shards,
+            "dispatch_window_seconds": dispatch_window_seconds,
         }),
     );
 
@@ -190,18 +254,8 @@ fn handle_parent_event_impl(
     }
 
     let mut dispatches = Vec::with_capacity(shard_plan.len());
-    for assignment in shard_plan {
-        let child_payload = ChildShardPayload {
-            run_id: normalized.run_id.clone(),
-            run_date: Some(run_date.clone()),
-            dimensions: normalized.dimensions.clone(),
-            total_points: normalized.total_points,
-            shard_id: assignment.shard_id,
-            start_index: assignment.start_index,
-            end_index_exclusive: assignment.end_index_exclusive,
-            seed: normalized.seed,
-            failure_injection_shards: normalized.failure_injection_shards.clone(),
-        };
+    for (assignment, delay_seconds) in shard_plan.into_iter().zip(shard_delays) {
+        let child_payload = shard_payload(&normalized, &assignment, Some(run_date.clone()));
 
         let bytes = match serde_json::to_vec(&child_payload) {
             Ok(value) => value,
@@ -216,8 +270,9 @@ fn handle_parent_event_impl(
             }
         };
 
-        let dispatch_result =
-            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| dispatch(&bytes)));
+        let dispatch_result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            dispatcher.dispatch(&bytes, delay_seconds)
+        }));
 
         match dispatch_result {
             Ok(Ok(())) => {}
@@ -231,6 +286,7 @@ fn handle_parent_event_impl(
                         "error": error.clone(),
                     }),
                 );
+                emit_dispatch_failure(&run_context.run_id, dispatches.len());
                 return error_response(
                     502,
                     json!({
@@ -252,6 +308,7 @@ fn handle_parent_event_impl(
                         "details": details.clone(),
                     }),
                 );
+                emit_dispatch_failure(&run_context.run_id, dispatches.len());
                 return error_response(
                     500,
                     json!({
@@ -268,6 +325,7 @@ fn handle_parent_event_impl(
         dispatches.push(DispatchRecord {
             shard_id: child_payload.shard_id,
             status_code: 202,
+            delay_seconds,
         });
     }
 
@@ -289,6 +347,25 @@ fn handle_parent_event_impl(
         }),
     );
 
+    emit_metrics(
+        "parent_handler",
+        &[
+            EmfMetric::new("ShardsDispatched", MetricUnit::Count, shard_count as f64),
+            EmfMetric::new(
+                "ParameterPoints",
+                MetricUnit::Count,
+                normalized.total_points as f64,
+            ),
+            EmfMetric::new(
+                "DispatchDuration",
+                MetricUnit::Milliseconds,
+                dispatch_duration_ms as f64,
+            ),
+            EmfMetric::new("DispatchFailures", MetricUnit::Count, 0.0),
+        ],
+        json!({"run_id": run_context.run_id.clone()}),
+    );
+
     let response = ParentAcceptedResponse {
         run_id: normalized.run_id,
         total_points: normalized.total_points,
@@ -296,10 +373,27 @@ fn handle_parent_event_impl(
         dispatches,
         status: "dispatch_submitted".to_string(),
         schema_version: ORCHESTRATION_SCHEMA_VERSION.to_string(),
+        estimate,
     };
     success_response(202, response)
 }
 
+/// Publish a failed dispatch (after `shards_dispatched` successful ones) as an EMF log line.
+fn emit_dispatch_failure(run_id: &str, shards_dispatched: usize) {
+    emit_metrics(
+        "parent_handler",
+        &[
+            EmfMetric::new(
+                "ShardsDispatched",
+                MetricUnit::Count,
+                shards_dispatched as f64,
+            ),
+            EmfMetric::new("DispatchFailures", MetricUnit::Count, 1.0),
+        ],
+        json!({"run_id": run_id}),
+    );
+}
+
 fn log_parent_info(event: &str, details: Value) {
     eprintln!(
         "{}",
@@ -443,31 +537,46 @@ mod tests {
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
 
+    use crate::adapters::dispatch::ChannelDispatcher;
+
     use super::*;
 
+    struct PanickingDispatcher(&'static str);
+
+    impl ShardDispatcher for PanickingDispatcher {
+        fn dispatch(&self, _payload: &[u8], _delay_seconds: u32) -> Result<(), String> {
+            panic!("{}", self.0)
+        }
+    }
+
+    struct EventBusDispatcher;
+
+    impl ShardDispatcher for EventBusDispatcher {
+        fn dispatch(&self, _payload: &[u8], _delay_seconds: u32) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn max_delay_seconds(&self) -> u32 {
+            0
+        }
+    }
+
     #[test]
     fn rejects_invalid_payload_without_dispatching() {
-        let payloads: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
+        let (dispatcher, shards) = ChannelDispatcher::new();
         let run_context_writes: Arc<Mutex<HashMap<String, Vec<u8>>>> =
             Arc::new(Mutex::new(HashMap::new()));
-        let payloads_for_dispatch = Arc::clone(&payloads);
         let writes_for_export = Arc::clone(&run_context_writes);
         let response = handle_parent_event(
             json!({"body": "{\"run_id\":\"missing-dimensions\"}"}),
             Some("arn:aws:lambda:example:child"),
-            &move |payload| {
-                payloads_for_dispatch
-                    .lock()
-                    .expect("poisoned mutex")
-                    .push(payload.to_vec());
-                Ok(())
-            },
+            &dispatcher,
         );
 
         let _ = handle_parent_event_with_context_export(
             json!({"body": "{\"run_id\":\"missing-dimensions\"}"}),
             Some("arn:aws:lambda:example:child"),
-            &|_payload| Ok(()),
+            &dispatcher,
             Some(RunContextExportConfig {
                 prefix: "serverless-sweeps/outcomes",
                 persist_object: &move |key, body| {
@@ -481,7 +590,7 @@ mod tests {
         );
 
         assert_eq!(response.status_code, 400);
-        assert!(payloads.lock().expect("poisoned mutex").is_empty());
+        assert!(shards.try_recv().is_err());
         assert!(run_context_writes
             .lock()
             .expect("poisoned mutex")
@@ -493,6 +602,7 @@ mod tests {
         let run_context_writes: Arc<Mutex<HashMap<String, Vec<u8>>>> =
             Arc::new(Mutex::new(HashMap::new()));
         let writes_for_export = Arc::clone(&run_context_writes);
+        let (dispatcher, _shards) = ChannelDispatcher::new();
 
         let response = handle_parent_event_with_context_export(
             json!({
@@ -507,7 +617,7 @@ mod tests {
                 }
             }),
             Some("arn:aws:lambda:example:child"),
-            &|_payload| Ok(()),
+            &dispatcher,
             Some(RunContextExportConfig {
                 prefix: "serverless-sweeps/outcomes",
                 persist_object: &move |key, body| {
@@ -535,8 +645,7 @@ mod tests {
 
     #[test]
     fn dispatches_reproducible_child_payloads() {
-        let payloads: Arc<Mutex<Vec<Vec<u8>>>> = Arc::new(Mutex::new(Vec::new()));
-        let payloads_for_dispatch = Arc::clone(&payloads);
+        let (dispatcher, shards) = ChannelDispatcher::new();
         let response = handle_parent_event(
             json!({
                 "body": {
@@ -551,23 +660,15 @@ mod tests {
                 }
             }),
             Some("arn:aws:lambda:example:child"),
-            &move |payload| {
-                payloads_for_dispatch
-                    .lock()
-                    .expect("poisoned mutex")
-                    .push(payload.to_vec());
-                Ok(())
-            },
+            &dispatcher,
         );
 
         assert_eq!(response.status_code, 202);
-        let payloads = payloads.lock().expect("poisoned mutex").clone();
-        assert_eq!(payloads.len(), 2);
+        let shards: Vec<_> = shards.try_iter().collect();
+        assert_eq!(shards.len(), 2);
 
-        let first: ChildShardPayload =
-            serde_json::from_slice(&payloads[0]).expect("payload should parse");
-        let second: ChildShardPayload =
-            serde_json::from_slice(&payloads[1]).expect("payload should parse");
+        let first = shards[0].payload().expect("payload should parse");
+        let second = shards[1].payload().expect("payload should parse");
 
         assert_eq!(first.run_id, "dispatch-run");
         assert_eq!(first.run_date, second.run_date);
@@ -576,6 +677,72 @@ mod tests {
         assert_eq!(second.failure_injection_shards, vec![1]);
     }
 
+    #[test]
+    fn paced_dispatch_delays_shards_beyond_the_in_flight_budget() {
+        let (dispatcher, shards) = ChannelDispatcher::new();
+        let request = json!({
+            "body": {
+                "run_id": "paced-run",
+                "dimensions": {
+                    "commission_rate": [0.1, 0.2, 0.3],
+                    "num_drivers": [100, 200]
+                },
+                "shard_count": 6,
+                "pacing": {"max_in_flight_shards": 2, "wave_interval_seconds": 120}
+            }
+        });
+        let response = handle_parent_event(
+            request.clone(),
+            Some("arn:aws:lambda:example:child"),
+            &dispatcher,
+        );
+
+        assert_eq!(response.status_code, 202);
+        assert_eq!(
+            shards
+                .try_iter()
+                .map(|shard| shard.delay_seconds)
+                .collect::<Vec<_>>(),
+            vec![0, 0, 120, 120, 240, 240]
+        );
+        let body: ParentAcceptedResponse =
+            serde_json::from_str(&response.body).expect("response body should parse");
+        assert_eq!(body.dispatches[5].delay_seconds, 240);
+
+        let response = handle_parent_event(request, Some("default"), &EventBusDispatcher);
+        assert_eq!(response.status_code, 400);
+        assert!(response.body.contains("supports delays of at most 0s"));
+    }
+
+    #[test]
+    fn estimate_only_returns_cost_estimate_without_dispatching() {
+        let response = handle_parent_event(
+            json!({
+                "body": {
+                    "run_id": "estimate-run",
+                    "dimensions": {
+                        "commission_rate": [0.1, 0.2, 0.3],
+                        "num_drivers": [100, 200]
+                    },
+                    "shard_size": 2,
+                    "benchmark": {"seconds_per_run": 4.0, "output_bytes_per_run": 1000},
+                    "estimate_only": true
+                }
+            }),
+            None,
+            &PanickingDispatcher("estimate_only must not dispatch"),
+        );
+
+        assert_eq!(response.status_code, 200);
+        let body: ParentAcceptedResponse =
+            serde_json::from_str(&response.body).expect("response body should parse");
+        assert_eq!(body.status, "estimated");
+        assert_eq!(body.shards_dispatched, 0);
+        let estimate = body.estimate.expect("estimate should be returned");
+        assert_eq!(estimate.shard_count, 3);
+        assert_eq!(estimate.s3_bytes, 6_000);
+    }
+
     #[test]
     fn returns_detailed_error_when_dispatch_panics() {
         let response = handle_parent_event(
@@ -591,7 +758,7 @@ mod tests {
                 }
             }),
             Some("arn:aws:lambda:example:child"),
-            &|_payload| panic!("simulated dispatch panic"),
+            &PanickingDispatcher("simulated dispatch panic"),
         );
 
         assert_eq!(response.status_code, 500);
@@ -1,10 +1,14 @@
-//! AWS-oriented adapters and handlers for serverless sweep execution.
+//! Adapters and handlers for serverless sweep execution.
 //!
-//! This crate owns runtime integration details (Lambda handlers, queue dispatch,
+//! This crate owns runtime integration details (parent/child handlers, queue dispatch,
 //! and storage adapters) and exposes a single runtime module boundary for
-//! contract, sharding, and storage key primitives.
+//! contract, sharding, and storage key primitives. The handlers only see the
+//! `OutcomeStore` and `ShardDispatcher` traits; the AWS clients live in the
+//! `sweep_runtime` binary behind the default `aws` feature, so other cloud adapters
+//! (see `sim_serverless_sweep_http`) reuse the handlers with `default-features = false`.
 //! See `crates/sim_serverless_sweep_lambda/README.md` for ownership boundaries.
 
 pub mod adapters;
+pub mod cli;
 pub mod handlers;
 pub mod runtime;
@@ -1 +1,3 @@
-pub use sim_serverless_sweep_core::{contract, sharding, storage_keys};
+pub use sim_serverless_sweep_core::{
+    contract, estimate, pacing, sharding, storage_keys, validation,
+};
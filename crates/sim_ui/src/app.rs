@@ -1,9 +1,17 @@
 //! Application state and core simulation wiring for the UI.
 
+mod alerts;
+mod console;
 mod defaults;
 mod map_tiles;
 mod presets;
+mod replay;
+mod run_queue;
 mod simulation;
+mod spawn_paint;
 
+pub use alerts::{AlertComparison, AlertMetric, MIN_RESOLVED_FOR_RATES};
 pub use map_tiles::{MapSignature, TileKey};
+pub use run_queue::RUN_QUEUE_FRAME_BUDGET;
 pub use simulation::{MatchingAlgorithmType, RoutingMode, SimUiApp, SpawnMode, TrafficProfileMode};
+pub use spawn_paint::{PaintTarget, SpawnPainting};
@@ -0,0 +1,296 @@
+//! Outcome alerts: user thresholds on snapshot metrics. A rule fires when its metric crosses
+//! the threshold; each firing is kept so the banner and the charts can point at it.
+
+use sim_core::telemetry::{SimCounts, SimSnapshot};
+
+/// Resolved riders needed before rate metrics are evaluated, so the first few outcomes do
+/// not fire rate alerts on their own.
+pub const MIN_RESOLVED_FOR_RATES: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertMetric {
+    WaitingRiders,
+    IdleDrivers,
+    ActiveTrips,
+    /// Cancelled share of resolved riders, in percent.
+    CancellationRate,
+    /// Share of resolved riders who gave up on quotes, in percent.
+    AbandonmentRate,
+    /// Completed share of resolved riders, in percent.
+    ConversionRate,
+}
+
+impl AlertMetric {
+    pub const ALL: [Self; 6] = [
+        Self::WaitingRiders,
+        Self::IdleDrivers,
+        Self::ActiveTrips,
+        Self::CancellationRate,
+        Self::AbandonmentRate,
+        Self::ConversionRate,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::WaitingRiders => "Waiting riders",
+            Self::IdleDrivers => "Idle drivers",
+            Self::ActiveTrips => "Active trips",
+            Self::CancellationRate => "Cancellation rate",
+            Self::AbandonmentRate => "Abandonment rate",
+            Self::ConversionRate => "Conversion rate",
+        }
+    }
+
+    pub fn is_rate(self) -> bool {
+        matches!(
+            self,
+            Self::CancellationRate | Self::AbandonmentRate | Self::ConversionRate
+        )
+    }
+
+    /// Metric value for a snapshot; `None` for rates until enough riders are resolved.
+    pub fn value(self, counts: &SimCounts) -> Option<f64> {
+        let resolved = counts.riders_completed_total
+            + counts.riders_cancelled_total
+            + counts.riders_abandoned_quote_total;
+        let rate = |count: u64| {
+            (resolved >= MIN_RESOLVED_FOR_RATES).then(|| 100.0 * count as f64 / resolved as f64)
+        };
+        match self {
+            Self::WaitingRiders => Some(counts.riders_waiting as f64),
+            Self::IdleDrivers => Some(counts.drivers_idle as f64),
+            Self::ActiveTrips => Some((counts.trips_en_route + counts.trips_on_trip) as f64),
+            Self::CancellationRate => rate(counts.riders_cancelled_total),
+            Self::AbandonmentRate => rate(counts.riders_abandoned_quote_total),
+            Self::ConversionRate => rate(counts.riders_completed_total),
+        }
+    }
+
+    /// Format a value of this metric.
+    pub fn format(self, value: f64) -> String {
+        if self.is_rate() {
+            format!("{value:.1}%")
+        } else {
+            format!("{value:.0}")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertComparison {
+    Above,
+    Below,
+}
+
+impl AlertComparison {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Above => ">",
+            Self::Below => "<",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub enabled: bool,
+    pub metric: AlertMetric,
+    pub comparison: AlertComparison,
+    /// Count, or percent for rate metrics.
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    pub fn new(metric: AlertMetric, comparison: AlertComparison, threshold: f64) -> Self {
+        Self {
+            enabled: true,
+            metric,
+            comparison,
+            threshold,
+        }
+    }
+
+    fn breached_by(&self, value: f64) -> bool {
+        match self.comparison {
+            AlertComparison::Above => value > self.threshold,
+            AlertComparison::Below => value < self.threshold,
+        }
+    }
+
+    /// E.g. "Waiting riders > 100".
+    pub fn describe(&self) -> String {
+        format!(
+            "{} {} {}",
+            self.metric.label(),
+            self.comparison.symbol(),
+            self.metric.format(self.threshold)
+        )
+    }
+}
+
+/// One threshold crossing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    /// Simulation time of the snapshot that crossed the threshold.
+    pub timestamp_ms: u64,
+    /// Rule as it read when it fired.
+    pub description: String,
+    pub value: String,
+}
+
+/// Alert rules and the crossings recorded for the current run.
+#[derive(Debug, Clone)]
+pub struct OutcomeAlerts {
+    pub rules: Vec<AlertRule>,
+    pub events: Vec<AlertEvent>,
+    /// Events the banner has been dismissed for.
+    acknowledged: usize,
+    /// Whether each rule was breached at the last checked snapshot (parallel to `rules`).
+    breached: Vec<bool>,
+    last_checked_ms: Option<u64>,
+}
+
+impl Default for OutcomeAlerts {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                AlertRule::new(AlertMetric::WaitingRiders, AlertComparison::Above, 100.0),
+                AlertRule::new(AlertMetric::CancellationRate, AlertComparison::Above, 20.0),
+            ],
+            events: Vec::new(),
+            acknowledged: 0,
+            breached: Vec::new(),
+            last_checked_ms: None,
+        }
+    }
+}
+
+impl OutcomeAlerts {
+    /// Evaluate the rules on snapshots newer than the last one checked. A rule fires when a
+    /// snapshot breaches it after one that did not; it can fire again once it has recovered.
+    pub fn check<'a>(&mut self, snapshots: impl IntoIterator<Item = &'a SimSnapshot>) {
+        self.breached.resize(self.rules.len(), false);
+        for snapshot in snapshots {
+            if self
+                .last_checked_ms
+                .is_some_and(|last| snapshot.timestamp_ms <= last)
+            {
+                continue;
+            }
+            self.last_checked_ms = Some(snapshot.timestamp_ms);
+            for (rule, breached) in self.rules.iter().zip(self.breached.iter_mut()) {
+                let value = rule.metric.value(&snapshot.counts).filter(|_| rule.enabled);
+                let now_breached = value.is_some_and(|value| rule.breached_by(value));
+                if now_breached && !*breached {
+                    self.events.push(AlertEvent {
+                        timestamp_ms: snapshot.timestamp_ms,
+                        description: rule.describe(),
+                        value: rule.metric.format(value.unwrap_or_default()),
+                    });
+                }
+                *breached = now_breached;
+            }
+        }
+    }
+
+    pub fn add_rule(&mut self) {
+        self.rules.push(AlertRule::new(
+            AlertMetric::WaitingRiders,
+            AlertComparison::Above,
+            100.0,
+        ));
+    }
+
+    pub fn remove_rule(&mut self, index: usize) {
+        if index < self.rules.len() {
+            self.rules.remove(index);
+        }
+        if index < self.breached.len() {
+            self.breached.remove(index);
+        }
+    }
+
+    /// Events the banner still shows, oldest first.
+    pub fn unacknowledged(&self) -> &[AlertEvent] {
+        &self.events[self.acknowledged.min(self.events.len())..]
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.acknowledged = self.events.len();
+    }
+
+    /// Drop the recorded events. Snapshots already checked are not checked again.
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+        self.acknowledged = 0;
+    }
+
+    /// Forget recorded events and checked snapshots, e.g. when the simulation is rebuilt.
+    /// Rules are kept.
+    pub fn clear_history(&mut self) {
+        self.clear_events();
+        self.breached.clear();
+        self.last_checked_ms = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp_ms: u64, waiting: usize, completed: u64, cancelled: u64) -> SimSnapshot {
+        SimSnapshot {
+            timestamp_ms,
+            counts: SimCounts {
+                riders_waiting: waiting,
+                riders_completed_total: completed,
+                riders_cancelled_total: cancelled,
+                ..SimCounts::default()
+            },
+            riders: Vec::new(),
+            drivers: Vec::new(),
+            trips: Vec::new(),
+            cells: Vec::new(),
+            world_growth: None,
+            keyframe: true,
+            removed: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rules_fire_on_crossing_and_again_after_recovering() {
+        let mut alerts = OutcomeAlerts::default();
+        let snapshots = [
+            snapshot(1_000, 50, 5, 5),
+            snapshot(2_000, 150, 10, 5),
+            snapshot(3_000, 180, 10, 5),
+            snapshot(4_000, 20, 20, 10),
+            snapshot(5_000, 120, 20, 10),
+        ];
+        alerts.check(&snapshots[..3]);
+        assert_eq!(alerts.events.len(), 1);
+        assert_eq!(alerts.events[0].timestamp_ms, 2_000);
+        assert_eq!(alerts.events[0].description, "Waiting riders > 100");
+
+        // Already checked snapshots are skipped.
+        alerts.check(&snapshots);
+        let fired: Vec<_> = alerts
+            .events
+            .iter()
+            .map(|event| (event.timestamp_ms, event.value.as_str()))
+            .collect();
+        assert_eq!(fired, [(2_000, "150"), (4_000, "33.3%"), (5_000, "120")]);
+
+        assert_eq!(alerts.unacknowledged().len(), 3);
+        alerts.acknowledge();
+        assert!(alerts.unacknowledged().is_empty());
+        alerts.clear_events();
+        alerts.check(&snapshots);
+        assert!(alerts.events.is_empty());
+
+        alerts.clear_history();
+        alerts.rules[0].enabled = false;
+        alerts.check(&snapshots);
+        assert_eq!(alerts.events.len(), 1);
+    }
+}
@@ -0,0 +1,104 @@
+//! Filters for the console panel, which lists the run's [`SimLog`](sim_core::sim_log::SimLog).
+
+use sim_core::sim_log::{LogCategory, LogSeverity, SimLogEntry};
+
+/// Which log entries the console shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleFilter {
+    pub show_info: bool,
+    pub show_warnings: bool,
+    pub show_errors: bool,
+    /// Only this category; `None` shows all.
+    pub category: Option<LogCategory>,
+    /// Case-insensitive substring of the message or entity id.
+    pub text: String,
+}
+
+impl Default for ConsoleFilter {
+    fn default() -> Self {
+        Self {
+            show_info: true,
+            show_warnings: true,
+            show_errors: true,
+            category: None,
+            text: String::new(),
+        }
+    }
+}
+
+impl ConsoleFilter {
+    pub fn shows_severity(&self, severity: LogSeverity) -> bool {
+        match severity {
+            LogSeverity::Info => self.show_info,
+            LogSeverity::Warning => self.show_warnings,
+            LogSeverity::Error => self.show_errors,
+        }
+    }
+
+    pub fn matches(&self, entry: &SimLogEntry) -> bool {
+        if !self.shows_severity(entry.severity()) {
+            return false;
+        }
+        if self
+            .category
+            .is_some_and(|category| category != entry.category())
+        {
+            return false;
+        }
+        let text = self.text.trim();
+        if text.is_empty() {
+            return true;
+        }
+        let text = text.to_lowercase();
+        entry.event.message().to_lowercase().contains(&text)
+            || entry
+                .event
+                .entity()
+                .is_some_and(|entity| entity.to_bits().to_string().contains(&text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::Entity;
+    use sim_core::sim_log::{SimLogEvent, SpawnerKind};
+
+    use super::*;
+
+    fn entry(event: SimLogEvent) -> SimLogEntry {
+        SimLogEntry {
+            timestamp_ms: 0,
+            event,
+        }
+    }
+
+    #[test]
+    fn filter_by_severity_category_and_text() {
+        let rider = Entity::from_raw(42);
+        let no_driver = entry(SimLogEvent::NoDriverInRadius {
+            rider,
+            idle_drivers: 0,
+        });
+        let exhausted = entry(SimLogEvent::SpawnerExhausted {
+            spawner: SpawnerKind::Rider,
+            spawned: 10,
+        });
+
+        let mut filter = ConsoleFilter::default();
+        assert!(filter.matches(&no_driver) && filter.matches(&exhausted));
+
+        filter.show_info = false;
+        assert!(filter.matches(&no_driver) && !filter.matches(&exhausted));
+
+        filter.show_info = true;
+        filter.category = Some(LogCategory::Spawner);
+        assert!(!filter.matches(&no_driver) && filter.matches(&exhausted));
+
+        filter.category = None;
+        filter.text = rider.to_bits().to_string();
+        assert!(filter.matches(&no_driver) && !filter.matches(&exhausted));
+
+        filter.text = "EXHAUSTED".to_string();
+        assert!(!filter.matches(&no_driver) && filter.matches(&exhausted));
+    }
+}
@@ -1,4 +1,6 @@
-use super::{map_tiles::MapTileState, simulation::*};
+use sim_core::clock::civil::CityTimeZone;
+
+use super::{map_tiles::MapTileState, simulation::*, spawn_paint::SpawnPainting};
 
 pub(super) struct AppDefaults {
     pub num_riders: usize,
@@ -40,12 +42,14 @@ pub(super) struct AppDefaults {
     pub base_speed_enabled: bool,
     pub base_speed_kmh: f64,
     pub spawn_mode: SpawnMode,
+    pub spawn_painting: SpawnPainting,
     pub map_tiles: MapTileState,
     pub start_year: i32,
     pub start_month: u32,
     pub start_day: u32,
     pub start_hour: u32,
     pub start_minute: u32,
+    pub time_zone: CityTimeZone,
 }
 
 impl AppDefaults {
@@ -90,12 +94,14 @@ impl AppDefaults {
             base_speed_enabled: false,
             base_speed_kmh: 50.0,
             spawn_mode: SpawnMode::Uniform,
+            spawn_painting: SpawnPainting::default(),
             map_tiles: MapTileState::new(),
             start_year: 2026,
             start_month: 2,
             start_day: 3,
             start_hour: 6,
             start_minute: 30,
+            time_zone: CityTimeZone::UTC,
         }
     }
 }
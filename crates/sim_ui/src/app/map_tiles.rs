@@ -1,15 +1,37 @@
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{Receiver, Sender};
 
+use directories::ProjectDirs;
+
 mod bookkeeping;
+mod disk_cache;
 mod fetch_decode;
 mod projection_cache;
+#[cfg(test)]
+mod tests;
 mod types;
 
-use types::{CachedProjection, ProjectionBounds, TileResult};
+use types::{CachedProjection, ProjectionBounds, TileResult, TileSource};
 pub use types::{MapSignature, TileGeometry, TileKey};
 
+/// Environment variables seeding the tile settings at startup.
+pub const TILE_URL_ENV: &str = "SIM_UI_TILE_URL";
+pub const TILE_API_KEY_ENV: &str = "SIM_UI_TILE_API_KEY";
+pub const TILES_OFFLINE_ENV: &str = "SIM_UI_TILES_OFFLINE";
+pub const TILE_CACHE_DIR_ENV: &str = "SIM_UI_TILE_CACHE_DIR";
+
+/// Road tiles for the map: fetched in the background, kept on disk per tile source and
+/// projected once per map signature.
 pub struct MapTileState {
+    /// Tile URL with `{z}`, `{x}`, `{y}` and optional `{api_key}` placeholders; empty uses the
+    /// tile service of the OSRM endpoint.
+    pub url_template: String,
+    pub api_key: String,
+    /// Draw only tiles already in the disk cache and never touch the network.
+    pub offline: bool,
+    cache_root: Option<PathBuf>,
+    last_source: Option<(String, String, bool)>,
     tiles: HashMap<TileKey, TileGeometry>,
     inflight: HashSet<TileKey>,
     errors: HashMap<TileKey, String>,
@@ -23,7 +45,18 @@ pub struct MapTileState {
 impl MapTileState {
     pub fn new() -> Self {
         let (sender, receiver) = std::sync::mpsc::channel();
+        let cache_root = std::env::var_os(TILE_CACHE_DIR_ENV)
+            .map(PathBuf::from)
+            .or_else(|| {
+                ProjectDirs::from("", "", "sim_ui").map(|dirs| dirs.cache_dir().join("tiles"))
+            });
         Self {
+            url_template: std::env::var(TILE_URL_ENV).unwrap_or_default(),
+            api_key: std::env::var(TILE_API_KEY_ENV).unwrap_or_default(),
+            offline: std::env::var(TILES_OFFLINE_ENV)
+                .is_ok_and(|value| matches!(value.trim(), "1" | "true" | "yes")),
+            cache_root,
+            last_source: None,
             tiles: HashMap::new(),
             inflight: HashSet::new(),
             errors: HashMap::new(),
@@ -39,13 +72,56 @@ impl MapTileState {
         if self.last_signature == Some(signature) {
             return;
         }
+        self.clear_tiles();
+        self.current_projection_bounds =
+            projection_cache::projection_bounds_from_signature(signature);
+        self.last_signature = Some(signature);
+    }
+
+    fn clear_tiles(&mut self) {
         self.tiles.clear();
         self.inflight.clear();
         self.errors.clear();
-        self.current_projection_bounds =
-            projection_cache::projection_bounds_from_signature(signature);
         self.projection_cache.clear();
-        self.last_signature = Some(signature);
+    }
+
+    /// A tile server other than the OSRM endpoint is configured, so tiles can be drawn in any
+    /// routing mode.
+    pub fn has_custom_source(&self) -> bool {
+        !self.url_template.trim().is_empty()
+    }
+
+    /// Root of the on-disk tile cache, if one could be resolved.
+    pub fn cache_root(&self) -> Option<&Path> {
+        self.cache_root.as_deref()
+    }
+
+    /// Tiles that could not be loaded for the current map (e.g. missing while offline).
+    pub fn failed_tile_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Source for the current settings; `None` when neither a tile URL nor an OSRM endpoint is
+    /// set.
+    fn resolve_source(&self, osrm_endpoint: &str) -> Option<TileSource> {
+        let url_template = if self.has_custom_source() {
+            self.url_template.trim().to_string()
+        } else {
+            let endpoint = osrm_endpoint.trim().trim_end_matches('/');
+            if endpoint.is_empty() {
+                return None;
+            }
+            format!("{endpoint}/tile/v1/driving/tile({{x}},{{y}},{{z}}).mvt")
+        };
+        Some(TileSource {
+            cache_dir: self
+                .cache_root
+                .as_deref()
+                .map(|root| disk_cache::source_dir(root, &url_template)),
+            url_template,
+            api_key: self.api_key.trim().to_string(),
+            offline: self.offline,
+        })
     }
 
     pub fn drain_results(&mut self) {
@@ -81,12 +157,25 @@ impl MapTileState {
         bookkeeping::evict_stale_projections(&mut self.projection_cache);
     }
 
-    pub fn request_missing_tiles<I>(&mut self, endpoint: &str, keys: I)
+    /// Start loading tiles not drawn yet. Changing the tile URL, API key or offline mode drops the
+    /// loaded tiles and earlier failures so they are retried from the new source.
+    pub fn request_missing_tiles<I>(&mut self, osrm_endpoint: &str, keys: I)
     where
         I: IntoIterator<Item = TileKey>,
     {
+        let Some(source) = self.resolve_source(osrm_endpoint) else {
+            return;
+        };
+        let source_signature = (
+            source.url_template.clone(),
+            source.api_key.clone(),
+            source.offline,
+        );
+        if self.last_source.as_ref() != Some(&source_signature) {
+            self.clear_tiles();
+            self.last_source = Some(source_signature);
+        }
         let mut inflight_count = self.inflight.len();
-        let endpoint = endpoint.trim_end_matches('/').to_string();
         for key in keys {
             if self.tiles.contains_key(&key) || self.inflight.contains(&key) {
                 continue;
@@ -101,12 +190,9 @@ impl MapTileState {
             inflight_count += 1;
             self.inflight.insert(key);
             let sender = self.sender.clone();
-            let url = format!(
-                "{}/tile/v1/driving/tile({},{},{}).mvt",
-                endpoint, key.x, key.y, key.z
-            );
+            let source = source.clone();
             std::thread::spawn(move || {
-                let result = fetch_decode::fetch_tile(&url, key);
+                let result = fetch_decode::load_tile(&source, key);
                 let _ = sender.send(result);
             });
         }
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::types::TileKey;
+
+/// Directory of one tile source inside the cache root. Sources are told apart by a hash of
+/// their URL template, which never contains the API key itself.
+pub(crate) fn source_dir(root: &Path, url_template: &str) -> PathBuf {
+    let hash = url_template
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    root.join(format!("{hash:016x}"))
+}
+
+pub(crate) fn tile_path(source_dir: &Path, key: TileKey) -> PathBuf {
+    source_dir
+        .join(key.z.to_string())
+        .join(key.x.to_string())
+        .join(format!("{}.mvt", key.y))
+}
+
+pub(crate) fn read_tile(path: &Path) -> Option<Vec<u8>> {
+    fs::read(path).ok()
+}
+
+/// Store raw tile bytes through a temp file so a concurrent reader never sees a partial tile.
+pub(crate) fn write_tile(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let temp_path = path.with_extension(format!("mvt.tmp.{nanos}"));
+    fs::write(&temp_path, bytes).map_err(|err| err.to_string())?;
+    fs::rename(&temp_path, path).map_err(|err| {
+        let _ = fs::remove_file(&temp_path);
+        err.to_string()
+    })
+}
@@ -1,49 +1,25 @@
 use std::time::Duration;
 
-use super::types::{TileGeometry, TileKey, TileResult};
+use super::disk_cache;
+use super::types::{TileGeometry, TileKey, TileResult, TileSource};
 
-pub(crate) fn fetch_tile(url: &str, key: TileKey) -> TileResult {
-    let client = match reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(3))
-        .build()
-    {
-        Ok(client) => client,
-        Err(err) => {
-            return TileResult {
-                key,
-                geometry: None,
-                error: Some(err.to_string()),
-            }
-        }
-    };
-    let response = match client.get(url).send() {
-        Ok(response) => response,
-        Err(err) => {
-            return TileResult {
-                key,
-                geometry: None,
-                error: Some(err.to_string()),
-            }
-        }
-    };
-    if !response.status().is_success() {
-        return TileResult {
-            key,
-            geometry: None,
-            error: Some(format!("status {}", response.status())),
-        };
-    }
-    let bytes = match response.bytes() {
-        Ok(bytes) => bytes,
-        Err(err) => {
-            return TileResult {
-                key,
-                geometry: None,
-                error: Some(err.to_string()),
+/// Load a tile from the disk cache, or fetch (and cache) it unless the source is offline.
+pub(crate) fn load_tile(source: &TileSource, key: TileKey) -> TileResult {
+    let cache_path = source
+        .cache_dir
+        .as_ref()
+        .map(|dir| disk_cache::tile_path(dir, key));
+    let bytes = match cache_path.as_deref().and_then(disk_cache::read_tile) {
+        Some(bytes) => Ok(bytes),
+        None if source.offline => Err("not in the offline tile cache".to_string()),
+        None => fetch_tile_bytes(&source.url(key)).inspect(|bytes| {
+            if let Some(path) = cache_path.as_deref() {
+                // A tile that cannot be cached is still drawn; it is fetched again next launch.
+                let _ = disk_cache::write_tile(path, bytes);
             }
-        }
+        }),
     };
-    match decode_tile_geometry(key, bytes.to_vec()) {
+    match bytes.and_then(|bytes| decode_tile_geometry(key, bytes)) {
         Ok(geometry) => TileResult {
             key,
             geometry: Some(geometry),
@@ -57,6 +33,19 @@ pub(crate) fn fetch_tile(url: &str, key: TileKey) -> TileResult {
     }
 }
 
+fn fetch_tile_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response = client.get(url).send().map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("status {}", response.status()));
+    }
+    let bytes = response.bytes().map_err(|err| err.to_string())?;
+    Ok(bytes.to_vec())
+}
+
 #[derive(Clone, PartialEq, ::prost::Message)]
 struct VectorTile {
     #[prost(message, repeated, tag = "3")]
@@ -96,11 +85,18 @@ enum GeomType {
     Polygon = 3,
 }
 
+/// Line layers drawn as roads, in order of preference: OSRM's `speeds` layer, then the road
+/// layers of OpenMapTiles / Mapbox-style vector tile servers.
+const ROAD_LAYERS: &[&str] = &["speeds", "transportation", "road"];
+
 fn decode_tile_geometry(key: TileKey, data: Vec<u8>) -> Result<TileGeometry, String> {
     use prost::Message;
 
     let tile = VectorTile::decode(data.as_slice()).map_err(|err| err.to_string())?;
-    let layer = match tile.layers.iter().find(|layer| layer.name == "speeds") {
+    let layer = match ROAD_LAYERS
+        .iter()
+        .find_map(|name| tile.layers.iter().find(|layer| layer.name == *name))
+    {
         Some(layer) => layer,
         None => return Ok(TileGeometry { lines: Vec::new() }),
     };
@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::disk_cache;
+use super::fetch_decode::load_tile;
+use super::types::TileSource;
+use super::*;
+
+fn unique_test_dir(label: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    std::env::temp_dir().join(format!("sim_ui_tile_tests_{label}_{nanos}"))
+}
+
+const KEY: TileKey = TileKey {
+    z: 14,
+    x: 8802,
+    y: 5373,
+};
+
+#[test]
+fn tile_urls_fill_placeholders_and_cache_dirs_ignore_the_api_key() {
+    let source = TileSource {
+        url_template: "https://tiles.example/{z}/{x}/{y}.pbf?key={api_key}".to_string(),
+        api_key: "secret".to_string(),
+        offline: false,
+        cache_dir: None,
+    };
+    assert_eq!(
+        source.url(KEY),
+        "https://tiles.example/14/8802/5373.pbf?key=secret"
+    );
+
+    let root = Path::new("/cache");
+    let dir = disk_cache::source_dir(root, &source.url_template);
+    assert_eq!(dir, disk_cache::source_dir(root, &source.url_template));
+    assert!(!dir.to_string_lossy().contains("secret"));
+    assert_ne!(
+        dir,
+        disk_cache::source_dir(
+            root,
+            "http://localhost:5000/tile/v1/driving/tile({x},{y},{z}).mvt"
+        )
+    );
+    assert_eq!(
+        disk_cache::tile_path(&dir, KEY),
+        dir.join("14").join("8802").join("5373.mvt")
+    );
+}
+
+#[test]
+fn offline_sources_load_only_cached_tiles() {
+    let cache_dir = unique_test_dir("offline");
+    let source = TileSource {
+        // Unroutable, so a network attempt would fail rather than hang.
+        url_template: "http://127.0.0.1:9/{z}/{x}/{y}.mvt".to_string(),
+        api_key: String::new(),
+        offline: true,
+        cache_dir: Some(cache_dir.clone()),
+    };
+
+    let missing = load_tile(&source, KEY);
+    assert!(missing.geometry.is_none());
+    assert_eq!(
+        missing.error.as_deref(),
+        Some("not in the offline tile cache")
+    );
+
+    // An empty buffer is a valid vector tile without layers.
+    disk_cache::write_tile(&disk_cache::tile_path(&cache_dir, KEY), &[])
+        .expect("cache write should succeed");
+    let cached = load_tile(&source, KEY);
+    assert!(cached.error.is_none());
+    assert!(cached.geometry.expect("cached tile").lines.is_empty());
+
+    let _ = std::fs::remove_dir_all(cache_dir);
+}
+
+#[test]
+fn changing_the_tile_source_retries_failed_tiles() {
+    let mut state = MapTileState::new();
+    state.cache_root = Some(unique_test_dir("retry"));
+    state.url_template = "http://127.0.0.1:9/{z}/{x}/{y}.mvt".to_string();
+    state.offline = true;
+
+    let wait_for_results = |state: &mut MapTileState| {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !state.inflight.is_empty() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+            state.drain_results();
+        }
+    };
+
+    state.request_missing_tiles("", [KEY]);
+    wait_for_results(&mut state);
+    assert_eq!(state.failed_tile_count(), 1);
+
+    // Same source: the failure is remembered and not retried.
+    state.request_missing_tiles("", [KEY]);
+    assert!(state.inflight.is_empty());
+
+    state.api_key = "new-key".to_string();
+    state.request_missing_tiles("", [KEY]);
+    assert_eq!(state.failed_tile_count(), 0);
+    assert!(state.inflight.contains(&KEY));
+    wait_for_results(&mut state);
+}
+
+#[test]
+fn without_a_tile_url_or_osrm_endpoint_nothing_is_requested() {
+    let mut state = MapTileState::new();
+    state.url_template.clear();
+    state.request_missing_tiles("  ", [KEY]);
+    assert!(state.inflight.is_empty());
+    assert!(!state.has_custom_source());
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use std::time::Instant;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -62,3 +63,24 @@ pub(crate) struct TileResult {
     pub geometry: Option<TileGeometry>,
     pub error: Option<String>,
 }
+
+/// Resolved tile source handed to a fetch thread.
+#[derive(Debug, Clone)]
+pub(crate) struct TileSource {
+    /// URL with `{z}`, `{x}`, `{y}` and optional `{api_key}` placeholders.
+    pub url_template: String,
+    pub api_key: String,
+    pub offline: bool,
+    /// Cache directory of this source; `None` disables the disk cache.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl TileSource {
+    pub(crate) fn url(&self, key: TileKey) -> String {
+        self.url_template
+            .replace("{z}", &key.z.to_string())
+            .replace("{x}", &key.x.to_string())
+            .replace("{y}", &key.y.to_string())
+            .replace("{api_key}", &self.api_key)
+    }
+}
@@ -0,0 +1,149 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+use super::store::{load_library, write_json_atomic};
+use super::{PresetStoreError, PRESETS_FILE_NAME, SETTINGS_FILE_NAME};
+
+/// Preset settings kept next to the default library in the platform config directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetSettingsV1 {
+    /// Library file chosen in the preset settings; `None` uses the config directory.
+    #[serde(default)]
+    library_path: Option<PathBuf>,
+}
+
+/// Where the preset library is read from and written to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PresetLocation {
+    pub(crate) config_dir: PathBuf,
+    pub(crate) library_path: PathBuf,
+    pub(crate) is_custom: bool,
+    /// Legacy working-directory library moved into the config directory on this launch.
+    pub(crate) migrated_from: Option<PathBuf>,
+    pub(crate) migration_warning: Option<String>,
+}
+
+/// Platform config directory of the UI (e.g. `~/.config/sim_ui` on Linux).
+pub(crate) fn platform_config_dir() -> Result<PathBuf, PresetStoreError> {
+    ProjectDirs::from("", "", "sim_ui")
+        .map(|dirs| dirs.config_dir().to_path_buf())
+        .ok_or_else(|| {
+            PresetStoreError::Io("failed to resolve the platform config directory".to_string())
+        })
+}
+
+/// Resolve the library for this launch, migrating a `sim_ui_presets.json` left in the working
+/// directory by earlier versions.
+pub(crate) fn presets_location() -> Result<PresetLocation, PresetStoreError> {
+    let config_dir = platform_config_dir()?;
+    let legacy_dir = std::env::current_dir().ok();
+    resolve_presets_location(&config_dir, legacy_dir.as_deref())
+}
+
+pub(crate) fn resolve_presets_location(
+    config_dir: &Path,
+    legacy_dir: Option<&Path>,
+) -> Result<PresetLocation, PresetStoreError> {
+    let settings = load_settings(config_dir)?;
+    if let Some(library_path) = settings.library_path {
+        return Ok(PresetLocation {
+            config_dir: config_dir.to_path_buf(),
+            library_path,
+            is_custom: true,
+            migrated_from: None,
+            migration_warning: None,
+        });
+    }
+
+    let library_path = config_dir.join(PRESETS_FILE_NAME);
+    let mut migrated_from = None;
+    let mut migration_warning = None;
+    if let Some(legacy_path) = legacy_dir.map(|dir| dir.join(PRESETS_FILE_NAME)) {
+        if legacy_path.is_file() && !library_path.exists() && legacy_path != library_path {
+            match migrate_legacy_library(&legacy_path, &library_path) {
+                Ok(()) => migrated_from = Some(legacy_path),
+                Err(error) => {
+                    migration_warning = Some(format!(
+                        "Preset migration warning: {error}; '{}' was left in place",
+                        legacy_path.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(PresetLocation {
+        config_dir: config_dir.to_path_buf(),
+        library_path,
+        is_custom: false,
+        migrated_from,
+        migration_warning,
+    })
+}
+
+/// Persist a custom library file (`None` restores the config directory default). A directory
+/// selects `sim_ui_presets.json` inside it; relative paths are made absolute so the choice does
+/// not depend on where the UI is launched from.
+pub(crate) fn set_library_path(
+    config_dir: &Path,
+    library_path: Option<&Path>,
+) -> Result<PresetLocation, PresetStoreError> {
+    let library_path = library_path
+        .map(|path| {
+            let path = std::path::absolute(path).map_err(|error| {
+                PresetStoreError::Io(format!(
+                    "failed to resolve library path '{}': {error}",
+                    path.display()
+                ))
+            })?;
+            Ok(if path.is_dir() {
+                path.join(PRESETS_FILE_NAME)
+            } else {
+                path
+            })
+        })
+        .transpose()?;
+    let settings = PresetSettingsV1 { library_path };
+    write_json_atomic(&config_dir.join(SETTINGS_FILE_NAME), &settings)?;
+    resolve_presets_location(config_dir, None)
+}
+
+fn load_settings(config_dir: &Path) -> Result<PresetSettingsV1, PresetStoreError> {
+    let path = config_dir.join(SETTINGS_FILE_NAME);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(PresetSettingsV1::default())
+        }
+        Err(error) => {
+            return Err(PresetStoreError::Io(format!(
+                "failed to read preset settings '{}': {error}",
+                path.display()
+            )))
+        }
+    };
+    serde_json::from_str(&contents).map_err(|error| {
+        PresetStoreError::InvalidFormat(format!(
+            "invalid preset settings '{}': {error}",
+            path.display()
+        ))
+    })
+}
+
+/// Copy a valid legacy library to `target`, then remove the original so later launches from
+/// the same directory do not see two libraries.
+fn migrate_legacy_library(legacy_path: &Path, target: &Path) -> Result<(), PresetStoreError> {
+    let library = load_library(legacy_path)?;
+    write_json_atomic(target, &library)?;
+    fs::remove_file(legacy_path).map_err(|error| {
+        PresetStoreError::Io(format!(
+            "migrated presets to '{}' but failed to remove '{}': {error}",
+            target.display(),
+            legacy_path.display()
+        ))
+    })
+}
@@ -1,4 +1,6 @@
+mod location;
 mod model;
+mod random;
 mod scenario;
 mod store;
 
@@ -8,6 +10,7 @@ mod tests;
 use std::fmt;
 
 pub(crate) const PRESETS_FILE_NAME: &str = "sim_ui_presets.json";
+pub(crate) const SETTINGS_FILE_NAME: &str = "sim_ui_settings.json";
 pub(super) const PRESET_FILE_VERSION: u32 = 1;
 pub(super) const AUTOSAVE_PRESET_NAME: &str = "autosave";
 
@@ -45,8 +48,9 @@ pub(crate) enum DeleteNamedPresetOutcome {
     NotFound,
 }
 
+pub(crate) use location::{presets_location, set_library_path, PresetLocation};
 pub(crate) use scenario::ScenarioPresetV1;
 pub(crate) use store::{
     delete_named_preset, export_library, import_library, list_named_presets, load_active_preset,
-    load_named_preset, presets_file_path, save_autosave_preset, save_named_preset,
+    load_named_preset, read_named_preset, save_autosave_preset, save_named_preset,
 };
@@ -0,0 +1,88 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::app::defaults::AppDefaults;
+
+use super::scenario::{
+    MatchingAlgorithmPresetV1, RoutingModePresetV1, ScenarioPresetV1, SpawnModePresetV1,
+    TrafficProfileModePresetV1,
+};
+
+/// Round to a step so generated values read like hand-picked inputs.
+fn round_to(value: f64, step: f64) -> f64 {
+    (value / step).round() * step
+}
+
+impl ScenarioPresetV1 {
+    /// Random but valid scenario for exploration and demos. The same `seed` always produces
+    /// the same scenario, and it is also used as the simulation seed so the run is reproducible.
+    /// Routing stays on the H3 grid so the scenario runs without an OSRM server; the start
+    /// date is kept from `defaults`.
+    pub(crate) fn random(seed: u64, defaults: &AppDefaults) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut preset = Self::from_defaults(defaults);
+
+        preset.map_size_km = round_to(rng.gen_range(2.0..=20.0), 0.5);
+        preset.num_drivers = rng.gen_range(10..=500);
+        preset.num_riders = preset.num_drivers * rng.gen_range(2..=10);
+        preset.initial_driver_count = rng.gen_range(0..=preset.num_drivers);
+        preset.initial_rider_count = rng.gen_range(0..=preset.num_riders / 10);
+        preset.request_window_hours = rng.gen_range(1..=6);
+        preset.driver_spread_hours = rng.gen_range(1..=preset.request_window_hours);
+        preset.simulation_duration_hours = preset.request_window_hours + rng.gen_range(1..=3);
+
+        preset.match_radius_km = round_to(rng.gen_range(0.5..=preset.map_size_km.min(5.0)), 0.1);
+        preset.min_trip_km = round_to(rng.gen_range(0.5..=3.0), 0.1);
+        preset.max_trip_km = round_to(
+            rng.gen_range(preset.min_trip_km + 1.0..=preset.map_size_km.max(5.0)),
+            0.1,
+        );
+        preset.rider_cancel_min_mins = rng.gen_range(2..=10);
+        preset.rider_cancel_max_mins = rng.gen_range(preset.rider_cancel_min_mins..=30);
+
+        preset.seed_enabled = true;
+        preset.seed_value = seed;
+        preset.matching_algorithm = match rng.gen_range(0..3) {
+            0 => MatchingAlgorithmPresetV1::Simple,
+            1 => MatchingAlgorithmPresetV1::CostBased,
+            _ => MatchingAlgorithmPresetV1::Hungarian,
+        };
+        preset.batch_matching_enabled = rng.gen_bool(0.5);
+        preset.batch_interval_secs = rng.gen_range(1..=30);
+
+        preset.base_fare = round_to(rng.gen_range(1.0..=5.0), 0.1);
+        preset.per_km_rate = round_to(rng.gen_range(0.8..=3.0), 0.05);
+        preset.commission_rate = round_to(rng.gen_range(0.05..=0.35), 0.01);
+        preset.surge_enabled = rng.gen_bool(0.5);
+        preset.surge_radius_k = rng.gen_range(1..=3);
+        preset.surge_max_multiplier = round_to(rng.gen_range(1.2..=3.0), 0.1);
+
+        preset.max_willingness_to_pay = round_to(rng.gen_range(20.0..=150.0), 1.0);
+        preset.max_acceptable_eta_min = rng.gen_range(5..=30);
+        preset.accept_probability = round_to(rng.gen_range(0.5..=1.0), 0.05);
+        preset.max_quote_rejections = rng.gen_range(1..=5);
+        preset.driver_base_acceptance_score = round_to(rng.gen_range(-1.0..=3.0), 0.1);
+        preset.driver_fare_weight = round_to(rng.gen_range(0.0..=1.0), 0.01);
+        preset.driver_pickup_distance_penalty = round_to(rng.gen_range(-3.0..=0.0), 0.1);
+
+        preset.routing_mode = RoutingModePresetV1::H3Grid;
+        preset.traffic_profile_mode = if rng.gen_bool(0.5) {
+            TrafficProfileModePresetV1::Berlin
+        } else {
+            TrafficProfileModePresetV1::None
+        };
+        preset.congestion_zones_enabled = rng.gen_bool(0.5);
+        preset.dynamic_congestion_enabled = rng.gen_bool(0.5);
+        preset.base_speed_enabled = rng.gen_bool(0.3);
+        preset.base_speed_kmh = round_to(rng.gen_range(20.0..=60.0), 5.0);
+        preset.spawn_mode = if rng.gen_bool(0.5) {
+            SpawnModePresetV1::BerlinHotspots
+        } else {
+            SpawnModePresetV1::Uniform
+        };
+        preset.start_hour = rng.gen_range(0..=23);
+        preset.start_minute = rng.gen_range(0..4) * 15;
+
+        preset.normalized(defaults)
+    }
+}
@@ -1,9 +1,17 @@
 use serde::{Deserialize, Serialize};
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::clock::ONE_HOUR_MS;
+use sim_core::scenario::{MatchingAlgorithmType as ScenarioMatchingAlgorithm, ScenarioParams};
+use sim_core::spawner::{Hotspot, SpawnWeightingKind};
+use sim_core::traffic::TrafficProfileKind;
 
 use crate::app::defaults::AppDefaults;
 use crate::app::simulation::{
     MatchingAlgorithmType, RoutingMode, SimUiApp, SpawnMode, TrafficProfileMode,
 };
+use crate::app::spawn_paint::SpawnPainting;
+use crate::ui::constants::{H3_RES9_CELL_WIDTH_KM, METERS_PER_DEG_LAT};
+use crate::ui::utils::datetime_from_unix_ms;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -88,6 +96,7 @@ impl From<TrafficProfileModePresetV1> for TrafficProfileMode {
 pub(super) enum SpawnModePresetV1 {
     Uniform,
     BerlinHotspots,
+    Painted,
 }
 
 impl From<SpawnMode> for SpawnModePresetV1 {
@@ -95,6 +104,7 @@ impl From<SpawnMode> for SpawnModePresetV1 {
         match value {
             SpawnMode::Uniform => Self::Uniform,
             SpawnMode::BerlinHotspots => Self::BerlinHotspots,
+            SpawnMode::Painted => Self::Painted,
         }
     }
 }
@@ -104,6 +114,7 @@ impl From<SpawnModePresetV1> for SpawnMode {
         match value {
             SpawnModePresetV1::Uniform => Self::Uniform,
             SpawnModePresetV1::BerlinHotspots => Self::BerlinHotspots,
+            SpawnModePresetV1::Painted => Self::Painted,
         }
     }
 }
@@ -150,15 +161,20 @@ pub(crate) struct ScenarioPresetV1 {
     pub(super) base_speed_enabled: bool,
     pub(super) base_speed_kmh: f64,
     pub(super) spawn_mode: SpawnModePresetV1,
+    /// Painted spawn weights; kept in every mode so switching back to Painted restores them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(super) painted_hotspots: Vec<Hotspot>,
     pub(super) start_year: i32,
     pub(super) start_month: u32,
     pub(super) start_day: u32,
     pub(super) start_hour: u32,
     pub(super) start_minute: u32,
+    /// IANA zone the start time is read in; older presets load as UTC.
+    #[serde(default)]
+    pub(super) time_zone: CityTimeZone,
 }
 
 impl ScenarioPresetV1 {
-    #[cfg(test)]
     pub(crate) fn from_defaults(defaults: &AppDefaults) -> Self {
         Self {
             num_riders: defaults.num_riders,
@@ -200,14 +216,101 @@ impl ScenarioPresetV1 {
             base_speed_enabled: defaults.base_speed_enabled,
             base_speed_kmh: defaults.base_speed_kmh,
             spawn_mode: defaults.spawn_mode.into(),
+            painted_hotspots: defaults.spawn_painting.to_hotspots(),
             start_year: defaults.start_year,
             start_month: defaults.start_month,
             start_day: defaults.start_day,
             start_hour: defaults.start_hour,
             start_minute: defaults.start_minute,
+            time_zone: defaults.time_zone,
         }
     }
 
+    /// Controls for a built-in `ScenarioParams::preset`. Settings the controls cannot express
+    /// (e.g. rider price sensitivity, non-square bounds) are approximated or left at `defaults`;
+    /// the app runs the preset's full parameters until a control changes.
+    pub(crate) fn from_scenario_params(params: &ScenarioParams, defaults: &AppDefaults) -> Self {
+        let mut preset = Self::from_defaults(defaults);
+        preset.num_riders = params.num_riders;
+        preset.num_drivers = params.num_drivers;
+        preset.initial_rider_count = params.initial_rider_count;
+        preset.initial_driver_count = params.initial_driver_count;
+        preset.request_window_hours = params.request_window_ms.div_ceil(ONE_HOUR_MS);
+        preset.driver_spread_hours = params.driver_spread_ms.div_ceil(ONE_HOUR_MS);
+        if let Some(end_ms) = params.simulation_end_time_ms {
+            preset.simulation_duration_hours = end_ms.div_ceil(ONE_HOUR_MS);
+        }
+        preset.match_radius_km = params.match_radius as f64 * H3_RES9_CELL_WIDTH_KM;
+        preset.min_trip_km = params.min_trip_cells as f64 * H3_RES9_CELL_WIDTH_KM;
+        preset.max_trip_km = params.max_trip_cells as f64 * H3_RES9_CELL_WIDTH_KM;
+        preset.map_size_km = (params.lat_max - params.lat_min) * METERS_PER_DEG_LAT / 1000.0;
+        if let Some(seed) = params.seed {
+            preset.seed_enabled = true;
+            preset.seed_value = seed;
+        }
+        if let Some(algorithm) = params.matching_algorithm_type {
+            preset.matching_algorithm = match algorithm {
+                ScenarioMatchingAlgorithm::Simple => MatchingAlgorithmPresetV1::Simple,
+                ScenarioMatchingAlgorithm::CostBased => MatchingAlgorithmPresetV1::CostBased,
+                ScenarioMatchingAlgorithm::Hungarian => MatchingAlgorithmPresetV1::Hungarian,
+            };
+        }
+        if let Some(enabled) = params.batch_matching_enabled {
+            preset.batch_matching_enabled = enabled;
+        }
+        if let Some(interval_secs) = params.batch_interval_secs {
+            preset.batch_interval_secs = interval_secs;
+        }
+        if let Some(pricing) = params.pricing_config {
+            preset.base_fare = pricing.base_fare;
+            preset.per_km_rate = pricing.per_km_rate;
+            preset.commission_rate = pricing.commission_rate;
+            preset.surge_enabled = pricing.surge_enabled;
+            preset.surge_radius_k = pricing.surge_radius_k;
+            preset.surge_max_multiplier = pricing.surge_max_multiplier;
+        }
+        if let Some(quote) = &params.rider_quote_config {
+            preset.max_willingness_to_pay = quote.max_willingness_to_pay;
+            preset.max_acceptable_eta_min = quote.max_acceptable_eta_ms / 60_000;
+            preset.accept_probability = quote.accept_probability;
+            preset.max_quote_rejections = quote.max_quote_rejections;
+        }
+        if let Some(decision) = &params.driver_decision_config {
+            preset.driver_base_acceptance_score = decision.base_acceptance_score;
+            preset.driver_fare_weight = decision.fare_weight;
+            preset.driver_pickup_distance_penalty = decision.pickup_distance_penalty;
+        }
+        preset.traffic_profile_mode = match params.traffic_profile {
+            TrafficProfileKind::Berlin => TrafficProfileModePresetV1::Berlin,
+            _ => TrafficProfileModePresetV1::None,
+        };
+        preset.congestion_zones_enabled = params.congestion_zones_enabled;
+        preset.dynamic_congestion_enabled = params.dynamic_congestion_enabled;
+        preset.base_speed_enabled = params.base_speed_kmh.is_some();
+        if let Some(speed) = params.base_speed_kmh {
+            preset.base_speed_kmh = speed;
+        }
+        preset.spawn_mode = match &params.spawn_weighting {
+            SpawnWeightingKind::BerlinHotspots => SpawnModePresetV1::BerlinHotspots,
+            SpawnWeightingKind::Hotspots(hotspots) => {
+                preset.painted_hotspots = hotspots.clone();
+                SpawnModePresetV1::Painted
+            }
+            _ => SpawnModePresetV1::Uniform,
+        };
+        preset.time_zone = params.local_time.time_zone().unwrap_or(CityTimeZone::UTC);
+        if let Some(epoch_ms) = params.epoch_ms.filter(|epoch_ms| *epoch_ms > 0) {
+            let (year, month, day, hour, minute) =
+                datetime_from_unix_ms(preset.time_zone, epoch_ms as u64);
+            preset.start_year = year;
+            preset.start_month = month;
+            preset.start_day = day;
+            preset.start_hour = hour;
+            preset.start_minute = minute;
+        }
+        preset
+    }
+
     pub(crate) fn from_app(app: &SimUiApp) -> Self {
         Self {
             num_riders: app.num_riders,
@@ -249,11 +352,13 @@ impl ScenarioPresetV1 {
             base_speed_enabled: app.base_speed_enabled,
             base_speed_kmh: app.base_speed_kmh,
             spawn_mode: app.spawn_mode.into(),
+            painted_hotspots: app.spawn_painting.to_hotspots(),
             start_year: app.start_year,
             start_month: app.start_month,
             start_day: app.start_day,
             start_hour: app.start_hour,
             start_minute: app.start_minute,
+            time_zone: app.time_zone,
         }
     }
 
@@ -299,14 +404,16 @@ impl ScenarioPresetV1 {
         defaults.base_speed_enabled = normalized.base_speed_enabled;
         defaults.base_speed_kmh = normalized.base_speed_kmh;
         defaults.spawn_mode = normalized.spawn_mode.into();
+        defaults.spawn_painting = SpawnPainting::from_hotspots(&normalized.painted_hotspots);
         defaults.start_year = normalized.start_year;
         defaults.start_month = normalized.start_month;
         defaults.start_day = normalized.start_day;
         defaults.start_hour = normalized.start_hour;
         defaults.start_minute = normalized.start_minute;
+        defaults.time_zone = normalized.time_zone;
     }
 
-    fn normalized(mut self, defaults: &AppDefaults) -> Self {
+    pub(super) fn normalized(mut self, defaults: &AppDefaults) -> Self {
         self.num_riders = self.num_riders.clamp(1, 10_000);
         self.num_drivers = self.num_drivers.clamp(1, 10_000);
         self.initial_rider_count = self.initial_rider_count.clamp(0, 10_000);
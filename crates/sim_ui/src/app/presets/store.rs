@@ -1,22 +1,17 @@
 use std::collections::HashSet;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::Serialize;
+
 use super::model::{NamedPresetV1, PresetLibraryV1};
 use super::{
     DeleteNamedPresetOutcome, PresetMetadata, PresetStoreError, SaveNamedPresetOutcome,
-    ScenarioPresetV1, AUTOSAVE_PRESET_NAME, PRESETS_FILE_NAME, PRESET_FILE_VERSION,
+    ScenarioPresetV1, AUTOSAVE_PRESET_NAME, PRESET_FILE_VERSION,
 };
 
-pub(crate) fn presets_file_path() -> Result<PathBuf, PresetStoreError> {
-    let cwd = std::env::current_dir().map_err(|error| {
-        PresetStoreError::Io(format!("failed to read current directory: {error}"))
-    })?;
-    Ok(cwd.join(PRESETS_FILE_NAME))
-}
-
 pub(crate) fn load_active_preset(
     path: &Path,
 ) -> Result<Option<ScenarioPresetV1>, PresetStoreError> {
@@ -129,6 +124,19 @@ pub(crate) fn load_named_preset(
     Ok(scenario)
 }
 
+/// Like [`load_named_preset`], but leaves the active marker untouched.
+pub(crate) fn read_named_preset(
+    path: &Path,
+    name: &str,
+) -> Result<Option<ScenarioPresetV1>, PresetStoreError> {
+    let library = load_library(path)?;
+    Ok(library
+        .presets
+        .into_iter()
+        .find(|preset| preset.name == name)
+        .map(|preset| preset.scenario))
+}
+
 pub(crate) fn delete_named_preset(
     path: &Path,
     name: &str,
@@ -272,6 +280,14 @@ pub(super) fn load_library(path: &Path) -> Result<PresetLibraryV1, PresetStoreEr
 }
 
 fn save_library_atomic(path: &Path, library: &PresetLibraryV1) -> Result<(), PresetStoreError> {
+    write_json_atomic(path, library)
+}
+
+/// Write `value` as pretty JSON through a temp file so a crash never leaves a truncated file.
+pub(super) fn write_json_atomic<T: Serialize>(
+    path: &Path,
+    value: &T,
+) -> Result<(), PresetStoreError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|error| {
             PresetStoreError::Io(format!(
@@ -281,7 +297,7 @@ fn save_library_atomic(path: &Path, library: &PresetLibraryV1) -> Result<(), Pre
         })?;
     }
 
-    let serialized = serde_json::to_string_pretty(library).map_err(|error| {
+    let serialized = serde_json::to_string_pretty(value).map_err(|error| {
         PresetStoreError::Io(format!("failed to serialize presets to json: {error}"))
     })?;
 
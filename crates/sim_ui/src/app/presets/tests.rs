@@ -374,3 +374,144 @@ fn export_import_round_trip_preserves_library_and_active_marker() {
         .expect("morning preset should exist");
     assert_eq!(loaded_morning, morning);
 }
+
+#[test]
+fn builtin_presets_map_onto_controls() {
+    use super::scenario::{SpawnModePresetV1, TrafficProfileModePresetV1};
+    use sim_core::scenario::ScenarioParams;
+
+    let defaults = AppDefaults::new();
+    for name in ScenarioParams::preset_names() {
+        let params = ScenarioParams::preset(name).expect("listed preset should resolve");
+        let mut applied = AppDefaults::new();
+        ScenarioPresetV1::from_scenario_params(&params, &defaults).apply_to_defaults(&mut applied);
+        assert_eq!(applied.num_riders, params.num_riders, "{name}");
+        assert_eq!(applied.num_drivers, params.num_drivers, "{name}");
+    }
+
+    let rush = ScenarioPresetV1::from_scenario_params(
+        &ScenarioParams::preset("berlin-weekday-rush").expect("preset should exist"),
+        &defaults,
+    );
+    assert!(rush.surge_enabled);
+    assert_eq!(
+        rush.traffic_profile_mode,
+        TrafficProfileModePresetV1::Berlin
+    );
+    assert_eq!(rush.spawn_mode, SpawnModePresetV1::BerlinHotspots);
+    assert_eq!(
+        (rush.start_year, rush.start_month, rush.start_day),
+        (2024, 3, 5)
+    );
+}
+
+#[test]
+fn time_zone_is_kept_and_older_presets_load_as_utc() {
+    use sim_core::clock::civil::CityTimeZone;
+    use sim_core::scenario::ScenarioParams;
+
+    let defaults = AppDefaults::new();
+    // 2024-07-01 06:00 UTC is 08:00 in Berlin (CEST).
+    let params = ScenarioParams::default()
+        .with_epoch_ms(1_719_813_600_000)
+        .with_time_zone(CityTimeZone::berlin());
+    let preset = ScenarioPresetV1::from_scenario_params(&params, &defaults);
+    assert_eq!(preset.time_zone, CityTimeZone::berlin());
+    assert_eq!((preset.start_hour, preset.start_minute), (8, 0));
+
+    let mut json = serde_json::to_value(&preset).expect("preset should serialize");
+    assert_eq!(json["time_zone"], "Europe/Berlin");
+    json.as_object_mut()
+        .expect("preset is an object")
+        .remove("time_zone");
+    let legacy: ScenarioPresetV1 = serde_json::from_value(json).expect("legacy preset loads");
+    assert_eq!(legacy.time_zone, CityTimeZone::UTC);
+}
+
+#[test]
+fn legacy_library_migrates_into_config_dir() {
+    let config_dir = unique_test_path("migrate_config");
+    let legacy_dir = unique_test_path("migrate_legacy");
+    let legacy_path = legacy_dir.join(PRESETS_FILE_NAME);
+    let scenario = ScenarioPresetV1::from_defaults(&AppDefaults::new());
+    save_named_preset(&legacy_path, "legacy", &scenario, false).expect("save should succeed");
+
+    let location = super::location::resolve_presets_location(&config_dir, Some(&legacy_dir))
+        .expect("resolve should succeed");
+    assert_eq!(location.library_path, config_dir.join(PRESETS_FILE_NAME));
+    assert!(!location.is_custom);
+    assert_eq!(location.migrated_from.as_ref(), Some(&legacy_path));
+    assert!(!legacy_path.exists());
+    assert_eq!(
+        load_named_preset(&location.library_path, "legacy").expect("load should succeed"),
+        Some(scenario)
+    );
+
+    // A library already in the config dir wins over a later legacy file.
+    fs::write(&legacy_path, "{ not json").expect("write should succeed");
+    let location = super::location::resolve_presets_location(&config_dir, Some(&legacy_dir))
+        .expect("resolve should succeed");
+    assert_eq!(location.migrated_from, None);
+    assert_eq!(location.migration_warning, None);
+    assert!(legacy_path.exists());
+}
+
+#[test]
+fn malformed_legacy_library_is_left_in_place() {
+    let config_dir = unique_test_path("migrate_malformed_config");
+    let legacy_dir = unique_test_path("migrate_malformed_legacy");
+    let legacy_path = legacy_dir.join(PRESETS_FILE_NAME);
+    fs::create_dir_all(&legacy_dir).expect("mkdir should succeed");
+    fs::write(&legacy_path, "{ not json").expect("write should succeed");
+
+    let location = super::location::resolve_presets_location(&config_dir, Some(&legacy_dir))
+        .expect("resolve should succeed");
+    assert_eq!(location.migrated_from, None);
+    assert!(location.migration_warning.is_some());
+    assert!(legacy_path.exists());
+    assert!(!location.library_path.exists());
+}
+
+#[test]
+fn custom_library_path_is_remembered() {
+    let config_dir = unique_test_path("custom_config");
+    let library_dir = unique_test_path("custom_library");
+    fs::create_dir_all(&library_dir).expect("mkdir should succeed");
+
+    let location =
+        set_library_path(&config_dir, Some(library_dir.as_path())).expect("set should succeed");
+    assert!(location.is_custom);
+    assert_eq!(location.library_path, library_dir.join(PRESETS_FILE_NAME));
+    let reloaded = super::location::resolve_presets_location(&config_dir, None)
+        .expect("resolve should succeed");
+    assert_eq!(reloaded, location);
+
+    let location = set_library_path(&config_dir, None).expect("reset should succeed");
+    assert!(!location.is_custom);
+    assert_eq!(location.library_path, config_dir.join(PRESETS_FILE_NAME));
+}
+
+#[test]
+fn random_scenarios_are_reproducible_and_already_normalized() {
+    let defaults = AppDefaults::new();
+    for seed in 0..200 {
+        let preset = ScenarioPresetV1::random(seed, &defaults);
+        assert_eq!(preset, ScenarioPresetV1::random(seed, &defaults), "{seed}");
+        assert_eq!(preset.clone().normalized(&defaults), preset, "{seed}");
+        assert!(preset.seed_enabled);
+        assert_eq!(preset.seed_value, seed);
+        assert!(preset.min_trip_km < preset.max_trip_km, "{seed}");
+        assert!(
+            preset.driver_spread_hours <= preset.request_window_hours,
+            "{seed}"
+        );
+        assert!(
+            preset.simulation_duration_hours > preset.request_window_hours,
+            "{seed}"
+        );
+    }
+    assert_ne!(
+        ScenarioPresetV1::random(1, &defaults),
+        ScenarioPresetV1::random(2, &defaults)
+    );
+}
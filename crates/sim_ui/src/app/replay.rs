@@ -0,0 +1,88 @@
+//! Playback of a recorded run bundle (`.simrun`) in place of a live simulation.
+
+use std::collections::VecDeque;
+use std::path::Path;
+
+use bevy_ecs::prelude::World;
+use sim_core::run_bundle::RunBundle;
+use sim_core::telemetry::{SimSnapshot, SimSnapshots};
+
+/// Recorded snapshots not yet shown, released into the world's `SimSnapshots` as replay time
+/// advances.
+pub struct Replay {
+    pub label: String,
+    pub now_ms: u64,
+    pub end_time_ms: u64,
+    pending: VecDeque<SimSnapshot>,
+}
+
+impl Replay {
+    /// Read `path` and return the world to display (see `RunBundle::restore_world`) with its
+    /// snapshots held back for playback.
+    pub fn load(path: &Path) -> Result<(World, Self), String> {
+        let bundle = RunBundle::read(path).map_err(|error| error.to_string())?;
+        let mut world = bundle.restore_world();
+        let pending = world
+            .get_resource_mut::<SimSnapshots>()
+            .map(|mut snapshots| {
+                snapshots.last_snapshot_at = None;
+                std::mem::take(&mut snapshots.snapshots)
+            })
+            .unwrap_or_default();
+        let label = bundle
+            .label
+            .clone()
+            .unwrap_or_else(|| path.display().to_string());
+        let replay = Self {
+            label,
+            now_ms: 0,
+            end_time_ms: bundle.end_time_ms,
+            pending,
+        };
+        Ok((world, replay))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Release up to `count` snapshots; returns how many were released.
+    pub fn step(&mut self, world: &mut World, count: usize) -> usize {
+        let mut released = 0;
+        while released < count {
+            let Some(snapshot) = self.pending.pop_front() else {
+                break;
+            };
+            self.show(world, snapshot);
+            released += 1;
+        }
+        if self.is_finished() {
+            self.now_ms = self.end_time_ms.max(self.now_ms);
+        }
+        released
+    }
+
+    /// Advance replay time by `budget_ms`, releasing every snapshot recorded up to it; returns
+    /// how many were released.
+    pub fn advance(&mut self, world: &mut World, budget_ms: f64) -> usize {
+        let until_ms = self.now_ms.saturating_add(budget_ms.max(0.0) as u64);
+        let mut released = 0;
+        while self
+            .pending
+            .front()
+            .is_some_and(|snapshot| snapshot.timestamp_ms <= until_ms)
+        {
+            released += self.step(world, 1);
+        }
+        self.now_ms = self.now_ms.max(until_ms.min(self.end_time_ms));
+        released
+    }
+
+    fn show(&mut self, world: &mut World, snapshot: SimSnapshot) {
+        self.now_ms = self.now_ms.max(snapshot.timestamp_ms);
+        if let Some(mut snapshots) = world.get_resource_mut::<SimSnapshots>() {
+            snapshots.last_snapshot_at = Some(snapshot.timestamp_ms);
+            snapshots.snapshots.push_back(snapshot);
+        }
+    }
+}
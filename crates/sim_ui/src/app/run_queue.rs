@@ -0,0 +1,191 @@
+//! Queue of presets run one after another to completion, off-screen, with an outcome summary
+//! per run for side-by-side comparison.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use bevy_ecs::prelude::World;
+use bevy_ecs::schedule::Schedule;
+use sim_core::clock::SimulationClock;
+use sim_core::runner::{run_next_event, simulation_schedule};
+use sim_core::telemetry::{CompletedTripRecord, SimTelemetry};
+
+use crate::app::presets::ScenarioPresetV1;
+
+/// Per-frame time slice given to the run queue so the UI stays responsive.
+pub const RUN_QUEUE_FRAME_BUDGET: Duration = Duration::from_millis(25);
+
+/// Events run between deadline checks.
+const EVENTS_PER_CHECK: usize = 256;
+
+/// Preset waiting for its turn.
+#[derive(Debug, Clone)]
+pub struct QueuedRun {
+    pub name: String,
+    scenario: ScenarioPresetV1,
+}
+
+/// Outcome of one finished queued run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    pub name: String,
+    pub end_time_ms: u64,
+    pub steps: usize,
+    pub wall_secs: f64,
+    pub riders_completed: u64,
+    pub riders_cancelled: u64,
+    pub riders_abandoned_quote: u64,
+    /// Completed share of resolved riders (0–1).
+    pub conversion: f64,
+    pub avg_time_to_match_ms: Option<f64>,
+    pub avg_time_to_pickup_ms: Option<f64>,
+    pub platform_revenue: f64,
+    pub total_fares: f64,
+}
+
+impl RunSummary {
+    fn from_world(name: String, world: &World, steps: usize, wall_secs: f64) -> Self {
+        let end_time_ms = world
+            .get_resource::<SimulationClock>()
+            .map_or(0, |clock| clock.now());
+        let Some(telemetry) = world.get_resource::<SimTelemetry>() else {
+            return Self {
+                name,
+                end_time_ms,
+                steps,
+                wall_secs,
+                riders_completed: 0,
+                riders_cancelled: 0,
+                riders_abandoned_quote: 0,
+                conversion: 0.0,
+                avg_time_to_match_ms: None,
+                avg_time_to_pickup_ms: None,
+                platform_revenue: 0.0,
+                total_fares: 0.0,
+            };
+        };
+        let resolved = telemetry.riders_resolved_total();
+        let trips = &telemetry.completed_trips;
+        let mean = |metric: fn(&CompletedTripRecord) -> u64| {
+            (!trips.is_empty()).then(|| {
+                trips.iter().map(|trip| metric(trip) as f64).sum::<f64>() / trips.len() as f64
+            })
+        };
+        Self {
+            name,
+            end_time_ms,
+            steps,
+            wall_secs,
+            riders_completed: telemetry.riders_completed_total,
+            riders_cancelled: telemetry.riders_cancelled_total,
+            riders_abandoned_quote: telemetry.riders_abandoned_quote_total,
+            conversion: if resolved > 0 {
+                telemetry.riders_completed_total as f64 / resolved as f64
+            } else {
+                0.0
+            },
+            avg_time_to_match_ms: mean(CompletedTripRecord::time_to_match),
+            avg_time_to_pickup_ms: mean(CompletedTripRecord::time_to_pickup),
+            platform_revenue: telemetry.platform_revenue_total,
+            total_fares: telemetry.total_fares_collected,
+        }
+    }
+}
+
+/// Run currently being simulated.
+pub struct ActiveRun {
+    pub name: String,
+    pub steps: usize,
+    world: World,
+    schedule: Schedule,
+    started_at: Instant,
+}
+
+impl ActiveRun {
+    fn new(name: String, world: World) -> Self {
+        Self {
+            name,
+            steps: 0,
+            world,
+            schedule: simulation_schedule(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Simulation time of the run.
+    pub fn now_ms(&self) -> u64 {
+        self.world
+            .get_resource::<SimulationClock>()
+            .map_or(0, |clock| clock.now())
+    }
+
+    /// Run events until the simulation ends (`true`) or `deadline` passes (`false`).
+    fn advance_until(&mut self, deadline: Instant) -> bool {
+        loop {
+            for _ in 0..EVENTS_PER_CHECK {
+                if !run_next_event(&mut self.world, &mut self.schedule) {
+                    return true;
+                }
+                self.steps += 1;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+        }
+    }
+
+    fn finish(self) -> RunSummary {
+        let wall_secs = self.started_at.elapsed().as_secs_f64();
+        RunSummary::from_world(self.name, &self.world, self.steps, wall_secs)
+    }
+}
+
+/// Pending presets, the run in progress and the summaries of finished runs.
+#[derive(Default)]
+pub struct RunQueue {
+    pub pending: VecDeque<QueuedRun>,
+    pub active: Option<ActiveRun>,
+    pub results: Vec<RunSummary>,
+    pub running: bool,
+}
+
+impl RunQueue {
+    pub(super) fn push(&mut self, name: String, scenario: ScenarioPresetV1) {
+        self.pending.push_back(QueuedRun { name, scenario });
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.active.is_none() && self.pending.is_empty()
+    }
+
+    /// Work on the queue until `deadline`: finish the active run, then build the next one with
+    /// `build_world`. Stops running once the queue is empty.
+    pub(super) fn advance(
+        &mut self,
+        deadline: Instant,
+        mut build_world: impl FnMut(&ScenarioPresetV1) -> World,
+    ) {
+        while self.running {
+            let active = match self.active.as_mut() {
+                Some(active) => active,
+                None => {
+                    let Some(next) = self.pending.pop_front() else {
+                        self.running = false;
+                        return;
+                    };
+                    let world = build_world(&next.scenario);
+                    self.active.insert(ActiveRun::new(next.name, world))
+                }
+            };
+            if !active.advance_until(deadline) {
+                return;
+            }
+            if let Some(finished) = self.active.take() {
+                self.results.push(finished.finish());
+            }
+            if Instant::now() >= deadline {
+                return;
+            }
+        }
+    }
+}
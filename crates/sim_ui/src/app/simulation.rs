@@ -1,30 +1,47 @@
-use bevy_ecs::prelude::World;
-use std::path::PathBuf;
-use std::time::Instant;
-
+use bevy_ecs::prelude::{Entity, World};
+use h3o::{CellIndex, LatLng, Resolution};
+use rand::Rng;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::event_trace::EventTrace;
 use sim_core::matching::{MatchingAlgorithmResource, DEFAULT_ETA_WEIGHT};
+use sim_core::pricing::probe::{probe_pricing, PricingProbe};
 use sim_core::pricing::PricingConfig;
+use sim_core::routing::osrm_cache::OsrmCacheMode;
 use sim_core::routing::RouteProviderKind;
 use sim_core::runner::{run_next_event, simulation_schedule};
 use sim_core::scenario::{
     build_scenario, create_cost_based_matching, create_hungarian_matching, create_simple_matching,
     DriverDecisionConfig, RiderQuoteConfig, ScenarioParams,
 };
+use sim_core::seeds::{SeedHierarchy, SeedStream};
 use sim_core::spawner::SpawnWeightingKind;
+use sim_core::telemetry::{SimSnapshotConfig, SimSnapshots, SnapshotRetention};
 use sim_core::traffic::TrafficProfileKind;
 
+use crate::app::alerts::OutcomeAlerts;
+use crate::app::console::ConsoleFilter;
 use crate::app::defaults::AppDefaults;
 use crate::app::map_tiles::MapTileState;
 use crate::app::presets::{
     delete_named_preset, export_library, import_library, list_named_presets, load_active_preset,
-    load_named_preset, presets_file_path, save_autosave_preset, save_named_preset,
-    DeleteNamedPresetOutcome, PresetMetadata, SaveNamedPresetOutcome, ScenarioPresetV1,
+    load_named_preset, presets_location, read_named_preset, save_autosave_preset,
+    save_named_preset, set_library_path, DeleteNamedPresetOutcome, PresetLocation, PresetMetadata,
+    SaveNamedPresetOutcome, ScenarioPresetV1,
 };
+use crate::app::replay::Replay;
+use crate::app::run_queue::RunQueue;
+use crate::app::spawn_paint::{SpawnBrush, SpawnPainting};
 use crate::ui::utils::{
     apply_batch_config, apply_cancel_config, apply_snapshot_interval, bounds_from_km,
     datetime_to_unix_ms, km_to_cells,
 };
 
+/// Trip length the pricing panel prices until the user changes it.
+const DEFAULT_WHAT_IF_DISTANCE_KM: f64 = 5.0;
+
 /// Main application state for the simulation UI.
 pub struct SimUiApp {
     pub world: World,
@@ -65,6 +82,8 @@ pub struct SimUiApp {
     pub start_day: u32,
     pub start_hour: u32,
     pub start_minute: u32,
+    /// City time zone the start time is entered in and sim datetimes are shown in.
+    pub time_zone: CityTimeZone,
     pub base_fare: f64,
     pub per_km_rate: f64,
     pub commission_rate: f64,
@@ -86,6 +105,12 @@ pub struct SimUiApp {
     pub base_speed_enabled: bool,
     pub base_speed_kmh: f64,
     pub spawn_mode: SpawnMode,
+    /// Spawn weights painted on the map, used by [`SpawnMode::Painted`].
+    pub spawn_painting: SpawnPainting,
+    pub spawn_brush: SpawnBrush,
+    /// Hotspot file the painting is exported to or imported from.
+    pub spawn_paint_path_input: String,
+    pub spawn_paint_status_message: Option<String>,
     pub map_tiles: MapTileState,
     pub preset_name_input: String,
     pub selected_preset_name: Option<String>,
@@ -96,7 +121,33 @@ pub struct SimUiApp {
     pub preset_load_error: Option<String>,
     pub preset_save_error: Option<String>,
     pub preset_transfer_path_input: String,
+    /// Custom preset library file; empty uses the platform config directory.
+    pub preset_library_path_input: String,
+    pub preset_library_is_custom: bool,
+    /// Seed of the last "Surprise me" scenario; editable to regenerate one.
+    pub surprise_seed: u64,
     preset_file_path: Option<PathBuf>,
+    preset_config_dir: Option<PathBuf>,
+    /// Entity whose event timeline the inspector shows.
+    pub inspected_entity: Option<Entity>,
+    pub inspector_entity_input: String,
+    pub console_filter: ConsoleFilter,
+    pub alerts: OutcomeAlerts,
+    /// Map cell the pricing panel probes; follows the pointer over the map.
+    pub pricing_probe_cell: Option<CellIndex>,
+    /// Trip length priced by the pricing panel's what-if fare.
+    pub what_if_distance_km: f64,
+    pub run_bundle_path_input: String,
+    pub run_bundle_status_message: Option<String>,
+    pub run_queue: RunQueue,
+    /// Saved preset picked for the run queue.
+    pub run_queue_selection: Option<String>,
+    pub run_queue_status_message: Option<String>,
+    /// Recorded run being played back; `None` while simulating.
+    pub replay: Option<Replay>,
+    /// Last built-in preset loaded: the controls it was mapped onto and its full parameters.
+    /// Runs use the full parameters until a control changes.
+    builtin_scenario: Option<(ScenarioPresetV1, ScenarioParams)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -122,6 +173,7 @@ pub enum TrafficProfileMode {
 pub enum SpawnMode {
     Uniform,
     BerlinHotspots,
+    Painted,
 }
 
 impl MatchingAlgorithmType {
@@ -134,20 +186,41 @@ impl MatchingAlgorithmType {
     }
 }
 
+/// Thinned retention keeps the timeline spanning the whole run with bounded memory.
+fn ui_snapshot_config() -> SimSnapshotConfig {
+    SimSnapshotConfig::default().with_retention(SnapshotRetention::Thinned)
+}
+
 impl SimUiApp {
     pub fn new() -> Self {
         let mut defaults = AppDefaults::new();
         let mut preset_load_error = None;
+        let mut preset_status_message = None;
         let mut active_preset_name = None;
         let mut preset_names = Vec::new();
         let mut selected_preset_name = None;
-        let preset_file_path = match presets_file_path() {
-            Ok(path) => Some(path),
+        let preset_location = match presets_location() {
+            Ok(location) => {
+                if let Some(legacy_path) = location.migrated_from.as_ref() {
+                    preset_status_message = Some(format!(
+                        "Moved preset library from '{}' to '{}'.",
+                        legacy_path.display(),
+                        location.library_path.display()
+                    ));
+                }
+                if let Some(warning) = location.migration_warning.clone() {
+                    preset_status_message = Some(warning);
+                }
+                Some(location)
+            }
             Err(error) => {
                 preset_load_error = Some(format!("Preset storage disabled: {error}"));
                 None
             }
         };
+        let preset_file_path = preset_location
+            .as_ref()
+            .map(|location| location.library_path.clone());
         if let Some(path) = preset_file_path.as_ref() {
             match load_active_preset(path) {
                 Ok(Some(preset)) => preset.apply_to_defaults(&mut defaults),
@@ -173,12 +246,18 @@ impl SimUiApp {
             }
         }
         let start_epoch_ms = datetime_to_unix_ms(
+            defaults.time_zone,
             defaults.start_year,
             defaults.start_month,
             defaults.start_day,
             defaults.start_hour,
             defaults.start_minute,
         );
+        let seeds = SeedHierarchy::new(if defaults.seed_enabled {
+            defaults.seed_value
+        } else {
+            0
+        });
 
         let mut params = ScenarioParams {
             num_riders: defaults.num_riders,
@@ -192,6 +271,8 @@ impl SimUiApp {
             km_to_cells(defaults.max_trip_km),
         )
         .with_epoch_ms(start_epoch_ms)
+        .with_time_zone(defaults.time_zone)
+        .with_snapshot_config(ui_snapshot_config())
         .with_pricing_config(PricingConfig {
             base_fare: defaults.base_fare,
             per_km_rate: defaults.per_km_rate,
@@ -199,27 +280,19 @@ impl SimUiApp {
             surge_enabled: defaults.surge_enabled,
             surge_radius_k: defaults.surge_radius_k,
             surge_max_multiplier: defaults.surge_max_multiplier,
+            ..Default::default()
         })
         .with_rider_quote_config(RiderQuoteConfig {
             max_quote_rejections: defaults.max_quote_rejections,
             re_quote_delay_secs: 10,
             accept_probability: defaults.accept_probability,
-            seed: if defaults.seed_enabled {
-                defaults.seed_value
-            } else {
-                0_u64
-            }
-            .wrapping_add(0x0071_1073_beef_u64),
+            seed: seeds.seed(SeedStream::Quotes),
             max_willingness_to_pay: defaults.max_willingness_to_pay,
             max_acceptable_eta_ms: defaults.max_acceptable_eta_min.saturating_mul(60_000),
+            ..Default::default()
         })
         .with_driver_decision_config(DriverDecisionConfig {
-            seed: if defaults.seed_enabled {
-                defaults.seed_value
-            } else {
-                0_u64
-            }
-            .wrapping_add(0xdead_beef_u64),
+            seed: seeds.seed(SeedStream::DriverDecisions),
             base_acceptance_score: defaults.driver_base_acceptance_score,
             fare_weight: defaults.driver_fare_weight,
             pickup_distance_penalty: defaults.driver_pickup_distance_penalty,
@@ -237,6 +310,7 @@ impl SimUiApp {
             defaults.batch_matching_enabled,
             defaults.batch_interval_secs,
         );
+        world.insert_resource(EventTrace::sampled(1));
         sim_core::runner::initialize_simulation(&mut world);
 
         Self {
@@ -278,6 +352,7 @@ impl SimUiApp {
             start_day: defaults.start_day,
             start_hour: defaults.start_hour,
             start_minute: defaults.start_minute,
+            time_zone: defaults.time_zone,
             base_fare: defaults.base_fare,
             per_km_rate: defaults.per_km_rate,
             commission_rate: defaults.commission_rate,
@@ -299,17 +374,44 @@ impl SimUiApp {
             base_speed_enabled: defaults.base_speed_enabled,
             base_speed_kmh: defaults.base_speed_kmh,
             spawn_mode: defaults.spawn_mode,
+            spawn_painting: defaults.spawn_painting,
+            spawn_brush: SpawnBrush::default(),
+            spawn_paint_path_input: String::new(),
+            spawn_paint_status_message: None,
             map_tiles: defaults.map_tiles,
             preset_name_input: String::new(),
             selected_preset_name,
             pending_overwrite_name: None,
-            preset_status_message: None,
+            preset_status_message,
             active_preset_name,
             preset_names,
             preset_load_error,
             preset_save_error: None,
             preset_transfer_path_input: String::new(),
+            preset_library_path_input: preset_location
+                .as_ref()
+                .filter(|location| location.is_custom)
+                .map(|location| location.library_path.display().to_string())
+                .unwrap_or_default(),
+            surprise_seed: 0,
+            preset_library_is_custom: preset_location
+                .as_ref()
+                .is_some_and(|location| location.is_custom),
             preset_file_path,
+            preset_config_dir: preset_location.map(|location| location.config_dir),
+            inspected_entity: None,
+            inspector_entity_input: String::new(),
+            console_filter: ConsoleFilter::default(),
+            alerts: OutcomeAlerts::default(),
+            pricing_probe_cell: None,
+            what_if_distance_km: DEFAULT_WHAT_IF_DISTANCE_KM,
+            run_bundle_path_input: String::new(),
+            run_bundle_status_message: None,
+            run_queue: RunQueue::default(),
+            run_queue_selection: None,
+            run_queue_status_message: None,
+            replay: None,
+            builtin_scenario: None,
         }
     }
 
@@ -472,6 +574,124 @@ impl SimUiApp {
         }
     }
 
+    /// Load a built-in `ScenarioParams::preset` into the controls (not saved to the library).
+    pub fn load_builtin_preset(&mut self, name: &str) {
+        if !self.can_mutate_presets() {
+            self.preset_status_message =
+                Some("Preset actions are disabled while simulation is running.".to_string());
+            return;
+        }
+
+        let Some(params) = ScenarioParams::preset(name) else {
+            self.preset_status_message = Some(format!("Unknown built-in preset '{name}'."));
+            return;
+        };
+        let preset = ScenarioPresetV1::from_scenario_params(&params, &AppDefaults::new());
+        self.apply_loaded_preset_to_controls(preset.clone());
+        self.builtin_scenario = Some((preset, params));
+        self.pending_overwrite_name = None;
+        self.preset_name_input = name.to_string();
+        self.preset_status_message = Some(format!("Loaded built-in preset '{name}'."));
+    }
+
+    /// Fill the controls with a random valid scenario from a fresh seed.
+    pub fn surprise_me(&mut self) {
+        self.surprise_seed = rand::thread_rng().gen_range(0..1_000_000);
+        self.generate_surprise_scenario();
+    }
+
+    /// Fill the controls with the random scenario of `surprise_seed`.
+    pub fn generate_surprise_scenario(&mut self) {
+        if !self.can_mutate_presets() {
+            self.preset_status_message =
+                Some("Preset actions are disabled while simulation is running.".to_string());
+            return;
+        }
+
+        let seed = self.surprise_seed;
+        let preset = ScenarioPresetV1::random(seed, &AppDefaults::new());
+        self.apply_loaded_preset_to_controls(preset);
+        self.pending_overwrite_name = None;
+        self.preset_name_input = format!("surprise-{seed}");
+        self.preset_status_message = Some(format!("Generated surprise scenario (seed {seed})."));
+    }
+
+    /// Queue the saved preset in `run_queue_selection`.
+    pub fn queue_selected_preset(&mut self) {
+        let Some(name) = self.run_queue_selection.clone() else {
+            self.run_queue_status_message = Some("Select a preset to queue.".to_string());
+            return;
+        };
+        let Some(path) = self.preset_file_path.clone() else {
+            self.run_queue_status_message = Some("Preset storage is unavailable.".to_string());
+            return;
+        };
+        match read_named_preset(&path, &name) {
+            Ok(Some(preset)) => {
+                self.run_queue.push(name.clone(), preset);
+                self.run_queue_status_message = Some(format!("Queued preset '{name}'."));
+            }
+            Ok(None) => {
+                self.run_queue_status_message = Some(format!("Preset '{name}' no longer exists."));
+            }
+            Err(error) => {
+                self.run_queue_status_message = Some(format!("Preset load warning: {error}"));
+            }
+        }
+    }
+
+    /// Queue a copy of the current controls.
+    pub fn queue_current_scenario(&mut self) {
+        let trimmed = self.preset_name_input.trim();
+        let name = if trimmed.is_empty() {
+            format!(
+                "current #{}",
+                self.run_queue.results.len() + self.run_queue.pending.len() + 1
+            )
+        } else {
+            trimmed.to_string()
+        };
+        self.run_queue
+            .push(name.clone(), ScenarioPresetV1::from_app(self));
+        self.run_queue_status_message = Some(format!("Queued current controls as '{name}'."));
+    }
+
+    pub fn start_run_queue(&mut self) {
+        if self.run_queue.is_idle() {
+            self.run_queue_status_message = Some("Run queue is empty.".to_string());
+            return;
+        }
+        self.run_queue.running = true;
+        self.run_queue_status_message = None;
+    }
+
+    /// Pause after the current slice; the active run resumes on the next start.
+    pub fn stop_run_queue(&mut self) {
+        self.run_queue.running = false;
+        self.run_queue_status_message = Some("Run queue paused.".to_string());
+    }
+
+    /// Drop pending runs, the active run and collected results.
+    pub fn clear_run_queue(&mut self) {
+        self.run_queue = RunQueue::default();
+        self.run_queue_status_message = None;
+    }
+
+    /// Give queued runs `budget` of wall time.
+    pub fn advance_run_queue(&mut self, budget: Duration) {
+        let deadline = Instant::now() + budget;
+        let mut queue = std::mem::take(&mut self.run_queue);
+        let was_running = queue.running;
+        queue.advance(deadline, |preset| self.build_world_for_preset(preset));
+        if was_running && !queue.running {
+            self.run_queue_status_message = Some(format!(
+                "Run queue finished ({} runs).",
+                queue.results.len()
+            ));
+        }
+        self.run_queue = queue;
+    }
+
     pub fn delete_selected_preset(&mut self) {
         if !self.can_mutate_presets() {
             self.preset_status_message =
@@ -579,6 +799,60 @@ impl SimUiApp {
         }
     }
 
+    /// Preset library file currently in use.
+    pub fn preset_library_path(&self) -> Option<&Path> {
+        self.preset_file_path.as_deref()
+    }
+
+    /// Switch to the library in `preset_library_path_input` (the config directory default when
+    /// empty) and remember the choice for later launches.
+    pub fn apply_preset_library_path(&mut self) {
+        if !self.can_mutate_presets() {
+            self.preset_status_message =
+                Some("Preset actions are disabled while simulation is running.".to_string());
+            return;
+        }
+
+        let Some(config_dir) = self.preset_config_dir.clone() else {
+            self.preset_status_message = Some("Preset storage is unavailable.".to_string());
+            return;
+        };
+
+        let trimmed = self.preset_library_path_input.trim();
+        let custom_path = (!trimmed.is_empty()).then(|| PathBuf::from(trimmed));
+        match set_library_path(&config_dir, custom_path.as_deref()) {
+            Ok(location) => {
+                self.use_preset_location(location);
+                if let Some(path) = self.preset_file_path.as_ref() {
+                    self.preset_status_message =
+                        Some(format!("Using preset library '{}'.", path.display()));
+                }
+            }
+            Err(error) => {
+                self.preset_status_message = Some(format!("Preset settings warning: {error}"));
+            }
+        }
+    }
+
+    pub fn reset_preset_library_path(&mut self) {
+        self.preset_library_path_input.clear();
+        self.apply_preset_library_path();
+    }
+
+    fn use_preset_location(&mut self, location: PresetLocation) {
+        self.preset_library_is_custom = location.is_custom;
+        self.preset_library_path_input = if location.is_custom {
+            location.library_path.display().to_string()
+        } else {
+            String::new()
+        };
+        self.preset_file_path = Some(location.library_path);
+        self.pending_overwrite_name = None;
+        let previous_selection = self.selected_preset_name.clone();
+        self.refresh_presets_from_store();
+        self.reconcile_selected_preset(previous_selection);
+    }
+
     fn parse_transfer_path_input(&mut self) -> Option<PathBuf> {
         let trimmed = self.preset_transfer_path_input.trim();
         if trimmed.is_empty() {
@@ -653,6 +927,7 @@ impl SimUiApp {
         self.start_day = defaults.start_day;
         self.start_hour = defaults.start_hour;
         self.start_minute = defaults.start_minute;
+        self.time_zone = defaults.time_zone;
         self.base_fare = defaults.base_fare;
         self.per_km_rate = defaults.per_km_rate;
         self.commission_rate = defaults.commission_rate;
@@ -674,14 +949,27 @@ impl SimUiApp {
         self.base_speed_enabled = defaults.base_speed_enabled;
         self.base_speed_kmh = defaults.base_speed_kmh;
         self.spawn_mode = defaults.spawn_mode;
+        self.spawn_painting = defaults.spawn_painting;
     }
 
     pub fn create_matching_algorithm(&self) -> MatchingAlgorithmResource {
         self.matching_algorithm.create_matching_algorithm()
     }
 
+    /// Full parameters of the loaded built-in preset, while the controls still show it.
+    fn builtin_params(&self) -> Option<&ScenarioParams> {
+        self.builtin_scenario
+            .as_ref()
+            .filter(|(controls, _)| *controls == ScenarioPresetV1::from_app(self))
+            .map(|(_, params)| params)
+    }
+
     pub fn current_params(&self) -> ScenarioParams {
+        if let Some(params) = self.builtin_params() {
+            return params.clone().with_snapshot_config(ui_snapshot_config());
+        }
         let start_epoch_ms = datetime_to_unix_ms(
+            self.time_zone,
             self.start_year,
             self.start_month,
             self.start_day,
@@ -700,13 +988,20 @@ impl SimUiApp {
         .with_simulation_end_time_ms(self.simulation_duration_hours * 3600 * 1000)
         .with_match_radius(km_to_cells(self.match_radius_km))
         .with_trip_duration_cells(km_to_cells(self.min_trip_km), km_to_cells(self.max_trip_km))
-        .with_epoch_ms(start_epoch_ms);
+        .with_epoch_ms(start_epoch_ms)
+        .with_time_zone(self.time_zone)
+        .with_snapshot_config(ui_snapshot_config());
         let (lat_min, lat_max, lng_min, lng_max) = bounds_from_km(self.map_size_km);
         params.lat_min = lat_min;
         params.lat_max = lat_max;
         params.lng_min = lng_min;
         params.lng_max = lng_max;
 
+        let seeds = SeedHierarchy::new(if self.seed_enabled {
+            self.seed_value
+        } else {
+            0
+        });
         params = params
             .with_pricing_config(PricingConfig {
                 base_fare: self.base_fare,
@@ -715,27 +1010,19 @@ impl SimUiApp {
                 surge_enabled: self.surge_enabled,
                 surge_radius_k: self.surge_radius_k,
                 surge_max_multiplier: self.surge_max_multiplier,
+                ..Default::default()
             })
             .with_rider_quote_config(RiderQuoteConfig {
                 max_quote_rejections: self.max_quote_rejections,
                 re_quote_delay_secs: 10,
                 accept_probability: self.accept_probability,
-                seed: if self.seed_enabled {
-                    self.seed_value
-                } else {
-                    0_u64
-                }
-                .wrapping_add(0x0071_1073_beef_u64),
+                seed: seeds.seed(SeedStream::Quotes),
                 max_willingness_to_pay: self.max_willingness_to_pay,
                 max_acceptable_eta_ms: self.max_acceptable_eta_min.saturating_mul(60_000),
+                ..Default::default()
             })
             .with_driver_decision_config(DriverDecisionConfig {
-                seed: if self.seed_enabled {
-                    self.seed_value
-                } else {
-                    0_u64
-                }
-                .wrapping_add(0xdead_beef_u64),
+                seed: seeds.seed(SeedStream::DriverDecisions),
                 base_acceptance_score: self.driver_base_acceptance_score,
                 fare_weight: self.driver_fare_weight,
                 pickup_distance_penalty: self.driver_pickup_distance_penalty,
@@ -750,6 +1037,7 @@ impl SimUiApp {
             RoutingMode::H3Grid => RouteProviderKind::H3Grid,
             RoutingMode::Osrm => RouteProviderKind::Osrm {
                 endpoint: self.osrm_endpoint.clone(),
+                cache: OsrmCacheMode::Off,
             },
         };
         params.traffic_profile = match self.traffic_profile_mode {
@@ -762,11 +1050,133 @@ impl SimUiApp {
         params.spawn_weighting = match self.spawn_mode {
             SpawnMode::Uniform => SpawnWeightingKind::Uniform,
             SpawnMode::BerlinHotspots => SpawnWeightingKind::BerlinHotspots,
+            SpawnMode::Painted => SpawnWeightingKind::Hotspots(self.spawn_painting.to_hotspots()),
         };
         params
     }
 
+    /// Apply one brush dab at a map position. Ignored once the simulation has started, since
+    /// spawn weighting is read when the scenario is built.
+    pub fn paint_spawns_at(&mut self, lat: f64, lng: f64) {
+        if self.started {
+            return;
+        }
+        if let Ok(ll) = LatLng::new(lat, lng) {
+            self.spawn_painting
+                .stroke(ll.to_cell(Resolution::Nine), &self.spawn_brush);
+        }
+    }
+
+    /// Point the pricing panel at the map cell containing `lat`/`lng`.
+    pub fn probe_pricing_at(&mut self, lat: f64, lng: f64) {
+        if let Ok(ll) = LatLng::new(lat, lng) {
+            self.pricing_probe_cell = Some(ll.to_cell(Resolution::Nine));
+        }
+    }
+
+    /// Pricing a quote from the probed cell would get in the current world.
+    pub fn pricing_probe(&self) -> Option<PricingProbe> {
+        probe_pricing(&self.world, self.pricing_probe_cell?)
+    }
+
+    /// Write the painted weights to `spawn_paint_path_input` as a hotspot file.
+    pub fn export_spawn_painting(&mut self) {
+        let path = PathBuf::from(self.spawn_paint_path_input.trim());
+        self.spawn_paint_status_message = Some(if path.as_os_str().is_empty() {
+            "Enter a hotspot file path (.csv or .json).".to_string()
+        } else if self.spawn_painting.is_empty() {
+            "Nothing painted yet.".to_string()
+        } else {
+            match self.spawn_painting.export(&path) {
+                Ok(()) => format!(
+                    "Exported {} painted cells to '{}'.",
+                    self.spawn_painting.len(),
+                    path.display()
+                ),
+                Err(error) => format!("Export failed: {error}"),
+            }
+        });
+    }
+
+    /// Replace the painting with the hotspot file at `spawn_paint_path_input` and switch the
+    /// spawn mode to Painted.
+    pub fn import_spawn_painting(&mut self) {
+        if self.started {
+            self.spawn_paint_status_message =
+                Some("Reset the simulation to change spawn weights.".to_string());
+            return;
+        }
+        let path = PathBuf::from(self.spawn_paint_path_input.trim());
+        self.spawn_paint_status_message = Some(match SpawnPainting::load(&path) {
+            Ok(painting) => {
+                self.spawn_painting = painting;
+                self.spawn_mode = SpawnMode::Painted;
+                format!(
+                    "Imported {} cells from '{}'.",
+                    self.spawn_painting.len(),
+                    path.display()
+                )
+            }
+            Err(error) => format!("Import failed: {error}"),
+        });
+    }
+
+    /// Show `entity`'s event timeline in the inspector.
+    pub fn inspect_entity(&mut self, entity: Entity) {
+        self.inspected_entity = Some(entity);
+        self.inspector_entity_input = entity.to_bits().to_string();
+    }
+
+    /// Load the `.simrun` bundle at `run_bundle_path_input` and play it back (paused) in place
+    /// of the current simulation.
+    pub fn load_run_bundle(&mut self) {
+        let trimmed = self.run_bundle_path_input.trim();
+        if trimmed.is_empty() {
+            self.run_bundle_status_message = Some("Run bundle path cannot be empty.".to_string());
+            return;
+        }
+        match Replay::load(Path::new(trimmed)) {
+            Ok((world, replay)) => {
+                self.run_bundle_status_message = Some(format!("Replaying '{}'.", replay.label));
+                self.world = world;
+                self.schedule = simulation_schedule();
+                self.steps_executed = 0;
+                self.started = true;
+                self.auto_run = false;
+                self.sim_budget_ms = 0.0;
+                self.last_frame_instant = None;
+                self.inspected_entity = None;
+                self.alerts.clear_history();
+                self.replay = Some(replay);
+            }
+            Err(error) => {
+                self.run_bundle_status_message = Some(format!("Run bundle load warning: {error}"));
+            }
+        }
+    }
+
+    /// Evaluate the alert rules on snapshots recorded since the last check.
+    pub fn check_alerts(&mut self) {
+        if let Some(snapshots) = self.world.get_resource::<SimSnapshots>() {
+            self.alerts.check(&snapshots.snapshots);
+        }
+    }
+
+    /// Current simulation time: the replay position while replaying, else the clock.
+    pub fn sim_now_ms(&self) -> u64 {
+        if let Some(replay) = &self.replay {
+            return replay.now_ms;
+        }
+        self.world
+            .get_resource::<sim_core::clock::SimulationClock>()
+            .map_or(0, |clock| clock.now())
+    }
+
     pub fn run_steps(&mut self, steps: usize) {
+        if let Some(replay) = self.replay.as_mut() {
+            self.steps_executed += replay.step(&mut self.world, steps);
+            return;
+        }
         for _ in 0..steps {
             if !run_next_event(&mut self.world, &mut self.schedule) {
                 break;
@@ -776,6 +1186,10 @@ impl SimUiApp {
     }
 
     pub fn run_until_done(&mut self) {
+        if let Some(replay) = self.replay.as_mut() {
+            self.steps_executed += replay.step(&mut self.world, usize::MAX);
+            return;
+        }
         loop {
             if !run_next_event(&mut self.world, &mut self.schedule) {
                 break;
@@ -785,6 +1199,11 @@ impl SimUiApp {
     }
 
     pub fn advance_by_budget(&mut self, budget_ms: f64) {
+        if let Some(replay) = self.replay.as_mut() {
+            self.steps_executed += replay.advance(&mut self.world, budget_ms);
+            self.sim_budget_ms = 0.0;
+            return;
+        }
         let mut remaining = budget_ms.max(0.0);
         while let Some((next_ts, sim_now)) = self
             .world
@@ -812,9 +1231,15 @@ impl SimUiApp {
         self.sim_budget_ms = remaining;
     }
 
-    fn rebuild_simulation(&mut self, started: bool, auto_run: bool) {
+    /// World for the current controls, not yet initialized.
+    fn build_world(&self) -> World {
         let mut world = World::new();
         build_scenario(&mut world, self.current_params());
+        if self.builtin_params().is_some() {
+            // The preset sets matching, batching and cancellation itself.
+            apply_snapshot_interval(&mut world, self.snapshot_interval_ms);
+            return world;
+        }
         world.insert_resource(self.create_matching_algorithm());
         apply_batch_config(
             &mut world,
@@ -827,6 +1252,24 @@ impl SimUiApp {
             self.rider_cancel_max_mins,
         );
         apply_snapshot_interval(&mut world, self.snapshot_interval_ms);
+        world
+    }
+
+    /// Initialized world for `preset`; the controls are left as they were.
+    fn build_world_for_preset(&mut self, preset: &ScenarioPresetV1) -> World {
+        let controls = ScenarioPresetV1::from_app(self);
+        let builtin = self.builtin_scenario.take();
+        self.apply_loaded_preset_to_controls(preset.clone());
+        let mut world = self.build_world();
+        self.apply_loaded_preset_to_controls(controls);
+        self.builtin_scenario = builtin;
+        sim_core::runner::initialize_simulation(&mut world);
+        world
+    }
+
+    fn rebuild_simulation(&mut self, started: bool, auto_run: bool) {
+        let mut world = self.build_world();
+        world.insert_resource(EventTrace::sampled(1));
         sim_core::runner::initialize_simulation(&mut world);
 
         self.world = world;
@@ -837,6 +1280,9 @@ impl SimUiApp {
         self.sim_budget_ms = 0.0;
         self.last_frame_instant = None;
         self.matching_algorithm_changed = false;
+        self.inspected_entity = None;
+        self.alerts.clear_history();
+        self.replay = None;
     }
 }
 
@@ -849,6 +1295,9 @@ impl Drop for SimUiApp {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::app::alerts::{AlertComparison, AlertMetric, AlertRule};
+    use crate::app::spawn_paint::PaintTarget;
+    use sim_core::ecs::{Position, Rider};
     use std::fs;
     use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -1014,6 +1463,221 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn builtin_preset_runs_its_full_params_until_a_control_changes() {
+        let mut app = SimUiApp::new();
+        app.load_builtin_preset("high-churn");
+        let quote = app
+            .current_params()
+            .rider_quote_config
+            .expect("preset sets a quote config");
+        assert_eq!(quote.price_sensitivity, -3.0);
+
+        app.load_builtin_preset("suburban-sprawl");
+        let params = app.current_params();
+        assert_eq!((params.lat_min, params.lng_max), (52.20, 14.00));
+        assert_eq!(params.base_speed_kmh, Some(50.0));
+        app.reset();
+        assert_eq!(
+            app.world.resource::<SimSnapshotConfig>().interval_ms,
+            app.snapshot_interval_ms
+        );
+
+        app.num_riders += 1;
+        let params = app.current_params();
+        assert_eq!(params.num_riders, 401);
+        assert_ne!((params.lat_min, params.lng_max), (52.20, 14.00));
+    }
+
+    #[test]
+    fn surprise_scenario_fills_controls_and_runs() {
+        let path = unique_test_path("surprise");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(path.clone());
+
+        app.surprise_seed = 42;
+        app.generate_surprise_scenario();
+        assert_eq!(app.seed_value, 42);
+        assert!(app.seed_enabled);
+        assert_eq!(app.preset_name_input, "surprise-42");
+        let generated = ScenarioPresetV1::from_app(&app);
+        assert_eq!(generated, ScenarioPresetV1::random(42, &AppDefaults::new()));
+
+        app.reset();
+        for _ in 0..100 {
+            if !run_next_event(&mut app.world, &mut app.schedule) {
+                break;
+            }
+        }
+
+        app.started = true;
+        app.surprise_seed = 7;
+        app.generate_surprise_scenario();
+        assert_eq!(ScenarioPresetV1::from_app(&app), generated);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn painted_spawns_drive_spawning_and_survive_export_and_presets() {
+        let path = unique_test_path("painted");
+        let hotspot_path = unique_test_path("painted_hotspots").with_extension("csv");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(path.clone());
+        app.initial_rider_count = 5;
+        app.map_size_km = 4.0;
+
+        let params = app.current_params();
+        let center = (
+            0.5 * (params.lat_min + params.lat_max),
+            0.5 * (params.lng_min + params.lng_max),
+        );
+        app.spawn_brush.target = PaintTarget::Riders;
+        app.spawn_brush.radius_k = 1;
+        app.paint_spawns_at(center.0, center.1);
+        assert_eq!(app.spawn_painting.len(), 7);
+
+        app.spawn_mode = SpawnMode::Painted;
+        let SpawnWeightingKind::Hotspots(hotspots) = app.current_params().spawn_weighting else {
+            panic!("painted mode should use inline hotspots");
+        };
+        assert_eq!(hotspots.len(), 7);
+
+        let painted = app.spawn_painting.clone();
+        app.spawn_paint_path_input = hotspot_path.display().to_string();
+        app.export_spawn_painting();
+        app.spawn_painting.clear();
+        app.spawn_mode = SpawnMode::Uniform;
+        app.import_spawn_painting();
+        assert_eq!(app.spawn_painting, painted);
+        assert_eq!(app.spawn_mode, SpawnMode::Painted);
+
+        let preset = ScenarioPresetV1::from_app(&app);
+        app.spawn_painting.clear();
+        app.apply_loaded_preset_to_controls(preset);
+        assert_eq!(app.spawn_painting, painted);
+
+        app.start_simulation();
+        run_next_event(&mut app.world, &mut app.schedule);
+        let painted_cells: Vec<_> = painted.cells().map(|(cell, _)| cell).collect();
+        let mut riders = app.world.query::<(&Rider, &Position)>();
+        let rider_cells: Vec<_> = riders.iter(&app.world).map(|(_, pos)| pos.0).collect();
+        assert_eq!(rider_cells.len(), 5);
+        assert!(rider_cells.iter().all(|cell| painted_cells.contains(cell)));
+
+        app.paint_spawns_at(center.0, center.1);
+        assert_eq!(
+            app.spawn_painting, painted,
+            "painting is locked while running"
+        );
+
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(hotspot_path);
+    }
+
+    #[test]
+    fn pricing_probe_follows_the_hovered_cell_and_prices_what_if_trips() {
+        let path = unique_test_path("pricing_probe");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(path.clone());
+        app.surge_enabled = true;
+        app.map_size_km = 2.0;
+        let params = app.current_params();
+        let center = (
+            0.5 * (params.lat_min + params.lat_max),
+            0.5 * (params.lng_min + params.lng_max),
+        );
+
+        app.probe_pricing_at(center.0, center.1);
+        assert!(app.pricing_probe_cell.is_some());
+        app.start_simulation();
+        run_next_event(&mut app.world, &mut app.schedule);
+
+        let probe = app.pricing_probe().expect("probe once the world is built");
+        assert!(probe.config.surge_enabled);
+        assert!(probe.surge_multiplier >= 1.0);
+        let short = probe.fare_for_distance(1.0);
+        let long = probe.fare_for_distance(app.what_if_distance_km);
+        assert!(long.fare > short.fare);
+        assert!((long.fare - long.base_fare * probe.surge_multiplier).abs() < 1e-9);
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn alerts_fire_on_snapshots_and_clear_when_the_simulation_is_rebuilt() {
+        let path = unique_test_path("alerts");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(path.clone());
+        app.alerts.rules = vec![AlertRule::new(
+            AlertMetric::IdleDrivers,
+            AlertComparison::Below,
+            1_000.0,
+        )];
+        app.start_simulation();
+        for _ in 0..50 {
+            run_next_event(&mut app.world, &mut app.schedule);
+        }
+        app.check_alerts();
+        assert_eq!(app.alerts.events.len(), 1);
+        app.check_alerts();
+        assert_eq!(app.alerts.events.len(), 1, "snapshots are checked once");
+
+        app.reset();
+        assert!(app.alerts.events.is_empty());
+        assert_eq!(app.alerts.rules.len(), 1, "rules survive a reset");
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn run_queue_runs_each_scenario_to_completion_without_touching_controls() {
+        let path = unique_test_path("run_queue");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(path.clone());
+        app.num_riders = 8;
+        app.num_drivers = 4;
+        app.initial_rider_count = 2;
+        app.initial_driver_count = 4;
+        app.request_window_hours = 1;
+        app.simulation_duration_hours = 2;
+        app.map_size_km = 2.0;
+        app.preset_name_input = "small".to_string();
+        app.queue_current_scenario();
+
+        app.preset_name_input = "saved".to_string();
+        app.save_named_preset();
+        app.run_queue_selection = Some("saved".to_string());
+        app.queue_selected_preset();
+        app.num_riders = 99;
+        let controls = ScenarioPresetV1::from_app(&app);
+
+        app.start_run_queue();
+        while app.run_queue.running {
+            app.advance_run_queue(Duration::from_secs(5));
+        }
+
+        let names: Vec<&str> = app
+            .run_queue
+            .results
+            .iter()
+            .map(|summary| summary.name.as_str())
+            .collect();
+        assert_eq!(names, ["small", "saved"]);
+        let small = &app.run_queue.results[0];
+        assert!(small.end_time_ms <= 2 * 3600 * 1000);
+        assert!(small.steps > 0);
+        assert!(small.riders_completed > 0);
+        assert!(app.run_queue.is_idle());
+        assert_eq!(ScenarioPresetV1::from_app(&app), controls);
+        assert_eq!(
+            app.run_queue_status_message.as_deref(),
+            Some("Run queue finished (2 runs).")
+        );
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn deleting_selected_active_preset_updates_active_indicator() {
         let path = unique_test_path("delete_active");
@@ -1155,6 +1819,64 @@ mod tests {
         let _ = fs::remove_file(transfer_path);
     }
 
+    #[test]
+    fn run_bundle_replay_releases_recorded_snapshots_and_reset_clears_it() {
+        let mut recorded = World::new();
+        build_scenario(
+            &mut recorded,
+            ScenarioParams {
+                num_riders: 10,
+                num_drivers: 5,
+                ..Default::default()
+            }
+            .with_seed(5)
+            .with_request_window_hours(1)
+            .with_simulation_end_time_ms(3_600_000),
+        );
+        sim_core::runner::initialize_simulation(&mut recorded);
+        let mut schedule = simulation_schedule();
+        sim_core::runner::run_until_empty(&mut recorded, &mut schedule, 100_000);
+        let bundle = sim_core::run_bundle::RunBundle::capture(&mut recorded).with_label("recorded");
+        let path = unique_test_path("run_bundle");
+        bundle.write(&path).expect("bundle should be written");
+
+        let store_path = unique_test_path("run_bundle_store");
+        let mut app = SimUiApp::new();
+        app.preset_file_path = Some(store_path.clone());
+        app.run_bundle_path_input = path.display().to_string();
+        app.load_run_bundle();
+
+        assert!(app.replay.is_some());
+        assert_eq!(
+            app.run_bundle_status_message.as_deref(),
+            Some("Replaying 'recorded'.")
+        );
+        let shown = |app: &SimUiApp| app.world.resource::<SimSnapshots>().snapshots.len();
+        assert_eq!(shown(&app), 0);
+
+        app.run_steps(1);
+        assert_eq!(shown(&app), 1);
+        app.advance_by_budget(60_000.0);
+        let first_ms = bundle.snapshots[0].timestamp_ms;
+        let expected = bundle
+            .snapshots
+            .iter()
+            .filter(|snapshot| snapshot.timestamp_ms <= first_ms + 60_000)
+            .count();
+        assert_eq!(shown(&app), expected);
+
+        app.run_until_done();
+        assert_eq!(shown(&app), bundle.snapshots.len());
+        assert_eq!(app.sim_now_ms(), bundle.end_time_ms);
+
+        app.started = false;
+        app.reset();
+        assert!(app.replay.is_none());
+
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(store_path);
+    }
+
     #[test]
     fn import_library_is_blocked_while_simulation_is_running() {
         let store_path = unique_test_path("running_import_store");
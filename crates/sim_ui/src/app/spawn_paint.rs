@@ -0,0 +1,253 @@
+//! Spawn weights painted on the map with a brush. The painting becomes inline hotspots
+//! ([`SpawnWeightingKind::Hotspots`](sim_core::spawner::SpawnWeightingKind::Hotspots)) when the
+//! spawn mode is Painted, and can be exported as (or imported from) a hotspot file.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use h3o::{CellIndex, LatLng, Resolution};
+use sim_core::spawner::{
+    hotspots_to_csv, parse_hotspots_csv, parse_hotspots_json, Hotspot, HotspotRole,
+};
+
+/// Highest weight a painted cell can reach for either role.
+pub const MAX_PAINTED_WEIGHT: f64 = 100.0;
+
+/// Which spawner the brush paints for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaintTarget {
+    Riders,
+    Drivers,
+    Both,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnBrush {
+    /// Dragging on the map paints instead of doing nothing.
+    pub enabled: bool,
+    pub target: PaintTarget,
+    /// Remove weight instead of adding it.
+    pub erase: bool,
+    /// Brush radius in H3 resolution-9 rings around the pointer cell.
+    pub radius_k: u32,
+    /// Weight added at the brush center per painted frame; it falls off linearly to the rim.
+    pub strength: f64,
+}
+
+impl Default for SpawnBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: PaintTarget::Both,
+            erase: false,
+            radius_k: 2,
+            strength: 0.2,
+        }
+    }
+}
+
+/// Painted rider and driver weight of one cell.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PaintedWeights {
+    pub rider: f64,
+    pub driver: f64,
+}
+
+/// Relative spawn weights per H3 resolution-9 cell.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpawnPainting {
+    cells: BTreeMap<u64, PaintedWeights>,
+}
+
+impl SpawnPainting {
+    /// Sum hotspot weights per resolution-9 cell. Hourly profiles are not kept.
+    pub fn from_hotspots(hotspots: &[Hotspot]) -> Self {
+        let mut painting = Self::default();
+        for hotspot in hotspots {
+            let Ok(ll) = LatLng::new(hotspot.lat, hotspot.lng) else {
+                continue;
+            };
+            let weights = painting
+                .cells
+                .entry(u64::from(ll.to_cell(Resolution::Nine)))
+                .or_default();
+            if matches!(hotspot.role, HotspotRole::Rider | HotspotRole::Both) {
+                weights.rider += hotspot.weight;
+            }
+            if matches!(hotspot.role, HotspotRole::Driver | HotspotRole::Both) {
+                weights.driver += hotspot.weight;
+            }
+        }
+        painting
+    }
+
+    /// One hotspot per painted cell center, or one per role when the weights differ.
+    pub fn to_hotspots(&self) -> Vec<Hotspot> {
+        let mut hotspots = Vec::new();
+        for (cell, weights) in self.cells() {
+            let center = LatLng::from(cell);
+            let mut push = |weight: f64, role: HotspotRole| {
+                if weight > 0.0 {
+                    hotspots.push(Hotspot {
+                        lat: center.lat(),
+                        lng: center.lng(),
+                        weight,
+                        role,
+                        hourly_profile: None,
+                    });
+                }
+            };
+            if weights.rider == weights.driver {
+                push(weights.rider, HotspotRole::Both);
+            } else {
+                push(weights.rider, HotspotRole::Rider);
+                push(weights.driver, HotspotRole::Driver);
+            }
+        }
+        hotspots
+    }
+
+    /// Apply one brush dab centered on `center`.
+    pub fn stroke(&mut self, center: CellIndex, brush: &SpawnBrush) {
+        let radius = brush.radius_k;
+        let dab: Vec<(CellIndex, u32)> = center.grid_disk_distances(radius);
+        for (cell, distance) in dab {
+            let falloff = 1.0 - f64::from(distance) / f64::from(radius + 1);
+            let amount = brush.strength * falloff * if brush.erase { -1.0 } else { 1.0 };
+            let key = u64::from(cell);
+            let weights = self.cells.entry(key).or_default();
+            let paint = |weight: &mut f64| {
+                *weight = (*weight + amount).clamp(0.0, MAX_PAINTED_WEIGHT);
+            };
+            match brush.target {
+                PaintTarget::Riders => paint(&mut weights.rider),
+                PaintTarget::Drivers => paint(&mut weights.driver),
+                PaintTarget::Both => {
+                    paint(&mut weights.rider);
+                    paint(&mut weights.driver);
+                }
+            }
+            if weights.rider <= 0.0 && weights.driver <= 0.0 {
+                self.cells.remove(&key);
+            }
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (CellIndex, PaintedWeights)> + '_ {
+        self.cells.iter().filter_map(|(cell, weights)| {
+            CellIndex::try_from(*cell).ok().map(|cell| (cell, *weights))
+        })
+    }
+
+    /// Largest rider or driver weight, for scaling the overlay.
+    pub fn max_weight(&self) -> f64 {
+        self.cells
+            .values()
+            .map(|weights| weights.rider.max(weights.driver))
+            .fold(0.0, f64::max)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Read a hotspot file: CSV for a `.csv` extension, JSON otherwise.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let hotspots = if is_csv(path) {
+            parse_hotspots_csv(&data)
+        } else {
+            parse_hotspots_json(&data)
+        }
+        .map_err(|e| e.to_string())?;
+        Ok(Self::from_hotspots(&hotspots))
+    }
+
+    /// Write the painting as a hotspot file: CSV for a `.csv` extension, JSON otherwise.
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let hotspots = self.to_hotspots();
+        let data = if is_csv(path) {
+            hotspots_to_csv(&hotspots)
+        } else {
+            serde_json::to_string_pretty(&hotspots).map_err(|e| e.to_string())?
+        };
+        fs::write(path, data).map_err(|e| e.to_string())
+    }
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn center() -> CellIndex {
+        LatLng::new(52.52, 13.405)
+            .expect("valid coordinates")
+            .to_cell(Resolution::Nine)
+    }
+
+    #[test]
+    fn brush_falls_off_from_center_and_erases() {
+        let brush = SpawnBrush {
+            radius_k: 1,
+            strength: 1.0,
+            target: PaintTarget::Drivers,
+            ..SpawnBrush::default()
+        };
+        let mut painting = SpawnPainting::default();
+        painting.stroke(center(), &brush);
+
+        assert_eq!(painting.len(), 7);
+        let weights: BTreeMap<_, _> = painting.cells().collect();
+        assert_eq!(weights[&center()].driver, 1.0);
+        assert_eq!(weights[&center()].rider, 0.0);
+        assert!(weights.values().all(|w| w.driver >= 0.5));
+
+        painting.stroke(
+            center(),
+            &SpawnBrush {
+                erase: true,
+                ..brush
+            },
+        );
+        assert!(painting.is_empty());
+    }
+
+    #[test]
+    fn hotspots_split_roles_only_when_weights_differ() {
+        let mut painting = SpawnPainting::default();
+        let both = SpawnBrush {
+            radius_k: 0,
+            strength: 2.0,
+            ..SpawnBrush::default()
+        };
+        painting.stroke(center(), &both);
+        let hotspots = painting.to_hotspots();
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].role, HotspotRole::Both);
+
+        painting.stroke(
+            center(),
+            &SpawnBrush {
+                target: PaintTarget::Riders,
+                ..both
+            },
+        );
+        let hotspots = painting.to_hotspots();
+        assert_eq!(hotspots.len(), 2);
+        assert_eq!(SpawnPainting::from_hotspots(&hotspots), painting);
+    }
+}
@@ -4,7 +4,7 @@ use eframe::egui;
 
 use sim_core::matching::MatchingAlgorithmResource;
 
-use crate::app::SimUiApp;
+use crate::app::{SimUiApp, RUN_QUEUE_FRAME_BUDGET};
 use crate::ui::controls::render_control_panel;
 use crate::ui::dashboard::render_dashboard;
 
@@ -46,6 +46,17 @@ impl eframe::App for SimUiApp {
             ctx.request_repaint_after(Duration::from_millis(16));
         }
 
+        if self.run_queue.running {
+            self.advance_run_queue(RUN_QUEUE_FRAME_BUDGET);
+            ctx.request_repaint();
+        }
+
+        self.check_alerts();
+        if !self.alerts.unacknowledged().is_empty() {
+            // Keep the alert banner flashing while the simulation is paused.
+            ctx.request_repaint_after(Duration::from_millis(500));
+        }
+
         egui::TopBottomPanel::top("controls").show(ctx, |ui| {
             render_control_panel(ui, self);
         });
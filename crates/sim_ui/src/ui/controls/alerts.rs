@@ -0,0 +1,76 @@
+use eframe::egui;
+
+use crate::app::{AlertComparison, AlertMetric, SimUiApp, MIN_RESOLVED_FOR_RATES};
+use crate::ui::utils::{format_sim_datetime_from_ms, SimCalendar};
+
+/// Edit the alert rules and list the alerts fired in this run.
+pub(super) fn render_alerts(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    ui.label("Alerts flash a banner and mark the charts when a metric crosses its threshold.");
+    let mut remove = None;
+    egui::Grid::new("alert_rules").striped(true).show(ui, |ui| {
+        for (index, rule) in app.alerts.rules.iter_mut().enumerate() {
+            ui.checkbox(&mut rule.enabled, "");
+            egui::ComboBox::from_id_salt(("alert_metric", index))
+                .selected_text(rule.metric.label())
+                .show_ui(ui, |ui| {
+                    for metric in AlertMetric::ALL {
+                        ui.selectable_value(&mut rule.metric, metric, metric.label());
+                    }
+                });
+            egui::ComboBox::from_id_salt(("alert_comparison", index))
+                .selected_text(rule.comparison.symbol())
+                .width(40.0)
+                .show_ui(ui, |ui| {
+                    for comparison in [AlertComparison::Above, AlertComparison::Below] {
+                        ui.selectable_value(&mut rule.comparison, comparison, comparison.symbol());
+                    }
+                });
+            let suffix = if rule.metric.is_rate() { " %" } else { "" };
+            ui.add(
+                egui::DragValue::new(&mut rule.threshold)
+                    .range(0.0..=f64::MAX)
+                    .suffix(suffix),
+            );
+            if ui.small_button("✕").on_hover_text("Remove rule").clicked() {
+                remove = Some(index);
+            }
+            ui.end_row();
+        }
+    });
+    if let Some(index) = remove {
+        app.alerts.remove_rule(index);
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Add rule").clicked() {
+            app.alerts.add_rule();
+        }
+        if ui
+            .add_enabled(
+                !app.alerts.events.is_empty(),
+                egui::Button::new("Clear fired"),
+            )
+            .clicked()
+        {
+            app.alerts.clear_events();
+        }
+    });
+    ui.small(format!(
+        "Rates are checked once {} riders are resolved.",
+        MIN_RESOLVED_FOR_RATES
+    ));
+
+    if app.alerts.events.is_empty() {
+        return;
+    }
+    let calendar = SimCalendar::from_world(&app.world);
+    egui::Grid::new("alert_events")
+        .striped(true)
+        .show(ui, |ui| {
+            for event in app.alerts.events.iter().rev() {
+                ui.label(format_sim_datetime_from_ms(calendar, event.timestamp_ms));
+                ui.colored_label(egui::Color32::from_rgb(230, 90, 90), &event.description);
+                ui.label(&event.value);
+                ui.end_row();
+            }
+        });
+}
@@ -1,13 +1,17 @@
 //! Control panel UI for simulation parameters and actions.
 
+mod alerts;
 mod outcomes;
+mod run_queue;
 mod scenario;
 mod topbar;
 
 use eframe::egui;
 
 use crate::app::SimUiApp;
+use crate::ui::controls::alerts::render_alerts;
 use crate::ui::controls::outcomes::{render_fleet, render_run_outcomes};
+use crate::ui::controls::run_queue::render_run_queue;
 use crate::ui::controls::scenario::render_scenario_parameters;
 use crate::ui::controls::topbar::render_top_controls;
 
@@ -31,4 +35,16 @@ pub fn render_control_panel(ui: &mut egui::Ui, app: &mut SimUiApp) {
         .show(ui, |ui| {
             render_fleet(ui, app);
         });
+
+    egui::CollapsingHeader::new("Alerts")
+        .default_open(false)
+        .show(ui, |ui| {
+            render_alerts(ui, app);
+        });
+
+    egui::CollapsingHeader::new("Run queue")
+        .default_open(false)
+        .show(ui, |ui| {
+            render_run_queue(ui, app);
+        });
 }
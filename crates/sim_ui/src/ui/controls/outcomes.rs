@@ -264,14 +264,7 @@ pub(super) fn render_fleet(ui: &mut egui::Ui, app: &SimUiApp) {
             return;
         }
     };
-    let clock = match app.world.get_resource::<sim_core::clock::SimulationClock>() {
-        Some(c) => c,
-        None => {
-            ui.label("—");
-            return;
-        }
-    };
-    let latest = match snapshots.snapshots.back() {
+    let latest = match snapshots.latest() {
         Some(s) => s,
         None => {
             ui.label("—");
@@ -280,7 +273,7 @@ pub(super) fn render_fleet(ui: &mut egui::Ui, app: &SimUiApp) {
     };
 
     let c = &latest.counts;
-    let sim_now_ms = clock.now();
+    let sim_now_ms = app.sim_now_ms();
     let total_drivers = c.drivers_idle
         + c.drivers_evaluating
         + c.drivers_en_route
@@ -580,6 +573,18 @@ mod tests {
             pickup_at: ms,
             fare: 0.0,
             surge_impact: 0.0,
+            commission: 0.0,
+            driver_earnings: 0.0,
+            tolls: 0.0,
+            taxes_and_fees: 0.0,
+            wait_and_save: false,
+            vehicle_class: None,
+            offered_vehicle_class: None,
+            pickup_km: 0.0,
+            deadhead_km: 0.0,
+            trip_km: 0.0,
+            idle_ms: 0,
+            route_polyline: None,
         }
     }
 
@@ -0,0 +1,133 @@
+use eframe::egui;
+
+use crate::app::SimUiApp;
+use crate::ui::utils::format_hms_from_ms;
+
+/// Render the run queue: pick presets, run them one after another, compare their outcomes.
+pub(super) fn render_run_queue(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    ui.horizontal(|ui| {
+        let selected_text = app
+            .run_queue_selection
+            .as_deref()
+            .unwrap_or("Select preset")
+            .to_string();
+        egui::ComboBox::from_id_salt("run_queue_preset_selector")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for preset_name in &app.preset_names {
+                    ui.selectable_value(
+                        &mut app.run_queue_selection,
+                        Some(preset_name.clone()),
+                        preset_name,
+                    );
+                }
+            });
+        if ui
+            .add_enabled(
+                app.run_queue_selection.is_some(),
+                egui::Button::new("Queue preset"),
+            )
+            .clicked()
+        {
+            app.queue_selected_preset();
+        }
+        if ui
+            .button("Queue current")
+            .on_hover_text("Queue the current scenario inputs, named after the preset name field.")
+            .clicked()
+        {
+            app.queue_current_scenario();
+        }
+
+        ui.separator();
+        if app.run_queue.running {
+            if ui.button("Pause queue").clicked() {
+                app.stop_run_queue();
+            }
+        } else if ui
+            .add_enabled(!app.run_queue.is_idle(), egui::Button::new("Run queue"))
+            .on_hover_text("Run each queued scenario to completion without drawing it.")
+            .clicked()
+        {
+            app.start_run_queue();
+        }
+        if ui.button("Clear").clicked() {
+            app.clear_run_queue();
+        }
+    });
+
+    if let Some(active) = app.run_queue.active.as_ref() {
+        ui.label(format!(
+            "Running '{}': sim time {}, {} events",
+            active.name,
+            format_hms_from_ms(active.now_ms()),
+            active.steps
+        ));
+    }
+    if !app.run_queue.pending.is_empty() {
+        let mut remove = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Pending:");
+            for (index, queued) in app.run_queue.pending.iter().enumerate() {
+                if ui
+                    .small_button(format!("{} ✕", queued.name))
+                    .on_hover_text("Remove from the queue")
+                    .clicked()
+                {
+                    remove = Some(index);
+                }
+            }
+        });
+        if let Some(index) = remove {
+            app.run_queue.pending.remove(index);
+        }
+    }
+    if let Some(message) = app.run_queue_status_message.as_ref() {
+        ui.colored_label(egui::Color32::from_rgb(220, 180, 80), message);
+    }
+
+    if app.run_queue.results.is_empty() {
+        return;
+    }
+    ui.add_space(4.0);
+    egui::Grid::new("run_queue_results")
+        .striped(true)
+        .show(ui, |ui| {
+            for header in [
+                "Run",
+                "Sim time",
+                "Completed",
+                "Cancelled",
+                "Abandoned (quote)",
+                "Conversion %",
+                "Avg time to match",
+                "Avg time to pickup",
+                "Platform revenue",
+                "Total rider pay",
+                "Events",
+                "Wall s",
+            ] {
+                ui.label(header);
+            }
+            ui.end_row();
+
+            for summary in &app.run_queue.results {
+                let format_avg = |avg: Option<f64>| {
+                    avg.map_or_else(|| "—".to_string(), |ms| format_hms_from_ms(ms as u64))
+                };
+                ui.label(&summary.name);
+                ui.label(format_hms_from_ms(summary.end_time_ms));
+                ui.label(summary.riders_completed.to_string());
+                ui.label(summary.riders_cancelled.to_string());
+                ui.label(summary.riders_abandoned_quote.to_string());
+                ui.label(format!("{:.1}", summary.conversion * 100.0));
+                ui.label(format_avg(summary.avg_time_to_match_ms));
+                ui.label(format_avg(summary.avg_time_to_pickup_ms));
+                ui.label(format!("{:.2}", summary.platform_revenue));
+                ui.label(format!("{:.2}", summary.total_fares));
+                ui.label(summary.steps.to_string());
+                ui.label(format!("{:.1}", summary.wall_secs));
+                ui.end_row();
+            }
+        });
+}
@@ -1,4 +1,6 @@
 use eframe::egui;
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::scenario::ScenarioParams;
 
 use crate::app::{MatchingAlgorithmType, RoutingMode, SimUiApp, SpawnMode, TrafficProfileMode};
 use crate::ui::utils::{datetime_from_unix_ms, now_unix_ms};
@@ -67,6 +69,47 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
             }
         });
 
+        ui.horizontal(|ui| {
+            ui.label("Built-in")
+                .on_hover_text("Curated scenarios shared with experiments and sweeps.");
+            let mut chosen_builtin = None;
+            ui.add_enabled_ui(can_edit, |ui| {
+                egui::ComboBox::from_id_salt("builtin_preset_selector")
+                    .selected_text("Load built-in preset")
+                    .show_ui(ui, |ui| {
+                        for name in ScenarioParams::preset_names() {
+                            if ui.selectable_label(false, *name).clicked() {
+                                chosen_builtin = Some(*name);
+                            }
+                        }
+                    });
+            });
+            if let Some(name) = chosen_builtin {
+                app.load_builtin_preset(name);
+            }
+
+            if ui
+                .add_enabled(can_edit, egui::Button::new("Surprise me"))
+                .on_hover_text("Fill the inputs with a random but valid scenario from a new seed.")
+                .clicked()
+            {
+                app.surprise_me();
+            }
+            ui.label("Seed").on_hover_text(
+                "Seed of the random scenario; the same seed always generates the same scenario and is also used as the simulation seed.",
+            );
+            ui.add_enabled(
+                can_edit,
+                egui::DragValue::new(&mut app.surprise_seed).range(0..=u64::MAX),
+            );
+            if ui
+                .add_enabled(can_edit, egui::Button::new("Generate"))
+                .clicked()
+            {
+                app.generate_surprise_scenario();
+            }
+        });
+
         ui.horizontal(|ui| {
             ui.label("Transfer path").on_hover_text(
                 "Path to a full-library preset transfer JSON file for export/import.",
@@ -92,6 +135,36 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
         });
         ui.small("Export/import applies to the full preset library. Import replaces current library after validation succeeds.");
 
+        ui.horizontal(|ui| {
+            ui.label("Library path").on_hover_text(
+                "Preset library file (or a folder to keep sim_ui_presets.json in). Leave empty to use the platform config directory. The choice is remembered across launches.",
+            );
+            ui.add_enabled(
+                can_edit,
+                egui::TextEdit::singleline(&mut app.preset_library_path_input)
+                    .desired_width(260.0)
+                    .hint_text("(config directory)"),
+            );
+            if ui
+                .add_enabled(can_edit, egui::Button::new("Use library"))
+                .clicked()
+            {
+                app.apply_preset_library_path();
+            }
+            if ui
+                .add_enabled(
+                    can_edit && app.preset_library_is_custom,
+                    egui::Button::new("Use default"),
+                )
+                .clicked()
+            {
+                app.reset_preset_library_path();
+            }
+        });
+        if let Some(path) = app.preset_library_path() {
+            ui.small(format!("Presets are stored in '{}'.", path.display()));
+        }
+
         if let Some(message) = app.preset_status_message.as_ref() {
             ui.colored_label(egui::Color32::from_rgb(220, 180, 80), message);
         }
@@ -497,12 +570,14 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
                 ui.label("Spawns").on_hover_text(
                     "Spawn location weighting for riders and drivers.\n\
                      Uniform: random positions within map bounds.\n\
-                     Berlin Hotspots: weighted toward realistic city hotspots.",
+                     Berlin Hotspots: weighted toward realistic city hotspots.\n\
+                     Painted: weights painted on the map with the spawn brush.",
                 );
                 egui::ComboBox::from_id_salt("spawn_mode")
                     .selected_text(match app.spawn_mode {
                         SpawnMode::Uniform => "Uniform",
                         SpawnMode::BerlinHotspots => "Berlin",
+                        SpawnMode::Painted => "Painted",
                     })
                     .show_ui(ui, |ui| {
                         ui.selectable_value(
@@ -515,6 +590,11 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
                             SpawnMode::BerlinHotspots,
                             "Berlin Hotspots",
                         );
+                        ui.selectable_value(
+                            &mut app.spawn_mode,
+                            SpawnMode::Painted,
+                            "Painted",
+                        );
                     });
             });
             ui.add_space(4.0);
@@ -543,7 +623,20 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
                 ui.label("Timing");
             });
             ui.horizontal(|ui| {
-                ui.label("Start (UTC)").on_hover_text("Real-world datetime (UTC) corresponding to simulation time 0. Affects time-of-day patterns (rush hours, day/night variations)");
+                ui.label("Start").on_hover_text("Real-world datetime (in the time zone below) corresponding to simulation time 0. Affects time-of-day patterns (rush hours, day/night variations)");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Time zone").on_hover_text("IANA time zone of the city. Spawn patterns, traffic and scheduled surge follow its wall clock, including DST, and sim datetimes are shown in it");
+                ui.add_enabled_ui(can_edit, |ui| {
+                    egui::ComboBox::from_id_salt("time_zone")
+                        .selected_text(app.time_zone.name())
+                        .height(320.0)
+                        .show_ui(ui, |ui| {
+                            for zone in CityTimeZone::all() {
+                                ui.selectable_value(&mut app.time_zone, zone, zone.name());
+                            }
+                        });
+                });
             });
             ui.horizontal(|ui| {
                 ui.add_enabled(
@@ -569,7 +662,7 @@ pub(super) fn render_scenario_parameters(ui: &mut egui::Ui, app: &mut SimUiApp)
                     egui::DragValue::new(&mut app.start_minute).range(0..=59).suffix(" m"),
                 );
                 if ui.add_enabled(can_edit, egui::Button::new("Now")).clicked() {
-                    let (year, month, day, hour, minute) = datetime_from_unix_ms(now_unix_ms());
+                    let (year, month, day, hour, minute) = datetime_from_unix_ms(app.time_zone, now_unix_ms());
                     app.start_year = year;
                     app.start_month = month;
                     app.start_day = day;
@@ -1,7 +1,10 @@
 use eframe::egui;
+use sim_core::clock::civil::CityTimeZone;
 
 use crate::app::SimUiApp;
-use crate::ui::utils::{format_datetime_from_unix_ms, format_hms_from_ms, now_unix_ms};
+use crate::ui::utils::{
+    format_datetime_from_unix_ms, format_hms_from_ms, now_unix_ms, SimCalendar,
+};
 
 pub(super) fn render_top_controls(ui: &mut egui::Ui, app: &mut SimUiApp) {
     ui.horizontal(|ui| {
@@ -44,6 +47,16 @@ pub(super) fn render_top_controls(ui: &mut egui::Ui, app: &mut SimUiApp) {
         if ui.button("Reset").clicked() {
             app.reset();
         }
+        ui.separator();
+        ui.label("Run bundle");
+        ui.add(
+            egui::TextEdit::singleline(&mut app.run_bundle_path_input)
+                .hint_text("run.simrun")
+                .desired_width(160.0),
+        );
+        if ui.button("Replay").clicked() {
+            app.load_run_bundle();
+        }
     });
 
     ui.horizontal(|ui| {
@@ -70,29 +83,34 @@ pub(super) fn render_top_controls(ui: &mut egui::Ui, app: &mut SimUiApp) {
         );
         ui.checkbox(&mut app.hide_off_duty_drivers, "Hide off-duty drivers");
         ui.checkbox(&mut app.grid_enabled, "Grid");
-        ui.label(format!("Steps executed: {}", app.steps_executed));
+        if app.replay.is_some() {
+            ui.label(format!("Snapshots replayed: {}", app.steps_executed));
+        } else {
+            ui.label(format!("Steps executed: {}", app.steps_executed));
+        }
         let active = app.active_preset_name.as_deref().unwrap_or("(none)");
         ui.label(format!("Preset: {active}"));
     });
 
-    let (sim_now_ms, sim_epoch_ms) = app
-        .world
-        .get_resource::<sim_core::clock::SimulationClock>()
-        .map(|clock| (clock.now(), clock.epoch_ms()))
-        .unwrap_or((0, 0));
-    let sim_real_ms = sim_epoch_ms.saturating_add(sim_now_ms as i64).max(0) as u64;
+    let sim_now_ms = app.sim_now_ms();
+    let calendar = SimCalendar::from_world(&app.world);
+    let sim_real_ms = calendar.unix_ms(sim_now_ms).max(0) as u64;
     ui.horizontal(|ui| {
         ui.label(format!("Sim time: {}", format_hms_from_ms(sim_now_ms)));
         ui.label(format!(
-            "Sim datetime (UTC): {}",
-            format_datetime_from_unix_ms(sim_real_ms)
+            "Sim datetime ({}): {}",
+            calendar.zone.name(),
+            format_datetime_from_unix_ms(calendar.zone, sim_real_ms)
         ));
         ui.label(format!(
             "Wall clock (UTC): {}",
-            format_datetime_from_unix_ms(now_unix_ms())
+            format_datetime_from_unix_ms(CityTimeZone::UTC, now_unix_ms())
         ));
     });
 
+    if let Some(message) = app.run_bundle_status_message.as_ref() {
+        ui.colored_label(egui::Color32::from_rgb(170, 170, 170), message);
+    }
     if let Some(message) = app.preset_load_error.as_ref() {
         ui.colored_label(egui::Color32::from_rgb(220, 180, 80), message);
     }
@@ -1,17 +1,24 @@
+use bevy_ecs::prelude::Entity;
 use eframe::egui;
-use egui_plot::{Line, Plot};
+use egui_plot::{Line, Plot, VLine};
 
-use sim_core::telemetry::SimSnapshots;
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::event_trace::EventTrace;
+use sim_core::sim_log::{LogCategory, LogSeverity, SimLog};
+use sim_core::telemetry::{SimSnapshots, SimTelemetry};
 
-use crate::app::{MapSignature, RoutingMode, SimUiApp};
+use crate::app::{MapSignature, PaintTarget, RoutingMode, SimUiApp, SpawnMode};
+use crate::ui::constants::H3_RES9_CELL_WIDTH_KM;
 use crate::ui::rendering::{
-    choose_tile_zoom, draw_agent, draw_grid, project_lat_lng_unclamped, project_position,
-    render_map_legend, render_metrics_legend, render_trip_table_all, tiles_for_bounds, MapBounds,
+    choose_tile_zoom, draw_agent, draw_cell_outline, draw_grid, draw_spawn_painting,
+    project_lat_lng_unclamped, project_position, render_event_timeline, render_map_legend,
+    render_metrics_legend, render_trip_table_all, tiles_for_bounds, unproject_pos, MapBounds,
 };
 use crate::ui::utils::{
-    chart_color_abandoned_quote, chart_color_active_trips, chart_color_cancelled_riders,
-    chart_color_cancelled_trips, chart_color_completed_trips, chart_color_idle_drivers,
-    chart_color_waiting_riders, driver_color, format_datetime_from_unix_ms, rider_color,
+    chart_color_abandoned_quote, chart_color_active_trips, chart_color_alert,
+    chart_color_cancelled_riders, chart_color_cancelled_trips, chart_color_completed_trips,
+    chart_color_demand_event, chart_color_idle_drivers, chart_color_waiting_riders, driver_color,
+    format_datetime_from_unix_ms, format_sim_datetime_from_ms, rider_color, SimCalendar,
 };
 
 struct MetricSeries {
@@ -23,29 +30,75 @@ struct MetricSeries {
     abandoned_quote: Vec<[f64; 2]>,
     completed_trips: Vec<[f64; 2]>,
     cancelled_trips: Vec<[f64; 2]>,
+    /// Time zone the plot's time axis is labelled in.
+    time_zone: CityTimeZone,
+    /// Special-event demand spikes as (label, start, end) in plot time (unix seconds).
+    demand_events: Vec<(String, f64, f64)>,
+    /// Alert firings as (label, time) in plot time.
+    alerts: Vec<(String, f64)>,
 }
 
 pub fn render_dashboard(ui: &mut egui::Ui, app: &mut SimUiApp) {
     app.map_tiles.drain_results();
     app.map_tiles.evict_stale_projections();
 
+    render_alert_banner(ui, app);
+
     let Some(series) = collect_metric_series(app) else {
         return;
     };
 
     render_map_panel(ui, app, series.latest_snapshot.as_ref());
+    render_pricing_panel(ui, app);
     render_metrics_panel(ui, &series);
     render_trips_panel(ui, app, series.latest_snapshot.as_ref());
+    render_inspector_panel(ui, app, series.latest_snapshot.as_ref());
+    render_console_panel(ui, app);
+}
+
+/// Flashing banner listing alert firings until dismissed.
+fn render_alert_banner(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    let pending = app.alerts.unacknowledged();
+    let Some(latest) = pending.last() else {
+        return;
+    };
+    let calendar = SimCalendar::from_world(&app.world);
+    let flash_on = ((ui.input(|i| i.time) * 2.0) as u64).is_multiple_of(2);
+    let fill = if flash_on {
+        egui::Color32::from_rgb(120, 30, 30)
+    } else {
+        egui::Color32::from_rgb(70, 25, 25)
+    };
+    let text = format!(
+        "Alert: {} ({}) at {}",
+        latest.description,
+        latest.value,
+        format_sim_datetime_from_ms(calendar, latest.timestamp_ms)
+    );
+    let more = pending.len() - 1;
+    let mut dismiss = false;
+    egui::Frame::new()
+        .fill(fill)
+        .inner_margin(6.0)
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::from_rgb(255, 220, 220), text);
+                if more > 0 {
+                    ui.label(format!("(+{more} more, see Alerts)"));
+                }
+                dismiss = ui.button("Dismiss").clicked();
+            });
+        });
+    if dismiss {
+        app.alerts.acknowledge();
+    }
 }
 
 fn collect_metric_series(app: &SimUiApp) -> Option<MetricSeries> {
     let snapshots = app.world.get_resource::<SimSnapshots>()?;
-    let latest_snapshot = snapshots.snapshots.back().cloned();
-    let sim_epoch_ms = app
-        .world
-        .get_resource::<sim_core::clock::SimulationClock>()
-        .map(|clock| clock.epoch_ms())
-        .unwrap_or(0);
+    let latest_snapshot = snapshots.latest().map(|snapshot| snapshot.into_owned());
+    let calendar = SimCalendar::from_world(&app.world);
 
     let mut active_trips = Vec::new();
     let mut waiting_riders = Vec::new();
@@ -56,7 +109,7 @@ fn collect_metric_series(app: &SimUiApp) -> Option<MetricSeries> {
     let mut cancelled_trips = Vec::new();
 
     for snapshot in snapshots.snapshots.iter() {
-        let real_ms = sim_epoch_ms.saturating_add(snapshot.timestamp_ms as i64);
+        let real_ms = calendar.unix_ms(snapshot.timestamp_ms);
         let t = real_ms as f64 / 1000.0;
         active_trips.push([
             t,
@@ -70,6 +123,39 @@ fn collect_metric_series(app: &SimUiApp) -> Option<MetricSeries> {
         cancelled_trips.push([t, snapshot.counts.trips_cancelled as f64]);
     }
 
+    let to_plot_time = |sim_ms: u64| calendar.unix_ms(sim_ms) as f64 / 1000.0;
+    let demand_events = app
+        .world
+        .get_resource::<SimTelemetry>()
+        .map(|telemetry| {
+            telemetry
+                .demand_events
+                .iter()
+                .map(|event| {
+                    (
+                        format!(
+                            "{} ({}/{} requests)",
+                            event.name, event.requests_spawned, event.requests_planned
+                        ),
+                        to_plot_time(event.start_ms),
+                        to_plot_time(event.end_ms),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let alerts = app
+        .alerts
+        .events
+        .iter()
+        .map(|event| {
+            (
+                format!("{} ({})", event.description, event.value),
+                to_plot_time(event.timestamp_ms),
+            )
+        })
+        .collect();
+
     Some(MetricSeries {
         latest_snapshot,
         active_trips,
@@ -79,6 +165,9 @@ fn collect_metric_series(app: &SimUiApp) -> Option<MetricSeries> {
         abandoned_quote,
         completed_trips,
         cancelled_trips,
+        time_zone: calendar.zone,
+        demand_events,
+        alerts,
     })
 }
 
@@ -93,10 +182,18 @@ fn render_map_panel(
             ui.group(|ui| {
                 ui.heading("Map Legend");
                 render_map_legend(ui);
+                render_tile_settings(ui, app);
+                render_spawn_brush_settings(ui, app);
 
                 let map_height = 680.0;
                 let map_size = egui::Vec2::new(ui.available_width(), map_height);
-                let (map_rect, _) = ui.allocate_exact_size(map_size, egui::Sense::hover());
+                let painting = app.spawn_brush.enabled && !app.started;
+                let sense = if painting {
+                    egui::Sense::click_and_drag()
+                } else {
+                    egui::Sense::hover()
+                };
+                let (map_rect, map_response) = ui.allocate_exact_size(map_size, sense);
                 let painter = ui.painter_at(map_rect);
 
                 painter.rect_filled(map_rect, 0.0, egui::Color32::from_gray(20));
@@ -107,15 +204,61 @@ fn render_map_panel(
                     egui::StrokeKind::Middle,
                 );
 
-                if let Some(snapshot) = latest_snapshot {
-                    let params = app.current_params();
-                    let bounds = MapBounds::new(
-                        params.lat_min,
-                        params.lat_max,
-                        params.lng_min,
-                        params.lng_max,
+                let params = app.current_params();
+                let bounds = MapBounds::new(
+                    params.lat_min,
+                    params.lat_max,
+                    params.lng_min,
+                    params.lng_max,
+                );
+                if app.spawn_mode == SpawnMode::Painted || app.spawn_brush.enabled {
+                    draw_spawn_painting(&painter, &app.spawn_painting, &bounds, map_rect);
+                }
+                if painting {
+                    if let Some(pointer) = map_response.hover_pos() {
+                        let cell_px = map_rect.width() as f64 / app.map_size_km.max(1.0)
+                            * H3_RES9_CELL_WIDTH_KM;
+                        let radius = (app.spawn_brush.radius_k as f64 + 0.5) * cell_px;
+                        painter.circle_stroke(
+                            pointer,
+                            radius as f32,
+                            egui::Stroke::new(1.0, egui::Color32::from_gray(200)),
+                        );
+                    }
+                    if map_response.is_pointer_button_down_on() {
+                        if let Some((lat, lng)) = map_response
+                            .interact_pointer_pos()
+                            .and_then(|pos| unproject_pos(pos, &bounds, map_rect))
+                        {
+                            app.paint_spawns_at(lat, lng);
+                        }
+                    }
+                }
+
+                if !painting {
+                    if let Some((lat, lng)) = map_response
+                        .hover_pos()
+                        .and_then(|pos| unproject_pos(pos, &bounds, map_rect))
+                    {
+                        app.probe_pricing_at(lat, lng);
+                    }
+                }
+                if let Some(cell) = app.pricing_probe_cell {
+                    let cluster_stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(120));
+                    for neighbor in cell.grid_disk::<Vec<_>>(app.surge_radius_k) {
+                        draw_cell_outline(&painter, neighbor, &bounds, map_rect, cluster_stroke);
+                    }
+                    draw_cell_outline(
+                        &painter,
+                        cell,
+                        &bounds,
+                        map_rect,
+                        egui::Stroke::new(2.0, egui::Color32::from_rgb(220, 180, 80)),
                     );
-                    if app.routing_mode == RoutingMode::Osrm {
+                }
+
+                if let Some(snapshot) = latest_snapshot {
+                    if app.routing_mode == RoutingMode::Osrm || app.map_tiles.has_custom_source() {
                         let zoom = choose_tile_zoom(&bounds);
                         let signature = MapSignature {
                             z: zoom,
@@ -126,10 +269,8 @@ fn render_map_panel(
                         };
                         app.map_tiles.update_signature(signature);
                         let tiles = tiles_for_bounds(&bounds, zoom);
-                        if !app.osrm_endpoint.trim().is_empty() {
-                            app.map_tiles
-                                .request_missing_tiles(&app.osrm_endpoint, tiles.iter().copied());
-                        }
+                        app.map_tiles
+                            .request_missing_tiles(&app.osrm_endpoint, tiles.iter().copied());
                         let road_stroke = egui::Stroke::new(1.0, egui::Color32::from_gray(80));
                         for tile in &tiles {
                             if let Some(lines) = app.map_tiles.cached_projection_lines(tile) {
@@ -230,6 +371,203 @@ fn render_map_panel(
         });
 }
 
+/// Surge state around the probed map cell and a what-if fare for a trip starting there.
+fn render_pricing_panel(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    egui::CollapsingHeader::new("Pricing what-if")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.group(|ui| {
+                ui.set_min_width(ui.available_width());
+                ui.label("Hover a map cell to see the quote a pickup there would get right now.");
+                ui.horizontal(|ui| {
+                    ui.label("Trip distance (km)");
+                    ui.add(
+                        egui::DragValue::new(&mut app.what_if_distance_km)
+                            .range(0.0..=100.0)
+                            .speed(0.1),
+                    );
+                });
+
+                let Some(cell) = app.pricing_probe_cell else {
+                    ui.label("No cell selected.");
+                    return;
+                };
+                let Some(probe) = app.pricing_probe() else {
+                    ui.label("Start the simulation to probe pricing.");
+                    return;
+                };
+                let fare = probe.fare_for_distance(app.what_if_distance_km);
+                let ratio = probe.demand_supply_ratio();
+
+                egui::Grid::new("pricing_probe")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Cell");
+                        ui.label(cell.to_string());
+                        ui.end_row();
+                        ui.label("Demand")
+                            .on_hover_text("Browsing and waiting riders within the surge radius");
+                        ui.label(probe.demand.to_string());
+                        ui.end_row();
+                        ui.label("Supply")
+                            .on_hover_text("Idle drivers within the surge radius");
+                        ui.label(probe.supply.to_string());
+                        ui.end_row();
+                        ui.label("Demand / supply");
+                        ui.label(if ratio.is_finite() {
+                            format!("{ratio:.2}")
+                        } else {
+                            "no supply".to_string()
+                        });
+                        ui.end_row();
+                        ui.label("Pickup ETA");
+                        ui.label(format!("{:.1} min", probe.eta_ms as f64 / 60_000.0));
+                        ui.end_row();
+                        ui.label("Surge multiplier");
+                        if !probe.config.surge_enabled {
+                            ui.label("1.00x (surge disabled)");
+                        } else if probe.surge_multiplier != probe.raw_multiplier {
+                            ui.label(format!(
+                                "{:.2}x (rate-limited from {:.2}x)",
+                                probe.surge_multiplier, probe.raw_multiplier
+                            ));
+                        } else {
+                            ui.label(format!("{:.2}x", probe.surge_multiplier));
+                        }
+                        ui.end_row();
+                        if let Some(last) = probe.last_quoted_multiplier {
+                            ui.label("Last quoted here");
+                            ui.label(format!("{last:.2}x"));
+                            ui.end_row();
+                        }
+                        ui.label("Base + distance");
+                        ui.label(format!("{:.2}", fare.base_fare));
+                        ui.end_row();
+                        ui.label("Fare");
+                        ui.label(format!("{:.2}", fare.fare));
+                        ui.end_row();
+                        ui.label("Taxes and fees");
+                        ui.label(format!("{:.2}", fare.taxes));
+                        ui.end_row();
+                        ui.label("Commission");
+                        ui.label(format!("{:.2}", fare.commission));
+                        ui.end_row();
+                        ui.label("Driver earnings");
+                        ui.label(format!("{:.2}", fare.driver_earnings));
+                        ui.end_row();
+                    });
+                ui.colored_label(
+                    egui::Color32::from_rgb(170, 170, 170),
+                    "Excludes tolls and vehicle class multipliers.",
+                );
+            });
+        });
+}
+
+/// Tile server URL, API key and offline mode for the road layer.
+fn render_tile_settings(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    let tiles = &mut app.map_tiles;
+    ui.horizontal(|ui| {
+        ui.label("Tile URL").on_hover_text(
+            "Vector tile URL with {z}, {x}, {y} and optional {api_key} placeholders. Empty uses the OSRM endpoint's tile service (OSRM routing only).",
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut tiles.url_template)
+                .desired_width(320.0)
+                .hint_text("(OSRM endpoint)"),
+        );
+        ui.label("API key");
+        ui.add(
+            egui::TextEdit::singleline(&mut tiles.api_key)
+                .password(true)
+                .desired_width(140.0),
+        );
+        ui.checkbox(&mut tiles.offline, "Offline").on_hover_text(
+            "Draw only tiles already in the disk cache and never contact the tile server.",
+        );
+    });
+    let cache = tiles.cache_root().map_or_else(
+        || "Tile cache disabled.".to_string(),
+        |root| format!("Tile cache: '{}'.", root.display()),
+    );
+    let failed = tiles.failed_tile_count();
+    if failed > 0 {
+        ui.small(format!("{cache} {failed} tiles unavailable."));
+    } else {
+        ui.small(cache);
+    }
+}
+
+/// Spawn brush: paint relative rider/driver spawn weights on the map, import or export them
+/// as a hotspot file.
+fn render_spawn_brush_settings(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    let can_paint = !app.started;
+    ui.horizontal(|ui| {
+        ui.add_enabled(
+            can_paint,
+            egui::Checkbox::new(&mut app.spawn_brush.enabled, "Paint spawns"),
+        )
+        .on_hover_text(
+            "Drag on the map to paint relative spawn weights. They are used when Spawns is set \
+             to Painted. Painting is disabled while the simulation runs.",
+        );
+        let brush = &mut app.spawn_brush;
+        egui::ComboBox::from_id_salt("spawn_brush_target")
+            .selected_text(match brush.target {
+                PaintTarget::Riders => "Riders",
+                PaintTarget::Drivers => "Drivers",
+                PaintTarget::Both => "Riders + drivers",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut brush.target, PaintTarget::Riders, "Riders");
+                ui.selectable_value(&mut brush.target, PaintTarget::Drivers, "Drivers");
+                ui.selectable_value(&mut brush.target, PaintTarget::Both, "Riders + drivers");
+            });
+        ui.checkbox(&mut brush.erase, "Erase");
+        ui.label("Radius");
+        ui.add(egui::DragValue::new(&mut brush.radius_k).range(0..=10))
+            .on_hover_text("Brush radius in H3 cells around the pointer.");
+        ui.label("Strength");
+        ui.add(
+            egui::DragValue::new(&mut brush.strength)
+                .range(0.01..=10.0)
+                .speed(0.01),
+        )
+        .on_hover_text("Weight added at the brush center per frame while dragging.");
+        if ui
+            .add_enabled(
+                can_paint && !app.spawn_painting.is_empty(),
+                egui::Button::new("Clear"),
+            )
+            .clicked()
+        {
+            app.spawn_painting.clear();
+        }
+        ui.label(format!("{} cells", app.spawn_painting.len()));
+    });
+    ui.horizontal(|ui| {
+        ui.label("Hotspot file");
+        ui.add(
+            egui::TextEdit::singleline(&mut app.spawn_paint_path_input)
+                .desired_width(320.0)
+                .hint_text("hotspots.csv or hotspots.json"),
+        );
+        if ui
+            .add_enabled(can_paint, egui::Button::new("Import"))
+            .clicked()
+        {
+            app.import_spawn_painting();
+        }
+        if ui.button("Export").clicked() {
+            app.export_spawn_painting();
+        }
+    });
+    if let Some(message) = app.spawn_paint_status_message.as_ref() {
+        ui.colored_label(egui::Color32::from_rgb(220, 180, 80), message);
+    }
+}
+
 fn render_metrics_panel(ui: &mut egui::Ui, series: &MetricSeries) {
     egui::CollapsingHeader::new("Metrics")
         .default_open(false)
@@ -240,7 +578,7 @@ fn render_metrics_panel(ui: &mut egui::Ui, series: &MetricSeries) {
                 Plot::new("active_trips_plot")
                     .height(340.0)
                     .x_axis_formatter(|mark, _| {
-                        format_datetime_from_unix_ms((mark.value * 1000.0) as u64)
+                        format_datetime_from_unix_ms(series.time_zone, (mark.value * 1000.0) as u64)
                     })
                     .show(ui, |plot_ui| {
                         plot_ui.line(
@@ -271,6 +609,20 @@ fn render_metrics_panel(ui: &mut egui::Ui, series: &MetricSeries) {
                             Line::new("Cancelled trips", series.cancelled_trips.clone())
                                 .color(chart_color_cancelled_trips()),
                         );
+                        for (label, start, end) in &series.demand_events {
+                            plot_ui.vline(
+                                VLine::new(label.clone(), *start).color(chart_color_demand_event()),
+                            );
+                            plot_ui.vline(
+                                VLine::new(label.clone(), *end)
+                                    .color(chart_color_demand_event())
+                                    .style(egui_plot::LineStyle::dashed_loose()),
+                            );
+                        }
+                        for (label, at) in &series.alerts {
+                            plot_ui
+                                .vline(VLine::new(label.clone(), *at).color(chart_color_alert()));
+                        }
                     });
             });
         });
@@ -285,14 +637,187 @@ fn render_trips_panel(
         .default_open(false)
         .show(ui, |ui| {
             if let Some(snapshot) = latest_snapshot {
-                let sim_epoch_ms = app
-                    .world
-                    .get_resource::<sim_core::clock::SimulationClock>()
-                    .map(|clock| clock.epoch_ms())
-                    .unwrap_or(0);
-                render_trip_table_all(ui, snapshot.trips.as_slice(), sim_epoch_ms);
+                let calendar = SimCalendar::from_world(&app.world);
+                render_trip_table_all(ui, snapshot.trips.as_slice(), calendar);
             } else {
                 ui.label("Waiting for first snapshot...");
             }
         });
 }
+
+fn render_inspector_panel(
+    ui: &mut egui::Ui,
+    app: &mut SimUiApp,
+    latest_snapshot: Option<&sim_core::telemetry::SimSnapshot>,
+) {
+    egui::CollapsingHeader::new("Inspector")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.group(|ui| {
+                ui.set_min_width(ui.available_width());
+                ui.heading("Event timeline");
+                ui.label("Every event that targeted the selected rider, driver or trip.");
+
+                ui.horizontal(|ui| {
+                    let selected_text = app
+                        .inspected_entity
+                        .map(|entity| entity.to_bits().to_string())
+                        .unwrap_or_else(|| "Select entity".to_string());
+                    egui::ComboBox::from_id_salt("inspector_entity")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            let Some(snapshot) = latest_snapshot else {
+                                return;
+                            };
+                            for rider in &snapshot.riders {
+                                ui.selectable_value(
+                                    &mut app.inspected_entity,
+                                    Some(rider.entity),
+                                    format!("Rider {} ({:?})", rider.entity.to_bits(), rider.state),
+                                );
+                            }
+                            for driver in &snapshot.drivers {
+                                ui.selectable_value(
+                                    &mut app.inspected_entity,
+                                    Some(driver.entity),
+                                    format!(
+                                        "Driver {} ({:?})",
+                                        driver.entity.to_bits(),
+                                        driver.state
+                                    ),
+                                );
+                            }
+                        });
+
+                    ui.label("Entity id")
+                        .on_hover_text("Id as shown in the trips table (riders, drivers or trips)");
+                    let response = ui.text_edit_singleline(&mut app.inspector_entity_input);
+                    let submitted =
+                        response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Inspect").clicked() || submitted {
+                        app.inspected_entity = app
+                            .inspector_entity_input
+                            .trim()
+                            .parse::<u64>()
+                            .ok()
+                            .and_then(|bits| Entity::try_from_bits(bits).ok());
+                    }
+                });
+
+                let Some(entity) = app.inspected_entity else {
+                    ui.label("No entity selected.");
+                    return;
+                };
+                let Some(trace) = app.world.get_resource::<EventTrace>() else {
+                    ui.label("Event tracing is disabled for this run.");
+                    return;
+                };
+                let calendar = SimCalendar::from_world(&app.world);
+                let events: Vec<_> = trace.timeline(entity).collect();
+                render_event_timeline(ui, &events, calendar);
+            });
+        });
+}
+
+fn severity_color(severity: LogSeverity) -> egui::Color32 {
+    match severity {
+        LogSeverity::Info => egui::Color32::from_rgb(170, 170, 170),
+        LogSeverity::Warning => egui::Color32::from_rgb(220, 180, 80),
+        LogSeverity::Error => egui::Color32::from_rgb(230, 90, 90),
+    }
+}
+
+fn render_console_panel(ui: &mut egui::Ui, app: &mut SimUiApp) {
+    egui::CollapsingHeader::new("Console")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.group(|ui| {
+                ui.set_min_width(ui.available_width());
+                ui.label(
+                    "Simulation warnings: unmatched riders, exhausted spawners, routing \
+                     fallbacks and scenario inputs that failed to load.",
+                );
+
+                let Some(log) = app.world.get_resource::<SimLog>() else {
+                    ui.label("No simulation log for this run.");
+                    return;
+                };
+                let filter = &mut app.console_filter;
+                ui.horizontal_wrapped(|ui| {
+                    ui.checkbox(
+                        &mut filter.show_errors,
+                        format!("Errors ({})", log.count(LogSeverity::Error)),
+                    );
+                    ui.checkbox(
+                        &mut filter.show_warnings,
+                        format!("Warnings ({})", log.count(LogSeverity::Warning)),
+                    );
+                    ui.checkbox(
+                        &mut filter.show_info,
+                        format!("Info ({})", log.count(LogSeverity::Info)),
+                    );
+                    egui::ComboBox::from_id_salt("console_category")
+                        .selected_text(
+                            filter
+                                .category
+                                .map_or_else(|| "All categories".to_string(), |c| c.to_string()),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut filter.category, None, "All categories");
+                            for category in LogCategory::ALL {
+                                ui.selectable_value(
+                                    &mut filter.category,
+                                    Some(category),
+                                    category.to_string(),
+                                );
+                            }
+                        });
+                    ui.label("Filter");
+                    ui.text_edit_singleline(&mut filter.text)
+                        .on_hover_text("Text in the message, or an entity id");
+                    if log.dropped() > 0 {
+                        ui.label(format!("{} older entries dropped", log.dropped()));
+                    }
+                });
+
+                let entries: Vec<_> = log.entries().filter(|e| filter.matches(e)).collect();
+                if entries.is_empty() {
+                    ui.label("No matching log entries.");
+                    return;
+                }
+                let calendar = SimCalendar::from_world(&app.world);
+                let row_height = ui.spacing().interact_size.y;
+                let mut inspect = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("console_scroll")
+                    .auto_shrink([false, true])
+                    .max_height(240.0)
+                    .stick_to_bottom(true)
+                    .show_rows(ui, row_height, entries.len(), |ui, rows| {
+                        for entry in &entries[rows] {
+                            ui.horizontal(|ui| {
+                                ui.label(format_sim_datetime_from_ms(calendar, entry.timestamp_ms));
+                                ui.colored_label(
+                                    severity_color(entry.severity()),
+                                    entry.severity().to_string(),
+                                );
+                                ui.label(entry.category().to_string());
+                                if let Some(entity) = entry.event.entity() {
+                                    if ui
+                                        .link(entity.to_bits().to_string())
+                                        .on_hover_text("Show in the inspector")
+                                        .clicked()
+                                    {
+                                        inspect = Some(entity);
+                                    }
+                                }
+                                ui.label(entry.event.message());
+                            });
+                        }
+                    });
+                if let Some(entity) = inspect {
+                    app.inspect_entity(entity);
+                }
+            });
+        });
+}
@@ -3,14 +3,17 @@
 use eframe::egui::{self, Align2, Color32, FontId, Vec2};
 use h3o::{CellIndex, LatLng};
 
+use sim_core::clock::EventSubject;
+use sim_core::event_trace::TracedEvent;
 use sim_core::telemetry::{DriverState, GeoPoint, RiderState, TripSnapshot, TripState};
 
-use crate::app::TileKey;
+use crate::app::{SpawnPainting, TileKey};
 use crate::ui::utils::{
-    chart_color_abandoned_quote, chart_color_active_trips, chart_color_cancelled_riders,
-    chart_color_cancelled_trips, chart_color_completed_trips, chart_color_idle_drivers,
-    chart_color_waiting_riders, driver_color, format_distance_km, format_optional_sim_datetime,
-    format_sim_datetime_from_ms, format_trip_distance_km, rider_color,
+    chart_color_abandoned_quote, chart_color_active_trips, chart_color_alert,
+    chart_color_cancelled_riders, chart_color_cancelled_trips, chart_color_completed_trips,
+    chart_color_demand_event, chart_color_idle_drivers, chart_color_waiting_riders, driver_color,
+    format_distance_km, format_optional_sim_datetime, format_sim_datetime_from_ms,
+    format_trip_distance_km, rider_color, SimCalendar,
 };
 
 /// Geographic bounds for map projection.
@@ -69,6 +72,71 @@ pub fn project_lat_lng_unclamped(
     Some(egui::pos2(px, py))
 }
 
+/// Map a screen position back to (lat, lng); `None` outside `rect`.
+pub fn unproject_pos(pos: egui::Pos2, bounds: &MapBounds, rect: egui::Rect) -> Option<(f64, f64)> {
+    if !rect.contains(pos) || rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return None;
+    }
+    let x = f64::from((pos.x - rect.left()) / rect.width());
+    let y = f64::from((pos.y - rect.top()) / rect.height());
+    let lat = bounds.lat_max - y * (bounds.lat_max - bounds.lat_min);
+    let lng = bounds.lng_min + x * (bounds.lng_max - bounds.lng_min);
+    Some((lat, lng))
+}
+
+/// Draw painted spawn weights as translucent hexagons: orange for riders, blue for drivers,
+/// violet where both are painted, more opaque with higher weight.
+pub fn draw_spawn_painting(
+    painter: &egui::Painter,
+    painting: &SpawnPainting,
+    bounds: &MapBounds,
+    rect: egui::Rect,
+) {
+    let max_weight = painting.max_weight();
+    if max_weight <= 0.0 {
+        return;
+    }
+    for (cell, weights) in painting.cells() {
+        let weight = weights.rider.max(weights.driver);
+        let (r, g, b) = match (weights.rider > 0.0, weights.driver > 0.0) {
+            (true, true) => (170, 110, 240),
+            (true, false) => (255, 150, 40),
+            _ => (70, 150, 255),
+        };
+        let alpha = (30.0 + 170.0 * weight / max_weight) as u8;
+        let points: Vec<egui::Pos2> = cell
+            .boundary()
+            .iter()
+            .filter_map(|ll| project_lat_lng_unclamped(ll.lat(), ll.lng(), bounds, rect))
+            .collect();
+        if points.len() >= 3 {
+            painter.add(egui::Shape::convex_polygon(
+                points,
+                Color32::from_rgba_unmultiplied(r, g, b, alpha),
+                egui::Stroke::NONE,
+            ));
+        }
+    }
+}
+
+/// Outline an H3 cell.
+pub fn draw_cell_outline(
+    painter: &egui::Painter,
+    cell: CellIndex,
+    bounds: &MapBounds,
+    rect: egui::Rect,
+    stroke: egui::Stroke,
+) {
+    let points: Vec<egui::Pos2> = cell
+        .boundary()
+        .iter()
+        .filter_map(|ll| project_lat_lng_unclamped(ll.lat(), ll.lng(), bounds, rect))
+        .collect();
+    if points.len() >= 3 {
+        painter.add(egui::Shape::closed_line(points, stroke));
+    }
+}
+
 /// Project either a cached geo position (preferred) or an H3 cell.
 pub fn project_position(
     cell: CellIndex,
@@ -234,6 +302,8 @@ pub fn render_metrics_legend(ui: &mut egui::Ui) {
         legend_item(ui, chart_color_abandoned_quote(), "Abandoned (quote)");
         legend_item(ui, chart_color_completed_trips(), "Completed trips");
         legend_item(ui, chart_color_cancelled_trips(), "Cancelled trips");
+        legend_item(ui, chart_color_demand_event(), "Demand events");
+        legend_item(ui, chart_color_alert(), "Alerts");
     });
 }
 
@@ -268,7 +338,7 @@ fn trip_state_label(state: TripState) -> &'static str {
 }
 
 /// Render the complete trip table with all trips.
-pub fn render_trip_table_all(ui: &mut egui::Ui, trips: &[TripSnapshot], sim_epoch_ms: i64) {
+pub fn render_trip_table_all(ui: &mut egui::Ui, trips: &[TripSnapshot], calendar: SimCalendar) {
     ui.group(|ui| {
         let available_width = ui.available_width();
         ui.set_min_width(available_width);
@@ -289,7 +359,7 @@ pub fn render_trip_table_all(ui: &mut egui::Ui, trips: &[TripSnapshot], sim_epoc
             &rows,
             available_width,
             280.0,
-            sim_epoch_ms,
+            calendar,
         );
     });
 }
@@ -301,7 +371,7 @@ fn render_trip_table_section(
     rows: &[&TripSnapshot],
     available_width: f32,
     max_height: f32,
-    sim_epoch_ms: i64,
+    calendar: SimCalendar,
 ) {
     egui::ScrollArea::vertical()
         .id_salt(format!("{}_scroll", table_id))
@@ -333,14 +403,49 @@ fn render_trip_table_section(
                         ui.label(trip_state_label(trip.state));
                         ui.label(format_distance_km(trip.pickup_distance_km_at_accept));
                         ui.label(format_trip_distance_km(trip.pickup_cell, trip.dropoff_cell));
-                        ui.label(format_sim_datetime_from_ms(sim_epoch_ms, trip.requested_at));
-                        ui.label(format_sim_datetime_from_ms(sim_epoch_ms, trip.matched_at));
-                        ui.label(format_optional_sim_datetime(sim_epoch_ms, trip.pickup_at));
-                        ui.label(format_optional_sim_datetime(sim_epoch_ms, trip.dropoff_at));
-                        ui.label(format_optional_sim_datetime(
-                            sim_epoch_ms,
-                            trip.cancelled_at,
-                        ));
+                        ui.label(format_sim_datetime_from_ms(calendar, trip.requested_at));
+                        ui.label(format_sim_datetime_from_ms(calendar, trip.matched_at));
+                        ui.label(format_optional_sim_datetime(calendar, trip.pickup_at));
+                        ui.label(format_optional_sim_datetime(calendar, trip.dropoff_at));
+                        ui.label(format_optional_sim_datetime(calendar, trip.cancelled_at));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Render an entity's traced events, oldest first.
+pub fn render_event_timeline(ui: &mut egui::Ui, events: &[&TracedEvent], calendar: SimCalendar) {
+    if events.is_empty() {
+        ui.label("No events recorded for this entity yet.");
+        return;
+    }
+    let available_width = ui.available_width();
+    egui::ScrollArea::vertical()
+        .id_salt("event_timeline_scroll")
+        .auto_shrink([false, true])
+        .max_height(280.0)
+        .show(ui, |ui| {
+            ui.set_min_width(available_width);
+            egui::Grid::new("event_timeline")
+                .min_col_width(available_width / 4.0)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Time");
+                    ui.label("Event");
+                    ui.label("Subject");
+                    ui.end_row();
+
+                    for event in events {
+                        ui.label(format_sim_datetime_from_ms(calendar, event.timestamp));
+                        ui.label(format!("{:?}", event.kind));
+                        ui.label(match event.subject {
+                            EventSubject::Rider(entity) => format!("Rider {}", entity.to_bits()),
+                            EventSubject::Driver(entity) => {
+                                format!("Driver {}", entity.to_bits())
+                            }
+                            EventSubject::Trip(entity) => format!("Trip {}", entity.to_bits()),
+                        });
                         ui.end_row();
                     }
                 });
@@ -1,7 +1,10 @@
 //! Utility functions for the UI: formatting, colors, conversions.
 
+use chrono::{Datelike, NaiveDate, Timelike};
 use eframe::egui::Color32;
 use h3o::{CellIndex, LatLng};
+use sim_core::clock::civil::CityTimeZone;
+use sim_core::clock::SimulationClock;
 use sim_core::telemetry::{DriverState, RiderState};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -25,79 +28,79 @@ pub fn format_hms_from_ms(ms: u64) -> String {
     format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
 }
 
-pub fn format_datetime_from_unix_ms(ms: u64) -> String {
-    let total_secs = (ms / 1000) as i64;
-    let days = total_secs / 86_400;
-    let day_secs = total_secs % 86_400;
-    let (year, month, day) = civil_from_days(days);
-    let hours = day_secs / 3600;
-    let minutes = (day_secs % 3600) / 60;
-    let seconds = day_secs % 60;
+pub fn format_datetime_from_unix_ms(zone: CityTimeZone, ms: u64) -> String {
+    let (year, month, day, hours, minutes) = datetime_from_unix_ms(zone, ms);
+    let seconds = (ms / 1000) % 60;
     format!(
         "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
         year, month, day, hours, minutes, seconds
     )
 }
 
-fn civil_from_days(days_since_unix_epoch: i64) -> (i32, u32, u32) {
-    let z = days_since_unix_epoch + 719_468;
-    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
-    let doe = z - era * 146_097;
-    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
-    let y = yoe + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let d = doy - (153 * mp + 2) / 5 + 1;
-    let m = mp + if mp < 10 { 3 } else { -9 };
-    let year = y + if m <= 2 { 1 } else { 0 };
-    (year as i32, m as u32, d as u32)
-}
-
-pub fn datetime_from_unix_ms(ms: u64) -> (i32, u32, u32, u32, u32) {
-    let total_secs = (ms / 1000) as i64;
-    let days = total_secs / 86_400;
-    let day_secs = total_secs % 86_400;
-    let (year, month, day) = civil_from_days(days);
-    let hours = (day_secs / 3600) as u32;
-    let minutes = ((day_secs % 3600) / 60) as u32;
-    (year, month, day, hours, minutes)
-}
-
-pub fn datetime_to_unix_ms(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
-    // Convert date to days since Unix epoch
-    // Algorithm from https://howardhinnant.github.io/date_algorithms.html
-    let y = year as i64;
-    let m = month as i64;
-    let d = day as i64;
-
-    // Adjust for month
-    let adjusted_m = if m <= 2 { m + 12 } else { m };
-    let adjusted_y = if m <= 2 { y - 1 } else { y };
-
-    // Calculate days since epoch (1970-01-01)
-    let era = (if adjusted_y >= 0 {
-        adjusted_y
-    } else {
-        adjusted_y - 399
-    }) / 400;
-    let yoe = adjusted_y - era * 400;
-    let doy = (153 * (adjusted_m - 3) + 2) / 5 + d - 1;
-    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
-    let days = era * 146097 + doe - 719468;
-
-    // Add time components
-    let total_secs = days * 86400 + hour as i64 * 3600 + minute as i64 * 60;
-    total_secs * 1000 // Convert to milliseconds
-}
-
-pub fn format_sim_datetime_from_ms(sim_epoch_ms: i64, sim_ms: u64) -> String {
-    let real_ms = sim_epoch_ms.saturating_add(sim_ms as i64).max(0) as u64;
-    format_datetime_from_unix_ms(real_ms)
-}
-
-pub fn format_optional_sim_datetime(sim_epoch_ms: i64, sim_ms: Option<u64>) -> String {
+/// Calendar date and time (year, month, day, hour, minute) in `zone` for Unix ms.
+pub fn datetime_from_unix_ms(zone: CityTimeZone, ms: u64) -> (i32, u32, u32, u32, u32) {
+    zone.local_datetime(ms as i64)
+        .map(|dt| (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute()))
+        .unwrap_or((1970, 1, 1, 0, 0))
+}
+
+/// Unix ms for a calendar date and time in `zone`. Days past the end of the month are clamped
+/// to its last day; times skipped by a DST change resolve to the same time an hour later.
+pub fn datetime_to_unix_ms(
+    zone: CityTimeZone,
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+) -> i64 {
+    (1..=day.max(1))
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .and_then(|date| date.and_hms_opt(hour, minute, 0))
+        .and_then(|local| {
+            zone.utc_ms_from_local(local)
+                .or_else(|| zone.utc_ms_from_local(local + chrono::Duration::hours(1)))
+        })
+        .unwrap_or(0)
+}
+
+/// Epoch and time zone of the simulation clock, for showing sim timestamps as datetimes.
+#[derive(Debug, Clone, Copy)]
+pub struct SimCalendar {
+    pub epoch_ms: i64,
+    pub zone: CityTimeZone,
+}
+
+impl SimCalendar {
+    /// Read from the world's `SimulationClock` (epoch 0, UTC before a world is built).
+    pub fn from_world(world: &World) -> Self {
+        world.get_resource::<SimulationClock>().map_or(
+            Self {
+                epoch_ms: 0,
+                zone: CityTimeZone::UTC,
+            },
+            |clock| Self {
+                epoch_ms: clock.epoch_ms(),
+                zone: clock.local_time().time_zone().unwrap_or(CityTimeZone::UTC),
+            },
+        )
+    }
+
+    /// Unix ms of a simulation timestamp.
+    pub fn unix_ms(&self, sim_ms: u64) -> i64 {
+        self.epoch_ms.saturating_add(sim_ms as i64)
+    }
+}
+
+pub fn format_sim_datetime_from_ms(calendar: SimCalendar, sim_ms: u64) -> String {
+    let real_ms = calendar.unix_ms(sim_ms).max(0) as u64;
+    format_datetime_from_unix_ms(calendar.zone, real_ms)
+}
+
+pub fn format_optional_sim_datetime(calendar: SimCalendar, sim_ms: Option<u64>) -> String {
     sim_ms
-        .map(|value| format_sim_datetime_from_ms(sim_epoch_ms, value))
+        .map(|value| format_sim_datetime_from_ms(calendar, value))
         .unwrap_or_else(|| "-".to_string())
 }
 
@@ -204,6 +207,14 @@ pub fn chart_color_cancelled_trips() -> Color32 {
     Color32::from_rgb(160, 80, 200)
 }
 
+pub fn chart_color_demand_event() -> Color32 {
+    Color32::from_rgb(230, 160, 30)
+}
+
+pub fn chart_color_alert() -> Color32 {
+    Color32::from_rgb(230, 90, 90)
+}
+
 pub fn chart_color_abandoned_quote() -> Color32 {
     Color32::from_rgb(200, 120, 180)
 }
@@ -0,0 +1,152 @@
+// Map viewer: renders snapshot messages relayed from the run's telemetry stream.
+
+// Same palette as the egui UI (sim_ui/src/ui/utils.rs).
+const RIDER_COLORS = {
+  Browsing: "rgb(120,180,255)",
+  Waiting: "rgb(255,200,0)",
+  InTransit: "rgb(0,200,120)",
+  Completed: "rgb(140,140,140)",
+  Cancelled: "rgb(200,80,80)",
+};
+const DRIVER_COLORS = {
+  Idle: "rgb(0,200,120)",
+  Evaluating: "rgb(255,200,0)",
+  EnRoute: "rgb(255,140,0)",
+  OnTrip: "rgb(80,140,255)",
+  OffDuty: "rgb(100,100,100)",
+};
+const COUNT_ROWS = [
+  ["Riders browsing", "riders_browsing", RIDER_COLORS.Browsing],
+  ["Riders waiting", "riders_waiting", RIDER_COLORS.Waiting],
+  ["Riders in transit", "riders_in_transit", RIDER_COLORS.InTransit],
+  ["Drivers idle", "drivers_idle", DRIVER_COLORS.Idle],
+  ["Drivers en route", "drivers_en_route", DRIVER_COLORS.EnRoute],
+  ["Drivers on trip", "drivers_on_trip", DRIVER_COLORS.OnTrip],
+  ["Drivers off duty", "drivers_off_duty", DRIVER_COLORS.OffDuty],
+  ["Trips completed", "trips_completed"],
+  ["Trips cancelled", "trips_cancelled"],
+];
+const MAX_EVENTS = 20;
+
+function colorExpression(colors) {
+  const expression = ["match", ["get", "state"]];
+  for (const [state, color] of Object.entries(colors)) {
+    expression.push(state, color);
+  }
+  expression.push("#fff");
+  return expression;
+}
+
+function formatSimTime(ms) {
+  const total = Math.floor(ms / 1000);
+  const pad = (n) => String(n).padStart(2, "0");
+  const days = Math.floor(total / 86400);
+  const clock = `${pad(Math.floor((total % 86400) / 3600))}:${pad(Math.floor((total % 3600) / 60))}:${pad(total % 60)}`;
+  return days > 0 ? `day ${days + 1} ${clock}` : clock;
+}
+
+function agentFeatures(agents) {
+  return {
+    type: "FeatureCollection",
+    features: agents.map((agent) => ({
+      type: "Feature",
+      geometry: { type: "Point", coordinates: [agent.lng, agent.lat] },
+      properties: { kind: agent.kind, id: agent.id, state: agent.state },
+    })),
+  };
+}
+
+function renderCounts(counts) {
+  const rows = COUNT_ROWS.map(([label, key, color]) => {
+    const swatch = color ? `<span class="swatch" style="background:${color}"></span>` : "";
+    return `<tr><td>${swatch}${label}</td><td>${counts[key] ?? 0}</td></tr>`;
+  });
+  document.getElementById("counts").innerHTML = rows.join("");
+}
+
+function renderEvent(message) {
+  const list = document.getElementById("events");
+  const item = document.createElement("li");
+  const who = message.trip != null ? `trip ${message.trip}` : message.rider != null ? `rider ${message.rider}` : `driver ${message.driver}`;
+  item.textContent = `${formatSimTime(message.timestamp_ms)} ${message.kind} (${who})`;
+  list.prepend(item);
+  while (list.children.length > MAX_EVENTS) {
+    list.lastChild.remove();
+  }
+}
+
+function fitToAgents(map, agents) {
+  if (agents.length === 0) {
+    return false;
+  }
+  const bounds = new maplibregl.LngLatBounds();
+  for (const agent of agents) {
+    bounds.extend([agent.lng, agent.lat]);
+  }
+  map.fitBounds(bounds, { padding: 60, maxZoom: 15, duration: 0 });
+  return true;
+}
+
+function connect(map) {
+  const status = document.getElementById("status");
+  const protocol = location.protocol === "https:" ? "wss" : "ws";
+  const socket = new WebSocket(`${protocol}://${location.host}/ws`);
+  let fitted = false;
+
+  socket.onopen = () => (status.textContent = "live");
+  socket.onclose = () => {
+    status.textContent = "reconnecting…";
+    setTimeout(() => connect(map), 2000);
+  };
+  socket.onmessage = (event) => {
+    const message = JSON.parse(event.data);
+    if (message.type === "snapshot") {
+      const agents = message.agents ?? [];
+      map.getSource("riders").setData(agentFeatures(agents.filter((a) => a.kind === "rider")));
+      map.getSource("drivers").setData(agentFeatures(agents.filter((a) => a.kind === "driver")));
+      document.getElementById("clock").textContent = formatSimTime(message.timestamp_ms);
+      renderCounts(message.counts);
+      if (!fitted) {
+        fitted = fitToAgents(map, agents);
+      }
+    } else if (message.type === "trip_event") {
+      renderEvent(message);
+    }
+  };
+}
+
+async function main() {
+  const config = await (await fetch("/config.json")).json();
+  const map = new maplibregl.Map({ container: "map", style: config.map_style, center: [0, 0], zoom: 1 });
+  map.on("load", () => {
+    const empty = agentFeatures([]);
+    map.addSource("riders", { type: "geojson", data: empty });
+    map.addSource("drivers", { type: "geojson", data: empty });
+    map.addLayer({
+      id: "drivers",
+      type: "circle",
+      source: "drivers",
+      paint: {
+        "circle-radius": 5,
+        "circle-color": colorExpression(DRIVER_COLORS),
+        "circle-stroke-width": 1,
+        "circle-stroke-color": "#222",
+      },
+    });
+    map.addLayer({
+      id: "riders",
+      type: "circle",
+      source: "riders",
+      paint: { "circle-radius": 3.5, "circle-color": colorExpression(RIDER_COLORS) },
+    });
+    for (const layer of ["riders", "drivers"]) {
+      map.on("click", layer, (event) => {
+        const { kind, id, state } = event.features[0].properties;
+        new maplibregl.Popup().setLngLat(event.lngLat).setText(`${kind} ${id}: ${state}`).addTo(map);
+      });
+    }
+    connect(map);
+  });
+}
+
+main();
@@ -0,0 +1,115 @@
+//! Web map viewer for headless runs.
+//!
+//! Connects to a run's telemetry WebSocket (`sim_core::telemetry_stream::WebSocketPublisher`,
+//! with agent positions enabled) and serves a MapLibre page that draws riders and drivers on
+//! real map tiles. Browsers only talk to this server, so one run can be watched by any number
+//! of remote viewers.
+
+mod relay;
+
+use std::sync::Arc;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::http::header;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+
+use relay::{spawn_upstream, Relay};
+
+const INDEX_HTML: &str = include_str!("../assets/index.html");
+const APP_JS: &str = include_str!("../assets/app.js");
+
+const DEFAULT_STREAM_URL: &str = "ws://127.0.0.1:9001/";
+const DEFAULT_MAP_STYLE: &str = "https://demotiles.maplibre.org/style.json";
+
+struct AppState {
+    relay: Arc<Relay>,
+    map_style: String,
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn app_js() -> Response {
+    ([(header::CONTENT_TYPE, "text/javascript")], APP_JS).into_response()
+}
+
+async fn config(State(state): State<Arc<AppState>>) -> Response {
+    Json(json!({ "map_style": state.map_style })).into_response()
+}
+
+async fn viewer(State(state): State<Arc<AppState>>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| stream_to_viewer(state, socket))
+}
+
+/// Send the latest snapshot, then every message from the run as it arrives.
+async fn stream_to_viewer(state: Arc<AppState>, mut socket: WebSocket) {
+    let mut messages = state.relay.subscribe();
+    if let Some(snapshot) = state.relay.latest_snapshot() {
+        if socket
+            .send(Message::Text(snapshot.as_ref().into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+    loop {
+        let text = match messages.recv().await {
+            Ok(text) => text,
+            // The viewer fell behind; skip ahead rather than disconnect.
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        };
+        if socket
+            .send(Message::Text(text.as_ref().into()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    std::env::var(name).unwrap_or_else(|_| default.to_string())
+}
+
+/// Port from `PORT`, default 8080.
+fn listen_port() -> u16 {
+    std::env::var("PORT")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(8080)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let relay = Relay::new();
+    spawn_upstream(
+        env_or("SIM_WEB_UI_STREAM_URL", DEFAULT_STREAM_URL),
+        relay.clone(),
+    );
+    let state = Arc::new(AppState {
+        relay,
+        map_style: env_or("SIM_WEB_UI_MAP_STYLE", DEFAULT_MAP_STYLE),
+    });
+
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/app.js", get(app_js))
+        .route("/config.json", get(config))
+        .route("/ws", get(viewer))
+        .with_state(state);
+
+    let port = listen_port();
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("Map viewer on http://localhost:{port}/");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
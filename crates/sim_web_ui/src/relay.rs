@@ -0,0 +1,76 @@
+//! Upstream connection to a run's telemetry WebSocket, fanned out to browser viewers.
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+use tungstenite::Message;
+
+/// Delay before reconnecting after the upstream closes or cannot be reached.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+/// Messages a slow viewer may fall behind before it skips ahead.
+const VIEWER_BUFFER: usize = 1024;
+
+/// Latest telemetry from the run, shared by all viewers.
+pub struct Relay {
+    messages: broadcast::Sender<Arc<str>>,
+    /// Last snapshot message, replayed to viewers that join mid-run.
+    latest_snapshot: Mutex<Option<Arc<str>>>,
+}
+
+impl Relay {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            messages: broadcast::channel(VIEWER_BUFFER).0,
+            latest_snapshot: Mutex::new(None),
+        })
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<str>> {
+        self.messages.subscribe()
+    }
+
+    pub fn latest_snapshot(&self) -> Option<Arc<str>> {
+        self.latest_snapshot
+            .lock()
+            .ok()
+            .and_then(|latest| latest.clone())
+    }
+
+    fn forward(&self, text: &str) {
+        let message: Arc<str> = Arc::from(text);
+        if text.contains(r#""type":"snapshot""#) {
+            if let Ok(mut latest) = self.latest_snapshot.lock() {
+                *latest = Some(message.clone());
+            }
+        }
+        // No viewers connected is fine; the message is just dropped.
+        let _ = self.messages.send(message);
+    }
+}
+
+/// Read `url` (a `WebSocketPublisher` endpoint) on a background thread, reconnecting whenever
+/// the run restarts or the connection drops.
+pub fn spawn_upstream(url: String, relay: Arc<Relay>) {
+    thread::Builder::new()
+        .name("telemetry-upstream".to_string())
+        .spawn(move || loop {
+            match tungstenite::connect(url.as_str()) {
+                Ok((mut socket, _)) => {
+                    println!("Connected to telemetry stream {url}");
+                    while let Ok(message) = socket.read() {
+                        match message {
+                            Message::Text(text) => relay.forward(text.as_str()),
+                            Message::Close(_) => break,
+                            _ => {}
+                        }
+                    }
+                    println!("Telemetry stream {url} closed");
+                }
+                Err(err) => eprintln!("Cannot connect to telemetry stream {url}: {err}"),
+            }
+            thread::sleep(RECONNECT_DELAY);
+        })
+        .expect("spawn telemetry upstream thread");
+}
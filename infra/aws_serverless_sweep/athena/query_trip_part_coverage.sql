@@ -0,0 +1,10 @@
+SELECT
+  run_id,
+  shard_id,
+  point_index,
+  COUNT(DISTINCT "$path") AS part_files,
+  COUNT(*) AS trip_rows
+FROM ride_sim_analytics.sweep_trip_data
+WHERE run_id = ':run_id'
+GROUP BY run_id, shard_id, point_index
+ORDER BY CAST(shard_id AS integer), CAST(point_index AS integer);
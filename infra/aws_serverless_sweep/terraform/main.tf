@@ -153,6 +153,7 @@ resource "aws_iam_role_policy" "runtime_s3_policy" {
       {
         Effect = "Allow",
         Action = [
+          "s3:GetObject",
           "s3:PutObject",
           "s3:DeleteObject"
         ],
@@ -232,6 +233,13 @@ resource "aws_lambda_event_source_mapping" "runtime_sqs" {
   function_name    = aws_lambda_function.runtime_lambda.arn
   batch_size       = 1
   enabled          = true
+
+  dynamic "scaling_config" {
+    for_each = var.max_shard_concurrency == null ? [] : [var.max_shard_concurrency]
+    content {
+      maximum_concurrency = scaling_config.value
+    }
+  }
 }
 
 resource "aws_api_gateway_rest_api" "sweep_api" {
@@ -261,6 +269,24 @@ resource "aws_api_gateway_model" "sweep_request" {
       shard_count = { type = "integer", minimum = 1 },
       shard_size  = { type = "integer", minimum = 1 },
       seed        = { type = "integer" }
+      benchmark = {
+        type     = "object",
+        required = ["seconds_per_run"],
+        properties = {
+          seconds_per_run      = { type = "number", exclusiveMinimum = 0 },
+          output_bytes_per_run = { type = "integer", minimum = 0 }
+        }
+      },
+      estimate_only = { type = "boolean" }
+      pacing = {
+        type = "object",
+        properties = {
+          max_in_flight_shards   = { type = "integer", minimum = 1 },
+          wave_interval_seconds  = { type = "integer", minimum = 1 },
+          max_enqueue_per_second = { type = "number", exclusiveMinimum = 0 },
+          enqueue_burst          = { type = "integer", minimum = 0 }
+        }
+      }
     },
     anyOf = [
       { required = ["shard_count"] },
@@ -375,6 +401,14 @@ output "shard_queue_url" {
   value = aws_sqs_queue.shard_queue.id
 }
 
+output "shard_dlq_url" {
+  value = aws_sqs_queue.shard_dlq.id
+}
+
+output "runtime_lambda_arn" {
+  value = aws_lambda_function.runtime_lambda.arn
+}
+
 output "athena_query_policy_arn" {
   value = aws_iam_policy.athena_query_access.arn
 }
@@ -34,6 +34,12 @@ variable "max_shards" {
   default     = 1000
 }
 
+variable "max_shard_concurrency" {
+  description = "Maximum concurrent shard executions drawn from the shard queue (null = unbounded; minimum 2)"
+  type        = number
+  default     = null
+}
+
 variable "athena_database" {
   description = "Athena database for sweep analytics"
   type        = string
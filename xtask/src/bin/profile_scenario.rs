@@ -0,0 +1,56 @@
+//! Run one headless scenario under a sampling profiler and write a flamegraph SVG.
+//!
+//! Usage: `profile_scenario --scenario <scenario.json> [--output <flamegraph.svg>]
+//! [--frequency <hz>]`. The scenario file is a JSON object of sweep dimension values, e.g.
+//! `{"preset": "berlin-weekday-rush", "num_riders": 2000, "seed": 7}`; keys are applied exactly as a
+//! single sweep point would be, and `seed` is the sweep seed. Build with debug symbols
+//! (`cargo run -p xtask -- profile` does) for readable frames.
+
+use std::fs;
+use std::process::exit;
+use std::time::Instant;
+
+use sim_experiments::run_single_simulation_with_artifacts;
+use sim_serverless_sweep_lambda::adapters::shard_execution::scenario_parameter_set;
+
+const DEFAULT_FREQUENCY_HZ: i32 = 997;
+
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+fn required_arg(name: &str) -> String {
+    arg_value(name).unwrap_or_else(|| {
+        eprintln!("missing required argument {name}");
+        exit(2);
+    })
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let scenario_path = required_arg("--scenario");
+    let output = arg_value("--output").unwrap_or_else(|| "flamegraph.svg".to_string());
+    let frequency = arg_value("--frequency")
+        .map(|value| value.parse())
+        .transpose()?
+        .unwrap_or(DEFAULT_FREQUENCY_HZ);
+    let parameter_set = scenario_parameter_set(&fs::read_to_string(&scenario_path)?)?;
+
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(frequency)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()?;
+    let started = Instant::now();
+    let artifacts = run_single_simulation_with_artifacts(&parameter_set)?;
+    let elapsed = started.elapsed();
+    let report = guard.report().build()?;
+
+    report.flamegraph(fs::File::create(&output)?)?;
+    println!(
+        "Profiled {scenario_path}: {}/{} riders completed in {elapsed:.2?} ({} distinct stacks at {frequency} Hz)",
+        artifacts.metrics.completed_riders,
+        artifacts.metrics.total_riders,
+        report.data.len()
+    );
+    println!("Wrote flamegraph to {output}");
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{exit, Command, ExitStatus};
 
+use base64::Engine;
 use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use zip::write::FileOptions;
 use zip::{CompressionMethod, ZipWriter};
 
@@ -30,8 +33,36 @@ enum Commands {
     /// Run the large scenario (10 000 riders, 7 000 drivers)
     RunLarge,
     /// Run a parameter sweep experiment
-    Sweep,
-    /// Export a precomputed route table to JSON
+    Sweep {
+        /// Hide the progress bar (completed/total, average run time, ETA)
+        #[arg(long)]
+        no_progress: bool,
+        /// Memory the sweep may use (MB); runs fewer simulations at once to stay within it
+        #[arg(long)]
+        memory_budget_mb: Option<u64>,
+    },
+    /// Render sweep results (Parquet) into a self-contained HTML report
+    Report {
+        /// Results Parquet file, e.g. from export_to_parquet
+        #[arg(long)]
+        input: String,
+        /// HTML output path
+        #[arg(long, default_value = "report.html")]
+        output: String,
+        /// Metric whose highest value marks the best run
+        #[arg(long)]
+        rank_by: Option<String>,
+        /// Comma-separated metrics to chart
+        #[arg(long)]
+        metrics: Option<String>,
+        /// Comma-separated tag filters (key=value); only matching runs are reported
+        #[arg(long)]
+        filter: Option<String>,
+        /// Tag key to summarize runs by
+        #[arg(long)]
+        group_by: Option<String>,
+    },
+    /// Export a precomputed route table (JSON summary or binary with metadata)
     RouteExport {
         /// Number of origin-destination samples
         #[arg(long, default_value_t = 100)]
@@ -39,11 +70,18 @@ enum Commands {
         /// Output file path
         #[arg(long, default_value = "route_table.json")]
         output: String,
+        /// Output format: json or bin (defaults to bin for `.bin` outputs)
+        #[arg(long)]
+        format: Option<String>,
     },
     /// Run Criterion benchmarks
     Bench,
     /// Compare benchmarks: stash changes, create baseline, restore, compare
-    BenchCompare,
+    BenchCompare {
+        /// Fail if a throughput benchmark loses more than this many percent of events/sec
+        #[arg(long, default_value_t = 10.0)]
+        max_throughput_regression: f64,
+    },
     /// Run CI checks (fmt, clippy, tests, examples, benchmarks)
     Ci {
         /// Job to run
@@ -52,6 +90,32 @@ enum Commands {
     },
     /// Run load tests (ignored tests in sim_core)
     LoadTest,
+    /// Run the scenario smoke matrix (algorithm × routing × batch) and check invariants
+    Smoke,
+    /// Profile a headless scenario and write a flamegraph SVG
+    Profile {
+        /// Scenario JSON: sweep dimension values plus an optional seed
+        #[arg(long)]
+        scenario: String,
+        /// Flamegraph output path
+        #[arg(long, default_value = "flamegraph.svg")]
+        output: String,
+        /// Sampling frequency (Hz)
+        #[arg(long, default_value_t = 997)]
+        frequency: u32,
+    },
+    /// Run a seeded scenario several times (with different thread counts) and diff final metrics
+    DeterminismCheck {
+        /// Scenario JSON: sweep dimension values plus an optional seed
+        #[arg(long)]
+        scenario: String,
+        /// Number of runs to compare
+        #[arg(long, default_value_t = 5)]
+        runs: usize,
+        /// Comma-separated compute thread counts cycled across runs (default 1,2,<cores>)
+        #[arg(long)]
+        threads: Option<String>,
+    },
     /// Build and package Rust Lambda artifacts for Terraform inputs
     ServerlessPackage {
         /// Compilation target triple for Lambda binaries
@@ -61,6 +125,45 @@ enum Commands {
         #[arg(value_enum, long, default_value_t = BuildProfile::Release)]
         profile: BuildProfile,
     },
+    /// Verify packaged Lambda artifacts, then terraform plan/apply the AWS sweep stack
+    ServerlessDeploy {
+        /// Extra Terraform variable (repeatable), e.g. --var results_bucket_name=my-bucket
+        #[arg(long = "var", value_name = "KEY=VALUE")]
+        vars: Vec<String>,
+        /// Stop after the plan and artifact checks without applying
+        #[arg(long)]
+        plan_only: bool,
+    },
+    /// Re-run one serverless shard locally with the same parameter points and seeds
+    ReproduceShard {
+        /// Run id of the failed shard outcome record
+        #[arg(long)]
+        run_id: String,
+        /// Shard id of the failed shard outcome record
+        #[arg(long)]
+        shard_id: usize,
+        /// Original sweep request JSON (the body posted to the API)
+        #[arg(long)]
+        request: String,
+        /// Shard outcome record JSON; its boundaries are checked against the recomputed plan
+        #[arg(long)]
+        outcome: Option<String>,
+    },
+    /// Estimate shard count, Lambda duration, S3 storage and cost of a serverless sweep
+    EstimateSweep {
+        /// Sweep request JSON (the body that would be posted to the API)
+        #[arg(long)]
+        request: String,
+        /// Seconds per run; when omitted, the first points are benchmarked locally
+        #[arg(long)]
+        seconds_per_run: Option<f64>,
+        /// Output bytes per run (used with --seconds-per-run)
+        #[arg(long)]
+        bytes_per_run: Option<u64>,
+        /// Points to benchmark locally when --seconds-per-run is omitted
+        #[arg(long, default_value_t = 1)]
+        benchmark_points: usize,
+    },
 }
 
 #[derive(Clone, ValueEnum)]
@@ -126,6 +229,19 @@ fn run_cargo(args: &[&str]) {
     }
 }
 
+/// Like [`run_cargo`], with extra environment variables for the cargo process.
+fn run_cargo_with_env(env: &[(&str, &str)], args: &[&str]) {
+    eprintln!("+ cargo {}", args.join(" "));
+    let status = Command::new("cargo")
+        .envs(env.iter().copied())
+        .args(args)
+        .status()
+        .expect("failed to execute cargo");
+    if !status.success() {
+        exit(status.code().unwrap_or(1));
+    }
+}
+
 fn run_git(args: &[&str]) {
     let status = git(args);
     if !status.success() {
@@ -159,17 +275,167 @@ fn package_serverless_lambdas(target: &str, profile: BuildProfile) {
     let dist_dir = Path::new("infra/aws_serverless_sweep/dist");
     fs::create_dir_all(dist_dir).expect("failed to create lambda dist directory");
 
+    let zip_path = dist_dir.join("runtime.zip");
     package_lambda_zip(
         &target_dir.join(binary_name("sweep_runtime", target)),
-        &dist_dir.join("runtime.zip"),
+        &zip_path,
     );
+    let hash = file_base64_sha256(&zip_path);
+    fs::write(artifact_hash_path(&zip_path), format!("{hash}\n"))
+        .expect("failed to write lambda artifact hash");
 
     eprintln!(
-        "\nPackaged artifact:\n- {}",
-        dist_dir.join("runtime.zip").display()
+        "\nPackaged artifact:\n- {} (sha256 {hash})",
+        zip_path.display()
     );
 }
 
+/// Base64 SHA-256 of a file, the same digest as Terraform's `filebase64sha256`.
+fn file_base64_sha256(path: &Path) -> String {
+    let bytes = fs::read(path)
+        .unwrap_or_else(|error| panic!("failed to read '{}': {error}", path.display()));
+    base64::engine::general_purpose::STANDARD.encode(Sha256::digest(&bytes))
+}
+
+/// Hash recorded next to a packaged zip by `serverless-package`.
+fn artifact_hash_path(zip_path: &Path) -> PathBuf {
+    zip_path.with_extension("zip.sha256")
+}
+
+// ── Terraform deploy ───────────────────────────────────────────────
+
+const TERRAFORM_DIR: &str = "infra/aws_serverless_sweep/terraform";
+const TERRAFORM_PLAN_FILE: &str = "serverless-deploy.tfplan";
+/// Terraform outputs the sweep launcher needs, printed after apply.
+const DEPLOY_OUTPUTS: [&str; 5] = [
+    "api_url",
+    "runtime_lambda_arn",
+    "shard_queue_url",
+    "shard_dlq_url",
+    "results_bucket",
+];
+
+fn terraform(args: &[&str]) -> ExitStatus {
+    eprintln!("+ terraform -chdir={TERRAFORM_DIR} {}", args.join(" "));
+    Command::new("terraform")
+        .arg(format!("-chdir={TERRAFORM_DIR}"))
+        .args(args)
+        .status()
+        .expect("failed to execute terraform")
+}
+
+fn run_terraform(args: &[&str]) {
+    let status = terraform(args);
+    if !status.success() {
+        exit(status.code().unwrap_or(1));
+    }
+}
+
+/// Run terraform and parse its JSON stdout.
+fn terraform_json(args: &[&str]) -> Value {
+    let output = Command::new("terraform")
+        .arg(format!("-chdir={TERRAFORM_DIR}"))
+        .args(args)
+        .output()
+        .expect("failed to execute terraform");
+    if !output.status.success() {
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        exit(output.status.code().unwrap_or(1));
+    }
+    serde_json::from_slice(&output.stdout).expect("terraform did not return valid JSON")
+}
+
+/// `source_code_hash` the plan will leave on each Lambda function, keyed by address.
+fn planned_lambda_hashes(plan: &Value) -> Vec<(String, String)> {
+    plan["resource_changes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|change| change["type"] == "aws_lambda_function")
+        .filter_map(|change| {
+            let address = change["address"].as_str()?.to_string();
+            let hash = change["change"]["after"]["source_code_hash"].as_str()?;
+            Some((address, hash.to_string()))
+        })
+        .collect()
+}
+
+fn serverless_deploy(vars: &[String], plan_only: bool) {
+    step("Verify packaged Lambda artifacts");
+    let zip_path = Path::new("infra/aws_serverless_sweep/dist/runtime.zip");
+    if !zip_path.exists() {
+        panic!(
+            "missing '{}'; run `cargo run -p xtask -- serverless-package` first",
+            zip_path.display()
+        );
+    }
+    let local_hash = file_base64_sha256(zip_path);
+    match fs::read_to_string(artifact_hash_path(zip_path)) {
+        Ok(recorded) if recorded.trim() == local_hash => {
+            eprintln!("{}: sha256 {local_hash}", zip_path.display());
+        }
+        Ok(recorded) => panic!(
+            "'{}' changed since it was packaged (recorded {}, found {local_hash}); re-run `cargo run -p xtask -- serverless-package`",
+            zip_path.display(),
+            recorded.trim()
+        ),
+        Err(_) => panic!(
+            "no packaging hash for '{}'; re-run `cargo run -p xtask -- serverless-package`",
+            zip_path.display()
+        ),
+    }
+
+    step("Terraform plan");
+    let zip_abs = fs::canonicalize(zip_path).expect("failed to resolve lambda zip path");
+    let zip_var = format!("runtime_lambda_zip={}", zip_abs.display());
+    let mut plan_args = vec![
+        "plan".to_string(),
+        "-input=false".to_string(),
+        format!("-out={TERRAFORM_PLAN_FILE}"),
+        "-var".to_string(),
+        zip_var,
+    ];
+    for var in vars {
+        plan_args.extend(["-var".to_string(), var.clone()]);
+    }
+    run_terraform(&["init", "-input=false"]);
+    run_terraform(&plan_args.iter().map(String::as_str).collect::<Vec<_>>());
+
+    step("Check planned Lambda code hashes");
+    let plan = terraform_json(&["show", "-json", TERRAFORM_PLAN_FILE]);
+    let planned = planned_lambda_hashes(&plan);
+    if planned.is_empty() {
+        panic!("the plan contains no aws_lambda_function resources; check {TERRAFORM_DIR}");
+    }
+    for (address, hash) in &planned {
+        if *hash != local_hash {
+            panic!(
+                "{address} would upload code with sha256 {hash}, but the packaged artifact is {local_hash}"
+            );
+        }
+        eprintln!("{address}: sha256 matches");
+    }
+
+    if plan_only {
+        eprintln!(
+            "\nPlan saved to {TERRAFORM_DIR}/{TERRAFORM_PLAN_FILE}; not applied (--plan-only)."
+        );
+        return;
+    }
+
+    step("Terraform apply");
+    run_terraform(&["apply", "-input=false", TERRAFORM_PLAN_FILE]);
+
+    step("Deployment outputs");
+    let outputs = terraform_json(&["output", "-json"]);
+    for name in DEPLOY_OUTPUTS {
+        match outputs[name]["value"].as_str() {
+            Some(value) => println!("{name} = {value}"),
+            None => eprintln!("warning: terraform output `{name}` is missing"),
+        }
+    }
+}
+
 fn ensure_rust_target_installed(target: &str) {
     let output = Command::new("rustup")
         .args(["target", "list", "--installed"])
@@ -311,6 +577,9 @@ fn ci_examples() {
         "--release",
     ]);
 
+    step("Run scenario smoke matrix");
+    run_smoke_matrix();
+
     step("Run scenario_run_large (10K riders, 7K drivers)");
     run_cargo(&[
         "run",
@@ -322,11 +591,73 @@ fn ci_examples() {
     ]);
 }
 
+fn run_smoke_matrix() {
+    run_cargo(&[
+        "run",
+        "-p",
+        "sim_experiments",
+        "--example",
+        "smoke_matrix",
+        "--release",
+    ]);
+}
+
 fn ci_bench() {
     step("Run benchmarks");
     run_cargo(&["bench", "--package", "sim_core", "--bench", "performance"]);
 }
 
+/// Criterion group whose benchmarks report events/sec and are tracked by `bench-compare`.
+const THROUGHPUT_GROUP: &str = "simulation_throughput";
+
+/// Events/sec of one Criterion run (`main` baseline or `new`), from its mean iteration time.
+fn criterion_events_per_sec(bench_dir: &Path, run: &str) -> Option<f64> {
+    let read_json = |file: &str| -> Option<Value> {
+        let data = fs::read_to_string(bench_dir.join(run).join(file)).ok()?;
+        serde_json::from_str(&data).ok()
+    };
+    let events = read_json("benchmark.json")?["throughput"]["Elements"].as_f64()?;
+    let mean_ns = read_json("estimates.json")?["mean"]["point_estimate"].as_f64()?;
+    (mean_ns > 0.0).then(|| events / (mean_ns / 1e9))
+}
+
+/// Print baseline vs current events/sec per throughput benchmark; false if any regressed by
+/// more than `max_regression_pct`.
+fn throughput_within_budget(criterion_dir: &Path, max_regression_pct: f64) -> bool {
+    let Ok(entries) = fs::read_dir(criterion_dir.join(THROUGHPUT_GROUP)) else {
+        eprintln!("No {THROUGHPUT_GROUP} results found");
+        return true;
+    };
+    let mut bench_dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join("new").is_dir())
+        .collect();
+    bench_dirs.sort();
+
+    let mut within_budget = true;
+    for bench_dir in bench_dirs {
+        let name = bench_dir.file_name().unwrap_or_default().to_string_lossy();
+        let (Some(baseline), Some(current)) = (
+            criterion_events_per_sec(&bench_dir, "main"),
+            criterion_events_per_sec(&bench_dir, "new"),
+        ) else {
+            eprintln!("{THROUGHPUT_GROUP}/{name}: no baseline to compare");
+            continue;
+        };
+        let change_pct = (current / baseline - 1.0) * 100.0;
+        let regressed = change_pct < -max_regression_pct;
+        eprintln!(
+            "{THROUGHPUT_GROUP}/{name}: {baseline:.0} -> {current:.0} events/sec ({change_pct:+.1}%){}",
+            if regressed { "  REGRESSION" } else { "" }
+        );
+        within_budget &= !regressed;
+    }
+    if !within_budget {
+        eprintln!("Throughput dropped by more than {max_regression_pct}% against the baseline");
+    }
+    within_budget
+}
+
 // ── main ───────────────────────────────────────────────────────────
 
 fn main() {
@@ -356,37 +687,92 @@ fn main() {
                 "--release",
             ]);
         }
-        Commands::Sweep => {
-            run_cargo(&[
+        Commands::Sweep {
+            no_progress,
+            memory_budget_mb,
+        } => {
+            let mut args = vec![
                 "run",
                 "-p",
                 "sim_experiments",
                 "--example",
                 "parameter_sweep",
-            ]);
+            ];
+            let budget = memory_budget_mb.map(|megabytes| megabytes.to_string());
+            let mut example_args = Vec::new();
+            if no_progress {
+                example_args.push("--no-progress");
+            }
+            if let Some(budget) = &budget {
+                example_args.extend(["--memory-budget-mb", budget.as_str()]);
+            }
+            if !example_args.is_empty() {
+                args.push("--");
+                args.extend(example_args);
+            }
+            run_cargo(&args);
         }
-        Commands::RouteExport {
-            sample_count,
+        Commands::Report {
+            input,
             output,
+            rank_by,
+            metrics,
+            filter,
+            group_by,
         } => {
-            let sc = sample_count.to_string();
-            run_cargo(&[
+            let mut args = vec![
                 "run",
                 "-p",
                 "sim_experiments",
                 "--example",
-                "route_export",
+                "results_report",
+                "--release",
                 "--",
-                "--sample-count",
-                &sc,
+                "--input",
+                &input,
                 "--output",
                 &output,
-            ]);
+            ];
+            if let Some(metric) = &rank_by {
+                args.extend(["--rank-by", metric.as_str()]);
+            }
+            if let Some(metrics) = &metrics {
+                args.extend(["--metrics", metrics.as_str()]);
+            }
+            if let Some(filter) = &filter {
+                args.extend(["--filter", filter.as_str()]);
+            }
+            if let Some(key) = &group_by {
+                args.extend(["--group-by", key.as_str()]);
+            }
+            run_cargo(&args);
+        }
+        Commands::RouteExport {
+            sample_count,
+            output,
+            format,
+        } => {
+            let sc = sample_count.to_string();
+            let binary = match &format {
+                Some(format) => format == "bin",
+                None => output.ends_with(".bin"),
+            };
+            let mut args = vec!["run", "-p", "sim_experiments", "--example", "route_export"];
+            if binary {
+                args.extend(["--features", "precomputed"]);
+            }
+            args.extend(["--", "--sample-count", &sc, "--output", &output]);
+            if let Some(format) = &format {
+                args.extend(["--format", format.as_str()]);
+            }
+            run_cargo(&args);
         }
         Commands::Bench => {
             run_cargo(&["bench", "--package", "sim_core", "--bench", "performance"]);
         }
-        Commands::BenchCompare => {
+        Commands::BenchCompare {
+            max_throughput_regression,
+        } => {
             let baseline_dir = Path::new("target/criterion");
             if baseline_dir.exists() {
                 step("Removing existing benchmark data");
@@ -428,6 +814,11 @@ fn main() {
                 "main",
             ]);
 
+            step("Comparing simulation throughput against baseline");
+            if !throughput_within_budget(baseline_dir, max_throughput_regression) {
+                exit(1);
+            }
+
             eprintln!("\nDone! Check the output above to see performance comparison.");
         }
         Commands::Ci { job } => {
@@ -454,8 +845,126 @@ fn main() {
                 "--ignored",
             ]);
         }
+        Commands::Smoke => {
+            run_smoke_matrix();
+        }
+        Commands::Profile {
+            scenario,
+            output,
+            frequency,
+        } => {
+            if cfg!(windows) {
+                panic!("`xtask profile` needs a Unix sampling profiler; run it on Linux or macOS");
+            }
+            let frequency = frequency.to_string();
+            // Release speed with symbols, so frames are named
+            run_cargo_with_env(
+                &[("CARGO_PROFILE_RELEASE_DEBUG", "true")],
+                &[
+                    "run",
+                    "-p",
+                    "xtask",
+                    "--features",
+                    "profiling",
+                    "--bin",
+                    "profile_scenario",
+                    "--release",
+                    "--",
+                    "--scenario",
+                    &scenario,
+                    "--output",
+                    &output,
+                    "--frequency",
+                    &frequency,
+                ],
+            );
+        }
+        Commands::DeterminismCheck {
+            scenario,
+            runs,
+            threads,
+        } => {
+            let runs = runs.to_string();
+            let mut args = vec![
+                "run",
+                "-p",
+                "sim_serverless_sweep_lambda",
+                "--bin",
+                "determinism_check",
+                "--release",
+                "--",
+                "--scenario",
+                &scenario,
+                "--runs",
+                &runs,
+            ];
+            if let Some(threads) = &threads {
+                args.extend(["--threads", threads.as_str()]);
+            }
+            run_cargo(&args);
+        }
         Commands::ServerlessPackage { target, profile } => {
             package_serverless_lambdas(&target, profile);
         }
+        Commands::ServerlessDeploy { vars, plan_only } => {
+            serverless_deploy(&vars, plan_only);
+        }
+        Commands::ReproduceShard {
+            run_id,
+            shard_id,
+            request,
+            outcome,
+        } => {
+            let shard_id = shard_id.to_string();
+            let mut args = vec![
+                "run",
+                "-p",
+                "sim_serverless_sweep_lambda",
+                "--bin",
+                "reproduce_shard",
+                "--release",
+                "--",
+                "--request",
+                &request,
+                "--run-id",
+                &run_id,
+                "--shard-id",
+                &shard_id,
+            ];
+            if let Some(outcome) = &outcome {
+                args.extend(["--outcome", outcome.as_str()]);
+            }
+            run_cargo(&args);
+        }
+        Commands::EstimateSweep {
+            request,
+            seconds_per_run,
+            bytes_per_run,
+            benchmark_points,
+        } => {
+            let seconds_per_run = seconds_per_run.map(|value| value.to_string());
+            let bytes_per_run = bytes_per_run.map(|value| value.to_string());
+            let benchmark_points = benchmark_points.to_string();
+            let mut args = vec![
+                "run",
+                "-p",
+                "sim_serverless_sweep_lambda",
+                "--bin",
+                "estimate_sweep",
+                "--release",
+                "--",
+                "--request",
+                &request,
+                "--benchmark-points",
+                &benchmark_points,
+            ];
+            if let Some(seconds) = &seconds_per_run {
+                args.extend(["--seconds-per-run", seconds.as_str()]);
+            }
+            if let Some(bytes) = &bytes_per_run {
+                args.extend(["--bytes-per-run", bytes.as_str()]);
+            }
+            run_cargo(&args);
+        }
     }
 }